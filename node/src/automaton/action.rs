@@ -1,23 +1,41 @@
+use super::{model::effect_name, offload::EffectPool};
 use linkme::distributed_slice;
 use serde::{Deserialize, Serialize};
 use serde_derive::{Deserialize, Serialize};
 use std::{
     any::{Any, TypeId},
     borrow::Cow,
-    collections::VecDeque,
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, VecDeque},
     fmt,
     fs::{File, OpenOptions},
     io::{BufReader, BufWriter},
-    ops::Deref,
+    ops::{Deref, Range},
     panic::Location,
     rc::Rc,
 };
 use type_uuid::TypeUuidDynamic;
 
+// Relative timeouts, converted to `TimeoutAbsolute` when a request is made
+// (see `time::model::get_timeout_absolute`). `Millis(0)` is not a
+// non-blocking request: it times out at the first deadline check.
 #[derive(PartialEq, Eq, Serialize, Deserialize, Clone, Debug)]
 pub enum Timeout {
     Millis(u64),
     Never,
+    // Like `Millis`, but send and recv requests push their deadline back by
+    // this much whenever they transfer data, so they only time out after a
+    // gap with no progress. Other requests treat it as `Millis`.
+    Inactivity(u64),
+}
+
+impl Timeout {
+    pub fn inactivity(&self) -> Option<u64> {
+        match self {
+            Timeout::Inactivity(ms) => Some(*ms),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
@@ -26,6 +44,19 @@ pub enum TimeoutAbsolute {
     Never,
 }
 
+impl TimeoutAbsolute {
+    // The earlier of the two deadlines.
+    pub fn earliest(self, other: TimeoutAbsolute) -> TimeoutAbsolute {
+        match (self, other) {
+            (TimeoutAbsolute::Millis(a), TimeoutAbsolute::Millis(b)) => {
+                TimeoutAbsolute::Millis(a.min(b))
+            }
+            (TimeoutAbsolute::Never, other) => other,
+            (this, TimeoutAbsolute::Never) => this,
+        }
+    }
+}
+
 pub fn serialize_rc_bytes<S>(data: &Rc<[u8]>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -42,6 +73,70 @@ where
     Ok(Rc::from(vec.into_boxed_slice()))
 }
 
+// A range of a shared buffer, passed in actions without copying it (e.g. the
+// part of a send request's data written by `MioEffectfulAction::TcpWrite`).
+// Only the bytes in the range are serialized.
+#[derive(Clone)]
+pub struct RcSlice {
+    data: Rc<[u8]>,
+    range: Range<usize>,
+}
+
+impl RcSlice {
+    pub fn new(data: Rc<[u8]>, range: Range<usize>) -> Self {
+        assert!(
+            range.start <= range.end && range.end <= data.len(),
+            "RcSlice range {:?} out of bounds (len {})",
+            range,
+            data.len()
+        );
+        Self { data, range }
+    }
+}
+
+impl Deref for RcSlice {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data[self.range.clone()]
+    }
+}
+
+impl PartialEq for RcSlice {
+    fn eq(&self, other: &Self) -> bool {
+        self.deref() == other.deref()
+    }
+}
+
+impl Eq for RcSlice {}
+
+impl fmt::Debug for RcSlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl Serialize for RcSlice {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.deref().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RcSlice {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let data = deserialize_rc_bytes(deserializer)?;
+        let len = data.len();
+
+        Ok(Self::new(data, 0..len))
+    }
+}
+
 // Actions fall into 2 categories:
 //
 // 1. `Pure`: these are both dispatched and processed by `PureModel`s.
@@ -59,6 +154,15 @@ pub enum ActionKind {
     Effectful = 1,
 }
 
+impl ActionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionKind::Pure => "pure",
+            ActionKind::Effectful => "effectful",
+        }
+    }
+}
+
 pub trait Action
 where
     Self: TypeUuidDynamic + fmt::Debug + 'static,
@@ -76,6 +180,15 @@ pub struct ActionDebugInfo {
     pub caller: u64,
     // Was the action dispatched with dispatch_back.
     pub callback: bool,
+    // Caller-defined context shared by the whole causal tree of actions, see
+    // `Dispatcher::set_trace_id`.
+    pub trace_id: Option<u64>,
+    // For effectful actions, their name (e.g. `MioEffectfulAction::TcpWrite`).
+    // For results dispatched back by an `EffectfulModel`: the effectful action
+    // they are the result of. Lets the replayer check that a recorded result
+    // is fed to the same kind of effect, and detect effects added since the
+    // recording was made.
+    pub effect: Option<String>,
 }
 
 pub struct AnyAction {
@@ -101,6 +214,8 @@ impl<T: Action> From<T> for AnyAction {
                 action_id: 0,
                 caller: 0,
                 callback: false,
+                trace_id: None,
+                effect: None,
             },
         }
     }
@@ -113,6 +228,9 @@ pub struct SerializableAction<T: Clone + type_uuid::TypeUuid + std::fmt::Debug +
 }
 
 #[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
+// `fun_ptr` is skipped, so `R` itself doesn't need to be (de)serializable or
+// `Default`. This allows results such as `Result<T, E>` in callbacks.
+#[serde(bound = "")]
 pub struct Redispatch<R> {
     #[serde(skip)]
     fun_ptr: Option<fn(R) -> AnyAction>,
@@ -149,6 +267,24 @@ impl<R> fmt::Debug for Redispatch<R> {
     }
 }
 
+// How the replayer handles effects that have no recorded counterpart, because
+// they were added to the models after the recording was made (see
+// `RunnerBuilder::replay_new_effects()`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum NewEffects {
+    // The replay diverged: panic.
+    #[default]
+    Reject,
+    // Run the effect (and handle its result) as if not replaying.
+    Live,
+    // Let the `EffectfulModel` dispatch back its placeholder result, as for
+    // the other effects while replaying, and handle it as is.
+    Placeholder,
+}
+
+// Replaces a recorded effect result while replaying, see `Replayer::patch`.
+pub type ReplayPatch = Box<dyn FnOnce(AnyAction) -> AnyAction>;
+
 pub struct Dispatcher {
     queue: VecDeque<AnyAction>,
     halt: bool,
@@ -170,10 +306,44 @@ pub struct Dispatcher {
     // The action's `caller` is the `action_id` of the action that was being
     // handled at the moment the current action was dispatched.
     pub caller: u64,
+    // The `trace_id` of the action being handled. Actions dispatched (or
+    // dispatched back) inherit it, so it propagates to all actions stemming
+    // from the one where it was set.
+    pub trace_id: Option<u64>,
+    // The effectful action being handled, if any (see `ActionDebugInfo::effect`).
+    pub effect: Option<String>,
 
     // Record/Replay
     pub record_file: Option<BufWriter<File>>,
     pub replay_file: Option<BufReader<File>>,
+    pub new_effects: NewEffects,
+    // Recorded action read ahead while handling a new effect.
+    pub replay_pending: Option<AnyAction>,
+    // Ids of new effects and of the actions stemming from them: they have no
+    // recorded counterpart.
+    pub replay_unrecorded: BTreeSet<u64>,
+    // Set while a new effect runs live (`NewEffects::Live`).
+    pub replay_live: bool,
+    // Set by the `Replayer` for the next processed action only.
+    pub replay_patch: Option<ReplayPatch>,
+
+    // Set by `RunnerBuilder::forbid_effects()`: any `dispatch_effect` panics.
+    // Used to prove that a sequence of actions is purely deterministic.
+    pub effects_forbidden: bool,
+
+    // Set by `RunnerBuilder::max_dispatch_depth()`: any dispatch deeper than
+    // this panics. `chain` describes the actions being handled at each depth
+    // down to the current one, it's only tracked with a limit.
+    pub max_depth: Option<usize>,
+    pub chain: Vec<String>,
+
+    // Set by `RunnerBuilder::priority()`: queued actions of models with a
+    // higher priority are processed first. Actions of models with the same
+    // priority (0 unless set) are processed in dispatch order.
+    pub priorities: BTreeMap<type_uuid::Bytes, i32>,
+
+    // Set by `RunnerBuilder::offload_effects()`, see `effect_pool()`.
+    pub effect_pool: Option<EffectPool>,
 }
 
 pub struct IfPure<const K: u8>;
@@ -192,8 +362,20 @@ impl Dispatcher {
             depth: 0,
             action_id: 0,
             caller: 0,
+            trace_id: None,
+            effect: None,
             record_file: None,
             replay_file: None,
+            new_effects: NewEffects::default(),
+            replay_pending: None,
+            replay_unrecorded: BTreeSet::new(),
+            replay_live: false,
+            replay_patch: None,
+            effects_forbidden: false,
+            max_depth: None,
+            chain: Vec::new(),
+            priorities: BTreeMap::new(),
+            effect_pool: None,
         }
     }
 
@@ -205,8 +387,14 @@ impl Dispatcher {
         self.halt
     }
 
+    // Drops the queued actions and a pending halt, see `Runner::restore`.
+    pub fn reset_queue(&mut self) {
+        self.queue.clear();
+        self.halt = false;
+    }
+
     pub fn next_action(&mut self) -> AnyAction {
-        self.queue.pop_front().unwrap_or_else(|| {
+        self.next_queued_action().unwrap_or_else(|| {
             let mut any_action = (self.tick)();
 
             any_action.dbginfo.action_id = self.action_id;
@@ -214,10 +402,29 @@ impl Dispatcher {
             self.depth = 0;
             self.action_id += 1;
             self.caller = 0;
+            self.trace_id = None;
             any_action
         })
     }
 
+    // Like `next_action()`, but doesn't produce a "tick" action when the queue
+    // is empty.
+    pub fn next_queued_action(&mut self) -> Option<AnyAction> {
+        if self.priorities.is_empty() {
+            return self.queue.pop_front();
+        }
+
+        let priority = |action: &AnyAction| self.priorities.get(&action.uuid).copied().unwrap_or(0);
+        // First of the highest priority actions.
+        let (index, _) = self
+            .queue
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, action)| (priority(action), Reverse(*index)))?;
+
+        self.queue.remove(index)
+    }
+
     pub fn record(&mut self, filename: &str) {
         assert!(self.record_file.is_none());
         self.record_file = Some(BufWriter::new(
@@ -237,8 +444,59 @@ impl Dispatcher {
         ));
     }
 
+    // Tags the actions dispatched from now on (while handling the current
+    // action) and all their descendants with `trace_id`.
+    pub fn set_trace_id(&mut self, trace_id: u64) {
+        self.trace_id = Some(trace_id);
+    }
+
+    // Dispatches from `f` with `trace_id` instead of the current one. For
+    // work deferred to another action (e.g. I/O retried at the next poll),
+    // so it keeps the trace of the action that requested it.
+    pub fn with_trace_id<T>(&mut self, trace_id: Option<u64>, f: impl FnOnce(&mut Self) -> T) -> T {
+        let current = std::mem::replace(&mut self.trace_id, trace_id);
+        let result = f(self);
+
+        self.trace_id = current;
+        result
+    }
+
+    // Records `action`, about to be handled, in the dispatch chain (see
+    // `max_depth`).
+    pub fn enter_chain(&mut self, action: &AnyAction) {
+        if self.max_depth.is_none() {
+            return;
+        }
+
+        let dbginfo = &action.dbginfo;
+        let name = action.type_name.rsplit("::").next().unwrap();
+
+        self.chain.truncate(dbginfo.depth);
+        self.chain.push(if dbginfo.location_file.is_empty() {
+            name.to_string()
+        } else {
+            format!(
+                "{} at {}:{}",
+                name, dbginfo.location_file, dbginfo.location_line
+            )
+        });
+    }
+
     pub fn is_replayer(&self) -> bool {
-        self.replay_file.is_some()
+        self.replay_file.is_some() && !self.replay_live
+    }
+
+    // Worker threads that `EffectfulModel`s can offload their thread-safe
+    // operations to. `None` unless enabled with `RunnerBuilder::offload_effects()`,
+    // and while replaying (effects are inhibited then) or recording: offloaded
+    // results are fed back later than inline ones, so a recording made with
+    // offloading wouldn't match the order of the (inline) replay.
+    pub fn effect_pool(&mut self) -> Option<&mut EffectPool> {
+        if self.is_replayer() || self.record_file.is_some() {
+            return None;
+        }
+
+        self.effect_pool.as_mut()
     }
 
     #[track_caller]
@@ -248,7 +506,7 @@ impl Dispatcher {
         IfPure<{ A::KIND as u8 }>: True,
     {
         let location = Location::caller();
-        self.dispatch_common(action, *location)
+        self.dispatch_common(action, *location, None)
     }
 
     #[track_caller]
@@ -258,14 +516,40 @@ impl Dispatcher {
         IfPure<{ A::KIND as u8 }>: False,
     {
         let location = Location::caller();
-        self.dispatch_common(action, *location)
+
+        if self.effects_forbidden {
+            panic!(
+                "Effectful action {} dispatched at {} while effects are forbidden: {:?}",
+                std::any::type_name::<A>(),
+                location,
+                action
+            )
+        }
+
+        let effect = effect_name(&action);
+
+        self.dispatch_common(action, *location, Some(effect))
+    }
+
+    fn check_depth<A: Action>(&self, action: &A, location: &Location) {
+        match self.max_depth {
+            Some(max_depth) if self.depth >= max_depth => panic!(
+                "Dispatch depth limit ({}) exceeded dispatching {} at {}: {}",
+                max_depth,
+                effect_name(action),
+                location,
+                self.chain.join(" -> ")
+            ),
+            _ => (),
+        }
     }
 
-    fn dispatch_common<A: Action>(&mut self, action: A, location: Location)
+    fn dispatch_common<A: Action>(&mut self, action: A, location: Location, effect: Option<String>)
     where
         A: Sized + 'static,
     {
         assert_ne!(TypeId::of::<A>(), TypeId::of::<AnyAction>());
+        self.check_depth(&action, &location);
         let mut any_action: AnyAction = action.into();
 
         any_action.dbginfo = ActionDebugInfo {
@@ -275,6 +559,8 @@ impl Dispatcher {
             action_id: self.action_id,
             caller: self.caller,
             callback: false,
+            trace_id: self.trace_id,
+            effect,
         };
         self.action_id += 1;
         self.queue.push_back(any_action);
@@ -295,6 +581,8 @@ impl Dispatcher {
             action_id: self.action_id,
             caller: self.caller,
             callback: true,
+            trace_id: self.trace_id,
+            effect: self.effect.clone(),
         };
         self.action_id += 1;
         self.queue.push_back(any_action);
@@ -0,0 +1,34 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::BTreeMap,
+};
+
+// `ConfigState` holds settings shared by all models (e.g. a global "chaos
+// enabled" flag), instead of duplicating them in every model's own config.
+// Settings are keyed by their type, there is at most one value per type.
+//
+// Values are set while registering the models (`RunnerBuilder::config`) and
+// are read-only afterwards, with `State::config`. Like metrics, they are
+// shared by all instances and are not part of snapshots.
+#[derive(Default, Debug)]
+pub struct ConfigState {
+    values: BTreeMap<TypeId, Box<dyn Any>>,
+}
+
+impl ConfigState {
+    pub fn set<T: 'static>(&mut self, value: T) {
+        if self
+            .values
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .is_some()
+        {
+            panic!("Attempt to re-set config {}", type_name::<T>())
+        }
+    }
+
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .map(|value| value.downcast_ref::<T>().unwrap())
+    }
+}
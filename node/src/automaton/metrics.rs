@@ -0,0 +1,136 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+// `Metrics` is a registry of the counters and gauges collected while the
+// state-machine runs. Metrics are grouped in families (one per metric name),
+// and each family holds one sample per distinct set of labels.
+//
+// Metrics are observability data only: they are not part of the recorded
+// state and they don't affect the processing of actions.
+//
+// The registry can be rendered in the Prometheus text exposition format, so
+// it can be served as-is from a `/metrics` endpoint.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+}
+
+impl MetricKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+        }
+    }
+}
+
+// (label name, label value) pairs, in rendering order.
+pub type Labels = Vec<(&'static str, String)>;
+
+#[derive(Debug)]
+struct MetricFamily {
+    kind: MetricKind,
+    help: &'static str,
+    samples: BTreeMap<Labels, f64>,
+}
+
+#[derive(Default, Debug)]
+pub struct Metrics {
+    families: BTreeMap<&'static str, MetricFamily>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn sample_mut(
+        &mut self,
+        name: &'static str,
+        kind: MetricKind,
+        help: &'static str,
+        labels: &Labels,
+    ) -> &mut f64 {
+        let family = self.families.entry(name).or_insert_with(|| MetricFamily {
+            kind,
+            help,
+            samples: BTreeMap::new(),
+        });
+
+        assert_eq!(
+            family.kind,
+            kind,
+            "Metric {} already registered as a {}",
+            name,
+            family.kind.as_str()
+        );
+        // `labels` are only cloned when the sample is created, see
+        // `State::counter_add_labels`.
+        if !family.samples.contains_key(labels) {
+            family.samples.insert(labels.clone(), 0.0);
+        }
+
+        family.samples.get_mut(labels).unwrap()
+    }
+
+    pub fn counter_add(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        labels: &Labels,
+        value: f64,
+    ) {
+        *self.sample_mut(name, MetricKind::Counter, help, labels) += value
+    }
+
+    pub fn gauge_set(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        labels: &Labels,
+        value: f64,
+    ) {
+        *self.sample_mut(name, MetricKind::Gauge, help, labels) = value
+    }
+
+    pub fn get(&self, name: &str, labels: &Labels) -> Option<f64> {
+        self.families
+            .get(name)
+            .and_then(|family| family.samples.get(labels).copied())
+    }
+
+    // Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+
+        for (name, family) in self.families.iter() {
+            writeln!(output, "# HELP {} {}", name, family.help).unwrap();
+            writeln!(output, "# TYPE {} {}", name, family.kind.as_str()).unwrap();
+
+            for (labels, value) in family.samples.iter() {
+                if labels.is_empty() {
+                    writeln!(output, "{} {}", name, value).unwrap();
+                } else {
+                    let labels: Vec<String> = labels
+                        .iter()
+                        .map(|(label, value)| {
+                            format!("{}=\"{}\"", label, escape_label_value(value))
+                        })
+                        .collect();
+
+                    writeln!(output, "{}{{{}}} {}", name, labels.join(","), value).unwrap();
+                }
+            }
+        }
+
+        output
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -1,4 +1,10 @@
 pub mod action;
+pub mod config;
+pub mod metrics;
 pub mod model;
+pub mod offload;
+pub mod record_filter;
+pub mod replayer;
 pub mod runner;
 pub mod state;
+pub mod stepper;
@@ -9,6 +9,7 @@ use log::debug;
 use serde::{Deserialize, Serialize};
 use std::{
     any::Any,
+    fmt::{self, Write},
     fs::File,
     io::{BufReader, BufWriter},
 };
@@ -30,6 +31,10 @@ use std::{
 // Finally, the `AnyModel` type is a central struct that can hold any model and
 // handles actions via its virtual method table. It provides the methods
 // (`process_pure`, `process_effectful`) to process different kinds of actions.
+//
+// Both traits also provide an optional `on_shutdown` hook, invoked by the
+// `Runner` when it stops, in reverse order of model registration (dependents
+// before their dependencies).
 
 pub struct AnyModel<Substates: ModelState> {
     model: Box<dyn Any>,
@@ -57,6 +62,14 @@ impl<Substates: ModelState> AnyModel<Substates> {
     pub fn deserialize_from(&mut self, reader: &mut BufReader<File>) -> AnyAction {
         (self.vtable.deserialize_from)(reader)
     }
+
+    pub fn to_json(&self, action: &AnyAction) -> serde_json::Value {
+        (self.vtable.to_json)(action)
+    }
+
+    pub fn on_shutdown(&mut self, state: &mut State<Substates>, dispatcher: &mut Dispatcher) {
+        (self.vtable.on_shutdown)(&mut self.model, state, dispatcher)
+    }
 }
 
 struct ModelVTable<Substates: ModelState> {
@@ -67,6 +80,9 @@ struct ModelVTable<Substates: ModelState> {
     process_effectful: fn(state: &mut Box<dyn Any>, action: AnyAction, dispatcher: &mut Dispatcher),
     serialize_into: fn(writer: &mut BufWriter<File>, action: &AnyAction),
     deserialize_from: fn(reader: &mut BufReader<File>) -> AnyAction,
+    to_json: fn(action: &AnyAction) -> serde_json::Value,
+    on_shutdown:
+        fn(model: &mut Box<dyn Any>, state: &mut State<Substates>, dispatcher: &mut Dispatcher),
 }
 
 pub trait PrivateModel
@@ -80,6 +96,8 @@ where
             process_effectful: Self::process_effectful,
             serialize_into: Self::serialize_into,
             deserialize_from: Self::deserialize_from,
+            to_json: Self::to_json,
+            on_shutdown: Self::on_shutdown,
         };
         AnyModel { model, vtable }
     }
@@ -91,6 +109,8 @@ where
             process_effectful: Self::process_effectful,
             serialize_into: Self::serialize_into,
             deserialize_from: Self::deserialize_from,
+            to_json: Self::to_json,
+            on_shutdown: Self::on_shutdown,
         };
         AnyModel { model, vtable }
     }
@@ -118,6 +138,17 @@ where
     fn deserialize_from(_reader: &mut BufReader<File>) -> AnyAction {
         unreachable!()
     }
+
+    fn to_json(_action: &AnyAction) -> serde_json::Value {
+        unreachable!()
+    }
+
+    fn on_shutdown<Substates: ModelState>(
+        _model: &mut Box<dyn Any>,
+        _state: &mut State<Substates>,
+        _dispatcher: &mut Dispatcher,
+    ) {
+    }
 }
 
 pub trait PureModel
@@ -139,6 +170,14 @@ where
         action: Self::Action,
         dispatcher: &mut Dispatcher,
     );
+
+    // Called once when the runner stops. Actions dispatched here are processed
+    // before the dependencies of this model are shut down.
+    fn on_shutdown<Substates: ModelState>(
+        _state: &mut State<Substates>,
+        _dispatcher: &mut Dispatcher,
+    ) {
+    }
 }
 
 pub struct Pure<T: PureModel>(T);
@@ -161,6 +200,8 @@ impl<T: PureModel> PrivateModel for Pure<T> {
             action_id,
             caller,
             callback,
+            trace_id,
+            ..
         } = action.dbginfo;
 
         let pad = "  ".repeat(depth);
@@ -202,16 +243,28 @@ impl<T: PureModel> PrivateModel for Pure<T> {
 
         dispatcher.depth = depth;
         dispatcher.caller = action_id;
+        dispatcher.trace_id = trace_id;
+        dispatcher.effect = None;
         T::process_pure(state, *downcasted_action, dispatcher)
     }
 
+    fn on_shutdown<Substates: ModelState>(
+        _model: &mut Box<dyn Any>,
+        state: &mut State<Substates>,
+        dispatcher: &mut Dispatcher,
+    ) {
+        T::on_shutdown(state, dispatcher)
+    }
+
+    // Writes the action's UUID followed by the action. The UUID tells the
+    // replayer which model deserializes the rest (see `deserialize_from`).
     fn serialize_into(writer: &mut BufWriter<File>, action: &AnyAction) {
         let downcasted_action = action
             .ptr
             .downcast_ref::<T::Action>()
             .expect("action not found");
 
-        serialize_into(writer.get_mut(), &action.uuid).expect("UUID serialization failed");
+        serialize_into(&mut *writer, &action.uuid).expect("UUID serialization failed");
         serialize_into(
             writer,
             &SerializableAction {
@@ -222,12 +275,8 @@ impl<T: PureModel> PrivateModel for Pure<T> {
         .expect("Action serialization failed");
     }
 
+    // Reads an action written by `serialize_into`, once its UUID was read.
     fn deserialize_from(reader: &mut BufReader<File>) -> AnyAction {
-        let uuid: type_uuid::Bytes =
-            deserialize_from(reader.get_mut()).expect("UUID deserialization failed");
-
-        debug!("Deserialized {:?}", uuid);
-
         let deserialized_action: SerializableAction<T::Action> =
             deserialize_from(reader).expect("Action deserialization failed");
 
@@ -237,6 +286,15 @@ impl<T: PureModel> PrivateModel for Pure<T> {
         action.dbginfo = deserialized_action.dbginfo;
         action
     }
+
+    fn to_json(action: &AnyAction) -> serde_json::Value {
+        let downcasted_action = action
+            .ptr
+            .downcast_ref::<T::Action>()
+            .expect("action not found");
+
+        serde_json::to_value(downcasted_action).expect("Action serialization failed")
+    }
 }
 
 pub trait EffectfulModel
@@ -254,6 +312,10 @@ where
         + 'static;
 
     fn process_effectful(&mut self, action: Self::Action, dispatcher: &mut Dispatcher);
+
+    // Called once when the runner stops, after all the models depending on
+    // this one were shut down.
+    fn on_shutdown(&mut self) {}
 }
 
 pub struct Effectful<T: EffectfulModel>(pub T);
@@ -276,6 +338,8 @@ impl<T: EffectfulModel> PrivateModel for Effectful<T> {
             action_id,
             caller,
             callback: false,
+            trace_id,
+            ..
         } = action.dbginfo
         else {
             panic!("Can't dispatch_back() an effectful action")
@@ -296,16 +360,30 @@ impl<T: EffectfulModel> PrivateModel for Effectful<T> {
         );
         dispatcher.depth = depth;
         dispatcher.caller = action_id;
+        dispatcher.trace_id = trace_id;
+        dispatcher.effect = Some(effect_name(&*downcasted_action));
         state.0.process_effectful(*downcasted_action, dispatcher)
     }
 
+    fn on_shutdown<Substates: ModelState>(
+        model: &mut Box<dyn Any>,
+        _state: &mut State<Substates>,
+        _dispatcher: &mut Dispatcher,
+    ) {
+        model
+            .downcast_mut::<Self>()
+            .expect("model's state not found")
+            .0
+            .on_shutdown()
+    }
+
     fn serialize_into(writer: &mut BufWriter<File>, action: &AnyAction) {
         let downcasted_action = action
             .ptr
             .downcast_ref::<T::Action>()
             .expect("action not found");
 
-        serialize_into(writer.get_mut(), &action.uuid).expect("UUID serialization failed");
+        serialize_into(&mut *writer, &action.uuid).expect("UUID serialization failed");
         serialize_into(
             writer,
             &SerializableAction {
@@ -317,11 +395,6 @@ impl<T: EffectfulModel> PrivateModel for Effectful<T> {
     }
 
     fn deserialize_from(reader: &mut BufReader<File>) -> AnyAction {
-        let uuid: type_uuid::Bytes =
-            deserialize_from(reader.get_mut()).expect("UUID deserialization failed");
-
-        debug!("Deserialized {:?}", uuid);
-
         let deserialized_action: SerializableAction<T::Action> =
             deserialize_from(reader).expect("Action deserialization failed");
 
@@ -331,4 +404,45 @@ impl<T: EffectfulModel> PrivateModel for Effectful<T> {
         action.dbginfo = deserialized_action.dbginfo;
         action
     }
+
+    fn to_json(action: &AnyAction) -> serde_json::Value {
+        let downcasted_action = action
+            .ptr
+            .downcast_ref::<T::Action>()
+            .expect("action not found");
+
+        serde_json::to_value(downcasted_action).expect("Action serialization failed")
+    }
+}
+
+// Name of an effectful action's variant, with its type (e.g.
+// `MioEffectfulAction::TcpWrite`), taken from the start of its `Debug`
+// representation: formatting stops at the variant name, so the payload (e.g.
+// a write's data) is never formatted.
+pub fn effect_name<A: Action>(action: &A) -> String {
+    let type_name = std::any::type_name::<A>().rsplit("::").next().unwrap();
+    let mut variant = VariantName(String::new());
+
+    // Fails by design once the name is complete.
+    let _ = write!(variant, "{:?}", action);
+    format!("{}::{}", type_name, variant.0)
+}
+
+// `fmt::Write` sink keeping the leading identifier of what is written to it,
+// then failing so the rest isn't formatted.
+struct VariantName(String);
+
+impl fmt::Write for VariantName {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match s.find(|c: char| !c.is_alphanumeric() && c != '_') {
+            Some(end) => {
+                self.0.push_str(&s[..end]);
+                Err(fmt::Error)
+            }
+            None => {
+                self.0.push_str(s);
+                Ok(())
+            }
+        }
+    }
 }
@@ -0,0 +1,123 @@
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle, ThreadId},
+};
+
+type Job = Box<dyn FnOnce() -> Box<dyn Any + Send> + Send>;
+type JobOutput = (u64, ThreadId, Box<dyn Any + Send>);
+
+// Worker threads running (thread-safe) effects off the state-machine thread,
+// see `RunnerBuilder::offload_effects`.
+//
+// Every submitted job gets a sequence number. Workers may complete jobs in
+// any order, but `EffectfulModel`s wait for their outputs by sequence number,
+// so the results fed back into the action queue keep a deterministic order.
+pub struct EffectPool {
+    jobs: Option<Sender<(u64, Job)>>,
+    outputs: Receiver<JobOutput>,
+    workers: Vec<JoinHandle<()>>,
+    next_seq: u64,
+    // Outputs received while waiting for another job.
+    completed: BTreeMap<u64, (ThreadId, Box<dyn Any + Send>)>,
+    // Number of jobs that ran on a thread other than the one waiting for them.
+    off_thread_jobs: u64,
+}
+
+impl EffectPool {
+    pub fn new(workers: usize) -> Self {
+        assert_ne!(workers, 0);
+
+        let (jobs, job_receiver) = channel::<(u64, Job)>();
+        let (output_sender, outputs) = channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let workers = (0..workers)
+            .map(|_| {
+                let job_receiver = job_receiver.clone();
+                let output_sender = output_sender.clone();
+
+                thread::spawn(move || loop {
+                    // The lock is released before running the job.
+                    let next_job = job_receiver.lock().unwrap().recv();
+                    let Ok((seq, job)) = next_job else {
+                        return;
+                    };
+
+                    if output_sender
+                        .send((seq, thread::current().id(), job()))
+                        .is_err()
+                    {
+                        return;
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            jobs: Some(jobs),
+            outputs,
+            workers,
+            next_seq: 0,
+            completed: BTreeMap::new(),
+            off_thread_jobs: 0,
+        }
+    }
+
+    // Runs `job` on a worker thread. Returns the sequence number to `wait()`
+    // for its output.
+    pub fn submit<T: Send + 'static>(&mut self, job: impl FnOnce() -> T + Send + 'static) -> u64 {
+        let seq = self.next_seq;
+
+        self.next_seq += 1;
+        self.jobs
+            .as_ref()
+            .unwrap()
+            .send((seq, Box::new(move || Box::new(job()) as Box<dyn Any + Send>)))
+            .expect("EffectPool workers are gone");
+        seq
+    }
+
+    // Blocks until the job `seq` is completed and returns its output.
+    pub fn wait<T: 'static>(&mut self, seq: u64) -> T {
+        let (thread_id, output) = loop {
+            if let Some(completed) = self.completed.remove(&seq) {
+                break completed;
+            }
+
+            let (completed_seq, thread_id, output) = self
+                .outputs
+                .recv()
+                .expect("EffectPool workers are gone");
+
+            self.completed.insert(completed_seq, (thread_id, output));
+        };
+
+        if thread_id != thread::current().id() {
+            self.off_thread_jobs += 1;
+        }
+
+        *output
+            .downcast::<T>()
+            .expect(&format!("Invalid output type for job {}", seq))
+    }
+
+    pub fn off_thread_jobs(&self) -> u64 {
+        self.off_thread_jobs
+    }
+}
+
+impl Drop for EffectPool {
+    // Closing the jobs channel stops the workers once they are done with the
+    // jobs already submitted.
+    fn drop(&mut self) {
+        self.jobs = None;
+
+        for worker in self.workers.drain(..) {
+            worker.join().ok();
+        }
+    }
+}
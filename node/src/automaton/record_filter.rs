@@ -0,0 +1,84 @@
+use super::{action::AnyAction, state::Uid};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+// Selects the actions of a recording pertaining to one connection, see
+// `RunnerBuilder::record_connection`. An action pertains to the connection if:
+//
+// - it has a `connection` field set to the connection's `Uid`,
+// - or a `uid` field set to the `Uid` of a request seen in an action
+//   pertaining to the connection (e.g. a `Send` on it),
+// - or it was dispatched while handling an action pertaining to the
+//   connection (e.g. the result of an effect on it), unless it's about
+//   another connection (e.g. a model connecting the next client once one is
+//   accepted).
+pub struct ConnectionFilter {
+    connection: Value,
+    uids: BTreeSet<u64>,
+    // (instance, `action_id`) of the actions that pertain to the connection.
+    action_ids: BTreeSet<(usize, u64)>,
+}
+
+impl ConnectionFilter {
+    pub fn new(connection: Uid) -> Self {
+        Self {
+            connection: Value::from(u64::from(connection)),
+            uids: BTreeSet::new(),
+            action_ids: BTreeSet::new(),
+        }
+    }
+
+    // `json` is `action` as exported by its model (see `AnyModel::to_json`).
+    pub fn matches(&mut self, instance: usize, action: &AnyAction, json: &Value) -> bool {
+        let matches = self.mentions(json)
+            || (self.action_ids.contains(&(instance, action.dbginfo.caller))
+                && !self.mentions_other(json));
+
+        if matches {
+            self.action_ids.insert((instance, action.dbginfo.action_id));
+            collect_uids(json, &mut self.uids);
+        }
+
+        matches
+    }
+
+    fn mentions(&self, json: &Value) -> bool {
+        match json {
+            Value::Object(fields) => fields.iter().any(|(key, value)| match key.as_str() {
+                "connection" if *value == self.connection => true,
+                "uid" if value.as_u64().is_some_and(|uid| self.uids.contains(&uid)) => true,
+                _ => self.mentions(value),
+            }),
+            Value::Array(values) => values.iter().any(|value| self.mentions(value)),
+            _ => false,
+        }
+    }
+
+    fn mentions_other(&self, json: &Value) -> bool {
+        match json {
+            Value::Object(fields) => fields.iter().any(|(key, value)| match key.as_str() {
+                "connection" => value.is_u64() && *value != self.connection,
+                _ => self.mentions_other(value),
+            }),
+            Value::Array(values) => values.iter().any(|value| self.mentions_other(value)),
+            _ => false,
+        }
+    }
+}
+
+fn collect_uids(json: &Value, uids: &mut BTreeSet<u64>) {
+    match json {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                match value.as_u64() {
+                    Some(uid) if key == "uid" => {
+                        uids.insert(uid);
+                    }
+                    _ => collect_uids(value, uids),
+                }
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| collect_uids(value, uids)),
+        _ => (),
+    }
+}
@@ -0,0 +1,90 @@
+use super::{
+    action::{Action, AnyAction, ReplayPatch},
+    state::{ModelState, State},
+    stepper::Stepper,
+};
+use std::collections::BTreeMap;
+
+// Replays a recording one action at a time, like a `Stepper` after
+// `Runner::open_replay`, but feeds patched results to selected effects
+// ("what if this read had failed here?"). The other recorded results are
+// replayed as they are.
+//
+// After a patched result the models may take another path than the recorded
+// one. The replay goes on as long as the recording still matches what they
+// do, and panics like any diverged replay otherwise.
+pub struct Replayer<'a, Substate: ModelState> {
+    stepper: Stepper<'a, Substate>,
+    patches: BTreeMap<u64, ReplayPatch>,
+}
+
+impl<'a, Substate: ModelState> Replayer<'a, Substate> {
+    pub fn new(stepper: Stepper<'a, Substate>) -> Self {
+        Self {
+            stepper,
+            patches: BTreeMap::new(),
+        }
+    }
+
+    // The action number `step` (see `Stepper`) is a recorded result of type
+    // `A`: replace it with `new_result`, which is given the recorded one
+    // (e.g. to keep its request `Uid`).
+    pub fn patch<A: Action + 'static>(
+        &mut self,
+        step: u64,
+        new_result: impl FnOnce(A) -> A + 'static,
+    ) -> &mut Self {
+        assert!(step > self.stepper.seq(), "Step {} already replayed", step);
+
+        let patch: ReplayPatch = Box::new(move |recorded: AnyAction| {
+            let dbginfo = recorded.dbginfo;
+            let result = recorded.ptr.downcast::<A>().expect(&format!(
+                "Replay patch at step {}: recorded {}, expected {}",
+                step,
+                recorded.type_name,
+                std::any::type_name::<A>()
+            ));
+            let mut patched: AnyAction = new_result(*result).into();
+
+            patched.dbginfo = dbginfo;
+            patched
+        });
+
+        if self.patches.insert(step, patch).is_some() {
+            panic!("Step {} already patched", step)
+        }
+
+        self
+    }
+
+    pub fn seq(&self) -> u64 {
+        self.stepper.seq()
+    }
+
+    pub fn state(&self) -> &State<Substate> {
+        self.stepper.state()
+    }
+
+    // Replays the next action, see `Stepper::step`.
+    pub fn step(&mut self) -> bool {
+        if let Some(patch) = self.patches.remove(&(self.stepper.seq() + 1)) {
+            let instance = self.stepper.next_instance();
+
+            self.stepper.runner_mut().patch_replay(instance, patch);
+        }
+
+        self.stepper.step()
+    }
+
+    // Replays until action number `n`. Returns false if the runner was shut
+    // down before.
+    pub fn run_to_seq(&mut self, n: u64) -> bool {
+        while self.stepper.seq() < n {
+            if !self.step() {
+                return false;
+            }
+        }
+
+        true
+    }
+}
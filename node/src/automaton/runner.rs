@@ -1,13 +1,43 @@
 use super::{
-    action::{ ActionKind, AnyAction, Dispatcher},
+    action::{Action, ActionKind, AnyAction, Dispatcher, NewEffects, ReplayPatch},
+    metrics::Labels,
     model::{AnyModel, Effectful, EffectfulModel, PrivateModel, Pure, PureModel},
-    state::{ModelState, State},
+    offload::EffectPool,
+    record_filter::ConnectionFilter,
+    replayer::Replayer,
+    state::{ModelState, State, Uid},
+    stepper::Stepper,
 };
 //use bincode::deserialize_from;
+use log::warn;
+use serde::{de::DeserializeOwned, Serialize};
 use std::collections::BTreeMap;
-use std::{env, io::Write};
+use std::{
+    env,
+    fs::File,
+    io::{BufReader, Write},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 use type_uuid::TypeUuid;
 
+// Header of `Runner::snapshot()`. The version is bumped whenever the snapshot
+// layout (not the model states) changes.
+const SNAPSHOT_MAGIC: [u8; 4] = *b"SMSN";
+const SNAPSHOT_VERSION: u32 = 1;
+
+fn decode_snapshot<Substate: DeserializeOwned>(snapshot: &[u8]) -> (Uid, Vec<Substate>) {
+    let (magic, version): ([u8; 4], u32) =
+        bincode::deserialize(snapshot).expect("Snapshot header deserialization failed");
+
+    assert_eq!(magic, SNAPSHOT_MAGIC, "Not a snapshot");
+    assert_eq!(version, SNAPSHOT_VERSION, "Unsupported snapshot version");
+
+    let (_, _, uid_source, substates): ([u8; 4], u32, Uid, Vec<Substate>) =
+        bincode::deserialize(snapshot).expect("Snapshot deserialization failed");
+
+    (uid_source, substates)
+}
+
 // This struct holds the registered models, the state-machine state, and one
 // or more dispatchers. Usually, we need only one `Dispatcher`, except for
 // testing scenarios where we want to run several "instances". For example,
@@ -16,8 +46,30 @@ use type_uuid::TypeUuid;
 // state-machine.
 pub struct Runner<Substate: ModelState> {
     models: BTreeMap<type_uuid::Bytes, AnyModel<Substate>>,
+    // Models in registration order. Since models register their dependencies
+    // first, shutting down in reverse order stops dependents before their
+    // dependencies.
+    registration_order: Vec<type_uuid::Bytes>,
     state: State<Substate>,
     dispatchers: Vec<Dispatcher>,
+    step_bound: Option<StepBound>,
+    ndjson_export: Option<NdjsonExport>,
+    record_filter: Option<ConnectionFilter>,
+    // See `RunnerBuilder::action_metrics`: labels of the action counters, per
+    // (instance, action type).
+    action_metrics: Option<BTreeMap<(usize, type_uuid::Bytes), Labels>>,
+}
+
+// See `RunnerBuilder::max_step_duration`.
+struct StepBound {
+    max: Duration,
+    on_exceed: Box<dyn FnMut(&'static str, Duration) -> bool>,
+}
+
+// See `RunnerBuilder::export_ndjson`.
+struct NdjsonExport {
+    writer: Box<dyn Write>,
+    seq: u64,
 }
 
 // Models should implement their own `register` function to register themselves
@@ -32,19 +84,125 @@ pub trait RegisterModel {
 // time of creating the Runner instance. Models remain immutable thereafter.
 pub struct RunnerBuilder<Substate: ModelState> {
     models: BTreeMap<type_uuid::Bytes, AnyModel<Substate>>,
+    registration_order: Vec<type_uuid::Bytes>,
     state: State<Substate>,
     dispatchers: Vec<Dispatcher>,
+    forbid_effects: bool,
+    max_dispatch_depth: Option<usize>,
+    priorities: BTreeMap<type_uuid::Bytes, i32>,
+    effect_workers: Option<usize>,
+    new_effects: NewEffects,
+    step_bound: Option<StepBound>,
+    ndjson_export: Option<NdjsonExport>,
+    record_filter: Option<ConnectionFilter>,
+    action_metrics: bool,
 }
 
 impl<Substate: ModelState> RunnerBuilder<Substate> {
     pub fn new() -> Self {
         Self {
             models: BTreeMap::default(),
+            registration_order: Vec::new(),
             state: State::<Substate>::new(),
             dispatchers: Vec::new(),
+            forbid_effects: false,
+            max_dispatch_depth: None,
+            priorities: BTreeMap::new(),
+            effect_workers: None,
+            new_effects: NewEffects::default(),
+            step_bound: None,
+            ndjson_export: None,
+            record_filter: None,
+            action_metrics: false,
         }
     }
 
+    // Testing mode: makes every instance's `Dispatcher::dispatch_effect` panic
+    // with the offending action name. Useful to verify that an interaction
+    // between (pure) models is effect-free.
+    pub fn forbid_effects(mut self) -> Self {
+        self.forbid_effects = true;
+        self
+    }
+
+    // Debugging aid: makes dispatching an action nested more than `depth`
+    // levels deep (see `Dispatcher::depth`) panic, with the chain of actions
+    // that led there. Catches accidental infinite recursion in model wiring.
+    pub fn max_dispatch_depth(mut self, depth: usize) -> Self {
+        self.max_dispatch_depth = Some(depth);
+        self
+    }
+
+    // Lets `EffectfulModel`s run their thread-safe operations (e.g. TCP reads
+    // and writes in `MioState`) on a pool of `workers` threads per instance.
+    // Pure models still run on the runner's thread, and the results of
+    // offloaded operations are fed back in the order they were dispatched.
+    // Recorded and replayed sessions run their effects inline, see
+    // `Dispatcher::effect_pool()`.
+    pub fn offload_effects(mut self, workers: usize) -> Self {
+        self.effect_workers = Some(workers);
+        self
+    }
+
+    // Replay mode: how to handle effects with no recorded counterpart, like
+    // the ones added to a model after the recording was made. By default
+    // (`NewEffects::Reject`) the replay panics. Otherwise a warning is logged,
+    // and the rest of the recording is replayed as usual.
+    pub fn replay_new_effects(mut self, new_effects: NewEffects) -> Self {
+        self.new_effects = new_effects;
+        self
+    }
+
+    // Times the processing of every action. When it takes longer than `ms`,
+    // a warning is logged and `on_exceed` is called with the action's type
+    // name and the time it took. If `on_exceed` returns true, the instance
+    // that processed the action is halted.
+    pub fn max_step_duration(
+        mut self,
+        ms: u64,
+        on_exceed: impl FnMut(&'static str, Duration) -> bool + 'static,
+    ) -> Self {
+        self.step_bound = Some(StepBound {
+            max: Duration::from_millis(ms),
+            on_exceed: Box::new(on_exceed),
+        });
+        self
+    }
+
+    // Writes every processed action to `writer` as a line of JSON
+    // (`{"seq":..,"time":..,"instance":..,"model":..,"action":..}`), for
+    // analysis by external tools. `time` is in nanoseconds since the UNIX
+    // epoch. Unlike a recording, the output can't be replayed.
+    pub fn export_ndjson(mut self, writer: impl Write + 'static) -> Self {
+        self.ndjson_export = Some(NdjsonExport {
+            writer: Box::new(writer),
+            seq: 0,
+        });
+        self
+    }
+
+    // Counts the processed actions (`state_machine_actions_total`) and the
+    // time spent processing them (`state_machine_action_duration_seconds_total`)
+    // per model and action kind. Off by default: it costs two metric updates
+    // per action. Labels are built once per instance and action type.
+    pub fn action_metrics(mut self) -> Self {
+        self.action_metrics = true;
+        self
+    }
+
+    // Records only the actions pertaining to `connection` (see
+    // `ConnectionFilter`), e.g. to share a small reproduction of a
+    // connection-specific bug. Such a recording isn't a complete session: it
+    // can be inspected with `Runner::recording_to_json`, but not replayed.
+    // Replaying needs every action of the session, in order: the polls
+    // reporting the connection's events are shared with the other
+    // connections, and the `Uid`s of the connection's requests depend on
+    // everything allocated before them.
+    pub fn record_connection(mut self, connection: Uid) -> Self {
+        self.record_filter = Some(ConnectionFilter::new(connection));
+        self
+    }
+
     // Usually called once, except for testing scenarios describied earlier.
     pub fn instance(mut self, substate: Substate, tick: fn() -> AnyAction) -> Self {
         self.state.substates.push(substate);
@@ -52,6 +210,28 @@ impl<Substate: ModelState> RunnerBuilder<Substate> {
         self
     }
 
+    // Instead of `instance()`: establishes one instance per substate of a
+    // `Runner::snapshot()`, and restores the `Uid` counter, so the session
+    // resumes from the snapshotted state. Each instance is driven by `tick`.
+    //
+    // Effectful models are not part of the snapshot and start fresh: the
+    // OS objects (polls, sockets, ...) referenced by the restored state don't
+    // exist in the new runner.
+    pub fn from_snapshot(mut self, snapshot: &[u8], tick: fn() -> AnyAction) -> Self
+    where
+        Substate: DeserializeOwned,
+    {
+        let (uid_source, substates) = decode_snapshot::<Substate>(snapshot);
+
+        self.state.uid_source = uid_source;
+
+        for substate in substates {
+            self = self.instance(substate, tick);
+        }
+
+        self
+    }
+
     // Should be called once with the top-most model. The top-most model's
     // `RegisterModel` trait should handle dependencies.
     pub fn register<T: RegisterModel>(self) -> Self {
@@ -62,20 +242,65 @@ impl<Substate: ModelState> RunnerBuilder<Substate> {
     // implementations only.
 
     pub fn model_pure<M: PureModel>(mut self) -> Self {
-        self.models
-            .insert(M::Action::UUID, Pure::<M>::into_vtable2());
+        if self
+            .models
+            .insert(M::Action::UUID, Pure::<M>::into_vtable2())
+            .is_none()
+        {
+            self.registration_order.push(M::Action::UUID);
+        }
         self
     }
 
     pub fn model_effectful<M: EffectfulModel>(mut self, model: Effectful<M>) -> Self {
-        self.models
-            .insert(M::Action::UUID, Box::new(model).into_vtable());
+        if self
+            .models
+            .insert(M::Action::UUID, Box::new(model).into_vtable())
+            .is_none()
+        {
+            self.registration_order.push(M::Action::UUID);
+        }
+        self
+    }
+
+    // Sets a setting shared by all models, read with `State::config::<T>()`.
+    // Can be called by `RegisterModel` implementations, or by the user of the
+    // top-most model.
+    pub fn config<T: 'static>(mut self, value: T) -> Self {
+        self.state.config.set(value);
+        self
+    }
+
+    // Sets the priority of the model handling actions of type `A` (0 by
+    // default). When actions of several models are queued, those of the model
+    // with the highest priority are processed first.
+    pub fn priority<A: Action + TypeUuid>(mut self, priority: i32) -> Self {
+        self.priorities.insert(A::UUID, priority);
         self
     }
 
     // Called once to construct the `Runner`.
-    pub fn build(self) -> Runner<Substate> {
-        Runner::new(self.state, self.models, self.dispatchers)
+    pub fn build(mut self) -> Runner<Substate> {
+        for dispatcher in self.dispatchers.iter_mut() {
+            dispatcher.effects_forbidden = self.forbid_effects;
+            dispatcher.max_depth = self.max_dispatch_depth;
+            dispatcher.priorities = self.priorities.clone();
+            dispatcher.effect_pool = self.effect_workers.map(EffectPool::new);
+            dispatcher.new_effects = self.new_effects;
+        }
+
+        let mut runner = Runner::new(
+            self.state,
+            self.models,
+            self.registration_order,
+            self.dispatchers,
+        );
+
+        runner.step_bound = self.step_bound;
+        runner.ndjson_export = self.ndjson_export;
+        runner.record_filter = self.record_filter;
+        runner.action_metrics = self.action_metrics.then(BTreeMap::new);
+        runner
     }
 }
 
@@ -83,63 +308,355 @@ impl<Substate: ModelState> Runner<Substate> {
     pub fn new(
         state: State<Substate>,
         models: BTreeMap<type_uuid::Bytes, AnyModel<Substate>>,
+        registration_order: Vec<type_uuid::Bytes>,
         dispatchers: Vec<Dispatcher>,
     ) -> Self {
         Self {
             models,
+            registration_order,
             state,
             dispatchers,
+            step_bound: None,
+            ndjson_export: None,
+            record_filter: None,
+            action_metrics: None,
         }
     }
 
     // State-machine main loop. If the runner contains more than one instance,
     // it interleaves the processing of actions fairly for each instance.
     pub fn run(&mut self) {
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
-            .format(|buf, record| writeln!(buf, "[{}] {}", record.level(), record.args()))
-            .init();
+        init_logger();
+
+        while self.step() {}
+    }
+
+    // Steps the runner until `predicate` holds for the state, for at most
+    // `max_ticks` steps. Returns whether the predicate held.
+    pub fn run_until(
+        &mut self,
+        mut predicate: impl FnMut(&State<Substate>) -> bool,
+        max_ticks: usize,
+    ) -> bool {
+        init_logger();
+
+        for _ in 0..max_ticks {
+            if predicate(&self.state) {
+                return true;
+            }
+
+            if !self.step() {
+                break;
+            }
+        }
+
+        predicate(&self.state)
+    }
+
+    // Processes the next action of each instance. Once an instance is halted,
+    // shuts the runner down and returns false.
+    pub fn step(&mut self) -> bool {
+        for instance in 0..self.dispatchers.len() {
+            if self.step_instance(instance).is_none() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    // Processes the next action of `instance` only, and returns its type name.
+    // If the instance is halted, shuts the runner down and returns `None`.
+    pub fn step_instance(&mut self, instance: usize) -> Option<&'static str> {
+        self.state.set_current_instance(instance);
+        let dispatcher = &mut self.dispatchers[instance];
+
+        if dispatcher.is_halted() {
+            self.shutdown();
+            return None;
+        }
+
+        let action = dispatcher.next_action();
+        let type_name = action.type_name;
+
+        self.process_action(action, instance);
+        Some(type_name)
+    }
+
+    // Single-steps the runner one action at a time, see `Stepper`.
+    pub fn stepper(&mut self) -> Stepper<'_, Substate> {
+        init_logger();
+        Stepper::new(self)
+    }
 
-        loop {
+    pub fn instances(&self) -> usize {
+        self.dispatchers.len()
+    }
+
+    // Replays a session's recording one action at a time, with some recorded
+    // effect results replaced, see `Replayer`.
+    pub fn replayer(&mut self, session_name: &str) -> Replayer<'_, Substate> {
+        self.open_replay(session_name);
+        Replayer::new(self.stepper())
+    }
+
+    // The next action processed by `instance` must be a recorded effect
+    // result, which is replaced by `patch` applied to it.
+    pub fn patch_replay(&mut self, instance: usize, patch: ReplayPatch) {
+        let dispatcher = &mut self.dispatchers[instance];
+
+        assert!(dispatcher.replay_file.is_some(), "Not replaying");
+        dispatcher.replay_patch = Some(patch);
+    }
+
+    // Invokes the `on_shutdown` hook of every model (dependents first), for
+    // each instance. Actions dispatched by a hook are processed before the
+    // next model is shut down, so lower-level models (e.g. `MioState`) are
+    // still alive while higher-level ones issue their final operations.
+    fn shutdown(&mut self) {
+        for uuid in self.registration_order.clone().iter().rev() {
             for instance in 0..self.dispatchers.len() {
                 self.state.set_current_instance(instance);
-                let dispatcher = &mut self.dispatchers[instance];
+                self.models
+                    .get_mut(uuid)
+                    .expect("registered model not found")
+                    .on_shutdown(&mut self.state, &mut self.dispatchers[instance]);
 
-                if dispatcher.is_halted() {
-                    return;
+                while let Some(action) = self.dispatchers[instance].next_queued_action() {
+                    self.process_action(action, instance)
                 }
+            }
+        }
 
-                let action = dispatcher.next_action();
-                self.process_action(action, instance)
+        if let Some(NdjsonExport { writer, .. }) = &mut self.ndjson_export {
+            writer.flush().expect("NDJSON export failed");
+        }
+
+        for dispatcher in self.dispatchers.iter_mut() {
+            if let Some(writer) = &mut dispatcher.record_file {
+                writer.flush().expect("Recorder: failed to flush recording");
             }
         }
     }
 
     fn process_action(&mut self, action: AnyAction, instance: usize) {
         let dispatcher = &mut self.dispatchers[instance];
+
+        // Replayer: every processed action has its recorded counterpart,
+        // except for new effects (see `RunnerBuilder::replay_new_effects`).
+        // Effects are inhibited, so the results they dispatched back are
+        // replaced by the recorded ones.
+        let mut live = false;
+        let mut patch = dispatcher.replay_patch.take();
+        let action = match &mut dispatcher.replay_file {
+            Some(_) if dispatcher.replay_unrecorded.contains(&action.dbginfo.caller) => {
+                dispatcher.replay_unrecorded.insert(action.dbginfo.action_id);
+                live = dispatcher.new_effects == NewEffects::Live;
+                action
+            }
+            Some(reader) => {
+                let recorded = match dispatcher.replay_pending.take() {
+                    Some(recorded) => recorded,
+                    None => {
+                        let uuid: type_uuid::Bytes = bincode::deserialize_from(&mut *reader)
+                            .expect("Replay: end of recording");
+
+                        self.models
+                            .get_mut(&uuid)
+                            .expect("Replay: recorded action of an unregistered model")
+                            .deserialize_from(reader)
+                    }
+                };
+
+                if matches!(action.kind, ActionKind::Effectful)
+                    && (action.uuid != recorded.uuid
+                        || action.dbginfo.effect != recorded.dbginfo.effect)
+                    && dispatcher.new_effects != NewEffects::Reject
+                {
+                    warn!(
+                        "Replay: new effect {} ({:?}), recorded {}",
+                        action.dbginfo.effect.as_deref().unwrap_or(action.type_name),
+                        dispatcher.new_effects,
+                        recorded.type_name
+                    );
+                    dispatcher.replay_pending = Some(recorded);
+                    dispatcher.replay_unrecorded.insert(action.dbginfo.action_id);
+                    live = dispatcher.new_effects == NewEffects::Live;
+                    action
+                } else {
+                    check_replayed(&action, &recorded);
+
+                    if action.dbginfo.effect.is_some() && matches!(action.kind, ActionKind::Pure) {
+                        match patch.take() {
+                            Some(patch) => patch(recorded),
+                            None => recorded,
+                        }
+                    } else {
+                        action
+                    }
+                }
+            }
+            None => action,
+        };
+
+        if patch.is_some() {
+            panic!(
+                "Replay patch: {} is not a recorded effect result",
+                action.type_name
+            )
+        }
+
         let model = self
             .models
             .get_mut(&action.uuid)
             .expect(&format!("action not found {}", action.type_name));
 
-        // Replayer
-        if let Some(_reader) = &mut dispatcher.replay_file {
-            todo!()
-        }
-
         // Recorder: no need to record all actions, but for the moment
         // we record them to ensure that the state-machine works properly.
         if let Some(writer) = &mut dispatcher.record_file {
-            model.serialize_into(writer, &action)
+            let recorded = match &mut self.record_filter {
+                Some(filter) => filter.matches(instance, &action, &model.to_json(&action)),
+                None => true,
+            };
+
+            if recorded {
+                model.serialize_into(writer, &action)
+            }
         }
 
+        if let Some(NdjsonExport { writer, seq }) = &mut self.ndjson_export {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("System time before UNIX epoch")
+                .as_nanos() as u64;
+            let line = serde_json::json!({
+                "seq": *seq,
+                "time": time,
+                "instance": instance,
+                "model": model_name(action.type_name),
+                "action": model.to_json(&action),
+            });
+
+            writeln!(writer, "{}", line).expect("NDJSON export failed");
+            *seq += 1;
+        }
+
+        let type_name = action.type_name;
+        let (uuid, kind) = (action.uuid, action.kind.as_str());
+        let start = Instant::now();
+
+        dispatcher.replay_live = live;
+        dispatcher.enter_chain(&action);
+
         match action.kind {
             ActionKind::Pure => model.process_pure(&mut self.state, action, dispatcher),
             ActionKind::Effectful => model.process_effectful(action, dispatcher),
         }
+
+        dispatcher.replay_live = false;
+
+        let duration = start.elapsed();
+
+        if let Some(StepBound { max, on_exceed }) = &mut self.step_bound {
+            if duration > *max {
+                warn!("Processing {} took {:?} (max {:?})", type_name, duration, max);
+
+                if on_exceed(type_name, duration) {
+                    dispatcher.halt()
+                }
+            }
+        }
+
+        if let Some(action_labels) = &mut self.action_metrics {
+            let state = &mut self.state;
+            let labels = action_labels.entry((instance, uuid)).or_insert_with(|| {
+                state.instance_labels(&[("model", model_name(type_name)), ("kind", kind)])
+            });
+
+            state.counter_add_labels(
+                "state_machine_actions_total",
+                "Number of processed actions.",
+                labels,
+                1.0,
+            );
+            state.counter_add_labels(
+                "state_machine_action_duration_seconds_total",
+                "Time spent processing actions.",
+                labels,
+                duration.as_secs_f64(),
+            );
+        }
+    }
+
+    pub fn state(&self) -> &State<Substate> {
+        &self.state
+    }
+
+    // Serializes the substates of all instances and the `Uid` counter, see
+    // `RunnerBuilder::from_snapshot()`. Metrics are not included.
+    //
+    // The model substates are serialized in the order of the fields of
+    // `Substate`, so a snapshot can be restored by any runner built with the
+    // same `Substate` type.
+    pub fn snapshot(&self) -> Vec<u8>
+    where
+        Substate: Serialize,
+    {
+        bincode::serialize(&(
+            SNAPSHOT_MAGIC,
+            SNAPSHOT_VERSION,
+            &self.state.uid_source,
+            &self.state.substates,
+        ))
+        .expect("Snapshot serialization failed")
+    }
+
+    // Rolls the running state-machine back (or forward) to a `snapshot()`
+    // taken with the same number of instances. Actions queued for the
+    // replaced state are dropped, each instance resumes with a "tick".
+    //
+    // Effectful models are not part of the snapshot and are left as they are:
+    // the OS objects (polls, sockets, ...) referenced by the restored state
+    // must still exist, e.g. the snapshot was taken by this runner and they
+    // weren't closed since.
+    pub fn restore(&mut self, snapshot: &[u8])
+    where
+        Substate: DeserializeOwned,
+    {
+        let (uid_source, substates) = decode_snapshot::<Substate>(snapshot);
+
+        assert_eq!(
+            substates.len(),
+            self.dispatchers.len(),
+            "Snapshot instances don't match the runner's"
+        );
+        self.state.uid_source = uid_source;
+        self.state.substates = substates;
+
+        for dispatcher in self.dispatchers.iter_mut() {
+            dispatcher.reset_queue()
+        }
+    }
+
+    // Number of offloaded effects that ran off the runner's thread, for all
+    // instances (see `RunnerBuilder::offload_effects`).
+    pub fn off_thread_effects(&self) -> u64 {
+        self.dispatchers
+            .iter()
+            .filter_map(|dispatcher| dispatcher.effect_pool.as_ref())
+            .map(EffectPool::off_thread_jobs)
+            .sum()
     }
 
     // Run the state-machine main loop and record actions
     pub fn record(&mut self, session_name: &str) {
+        self.open_record(session_name);
+        self.run()
+    }
+
+    // Creates a session's recording files without running, so a session can
+    // be recorded with a `Stepper` (e.g. up to the point of interest).
+    pub fn open_record(&mut self, session_name: &str) {
         let path = env::current_dir().expect("Failed to retrieve current directory");
 
         for (instance, dispatcher) in self.dispatchers.iter_mut().enumerate() {
@@ -150,12 +667,66 @@ impl<Substate: ModelState> Runner<Substate> {
                 instance
             ))
         }
+    }
 
-        self.run()
+    // Decodes the recording of a session's `instance`, one action per
+    // recorded action. The models of the recorded actions must be registered.
+    pub fn recorded_actions(&mut self, session_name: &str, instance: usize) -> Vec<AnyAction> {
+        let path = env::current_dir().expect("Failed to retrieve current directory");
+        let filename = format!(
+            "{}/{}_{}.rec",
+            path.to_str().unwrap(),
+            session_name,
+            instance
+        );
+        let mut reader = BufReader::new(
+            File::open(&filename)
+                .unwrap_or_else(|_| panic!("Failed to open recording: {}", filename)),
+        );
+        let mut actions = Vec::new();
+
+        while let Ok(uuid) = bincode::deserialize_from::<_, type_uuid::Bytes>(&mut reader) {
+            let model = self
+                .models
+                .get_mut(&uuid)
+                .expect("Recorded action of an unregistered model");
+
+            actions.push(model.deserialize_from(&mut reader));
+        }
+
+        actions
+    }
+
+    // Decodes the recording of a session's `instance` into JSON, one
+    // `{"model":..,"action":..}` object per recorded action (like
+    // `RunnerBuilder::export_ndjson`).
+    pub fn recording_to_json(
+        &mut self,
+        session_name: &str,
+        instance: usize,
+    ) -> Vec<serde_json::Value> {
+        self.recorded_actions(session_name, instance)
+            .iter()
+            .map(|action| {
+                let model = &self.models[&action.uuid];
+
+                serde_json::json!({
+                    "model": model_name(action.type_name),
+                    "action": model.to_json(action),
+                })
+            })
+            .collect()
     }
 
     // Replay deterministically from a session's recording files
     pub fn replay(&mut self, session_name: &str) {
+        self.open_replay(session_name);
+        self.run()
+    }
+
+    // Opens a session's recording files without running, so the replay can
+    // be walked through with a `Stepper`.
+    pub fn open_replay(&mut self, session_name: &str) {
         let path = env::current_dir().expect("Failed to retrieve current directory");
 
         for (instance, dispatcher) in self.dispatchers.iter_mut().enumerate() {
@@ -166,7 +737,46 @@ impl<Substate: ModelState> Runner<Substate> {
                 instance
             ))
         }
+    }
+}
 
-        self.run()
+// Panics if the action being replayed doesn't match the recorded one: both
+// must be of the same type and, for effect results, the result of the same
+// kind of effect. Otherwise the recording was made by different code, and its
+// results can't be fed to the current one.
+fn check_replayed(action: &AnyAction, recorded: &AnyAction) {
+    if action.uuid != recorded.uuid {
+        panic!(
+            "Replay diverged: recorded {}, replaying {}",
+            recorded.type_name, action.type_name
+        )
+    }
+
+    if action.dbginfo.effect != recorded.dbginfo.effect {
+        panic!(
+            "Replay mismatch: recorded result of {} fed to {} ({})",
+            recorded.dbginfo.effect.as_deref().unwrap_or("no effect"),
+            action.dbginfo.effect.as_deref().unwrap_or("no effect"),
+            action.type_name
+        )
     }
 }
+
+// Name of the model handling the actions of type `type_name`, taken from the
+// module path of the action type (e.g. `tcp` for `...::net::tcp::action::TcpAction`).
+fn model_name(type_name: &'static str) -> &'static str {
+    let mut segments = type_name.rsplit("::").skip(1);
+
+    match (segments.next(), segments.next()) {
+        (Some("action"), Some(model)) => model,
+        _ => type_name,
+    }
+}
+
+fn init_logger() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format(|buf, record| writeln!(buf, "[{}] {}", record.level(), record.args()))
+        // Several runners can be run by the same process (e.g. tests)
+        .try_init()
+        .ok();
+}
@@ -1,3 +1,7 @@
+use super::{
+    config::ConfigState,
+    metrics::{Labels, Metrics},
+};
 use serde_derive::{Deserialize, Serialize};
 use std::{any::Any, collections::BTreeMap};
 
@@ -70,9 +74,14 @@ pub type Objects<T> = BTreeMap<Uid, T>;
 // In most scenarios, the `Substates` vector contains a single element.
 // Multiple elements mainly occur in testing scenarios where multiple
 // instances are simulated.
+//
+// The `metrics` registry is shared by all instances, samples are told apart
+// by their `instance` label. So are the settings in `config`.
 pub struct State<Substates: ModelState> {
     pub uid_source: Uid,
     pub substates: Vec<Substates>,
+    pub metrics: Metrics,
+    pub config: ConfigState,
     current_instance: usize,
 }
 
@@ -104,11 +113,23 @@ pub trait ModelState {
     fn state_mut<T: 'static + Any>(&mut self) -> &mut T;
 }
 
+// A typed, read-only query against a model's state, answered synchronously by
+// `State::query` (e.g. "how many connections are active right now?"). Unlike
+// actions, queries go through neither the dispatcher nor the recorder, so the
+// answer must not be used to drive state transitions.
+pub trait Query<Q> {
+    type Answer;
+
+    fn answer(&self, query: Q) -> Self::Answer;
+}
+
 impl<Substates: ModelState> State<Substates> {
     pub fn new() -> Self {
         Self {
             uid_source: Uid::default(),
             substates: Vec::new(),
+            metrics: Metrics::new(),
+            config: ConfigState::default(),
             current_instance: 0,
         }
     }
@@ -135,4 +156,66 @@ impl<Substates: ModelState> State<Substates> {
     pub fn substate_mut<T: 'static + Any>(&mut self) -> &mut T {
         self.substates[self.current_instance].state_mut()
     }
+
+    // Answers `query` from the state of model `M` in the currently active
+    // substate, see `Query`.
+    pub fn query<M: 'static + Any + Query<Q>, Q>(&self, query: Q) -> M::Answer {
+        self.substate::<M>().answer(query)
+    }
+
+    // The shared setting of type `T`, see `RunnerBuilder::config`.
+    pub fn config<T: 'static>(&self) -> &T {
+        self.config
+            .get()
+            .unwrap_or_else(|| panic!("Config {} not set", std::any::type_name::<T>()))
+    }
+
+    pub fn instance_labels(&self, labels: &[(&'static str, &str)]) -> Labels {
+        let instance = ("instance", self.current_instance.to_string());
+
+        std::iter::once(instance)
+            .chain(labels.iter().map(|(label, value)| (*label, value.to_string())))
+            .collect()
+    }
+
+    // Adds `value` to a counter of the currently active instance.
+    pub fn counter_add(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&'static str, &str)],
+        value: f64,
+    ) {
+        let labels = self.instance_labels(labels);
+        self.metrics.counter_add(name, help, &labels, value)
+    }
+
+    // Same as `counter_add()`, with `labels` already built by
+    // `instance_labels()`, so hot paths can build them once and reuse them.
+    pub fn counter_add_labels(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        labels: &Labels,
+        value: f64,
+    ) {
+        self.metrics.counter_add(name, help, labels, value)
+    }
+
+    // Sets a gauge of the currently active instance.
+    pub fn gauge_set(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&'static str, &str)],
+        value: f64,
+    ) {
+        let labels = self.instance_labels(labels);
+        self.metrics.gauge_set(name, help, &labels, value)
+    }
+
+    // Renders the metrics of all instances in the Prometheus text format.
+    pub fn metrics_prometheus(&self) -> String {
+        self.metrics.render()
+    }
 }
@@ -0,0 +1,94 @@
+use super::{
+    runner::Runner,
+    state::{ModelState, State},
+};
+use log::info;
+
+// Drives a `Runner` one action at a time under external control (debugging,
+// tests), printing every processed action. The state can be inspected with
+// `state()` between steps.
+//
+// Actions are numbered from 1 in processing order, interleaving instances
+// like `Runner::step`. After `Runner::open_replay` the same numbers identify
+// the same actions on every replay, so `run_to_seq` can walk a recording to
+// the point of interest.
+pub struct Stepper<'a, Substate: ModelState> {
+    runner: &'a mut Runner<Substate>,
+    seq: u64,
+    next_instance: usize,
+    halted: bool,
+}
+
+impl<'a, Substate: ModelState> Stepper<'a, Substate> {
+    pub fn new(runner: &'a mut Runner<Substate>) -> Self {
+        Self {
+            runner,
+            seq: 0,
+            next_instance: 0,
+            halted: false,
+        }
+    }
+
+    // Number of actions processed so far.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    pub fn state(&self) -> &State<Substate> {
+        self.runner.state()
+    }
+
+    // The instance that processes the next action.
+    pub fn next_instance(&self) -> usize {
+        self.next_instance
+    }
+
+    pub(super) fn runner_mut(&mut self) -> &mut Runner<Substate> {
+        self.runner
+    }
+
+    // Processes the next action. Returns false once the runner was shut down
+    // (an instance halted), in which case nothing was processed.
+    pub fn step(&mut self) -> bool {
+        if self.halted {
+            return false;
+        }
+
+        let instance = self.next_instance;
+
+        let Some(type_name) = self.runner.step_instance(instance) else {
+            self.halted = true;
+            return false;
+        };
+
+        self.seq += 1;
+        self.next_instance = (instance + 1) % self.runner.instances();
+        info!("step {} ({}): {}", self.seq, instance, type_name);
+        true
+    }
+
+    // Processes up to `k` actions, returns how many were processed.
+    pub fn step_n(&mut self, k: u64) -> u64 {
+        let start = self.seq;
+
+        for _ in 0..k {
+            if !self.step() {
+                break;
+            }
+        }
+
+        self.seq - start
+    }
+
+    // Steps until action number `n` was processed. Returns false if the
+    // runner was shut down before.
+    pub fn run_to_seq(&mut self, n: u64) -> bool {
+        while self.seq < n {
+            if !self.step() {
+                return false;
+            }
+        }
+
+        true
+    }
+}
@@ -0,0 +1,31 @@
+use crate::automaton::{
+    action::{Action, ActionKind},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "9d1c6efd-5cc6-41fb-90e2-0a2fc149c54f"]
+pub enum FuzzDriverAction {
+    Tick,
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    InitListenerSuccess { listener: Uid },
+    InitListenerError { listener: Uid, error: String },
+    ListenerCloseEvent { listener: Uid },
+    ConnectionEvent { listener: Uid, connection: Uid },
+    CloseEvent { listener: Uid, connection: Uid },
+    PollSuccess { uid: Uid },
+    PollError { uid: Uid, error: String },
+    SendSuccess { uid: Uid },
+    SendTimeout { uid: Uid },
+    SendError { uid: Uid, error: String },
+    RecvSuccess { uid: Uid, data: Vec<u8> },
+    RecvTimeout { uid: Uid, partial_data: Vec<u8> },
+    RecvError { uid: Uid, error: String },
+}
+
+impl Action for FuzzDriverAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,3 @@
+pub mod action;
+pub mod model;
+pub mod state;
@@ -0,0 +1,188 @@
+use super::{
+    action::FuzzDriverAction,
+    state::{FuzzDriverState, FuzzDriverStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    fuzz::input::FuzzInput,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `FuzzDriverState` model is the application side of the fuzz harness:
+// on each tick it decodes the next input byte into a `TcpServerAction`
+// request (listen, poll, send, recv or close), with its arguments taken from
+// the following bytes. Requests only target listeners and connections the
+// driver has been told about, as a well-behaved application would. Every
+// callback, including errors, is accepted, and the driver halts the
+// state-machine once the input is exhausted.
+
+const MAX_LISTENERS: usize = 2;
+const MAX_CONNECTIONS: usize = 4;
+const MAX_DATA_LEN: usize = 16;
+const MAX_TIMEOUT: usize = 256;
+
+// This model depends on `TcpServerState`.
+impl RegisterModel for FuzzDriverState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpServerState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for FuzzDriverState {
+    type Action = FuzzDriverAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            FuzzDriverAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                let driver_state: &FuzzDriverState = state.substate();
+
+                match driver_state.status {
+                    FuzzDriverStatus::Init => dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| FuzzDriverAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| FuzzDriverAction::InitError { instance, error }),
+                    }),
+                    FuzzDriverStatus::Ready => dispatch_request(state, dispatcher),
+                }
+            }
+            FuzzDriverAction::InitSuccess { .. } => {
+                state.substate_mut::<FuzzDriverState>().status = FuzzDriverStatus::Ready
+            }
+            FuzzDriverAction::InitError { .. } => dispatcher.halt(),
+            FuzzDriverAction::InitListenerSuccess { listener } => state
+                .substate_mut::<FuzzDriverState>()
+                .listeners
+                .push(listener),
+            FuzzDriverAction::ListenerCloseEvent { listener } => state
+                .substate_mut::<FuzzDriverState>()
+                .listeners
+                .retain(|uid| *uid != listener),
+            FuzzDriverAction::ConnectionEvent { connection, .. } => state
+                .substate_mut::<FuzzDriverState>()
+                .connections
+                .push(connection),
+            FuzzDriverAction::CloseEvent { connection, .. } => state
+                .substate_mut::<FuzzDriverState>()
+                .remove_connection(&connection),
+            FuzzDriverAction::InitListenerError { .. }
+            | FuzzDriverAction::PollSuccess { .. }
+            | FuzzDriverAction::PollError { .. }
+            | FuzzDriverAction::SendSuccess { .. }
+            | FuzzDriverAction::SendTimeout { .. }
+            | FuzzDriverAction::SendError { .. }
+            | FuzzDriverAction::RecvSuccess { .. }
+            | FuzzDriverAction::RecvTimeout { .. }
+            | FuzzDriverAction::RecvError { .. } => (),
+        }
+    }
+}
+
+fn dispatch_request<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let input = state.substate::<FuzzDriverState>().input.clone();
+    let mut input = input.borrow_mut();
+
+    let Some(request) = input.byte() else {
+        dispatcher.halt();
+        return;
+    };
+
+    let driver_state: &FuzzDriverState = state.substate();
+    // Send, recv and close requests target one of the known connections.
+    let connection = match (request % 5, driver_state.connections.len()) {
+        (0 | 1, _) | (_, 0) => None,
+        (_, len) => Some(driver_state.connections[input.choose(len, 0)]),
+    };
+
+    match (request % 5, connection) {
+        (0, _) if driver_state.listeners.len() < MAX_LISTENERS => {
+            let address = format!("127.0.0.1:{}", 8000 + driver_state.listeners.len());
+
+            dispatcher.dispatch(TcpServerAction::New {
+                listener: state.new_uid(),
+                address,
+                max_connections: 1 + input.choose(MAX_CONNECTIONS, 0),
+                backlog: None,
+                routing: RoutingPolicy::None,
+                admission_control: None,
+                nodelay: false,
+                on_success: callback!(|listener: Uid| FuzzDriverAction::InitListenerSuccess { listener }),
+                on_error: callback!(|(listener: Uid, error: String)| FuzzDriverAction::InitListenerError { listener, error }),
+                on_new_connection: callback!(|(listener: Uid, connection: Uid)| FuzzDriverAction::ConnectionEvent { listener, connection }),
+                on_connection_closed: callback!(|(listener: Uid, connection: Uid)| FuzzDriverAction::CloseEvent { listener, connection }),
+                on_listener_closed: callback!(|listener: Uid| FuzzDriverAction::ListenerCloseEvent { listener }),
+            })
+        }
+        (1, _) => dispatcher.dispatch(TcpServerAction::Poll {
+            uid: state.new_uid(),
+            timeout: timeout(&mut input),
+            on_success: callback!(|uid: Uid| FuzzDriverAction::PollSuccess { uid }),
+            on_error: callback!(|(uid: Uid, error: String)| FuzzDriverAction::PollError { uid, error }),
+        }),
+        (2, Some(connection)) => {
+            let len = 1 + input.choose(MAX_DATA_LEN, 0);
+
+            dispatcher.dispatch(TcpServerAction::Send {
+                uid: state.new_uid(),
+                connection,
+                data: vec![request; len].into(),
+                timeout: timeout(&mut input),
+                on_success: callback!(|uid: Uid| FuzzDriverAction::SendSuccess { uid }),
+                on_timeout: callback!(|uid: Uid| FuzzDriverAction::SendTimeout { uid }),
+                on_error: callback!(|(uid: Uid, error: String)| FuzzDriverAction::SendError { uid, error }),
+            })
+        }
+        (3, Some(connection)) => dispatcher.dispatch(TcpServerAction::Recv {
+            uid: state.new_uid(),
+            connection,
+            count: 1 + input.choose(MAX_DATA_LEN, 0),
+            timeout: timeout(&mut input),
+            on_success: callback!(|(uid: Uid, data: Vec<u8>)| FuzzDriverAction::RecvSuccess { uid, data }),
+            on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| FuzzDriverAction::RecvTimeout { uid, partial_data }),
+            on_error: callback!(|(uid: Uid, error: String)| FuzzDriverAction::RecvError { uid, error }),
+        }),
+        (4, Some(connection)) => {
+            let deliver_buffered = input.choose(2, 0) == 1;
+
+            state
+                .substate_mut::<FuzzDriverState>()
+                .remove_connection(&connection);
+            dispatcher.dispatch(TcpServerAction::Close {
+                connection,
+                deliver_buffered,
+            })
+        }
+        // Requests that don't apply to the current state are skipped.
+        _ => (),
+    }
+}
+
+fn timeout(input: &mut FuzzInput) -> Timeout {
+    Timeout::Millis(input.choose(MAX_TIMEOUT, 0) as u64)
+}
@@ -0,0 +1,32 @@
+use crate::{automaton::state::Uid, fuzz::input::SharedInput};
+
+#[derive(Debug)]
+pub enum FuzzDriverStatus {
+    Init,
+    Ready,
+}
+
+#[derive(Debug)]
+pub struct FuzzDriverState {
+    pub input: SharedInput,
+    pub status: FuzzDriverStatus,
+    pub listeners: Vec<Uid>,
+    // Connections the driver can issue requests on: reported by the listener
+    // and not closed yet.
+    pub connections: Vec<Uid>,
+}
+
+impl FuzzDriverState {
+    pub fn new(input: SharedInput) -> Self {
+        Self {
+            input,
+            status: FuzzDriverStatus::Init,
+            listeners: Vec::new(),
+            connections: Vec::new(),
+        }
+    }
+
+    pub fn remove_connection(&mut self, connection: &Uid) {
+        self.connections.retain(|uid| uid != connection)
+    }
+}
@@ -0,0 +1,37 @@
+use std::{cell::RefCell, rc::Rc};
+
+// Fuzzer-provided bytes, consumed by the fuzz driver and the fake effectful
+// models in the order they need them.
+#[derive(Debug)]
+pub struct FuzzInput {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+pub type SharedInput = Rc<RefCell<FuzzInput>>;
+
+impl FuzzInput {
+    pub fn new(data: &[u8]) -> Self {
+        Self {
+            data: data.to_vec(),
+            pos: 0,
+        }
+    }
+
+    pub fn byte(&mut self) -> Option<u8> {
+        let byte = self.data.get(self.pos).copied();
+
+        self.pos += byte.is_some() as usize;
+        byte
+    }
+
+    // Picks a value in `0..n`, or `default` once the input is exhausted.
+    pub fn choose(&mut self, n: usize, default: usize) -> usize {
+        self.byte().map_or(default, |byte| byte as usize % n)
+    }
+
+    // Zero-padded once the input is exhausted.
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.byte().unwrap_or(0)).collect()
+    }
+}
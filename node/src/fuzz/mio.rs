@@ -0,0 +1,650 @@
+use super::{
+    input::{FuzzInput, SharedInput},
+    time::SharedClock,
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::EffectfulModel,
+        state::Uid,
+    },
+    models::effectful::mio::action::{
+        MioEffectfulAction, MioEvent, PollResult, TcpAcceptResult, TcpReadResult, TcpWriteResult,
+        UdpRecvResult, UdpSendResult,
+    },
+};
+use std::{collections::BTreeSet, time::Duration};
+
+// Conditions simulated by `FuzzMioState` on top of the input-defined results,
+// to deterministically reproduce situations that are hard to trigger with real
+// sockets.
+#[derive(Clone, Default, Debug)]
+pub struct NetworkConditions {
+    // Peers advertise a zero receive window until the virtual clock reaches
+    // this time: writes return `WouldBlock` (without consuming input), then
+    // behave as usual once the window reopens.
+    pub zero_window_until: Option<Duration>,
+}
+
+// Fake `MioState` (effectful): keeps track of the objects the real model would
+// create, and returns input-defined results for every request. Requests on
+// unknown objects panic like they do in `MioState`. Polls without events
+// sleep for their timeout on the virtual clock.
+pub struct FuzzMioState {
+    input: SharedInput,
+    clock: SharedClock,
+    conditions: NetworkConditions,
+    polls: BTreeSet<Uid>,
+    events: BTreeSet<Uid>,
+    listeners: BTreeSet<Uid>,
+    connections: BTreeSet<Uid>,
+    udp_sockets: BTreeSet<Uid>,
+    // Listeners, connections and UDP sockets currently registered with a poll object.
+    registered: BTreeSet<Uid>,
+    accepted: u16,
+}
+
+const ERRORS: [&str; 5] = [
+    "Connection reset by peer (os error 104)",
+    "Broken pipe (os error 32)",
+    "Resource temporarily unavailable (os error 11)",
+    "Cannot allocate memory (os error 12)",
+    "Too many open files (os error 24)",
+];
+
+fn error(input: &mut FuzzInput) -> String {
+    ERRORS[input.choose(ERRORS.len(), 0)].to_string()
+}
+
+// Fails one out of 8 times, never once the input is exhausted.
+fn result(input: &mut FuzzInput) -> Result<(), String> {
+    match input.choose(8, 0) {
+        7 => Err(error(input)),
+        _ => Ok(()),
+    }
+}
+
+fn event(token: Uid, flags: u8) -> MioEvent {
+    MioEvent {
+        token,
+        readable: flags & 1 != 0,
+        writable: flags & 2 != 0,
+        read_closed: flags & 4 != 0,
+        write_closed: flags & 8 != 0,
+        error: flags & 16 != 0,
+        priority: false,
+        aio: false,
+        lio: false,
+    }
+}
+
+impl FuzzMioState {
+    pub fn new(input: SharedInput, clock: SharedClock, conditions: NetworkConditions) -> Self {
+        Self {
+            input,
+            clock,
+            conditions,
+            polls: BTreeSet::new(),
+            events: BTreeSet::new(),
+            listeners: BTreeSet::new(),
+            connections: BTreeSet::new(),
+            udp_sockets: BTreeSet::new(),
+            registered: BTreeSet::new(),
+            accepted: 0,
+        }
+    }
+
+    fn new_object(objects: &mut BTreeSet<Uid>, uid: Uid) {
+        if !objects.insert(uid) {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    fn check_poll(&self, poll: &Uid) {
+        assert!(
+            self.polls.contains(poll),
+            "Poll object not found {:?}",
+            poll
+        );
+    }
+
+    fn check_connection(&self, connection: &Uid) {
+        assert!(
+            self.connections.contains(connection),
+            "TCP connection stream object not found for uid {:?}",
+            connection
+        );
+    }
+
+    fn check_udp_socket(&self, socket: &Uid) {
+        assert!(
+            self.udp_sockets.contains(socket),
+            "UdpSocket object not found {:?}",
+            socket
+        );
+    }
+
+    fn register(&mut self, poll: &Uid, uid: Uid) -> Result<(), String> {
+        self.check_poll(poll);
+
+        if self.registered.contains(&uid) {
+            return Err("File exists (os error 17)".to_string());
+        }
+
+        let result = result(&mut self.input.borrow_mut());
+
+        if result.is_ok() {
+            self.registered.insert(uid);
+        }
+
+        result
+    }
+
+    fn poll_events(&mut self, poll: &Uid, events: &Uid) -> PollResult {
+        self.check_poll(poll);
+        assert!(
+            self.events.contains(events),
+            "Events object not found {:?}",
+            events
+        );
+
+        let mut input = self.input.borrow_mut();
+
+        match input.choose(8, 0) {
+            6 => PollResult::Interrupted,
+            7 => PollResult::Error(error(&mut input)),
+            _ => {
+                let count = input.choose(4, 0);
+                let tokens: Vec<Uid> = self.registered.iter().copied().collect();
+
+                if tokens.is_empty() {
+                    return PollResult::Events(Vec::new());
+                }
+
+                PollResult::Events(
+                    (0..count)
+                        .map(|_| {
+                            let token = tokens[input.choose(tokens.len(), 0)];
+                            event(token, input.byte().unwrap_or(0))
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    fn tcp_accept(&mut self, connection: Uid, listener: &Uid) -> TcpAcceptResult {
+        assert!(
+            self.listeners.contains(listener),
+            "TcpListener object {:?} not found",
+            listener
+        );
+
+        let mut input = self.input.borrow_mut();
+
+        match input.choose(8, 6) {
+            6 => TcpAcceptResult::WouldBlock,
+            7 => TcpAcceptResult::Error(error(&mut input)),
+            _ => {
+                Self::new_object(&mut self.connections, connection);
+                self.accepted = self.accepted.wrapping_add(1);
+                TcpAcceptResult::Success {
+                    local_address: "127.0.0.1:8000".to_string(),
+                    peer_address: format!("127.0.0.1:{}", 40000 + self.accepted % 20000),
+                }
+            }
+        }
+    }
+
+    fn tcp_close(&mut self, connection: &Uid) {
+        self.check_connection(connection);
+        self.connections.remove(connection);
+        self.registered.remove(connection);
+    }
+
+    fn tcp_write(&mut self, connection: &Uid, data: &[u8]) -> TcpWriteResult {
+        self.check_connection(connection);
+
+        if let Some(until) = self.conditions.zero_window_until {
+            if self.clock.get() < until {
+                return TcpWriteResult::WouldBlock;
+            }
+        }
+
+        let mut input = self.input.borrow_mut();
+
+        match input.choose(8, 0) {
+            4 if data.len() > 1 => {
+                TcpWriteResult::WrittenPartial(1 + input.choose(data.len() - 1, 0))
+            }
+            5 => TcpWriteResult::Interrupted,
+            6 => TcpWriteResult::WouldBlock,
+            7 => TcpWriteResult::Error(error(&mut input)),
+            _ => TcpWriteResult::WrittenAll,
+        }
+    }
+
+    fn udp_send(&mut self, socket: &Uid) -> UdpSendResult {
+        self.check_udp_socket(socket);
+
+        let mut input = self.input.borrow_mut();
+
+        match input.choose(8, 0) {
+            5 => UdpSendResult::Interrupted,
+            6 => UdpSendResult::WouldBlock,
+            7 => UdpSendResult::Error(error(&mut input)),
+            _ => UdpSendResult::Sent,
+        }
+    }
+
+    fn udp_recv_from(&mut self, socket: &Uid, len: usize) -> UdpRecvResult {
+        assert_ne!(len, 0);
+        self.check_udp_socket(socket);
+
+        let mut input = self.input.borrow_mut();
+
+        match input.choose(8, 6) {
+            5 => UdpRecvResult::Interrupted,
+            6 => UdpRecvResult::WouldBlock,
+            7 => UdpRecvResult::Error(error(&mut input)),
+            _ => {
+                let count = 1 + input.choose(len, 0);
+                UdpRecvResult::Received {
+                    peer_address: format!("127.0.0.1:{}", 40000 + input.choose(20000, 0)),
+                    data: input.bytes(count),
+                }
+            }
+        }
+    }
+
+    fn tcp_read(&mut self, connection: &Uid, len: usize) -> TcpReadResult {
+        assert_ne!(len, 0);
+        self.check_connection(connection);
+
+        let mut input = self.input.borrow_mut();
+
+        match input.choose(8, 6) {
+            4 if len > 1 => {
+                let count = 1 + input.choose(len - 1, 0);
+                TcpReadResult::ReadPartial(input.bytes(count))
+            }
+            5 => TcpReadResult::Interrupted,
+            6 => TcpReadResult::WouldBlock,
+            7 => TcpReadResult::Error("Connection closed".to_string()),
+            _ => TcpReadResult::ReadAll(input.bytes(len)),
+        }
+    }
+}
+
+impl EffectfulModel for FuzzMioState {
+    type Action = MioEffectfulAction;
+
+    fn process_effectful(&mut self, action: Self::Action, dispatcher: &mut Dispatcher) {
+        match action {
+            MioEffectfulAction::PollCreate {
+                poll,
+                on_success,
+                on_error,
+            } => match result(&mut self.input.borrow_mut()) {
+                Ok(_) => {
+                    Self::new_object(&mut self.polls, poll);
+                    dispatcher.dispatch_back(&on_success, poll)
+                }
+                Err(error) => dispatcher.dispatch_back(&on_error, (poll, error)),
+            },
+            MioEffectfulAction::PollRegisterTcpServer {
+                poll,
+                listener,
+                on_success,
+                on_error,
+            } => {
+                assert!(
+                    self.listeners.contains(&listener),
+                    "TcpListener object {:?} not found",
+                    listener
+                );
+
+                match self.register(&poll, listener) {
+                    Ok(_) => dispatcher.dispatch_back(&on_success, listener),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (listener, error)),
+                }
+            }
+            MioEffectfulAction::PollRegisterTcpConnection {
+                poll,
+                connection,
+                interest: _,
+                on_success,
+                on_error,
+            } => {
+                self.check_connection(&connection);
+
+                match self.register(&poll, connection) {
+                    Ok(_) => dispatcher.dispatch_back(&on_success, connection),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
+                }
+            }
+            MioEffectfulAction::PollDeregisterTcpConnection {
+                poll,
+                connection,
+                on_success,
+                on_error,
+            } => {
+                self.check_poll(&poll);
+                self.check_connection(&connection);
+
+                if self.registered.remove(&connection) {
+                    dispatcher.dispatch_back(&on_success, connection)
+                } else {
+                    let error = "No such file or directory (os error 2)".to_string();
+                    dispatcher.dispatch_back(&on_error, (connection, error))
+                }
+            }
+            MioEffectfulAction::PollReregisterTcpConnection {
+                poll,
+                connection,
+                interest: _,
+                on_success,
+                on_error,
+            } => {
+                self.check_poll(&poll);
+                self.check_connection(&connection);
+
+                if self.registered.contains(&connection) {
+                    dispatcher.dispatch_back(&on_success, connection)
+                } else {
+                    let error = "No such file or directory (os error 2)".to_string();
+                    dispatcher.dispatch_back(&on_error, (connection, error))
+                }
+            }
+            MioEffectfulAction::PollEvents {
+                uid,
+                poll,
+                events,
+                timeout,
+                on_success,
+                on_interrupted,
+                on_error,
+            } => match self.poll_events(&poll, &events) {
+                PollResult::Events(events) => {
+                    if let (true, Timeout::Millis(ms)) = (events.is_empty(), timeout) {
+                        self.clock.set(self.clock.get() + Duration::from_millis(ms));
+                    }
+
+                    dispatcher.dispatch_back(&on_success, (uid, events))
+                }
+                PollResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
+                PollResult::Error(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+            },
+            MioEffectfulAction::EventsCreate {
+                uid,
+                capacity: _,
+                on_success,
+            } => {
+                Self::new_object(&mut self.events, uid);
+                dispatcher.dispatch_back(&on_success, uid);
+            }
+            MioEffectfulAction::TcpListen {
+                listener,
+                address: _,
+                options: _,
+                backlog: _,
+                on_success,
+                on_error,
+            } => match result(&mut self.input.borrow_mut()) {
+                Ok(_) => {
+                    Self::new_object(&mut self.listeners, listener);
+                    dispatcher.dispatch_back(&on_success, listener)
+                }
+                Err(error) => dispatcher.dispatch_back(&on_error, (listener, error)),
+            },
+            MioEffectfulAction::TcpListenFromFd {
+                listener,
+                fd: _,
+                on_success,
+                on_error,
+            } => match result(&mut self.input.borrow_mut()) {
+                Ok(_) => {
+                    Self::new_object(&mut self.listeners, listener);
+                    dispatcher.dispatch_back(&on_success, (listener, "127.0.0.1:0".to_string()))
+                }
+                Err(error) => dispatcher.dispatch_back(&on_error, (listener, error)),
+            },
+            MioEffectfulAction::TcpAccept {
+                connection,
+                listener,
+                on_success,
+                on_would_block,
+                on_error,
+            } => match self.tcp_accept(connection, &listener) {
+                TcpAcceptResult::Success {
+                    local_address,
+                    peer_address,
+                } => {
+                    dispatcher.dispatch_back(&on_success, (connection, local_address, peer_address))
+                }
+                TcpAcceptResult::WouldBlock => {
+                    dispatcher.dispatch_back(&on_would_block, connection)
+                }
+                TcpAcceptResult::Error(error) => {
+                    dispatcher.dispatch_back(&on_error, (connection, error))
+                }
+            },
+            MioEffectfulAction::TcpConnect {
+                connection,
+                address: _,
+                options: _,
+                bind_address: _,
+                on_success,
+                on_error,
+            } => match result(&mut self.input.borrow_mut()) {
+                Ok(_) => {
+                    Self::new_object(&mut self.connections, connection);
+                    dispatcher.dispatch_back(&on_success, connection)
+                }
+                Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
+            },
+            MioEffectfulAction::TcpClose {
+                connection,
+                on_success,
+            } => {
+                self.tcp_close(&connection);
+                dispatcher.dispatch_back(&on_success, connection);
+            }
+            MioEffectfulAction::TcpWrite {
+                uid,
+                connection,
+                data,
+                on_success,
+                on_success_partial,
+                on_interrupted,
+                on_would_block,
+                on_error,
+            } => match self.tcp_write(&connection, &data) {
+                TcpWriteResult::WrittenAll => dispatcher.dispatch_back(&on_success, uid),
+                TcpWriteResult::WrittenPartial(count) => {
+                    dispatcher.dispatch_back(&on_success_partial, (uid, count))
+                }
+                TcpWriteResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
+                TcpWriteResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
+                TcpWriteResult::Error(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+            },
+            MioEffectfulAction::TcpRead {
+                uid,
+                connection,
+                len,
+                on_success,
+                on_success_partial,
+                on_interrupted,
+                on_would_block,
+                on_error,
+            } => match self.tcp_read(&connection, len) {
+                TcpReadResult::ReadAll(data) => dispatcher.dispatch_back(&on_success, (uid, data)),
+                TcpReadResult::ReadPartial(partial_data) => {
+                    dispatcher.dispatch_back(&on_success_partial, (uid, partial_data))
+                }
+                TcpReadResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
+                TcpReadResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
+                TcpReadResult::Error(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+            },
+            MioEffectfulAction::TcpGetPeerAddress {
+                connection,
+                on_success,
+                on_error,
+            } => {
+                self.check_connection(&connection);
+
+                match result(&mut self.input.borrow_mut()) {
+                    Ok(_) => {
+                        let addresses =
+                            ("127.0.0.1:8000".to_string(), "127.0.0.1:40000".to_string());
+                        dispatcher
+                            .dispatch_back(&on_success, (connection, addresses.0, addresses.1))
+                    }
+                    Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
+                }
+            }
+            MioEffectfulAction::TcpGetLocalAddress { uid, on_result } => {
+                if !self.listeners.contains(&uid) {
+                    self.check_connection(&uid);
+                }
+
+                let result =
+                    result(&mut self.input.borrow_mut()).map(|_| "127.0.0.1:8000".to_string());
+
+                dispatcher.dispatch_back(&on_result, (uid, result));
+            }
+            MioEffectfulAction::TcpGetBufferStatus {
+                uid,
+                connection,
+                on_success,
+                on_error,
+            } => {
+                self.check_connection(&connection);
+
+                let mut input = self.input.borrow_mut();
+
+                match result(&mut input) {
+                    Ok(_) => {
+                        let send_queued = input.choose(256, 0);
+                        let recv_available = input.choose(256, 0);
+                        dispatcher.dispatch_back(&on_success, (uid, send_queued, recv_available))
+                    }
+                    Err(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+                }
+            }
+            MioEffectfulAction::PollRegisterUdpSocket {
+                poll,
+                socket,
+                on_success,
+                on_error,
+            } => {
+                self.check_udp_socket(&socket);
+
+                match self.register(&poll, socket) {
+                    Ok(_) => dispatcher.dispatch_back(&on_success, socket),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (socket, error)),
+                }
+            }
+            MioEffectfulAction::TcpBytesAvailable {
+                connection,
+                on_success,
+                on_not_supported: _,
+                on_error,
+            } => {
+                self.check_connection(&connection);
+
+                let mut input = self.input.borrow_mut();
+
+                match result(&mut input) {
+                    Ok(_) => {
+                        let count = input.choose(256, 0);
+                        dispatcher.dispatch_back(&on_success, (connection, count))
+                    }
+                    Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
+                }
+            }
+            MioEffectfulAction::TcpShutdown {
+                connection,
+                how: _,
+                on_success,
+                on_error,
+            } => {
+                self.check_connection(&connection);
+
+                match result(&mut self.input.borrow_mut()) {
+                    Ok(()) => dispatcher.dispatch_back(&on_success, connection),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
+                }
+            }
+            MioEffectfulAction::TcpSetNodelay {
+                connection,
+                value: _,
+                on_result,
+            } => {
+                self.check_connection(&connection);
+
+                let result = result(&mut self.input.borrow_mut());
+
+                dispatcher.dispatch_back(&on_result, (connection, result));
+            }
+            MioEffectfulAction::TcpGetCongestion {
+                connection,
+                on_result,
+            } => {
+                self.check_connection(&connection);
+
+                let result = result(&mut self.input.borrow_mut()).map(|_| "cubic".to_string());
+
+                dispatcher.dispatch_back(&on_result, (connection, result));
+            }
+            MioEffectfulAction::UdpBind {
+                socket,
+                address: _,
+                on_success,
+                on_error,
+            } => match result(&mut self.input.borrow_mut()) {
+                Ok(_) => {
+                    Self::new_object(&mut self.udp_sockets, socket);
+                    dispatcher.dispatch_back(&on_success, socket)
+                }
+                Err(error) => dispatcher.dispatch_back(&on_error, (socket, error)),
+            },
+            MioEffectfulAction::UdpClose { socket, on_success } => {
+                self.check_udp_socket(&socket);
+                self.udp_sockets.remove(&socket);
+                self.registered.remove(&socket);
+                dispatcher.dispatch_back(&on_success, socket);
+            }
+            MioEffectfulAction::UdpSend {
+                uid,
+                socket,
+                on_success,
+                on_interrupted,
+                on_would_block,
+                on_error,
+                ..
+            } => match self.udp_send(&socket) {
+                UdpSendResult::Sent => dispatcher.dispatch_back(&on_success, uid),
+                UdpSendResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
+                UdpSendResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
+                UdpSendResult::Error(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+            },
+            MioEffectfulAction::UdpRecvFrom {
+                uid,
+                socket,
+                len,
+                on_success,
+                on_interrupted,
+                on_would_block,
+                on_error,
+            } => match self.udp_recv_from(&socket, len) {
+                UdpRecvResult::Received { peer_address, data } => {
+                    dispatcher.dispatch_back(&on_success, (uid, peer_address, data))
+                }
+                UdpRecvResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
+                UdpRecvResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
+                UdpRecvResult::Error(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+            },
+        }
+    }
+}
@@ -0,0 +1,107 @@
+pub mod driver;
+pub mod input;
+mod mio;
+mod time;
+
+pub use self::mio::NetworkConditions;
+
+use self::{
+    driver::{action::FuzzDriverAction, state::FuzzDriverState},
+    input::{FuzzInput, SharedInput},
+    mio::FuzzMioState,
+    time::{FuzzTimeState, SharedClock},
+};
+use crate::{
+    automaton::{
+        model::Effectful,
+        runner::{Runner, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::{TcpConfig, TcpState},
+            tcp_server::state::TcpServerState,
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, cell::RefCell, rc::Rc};
+
+// Fuzzing support.
+//
+// `fuzz_drive` interprets arbitrary bytes as a sequence of `TcpServerAction`s
+// (issued by the `FuzzDriverState` model), interleaved with the results of
+// the effects they trigger. Effect results are produced by fake `MioState`
+// and `TimeState` effectful models instead of the OS, while the pure models
+// run unmodified: any panic they reach (`unreachable!()`, `expect()`, ...)
+// is a crash reported by the fuzzer.
+//
+// The fake effectful models only return results the real ones could return,
+// and panic on the requests the real ones panic on (e.g. a write to an
+// unknown connection), so that crashes point at bugs in the pure models and
+// not in the harness. Once the input is exhausted, effects get benign
+// results and the driver halts the state-machine on its next tick.
+//
+// `fuzz_run_with` additionally simulates `NetworkConditions` (e.g. a peer
+// advertising a zero receive window for a while), for regression tests of
+// situations that are hard to reproduce with real sockets.
+//
+// A `cargo fuzz` target only needs to forward its input:
+//
+//     fuzz_target!(|data: &[u8]| node::fuzz::fuzz_drive(data));
+
+#[derive(ModelState, Debug)]
+pub struct FuzzNode {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub driver: FuzzDriverState,
+}
+
+impl FuzzNode {
+    pub fn new(input: SharedInput) -> Self {
+        Self {
+            time: TimeState::default(),
+            tcp: TcpState::from_config(TcpConfig {
+                register_retries: 2,
+                register_backoff: 10,
+                fd_exhaustion_backoff: 50,
+                writes_per_poll: None,
+                reuse_diagnostics: true,
+            }),
+            tcp_server: TcpServerState::new(),
+            driver: FuzzDriverState::new(input),
+        }
+    }
+}
+
+pub fn fuzz_drive(data: &[u8]) {
+    fuzz_run(data);
+}
+
+// Same as `fuzz_drive`, returns the halted runner for inspection.
+pub fn fuzz_run(data: &[u8]) -> Runner<FuzzNode> {
+    fuzz_run_with(data, NetworkConditions::default())
+}
+
+// Same as `fuzz_run`, under the given simulated network conditions.
+pub fn fuzz_run_with(data: &[u8], conditions: NetworkConditions) -> Runner<FuzzNode> {
+    let input = Rc::new(RefCell::new(FuzzInput::new(data)));
+    let clock = SharedClock::default();
+    let mut runner = RunnerBuilder::<FuzzNode>::new()
+        .register::<FuzzDriverState>()
+        // Replace the OS-backed effectful models registered by the dependencies
+        // of `FuzzDriverState`.
+        .model_effectful(Effectful(FuzzMioState::new(
+            input.clone(),
+            clock.clone(),
+            conditions,
+        )))
+        .model_effectful(Effectful(FuzzTimeState::new(input.clone(), clock)))
+        .instance(FuzzNode::new(input), || FuzzDriverAction::Tick.into())
+        .build();
+
+    runner.run();
+    runner
+}
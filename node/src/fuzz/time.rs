@@ -0,0 +1,38 @@
+use super::input::SharedInput;
+use crate::{
+    automaton::{action::Dispatcher, model::EffectfulModel},
+    models::effectful::time::action::TimeEffectfulAction,
+};
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+// Virtual clock shared by the fake effectful models.
+pub type SharedClock = Rc<Cell<Duration>>;
+
+// Fake `TimeState` (effectful): time starts at the UNIX epoch and each query
+// advances it by an input-defined number of milliseconds.
+pub struct FuzzTimeState {
+    input: SharedInput,
+    now: SharedClock,
+}
+
+impl FuzzTimeState {
+    pub fn new(input: SharedInput, now: SharedClock) -> Self {
+        Self { input, now }
+    }
+}
+
+impl EffectfulModel for FuzzTimeState {
+    type Action = TimeEffectfulAction;
+
+    fn process_effectful(&mut self, action: Self::Action, dispatcher: &mut Dispatcher) {
+        match action {
+            TimeEffectfulAction::GetSystemTime { uid, on_result } => {
+                let elapsed = self.input.borrow_mut().choose(256, 1);
+
+                self.now
+                    .set(self.now.get() + Duration::from_millis(elapsed as u64));
+                dispatcher.dispatch_back(&on_result, (uid, self.now.get()));
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 #![feature(generic_const_exprs)]
 pub mod automaton;
+pub mod fuzz;
 pub mod models;
 
 #[cfg(test)]
@@ -0,0 +1,20 @@
+use crate::automaton::{
+    action::{Action, ActionKind, Redispatch},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "236d1326-3839-43f5-beca-33d231cb7296"]
+pub enum ConfigEffectfulAction {
+    LoadFile {
+        uid: Uid,
+        path: String,
+        on_result: Redispatch<(Uid, Result<Vec<u8>, String>)>,
+    },
+}
+
+impl Action for ConfigEffectfulAction {
+    const KIND: ActionKind = ActionKind::Effectful;
+}
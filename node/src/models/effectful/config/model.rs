@@ -0,0 +1,44 @@
+use super::{action::ConfigEffectfulAction, state::ConfigLoaderState};
+use crate::automaton::{
+    action::Dispatcher,
+    model::{Effectful, EffectfulModel},
+    runner::{RegisterModel, RunnerBuilder},
+    state::ModelState,
+};
+use std::fs;
+
+// This is an `EffectfulModel` responsible for loading configuration (and
+// secrets, like the pnet key) from the filesystem.
+//
+// The `LoadFile` action reads the whole file at `path` and dispatches its
+// contents (or the error) back as a `PureAction` defined by the caller. Since
+// the result is dispatched back as a `PureAction`, it gets recorded, and a
+// replay reuses the recorded contents instead of reading the file again.
+
+impl RegisterModel for ConfigLoaderState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.model_effectful(Effectful::<Self>(Self()))
+    }
+}
+
+impl EffectfulModel for ConfigLoaderState {
+    type Action = ConfigEffectfulAction;
+
+    fn process_effectful(&mut self, action: Self::Action, dispatcher: &mut Dispatcher) {
+        match action {
+            ConfigEffectfulAction::LoadFile {
+                uid,
+                path,
+                on_result,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    Ok(Vec::new()) // Ignored
+                } else {
+                    fs::read(&path).map_err(|error| format!("{}: {}", path, error))
+                };
+
+                dispatcher.dispatch_back(&on_result, (uid, result));
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+// The effectful side of configuration loading, not to be confused with
+// `automaton::config::ConfigState`. It keeps no state: file contents are only
+// passed to the caller's `on_result`, so that they get recorded.
+pub struct ConfigLoaderState();
@@ -1,9 +1,9 @@
 use crate::automaton::{
-    action::{self, Action, ActionKind, Redispatch, Timeout},
+    action::{self, Action, ActionKind, RcSlice, Redispatch, Timeout},
     state::Uid,
 };
 use serde_derive::{Deserialize, Serialize};
-use std::rc::Rc;
+use std::{net::Shutdown, rc::Rc};
 use type_uuid::TypeUuid;
 
 // `MioAction` is an enum representing various I/O related operations
@@ -14,9 +14,13 @@ use type_uuid::TypeUuid;
 //
 // Operations include:
 // - Poll creation, registration, and deregistration.
-// - TCP server and connection management: listen, accept, connect, close.
+// - TCP server and connection management: listen, accept, connect, close,
+//   shutdown of either half of a connection.
 // - Data transmission over TCP: write, read.
-// - Miscellaneous: event creation, polling events, getting peer address.
+// - UDP sockets: bind, close, and sending/receiving datagrams.
+// - Miscellaneous: event creation, polling events, getting peer address,
+//   querying the OS send/recv buffer fill levels and the bytes readable
+//   without blocking.
 //
 // Note: `Uid` is used to uniquely identify instances of various Model-
 // specific objects like polls, connections, events etc.
@@ -38,6 +42,22 @@ pub enum MioEffectfulAction {
     PollRegisterTcpConnection {
         poll: Uid,       // created by PollCreate
         connection: Uid, // created by TcpAccept/TcpConnect
+        interest: Interest,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    // Changes the interest of a connection registered with
+    // PollRegisterTcpConnection.
+    PollReregisterTcpConnection {
+        poll: Uid,
+        connection: Uid,
+        interest: Interest,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    PollRegisterUdpSocket {
+        poll: Uid,   // created by PollCreate
+        socket: Uid, // created by UdpBind
         on_success: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
@@ -64,19 +84,34 @@ pub enum MioEffectfulAction {
     TcpListen {
         listener: Uid,
         address: String,
+        // Applied before binding, `None` keeps mio's defaults.
+        options: Option<SocketOptions>,
+        // Length of the queue of connections waiting to be accepted,
+        // `DEFAULT_BACKLOG` if `None`.
+        backlog: Option<i32>,
         on_success: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
+    TcpListenFromFd {
+        listener: Uid,
+        fd: i32, // inherited listening socket, owned by the listener once adopted
+        on_success: Redispatch<(Uid, String)>, // (listener, local address)
+        on_error: Redispatch<(Uid, String)>,
+    },
     TcpAccept {
         connection: Uid,
         listener: Uid, // created by TcpListen
-        on_success: Redispatch<Uid>,
+        on_success: Redispatch<(Uid, String, String)>, // (connection, local address, peer address)
         on_would_block: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
     TcpConnect {
         connection: Uid,
         address: String,
+        // Applied before connecting, `None` keeps mio's defaults.
+        options: Option<SocketOptions>,
+        // Local address the socket is bound to before connecting.
+        bind_address: Option<String>,
         on_success: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
@@ -90,11 +125,8 @@ pub enum MioEffectfulAction {
 
         // Strictly speaking, we should pass a copy here instead of referencing memory,
         // but the Rc guarantees immutability, allowing safe and efficient data sharing.
-        #[serde(
-            serialize_with = "action::serialize_rc_bytes",
-            deserialize_with = "action::deserialize_rc_bytes"
-        )]
-        data: Rc<[u8]>,
+        // Partial writes of a large buffer pass the remaining range, not a copy of it.
+        data: RcSlice,
         on_success: Redispatch<Uid>,
         on_success_partial: Redispatch<(Uid, usize)>,
         on_interrupted: Redispatch<Uid>,
@@ -113,7 +145,76 @@ pub enum MioEffectfulAction {
     },
     TcpGetPeerAddress {
         connection: Uid, // created by TcpAccept/TcpConnect
-        on_success: Redispatch<(Uid, String)>,
+        on_success: Redispatch<(Uid, String, String)>, // (connection, local address, peer address)
+        on_error: Redispatch<(Uid, String)>,
+    },
+    // Reports the local socket address of a listener (the port actually
+    // bound when listening on port 0) or of a connection.
+    TcpGetLocalAddress {
+        uid: Uid, // created by TcpListen or TcpAccept/TcpConnect
+        on_result: Redispatch<(Uid, Result<String, String>)>,
+    },
+    TcpGetBufferStatus {
+        uid: Uid,        // passed back to call-back action to identify the request
+        connection: Uid, // created by TcpAccept/TcpConnect
+        // (uid, bytes queued in the OS send buffer, bytes available in the OS recv buffer)
+        on_success: Redispatch<(Uid, usize, usize)>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    TcpBytesAvailable {
+        connection: Uid,                        // created by TcpAccept/TcpConnect
+        on_success: Redispatch<(Uid, usize)>,   // (connection, bytes readable without blocking)
+        on_not_supported: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    TcpShutdown {
+        connection: Uid, // created by TcpAccept/TcpConnect
+        how: ShutdownHow,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    TcpSetNodelay {
+        connection: Uid, // created by TcpAccept/TcpConnect
+        value: bool,     // TCP_NODELAY: true disables Nagle's algorithm
+        on_result: Redispatch<(Uid, Result<(), String>)>,
+    },
+    // Reports the TCP_CONGESTION setting (congestion control algorithm name)
+    // of a connection.
+    TcpGetCongestion {
+        connection: Uid, // created by TcpAccept/TcpConnect
+        on_result: Redispatch<(Uid, Result<String, String>)>,
+    },
+    UdpBind {
+        socket: Uid,
+        address: String,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    UdpClose {
+        socket: Uid, // created by UdpBind
+        on_success: Redispatch<Uid>,
+    },
+    UdpSend {
+        uid: Uid,        // passed back to call-back action to identify the request
+        socket: Uid,     // created by UdpBind
+        address: String, // destination
+        #[serde(
+            serialize_with = "action::serialize_rc_bytes",
+            deserialize_with = "action::deserialize_rc_bytes"
+        )]
+        data: Rc<[u8]>, // sent as a single datagram
+        on_success: Redispatch<Uid>,
+        on_interrupted: Redispatch<Uid>,
+        on_would_block: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    UdpRecvFrom {
+        uid: Uid,    // passed back to call-back action to identify the request
+        socket: Uid, // created by UdpBind
+        len: usize,  // max datagram size, the rest of a longer datagram is discarded
+        on_success: Redispatch<(Uid, String, Vec<u8>)>, // (uid, peer address, data)
+        on_interrupted: Redispatch<Uid>,
+        on_would_block: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
 }
@@ -122,6 +223,75 @@ impl Action for MioEffectfulAction {
     const KIND: ActionKind = ActionKind::Effectful;
 }
 
+// Serializable counterpart of `std::net::Shutdown`.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum ShutdownHow {
+    Read,
+    Write,
+    Both,
+}
+
+impl From<ShutdownHow> for Shutdown {
+    fn from(how: ShutdownHow) -> Self {
+        match how {
+            ShutdownHow::Read => Shutdown::Read,
+            ShutdownHow::Write => Shutdown::Write,
+            ShutdownHow::Both => Shutdown::Both,
+        }
+    }
+}
+
+// Serializable counterpart of `mio::Interest`: the readiness a connection is
+// polled for.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Debug)]
+pub enum Interest {
+    Readable,
+    Writable,
+    #[default]
+    Both,
+}
+
+impl From<Interest> for mio::Interest {
+    fn from(interest: Interest) -> Self {
+        match interest {
+            Interest::Readable => mio::Interest::READABLE,
+            Interest::Writable => mio::Interest::WRITABLE,
+            Interest::Both => mio::Interest::READABLE.add(mio::Interest::WRITABLE),
+        }
+    }
+}
+
+// The backlog mio listens with.
+pub const DEFAULT_BACKLOG: i32 = 1024;
+
+// Socket options applied by `TcpListen`/`TcpConnect` before the socket is
+// bound or connected. The defaults match mio's: Nagle's algorithm enabled,
+// `SO_REUSEADDR` set and the OS default buffer sizes. Options set on a
+// listener are inherited by the connections it accepts.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct SocketOptions {
+    pub nodelay: bool,
+    pub reuse_address: bool,
+    pub recv_buffer: Option<usize>,
+    pub send_buffer: Option<usize>,
+    // TCP_CONGESTION: name of the congestion control algorithm (e.g. "bbr",
+    // "cubic"), the system default if `None`. The algorithm's kernel module
+    // must be available.
+    pub congestion: Option<String>,
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self {
+            nodelay: false,
+            reuse_address: true,
+            recv_buffer: None,
+            send_buffer: None,
+            congestion: None,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub enum TcpWriteResult {
     WrittenAll,
@@ -140,9 +310,28 @@ pub enum TcpReadResult {
     Error(String),
 }
 
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum UdpSendResult {
+    Sent,
+    Interrupted,
+    WouldBlock,
+    Error(String),
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum UdpRecvResult {
+    Received { peer_address: String, data: Vec<u8> },
+    Interrupted,
+    WouldBlock,
+    Error(String),
+}
+
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub enum TcpAcceptResult {
-    Success,
+    Success {
+        local_address: String,
+        peer_address: String,
+    },
     WouldBlock,
     Error(String),
 }
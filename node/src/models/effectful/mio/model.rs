@@ -1,9 +1,10 @@
 use super::action::{
     MioEffectfulAction, PollResult, TcpAcceptResult, TcpReadResult, TcpWriteResult,
+    UdpRecvResult, UdpSendResult,
 };
-use super::state::MioState;
+use super::state::{MioState, OffloadedResult};
 use crate::automaton::action::Dispatcher;
-use crate::automaton::model::{Effectful, EffectfulModel};
+use crate::automaton::model::{effect_name, Effectful, EffectfulModel};
 use crate::automaton::runner::{RegisterModel, RunnerBuilder};
 use crate::automaton::state::ModelState;
 
@@ -16,13 +17,23 @@ use crate::automaton::state::ModelState;
 // - Registering/deregistering TCP servers and connections with poll objects.
 // - Polling events for asynchronous I/O notifications.
 // - Managing TCP connections, including listening for, accepting, and
-//   establishing connections, closing active connections, and reading/writing
-//   data over established TCP connections.
+//   establishing connections, closing active connections (or shutting down
+//   one of their halves), and reading/writing data over established TCP
+//   connections.
+// - Querying the fill levels of the OS send/recv buffers of a connection, and
+//   how many bytes can be read from it without blocking.
+// - Binding UDP sockets, and sending/receiving datagrams. The peer address of
+//   received datagrams is passed back as a `String`.
 //
 // Each of these operations corresponds to a variant in `MioAction`.
 // The `process_effectful` function handles these actions by invoking the
 // appropriate function in `MioState`, and dispatches the result back as a
 // caller-defined `PureAction`.
+//
+// When effects are offloaded (`RunnerBuilder::offload_effects`, except while
+// recording or replaying), reads and writes run on worker threads instead.
+// Their results are dispatched back, in order, before the next action that
+// isn't a read or write of another connection is processed.
 
 impl RegisterModel for MioState {
     fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
@@ -34,6 +45,18 @@ impl EffectfulModel for MioState {
     type Action = MioEffectfulAction;
 
     fn process_effectful(&mut self, action: Self::Action, dispatcher: &mut Dispatcher) {
+        let offloadable = match &action {
+            MioEffectfulAction::TcpWrite { connection, .. }
+            | MioEffectfulAction::TcpRead { connection, .. } => !self.is_offloaded(connection),
+            _ => false,
+        };
+
+        if !offloadable {
+            self.complete_offloaded(dispatcher);
+        } else if let Some(pool) = dispatcher.effect_pool() {
+            return self.tcp_offload(pool, action);
+        }
+
         match action {
             MioEffectfulAction::PollCreate {
                 poll,
@@ -74,13 +97,32 @@ impl EffectfulModel for MioState {
             MioEffectfulAction::PollRegisterTcpConnection {
                 poll,
                 connection,
+                interest,
+                on_success,
+                on_error,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    Ok(()) // Ignored
+                } else {
+                    self.poll_register_tcp_connection(&poll, connection, interest)
+                };
+
+                match result {
+                    Ok(_) => dispatcher.dispatch_back(&on_success, connection),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
+                }
+            }
+            MioEffectfulAction::PollReregisterTcpConnection {
+                poll,
+                connection,
+                interest,
                 on_success,
                 on_error,
             } => {
                 let result = if dispatcher.is_replayer() {
                     Ok(()) // Ignored
                 } else {
-                    self.poll_register_tcp_connection(&poll, connection)
+                    self.poll_reregister_tcp_connection(&poll, connection, interest)
                 };
 
                 match result {
@@ -88,6 +130,23 @@ impl EffectfulModel for MioState {
                     Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
                 }
             }
+            MioEffectfulAction::PollRegisterUdpSocket {
+                poll,
+                socket,
+                on_success,
+                on_error,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    Ok(()) // Ignored
+                } else {
+                    self.poll_register_udp_socket(&poll, socket)
+                };
+
+                match result {
+                    Ok(_) => dispatcher.dispatch_back(&on_success, socket),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (socket, error)),
+                }
+            }
             MioEffectfulAction::PollDeregisterTcpConnection {
                 poll,
                 connection,
@@ -141,13 +200,15 @@ impl EffectfulModel for MioState {
             MioEffectfulAction::TcpListen {
                 listener,
                 address,
+                options,
+                backlog,
                 on_success,
                 on_error,
             } => {
                 let result = if dispatcher.is_replayer() {
                     Ok(()) // Ignored
                 } else {
-                    self.tcp_listen(listener, address)
+                    self.tcp_listen(listener, address, options, backlog)
                 };
 
                 match result {
@@ -155,6 +216,23 @@ impl EffectfulModel for MioState {
                     Err(error) => dispatcher.dispatch_back(&on_error, (listener, error)),
                 }
             }
+            MioEffectfulAction::TcpListenFromFd {
+                listener,
+                fd,
+                on_success,
+                on_error,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    Ok(String::new()) // Ignored
+                } else {
+                    self.tcp_listen_from_fd(listener, fd)
+                };
+
+                match result {
+                    Ok(address) => dispatcher.dispatch_back(&on_success, (listener, address)),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (listener, error)),
+                }
+            }
             MioEffectfulAction::TcpAccept {
                 connection,
                 listener,
@@ -163,13 +241,21 @@ impl EffectfulModel for MioState {
                 on_error,
             } => {
                 let result = if dispatcher.is_replayer() {
-                    TcpAcceptResult::Success // Ignored
+                    // Ignored
+                    TcpAcceptResult::Success {
+                        local_address: String::new(),
+                        peer_address: String::new(),
+                    }
                 } else {
                     self.tcp_accept(connection, &listener)
                 };
 
                 match result {
-                    TcpAcceptResult::Success => dispatcher.dispatch_back(&on_success, connection),
+                    TcpAcceptResult::Success {
+                        local_address,
+                        peer_address,
+                    } => dispatcher
+                        .dispatch_back(&on_success, (connection, local_address, peer_address)),
                     TcpAcceptResult::WouldBlock => {
                         dispatcher.dispatch_back(&on_would_block, connection)
                     }
@@ -181,13 +267,15 @@ impl EffectfulModel for MioState {
             MioEffectfulAction::TcpConnect {
                 connection,
                 address,
+                options,
+                bind_address,
                 on_success,
                 on_error,
             } => {
                 let result = if dispatcher.is_replayer() {
                     Ok(()) // Ignored
                 } else {
-                    self.tcp_connect(connection, address)
+                    self.tcp_connect(connection, address, options, bind_address)
                 };
 
                 match result {
@@ -206,78 +294,295 @@ impl EffectfulModel for MioState {
                 dispatcher.dispatch_back(&on_success, connection);
             }
             MioEffectfulAction::TcpWrite {
-                uid,
-                connection: connection_uid,
-                data,
+                connection,
+                ref data,
+                ..
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    TcpWriteResult::WrittenAll // Ignored
+                } else {
+                    self.tcp_write(&connection, data)
+                };
+
+                dispatch_write_result(dispatcher, action, result)
+            }
+            MioEffectfulAction::TcpRead {
+                connection, len, ..
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    TcpReadResult::ReadAll(Vec::new()) // Ignored
+                } else {
+                    self.tcp_read(&connection, len)
+                };
+
+                dispatch_read_result(dispatcher, action, result)
+            }
+            MioEffectfulAction::TcpGetPeerAddress {
+                connection,
                 on_success,
-                on_success_partial,
-                on_interrupted,
-                on_would_block,
                 on_error,
             } => {
                 let result = if dispatcher.is_replayer() {
-                    TcpWriteResult::WrittenAll // Ignored
+                    Ok((String::new(), String::new())) // Ignored
                 } else {
-                    self.tcp_write(&connection_uid, &data)
+                    self.tcp_peer_address(&connection)
                 };
 
                 match result {
-                    TcpWriteResult::WrittenAll => dispatcher.dispatch_back(&on_success, uid),
-                    TcpWriteResult::WrittenPartial(count) => {
-                        dispatcher.dispatch_back(&on_success_partial, (uid, count))
-                    }
-                    TcpWriteResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
-                    TcpWriteResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
-                    TcpWriteResult::Error(error) => {
-                        dispatcher.dispatch_back(&on_error, (uid, error))
-                    }
+                    Ok((local_address, peer_address)) => dispatcher
+                        .dispatch_back(&on_success, (connection, local_address, peer_address)),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
                 }
             }
-            MioEffectfulAction::TcpRead {
+            MioEffectfulAction::TcpGetLocalAddress { uid, on_result } => {
+                let result = if dispatcher.is_replayer() {
+                    Ok(String::new()) // Ignored
+                } else {
+                    self.tcp_local_address(&uid)
+                };
+
+                dispatcher.dispatch_back(&on_result, (uid, result));
+            }
+            MioEffectfulAction::TcpGetBufferStatus {
                 uid,
                 connection,
-                len,
                 on_success,
-                on_success_partial,
-                on_interrupted,
-                on_would_block,
                 on_error,
             } => {
                 let result = if dispatcher.is_replayer() {
-                    TcpReadResult::ReadAll(Vec::new()) // Ignored
+                    Ok((0, 0)) // Ignored
                 } else {
-                    self.tcp_read(&connection, len)
+                    self.tcp_buffer_status(&connection)
                 };
+
                 match result {
-                    TcpReadResult::ReadAll(data) => {
-                        dispatcher.dispatch_back(&on_success, (uid, data))
-                    }
-                    TcpReadResult::ReadPartial(partial_data) => {
-                        dispatcher.dispatch_back(&on_success_partial, (uid, partial_data))
-                    }
-                    TcpReadResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
-                    TcpReadResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
-                    TcpReadResult::Error(error) => {
-                        dispatcher.dispatch_back(&on_error, (uid, error))
+                    Ok((send_queued, recv_available)) => {
+                        dispatcher.dispatch_back(&on_success, (uid, send_queued, recv_available))
                     }
+                    Err(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
                 }
             }
-            MioEffectfulAction::TcpGetPeerAddress {
+            MioEffectfulAction::TcpBytesAvailable {
                 connection,
                 on_success,
+                on_not_supported,
                 on_error,
             } => {
                 let result = if dispatcher.is_replayer() {
-                    Ok(String::new()) // Ignored
+                    Ok(Some(0)) // Ignored
                 } else {
-                    self.tcp_peer_address(&connection)
+                    self.tcp_bytes_available(&connection)
                 };
 
                 match result {
-                    Ok(address) => dispatcher.dispatch_back(&on_success, (connection, address)),
+                    Ok(Some(count)) => dispatcher.dispatch_back(&on_success, (connection, count)),
+                    Ok(None) => dispatcher.dispatch_back(&on_not_supported, connection),
                     Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
                 }
             }
+            MioEffectfulAction::TcpShutdown {
+                connection,
+                how,
+                on_success,
+                on_error,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    Ok(()) // Ignored
+                } else {
+                    self.tcp_shutdown(&connection, how)
+                };
+
+                match result {
+                    Ok(()) => dispatcher.dispatch_back(&on_success, connection),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (connection, error)),
+                }
+            }
+            MioEffectfulAction::TcpSetNodelay {
+                connection,
+                value,
+                on_result,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    Ok(()) // Ignored
+                } else {
+                    self.tcp_set_nodelay(&connection, value)
+                };
+
+                dispatcher.dispatch_back(&on_result, (connection, result));
+            }
+            MioEffectfulAction::TcpGetCongestion {
+                connection,
+                on_result,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    Ok(String::new()) // Ignored
+                } else {
+                    self.tcp_get_congestion(&connection)
+                };
+
+                dispatcher.dispatch_back(&on_result, (connection, result));
+            }
+            MioEffectfulAction::UdpBind {
+                socket,
+                address,
+                on_success,
+                on_error,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    Ok(()) // Ignored
+                } else {
+                    self.udp_bind(socket, address)
+                };
+
+                match result {
+                    Ok(_) => dispatcher.dispatch_back(&on_success, socket),
+                    Err(error) => dispatcher.dispatch_back(&on_error, (socket, error)),
+                }
+            }
+            MioEffectfulAction::UdpClose { socket, on_success } => {
+                if !dispatcher.is_replayer() {
+                    self.udp_close(&socket);
+                }
+
+                dispatcher.dispatch_back(&on_success, socket);
+            }
+            MioEffectfulAction::UdpSend {
+                uid,
+                socket,
+                address,
+                data,
+                on_success,
+                on_interrupted,
+                on_would_block,
+                on_error,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    UdpSendResult::Sent // Ignored
+                } else {
+                    self.udp_send(&socket, &address, &data)
+                };
+
+                match result {
+                    UdpSendResult::Sent => dispatcher.dispatch_back(&on_success, uid),
+                    UdpSendResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
+                    UdpSendResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
+                    UdpSendResult::Error(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+                }
+            }
+            MioEffectfulAction::UdpRecvFrom {
+                uid,
+                socket,
+                len,
+                on_success,
+                on_interrupted,
+                on_would_block,
+                on_error,
+            } => {
+                let result = if dispatcher.is_replayer() {
+                    // Ignored
+                    UdpRecvResult::Received {
+                        peer_address: String::new(),
+                        data: Vec::new(),
+                    }
+                } else {
+                    self.udp_recv_from(&socket, len)
+                };
+
+                match result {
+                    UdpRecvResult::Received { peer_address, data } => {
+                        dispatcher.dispatch_back(&on_success, (uid, peer_address, data))
+                    }
+                    UdpRecvResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
+                    UdpRecvResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
+                    UdpRecvResult::Error(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+                }
+            }
+        }
+    }
+
+    fn on_shutdown(&mut self) {
+        self.shutdown()
+    }
+}
+
+impl MioState {
+    fn complete_offloaded(&mut self, dispatcher: &mut Dispatcher) {
+        // Not `effect_pool()`: operations offloaded before the recording was
+        // opened must still complete.
+        let Some(pool) = dispatcher.effect_pool.as_mut() else {
+            return;
+        };
+        let results = self.take_offloaded(pool);
+        let effect = dispatcher.effect.take();
+
+        for (action, result) in results {
+            // Tag the result with the action it belongs to, not the one being handled.
+            dispatcher.effect = Some(effect_name(&action));
+
+            match result {
+                OffloadedResult::Write(result) => dispatch_write_result(dispatcher, action, result),
+                OffloadedResult::Read(result) => dispatch_read_result(dispatcher, action, result),
+            }
+        }
+
+        dispatcher.effect = effect;
+    }
+}
+
+fn dispatch_write_result(
+    dispatcher: &mut Dispatcher,
+    action: MioEffectfulAction,
+    result: TcpWriteResult,
+) {
+    let MioEffectfulAction::TcpWrite {
+        uid,
+        on_success,
+        on_success_partial,
+        on_interrupted,
+        on_would_block,
+        on_error,
+        ..
+    } = action
+    else {
+        unreachable!()
+    };
+
+    match result {
+        TcpWriteResult::WrittenAll => dispatcher.dispatch_back(&on_success, uid),
+        TcpWriteResult::WrittenPartial(count) => {
+            dispatcher.dispatch_back(&on_success_partial, (uid, count))
+        }
+        TcpWriteResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
+        TcpWriteResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
+        TcpWriteResult::Error(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+    }
+}
+
+fn dispatch_read_result(
+    dispatcher: &mut Dispatcher,
+    action: MioEffectfulAction,
+    result: TcpReadResult,
+) {
+    let MioEffectfulAction::TcpRead {
+        uid,
+        on_success,
+        on_success_partial,
+        on_interrupted,
+        on_would_block,
+        on_error,
+        ..
+    } = action
+    else {
+        unreachable!()
+    };
+
+    match result {
+        TcpReadResult::ReadAll(data) => dispatcher.dispatch_back(&on_success, (uid, data)),
+        TcpReadResult::ReadPartial(partial_data) => {
+            dispatcher.dispatch_back(&on_success_partial, (uid, partial_data))
         }
+        TcpReadResult::Interrupted => dispatcher.dispatch_back(&on_interrupted, uid),
+        TcpReadResult::WouldBlock => dispatcher.dispatch_back(&on_would_block, uid),
+        TcpReadResult::Error(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
     }
 }
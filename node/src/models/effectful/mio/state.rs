@@ -1,17 +1,41 @@
-use super::action::{MioEvent, PollResult, TcpAcceptResult, TcpReadResult, TcpWriteResult};
+use super::action::{
+    MioEffectfulAction, MioEvent, PollResult, ShutdownHow, SocketOptions, TcpAcceptResult,
+    TcpReadResult, TcpWriteResult, UdpRecvResult, UdpSendResult, DEFAULT_BACKLOG,
+};
 use crate::automaton::action::Timeout;
+use crate::automaton::offload::EffectPool;
 use crate::automaton::state::{Objects, Uid};
-use mio::net::{TcpListener, TcpStream};
+use mio::net::{TcpListener, TcpStream, UdpSocket};
 use mio::{Events, Interest, Poll, Token};
+use socket2::{Domain, Protocol, Socket, Type};
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::io::{self, Read, Write};
+use std::mem;
+use std::net::{AddrParseError, SocketAddr};
 use std::time::Duration;
 
+// A `TcpWrite` or `TcpRead` action running on an `EffectPool` worker. The
+// connection's stream is moved to the worker until the operation completes.
+struct OffloadedOp {
+    seq: u64,
+    connection: Uid,
+    action: MioEffectfulAction,
+}
+
+pub enum OffloadedResult {
+    Write(TcpWriteResult),
+    Read(TcpReadResult),
+}
+
 pub struct MioState {
     poll_objects: RefCell<Objects<Poll>>,
     events_objects: RefCell<Objects<Events>>,
     tcp_listener_objects: RefCell<Objects<TcpListener>>,
     tcp_connection_objects: RefCell<Objects<TcpStream>>,
+    udp_socket_objects: RefCell<Objects<UdpSocket>>,
+    // In submission order.
+    offloaded_ops: VecDeque<OffloadedOp>,
 }
 
 impl MioState {
@@ -21,9 +45,21 @@ impl MioState {
             events_objects: RefCell::new(Objects::<Events>::new()),
             tcp_listener_objects: RefCell::new(Objects::<TcpListener>::new()),
             tcp_connection_objects: RefCell::new(Objects::<TcpStream>::new()),
+            udp_socket_objects: RefCell::new(Objects::<UdpSocket>::new()),
+            offloaded_ops: VecDeque::new(),
         }
     }
 
+    // Drops all OS objects: sockets first, then the polls they were
+    // registered with.
+    pub fn shutdown(&mut self) {
+        self.tcp_connection_objects.borrow_mut().clear();
+        self.tcp_listener_objects.borrow_mut().clear();
+        self.udp_socket_objects.borrow_mut().clear();
+        self.events_objects.borrow_mut().clear();
+        self.poll_objects.borrow_mut().clear();
+    }
+
     fn new_poll(&mut self, uid: Uid, obj: Poll) {
         if self.poll_objects.borrow_mut().insert(uid, obj).is_some() {
             panic!("Attempt to re-use existing {:?}", uid)
@@ -58,6 +94,17 @@ impl MioState {
         }
     }
 
+    fn new_udp_socket(&mut self, uid: Uid, obj: UdpSocket) {
+        if self
+            .udp_socket_objects
+            .borrow_mut()
+            .insert(uid, obj)
+            .is_some()
+        {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
     pub fn poll_create(&mut self, uid: Uid) -> Result<(), String> {
         match Poll::new() {
             Ok(poll_obj) => {
@@ -96,12 +143,56 @@ impl MioState {
         &mut self,
         poll: &Uid,
         connection: Uid,
+        interest: super::action::Interest,
     ) -> Result<(), String> {
         let mut tcp_connection_objects = self.tcp_connection_objects.borrow_mut();
         let stream = tcp_connection_objects
             .get_mut(&connection)
             .expect(&format!("TcpConnection object not found {:?}", connection));
 
+        match self
+            .poll_objects
+            .borrow()
+            .get(poll)
+            .expect(&format!("Poll object not found {:?}", poll))
+            .registry()
+            .register(stream, Token(connection.into()), interest.into())
+        {
+            Ok(_) => Ok(()),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    pub fn poll_reregister_tcp_connection(
+        &mut self,
+        poll: &Uid,
+        connection: Uid,
+        interest: super::action::Interest,
+    ) -> Result<(), String> {
+        let mut tcp_connection_objects = self.tcp_connection_objects.borrow_mut();
+        let stream = tcp_connection_objects
+            .get_mut(&connection)
+            .unwrap_or_else(|| panic!("TcpConnection object not found {:?}", connection));
+
+        match self
+            .poll_objects
+            .borrow()
+            .get(poll)
+            .unwrap_or_else(|| panic!("Poll object not found {:?}", poll))
+            .registry()
+            .reregister(stream, Token(connection.into()), interest.into())
+        {
+            Ok(_) => Ok(()),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    pub fn poll_register_udp_socket(&mut self, poll: &Uid, socket: Uid) -> Result<(), String> {
+        let mut udp_socket_objects = self.udp_socket_objects.borrow_mut();
+        let udp_socket = udp_socket_objects
+            .get_mut(&socket)
+            .expect(&format!("UdpSocket object not found {:?}", socket));
+
         match self
             .poll_objects
             .borrow()
@@ -109,8 +200,8 @@ impl MioState {
             .expect(&format!("Poll object not found {:?}", poll))
             .registry()
             .register(
-                stream,
-                Token(connection.into()),
+                udp_socket,
+                Token(socket.into()),
                 Interest::READABLE.add(Interest::WRITABLE),
             ) {
             Ok(_) => Ok(()),
@@ -148,7 +239,7 @@ impl MioState {
             .expect(&format!("Events object not found {:?}", events));
 
         let timeout = match timeout {
-            Timeout::Millis(ms) => Some(Duration::from_millis(ms)),
+            Timeout::Millis(ms) | Timeout::Inactivity(ms) => Some(Duration::from_millis(ms)),
             Timeout::Never => None,
         };
 
@@ -186,17 +277,36 @@ impl MioState {
         self.new_events(uid, Events::with_capacity(capacity));
     }
 
-    pub fn tcp_listen(&mut self, uid: Uid, address: String) -> Result<(), String> {
-        match address.parse() {
-            Ok(address) => match TcpListener::bind(address) {
-                Ok(tcp_listener) => {
-                    self.new_tcp_listener(uid, tcp_listener);
-                    Ok(())
-                }
-                Err(error) => Err(error.to_string()),
-            },
-            Err(error) => Err(error.to_string()),
-        }
+    pub fn tcp_listen(
+        &mut self,
+        uid: Uid,
+        address: String,
+        options: Option<SocketOptions>,
+        backlog: Option<i32>,
+    ) -> Result<(), String> {
+        let address = address
+            .parse::<SocketAddr>()
+            .map_err(|error| error.to_string())?;
+        let tcp_listener = tcp_listen_with(
+            address,
+            &options.unwrap_or_default(),
+            backlog.unwrap_or(DEFAULT_BACKLOG),
+        )
+        .map_err(|error| error.to_string())?;
+
+        self.new_tcp_listener(uid, tcp_listener);
+        Ok(())
+    }
+
+    // Returns the local address of the adopted listener.
+    pub fn tcp_listen_from_fd(&mut self, uid: Uid, fd: i32) -> Result<String, String> {
+        let tcp_listener = listener_from_fd(fd).map_err(|error| error.to_string())?;
+        let address = tcp_listener
+            .local_addr()
+            .map_err(|error| error.to_string())?;
+
+        self.new_tcp_listener(uid, tcp_listener);
+        Ok(address.to_string())
     }
 
     pub fn tcp_accept(&mut self, connection: Uid, listener: &Uid) -> TcpAcceptResult {
@@ -210,10 +320,16 @@ impl MioState {
         };
 
         match accept_result {
-            Ok((stream, _address)) => {
-                self.new_tcp_connection(connection, stream);
-                TcpAcceptResult::Success
-            }
+            Ok((stream, address)) => match stream.local_addr() {
+                Ok(local_address) => {
+                    self.new_tcp_connection(connection, stream);
+                    TcpAcceptResult::Success {
+                        local_address: local_address.to_string(),
+                        peer_address: address.to_string(),
+                    }
+                }
+                Err(error) => TcpAcceptResult::Error(error.to_string()),
+            },
 
             Err(error) => {
                 if error.kind() == std::io::ErrorKind::WouldBlock {
@@ -225,15 +341,32 @@ impl MioState {
         }
     }
 
-    pub fn tcp_connect(&mut self, connection: Uid, address: String) -> Result<(), String> {
-        match address.parse() {
-            Ok(address) => match TcpStream::connect(address) {
-                Ok(stream) => {
-                    self.new_tcp_connection(connection, stream);
-                    Ok(())
-                }
-                Err(error) => Err(error.to_string()),
-            },
+    pub fn tcp_connect(
+        &mut self,
+        connection: Uid,
+        address: String,
+        options: Option<SocketOptions>,
+        bind_address: Option<String>,
+    ) -> Result<(), String> {
+        let address = address
+            .parse()
+            .map_err(|error: AddrParseError| error.to_string())?;
+        let bind_address = bind_address
+            .map(|bind_address| bind_address.parse())
+            .transpose()
+            .map_err(|error: AddrParseError| error.to_string())?;
+        let result = match (options, bind_address) {
+            (None, None) => TcpStream::connect(address),
+            (options, bind_address) => {
+                tcp_connect_with(address, &options.unwrap_or_default(), bind_address)
+            }
+        };
+
+        match result {
+            Ok(stream) => {
+                self.new_tcp_connection(connection, stream);
+                Ok(())
+            }
             Err(error) => Err(error.to_string()),
         }
     }
@@ -248,6 +381,48 @@ impl MioState {
         // implict stream drop
     }
 
+    pub fn tcp_shutdown(&mut self, connection: &Uid, how: ShutdownHow) -> Result<(), String> {
+        let tcp_connection_objects = self.tcp_connection_objects.borrow();
+        let stream = tcp_connection_objects.get(connection).expect(&format!(
+            "TCP connection stream object not found {:?}",
+            connection
+        ));
+
+        stream
+            .shutdown(how.into())
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn tcp_set_nodelay(&mut self, connection: &Uid, value: bool) -> Result<(), String> {
+        let tcp_connection_objects = self.tcp_connection_objects.borrow();
+        let stream = tcp_connection_objects.get(connection).expect(&format!(
+            "TCP connection stream object not found {:?}",
+            connection
+        ));
+
+        stream.set_nodelay(value).map_err(|error| error.to_string())
+    }
+
+    pub fn tcp_get_congestion(&mut self, connection: &Uid) -> Result<String, String> {
+        let tcp_connection_objects = self.tcp_connection_objects.borrow();
+        let stream = tcp_connection_objects
+            .get(connection)
+            .unwrap_or_else(|| panic!("TCP connection stream object not found {:?}", connection));
+
+        get_congestion(stream).map_err(|error| error.to_string())
+    }
+
+    // Reads back the options applied to the connection's socket.
+    #[cfg(test)]
+    pub fn tcp_socket_options(&self, connection: &Uid) -> Result<SocketOptions, String> {
+        let tcp_connection_objects = self.tcp_connection_objects.borrow();
+        let stream = tcp_connection_objects
+            .get(connection)
+            .unwrap_or_else(|| panic!("TCP connection stream object not found {:?}", connection));
+
+        get_socket_options(stream).map_err(|error| error.to_string())
+    }
+
     pub fn tcp_write(&mut self, connection: &Uid, data: &[u8]) -> TcpWriteResult {
         let mut tcp_connection_objects = self.tcp_connection_objects.borrow_mut();
         let stream = tcp_connection_objects.get_mut(connection).expect(&format!(
@@ -255,61 +430,448 @@ impl MioState {
             connection
         ));
 
-        match stream.write(data) {
-            Ok(written) => {
-                if written < data.len() {
-                    TcpWriteResult::WrittenPartial(written)
-                } else {
-                    TcpWriteResult::WrittenAll
-                }
-            }
-            Err(error) => match error.kind() {
-                io::ErrorKind::Interrupted => TcpWriteResult::Interrupted,
-                io::ErrorKind::WouldBlock => TcpWriteResult::WouldBlock,
-                _ => TcpWriteResult::Error(error.to_string()),
-            },
-        }
+        stream_write(stream, data)
     }
 
     pub fn tcp_read(&mut self, connection: &Uid, len: usize) -> TcpReadResult {
-        assert_ne!(len, 0);
-
         let mut tcp_connection_objects = self.tcp_connection_objects.borrow_mut();
         let stream = tcp_connection_objects.get_mut(connection).expect(&format!(
             "TCP connection stream object not found {:?}",
             connection
         ));
 
+        stream_read(stream, len)
+    }
+
+    // Runs a `TcpWrite` or `TcpRead` action on a `pool` worker. Its result is
+    // returned by `take_offloaded()`.
+    pub fn tcp_offload(&mut self, pool: &mut EffectPool, action: MioEffectfulAction) {
+        let connection = match &action {
+            MioEffectfulAction::TcpWrite { connection, .. }
+            | MioEffectfulAction::TcpRead { connection, .. } => *connection,
+            _ => unreachable!(),
+        };
+        let mut stream = self
+            .tcp_connection_objects
+            .borrow_mut()
+            .remove(&connection)
+            .expect(&format!(
+                "TCP connection stream object not found {:?}",
+                connection
+            ));
+
+        let seq = match &action {
+            MioEffectfulAction::TcpWrite { data, .. } => {
+                let data = data.to_vec();
+
+                pool.submit(move || {
+                    let result = stream_write(&mut stream, &data);
+                    (stream, OffloadedResult::Write(result))
+                })
+            }
+            &MioEffectfulAction::TcpRead { len, .. } => pool.submit(move || {
+                let result = stream_read(&mut stream, len);
+                (stream, OffloadedResult::Read(result))
+            }),
+            _ => unreachable!(),
+        };
+
+        self.offloaded_ops.push_back(OffloadedOp {
+            seq,
+            connection,
+            action,
+        });
+    }
+
+    pub fn is_offloaded(&self, connection: &Uid) -> bool {
+        self.offloaded_ops
+            .iter()
+            .any(|op| op.connection == *connection)
+    }
+
+    // Waits for all the offloaded actions and returns their results, in the
+    // order they were offloaded.
+    pub fn take_offloaded(
+        &mut self,
+        pool: &mut EffectPool,
+    ) -> Vec<(MioEffectfulAction, OffloadedResult)> {
+        mem::take(&mut self.offloaded_ops)
+            .into_iter()
+            .map(|op| {
+                let (stream, result) = pool.wait::<(TcpStream, OffloadedResult)>(op.seq);
+
+                self.new_tcp_connection(op.connection, stream);
+                (op.action, result)
+            })
+            .collect()
+    }
+
+    pub fn udp_bind(&mut self, socket: Uid, address: String) -> Result<(), String> {
+        match address.parse() {
+            Ok(address) => match UdpSocket::bind(address) {
+                Ok(udp_socket) => {
+                    self.new_udp_socket(socket, udp_socket);
+                    Ok(())
+                }
+                Err(error) => Err(error.to_string()),
+            },
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    pub fn udp_close(&mut self, socket: &Uid) {
+        self.udp_socket_objects
+            .borrow_mut()
+            .remove(socket)
+            .expect(&format!("UdpSocket object not found {:?}", socket));
+    }
+
+    pub fn udp_send(&mut self, socket: &Uid, address: &str, data: &[u8]) -> UdpSendResult {
+        let address = match address.parse() {
+            Ok(address) => address,
+            Err(error) => return UdpSendResult::Error(format!("{}: {}", address, error)),
+        };
+        let udp_socket_objects = self.udp_socket_objects.borrow();
+        let udp_socket = udp_socket_objects
+            .get(socket)
+            .expect(&format!("UdpSocket object not found {:?}", socket));
+
+        match udp_socket.send_to(data, address) {
+            Ok(_) => UdpSendResult::Sent,
+            Err(error) => match error.kind() {
+                io::ErrorKind::Interrupted => UdpSendResult::Interrupted,
+                io::ErrorKind::WouldBlock => UdpSendResult::WouldBlock,
+                _ => UdpSendResult::Error(error.to_string()),
+            },
+        }
+    }
+
+    pub fn udp_recv_from(&mut self, socket: &Uid, len: usize) -> UdpRecvResult {
+        assert_ne!(len, 0);
+
+        let udp_socket_objects = self.udp_socket_objects.borrow();
+        let udp_socket = udp_socket_objects
+            .get(socket)
+            .expect(&format!("UdpSocket object not found {:?}", socket));
         let mut recv_buf = vec![0u8; len];
 
-        match stream.read(&mut recv_buf) {
-            Ok(read) if read > 0 => {
-                if read < len {
-                    recv_buf.truncate(read);
-                    TcpReadResult::ReadPartial(recv_buf)
-                } else {
-                    TcpReadResult::ReadAll(recv_buf)
+        match udp_socket.recv_from(&mut recv_buf) {
+            Ok((read, peer_address)) => {
+                recv_buf.truncate(read);
+                UdpRecvResult::Received {
+                    peer_address: peer_address.to_string(),
+                    data: recv_buf,
                 }
             }
-            Ok(_) => TcpReadResult::Error("Connection closed".to_string()),
             Err(error) => match error.kind() {
-                io::ErrorKind::Interrupted => TcpReadResult::Interrupted,
-                io::ErrorKind::WouldBlock => TcpReadResult::WouldBlock,
-                _ => TcpReadResult::Error(error.to_string()),
+                io::ErrorKind::Interrupted => UdpRecvResult::Interrupted,
+                io::ErrorKind::WouldBlock => UdpRecvResult::WouldBlock,
+                _ => UdpRecvResult::Error(error.to_string()),
             },
         }
     }
 
-    pub fn tcp_peer_address(&mut self, connection: &Uid) -> Result<String, String> {
+    // Returns the (local, peer) addresses of the connection. Fails if the
+    // connection is not established yet.
+    pub fn tcp_peer_address(&mut self, connection: &Uid) -> Result<(String, String), String> {
+        let tcp_connection_objects = self.tcp_connection_objects.borrow();
+        let stream = tcp_connection_objects.get(connection).expect(&format!(
+            "TCP connection stream object not found {:?}",
+            connection
+        ));
+
+        match (stream.local_addr(), stream.peer_addr()) {
+            (Ok(local_addr), Ok(peer_addr)) => Ok((local_addr.to_string(), peer_addr.to_string())),
+            (Err(err), _) | (_, Err(err)) => Err(err.to_string()),
+        }
+    }
+
+    // Returns the local address of a listener or a connection.
+    pub fn tcp_local_address(&mut self, uid: &Uid) -> Result<String, String> {
+        let local_addr = match self.tcp_listener_objects.borrow().get(uid) {
+            Some(listener) => listener.local_addr(),
+            None => self
+                .tcp_connection_objects
+                .borrow()
+                .get(uid)
+                .unwrap_or_else(|| panic!("TCP listener or connection object not found {:?}", uid))
+                .local_addr(),
+        };
+
+        local_addr
+            .map(|address| address.to_string())
+            .map_err(|error| error.to_string())
+    }
+
+    // Returns the number of bytes queued in the OS send buffer (not yet
+    // acknowledged by the peer) and the number of bytes available for reading
+    // in the OS recv buffer.
+    pub fn tcp_buffer_status(&mut self, connection: &Uid) -> Result<(usize, usize), String> {
+        let tcp_connection_objects = self.tcp_connection_objects.borrow();
+        let stream = tcp_connection_objects.get(connection).expect(&format!(
+            "TCP connection stream object not found {:?}",
+            connection
+        ));
+
+        socket_buffer_status(stream)
+    }
+
+    // `None` if the platform can't tell without reading.
+    pub fn tcp_bytes_available(&mut self, connection: &Uid) -> Result<Option<usize>, String> {
         let tcp_connection_objects = self.tcp_connection_objects.borrow();
         let stream = tcp_connection_objects.get(connection).expect(&format!(
             "TCP connection stream object not found {:?}",
             connection
         ));
 
-        match stream.peer_addr() {
-            Ok(addr) => Ok(addr.to_string()),
-            Err(err) => Err(err.to_string()),
+        socket_bytes_available(stream)
+    }
+}
+
+fn stream_write(stream: &mut TcpStream, data: &[u8]) -> TcpWriteResult {
+    match stream.write(data) {
+        Ok(written) => {
+            if written < data.len() {
+                TcpWriteResult::WrittenPartial(written)
+            } else {
+                TcpWriteResult::WrittenAll
+            }
         }
+        Err(error) => match error.kind() {
+            io::ErrorKind::Interrupted => TcpWriteResult::Interrupted,
+            io::ErrorKind::WouldBlock => TcpWriteResult::WouldBlock,
+            _ => TcpWriteResult::Error(error.to_string()),
+        },
+    }
+}
+
+fn stream_read(stream: &mut TcpStream, len: usize) -> TcpReadResult {
+    assert_ne!(len, 0);
+
+    let mut recv_buf = vec![0u8; len];
+
+    match stream.read(&mut recv_buf) {
+        Ok(read) if read > 0 => {
+            if read < len {
+                recv_buf.truncate(read);
+                TcpReadResult::ReadPartial(recv_buf)
+            } else {
+                TcpReadResult::ReadAll(recv_buf)
+            }
+        }
+        Ok(_) => TcpReadResult::Error("Connection closed".to_string()),
+        Err(error) => match error.kind() {
+            io::ErrorKind::Interrupted => TcpReadResult::Interrupted,
+            io::ErrorKind::WouldBlock => TcpReadResult::WouldBlock,
+            _ => TcpReadResult::Error(error.to_string()),
+        },
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn socket_buffer_status(stream: &TcpStream) -> Result<(usize, usize), String> {
+    use std::os::fd::AsRawFd;
+
+    let fd = stream.as_raw_fd();
+    let mut send_queued: libc::c_int = 0;
+    let mut recv_available: libc::c_int = 0;
+
+    // SAFETY: `fd` is a valid socket owned by `stream` and both ioctls write a
+    // single `c_int` to the provided pointer.
+    if unsafe { libc::ioctl(fd, libc::TIOCOUTQ, &mut send_queued) } < 0 {
+        return Err(io::Error::last_os_error().to_string());
+    }
+
+    if unsafe { libc::ioctl(fd, libc::FIONREAD, &mut recv_available) } < 0 {
+        return Err(io::Error::last_os_error().to_string());
+    }
+
+    Ok((send_queued as usize, recv_available as usize))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn socket_buffer_status(_stream: &TcpStream) -> Result<(usize, usize), String> {
+    Err("Socket buffer status is not supported on this platform".to_string())
+}
+
+#[cfg(unix)]
+fn socket_bytes_available(stream: &TcpStream) -> Result<Option<usize>, String> {
+    use std::os::fd::AsRawFd;
+
+    let mut available: libc::c_int = 0;
+
+    // SAFETY: the fd is a valid socket owned by `stream` and the ioctl writes
+    // a single `c_int` to the provided pointer.
+    if unsafe { libc::ioctl(stream.as_raw_fd(), libc::FIONREAD, &mut available) } < 0 {
+        return Err(io::Error::last_os_error().to_string());
+    }
+
+    Ok(Some(available as usize))
+}
+
+#[cfg(not(unix))]
+fn socket_bytes_available(_stream: &TcpStream) -> Result<Option<usize>, String> {
+    Ok(None)
+}
+
+#[cfg(unix)]
+fn listener_from_fd(fd: i32) -> io::Result<TcpListener> {
+    use std::os::fd::FromRawFd;
+
+    if fd < 0 {
+        return Err(io::Error::from_raw_os_error(libc::EBADF));
+    }
+
+    // SAFETY: the caller hands over `fd`, an open listening socket that
+    // nothing else owns.
+    let tcp_listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+
+    tcp_listener.set_nonblocking(true)?;
+    Ok(TcpListener::from_std(tcp_listener))
+}
+
+#[cfg(not(unix))]
+fn listener_from_fd(_fd: i32) -> io::Result<TcpListener> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Adopting a listener file descriptor is not supported on this platform",
+    ))
+}
+
+// Same as mio's `TcpListener::bind`, with `options` applied before binding.
+fn tcp_listen_with(
+    address: SocketAddr,
+    options: &SocketOptions,
+    backlog: i32,
+) -> io::Result<TcpListener> {
+    let socket = new_socket(&address, options)?;
+
+    socket.bind(&address.into())?;
+    socket.listen(backlog)?;
+    Ok(TcpListener::from_std(socket.into()))
+}
+
+// Same as mio's `TcpStream::connect`, with `options` applied before
+// connecting, and the socket bound to `bind_address` if set.
+fn tcp_connect_with(
+    address: SocketAddr,
+    options: &SocketOptions,
+    bind_address: Option<SocketAddr>,
+) -> io::Result<TcpStream> {
+    let socket = new_socket(&address, options)?;
+
+    if let Some(bind_address) = bind_address {
+        socket.bind(&bind_address.into())?;
+    }
+
+    match socket.connect(&address.into()) {
+        Ok(()) => (),
+        // Non-blocking socket, completion is signaled by a writable event.
+        Err(error) if connect_in_progress(&error) => (),
+        Err(error) => return Err(error),
+    }
+
+    Ok(TcpStream::from_std(socket.into()))
+}
+
+#[cfg(unix)]
+fn connect_in_progress(error: &io::Error) -> bool {
+    error.raw_os_error() == Some(libc::EINPROGRESS)
+}
+
+#[cfg(not(unix))]
+fn connect_in_progress(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::WouldBlock
+}
+
+fn new_socket(address: &SocketAddr, options: &SocketOptions) -> io::Result<Socket> {
+    let socket = Socket::new(
+        Domain::for_address(*address),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+
+    socket.set_nonblocking(true)?;
+    socket.set_reuse_address(options.reuse_address)?;
+    socket.set_nodelay(options.nodelay)?;
+
+    if let Some(size) = options.recv_buffer {
+        socket.set_recv_buffer_size(size)?;
     }
+
+    if let Some(size) = options.send_buffer {
+        socket.set_send_buffer_size(size)?;
+    }
+
+    if let Some(algorithm) = &options.congestion {
+        set_congestion(&socket, algorithm)?;
+    }
+
+    Ok(socket)
+}
+
+#[cfg(target_os = "linux")]
+fn set_congestion(socket: &Socket, algorithm: &str) -> io::Result<()> {
+    socket.set_tcp_congestion(algorithm.as_bytes())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_congestion(_socket: &Socket, _algorithm: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "TCP_CONGESTION is not supported on this platform",
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn get_congestion(stream: &TcpStream) -> io::Result<String> {
+    use socket2::SockRef;
+
+    let name = SockRef::from(&borrow_fd(stream)).tcp_congestion()?;
+    let end = name
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(name.len());
+
+    Ok(String::from_utf8_lossy(&name[..end]).into_owned())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_congestion(_stream: &TcpStream) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "TCP_CONGESTION is not supported on this platform",
+    ))
+}
+
+#[cfg(all(test, unix))]
+fn get_socket_options(stream: &TcpStream) -> io::Result<SocketOptions> {
+    use socket2::SockRef;
+
+    let fd = borrow_fd(stream);
+    let socket = SockRef::from(&fd);
+
+    Ok(SocketOptions {
+        nodelay: socket.nodelay()?,
+        reuse_address: socket.reuse_address()?,
+        recv_buffer: Some(socket.recv_buffer_size()?),
+        send_buffer: Some(socket.send_buffer_size()?),
+        congestion: get_congestion(stream).ok(),
+    })
+}
+
+#[cfg(all(test, not(unix)))]
+fn get_socket_options(_stream: &TcpStream) -> io::Result<SocketOptions> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Reading socket options is not supported on this platform",
+    ))
+}
+
+// `SockRef` borrows sockets through `AsFd`, which mio's types don't
+// implement.
+#[cfg(any(target_os = "linux", all(test, unix)))]
+fn borrow_fd<S: std::os::fd::AsRawFd>(socket: &S) -> std::os::fd::BorrowedFd<'_> {
+    // SAFETY: the fd is owned by `socket`, which outlives the borrow.
+    unsafe { std::os::fd::BorrowedFd::borrow_raw(socket.as_raw_fd()) }
 }
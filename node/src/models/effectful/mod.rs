@@ -1,2 +1,4 @@
+pub(crate) mod config;
 pub(crate) mod mio;
+pub(crate) mod output;
 pub(crate) mod time;
@@ -0,0 +1,32 @@
+use crate::automaton::action::{Action, ActionKind};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum OutputLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl From<OutputLevel> for log::Level {
+    fn from(level: OutputLevel) -> Self {
+        match level {
+            OutputLevel::Error => log::Level::Error,
+            OutputLevel::Warn => log::Level::Warn,
+            OutputLevel::Info => log::Level::Info,
+            OutputLevel::Debug => log::Level::Debug,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "5c462b4d-7bcc-4aa8-8116-bd20de21317f"]
+pub enum OutputEffectfulAction {
+    Log { level: OutputLevel, message: String },
+}
+
+impl Action for OutputEffectfulAction {
+    const KIND: ActionKind = ActionKind::Effectful;
+}
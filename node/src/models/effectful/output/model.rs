@@ -0,0 +1,48 @@
+use super::{
+    action::OutputEffectfulAction,
+    state::{OutputSink, OutputState},
+};
+use crate::automaton::{
+    action::Dispatcher,
+    model::{Effectful, EffectfulModel},
+    runner::{RegisterModel, RunnerBuilder},
+    state::ModelState,
+};
+
+// This is an `EffectfulModel` for the output of models (e.g. progress reports
+// or results) that should be part of the recordings, unlike the messages
+// logged directly with `info!` and the like.
+//
+// The `Log` action writes `message` to the `OutputSink`. Like any other
+// action, it gets recorded. In replay the output was already produced by the
+// recorded session, so it's suppressed.
+//
+// The sink is the `log` crate by default. A different one can be set by
+// registering `OutputState::with_sink()` after the models depending on
+// `OutputState`.
+
+impl RegisterModel for OutputState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.model_effectful(Effectful::<Self>(Self::new()))
+    }
+}
+
+impl EffectfulModel for OutputState {
+    type Action = OutputEffectfulAction;
+
+    fn process_effectful(&mut self, action: Self::Action, dispatcher: &mut Dispatcher) {
+        match action {
+            OutputEffectfulAction::Log { level, message } => {
+                if dispatcher.is_replayer() {
+                    return;
+                }
+
+                match &self.sink {
+                    OutputSink::Log => log::log!(target: "output", level.into(), "{}", message),
+                    #[cfg(test)]
+                    OutputSink::Capture(output) => output.borrow_mut().push((level, message)),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+#[cfg(test)]
+use super::action::OutputLevel;
+#[cfg(test)]
+use std::{cell::RefCell, rc::Rc};
+
+#[derive(Clone, Debug)]
+pub enum OutputSink {
+    // Through the `log` crate, with the "output" target.
+    Log,
+    // Kept in memory for tests to inspect.
+    #[cfg(test)]
+    Capture(Rc<RefCell<Vec<(OutputLevel, String)>>>),
+}
+
+pub struct OutputState {
+    pub sink: OutputSink,
+}
+
+impl OutputState {
+    pub fn new() -> Self {
+        Self::with_sink(OutputSink::Log)
+    }
+
+    pub fn with_sink(sink: OutputSink) -> Self {
+        Self { sink }
+    }
+}
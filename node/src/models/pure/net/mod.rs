@@ -1,4 +1,11 @@
 pub mod tcp;
+pub mod udp;
 pub mod tcp_server;
 pub mod tcp_client;
+pub mod retry_send;
+pub mod replay_sends;
+pub mod protocol_fsm;
 pub mod pnet;
+pub mod tee;
+pub mod topology;
+pub mod ring_buffer;
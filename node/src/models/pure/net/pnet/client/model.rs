@@ -111,7 +111,10 @@ impl PureModel for PnetClientState {
                     .find_connection_by_nonce_request(&uid);
 
                 // Rest of logic handled by `PnetClientInputAction::CloseEvent`
-                dispatcher.dispatch(TcpClientAction::Close { connection });
+                dispatcher.dispatch(TcpClientAction::Close {
+                    connection,
+                    deliver_buffered: false,
+                });
             }
             PnetClientAction::SendNonceError { .. } => {
                 // at this point the connection is closed by TcpClient model
@@ -126,13 +129,19 @@ impl PureModel for PnetClientState {
                     .find_connection_by_nonce_request(&uid);
 
                 // Rest of logic handled by `PnetClientInputAction::CloseEvent`
-                dispatcher.dispatch(TcpClientAction::Close { connection });
+                dispatcher.dispatch(TcpClientAction::Close {
+                    connection,
+                    deliver_buffered: false,
+                });
             }
             PnetClientAction::RecvNonceError { .. } => {
                 // Same handling as described for the SendNonceError case
             }
             PnetClientAction::Close { connection } => {
-                dispatcher.dispatch(TcpClientAction::Close { connection })
+                dispatcher.dispatch(TcpClientAction::Close {
+                    connection,
+                    deliver_buffered: false,
+                })
             }
             PnetClientAction::CloseEvent { connection } => {
                 let client_state: &mut PnetClientState = state.substate_mut();
@@ -292,6 +301,9 @@ fn recv_nonce(
         dispatcher.dispatch(TcpClientAction::Recv {
             uid,
             connection,
+            // Exactly the nonce: a fast peer may send application data right
+            // after it, which must be left in the socket for the first recv
+            // after the handshake.
             count: 24,
             timeout,
             on_success: callback!(|(uid: Uid, nonce: Vec<u8>)| PnetClientAction::RecvNonceSuccess { uid, nonce }),
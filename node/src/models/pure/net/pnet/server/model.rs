@@ -14,7 +14,7 @@ use crate::{
         net::{
             pnet::common::{ConnectionState, XSalsa20Wrapper},
             tcp_server::{
-                action::TcpServerAction,
+                action::{RoutingPolicy, TcpServerAction},
                 state::{RecvRequest, TcpServerState},
             },
         },
@@ -66,18 +66,24 @@ impl PureModel for PnetServerState {
             } => {
                 state.substate_mut::<PnetServerState>().new_listener(
                     listener,
-                    on_success,
-                    on_error,
-                    on_new_connection,
-                    on_new_connection_error,
-                    on_connection_closed,
-                    on_listener_closed,
+                    Listener::new(
+                        on_success,
+                        on_error,
+                        on_new_connection,
+                        on_new_connection_error,
+                        on_connection_closed,
+                        on_listener_closed,
+                    ),
                 );
 
                 dispatcher.dispatch(TcpServerAction::New {
                     address,
                     listener,
                     max_connections,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
                     on_success: callback!(|listener: Uid| PnetServerAction::NewSuccess { listener }),
                     on_error: callback!(|(listener: Uid, error: String)| PnetServerAction::NewError { listener, error }),
                     on_new_connection: callback!(|(listener: Uid, connection: Uid)| PnetServerAction::ConnectionEvent { listener, connection }),
@@ -127,7 +133,10 @@ impl PureModel for PnetServerState {
                     .find_connection_uid_by_nonce_request(&uid);
 
                 // The rest is handled by `PnetServerAction::CloseEvent`
-                dispatcher.dispatch(TcpServerAction::Close { connection });
+                dispatcher.dispatch(TcpServerAction::Close {
+                    connection,
+                    deliver_buffered: false,
+                });
             }
             PnetServerAction::SendNonceError { .. } => {
                 // The connection is closed by TcpServer model.
@@ -142,7 +151,10 @@ impl PureModel for PnetServerState {
                     .find_connection_uid_by_nonce_request(&uid);
 
                 // Rest of logic handled by `PnetServerAction::CloseEvent`
-                dispatcher.dispatch(TcpServerAction::Close { connection });
+                dispatcher.dispatch(TcpServerAction::Close {
+                    connection,
+                    deliver_buffered: false,
+                });
             }
             PnetServerAction::RecvNonceError { .. } => {
                 // Same handling as described for the SendNonceError case
@@ -179,7 +191,10 @@ impl PureModel for PnetServerState {
                     .remove_connection(&connection);
             }
             PnetServerAction::Close { connection } => {
-                dispatcher.dispatch(TcpServerAction::Close { connection })
+                dispatcher.dispatch(TcpServerAction::Close {
+                    connection,
+                    deliver_buffered: false,
+                })
             }
             PnetServerAction::Send {
                 uid,
@@ -314,6 +329,9 @@ fn recv_nonce(
         dispatcher.dispatch(TcpServerAction::Recv {
             uid,
             connection,
+            // Exactly the nonce: a fast peer may send application data right
+            // after it, which must be left in the socket for the first recv
+            // after the handshake.
             count: 24,
             timeout,
             on_success: callback!(|(uid: Uid, nonce: Vec<u8>)| PnetServerAction::RecvNonceSuccess { uid, nonce }),
@@ -73,31 +73,8 @@ impl PnetServerState {
         }
     }
 
-    pub fn new_listener(
-        &mut self,
-        listener: Uid,
-        on_success: Redispatch<Uid>,
-        on_error: Redispatch<(Uid, String)>,
-        on_new_connection: Redispatch<(Uid, Uid)>,
-        on_new_connection_error: Redispatch<(Uid, Uid, String)>,
-        on_connection_closed: Redispatch<(Uid, Uid)>,
-        on_listener_closed: Redispatch<Uid>,
-    ) {
-        if self
-            .listeners
-            .insert(
-                listener,
-                Listener::new(
-                    on_success,
-                    on_error,
-                    on_new_connection,
-                    on_new_connection_error,
-                    on_connection_closed,
-                    on_listener_closed,
-                ),
-            )
-            .is_some()
-        {
+    pub fn new_listener(&mut self, listener: Uid, listener_object: Listener) {
+        if self.listeners.insert(listener, listener_object).is_some() {
             panic!("Attempt to re-use existing {:?}", listener)
         }
     }
@@ -217,6 +194,7 @@ impl PnetServerState {
                 *uid,
                 RecvRequest {
                     connection,
+                    count: None,
                     on_success,
                     on_timeout,
                     on_error,
@@ -0,0 +1,43 @@
+use crate::automaton::{
+    action::{Action, ActionKind, Redispatch, Timeout},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use type_uuid::TypeUuid;
+
+// Timeout of a protocol state, armed when the state is entered. The callback
+// gets (connection, state).
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct StateTimeout {
+    pub timeout: Timeout,
+    pub on_timeout: Redispatch<(Uid, String)>,
+}
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "499f517c-fdc9-4e0e-a913-8679be2be606"]
+pub enum ProtocolFsmAction {
+    // Tracks the protocol state of `connection`, starting at `initial`. Each
+    // state of the FSM must be in `states`, use `Timeout::Never` for states
+    // without a timeout.
+    New {
+        connection: Uid,
+        states: BTreeMap<String, StateTimeout>,
+        initial: String,
+    },
+    // Cancels the timeout of the current state and arms the one of `state`.
+    Transition {
+        connection: Uid,
+        state: String,
+    },
+    // Fires the timeouts that expired, the owner dispatches it on each poll.
+    // The FSM stays in the timed out state until the next transition.
+    CheckTimeouts,
+    Remove {
+        connection: Uid,
+    },
+}
+
+impl Action for ProtocolFsmAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,60 @@
+use super::{
+    action::{ProtocolFsmAction, StateTimeout},
+    state::ProtocolFsmState,
+};
+use crate::{
+    automaton::{
+        action::Dispatcher,
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State},
+    },
+    models::pure::time::{model::get_current_time, state::TimeState},
+};
+
+// The `ProtocolFsmState` model tracks the protocol state of connections for
+// models implementing a protocol on top of them. Each protocol state has its
+// own timeout: entering a state arms it and the next transition cancels it,
+// so the protocol model only has to report its transitions.
+//
+// Deadlines are absolute `TimeState` times, like the ones of `TcpState`
+// requests, and are checked on `ProtocolFsmAction::CheckTimeouts`.
+
+// This model depends on the `TimeState` model.
+impl RegisterModel for ProtocolFsmState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TimeState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for ProtocolFsmState {
+    type Action = ProtocolFsmAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        let current_time = get_current_time(state);
+        let fsm_state: &mut ProtocolFsmState = state.substate_mut();
+
+        match action {
+            ProtocolFsmAction::New {
+                connection,
+                states,
+                initial,
+            } => fsm_state.new_fsm(connection, states, initial, current_time),
+            ProtocolFsmAction::Transition { connection, state } => fsm_state
+                .get_fsm_mut(&connection)
+                .enter(state, current_time),
+            ProtocolFsmAction::CheckTimeouts => {
+                for (connection, state, StateTimeout { on_timeout, .. }) in
+                    fsm_state.take_timed_out(current_time)
+                {
+                    dispatcher.dispatch_back(&on_timeout, (connection, state))
+                }
+            }
+            ProtocolFsmAction::Remove { connection } => fsm_state.remove_fsm(&connection),
+        }
+    }
+}
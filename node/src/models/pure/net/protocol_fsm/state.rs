@@ -0,0 +1,112 @@
+use super::action::StateTimeout;
+use crate::{
+    automaton::{
+        action::TimeoutAbsolute,
+        state::{Objects, Uid},
+    },
+    models::pure::time::model::timeout_absolute,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProtocolFsm {
+    pub states: BTreeMap<String, StateTimeout>,
+    pub state: String,
+    // Deadline of the current state, `Never` once its timeout fired.
+    pub deadline: TimeoutAbsolute,
+}
+
+impl ProtocolFsm {
+    pub fn enter(&mut self, state: String, current_time: u128) {
+        let StateTimeout { timeout, .. } = self
+            .states
+            .get(&state)
+            .expect(&format!("Unknown protocol state {:?}", state));
+
+        self.deadline = timeout_absolute(current_time, timeout.clone());
+        self.state = state;
+    }
+
+    pub fn timed_out(&self, current_time: u128) -> bool {
+        match self.deadline {
+            TimeoutAbsolute::Millis(ms) => current_time >= ms,
+            TimeoutAbsolute::Never => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProtocolFsmState {
+    fsm_objects: Objects<ProtocolFsm>,
+}
+
+impl Default for ProtocolFsmState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProtocolFsmState {
+    pub fn new() -> Self {
+        Self {
+            fsm_objects: Objects::<ProtocolFsm>::new(),
+        }
+    }
+
+    pub fn new_fsm(
+        &mut self,
+        connection: Uid,
+        states: BTreeMap<String, StateTimeout>,
+        initial: String,
+        current_time: u128,
+    ) {
+        let mut fsm = ProtocolFsm {
+            states,
+            state: String::new(),
+            deadline: TimeoutAbsolute::Never,
+        };
+
+        fsm.enter(initial, current_time);
+
+        if self.fsm_objects.insert(connection, fsm).is_some() {
+            panic!("Attempt to re-use existing {:?}", connection)
+        }
+    }
+
+    pub fn get_fsm(&self, connection: &Uid) -> &ProtocolFsm {
+        self.fsm_objects
+            .get(connection)
+            .expect(&format!("ProtocolFsm object {:?} not found", connection))
+    }
+
+    pub fn get_fsm_mut(&mut self, connection: &Uid) -> &mut ProtocolFsm {
+        self.fsm_objects
+            .get_mut(connection)
+            .expect(&format!("ProtocolFsm object {:?} not found", connection))
+    }
+
+    pub fn remove_fsm(&mut self, connection: &Uid) {
+        self.fsm_objects.remove(connection).expect(&format!(
+            "Attempt to remove an inexistent ProtocolFsm {:?}",
+            connection
+        ));
+    }
+
+    // Disarms the expired timeouts and returns their (connection, state,
+    // callback), in connection order.
+    pub fn take_timed_out(&mut self, current_time: u128) -> Vec<(Uid, String, StateTimeout)> {
+        self.fsm_objects
+            .iter_mut()
+            .filter(|(_, fsm)| fsm.timed_out(current_time))
+            .map(|(connection, fsm)| {
+                fsm.deadline = TimeoutAbsolute::Never;
+                (
+                    *connection,
+                    fsm.state.clone(),
+                    fsm.states[&fsm.state].clone(),
+                )
+            })
+            .collect()
+    }
+}
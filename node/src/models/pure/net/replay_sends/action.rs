@@ -0,0 +1,69 @@
+use super::state::{RecordedOperation, ReplayReport};
+use crate::automaton::{
+    action::{Action, ActionKind, Redispatch, Timeout},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "a51b8d62-5b63-497a-b867-15b51843f3e0"]
+pub enum ReplaySendsAction {
+    // Connects to `address` and replays the `Send` operations of `exchange`
+    // (see `recorded_exchange`) in order, against a live peer. `Recv`
+    // operations are not replayed but read live: as many bytes as recorded,
+    // within `timeout`. With `compare`, the live bytes are checked against
+    // the recorded ones.
+    //
+    // Once the whole exchange was replayed the connection is closed and the
+    // outcome is passed to `on_success`. A connect, send or recv error (or a
+    // send timeout) ends the replay and is passed to `on_error`.
+    Replay {
+        uid: Uid,
+        address: String,
+        exchange: Vec<RecordedOperation>,
+        compare: bool,
+        timeout: Timeout,
+        on_success: Redispatch<(Uid, ReplayReport)>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    ConnectSuccess {
+        connection: Uid,
+    },
+    ConnectTimeout {
+        connection: Uid,
+    },
+    ConnectError {
+        connection: Uid,
+        error: String,
+    },
+    CloseEvent {
+        connection: Uid,
+    },
+    SendSuccess {
+        uid: Uid,
+    },
+    SendTimeout {
+        uid: Uid,
+    },
+    SendError {
+        uid: Uid,
+        error: String,
+    },
+    RecvSuccess {
+        uid: Uid,
+        data: Vec<u8>,
+    },
+    RecvTimeout {
+        uid: Uid,
+        partial_data: Vec<u8>,
+    },
+    RecvError {
+        uid: Uid,
+        error: String,
+    },
+}
+
+impl Action for ReplaySendsAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
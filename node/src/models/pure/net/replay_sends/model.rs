@@ -0,0 +1,210 @@
+use super::{
+    action::ReplaySendsAction,
+    state::{ReplayRequest, ReplaySendsState},
+};
+use crate::{
+    automaton::{
+        action::Dispatcher,
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::net::{
+        tcp::state::OperationKind,
+        tcp_client::{action::TcpClientAction, state::TcpClientState},
+    },
+};
+
+// The `ReplaySendsState` model checks that a live peer still behaves as
+// recorded (conformance testing). Unlike a full replay, which feeds the
+// recorded results to the models without any I/O, only the bytes written to
+// a recorded connection are replayed, over a new `TcpClientState` connection.
+// What the peer sends back is read live, and optionally compared with what
+// was recorded.
+
+// This model depends on the `TcpClientState` model.
+impl RegisterModel for ReplaySendsState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpClientState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for ReplaySendsState {
+    type Action = ReplaySendsAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ReplaySendsAction::Replay {
+                uid,
+                address,
+                exchange,
+                compare,
+                timeout,
+                on_success,
+                on_error,
+            } => {
+                let connection = state.new_uid();
+
+                state.substate_mut::<ReplaySendsState>().new_replay_request(
+                    &uid,
+                    ReplayRequest {
+                        connection,
+                        exchange,
+                        next: 0,
+                        step: None,
+                        compare,
+                        timeout: timeout.clone(),
+                        report: Default::default(),
+                        on_success,
+                        on_error,
+                    },
+                );
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection,
+                    address,
+                    timeout,
+                    on_success: callback!(|connection: Uid| ReplaySendsAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| ReplaySendsAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| ReplaySendsAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| ReplaySendsAction::CloseEvent { connection }),
+                });
+            }
+            ReplaySendsAction::ConnectSuccess { connection } => {
+                let uid = state
+                    .substate::<ReplaySendsState>()
+                    .find_replay_request_by_connection(&connection)
+                    .unwrap_or_else(|| panic!("No ReplayRequest connecting {:?}", connection));
+
+                replay_next(state, dispatcher, uid)
+            }
+            ReplaySendsAction::ConnectTimeout { connection } => {
+                connect_failed(state, dispatcher, connection, "Connect timeout".to_string())
+            }
+            ReplaySendsAction::ConnectError { connection, error } => {
+                connect_failed(state, dispatcher, connection, error)
+            }
+            // Closed once replayed, or after an error that was already
+            // reported.
+            ReplaySendsAction::CloseEvent { .. } => (),
+            ReplaySendsAction::SendSuccess { uid: step } => {
+                let replay_state: &mut ReplaySendsState = state.substate_mut();
+                let uid = replay_state.find_replay_request_by_step(&step);
+
+                replay_state.get_replay_request_mut(&uid).next += 1;
+                replay_next(state, dispatcher, uid)
+            }
+            ReplaySendsAction::SendTimeout { uid: step } => {
+                let replay_state: &mut ReplaySendsState = state.substate_mut();
+                let uid = replay_state.find_replay_request_by_step(&step);
+                let ReplayRequest {
+                    connection,
+                    on_error,
+                    ..
+                } = replay_state.take_replay_request(&uid);
+
+                // Unlike errors, timeouts leave the connection open.
+                dispatcher.dispatch(TcpClientAction::Close {
+                    connection,
+                    deliver_buffered: false,
+                });
+                dispatcher.dispatch_back(&on_error, (uid, "Send timeout".to_string()))
+            }
+            ReplaySendsAction::SendError { uid: step, error }
+            | ReplaySendsAction::RecvError { uid: step, error } => {
+                let replay_state: &mut ReplaySendsState = state.substate_mut();
+                let uid = replay_state.find_replay_request_by_step(&step);
+                let ReplayRequest { on_error, .. } = replay_state.take_replay_request(&uid);
+
+                // `TcpClientState` closes the failed connection by itself.
+                dispatcher.dispatch_back(&on_error, (uid, error))
+            }
+            // A recv that timed out only got part of the bytes recorded: that's
+            // for the caller to judge, so the replay goes on.
+            ReplaySendsAction::RecvSuccess { uid: step, data }
+            | ReplaySendsAction::RecvTimeout {
+                uid: step,
+                partial_data: data,
+            } => {
+                let replay_state: &mut ReplaySendsState = state.substate_mut();
+                let uid = replay_state.find_replay_request_by_step(&step);
+                let request = replay_state.get_replay_request_mut(&uid);
+
+                if request.compare && request.exchange[request.next].data != data {
+                    request.report.mismatches.push(request.next);
+                }
+
+                request.report.received.push(data);
+                request.next += 1;
+                replay_next(state, dispatcher, uid)
+            }
+        }
+    }
+}
+
+fn connect_failed<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    connection: Uid,
+    error: String,
+) {
+    let replay_state: &mut ReplaySendsState = state.substate_mut();
+    let uid = replay_state
+        .find_replay_request_by_connection(&connection)
+        .unwrap_or_else(|| panic!("No ReplayRequest connecting {:?}", connection));
+    let ReplayRequest { on_error, .. } = replay_state.take_replay_request(&uid);
+
+    dispatcher.dispatch_back(&on_error, (uid, error))
+}
+
+// Replays the next operation of the exchange, or completes the replay.
+fn replay_next<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    uid: Uid,
+) {
+    let step = state.new_uid();
+    let replay_state: &mut ReplaySendsState = state.substate_mut();
+    let request = replay_state.get_replay_request_mut(&uid);
+    let connection = request.connection;
+    let timeout = request.timeout.clone();
+
+    let Some(operation) = request.exchange.get(request.next) else {
+        let ReplayRequest {
+            report, on_success, ..
+        } = replay_state.take_replay_request(&uid);
+
+        dispatcher.dispatch(TcpClientAction::Close {
+            connection,
+            deliver_buffered: false,
+        });
+        return dispatcher.dispatch_back(&on_success, (uid, report));
+    };
+
+    match operation.kind {
+        OperationKind::Send => dispatcher.dispatch(TcpClientAction::Send {
+            uid: step,
+            connection,
+            data: operation.data.clone().into(),
+            timeout,
+            on_success: callback!(|uid: Uid| ReplaySendsAction::SendSuccess { uid }),
+            on_timeout: callback!(|uid: Uid| ReplaySendsAction::SendTimeout { uid }),
+            on_error: callback!(|(uid: Uid, error: String)| ReplaySendsAction::SendError { uid, error }),
+        }),
+        OperationKind::Recv => dispatcher.dispatch(TcpClientAction::Recv {
+            uid: step,
+            connection,
+            count: operation.data.len(),
+            timeout,
+            on_success: callback!(|(uid: Uid, data: Vec<u8>)| ReplaySendsAction::RecvSuccess { uid, data }),
+            on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| ReplaySendsAction::RecvTimeout { uid, partial_data }),
+            on_error: callback!(|(uid: Uid, error: String)| ReplaySendsAction::RecvError { uid, error }),
+        }),
+    }
+
+    request.step = Some(step);
+}
@@ -0,0 +1,154 @@
+use crate::{
+    automaton::{
+        action::{AnyAction, Redispatch, Timeout},
+        state::{Objects, Uid},
+    },
+    models::{
+        effectful::mio::action::MioEffectfulAction,
+        pure::net::tcp::{action::TcpAction, state::OperationKind},
+    },
+};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Bytes written to (`Send`) or read from (`Recv`) a connection.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct RecordedOperation {
+    pub kind: OperationKind,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub struct ReplayReport {
+    // The live bytes read in place of each `Recv` operation, in order. Fewer
+    // than recorded if the recv timed out.
+    pub received: Vec<Vec<u8>>,
+    // Indexes (in the exchange) of the `Recv` operations whose live bytes
+    // differ from the recorded ones. Always empty without `compare`.
+    pub mismatches: Vec<usize>,
+}
+
+// Splits the traffic of `connection` in a recording (see
+// `Runner::recorded_actions`) into the bytes written and the bytes read, in
+// order. Consecutive writes (or reads) are merged: how the bytes were split
+// into writes and reads is up to the OS, not the application.
+pub fn recorded_exchange(actions: &[AnyAction], connection: Uid) -> Vec<RecordedOperation> {
+    // `TcpWrite`/`TcpRead` effects on the connection, waiting for their result.
+    let mut writes: BTreeMap<Uid, Vec<u8>> = BTreeMap::new();
+    let mut reads: BTreeSet<Uid> = BTreeSet::new();
+    let mut exchange: Vec<RecordedOperation> = Vec::new();
+
+    for action in actions {
+        if let Some(effect) = action.ptr.downcast_ref::<MioEffectfulAction>() {
+            match effect {
+                MioEffectfulAction::TcpWrite {
+                    uid,
+                    connection: write_connection,
+                    data,
+                    ..
+                } if *write_connection == connection => {
+                    writes.insert(*uid, data.to_vec());
+                }
+                MioEffectfulAction::TcpRead {
+                    uid,
+                    connection: read_connection,
+                    ..
+                } if *read_connection == connection => {
+                    reads.insert(*uid);
+                }
+                _ => (),
+            }
+
+            continue;
+        }
+
+        let (kind, data) = match action.ptr.downcast_ref::<TcpAction>() {
+            Some(TcpAction::SendSuccess { uid }) => match writes.remove(uid) {
+                Some(data) => (OperationKind::Send, data),
+                None => continue,
+            },
+            Some(TcpAction::SendSuccessPartial { uid, count }) => match writes.remove(uid) {
+                Some(data) => (OperationKind::Send, data[..*count].to_vec()),
+                None => continue,
+            },
+            Some(
+                TcpAction::RecvSuccess { uid, data }
+                | TcpAction::RecvSuccessPartial {
+                    uid,
+                    partial_data: data,
+                },
+            ) if reads.remove(uid) => (OperationKind::Recv, data.clone()),
+            _ => continue,
+        };
+
+        match exchange.last_mut() {
+            Some(last) if last.kind == kind => last.data.extend(data),
+            _ if data.is_empty() => (),
+            _ => exchange.push(RecordedOperation { kind, data }),
+        }
+    }
+
+    exchange
+}
+
+#[derive(Debug)]
+pub struct ReplayRequest {
+    pub connection: Uid,
+    pub exchange: Vec<RecordedOperation>,
+    // Index of the operation being replayed, and the `Uid` of its send or
+    // recv request.
+    pub next: usize,
+    pub step: Option<Uid>,
+    pub compare: bool,
+    pub timeout: Timeout,
+    pub report: ReplayReport,
+    pub on_success: Redispatch<(Uid, ReplayReport)>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplaySendsState {
+    pub replay_requests: Objects<ReplayRequest>,
+}
+
+impl ReplaySendsState {
+    pub fn new() -> Self {
+        Self {
+            replay_requests: Objects::<ReplayRequest>::new(),
+        }
+    }
+
+    pub fn new_replay_request(&mut self, uid: &Uid, request: ReplayRequest) {
+        if self.replay_requests.insert(*uid, request).is_some() {
+            panic!("Attempt to re-use existing ReplayRequest {:?}", uid)
+        }
+    }
+
+    pub fn get_replay_request_mut(&mut self, uid: &Uid) -> &mut ReplayRequest {
+        self.replay_requests
+            .get_mut(uid)
+            .unwrap_or_else(|| panic!("ReplayRequest {:?} not found", uid))
+    }
+
+    pub fn take_replay_request(&mut self, uid: &Uid) -> ReplayRequest {
+        self.replay_requests
+            .remove(uid)
+            .unwrap_or_else(|| panic!("Take attempt on inexistent ReplayRequest {:?}", uid))
+    }
+
+    pub fn find_replay_request_by_connection(&self, connection: &Uid) -> Option<Uid> {
+        self.replay_requests
+            .iter()
+            .find(|(_, request)| request.connection == *connection)
+            .map(|(uid, _)| *uid)
+    }
+
+    pub fn find_replay_request_by_step(&self, step: &Uid) -> Uid {
+        *self
+            .replay_requests
+            .iter()
+            .find(|(_, request)| request.step == Some(*step))
+            .unwrap_or_else(|| panic!("No ReplayRequest replaying step {:?}", step))
+            .0
+    }
+}
@@ -0,0 +1,61 @@
+use crate::automaton::{
+    action::{self, Action, ActionKind, Redispatch, Timeout},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::rc::Rc;
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "cfdcc9ca-a566-4847-93fc-4076ede94177"]
+pub enum RetrySendAction {
+    // Sends `data` over an established `TcpClientState` connection. If the send
+    // fails with a transient error before any bytes were written, the model
+    // reconnects to `address` and re-sends the same data, up to `max_attempts`
+    // sends in total.
+    //
+    // The connection used by the successful attempt is passed to `on_success`
+    // (uid, connection). Connections created by a reconnect report their close
+    // event to `on_close`. Timeouts are not retried, since the data might have
+    // been partially written.
+    Send {
+        uid: Uid,
+        connection: Uid,
+        address: String,
+        #[serde(
+            serialize_with = "action::serialize_rc_bytes",
+            deserialize_with = "action::deserialize_rc_bytes"
+        )]
+        data: Rc<[u8]>,
+        timeout: Timeout,
+        max_attempts: usize,
+        on_success: Redispatch<(Uid, Uid)>,
+        on_timeout: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+        on_close: Redispatch<Uid>,
+    },
+    SendSuccess {
+        uid: Uid,
+    },
+    SendTimeout {
+        uid: Uid,
+    },
+    SendError {
+        uid: Uid,
+        error: String,
+    },
+    ReconnectSuccess {
+        connection: Uid,
+    },
+    ReconnectTimeout {
+        connection: Uid,
+    },
+    ReconnectError {
+        connection: Uid,
+        error: String,
+    },
+}
+
+impl Action for RetrySendAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,165 @@
+use super::{
+    action::RetrySendAction,
+    state::{RetrySendState, SendRequest},
+};
+use crate::{
+    automaton::{
+        action::Dispatcher,
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::net::{
+        tcp::state::TcpState,
+        tcp_client::{action::TcpClientAction, state::TcpClientState},
+    },
+};
+
+// The `RetrySendState` model wraps `TcpClientState` send operations with a
+// reconnect-and-retry policy for transient failures. The failure is classified
+// by `TcpState::send_error_kind()`: a send is only retried when it failed
+// before any of its bytes were written, so the peer never receives the same
+// data twice.
+
+// This model depends on the `TcpClientState` model.
+impl RegisterModel for RetrySendState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpClientState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for RetrySendState {
+    type Action = RetrySendAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            RetrySendAction::Send {
+                uid,
+                connection,
+                address,
+                data,
+                timeout,
+                max_attempts,
+                on_success,
+                on_timeout,
+                on_error,
+                on_close,
+            } => {
+                assert_ne!(max_attempts, 0);
+                let retry_state: &mut RetrySendState = state.substate_mut();
+
+                retry_state.new_send_request(
+                    &uid,
+                    SendRequest {
+                        connection,
+                        address,
+                        data,
+                        timeout,
+                        attempts_left: max_attempts,
+                        on_success,
+                        on_timeout,
+                        on_error,
+                        on_close,
+                    },
+                );
+                dispatch_send(retry_state, dispatcher, uid)
+            }
+            RetrySendAction::SendSuccess { uid } => {
+                let SendRequest {
+                    connection,
+                    on_success,
+                    ..
+                } = state
+                    .substate_mut::<RetrySendState>()
+                    .take_send_request(&uid);
+
+                dispatcher.dispatch_back(&on_success, (uid, connection))
+            }
+            RetrySendAction::SendTimeout { uid } => {
+                let SendRequest { on_timeout, .. } = state
+                    .substate_mut::<RetrySendState>()
+                    .take_send_request(&uid);
+
+                dispatcher.dispatch_back(&on_timeout, uid)
+            }
+            RetrySendAction::SendError { uid, error } => {
+                let retry = {
+                    let request = state.substate::<RetrySendState>().get_send_request(&uid);
+                    let kind = state
+                        .substate::<TcpState>()
+                        .send_error_kind(&request.connection, &uid);
+
+                    request.attempts_left > 0 && kind.is_transient()
+                };
+
+                if retry {
+                    // `TcpClientState` closes the failed connection by itself,
+                    // so we only need to open a new one.
+                    let connection = state.new_uid();
+                    let request = state
+                        .substate_mut::<RetrySendState>()
+                        .get_send_request_mut(&uid);
+
+                    request.connection = connection;
+                    dispatcher.dispatch(TcpClientAction::Connect {
+                        connection,
+                        address: request.address.clone(),
+                        timeout: request.timeout.clone(),
+                        on_success: callback!(|connection: Uid| RetrySendAction::ReconnectSuccess { connection }),
+                        on_timeout: callback!(|connection: Uid| RetrySendAction::ReconnectTimeout { connection }),
+                        on_error: callback!(|(connection: Uid, error: String)| RetrySendAction::ReconnectError { connection, error }),
+                        on_close: request.on_close.clone(),
+                    });
+                } else {
+                    let SendRequest { on_error, .. } = state
+                        .substate_mut::<RetrySendState>()
+                        .take_send_request(&uid);
+
+                    dispatcher.dispatch_back(&on_error, (uid, error))
+                }
+            }
+            RetrySendAction::ReconnectSuccess { connection } => {
+                let retry_state: &mut RetrySendState = state.substate_mut();
+                let uid = retry_state.find_send_request_by_connection(&connection);
+
+                dispatch_send(retry_state, dispatcher, uid)
+            }
+            RetrySendAction::ReconnectTimeout { connection } => {
+                let retry_state: &mut RetrySendState = state.substate_mut();
+                let uid = retry_state.find_send_request_by_connection(&connection);
+                let SendRequest { on_timeout, .. } = retry_state.take_send_request(&uid);
+
+                dispatcher.dispatch_back(&on_timeout, uid)
+            }
+            RetrySendAction::ReconnectError { connection, error } => {
+                let retry_state: &mut RetrySendState = state.substate_mut();
+                let uid = retry_state.find_send_request_by_connection(&connection);
+                let SendRequest { on_error, .. } = retry_state.take_send_request(&uid);
+
+                dispatcher.dispatch_back(&on_error, (uid, error))
+            }
+        }
+    }
+}
+
+fn dispatch_send(retry_state: &mut RetrySendState, dispatcher: &mut Dispatcher, uid: Uid) {
+    let request = retry_state.get_send_request_mut(&uid);
+
+    request.attempts_left -= 1;
+    // The same `uid` is re-used for every attempt, the previous `TcpClientState`
+    // request is already gone by the time we retry.
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid,
+        connection: request.connection,
+        data: request.data.clone(),
+        timeout: request.timeout.clone(),
+        on_success: callback!(|uid: Uid| RetrySendAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| RetrySendAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| RetrySendAction::SendError { uid, error }),
+    });
+}
@@ -0,0 +1,67 @@
+use crate::automaton::{
+    action::{Redispatch, Timeout},
+    state::{Objects, Uid},
+};
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub struct SendRequest {
+    pub connection: Uid,
+    pub address: String,
+    pub data: Rc<[u8]>,
+    pub timeout: Timeout,
+    pub attempts_left: usize,
+    pub on_success: Redispatch<(Uid, Uid)>,
+    pub on_timeout: Redispatch<Uid>,
+    pub on_error: Redispatch<(Uid, String)>,
+    pub on_close: Redispatch<Uid>,
+}
+
+#[derive(Default, Debug)]
+pub struct RetrySendState {
+    pub send_requests: Objects<SendRequest>,
+}
+
+impl RetrySendState {
+    pub fn new() -> Self {
+        Self {
+            send_requests: Objects::<SendRequest>::new(),
+        }
+    }
+
+    pub fn new_send_request(&mut self, uid: &Uid, request: SendRequest) {
+        if self.send_requests.insert(*uid, request).is_some() {
+            panic!("Attempt to re-use existing SendRequest {:?}", uid)
+        }
+    }
+
+    pub fn get_send_request(&self, uid: &Uid) -> &SendRequest {
+        self.send_requests
+            .get(uid)
+            .expect(&format!("SendRequest {:?} not found", uid))
+    }
+
+    pub fn get_send_request_mut(&mut self, uid: &Uid) -> &mut SendRequest {
+        self.send_requests
+            .get_mut(uid)
+            .expect(&format!("SendRequest {:?} not found", uid))
+    }
+
+    pub fn take_send_request(&mut self, uid: &Uid) -> SendRequest {
+        self.send_requests
+            .remove(uid)
+            .expect(&format!("Take attempt on inexistent SendRequest {:?}", uid))
+    }
+
+    pub fn find_send_request_by_connection(&self, connection: &Uid) -> Uid {
+        *self
+            .send_requests
+            .iter()
+            .find(|(_, request)| request.connection == *connection)
+            .expect(&format!(
+                "No SendRequest reconnecting with connection {:?}",
+                connection
+            ))
+            .0
+    }
+}
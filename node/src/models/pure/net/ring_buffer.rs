@@ -0,0 +1,42 @@
+use serde_derive::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+// Per-connection receive buffer for streaming parsers, see
+// `TcpClientAction::RecvIntoRing` and `TcpServerAction::RecvIntoRing`.
+// Received bytes accumulate across recvs until the parser consumes them. The
+// storage is reused, so once it has grown to the parser's working size
+// receiving doesn't allocate.
+#[derive(Default, Serialize, Deserialize, Debug)]
+pub struct RingBuffer {
+    buffer: VecDeque<u8>,
+}
+
+impl RingBuffer {
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn extend(&mut self, data: &[u8]) {
+        self.buffer.extend(data)
+    }
+
+    // All the buffered bytes, oldest first.
+    pub fn peek(&mut self) -> &[u8] {
+        self.buffer.make_contiguous()
+    }
+
+    // Drops the first `count` bytes, once the parser is done with them.
+    pub fn consume(&mut self, count: usize) {
+        assert!(
+            count <= self.buffer.len(),
+            "Attempt to consume {} bytes out of {}",
+            count,
+            self.buffer.len()
+        );
+        self.buffer.drain(..count);
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
         action::{self, Action, ActionKind, Redispatch, Timeout},
         state::Uid,
     },
-    models::effectful::mio::action::MioEvent,
+    models::effectful::mio::action::{Interest, MioEvent, ShutdownHow, SocketOptions},
 };
 use serde_derive::{Deserialize, Serialize};
 use std::rc::Rc;
@@ -14,6 +14,12 @@ use type_uuid::TypeUuid;
 pub enum TcpAction {
     Init {
         instance: Uid,
+        // Capacity of the poll events buffer, `DEFAULT_EVENTS_CAPACITY` if
+        // unset. Servers expecting many simultaneous connections can raise it
+        // to handle more events per poll. Events beyond the capacity are only
+        // delayed to the next poll, it doesn't bound the number of
+        // connections (see `TcpServerAction::New` for that).
+        events_capacity: Option<usize>,
         on_success: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
@@ -30,12 +36,30 @@ pub enum TcpAction {
     Listen {
         listener: Uid,
         address: String,
+        // Applied to the listening socket, see `SocketOptions`.
+        options: Option<SocketOptions>,
+        // See `MioEffectfulAction::TcpListen`.
+        backlog: Option<i32>,
         on_success: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
     ListenSuccess {
         listener: Uid,
     },
+    // Like `Listen`, but adopts `fd`, an already bound and listening socket
+    // (e.g. from systemd socket activation, or passed by a parent process),
+    // instead of binding an address. The listener takes ownership of `fd`.
+    // Unix only.
+    ListenFromFd {
+        listener: Uid,
+        fd: i32,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    ListenFromFdSuccess {
+        listener: Uid,
+        address: String,
+    },
     ListenError {
         listener: Uid,
         error: String,
@@ -50,12 +74,19 @@ pub enum TcpAction {
     Accept {
         connection: Uid,
         listener: Uid,
+        // Delays the poll registration of the accepted connection by this
+        // many milliseconds (testing only, see `TcpServerConfig`).
+        register_delay: Option<u64>,
+        // Sets TCP_NODELAY on the connection once accepted.
+        nodelay: bool,
         on_success: Redispatch<Uid>,
         on_would_block: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
     AcceptSuccess {
         connection: Uid,
+        local_address: String,
+        peer_address: String,
     },
     AcceptTryAgain {
         connection: Uid,
@@ -67,8 +98,17 @@ pub enum TcpAction {
     Connect {
         connection: Uid,
         address: String,
+        // See `Listen`.
+        options: Option<SocketOptions>,
+        // Local address to bind the connection to, e.g. to choose the source
+        // interface on multi-homed hosts.
+        bind_address: Option<String>,
+        // Sets TCP_NODELAY on the connection once connected.
+        nodelay: bool,
         timeout: Timeout,
         on_success: Redispatch<Uid>,
+        // The connection is already being closed when this is called: it must
+        // not be closed again with `Close`.
         on_timeout: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
@@ -81,7 +121,8 @@ pub enum TcpAction {
     },
     GetPeerAddressSuccess {
         connection: Uid,
-        address: String,
+        local_address: String,
+        peer_address: String,
     },
     GetPeerAddressError {
         connection: Uid,
@@ -101,16 +142,32 @@ pub enum TcpAction {
         connection: Uid,
         error: String,
     },
+    ReregisterConnectionSuccess {
+        connection: Uid,
+    },
+    ReregisterConnectionError {
+        connection: Uid,
+        error: String,
+    },
     Close {
         connection: Uid,
+        // Hand the data buffered by partially filled recv requests to their
+        // `on_timeout` callbacks before the connection is removed.
+        deliver_buffered: bool,
         on_success: Redispatch<Uid>,
     },
     CloseSuccess {
         connection: Uid,
     },
+    // Reports the events of `objects`. With no objects (e.g. a server without
+    // listeners yet) it's a sleep: it waits for `timeout` (or the nearest
+    // request deadline) and reports no events.
+    //
+    // Connections are polled for the given `Interest` from now on (listeners
+    // are always polled for readability). See `TcpAction::poll`.
     Poll {
         uid: Uid,
-        objects: Vec<Uid>,
+        objects: Vec<(Uid, Interest)>,
         timeout: Timeout,
         on_success: Redispatch<(Uid, TcpPollEvents)>,
         on_error: Redispatch<(Uid, String)>,
@@ -138,6 +195,9 @@ pub enum TcpAction {
         on_success: Redispatch<Uid>,
         on_timeout: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
+        // Set by a model issuing the request on behalf of another one (e.g.
+        // `TcpServerState`), to be told if it's cancelled, see `CancelSend`.
+        on_cancelled: Option<Redispatch<(Uid, SendResult)>>,
     },
     SendSuccess {
         uid: Uid,
@@ -156,6 +216,13 @@ pub enum TcpAction {
         uid: Uid,
         error: String,
     },
+    // Drops a pending `Send` (written data can't be taken back) and reports
+    // `SendResult::Cancelled` to the request's `on_cancelled`, if set, and to
+    // `on_result`. A no-op if the request already completed.
+    CancelSend {
+        uid: Uid,
+        on_result: Redispatch<(Uid, SendResult)>,
+    },
     Recv {
         uid: Uid,
         connection: Uid,
@@ -164,6 +231,8 @@ pub enum TcpAction {
         on_success: Redispatch<(Uid, Vec<u8>)>,
         on_timeout: Redispatch<(Uid, Vec<u8>)>,
         on_error: Redispatch<(Uid, String)>,
+        // Same as `Send::on_cancelled`.
+        on_cancelled: Option<Redispatch<(Uid, RecvResult)>>,
     },
     RecvSuccess {
         uid: Uid,
@@ -183,14 +252,252 @@ pub enum TcpAction {
         uid: Uid,
         error: String,
     },
+    // Drops a pending `Recv` or `RecvUntil`, along with the data it buffered,
+    // and reports `RecvResult::Cancelled` to the request's `on_cancelled`, if
+    // set, and to `on_result`. A no-op if the request already completed.
+    CancelRecv {
+        uid: Uid,
+        on_result: Redispatch<(Uid, RecvResult)>,
+    },
+    // Like `Recv`, but completes as soon as the received data contains
+    // `delimiter`, or once `max_bytes` were received. The data passed to
+    // `on_success` includes the delimiter, and whatever followed it in the
+    // same read.
+    RecvUntil {
+        uid: Uid,
+        connection: Uid,
+        delimiter: Vec<u8>,
+        max_bytes: usize,
+        timeout: Timeout,
+        on_success: Redispatch<(Uid, Vec<u8>)>,
+        on_timeout: Redispatch<(Uid, Vec<u8>)>,
+        on_error: Redispatch<(Uid, String)>,
+        // Same as `Send::on_cancelled`.
+        on_cancelled: Option<Redispatch<(Uid, RecvResult)>>,
+    },
+    // Receives a CRLF-terminated line of at most `max_len` bytes (without the
+    // CRLF), passed to `on_line` with the connection's `Uid`. Data received
+    // past the end of the line is kept for the next `RecvLine`. A longer line
+    // is a protocol error: it's reported to `on_error` and the connection is
+    // closed.
+    RecvLine {
+        connection: Uid,
+        max_len: usize,
+        timeout: Timeout,
+        on_line: Redispatch<(Uid, Vec<u8>)>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    RecvLineSuccess {
+        uid: Uid,
+        data: Vec<u8>,
+    },
+    RecvLineTimeout {
+        uid: Uid,
+        partial_data: Vec<u8>,
+    },
+    RecvLineError {
+        uid: Uid,
+        error: String,
+    },
+    GetBufferStatus {
+        uid: Uid,
+        connection: Uid,
+        // (uid, bytes queued in the OS send buffer, bytes available in the OS recv buffer)
+        on_success: Redispatch<(Uid, usize, usize)>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    GetBufferStatusSuccess {
+        uid: Uid,
+        send_queued: usize,
+        recv_available: usize,
+    },
+    GetBufferStatusError {
+        uid: Uid,
+        error: String,
+    },
+    // Reports the (local, peer) socket addresses of `connection`, available
+    // once it's established.
+    GetPeerAddress {
+        connection: Uid,
+        on_result: Redispatch<(Uid, PeerAddressResult)>,
+    },
+    // Reports the local socket address of a listener (e.g. the port assigned
+    // when listening on port 0) or of a connection. Listeners can be queried
+    // once `Listen` has succeeded.
+    GetLocalAddress {
+        listener_or_connection: Uid,
+        on_success: Redispatch<(Uid, String)>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    GetLocalAddressResult {
+        uid: Uid,
+        result: Result<String, String>,
+    },
+    // Reports how many bytes can be read from `connection` right away,
+    // without consuming them (e.g. to size a recv request that drains them).
+    BytesAvailable {
+        connection: Uid,
+        on_result: Redispatch<(Uid, BytesAvailableResult)>,
+    },
+    BytesAvailableSuccess {
+        connection: Uid,
+        count: usize,
+    },
+    BytesAvailableNotSupported {
+        connection: Uid,
+    },
+    BytesAvailableError {
+        connection: Uid,
+        error: String,
+    },
+    // Shuts down one half (or both) of `connection` without closing it. Once
+    // the write side is shut down, sends fail while recvs keep working until
+    // the peer closes its side.
+    Shutdown {
+        connection: Uid,
+        how: ShutdownHow,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    ShutdownSuccess {
+        connection: Uid,
+    },
+    ShutdownError {
+        connection: Uid,
+        error: String,
+    },
+    // Enables (or disables) TCP_NODELAY on `connection`, the new setting is
+    // kept in `Connection::nodelay`.
+    SetNodelay {
+        connection: Uid,
+        value: bool,
+        on_result: Redispatch<(Uid, Result<(), String>)>,
+    },
+    SetNodelayResult {
+        connection: Uid,
+        result: Result<(), String>,
+    },
+    // Reports the congestion control algorithm of `connection` (set with
+    // `SocketOptions::congestion`), also kept in `Connection::congestion`.
+    // Only supported on Linux.
+    GetCongestion {
+        connection: Uid,
+        on_result: Redispatch<(Uid, Result<String, String>)>,
+    },
+    GetCongestionResult {
+        connection: Uid,
+        result: Result<String, String>,
+    },
+    // Within a poll cycle, the events (pending connect, send and recv
+    // requests) of connections with a higher `priority` are processed first.
+    // Connections start at priority 0.
+    SetPriority {
+        connection: Uid,
+        priority: u8,
+    },
+    // Overall deadline of `connection`: its subsequent send and recv requests
+    // time out `timeout` from now at the latest, whatever their own timeout
+    // (inactivity timeouts aren't pushed past it either). `Timeout::Never`
+    // removes the deadline.
+    SetDeadline {
+        connection: Uid,
+        timeout: Timeout,
+    },
+    // Sets the share of `connection` in the writes started per poll, when
+    // limited by `TcpConfig::writes_per_poll`. Connections start at weight 1,
+    // which is also the minimum (0 is clamped to 1).
+    SetWeight {
+        connection: Uid,
+        weight: u32,
+    },
+    // Scripts the byte-rate of a connection. The same schedule shapes both
+    // send and recv, each direction with its own budget (a connection
+    // shaped to N bytes/sec can send N and receive N bytes per second).
+    // Each entry is (at_time, bytes_per_sec): starting at `at_time` (ms,
+    // state-machine time) the connection is shaped to `bytes_per_sec`. The
+    // connection is not shaped before the first entry.
+    SetRateSchedule {
+        connection: Uid,
+        schedule: Vec<(u128, u64)>,
+    },
+    // Subscribes to the start and end of the fd-exhaustion degraded mode (see
+    // `FdExhaustion`). Callbacks get the `TcpState` instance. Freeing
+    // descriptors, e.g. by closing idle connections, is up to the watcher.
+    WatchFdExhaustion {
+        on_exhausted: Redispatch<Uid>,
+        on_recovered: Redispatch<Uid>,
+    },
+    // Starts (or stops) logging the completed send and recv requests of a
+    // connection, see `TcpState::operation_log`.
+    SetOperationLog {
+        connection: Uid,
+        enabled: bool,
+    },
+    // Checks whether `address` accepts connections (e.g. for health checks):
+    // connects, and closes the connection as soon as the peer address check
+    // confirms it's established. The connection is never handed to the
+    // caller, `connection` only identifies the probe in `on_result`.
+    Probe {
+        connection: Uid,
+        address: String,
+        timeout: Timeout,
+        on_result: Redispatch<(Uid, ProbeResult)>,
+    },
+    ProbeConnectSuccess {
+        connection: Uid,
+    },
+    ProbeConnectTimeout {
+        connection: Uid,
+    },
+    ProbeConnectError {
+        connection: Uid,
+        error: String,
+    },
+    ProbeCloseSuccess {
+        connection: Uid,
+    },
+    // Panics if `TcpState::validate` finds inconsistencies (debugging aid).
+    Validate,
+    // Test-only: makes the send, recv or connect request `uid` take its
+    // timeout path right away, regardless of its deadline. Must not be used
+    // while the request has an effect in flight.
+    #[cfg(test)]
+    ExpireRequest {
+        uid: Uid,
+    },
 }
 
 impl Action for TcpAction {
     const KIND: ActionKind = ActionKind::Pure;
 }
 
+impl TcpAction {
+    // `Poll` with every object polled for `Interest::Both`.
+    pub fn poll(
+        uid: Uid,
+        objects: Vec<Uid>,
+        timeout: Timeout,
+        on_success: Redispatch<(Uid, TcpPollEvents)>,
+        on_error: Redispatch<(Uid, String)>,
+    ) -> Self {
+        TcpAction::Poll {
+            uid,
+            objects: objects
+                .into_iter()
+                .map(|uid| (uid, Interest::Both))
+                .collect(),
+            timeout,
+            on_success,
+            on_error,
+        }
+    }
+}
+
 pub type TcpPollEvents = Vec<(Uid, Event)>;
 
+// (local, peer) socket addresses of a connection.
+pub type PeerAddressResult = Result<(String, String), String>;
+
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub enum ListenerEvent {
     AcceptPending,
@@ -217,6 +524,7 @@ pub enum RecvResult {
     Success(Vec<u8>),
     Timeout(Vec<u8>),
     Error(String),
+    Cancelled,
 }
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
@@ -224,6 +532,7 @@ pub enum SendResult {
     Success,
     Timeout,
     Error(String),
+    Cancelled,
 }
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
@@ -233,6 +542,21 @@ pub enum ConnectResult {
     Error(String),
 }
 
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum ProbeResult {
+    Reachable,
+    Refused(String),
+    Timeout,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum BytesAvailableResult {
+    Available(usize),
+    // The platform can't tell without reading.
+    NotSupported,
+    Error(String),
+}
+
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub enum AcceptResult {
     Success,
@@ -1,11 +1,17 @@
 use super::{
-    action::{ListenerEvent, TcpAction},
-    state::{ConnectionStatus, EventUpdater, Listener, RecvRequest, Status, TcpState},
+    action::{BytesAvailableResult, ListenerEvent, ProbeResult, RecvResult, SendResult, TcpAction},
+    state::{
+        is_fd_exhaustion_error, split_line, BufferStatusRequest, CongestionRequest,
+        ConnectionLogEvent, ConnectionStatus, EventUpdater, FdExhaustionWatcher, InactivityTimeout,
+        Line, LineRequest, Listener, LocalAddressRequest, NodelayRequest, OperationKind,
+        ProbeRequest, RecvRequest, SendRequest, ShutdownRequest, Status, TcpState,
+        DEFAULT_EVENTS_CAPACITY, LINE_DELIMITER,
+    },
     util::*,
 };
 use crate::{
     automaton::{
-        action::{Dispatcher, TimeoutAbsolute},
+        action::{Dispatcher, Redispatch, TimeoutAbsolute},
         model::PureModel,
         runner::{RegisterModel, RunnerBuilder},
         state::{ModelState, State, Uid},
@@ -13,7 +19,7 @@ use crate::{
     callback,
     models::{
         effectful::mio::{
-            action::{MioEffectfulAction, MioEvent},
+            action::{MioEffectfulAction, MioEvent, ShutdownHow},
             state::MioState,
         },
         pure::{
@@ -26,6 +32,7 @@ use crate::{
     },
 };
 use core::panic;
+use log::{info, warn};
 
 // The `TcpState` model handles the state of a TCP connection system, which is
 // built on top of the `MioState` model. It processes the outcomes of external
@@ -36,6 +43,12 @@ use core::panic;
 // - Establishing connections to remote peers.
 // - Listening for connections.
 // - Sending and receiving data.
+// - Querying the fill levels of the OS socket buffers of a connection, and
+//   the bytes readable from it without consuming them.
+// - Shutting down one half of a connection (e.g. to signal the end of a
+//   request) while the other half keeps working.
+// - Probing whether a remote address accepts connections, without keeping
+//   the connection.
 //
 // Another feature provided by this model is timeout support for the async IO.
 // While the `TcpState` model simplifies some aspects of the `MioState` model,
@@ -63,6 +76,7 @@ impl PureModel for TcpState {
         match action {
             TcpAction::Init {
                 instance,
+                events_capacity,
                 on_success,
                 on_error,
             } => {
@@ -72,6 +86,7 @@ impl PureModel for TcpState {
                 tcp_state.status = Status::InitPollCreate {
                     instance,
                     poll,
+                    events_capacity: events_capacity.unwrap_or(DEFAULT_EVENTS_CAPACITY),
                     on_success,
                     on_error,
                 };
@@ -89,6 +104,7 @@ impl PureModel for TcpState {
                 if let Status::InitPollCreate {
                     instance,
                     poll,
+                    events_capacity,
                     on_success,
                     ..
                 } = tcp_state.status.clone()
@@ -96,7 +112,7 @@ impl PureModel for TcpState {
                     // Dispatch next action to continue initialization
                     dispatcher.dispatch_effect(MioEffectfulAction::EventsCreate {
                         uid: events,
-                        capacity: 1024,
+                        capacity: events_capacity,
                         on_success: callback!(|uid: Uid| TcpAction::EventsCreate { uid }),
                     });
 
@@ -105,6 +121,7 @@ impl PureModel for TcpState {
                         instance,
                         poll,
                         events,
+                        events_capacity,
                         on_success,
                     };
                 } else {
@@ -133,6 +150,7 @@ impl PureModel for TcpState {
                     poll,
                     events,
                     on_success,
+                    ..
                 } = tcp_state.status.clone()
                 {
                     dispatcher.dispatch_back(&on_success, instance);
@@ -148,6 +166,8 @@ impl PureModel for TcpState {
             TcpAction::Listen {
                 listener,
                 address,
+                options,
+                backlog,
                 on_success,
                 on_error,
             } => {
@@ -157,22 +177,37 @@ impl PureModel for TcpState {
                 dispatcher.dispatch_effect(MioEffectfulAction::TcpListen {
                     listener,
                     address,
+                    options,
+                    backlog,
                     on_success: callback!(|listener: Uid| TcpAction::ListenSuccess { listener }),
                     on_error: callback!(|(listener: Uid, error: String)| TcpAction::ListenError { listener, error })
                 });
             }
             TcpAction::ListenSuccess { listener } => {
-                // If the listen operation was successful we register the listener in the MIO poll object.
-                if let Status::Ready { poll, .. } = state.substate_mut::<TcpState>().status {
-                    dispatcher.dispatch_effect(MioEffectfulAction::PollRegisterTcpServer {
-                        poll,
-                        listener,
-                        on_success: callback!(|listener: Uid| TcpAction::RegisterListenerSuccess { listener }),
-                        on_error: callback!(|(listener: Uid, error: String)| TcpAction::RegisterListenerError { listener, error }),
-                    });
-                } else {
-                    unreachable!()
-                };
+                register_listener(state.substate(), dispatcher, listener)
+            }
+            TcpAction::ListenFromFd {
+                listener,
+                fd,
+                on_success,
+                on_error,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                // The actual address is known once the socket is adopted.
+                tcp_state.new_listener(listener, format!("fd:{}", fd), on_success, on_error);
+                dispatcher.dispatch_effect(MioEffectfulAction::TcpListenFromFd {
+                    listener,
+                    fd,
+                    on_success: callback!(|(listener: Uid, address: String)| TcpAction::ListenFromFdSuccess { listener, address }),
+                    on_error: callback!(|(listener: Uid, error: String)| TcpAction::ListenError { listener, error })
+                });
+            }
+            TcpAction::ListenFromFdSuccess { listener, address } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                tcp_state.get_listener_mut(&listener).address = address;
+                register_listener(tcp_state, dispatcher, listener)
             }
             TcpAction::ListenError { listener, error } => {
                 let tcp_state: &mut TcpState = state.substate_mut();
@@ -197,27 +232,38 @@ impl PureModel for TcpState {
             TcpAction::Accept {
                 connection,
                 listener,
+                register_delay,
+                nodelay,
                 on_success,
                 on_would_block,
                 on_error,
             } => {
+                let current_time = get_current_time(state);
                 let tcp_state: &mut TcpState = state.substate_mut();
 
+                // The listener is left readable, so the server tries again
+                // after its next poll.
+                if tcp_state.accepts_held(current_time) {
+                    return dispatcher.dispatch_back(&on_would_block, connection);
+                }
+
                 if let ListenerEvent::AcceptPending = tcp_state.get_listener(&listener).events() {
                     tcp_state.new_connection(
                         connection,
                         ConnectionType::Incoming {
                             listener,
+                            register_delay,
                             on_success,
                             on_would_block,
                             on_error,
                         },
                         TimeoutAbsolute::Never,
                     );
+                    tcp_state.get_connection_mut(&connection).nodelay = nodelay;
                     dispatcher.dispatch_effect(MioEffectfulAction::TcpAccept {
                         connection,
                         listener,
-                        on_success: callback!(|connection: Uid| TcpAction::AcceptSuccess { connection }),
+                        on_success: callback!(|(connection: Uid, local_address: String, peer_address: String)| TcpAction::AcceptSuccess { connection, local_address, peer_address }),
                         on_would_block: callback!(|connection: Uid| TcpAction::AcceptTryAgain { connection }),
                         on_error: callback!(|(connection: Uid, error: String)| TcpAction::AcceptError { connection, error })
                     });
@@ -225,24 +271,41 @@ impl PureModel for TcpState {
                     unreachable!()
                 }
             }
-            TcpAction::AcceptSuccess { connection } => {
+            TcpAction::AcceptSuccess {
+                connection,
+                local_address,
+                peer_address,
+            } => {
+                let current_time = get_current_time(state);
                 let tcp_state: &mut TcpState = state.substate_mut();
 
-                if let ConnectionType::Incoming { .. } =
-                    tcp_state.get_connection(&connection).conn_type
-                {
-                    if let Status::Ready { poll, .. } = tcp_state.status {
-                        dispatcher.dispatch_effect(MioEffectfulAction::PollRegisterTcpConnection {
-                            poll,
-                            connection,
-                            on_success: callback!(|connection: Uid| TcpAction::RegisterConnectionSuccess { connection }),
-                            on_error: callback!(|(connection: Uid, error: String)| TcpAction::RegisterConnectionError { connection, error }),
-                        })
-                    } else {
-                        unreachable!()
-                    }
-                } else {
+                tcp_state.record_accept_latency(&connection, current_time);
+                on_fd_available(state, dispatcher);
+
+                let tcp_state: &mut TcpState = state.substate_mut();
+                let number = tcp_state.new_connection_number(&connection);
+                let conn = tcp_state.get_connection_mut(&connection);
+
+                conn.addrs = Some((local_address, peer_address));
+                conn.established_at = Some(current_time);
+                conn.log(current_time, ConnectionLogEvent::Accepted { number });
+
+                if conn.nodelay {
+                    set_nodelay(tcp_state, dispatcher, connection, true, None);
+                }
+
+                let conn = tcp_state.get_connection_mut(&connection);
+
+                let ConnectionType::Incoming { register_delay, .. } = conn.conn_type else {
                     unreachable!()
+                };
+
+                if let Some(delay) = register_delay {
+                    // Registered from `handle_poll_success` once the delay
+                    // expires, like registration retries.
+                    conn.register_retry_at = Some(current_time.saturating_add(delay.into()));
+                } else {
+                    register_connection(tcp_state, dispatcher, connection)
                 }
             }
             TcpAction::AcceptTryAgain { connection } => {
@@ -256,7 +319,11 @@ impl PureModel for TcpState {
                 {
                     dispatcher.dispatch_back(&on_would_block, connection);
 
-                    let events = tcp_state.get_listener_mut(&listener).events_mut();
+                    let listener = tcp_state.get_listener_mut(&listener);
+
+                    listener.readable_at = None;
+
+                    let events = listener.events_mut();
 
                     if let ListenerEvent::AcceptPending = events {
                         *events = ListenerEvent::AllAccepted;
@@ -269,6 +336,8 @@ impl PureModel for TcpState {
                 }
             }
             TcpAction::AcceptError { connection, error } => {
+                on_fd_error(state, dispatcher, &error);
+
                 let tcp_state: &mut TcpState = state.substate_mut();
 
                 if let ConnectionType::Incoming { on_error, .. } =
@@ -283,14 +352,19 @@ impl PureModel for TcpState {
             TcpAction::Connect {
                 connection,
                 address,
+                options,
+                bind_address,
+                nodelay,
                 timeout,
                 on_success,
                 on_timeout,
                 on_error,
             } => {
                 let timeout = get_timeout_absolute(state, timeout);
+                let current_time = get_current_time(state);
+                let tcp_state: &mut TcpState = state.substate_mut();
 
-                state.substate_mut::<TcpState>().new_connection(
+                tcp_state.new_connection(
                     connection,
                     ConnectionType::Outgoing {
                         on_success,
@@ -299,26 +373,31 @@ impl PureModel for TcpState {
                     },
                     timeout,
                 );
+                tcp_state.get_connection_mut(&connection).nodelay = nodelay;
+                tcp_state.log_connection(&connection, current_time, ConnectionLogEvent::Connecting);
                 dispatcher.dispatch_effect(MioEffectfulAction::TcpConnect {
                     connection,
                     address,
+                    options,
+                    bind_address,
                     on_success: callback!(|connection: Uid| TcpAction::ConnectSuccess { connection }),
                     on_error: callback!(|(connection: Uid, error: String)| TcpAction::ConnectError { connection, error })
                 });
             }
             TcpAction::ConnectSuccess { connection } => {
-                if let Status::Ready { poll, .. } = state.substate::<TcpState>().status {
-                    dispatcher.dispatch_effect(MioEffectfulAction::PollRegisterTcpConnection {
-                        poll,
-                        connection,
-                        on_success: callback!(|connection: Uid| TcpAction::RegisterConnectionSuccess { connection }),
-                        on_error: callback!(|(connection: Uid, error: String)| TcpAction::RegisterConnectionError { connection, error }),
-                    });
-                } else {
-                    unreachable!()
-                };
+                on_fd_available(state, dispatcher);
+
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if tcp_state.get_connection(&connection).nodelay {
+                    set_nodelay(tcp_state, dispatcher, connection, true, None);
+                }
+
+                register_connection(state.substate_mut(), dispatcher, connection);
             }
             TcpAction::ConnectError { connection, error } => {
+                on_fd_error(state, dispatcher, &error);
+
                 let tcp_state: &mut TcpState = state.substate_mut();
 
                 if let ConnectionType::Outgoing { on_error, .. } =
@@ -331,20 +410,41 @@ impl PureModel for TcpState {
                 };
             }
             TcpAction::RegisterConnectionSuccess { connection } => {
+                let current_time = get_current_time(state);
+                let tcp_state: &mut TcpState = state.substate_mut();
+                let poll = tcp_state.poll_for_connection(&connection);
+                let conn = tcp_state.get_connection_mut(&connection);
+
+                conn.poll = Some(poll);
+                conn.log(current_time, ConnectionLogEvent::Registered);
+
                 // Ignore outgoing connections
-                if let ConnectionType::Incoming { on_success, .. } = state
-                    .substate::<TcpState>()
-                    .get_connection(&connection)
-                    .conn_type
-                    .clone()
-                {
+                if let ConnectionType::Incoming { on_success, .. } = conn.conn_type.clone() {
                     dispatcher.dispatch_back(&on_success, connection);
                 }
             }
             TcpAction::RegisterConnectionError { connection, error } => {
-                let conn = state
-                    .substate_mut::<TcpState>()
-                    .get_connection_mut(&connection);
+                let current_time = get_current_time(state);
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                tcp_state.log_connection(
+                    &connection,
+                    current_time,
+                    ConnectionLogEvent::RegisterError(error.clone()),
+                );
+
+                let conn = tcp_state.get_connection_mut(&connection);
+
+                conn.registered_interest = None;
+                conn.poll = None;
+
+                // Retried from `handle_poll_success` once the backoff expires.
+                if tcp_state.schedule_register_retry(&connection, &error, current_time) {
+                    return;
+                }
+
+                let error = format!("Error registering connection {:?}: {}", connection, error);
+                let conn = tcp_state.get_connection_mut(&connection);
 
                 conn.status = ConnectionStatus::CloseRequestInternal;
                 dispatcher.dispatch_effect(MioEffectfulAction::TcpClose {
@@ -352,36 +452,60 @@ impl PureModel for TcpState {
                     on_success: callback!(|connection: Uid| TcpAction::CloseSuccess { connection }),
                 });
 
-                dispatcher.dispatch_back(
-                    &conn.conn_type.on_error(),
-                    (
-                        connection,
-                        format!("Error registering connection {:?}: {}", connection, error),
-                    ),
-                )
+                dispatcher.dispatch_back(&conn.conn_type.on_error(), (connection, error))
             }
             TcpAction::Close {
                 connection,
+                deliver_buffered,
                 on_success,
             } => {
+                let current_time = get_current_time(state);
                 let tcp_state: &mut TcpState = state.substate_mut();
 
+                tcp_state.log_connection(
+                    &connection,
+                    current_time,
+                    ConnectionLogEvent::CloseRequested,
+                );
+
+                if deliver_buffered {
+                    for (uid, RecvRequest { buffered_data, on_timeout, .. }) in
+                        tcp_state.take_buffered_recv_requests(&connection)
+                    {
+                        dispatcher.dispatch_back(&on_timeout, (uid, buffered_data));
+                    }
+                }
+
                 if let Status::Ready { poll, .. } = tcp_state.status {
-                    tcp_state.get_connection_mut(&connection).status =
-                        ConnectionStatus::CloseRequestNotify { on_success };
+                    let conn = tcp_state.get_connection_mut(&connection);
 
-                    // before closing the stream remove it from the poll object
-                    dispatcher.dispatch_effect(MioEffectfulAction::PollDeregisterTcpConnection {
-                        poll,
-                        connection,
-                        on_success: callback!(|connection: Uid| TcpAction::DeregisterConnectionSuccess { connection }),
-                        on_error: callback!(|(connection: Uid, error: String)| TcpAction::DeregisterConnectionError { connection, error })
-                    });
+                    conn.status = ConnectionStatus::CloseRequestNotify { on_success };
+
+                    // A connection waiting for a registration retry is not
+                    // registered with the poll object, so close it right away.
+                    if conn.register_retry_at.take().is_some() {
+                        dispatcher.dispatch_effect(MioEffectfulAction::TcpClose {
+                            connection,
+                            on_success: callback!(|connection: Uid| TcpAction::CloseSuccess { connection }),
+                        });
+                    } else {
+                        // before closing the stream remove it from the poll object
+                        dispatcher.dispatch_effect(MioEffectfulAction::PollDeregisterTcpConnection {
+                            poll,
+                            connection,
+                            on_success: callback!(|connection: Uid| TcpAction::DeregisterConnectionSuccess { connection }),
+                            on_error: callback!(|(connection: Uid, error: String)| TcpAction::DeregisterConnectionError { connection, error })
+                        });
+                    }
                 } else {
                     unreachable!()
                 };
             }
             TcpAction::DeregisterConnectionSuccess { connection } => {
+                state
+                    .substate_mut::<TcpState>()
+                    .get_connection_mut(&connection)
+                    .poll = None;
                 dispatcher.dispatch_effect(MioEffectfulAction::TcpClose {
                     connection,
                     on_success: callback!(|connection: Uid| TcpAction::CloseSuccess { connection }),
@@ -390,6 +514,48 @@ impl PureModel for TcpState {
             TcpAction::DeregisterConnectionError { connection, error } => {
                 panic!("DeregisterConnectionError {:?}: {}", connection, error)
             }
+            TcpAction::ReregisterConnectionSuccess { .. } => (),
+            TcpAction::ReregisterConnectionError { connection, error } => {
+                let current_time = get_current_time(state);
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                // The registration itself failed meanwhile (and is retried
+                // with the new interest), or the connection is gone or closing.
+                if !tcp_state.has_connection(&connection) {
+                    return;
+                }
+
+                let conn = tcp_state.get_connection(&connection);
+
+                if conn.registered_interest.is_none()
+                    || matches!(
+                        conn.status,
+                        ConnectionStatus::CloseRequestInternal
+                            | ConnectionStatus::CloseRequestNotify { .. }
+                    )
+                {
+                    return;
+                }
+
+                tcp_state.log_connection(
+                    &connection,
+                    current_time,
+                    ConnectionLogEvent::RegisterError(error.clone()),
+                );
+
+                // Unlike a failed registration, the connection is still
+                // registered (with the previous interest), so it isn't
+                // retried: the connection is closed.
+                let error = format!("Error re-registering connection {:?}: {}", connection, error);
+                let on_error = tcp_state
+                    .get_connection(&connection)
+                    .conn_type
+                    .on_error()
+                    .clone();
+
+                close_internal(tcp_state, dispatcher, connection);
+                dispatcher.dispatch_back(&on_error, (connection, error))
+            }
             TcpAction::CloseSuccess { connection } => {
                 let tcp_state: &mut TcpState = state.substate_mut();
 
@@ -411,9 +577,29 @@ impl PureModel for TcpState {
                 on_success,
                 on_error,
             } => {
+                let current_time = get_current_time(state);
                 let tcp_state: &mut TcpState = state.substate_mut();
+                let timeout = tcp_state.poll_timeout(current_time, timeout);
 
                 if let Status::Ready { poll, events, .. } = tcp_state.status {
+                    for &(connection, interest) in objects.iter() {
+                        if !tcp_state.has_connection(&connection) {
+                            continue;
+                        }
+
+                        if let Some(interest) = tcp_state.set_interest(&connection, interest) {
+                            dispatcher.dispatch_effect(MioEffectfulAction::PollReregisterTcpConnection {
+                                poll: tcp_state.poll_for_connection(&connection),
+                                connection,
+                                interest,
+                                on_success: callback!(|connection: Uid| TcpAction::ReregisterConnectionSuccess { connection }),
+                                on_error: callback!(|(connection: Uid, error: String)| TcpAction::ReregisterConnectionError { connection, error }),
+                            });
+                        }
+                    }
+
+                    let objects = objects.into_iter().map(|(uid, _)| uid).collect();
+
                     tcp_state.new_poll(uid, objects, timeout.clone(), on_success, on_error);
                     dispatcher.dispatch_effect(MioEffectfulAction::PollEvents {
                         uid,
@@ -430,7 +616,8 @@ impl PureModel for TcpState {
             }
             TcpAction::PollSuccess { uid, events } => {
                 let current_time = get_current_time(state);
-                handle_poll_success(state.substate_mut(), dispatcher, current_time, uid, events)
+                handle_poll_success(state.substate_mut(), dispatcher, current_time, uid, events);
+                update_connection_metrics(state)
             }
             TcpAction::PollInterrupted { uid } => {
                 let tcp_state: &TcpState = state.substate();
@@ -457,11 +644,19 @@ impl PureModel for TcpState {
                 tcp_state.remove_poll_request(&uid)
             }
             // dispatched from process_pending_connections()
-            TcpAction::GetPeerAddressSuccess { connection, .. } => {
+            TcpAction::GetPeerAddressSuccess {
+                connection,
+                local_address,
+                peer_address,
+            } => {
+                let current_time = get_current_time(state);
                 let conn = state
                     .substate_mut::<TcpState>()
                     .get_connection_mut(&connection);
 
+                conn.addrs = Some((local_address, peer_address));
+                conn.log(current_time, ConnectionLogEvent::Connected);
+
                 if let Connection {
                     status: ConnectionStatus::PendingCheck,
                     conn_type: ConnectionType::Outgoing { on_success, .. },
@@ -469,6 +664,7 @@ impl PureModel for TcpState {
                 } = conn
                 {
                     conn.status = ConnectionStatus::Established;
+                    conn.established_at = Some(current_time);
                     dispatcher.dispatch_back(on_success, connection);
                 } else {
                     unreachable!()
@@ -497,8 +693,11 @@ impl PureModel for TcpState {
                 on_success,
                 on_timeout,
                 on_error,
+                on_cancelled,
             } => {
+                let inactivity = timeout.inactivity().map(InactivityTimeout::new);
                 let timeout = get_timeout_absolute(state, timeout);
+                let current_time = get_current_time(state);
                 let tcp_state: &mut TcpState = state.substate_mut();
 
                 if !tcp_state.has_connection(&connection) {
@@ -507,41 +706,141 @@ impl PureModel for TcpState {
                         (uid, format!("No such connection: {:?}", connection)),
                     );
                 } else {
+                    let timeout = tcp_state.request_deadline(&connection, timeout);
+
                     tcp_state.new_send_request(
-                        uid, connection, data, false, timeout, on_success, on_timeout, on_error,
+                        uid,
+                        SendRequest::new(
+                            connection, data, false, timeout, on_success, on_timeout, on_error,
+                        ),
                     );
-                    dispatch_send(tcp_state, dispatcher, uid)
+                    let request = tcp_state.get_send_request_mut(&uid);
+
+                    request.inactivity = inactivity;
+                    request.trace_id = dispatcher.trace_id;
+                    request.on_cancelled = on_cancelled;
+                    dispatch_send(tcp_state, dispatcher, current_time, uid)
                 }
             }
+            // The result of a write started before the request was cancelled.
+            TcpAction::SendSuccess { uid }
+            | TcpAction::SendSuccessPartial { uid, .. }
+            | TcpAction::SendErrorInterrupted { uid }
+            | TcpAction::SendErrorTryAgain { uid }
+            | TcpAction::SendError { uid, .. }
+                if !state.substate::<TcpState>().has_send_request(&uid) => {}
             // dispatched from dispatch_send()
             TcpAction::SendSuccess { uid } => {
+                let current_time = get_current_time(state);
+                let written = state.substate::<TcpState>().get_send_request(&uid).write_len;
+
+                count_bytes_sent(state, written);
+
                 let tcp_state = state.substate_mut::<TcpState>();
 
-                dispatcher.dispatch_back(&tcp_state.get_send_request(&uid).on_success, uid);
-                tcp_state.remove_send_request(&uid)
+                tcp_state.complete_send(&uid, written);
+
+                let request = tcp_state.get_send_request(&uid);
+
+                // The write might have been shortened by the connection's shaper.
+                if request.bytes_sent < request.data.len() {
+                    handle_send_common(tcp_state, dispatcher, current_time, uid, true)
+                } else {
+                    let (connection, data) = (request.connection, request.data.clone());
+
+                    dispatcher.dispatch_back(&request.on_success, uid);
+                    tcp_state.remove_send_request(&uid);
+                    tcp_state.log_operation(&connection, current_time, OperationKind::Send, &data);
+
+                    // Sends queued behind this one can proceed.
+                    if let Some(next) = tcp_state.next_send_request(&connection) {
+                        dispatch_send(tcp_state, dispatcher, current_time, next)
+                    }
+                }
             }
             TcpAction::SendSuccessPartial { uid, count } => {
                 let current_time = get_current_time(state);
+
+                count_bytes_sent(state, count);
+
                 let tcp_state = state.substate_mut::<TcpState>();
+                let SendRequest {
+                    connection,
+                    write_len,
+                    ..
+                } = *tcp_state.get_send_request(&uid);
 
-                tcp_state.get_send_request_mut(&uid).bytes_sent += count;
+                tcp_state.log_connection(
+                    &connection,
+                    current_time,
+                    ConnectionLogEvent::PartialWrite {
+                        written: count,
+                        len: write_len,
+                    },
+                );
+                tcp_state.complete_send(&uid, count);
                 handle_send_common(tcp_state, dispatcher, current_time, uid, true)
             }
             TcpAction::SendErrorInterrupted { uid } => {
                 let current_time = get_current_time(state);
+                let tcp_state = state.substate_mut::<TcpState>();
 
-                handle_send_common(state.substate_mut(), dispatcher, current_time, uid, true)
+                tcp_state.complete_send(&uid, 0);
+                handle_send_common(tcp_state, dispatcher, current_time, uid, true)
             }
             TcpAction::SendErrorTryAgain { uid } => {
                 let current_time = get_current_time(state);
+                let tcp_state = state.substate_mut::<TcpState>();
 
-                handle_send_common(state.substate_mut(), dispatcher, current_time, uid, false)
+                tcp_state.complete_send(&uid, 0);
+                handle_send_common(tcp_state, dispatcher, current_time, uid, false)
             }
             TcpAction::SendError { uid, error } => {
+                let current_time = get_current_time(state);
+                let tcp_state: &mut TcpState = state.substate_mut();
+                let connection = tcp_state.get_send_request(&uid).connection;
+
+                tcp_state.log_connection(
+                    &connection,
+                    current_time,
+                    ConnectionLogEvent::SendError(error.clone()),
+                );
+
+                let request = tcp_state.get_send_request(&uid);
+                let error = request.error_message(error);
+
+                dispatcher.dispatch_back(&request.on_error, (uid, error));
+                tcp_state.record_send_error(&uid, request.error_kind());
+                tcp_state.remove_send_request(&uid);
+            }
+            TcpAction::CancelSend { uid, on_result } => {
+                let current_time = get_current_time(state);
                 let tcp_state: &mut TcpState = state.substate_mut();
 
-                dispatcher.dispatch_back(&tcp_state.get_send_request(&uid).on_error, (uid, error));
-                tcp_state.remove_send_request(&uid)
+                // The result may be in flight already.
+                if tcp_state.has_send_request(&uid) {
+                    let was_head = tcp_state.is_send_head(&uid);
+                    let SendRequest {
+                        connection,
+                        on_cancelled,
+                        ..
+                    } = tcp_state.remove_send_request(&uid);
+
+                    // The issuer first, e.g. so a layer's request tables are
+                    // up to date by the time `on_result` is called.
+                    if let Some(on_cancelled) = on_cancelled {
+                        dispatcher.dispatch_back(&on_cancelled, (uid, SendResult::Cancelled));
+                    }
+
+                    dispatcher.dispatch_back(&on_result, (uid, SendResult::Cancelled));
+
+                    // Sends queued behind this one can proceed.
+                    if was_head {
+                        if let Some(next) = tcp_state.next_send_request(&connection) {
+                            dispatch_send(tcp_state, dispatcher, current_time, next)
+                        }
+                    }
+                }
             }
             TcpAction::Recv {
                 uid,
@@ -551,8 +850,49 @@ impl PureModel for TcpState {
                 on_success,
                 on_timeout,
                 on_error,
+                on_cancelled,
+            } => {
+                let inactivity = timeout.inactivity().map(InactivityTimeout::new);
+                let timeout = get_timeout_absolute(state, timeout);
+                let current_time = get_current_time(state);
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if !tcp_state.has_connection(&connection) {
+                    dispatcher.dispatch_back(
+                        &on_error,
+                        (uid, format!("No such connection: {:?}", connection)),
+                    );
+                } else {
+                    let timeout = tcp_state.request_deadline(&connection, timeout);
+
+                    tcp_state.new_recv_request(
+                        uid,
+                        RecvRequest::new(
+                            connection, count, false, timeout, on_success, on_timeout, on_error,
+                        ),
+                    );
+                    let request = tcp_state.get_recv_request_mut(&uid);
+
+                    request.inactivity = inactivity;
+                    request.trace_id = dispatcher.trace_id;
+                    request.on_cancelled = on_cancelled;
+                    dispatch_recv(tcp_state, dispatcher, current_time, uid)
+                }
+            }
+            TcpAction::RecvUntil {
+                uid,
+                connection,
+                delimiter,
+                max_bytes,
+                timeout,
+                on_success,
+                on_timeout,
+                on_error,
+                on_cancelled,
             } => {
+                let inactivity = timeout.inactivity().map(InactivityTimeout::new);
                 let timeout = get_timeout_absolute(state, timeout);
+                let current_time = get_current_time(state);
                 let tcp_state: &mut TcpState = state.substate_mut();
 
                 if !tcp_state.has_connection(&connection) {
@@ -560,19 +900,64 @@ impl PureModel for TcpState {
                         &on_error,
                         (uid, format!("No such connection: {:?}", connection)),
                     );
+                } else if delimiter.is_empty() {
+                    dispatcher.dispatch_back(&on_error, (uid, "Empty delimiter".to_string()));
                 } else {
+                    let timeout = tcp_state.request_deadline(&connection, timeout);
+
                     tcp_state.new_recv_request(
-                        uid, connection, count, false, timeout, on_success, on_timeout, on_error,
+                        uid,
+                        RecvRequest::new(
+                            connection, max_bytes, false, timeout, on_success, on_timeout,
+                            on_error,
+                        ),
                     );
-                    dispatch_recv(tcp_state, dispatcher, uid)
+
+                    let request = tcp_state.get_recv_request_mut(&uid);
+
+                    request.inactivity = inactivity;
+                    request.trace_id = dispatcher.trace_id;
+                    request.on_cancelled = on_cancelled;
+                    request.delimiter = Some(delimiter);
+                    dispatch_recv(tcp_state, dispatcher, current_time, uid)
+                }
+            }
+            TcpAction::CancelRecv { uid, on_result } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                // The result may be in flight already.
+                if tcp_state.has_recv_request(&uid) {
+                    let RecvRequest { on_cancelled, .. } = tcp_state.remove_recv_request(&uid);
+
+                    tcp_state.discard_line_request(&uid);
+
+                    // Same as `CancelSend`.
+                    if let Some(on_cancelled) = on_cancelled {
+                        dispatcher.dispatch_back(&on_cancelled, (uid, RecvResult::Cancelled));
+                    }
+
+                    dispatcher.dispatch_back(&on_result, (uid, RecvResult::Cancelled));
                 }
             }
+            // The result of a read started before the request was cancelled.
+            TcpAction::RecvSuccess { uid, .. }
+            | TcpAction::RecvSuccessPartial { uid, .. }
+            | TcpAction::RecvErrorInterrupted { uid }
+            | TcpAction::RecvErrorTryAgain { uid }
+            | TcpAction::RecvError { uid, .. }
+                if !state.substate::<TcpState>().has_recv_request(&uid) => {}
             TcpAction::RecvSuccess { uid, data } => {
+                let current_time = get_current_time(state);
+
+                count_bytes_received(state, data.len());
+
                 let tcp_state: &mut TcpState = state.substate_mut();
+
+                tcp_state.complete_recv(&uid, data.len());
+
                 let RecvRequest {
                     buffered_data,
                     remaining_bytes,
-                    on_success,
                     ..
                 } = tcp_state.get_recv_request_mut(&uid);
 
@@ -580,15 +965,27 @@ impl PureModel for TcpState {
                     .checked_sub(data.len())
                     .expect("Received more data than requested");
                 buffered_data.extend_from_slice(&data);
-                dispatcher.dispatch_back(&on_success, (uid, buffered_data.clone()));
-                tcp_state.remove_recv_request(&uid);
+                // The read might have been shortened by the connection's shaper.
+                continue_recv(tcp_state, dispatcher, current_time, uid)
             }
             TcpAction::RecvSuccessPartial {
                 uid,
                 partial_data: data,
             } => {
                 let current_time = get_current_time(state);
+
+                count_bytes_received(state, data.len());
+
                 let tcp_state: &mut TcpState = state.substate_mut();
+                let connection = tcp_state.get_recv_request(&uid).connection;
+
+                tcp_state.log_connection(
+                    &connection,
+                    current_time,
+                    ConnectionLogEvent::PartialRead { count: data.len() },
+                );
+                tcp_state.complete_recv(&uid, data.len());
+
                 let RecvRequest {
                     buffered_data,
                     remaining_bytes,
@@ -599,24 +996,675 @@ impl PureModel for TcpState {
                     .checked_sub(data.len())
                     .expect("Received more data than requested");
                 buffered_data.extend_from_slice(&data);
-                handle_recv_common(tcp_state, dispatcher, current_time, uid, true)
+                continue_recv(tcp_state, dispatcher, current_time, uid)
             }
             TcpAction::RecvErrorInterrupted { uid } => {
                 let current_time = get_current_time(state);
+                let tcp_state: &mut TcpState = state.substate_mut();
 
-                handle_recv_common(state.substate_mut(), dispatcher, current_time, uid, true)
+                tcp_state.complete_recv(&uid, 0);
+                handle_recv_common(tcp_state, dispatcher, current_time, uid, true)
             }
             TcpAction::RecvErrorTryAgain { uid } => {
                 let current_time = get_current_time(state);
+                let tcp_state: &mut TcpState = state.substate_mut();
 
-                handle_recv_common(state.substate_mut(), dispatcher, current_time, uid, false)
+                tcp_state.complete_recv(&uid, 0);
+                handle_recv_common(tcp_state, dispatcher, current_time, uid, false)
             }
             TcpAction::RecvError { uid, error } => {
+                let current_time = get_current_time(state);
                 let tcp_state = state.substate_mut::<TcpState>();
+                let connection = tcp_state.get_recv_request(&uid).connection;
+
+                tcp_state.log_connection(
+                    &connection,
+                    current_time,
+                    ConnectionLogEvent::RecvError(error.clone()),
+                );
+
+                // The read failed or hit the end of a closed connection.
+                if tcp_state.get_recv_request(&uid).draining {
+                    complete_drain(tcp_state, dispatcher, uid, error)
+                } else {
+                    dispatcher
+                        .dispatch_back(&tcp_state.get_recv_request(&uid).on_error, (uid, error));
+                    tcp_state.remove_recv_request(&uid);
+                }
+            }
+            TcpAction::RecvLine {
+                connection,
+                max_len,
+                timeout,
+                on_line,
+                on_error,
+            } => {
+                let timeout = get_timeout_absolute(state, timeout);
+                let current_time = get_current_time(state);
+                let uid = state.new_uid();
+                let tcp_state: &mut TcpState = state.substate_mut();
 
-                dispatcher.dispatch_back(&tcp_state.get_recv_request(&uid).on_error, (uid, error));
-                tcp_state.remove_recv_request(&uid)
+                if !tcp_state.has_connection(&connection) {
+                    dispatcher.dispatch_back(
+                        &on_error,
+                        (connection, format!("No such connection: {:?}", connection)),
+                    );
+                    return;
+                }
+
+                let buffered_data =
+                    std::mem::take(&mut tcp_state.get_connection_mut(&connection).line_buffer);
+
+                tcp_state.new_line_request(uid, connection, max_len, on_line, on_error);
+
+                // The line might have been received already by a previous read.
+                match split_line(buffered_data, max_len) {
+                    Line::Complete(line, rest) => {
+                        complete_line(tcp_state, dispatcher, uid, line, rest)
+                    }
+                    Line::Incomplete(buffered_data) => {
+                        tcp_state.new_recv_request(
+                            uid,
+                            RecvRequest::new(
+                                connection,
+                                max_len + LINE_DELIMITER.len() - buffered_data.len(),
+                                false,
+                                timeout,
+                                callback!(|(uid: Uid, data: Vec<u8>)| TcpAction::RecvLineSuccess { uid, data }),
+                                callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpAction::RecvLineTimeout { uid, partial_data }),
+                                callback!(|(uid: Uid, error: String)| TcpAction::RecvLineError { uid, error }),
+                            ),
+                        );
+
+                        let request = tcp_state.get_recv_request_mut(&uid);
+
+                        request.buffered_data = buffered_data;
+                        request.trace_id = dispatcher.trace_id;
+                        request.delimiter = Some(LINE_DELIMITER.to_vec());
+                        dispatch_recv(tcp_state, dispatcher, current_time, uid)
+                    }
+                    Line::Overflow => line_overflow(tcp_state, dispatcher, current_time, uid),
+                }
+            }
+            TcpAction::RecvLineSuccess { uid, data } => {
+                let current_time = get_current_time(state);
+                let tcp_state: &mut TcpState = state.substate_mut();
+                let max_len = tcp_state.get_line_request(&uid).max_len;
+
+                // The recv request completes on CRLF or once it received more
+                // than `max_len` bytes.
+                match split_line(data, max_len) {
+                    Line::Complete(line, rest) => {
+                        complete_line(tcp_state, dispatcher, uid, line, rest)
+                    }
+                    Line::Incomplete(_) => unreachable!(),
+                    Line::Overflow => line_overflow(tcp_state, dispatcher, current_time, uid),
+                }
+            }
+            TcpAction::RecvLineTimeout { uid, partial_data } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+                let LineRequest {
+                    connection,
+                    on_error,
+                    ..
+                } = tcp_state.take_line_request(&uid);
+
+                // Keep the partial line for the next `RecvLine`.
+                if tcp_state.has_connection(&connection) {
+                    tcp_state.get_connection_mut(&connection).line_buffer = partial_data;
+                }
+
+                dispatcher.dispatch_back(&on_error, (connection, "Timeout".to_string()));
+            }
+            TcpAction::RecvLineError { uid, error } => {
+                let LineRequest {
+                    connection,
+                    on_error,
+                    ..
+                } = state.substate_mut::<TcpState>().take_line_request(&uid);
+
+                dispatcher.dispatch_back(&on_error, (connection, error));
+            }
+            TcpAction::GetBufferStatus {
+                uid,
+                connection,
+                on_success,
+                on_error,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if !tcp_state.has_connection(&connection) {
+                    dispatcher.dispatch_back(
+                        &on_error,
+                        (uid, format!("No such connection: {:?}", connection)),
+                    );
+                } else {
+                    tcp_state.new_buffer_status_request(uid, connection, on_success, on_error);
+                    dispatcher.dispatch_effect(MioEffectfulAction::TcpGetBufferStatus {
+                        uid,
+                        connection,
+                        on_success: callback!(|(uid: Uid, send_queued: usize, recv_available: usize)| TcpAction::GetBufferStatusSuccess { uid, send_queued, recv_available }),
+                        on_error: callback!(|(uid: Uid, error: String)| TcpAction::GetBufferStatusError { uid, error }),
+                    });
+                }
+            }
+            TcpAction::GetBufferStatusSuccess {
+                uid,
+                send_queued,
+                recv_available,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if let Some(BufferStatusRequest {
+                    connection,
+                    on_success,
+                    ..
+                }) = tcp_state.take_buffer_status_request(&uid)
+                {
+                    tcp_state.get_connection_mut(&connection).buffer_status =
+                        Some((send_queued, recv_available));
+                    dispatcher.dispatch_back(&on_success, (uid, send_queued, recv_available));
+                }
+            }
+            TcpAction::GetBufferStatusError { uid, error } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if let Some(BufferStatusRequest { on_error, .. }) =
+                    tcp_state.take_buffer_status_request(&uid)
+                {
+                    dispatcher.dispatch_back(&on_error, (uid, error));
+                }
+            }
+            TcpAction::GetPeerAddress {
+                connection,
+                on_result,
+            } => {
+                let tcp_state: &TcpState = state.substate();
+
+                let result = if !tcp_state.has_connection(&connection) {
+                    Err(format!("No such connection: {:?}", connection))
+                } else {
+                    tcp_state
+                        .connection_addrs(&connection)
+                        .ok_or_else(|| format!("Connection not established: {:?}", connection))
+                };
+
+                dispatcher.dispatch_back(&on_result, (connection, result));
+            }
+            TcpAction::GetLocalAddress {
+                listener_or_connection: uid,
+                on_success,
+                on_error,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if !tcp_state.has_listener(&uid) && !tcp_state.has_connection(&uid) {
+                    let error = format!("No such listener or connection: {:?}", uid);
+
+                    dispatcher.dispatch_back(&on_error, (uid, error));
+                } else {
+                    tcp_state.new_local_address_request(uid, on_success, on_error);
+                    dispatcher.dispatch_effect(MioEffectfulAction::TcpGetLocalAddress {
+                        uid,
+                        on_result: callback!(|(uid: Uid, result: Result<String, String>)| TcpAction::GetLocalAddressResult { uid, result }),
+                    });
+                }
+            }
+            TcpAction::GetLocalAddressResult { uid, result } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if let Some(LocalAddressRequest {
+                    on_success,
+                    on_error,
+                }) = tcp_state.take_local_address_request(&uid)
+                {
+                    match result {
+                        Ok(address) => dispatcher.dispatch_back(&on_success, (uid, address)),
+                        Err(error) => dispatcher.dispatch_back(&on_error, (uid, error)),
+                    }
+                }
+            }
+            TcpAction::BytesAvailable {
+                connection,
+                on_result,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if !tcp_state.has_connection(&connection) {
+                    let error = format!("No such connection: {:?}", connection);
+
+                    dispatcher.dispatch_back(
+                        &on_result,
+                        (connection, BytesAvailableResult::Error(error)),
+                    );
+                } else {
+                    tcp_state.new_bytes_available_request(connection, on_result);
+                    dispatcher.dispatch_effect(MioEffectfulAction::TcpBytesAvailable {
+                        connection,
+                        on_success: callback!(|(connection: Uid, count: usize)| TcpAction::BytesAvailableSuccess { connection, count }),
+                        on_not_supported: callback!(|connection: Uid| TcpAction::BytesAvailableNotSupported { connection }),
+                        on_error: callback!(|(connection: Uid, error: String)| TcpAction::BytesAvailableError { connection, error }),
+                    });
+                }
+            }
+            TcpAction::BytesAvailableSuccess { connection, count } => bytes_available_result(
+                state.substate_mut(),
+                dispatcher,
+                connection,
+                BytesAvailableResult::Available(count),
+            ),
+            TcpAction::BytesAvailableNotSupported { connection } => bytes_available_result(
+                state.substate_mut(),
+                dispatcher,
+                connection,
+                BytesAvailableResult::NotSupported,
+            ),
+            TcpAction::BytesAvailableError { connection, error } => bytes_available_result(
+                state.substate_mut(),
+                dispatcher,
+                connection,
+                BytesAvailableResult::Error(error),
+            ),
+            TcpAction::Shutdown {
+                connection,
+                how,
+                on_success,
+                on_error,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if !tcp_state.has_connection(&connection) {
+                    dispatcher.dispatch_back(
+                        &on_error,
+                        (connection, format!("No such connection: {:?}", connection)),
+                    );
+                } else {
+                    tcp_state.new_shutdown_request(connection, how, on_success, on_error);
+                    dispatcher.dispatch_effect(MioEffectfulAction::TcpShutdown {
+                        connection,
+                        how,
+                        on_success: callback!(|connection: Uid| TcpAction::ShutdownSuccess { connection }),
+                        on_error: callback!(|(connection: Uid, error: String)| TcpAction::ShutdownError { connection, error }),
+                    });
+                }
+            }
+            TcpAction::ShutdownSuccess { connection } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if let Some(ShutdownRequest { how, on_success, .. }) =
+                    tcp_state.take_shutdown_request(&connection)
+                {
+                    let conn = tcp_state.get_connection_mut(&connection);
+
+                    // A close requested meanwhile takes precedence.
+                    if how != ShutdownHow::Read
+                        && matches!(conn.status, ConnectionStatus::Established)
+                    {
+                        conn.status = ConnectionStatus::WriteClosed;
+                    }
+
+                    dispatcher.dispatch_back(&on_success, connection);
+                }
+            }
+            TcpAction::ShutdownError { connection, error } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if let Some(ShutdownRequest { on_error, .. }) =
+                    tcp_state.take_shutdown_request(&connection)
+                {
+                    dispatcher.dispatch_back(&on_error, (connection, error));
+                }
+            }
+            TcpAction::SetNodelay {
+                connection,
+                value,
+                on_result,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if !tcp_state.has_connection(&connection) {
+                    let error = format!("No such connection: {:?}", connection);
+
+                    dispatcher.dispatch_back(&on_result, (connection, Err(error)));
+                } else {
+                    set_nodelay(tcp_state, dispatcher, connection, value, Some(on_result));
+                }
+            }
+            TcpAction::SetNodelayResult { connection, result } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if let Some(NodelayRequest { value, on_result }) =
+                    tcp_state.take_nodelay_request(&connection)
+                {
+                    let conn = tcp_state.get_connection_mut(&connection);
+
+                    match (&result, on_result) {
+                        (Ok(()), on_result) => {
+                            conn.nodelay = value;
+
+                            if let Some(on_result) = on_result {
+                                dispatcher.dispatch_back(&on_result, (connection, result));
+                            }
+                        }
+                        (Err(error), None) => {
+                            // Requested on connect/accept, not worth failing the connection.
+                            warn!(
+                                "|TCP| failed to set TCP_NODELAY on {:?}: {}",
+                                connection, error
+                            );
+                            conn.nodelay = false;
+                        }
+                        (Err(error), Some(on_result)) => {
+                            dispatcher.dispatch_back(&on_result, (connection, Err(error.clone())));
+                        }
+                    }
+                }
+            }
+            TcpAction::GetCongestion {
+                connection,
+                on_result,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if !tcp_state.has_connection(&connection) {
+                    let error = format!("No such connection: {:?}", connection);
+
+                    dispatcher.dispatch_back(&on_result, (connection, Err(error)));
+                } else {
+                    tcp_state.new_congestion_request(connection, on_result);
+                    dispatcher.dispatch_effect(MioEffectfulAction::TcpGetCongestion {
+                        connection,
+                        on_result: callback!(|(connection: Uid, result: Result<String, String>)| TcpAction::GetCongestionResult { connection, result }),
+                    });
+                }
+            }
+            TcpAction::GetCongestionResult { connection, result } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                if let Some(CongestionRequest { on_result }) =
+                    tcp_state.take_congestion_request(&connection)
+                {
+                    if let Ok(algorithm) = &result {
+                        tcp_state.get_connection_mut(&connection).congestion =
+                            Some(algorithm.clone());
+                    }
+
+                    dispatcher.dispatch_back(&on_result, (connection, result));
+                }
+            }
+            TcpAction::SetPriority {
+                connection,
+                priority,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                // The connection might have been closed meanwhile.
+                if tcp_state.has_connection(&connection) {
+                    tcp_state.set_priority(&connection, priority)
+                } else {
+                    warn!("|TCP| SetPriority on unknown connection {:?}", connection)
+                }
+            }
+            TcpAction::SetDeadline {
+                connection,
+                timeout,
+            } => {
+                let deadline = get_timeout_absolute(state, timeout);
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                // The connection might have been closed meanwhile.
+                if tcp_state.has_connection(&connection) {
+                    tcp_state.set_deadline(&connection, deadline)
+                } else {
+                    warn!("|TCP| SetDeadline on unknown connection {:?}", connection)
+                }
+            }
+            TcpAction::SetWeight { connection, weight } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                // The connection might have been closed meanwhile.
+                if tcp_state.has_connection(&connection) {
+                    tcp_state.set_weight(&connection, weight)
+                } else {
+                    warn!("|TCP| SetWeight on unknown connection {:?}", connection)
+                }
+            }
+            TcpAction::SetRateSchedule {
+                connection,
+                schedule,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                // The connection might have been closed meanwhile.
+                if tcp_state.has_connection(&connection) {
+                    tcp_state.set_rate_schedule(&connection, schedule)
+                } else {
+                    warn!("|TCP| SetRateSchedule on unknown connection {:?}", connection)
+                }
+            }
+            TcpAction::WatchFdExhaustion {
+                on_exhausted,
+                on_recovered,
+            } => {
+                state.substate_mut::<TcpState>().fd_exhaustion_watcher = Some(FdExhaustionWatcher {
+                    on_exhausted,
+                    on_recovered,
+                })
+            }
+            TcpAction::SetOperationLog {
+                connection,
+                enabled,
+            } => {
+                let tcp_state: &mut TcpState = state.substate_mut();
+
+                // The connection might have been closed meanwhile.
+                if tcp_state.has_connection(&connection) {
+                    tcp_state.set_operation_log(&connection, enabled)
+                } else {
+                    warn!("|TCP| SetOperationLog on unknown connection {:?}", connection)
+                }
+            }
+            TcpAction::Probe {
+                connection,
+                address,
+                timeout,
+                on_result,
+            } => {
+                state
+                    .substate_mut::<TcpState>()
+                    .new_probe_request(connection, on_result);
+                dispatcher.dispatch(TcpAction::Connect {
+                    connection,
+                    address,
+                    options: None,
+                    bind_address: None,
+                    nodelay: false,
+                    timeout,
+                    on_success: callback!(|connection: Uid| TcpAction::ProbeConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| TcpAction::ProbeConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| TcpAction::ProbeConnectError { connection, error }),
+                });
+            }
+            TcpAction::ProbeConnectSuccess { connection } => {
+                close_probe(state, dispatcher, connection, ProbeResult::Reachable)
+            }
+            TcpAction::ProbeConnectTimeout { connection } => {
+                // The connection is already being closed.
+                let ProbeRequest { on_result, .. } = state
+                    .substate_mut::<TcpState>()
+                    .take_probe_request(&connection);
+
+                dispatcher.dispatch_back(&on_result, (connection, ProbeResult::Timeout))
+            }
+            TcpAction::ProbeConnectError { connection, error } => {
+                // The connection was already removed.
+                let ProbeRequest { on_result, .. } = state
+                    .substate_mut::<TcpState>()
+                    .take_probe_request(&connection);
+
+                dispatcher.dispatch_back(&on_result, (connection, ProbeResult::Refused(error)))
+            }
+            TcpAction::ProbeCloseSuccess { connection } => {
+                let ProbeRequest { on_result, result } = state
+                    .substate_mut::<TcpState>()
+                    .take_probe_request(&connection);
+
+                dispatcher.dispatch_back(
+                    &on_result,
+                    (connection, result.expect("Probe closed without result")),
+                )
+            }
+            TcpAction::Validate => {
+                if let Err(errors) = state.substate::<TcpState>().validate() {
+                    panic!("Inconsistent TcpState: {:#?}", errors)
+                }
+            }
+            #[cfg(test)]
+            TcpAction::ExpireRequest { uid } => {
+                expire_request(state.substate_mut::<TcpState>(), dispatcher, uid)
             }
         }
     }
 }
+
+// The result of a probe is reported once its connection is closed.
+fn close_probe<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    connection: Uid,
+    result: ProbeResult,
+) {
+    state
+        .substate_mut::<TcpState>()
+        .get_probe_request_mut(&connection)
+        .result = Some(result);
+    dispatcher.dispatch(TcpAction::Close {
+        connection,
+        deliver_buffered: false,
+        on_success: callback!(|connection: Uid| TcpAction::ProbeCloseSuccess { connection }),
+    })
+}
+
+// If the listen operation was successful we register the listener in the MIO poll object.
+fn register_listener(tcp_state: &TcpState, dispatcher: &mut Dispatcher, listener: Uid) {
+    if let Status::Ready { poll, .. } = tcp_state.status {
+        dispatcher.dispatch_effect(MioEffectfulAction::PollRegisterTcpServer {
+            poll,
+            listener,
+            on_success: callback!(|listener: Uid| TcpAction::RegisterListenerSuccess { listener }),
+            on_error: callback!(|(listener: Uid, error: String)| TcpAction::RegisterListenerError { listener, error }),
+        });
+    } else {
+        unreachable!()
+    };
+}
+
+fn set_nodelay(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    connection: Uid,
+    value: bool,
+    on_result: Option<Redispatch<(Uid, Result<(), String>)>>,
+) {
+    tcp_state.new_nodelay_request(connection, value, on_result);
+    dispatcher.dispatch_effect(MioEffectfulAction::TcpSetNodelay {
+        connection,
+        value,
+        on_result: callback!(|(connection: Uid, result: Result<(), String>)| TcpAction::SetNodelayResult { connection, result }),
+    });
+}
+
+fn bytes_available_result(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    connection: Uid,
+    result: BytesAvailableResult,
+) {
+    if let Some(on_result) = tcp_state.take_bytes_available_request(&connection) {
+        dispatcher.dispatch_back(&on_result, (connection, result))
+    }
+}
+
+// Enters (or extends) the degraded mode if `error` is an fd-exhaustion error,
+// see `FdExhaustion`.
+fn on_fd_error<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    error: &str,
+) {
+    if !is_fd_exhaustion_error(error) {
+        return;
+    }
+
+    let current_time = get_current_time(state);
+    let tcp_state: &mut TcpState = state.substate_mut();
+
+    if tcp_state.fd_exhausted(current_time) {
+        warn!("|TCP| file descriptors exhausted, holding back accepts: {}", error);
+        notify_fd_exhaustion(state, dispatcher, true)
+    }
+}
+
+fn on_fd_available<Substate: ModelState>(state: &mut State<Substate>, dispatcher: &mut Dispatcher) {
+    if state.substate_mut::<TcpState>().fd_available() {
+        info!("|TCP| file descriptors available again");
+        notify_fd_exhaustion(state, dispatcher, false)
+    }
+}
+
+fn notify_fd_exhaustion<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    exhausted: bool,
+) {
+    let tcp_state: &TcpState = state.substate();
+
+    if let (Some(watcher), Status::Ready { instance, .. }) =
+        (&tcp_state.fd_exhaustion_watcher, &tcp_state.status)
+    {
+        let on_event = if exhausted {
+            &watcher.on_exhausted
+        } else {
+            &watcher.on_recovered
+        };
+
+        dispatcher.dispatch_back(on_event, *instance)
+    }
+
+    state.counter_add(
+        "tcp_fd_exhaustion_total",
+        "Number of times the TCP model entered and left the fd-exhaustion degraded mode.",
+        &[("event", if exhausted { "exhausted" } else { "recovered" })],
+        1.0,
+    )
+}
+
+fn count_bytes_sent<Substate: ModelState>(state: &mut State<Substate>, count: usize) {
+    state.counter_add(
+        "tcp_bytes_sent_total",
+        "Number of bytes written to TCP connections.",
+        &[],
+        count as f64,
+    )
+}
+
+fn count_bytes_received<Substate: ModelState>(state: &mut State<Substate>, count: usize) {
+    state.counter_add(
+        "tcp_bytes_received_total",
+        "Number of bytes read from TCP connections.",
+        &[],
+        count as f64,
+    )
+}
+
+// Sampled on every poll.
+fn update_connection_metrics<Substate: ModelState>(state: &mut State<Substate>) {
+    for (status, count) in state.substate::<TcpState>().connection_counts() {
+        state.gauge_set(
+            "tcp_connections",
+            "Number of TCP connections by state.",
+            &[("state", status)],
+            count as f64,
+        )
+    }
+}
@@ -1,14 +1,21 @@
-use super::action::{ConnectionEvent, Event, ListenerEvent, TcpPollEvents};
+use super::action::{
+    BytesAvailableResult, ConnectionEvent, Event, ListenerEvent, ProbeResult, RecvResult,
+    SendResult, TcpPollEvents,
+};
 use crate::{
     automaton::{
         action::{self, Redispatch, Timeout, TimeoutAbsolute},
         state::{Objects, Uid},
     },
-    models::effectful::mio::action::MioEvent,
+    models::effectful::mio::action::{Interest, MioEvent, ShutdownHow},
 };
 use core::panic;
 use serde_derive::{Deserialize, Serialize};
-use std::rc::Rc;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
+    rc::Rc,
+};
 
 pub trait EventUpdater {
     type Event;
@@ -23,6 +30,11 @@ pub struct Listener {
     pub on_success: Redispatch<Uid>,
     pub on_error: Redispatch<(Uid, String)>,
     pub events: Option<ListenerEvent>,
+    // When the listener became readable with connections pending, cleared
+    // once they are all accepted (see `TcpState::record_accept_latency`).
+    pub readable_at: Option<u128>,
+    // Connections accepted so far, see `Connection::number`.
+    pub accepted: u64,
 }
 
 impl Listener {
@@ -36,6 +48,8 @@ impl Listener {
             on_success,
             on_error,
             events: None,
+            readable_at: None,
+            accepted: 0,
         }
     }
 }
@@ -104,6 +118,7 @@ impl PollRequest {
 pub enum ConnectionType {
     Incoming {
         listener: Uid,
+        register_delay: Option<u64>,
         on_success: Redispatch<Uid>,
         on_would_block: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
@@ -138,6 +153,210 @@ pub enum ConnectionStatus {
     Established,
     CloseRequestInternal,
     CloseRequestNotify { on_success: Redispatch<Uid> },
+    // Our write side was shut down, see `TcpAction::Shutdown`.
+    WriteClosed,
+}
+
+impl ConnectionStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConnectionStatus::Pending => "pending",
+            ConnectionStatus::PendingCheck => "pending check",
+            ConnectionStatus::Established => "established",
+            ConnectionStatus::CloseRequestInternal
+            | ConnectionStatus::CloseRequestNotify { .. } => "closing",
+            ConnectionStatus::WriteClosed => "write closed",
+        }
+    }
+}
+
+// Limits the number of bytes transferred per one-second window, following a
+// schedule of (at_time, bytes_per_sec) entries sorted by `at_time`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RateShaper {
+    schedule: Vec<(u128, u64)>,
+    window_start: u128,
+    window_bytes: u64,
+}
+
+impl RateShaper {
+    pub fn new(mut schedule: Vec<(u128, u64)>) -> Self {
+        schedule.sort_by_key(|(at_time, _)| *at_time);
+
+        Self {
+            schedule,
+            window_start: 0,
+            window_bytes: 0,
+        }
+    }
+
+    pub fn rate(&self, current_time: u128) -> Option<u64> {
+        self.schedule
+            .iter()
+            .rev()
+            .find(|(at_time, _)| *at_time <= current_time)
+            .map(|(_, bytes_per_sec)| *bytes_per_sec)
+    }
+
+    // Reserves up to `count` bytes from the current window, returns the number
+    // of bytes that can be transferred now.
+    pub fn reserve(&mut self, current_time: u128, count: usize) -> usize {
+        let Some(rate) = self.rate(current_time) else {
+            return count;
+        };
+
+        if current_time >= self.window_start + 1000 {
+            self.window_start = current_time;
+            self.window_bytes = 0;
+        }
+
+        let allowed = count.min(rate.saturating_sub(self.window_bytes) as usize);
+
+        self.window_bytes += allowed as u64;
+        allowed
+    }
+
+    // Gives back bytes that were reserved but not transferred.
+    pub fn refund(&mut self, count: usize) {
+        self.window_bytes = self.window_bytes.saturating_sub(count as u64);
+    }
+}
+
+// Capacity of the poll events buffer, unless set by `TcpAction::Init`.
+pub const DEFAULT_EVENTS_CAPACITY: usize = 1024;
+
+// Maximum number of entries kept in `Connection::history`; older entries are
+// dropped first.
+pub const CONNECTION_HISTORY_LEN: usize = 32;
+// Number of removed connections whose history and last error are kept, see
+// `TcpState::connection_history`.
+pub const CLOSED_CONNECTION_HISTORY_LEN: usize = 16;
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum ConnectionLogEvent {
+    Connecting,
+    Accepted { number: u64 },
+    Registered,
+    RegisterError(String),
+    Connected,
+    Ready { readable: bool, writable: bool },
+    Closed,
+    Error,
+    PartialWrite { written: usize, len: usize },
+    PartialRead { count: usize },
+    SendError(String),
+    RecvError(String),
+    CloseRequested,
+}
+
+impl ConnectionLogEvent {
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            Self::RegisterError(error) | Self::SendError(error) | Self::RecvError(error) => {
+                Some(error)
+            }
+            Self::Error => Some("Connection error"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct ConnectionLogEntry {
+    pub time: u128,
+    pub event: ConnectionLogEvent,
+}
+
+// "<time>: <event>" entries, comma-separated. Errors reported to callers don't
+// include the history: it's queried with `TcpState::connection_history`
+// (which keeps it for a while after the connection is removed) and formatted
+// with this to be logged along with the error.
+pub fn history_summary(history: &[ConnectionLogEntry]) -> String {
+    history
+        .iter()
+        .map(|entry| format!("{}: {:?}", entry.time, entry.event))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// Number of leading bytes of each operation kept in `Operation::preview`.
+pub const OPERATION_PREVIEW_LEN: usize = 16;
+
+// Virtual time taken by a write opportunity of a weight 1 connection, see
+// `TcpState::schedule_writes`.
+const WRITE_QUANTUM: u64 = 1 << 16;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum OperationKind {
+    Send,
+    Recv,
+}
+
+// A completed application-level send or recv request, see
+// `TcpAction::SetOperationLog`.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct Operation {
+    pub time: u128,
+    pub kind: OperationKind,
+    pub len: usize,
+    // Hex of the first `OPERATION_PREVIEW_LEN` bytes.
+    pub preview: String,
+}
+
+impl Operation {
+    pub fn new(time: u128, kind: OperationKind, data: &[u8]) -> Self {
+        let preview = data
+            .iter()
+            .take(OPERATION_PREVIEW_LEN)
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        Self {
+            time,
+            kind,
+            len: data.len(),
+            preview,
+        }
+    }
+}
+
+// One line per operation: "<time> <Send|Recv> <len> <preview>", with a
+// trailing ".." if the preview is truncated.
+pub fn operation_transcript(operations: &[Operation]) -> String {
+    operations
+        .iter()
+        .map(|operation| {
+            let truncated = if operation.len > OPERATION_PREVIEW_LEN {
+                ".."
+            } else {
+                ""
+            };
+
+            format!(
+                "{} {:?} {} {}{}\n",
+                operation.time, operation.kind, operation.len, operation.preview, truncated
+            )
+        })
+        .collect()
+}
+
+// What is kept of a connection once it's removed.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClosedConnection {
+    pub uid: Uid,
+    pub history: Vec<ConnectionLogEntry>,
+    pub last_error: Option<String>,
+    pub failed_send: Option<(Uid, SendErrorKind)>,
+    pub operation_log: Option<Vec<Operation>>,
+}
+
+// Data moved on a connection so far, see `TcpState::connection_stats`.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct ConnectionStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    // `None` until the connection is established (or accepted).
+    pub established_at: Option<u128>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -146,10 +365,78 @@ pub struct Connection {
     pub conn_type: ConnectionType,
     pub timeout: TimeoutAbsolute,
     pub events: Option<ConnectionEvent>,
+    // Creation sequence number, see `TcpState::next_seq`.
+    pub seq: u64,
+    // Last observed (send queued, recv available) byte counts of the OS socket
+    // buffers, updated by `TcpAction::GetBufferStatus`.
+    pub buffer_status: Option<(usize, usize)>,
+    // Set by `TcpAction::SetRateSchedule`.
+    pub send_shaper: Option<RateShaper>,
+    pub recv_shaper: Option<RateShaper>,
+    // (local, peer) socket addresses, set once the connection is established.
+    pub addrs: Option<(String, String)>,
+    // Failed poll registrations so far, and when the next one is due (see
+    // `TcpConfig::register_retries`).
+    pub register_attempts: u32,
+    pub register_retry_at: Option<u128>,
+    // Most recent state transitions, bounded by `CONNECTION_HISTORY_LEN`.
+    pub history: Vec<ConnectionLogEntry>,
+    // Readiness last logged by `TcpState::update_events`: polls reporting
+    // the same readiness again are not logged.
+    pub readiness: Option<ConnectionLogEvent>,
+    // Last error logged for the connection, usually the reason it's closed.
+    pub last_error: Option<String>,
+    // Last send request that failed, and why.
+    pub failed_send: Option<(Uid, SendErrorKind)>,
+    // Data received past the end of the last line, see `TcpAction::RecvLine`.
+    pub line_buffer: Vec<u8>,
+    // Incoming connections: milliseconds between the listener becoming
+    // readable and the connection being accepted.
+    pub accept_latency: Option<u128>,
+    // Completed send/recv requests, in order. `None` unless enabled with
+    // `TcpAction::SetOperationLog`.
+    pub operation_log: Option<Vec<Operation>>,
+    // Within a poll cycle, events of higher priority connections are processed
+    // first. Set by `TcpAction::SetPriority`.
+    pub priority: u8,
+    // Share of the write opportunities of a poll, relative to the other
+    // connections (see `TcpConfig::writes_per_poll`). Set by
+    // `TcpAction::SetWeight`, connections start at weight 1.
+    pub weight: u32,
+    // Virtual time at which the connection's last write opportunity ended.
+    pub virtual_finish: u64,
+    // Incoming connections: 1, 2, 3... in the order the listener accepted
+    // them. Unlike the `Uid`, it doesn't depend on the other objects
+    // allocated meanwhile, so it's easier to follow in logs.
+    pub number: Option<u64>,
+    // TCP_NODELAY setting, requested with `TcpAction::Connect`/`Accept` and
+    // updated by `TcpAction::SetNodelay`. Cleared if it couldn't be applied.
+    pub nodelay: bool,
+    // Congestion control algorithm, as last reported by
+    // `TcpAction::GetCongestion`.
+    pub congestion: Option<String>,
+    // Send and recv requests on the connection time out no later than this,
+    // see `TcpAction::SetDeadline`.
+    pub deadline: TimeoutAbsolute,
+    // Pending SendRequests in creation order. Only the head is written, so
+    // that the data of back-to-back sends doesn't interleave.
+    pub send_queue: VecDeque<Uid>,
+    // Bytes written to and read from the connection so far, and when it was
+    // established.
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub established_at: Option<u128>,
+    // Readiness the connection is polled for, set by `TcpAction::Poll`, and
+    // the one it was last (re-)registered with, if any.
+    pub interest: Interest,
+    pub registered_interest: Option<Interest>,
+    // The poll the connection is registered with, see
+    // `TcpState::connection_poll`.
+    pub poll: Option<Uid>,
 }
 
 impl Connection {
-    pub fn new(conn_type: ConnectionType, timeout: TimeoutAbsolute) -> Self {
+    pub fn new(conn_type: ConnectionType, timeout: TimeoutAbsolute, seq: u64) -> Self {
         let status = match conn_type {
             ConnectionType::Outgoing { .. } => ConnectionStatus::Pending,
             ConnectionType::Incoming { .. } => ConnectionStatus::Established,
@@ -160,8 +447,60 @@ impl Connection {
             conn_type,
             timeout,
             events: None,
+            seq,
+            buffer_status: None,
+            send_shaper: None,
+            recv_shaper: None,
+            addrs: None,
+            register_attempts: 0,
+            register_retry_at: None,
+            history: Vec::new(),
+            readiness: None,
+            last_error: None,
+            failed_send: None,
+            line_buffer: Vec::new(),
+            accept_latency: None,
+            operation_log: None,
+            priority: 0,
+            weight: 1,
+            virtual_finish: 0,
+            number: None,
+            nodelay: false,
+            congestion: None,
+            deadline: TimeoutAbsolute::Never,
+            send_queue: VecDeque::new(),
+            bytes_sent: 0,
+            bytes_received: 0,
+            established_at: None,
+            interest: Interest::Both,
+            registered_interest: None,
+            poll: None,
         }
     }
+
+    // Sort key of the pending connections (and their requests) processed on
+    // each poll: highest priority first, then creation order.
+    pub fn service_order(&self) -> (Reverse<u8>, u64) {
+        (Reverse(self.priority), self.seq)
+    }
+
+    // Shutting down our own write side makes the poll report `write_closed`,
+    // which must not be taken for the connection being closed.
+    pub fn write_shut_down(&self) -> bool {
+        matches!(self.status, ConnectionStatus::WriteClosed)
+    }
+
+    pub fn log(&mut self, time: u128, event: ConnectionLogEvent) {
+        if let Some(error) = event.error() {
+            self.last_error = Some(error.to_string());
+        }
+
+        if self.history.len() == CONNECTION_HISTORY_LEN {
+            self.history.remove(0);
+        }
+
+        self.history.push(ConnectionLogEntry { time, event });
+    }
 }
 
 impl EventUpdater for Connection {
@@ -174,7 +513,9 @@ impl EventUpdater for Connection {
                 read_closed,
                 write_closed,
                 ..
-            } if *read_closed || *write_closed => ConnectionEvent::Closed,
+            } if *read_closed || (*write_closed && !self.write_shut_down()) => {
+                ConnectionEvent::Closed
+            }
             MioEvent {
                 readable, writable, ..
             } => ConnectionEvent::Ready {
@@ -217,6 +558,42 @@ impl EventUpdater for Connection {
     }
 }
 
+// The period of a `Timeout::Inactivity` request, and how many bytes it had
+// transferred when its deadline was last set.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct InactivityTimeout {
+    pub period: u64,
+    pub progress: usize,
+}
+
+impl InactivityTimeout {
+    pub fn new(period: u64) -> Self {
+        Self {
+            period,
+            progress: 0,
+        }
+    }
+
+    // The new deadline (no later than `limit`), if the request transferred
+    // data since the last one.
+    fn reset(
+        &mut self,
+        current_time: u128,
+        progress: usize,
+        limit: TimeoutAbsolute,
+    ) -> Option<TimeoutAbsolute> {
+        if progress <= self.progress {
+            return None;
+        }
+
+        self.progress = progress;
+
+        let deadline = TimeoutAbsolute::Millis(current_time.saturating_add(self.period.into()));
+
+        Some(deadline.earliest(limit))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SendRequest {
     pub connection: Uid,
@@ -226,11 +603,20 @@ pub struct SendRequest {
     )]
     pub data: Rc<[u8]>,
     pub bytes_sent: usize,
+    // Length of the in-flight write (bounded by the connection's `send_shaper`).
+    pub write_len: usize,
     pub send_on_poll: bool,
     pub timeout: TimeoutAbsolute,
+    pub inactivity: Option<InactivityTimeout>,
+    pub seq: u64,
+    // The trace id of the `Send` action, restored for the writes issued from
+    // a later poll, see `Dispatcher::with_trace_id`.
+    pub trace_id: Option<u64>,
     pub on_success: Redispatch<Uid>,
     pub on_timeout: Redispatch<Uid>,
     pub on_error: Redispatch<(Uid, String)>,
+    // See `TcpAction::Send::on_cancelled`.
+    pub on_cancelled: Option<Redispatch<(Uid, SendResult)>>,
 }
 
 impl SendRequest {
@@ -247,25 +633,110 @@ impl SendRequest {
             connection,
             data,
             bytes_sent: 0,
+            write_len: 0,
             send_on_poll,
             timeout,
+            inactivity: None,
+            seq: 0,
+            trace_id: None,
             on_success,
             on_timeout,
             on_error,
+            on_cancelled: None,
+        }
+    }
+
+    // Pushes the deadline of a `Timeout::Inactivity` request back if it wrote
+    // data since the deadline was set, but not past `limit`.
+    pub fn reset_inactivity_deadline(&mut self, current_time: u128, limit: TimeoutAbsolute) {
+        if let Some(timeout) = self
+            .inactivity
+            .as_mut()
+            .and_then(|inactivity| inactivity.reset(current_time, self.bytes_sent, limit))
+        {
+            self.timeout = timeout
+        }
+    }
+
+    // Kind of the errors caused by the connection being lost.
+    pub fn error_kind(&self) -> SendErrorKind {
+        if self.bytes_sent == 0 {
+            SendErrorKind::ConnectionLost
+        } else {
+            SendErrorKind::PartiallySent {
+                bytes_sent: self.bytes_sent,
+            }
+        }
+    }
+
+    // Errors reported after part of the data was already written are tagged,
+    // so upper layers can tell that re-sending the data could duplicate it.
+    pub fn error_message(&self, error: String) -> String {
+        if self.bytes_sent == 0 {
+            error
+        } else {
+            format!(
+                "{} {} {} bytes written)",
+                error, PARTIAL_SEND_TAG, self.bytes_sent
+            )
         }
     }
 }
 
+const PARTIAL_SEND_TAG: &str = "(partial send:";
+
+// Why a send request failed, see `TcpState::send_error_kind`.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum SendErrorKind {
+    // The connection was closed by the peer or failed (e.g. it was reset)
+    // before any of the data was written.
+    ConnectionLost,
+    // Like `ConnectionLost`, after `bytes_sent` bytes of the data were
+    // written.
+    PartiallySent { bytes_sent: usize },
+    // The write side of the connection was shut down, see
+    // `TcpAction::Shutdown`.
+    WriteShutDown,
+    // The connection doesn't exist (anymore).
+    NoConnection,
+}
+
+impl SendErrorKind {
+    // Whether the data can be sent again on a new connection without the peer
+    // receiving any of it twice.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::ConnectionLost | Self::NoConnection)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RecvRequest {
     pub connection: Uid,
     pub buffered_data: Vec<u8>,
     pub remaining_bytes: usize,
+    // Length of the in-flight read (bounded by the connection's `recv_shaper`).
+    pub read_len: usize,
     pub recv_on_poll: bool,
+    // Set once the connection was closed by the peer: the request reads what
+    // is left in the socket before the closure is reported, see
+    // `util::complete_drain`.
+    pub draining: bool,
+    // If set, the request completes as soon as the buffered data contains
+    // the delimiter, even if fewer than the requested bytes were received.
+    pub delimiter: Option<Vec<u8>>,
+    // Length of the buffered data already searched for the delimiter, so
+    // each read only scans the newly appended bytes.
+    delimiter_scanned: usize,
     pub timeout: TimeoutAbsolute,
+    pub inactivity: Option<InactivityTimeout>,
+    pub seq: u64,
+    // Same as `SendRequest::trace_id`.
+    pub trace_id: Option<u64>,
     pub on_success: Redispatch<(Uid, Vec<u8>)>,
     pub on_timeout: Redispatch<(Uid, Vec<u8>)>,
     pub on_error: Redispatch<(Uid, String)>,
+    // See `TcpAction::Recv::on_cancelled`.
+    pub on_cancelled: Option<Redispatch<(Uid, RecvResult)>>,
 }
 
 impl RecvRequest {
@@ -282,15 +753,131 @@ impl RecvRequest {
             connection,
             buffered_data: Vec::new(),
             remaining_bytes: count,
+            read_len: 0,
             recv_on_poll,
+            draining: false,
+            delimiter: None,
+            delimiter_scanned: 0,
             timeout,
+            inactivity: None,
+            seq: 0,
+            trace_id: None,
             on_success,
             on_timeout,
             on_error,
+            on_cancelled: None,
+        }
+    }
+
+    // Pushes the deadline of a `Timeout::Inactivity` request back if it read
+    // data since the deadline was set, but not past `limit`.
+    pub fn reset_inactivity_deadline(&mut self, current_time: u128, limit: TimeoutAbsolute) {
+        if let Some(timeout) = self
+            .inactivity
+            .as_mut()
+            .and_then(|inactivity| inactivity.reset(current_time, self.buffered_data.len(), limit))
+        {
+            self.timeout = timeout
+        }
+    }
+
+    pub fn is_complete(&mut self) -> bool {
+        if self.remaining_bytes == 0 {
+            return true;
+        }
+
+        let Some(delimiter) = self.delimiter.as_ref() else {
+            return false;
+        };
+        // The delimiter might straddle the previously scanned bytes and the
+        // new ones.
+        let start = self
+            .delimiter_scanned
+            .saturating_sub(delimiter.len().saturating_sub(1));
+
+        self.delimiter_scanned = self.buffered_data.len();
+        find_delimiter(&self.buffered_data[start..], delimiter).is_some()
+    }
+}
+
+pub fn find_delimiter(data: &[u8], delimiter: &[u8]) -> Option<usize> {
+    data.windows(delimiter.len())
+        .position(|window| window == delimiter)
+}
+
+// Keyed by the `Uid` of the `RecvRequest` reading the line.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LineRequest {
+    pub connection: Uid,
+    pub max_len: usize,
+    pub on_line: Redispatch<(Uid, Vec<u8>)>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+pub const LINE_DELIMITER: &[u8] = b"\r\n";
+
+pub enum Line {
+    // The line (without CRLF) and the data following it.
+    Complete(Vec<u8>, Vec<u8>),
+    Incomplete(Vec<u8>),
+    // Longer than the maximum length.
+    Overflow,
+}
+
+pub fn split_line(mut data: Vec<u8>, max_len: usize) -> Line {
+    match find_delimiter(&data, LINE_DELIMITER) {
+        Some(len) if len <= max_len => {
+            let rest = data.split_off(len + LINE_DELIMITER.len());
+
+            data.truncate(len);
+            Line::Complete(data, rest)
         }
+        Some(_) => Line::Overflow,
+        // A trailing CR could still be followed by LF.
+        None if data.len() > max_len + 1 => Line::Overflow,
+        None => Line::Incomplete(data),
     }
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BufferStatusRequest {
+    pub connection: Uid,
+    pub on_success: Redispatch<(Uid, usize, usize)>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShutdownRequest {
+    pub how: ShutdownHow,
+    pub on_success: Redispatch<Uid>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+// `on_result` is `None` when the setting was requested on connect/accept.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NodelayRequest {
+    pub value: bool,
+    pub on_result: Option<Redispatch<(Uid, Result<(), String>)>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CongestionRequest {
+    pub on_result: Redispatch<(Uid, Result<String, String>)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LocalAddressRequest {
+    pub on_success: Redispatch<(Uid, String)>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProbeRequest {
+    pub on_result: Redispatch<(Uid, ProbeResult)>,
+    // Reported once the probe connection is closed.
+    pub result: Option<ProbeResult>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Status {
     New,
@@ -300,6 +887,7 @@ pub enum Status {
     InitPollCreate {
         instance: Uid,
         poll: Uid,
+        events_capacity: usize,
         on_success: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     },
@@ -307,6 +895,7 @@ pub enum Status {
         instance: Uid,
         poll: Uid,
         events: Uid,
+        events_capacity: usize,
         on_success: Redispatch<Uid>,
     },
     Ready {
@@ -316,32 +905,227 @@ pub enum Status {
     },
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TcpConfig {
+    // Number of times the poll registration of a connection is retried when it
+    // fails with a transient error, before closing the connection.
+    pub register_retries: u32,
+    // Delay (in milliseconds) before the first registration retry, doubled on
+    // each subsequent attempt.
+    pub register_backoff: u64,
+    // While file descriptors are exhausted, accepts are held back for this
+    // many milliseconds after each failure, see `FdExhaustion`.
+    pub fd_exhaustion_backoff: u64,
+    // Maximum number of writes of pending send requests started per poll.
+    // When more connections are writable, the writes are shared according to
+    // the connections' `weight` (weighted fair queuing). `None` starts them
+    // all.
+    pub writes_per_poll: Option<usize>,
+    // On a `Uid` collision in `new_listener`, `new_connection` or
+    // `new_send_request`, include what the existing object is in the panic
+    // message (see `TcpState::describe_object`). Objects of any kind are
+    // checked, so re-using a listener's `Uid` for a connection is caught too.
+    pub reuse_diagnostics: bool,
+}
+
+impl Default for TcpConfig {
+    fn default() -> Self {
+        Self {
+            register_retries: 0,
+            register_backoff: 10,
+            fd_exhaustion_backoff: 100,
+            writes_per_poll: None,
+            reuse_diagnostics: false,
+        }
+    }
+}
+
+// Registration errors caused by temporary resource exhaustion, that could
+// succeed if tried again later.
+pub fn is_transient_register_error(error: &str) -> bool {
+    const TRANSIENT_ERRORS: [&str; 4] = [
+        "Resource temporarily unavailable",
+        "Interrupted system call",
+        "Cannot allocate memory",
+        "No space left on device",
+    ];
+
+    TRANSIENT_ERRORS
+        .iter()
+        .any(|transient| error.starts_with(transient))
+}
+
+// Accept and connect errors caused by the process (or system) running out of
+// file descriptors.
+pub fn is_fd_exhaustion_error(error: &str) -> bool {
+    // EMFILE and ENFILE
+    error.starts_with("Too many open files")
+}
+
+// Degraded mode, entered when an accept or connect fails with an
+// fd-exhaustion error. New connections are not accepted until `retry_at`:
+// the next accept then probes whether descriptors are available again. The
+// first accept or connect that succeeds ends the degraded mode.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FdExhaustion {
+    pub since: u128,
+    pub retry_at: u128,
+}
+
+// See `TcpAction::WatchFdExhaustion`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FdExhaustionWatcher {
+    pub on_exhausted: Redispatch<Uid>,
+    pub on_recovered: Redispatch<Uid>,
+}
+
+// Accept latencies of all the connections accepted so far, in milliseconds.
+// It depends on the poll cadence: connections are accepted after the poll
+// reporting their listener readable.
+#[derive(Default, Clone, Serialize, Deserialize, Debug)]
+pub struct AcceptLatencyStats {
+    pub count: u64,
+    pub total: u128,
+    pub max: u128,
+}
+
+impl AcceptLatencyStats {
+    pub fn mean(&self) -> Option<u128> {
+        (self.count != 0).then(|| self.total / self.count as u128)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct TcpState {
     pub status: Status,
+    pub config: TcpConfig,
     listener_objects: Objects<Listener>,
     connection_objects: Objects<Connection>,
     poll_request_objects: Objects<PollRequest>,
     send_request_objects: Objects<SendRequest>,
     recv_request_objects: Objects<RecvRequest>,
+    buffer_status_request_objects: Objects<BufferStatusRequest>,
+    // Keyed by connection, queries on the same connection complete in order.
+    bytes_available_request_objects: Objects<VecDeque<Redispatch<(Uid, BytesAvailableResult)>>>,
+    // Keyed by connection, in request order, see `TcpAction::Shutdown`.
+    shutdown_request_objects: Objects<VecDeque<ShutdownRequest>>,
+    // Keyed by connection, in request order, see `TcpAction::SetNodelay`.
+    nodelay_request_objects: Objects<VecDeque<NodelayRequest>>,
+    // Keyed by connection, see `TcpAction::GetCongestion`.
+    congestion_request_objects: Objects<VecDeque<CongestionRequest>>,
+    // Keyed by listener or connection, see `TcpAction::GetLocalAddress`.
+    local_address_request_objects: Objects<VecDeque<LocalAddressRequest>>,
+    // Keyed by the probe connection's `Uid`, see `TcpAction::Probe`.
+    probe_request_objects: Objects<ProbeRequest>,
+    line_request_objects: Objects<LineRequest>,
+    // Monotonic counter used to stamp connections and send/recv requests with
+    // their creation order. Sweeps over pending objects process them in this
+    // order, so when several deadlines expire at once the first requested is
+    // the first to time out, regardless of `Uid` values.
+    seq: u64,
+    // Virtual time of the weighted fair queuing of writes, see
+    // `TcpState::schedule_writes`.
+    virtual_time: u64,
+    // The most recently removed connections, oldest first.
+    closed_connections: VecDeque<ClosedConnection>,
+    accept_latency: AcceptLatencyStats,
+    fd_exhaustion: Option<FdExhaustion>,
+    pub fd_exhaustion_watcher: Option<FdExhaustionWatcher>,
 }
 
 impl TcpState {
     pub fn new() -> Self {
+        Self::from_config(TcpConfig::default())
+    }
+
+    pub fn from_config(config: TcpConfig) -> Self {
         Self {
             status: Status::New,
+            config,
             listener_objects: Objects::<Listener>::new(),
             connection_objects: Objects::<Connection>::new(),
             poll_request_objects: Objects::<PollRequest>::new(),
             send_request_objects: Objects::<SendRequest>::new(),
             recv_request_objects: Objects::<RecvRequest>::new(),
+            buffer_status_request_objects: Objects::<BufferStatusRequest>::new(),
+            bytes_available_request_objects: Objects::new(),
+            shutdown_request_objects: Objects::<VecDeque<ShutdownRequest>>::new(),
+            nodelay_request_objects: Objects::new(),
+            congestion_request_objects: Objects::new(),
+            local_address_request_objects: Objects::new(),
+            probe_request_objects: Objects::<ProbeRequest>::new(),
+            line_request_objects: Objects::<LineRequest>::new(),
+            seq: 0,
+            virtual_time: 0,
+            closed_connections: VecDeque::new(),
+            accept_latency: AcceptLatencyStats::default(),
+            fd_exhaustion: None,
+            fd_exhaustion_watcher: None,
         }
     }
 
+    fn next_seq(&mut self) -> u64 {
+        let seq = self.seq;
+        self.seq += 1;
+        seq
+    }
+
     pub fn is_ready(&self) -> bool {
         matches!(self.status, Status::Ready { .. })
     }
 
+    // Describes the object (of any kind) allocated with `uid`, if any.
+    pub fn describe_object(&self, uid: &Uid) -> Option<String> {
+        if let Some(listener) = self.listener_objects.get(uid) {
+            return Some(format!(
+                "listener on {} ({} connections accepted)",
+                listener.address, listener.accepted
+            ));
+        }
+
+        if let Some(conn) = self.connection_objects.get(uid) {
+            let direction = match &conn.conn_type {
+                ConnectionType::Incoming { listener, .. } => {
+                    format!("incoming connection from listener {:?}", listener)
+                }
+                ConnectionType::Outgoing { .. } => "outgoing connection".to_string(),
+            };
+
+            return Some(format!("{} ({})", direction, conn.status.name()));
+        }
+
+        if let Some(request) = self.send_request_objects.get(uid) {
+            return Some(format!(
+                "send request on connection {:?} ({}/{} bytes sent)",
+                request.connection,
+                request.bytes_sent,
+                request.data.len()
+            ));
+        }
+
+        if let Some(request) = self.recv_request_objects.get(uid) {
+            return Some(format!(
+                "recv request on connection {:?} ({} bytes remaining)",
+                request.connection, request.remaining_bytes
+            ));
+        }
+
+        self.poll_request_objects
+            .get(uid)
+            .map(|request| format!("poll request on {} objects", request.objects.len()))
+    }
+
+    // See `TcpConfig::reuse_diagnostics`.
+    fn check_reuse(&self, uid: &Uid) {
+        if !self.config.reuse_diagnostics {
+            return;
+        }
+
+        if let Some(existing) = self.describe_object(uid) {
+            panic!("Attempt to re-use existing {:?}: {}", uid, existing)
+        }
+    }
+
     pub fn new_listener(
         &mut self,
         uid: Uid,
@@ -349,6 +1133,8 @@ impl TcpState {
         on_success: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
     ) {
+        self.check_reuse(&uid);
+
         if self
             .listener_objects
             .insert(uid, Listener::new(address, on_success, on_error))
@@ -389,79 +1175,454 @@ impl TcpState {
         conn_type: ConnectionType,
         timeout: TimeoutAbsolute,
     ) {
+        self.check_reuse(&connection);
+
+        let seq = self.next_seq();
+
         if self
             .connection_objects
-            .insert(connection, Connection::new(conn_type, timeout))
+            .insert(connection, Connection::new(conn_type, timeout, seq))
             .is_some()
         {
             panic!("Attempt to re-use existing {:?}", connection)
         }
     }
 
-    pub fn has_connection(&self, uid: &Uid) -> bool {
-        self.connection_objects.contains_key(uid)
+    // Selects the poll a connection is registered with. The model drives a
+    // single poll for now, so every connection is assigned to it.
+    pub fn poll_for_connection(&self, connection: &Uid) -> Uid {
+        if let Status::Ready { poll, .. } = self.status {
+            poll
+        } else {
+            panic!("Attempt to assign a poll to {:?} before init", connection)
+        }
     }
 
-    pub fn new_send_request(
-        &mut self,
-        uid: Uid,
-        connection: Uid,
-        data: Rc<[u8]>,
-        send_on_poll: bool,
-        timeout: TimeoutAbsolute,
-        on_success: Redispatch<Uid>,
-        on_timeout: Redispatch<Uid>,
-        on_error: Redispatch<(Uid, String)>,
-    ) {
-        if self
-            .send_request_objects
-            .insert(
-                uid,
-                SendRequest::new(
-                    connection,
-                    data,
-                    send_on_poll,
-                    timeout,
-                    on_success,
-                    on_timeout,
-                    on_error,
-                ),
-            )
-            .is_some()
-        {
-            panic!("Attempt to re-use existing {:?}", uid)
-        }
+    // The poll `connection` is registered with, if it is registered. Unlike
+    // `poll_for_connection`, it's the actual registration, which helps when
+    // debugging connections spread across several polls.
+    pub fn connection_poll(&self, connection: &Uid) -> Option<Uid> {
+        self.connection_objects
+            .get(connection)
+            .and_then(|conn| conn.poll)
     }
 
-    pub fn new_recv_request(
+    // Schedules a new poll registration attempt for `connection` if `error` is
+    // transient and the connection has retries left. Returns false if the
+    // connection should be closed instead.
+    pub fn schedule_register_retry(
         &mut self,
-        uid: Uid,
-        connection: Uid,
-        count: usize,
-        recv_on_poll: bool,
-        timeout: TimeoutAbsolute,
-        on_success: Redispatch<(Uid, Vec<u8>)>,
-        on_timeout: Redispatch<(Uid, Vec<u8>)>,
-        on_error: Redispatch<(Uid, String)>,
-    ) {
-        if self
-            .recv_request_objects
-            .insert(
-                uid,
-                RecvRequest::new(
-                    connection,
-                    count,
-                    recv_on_poll,
-                    timeout,
-                    on_success,
-                    on_timeout,
-                    on_error,
-                ),
-            )
-            .is_some()
-        {
-            panic!("Attempt to re-use existing {:?}", uid)
+        connection: &Uid,
+        error: &str,
+        current_time: u128,
+    ) -> bool {
+        let TcpConfig {
+            register_retries,
+            register_backoff,
+            ..
+        } = self.config;
+        let conn = self.get_connection_mut(connection);
+
+        if conn.register_attempts >= register_retries || !is_transient_register_error(error) {
+            return false;
         }
+
+        let backoff = register_backoff.saturating_mul(1 << conn.register_attempts.min(16));
+
+        conn.register_attempts += 1;
+        conn.register_retry_at = Some(current_time.saturating_add(backoff.into()));
+        true
+    }
+
+    // Sets the interest of `connection`, returns it if the connection has to
+    // be re-registered to apply it. Connections not registered yet are
+    // registered with it.
+    pub fn set_interest(&mut self, connection: &Uid, interest: Interest) -> Option<Interest> {
+        let conn = self.get_connection_mut(connection);
+
+        conn.interest = interest;
+
+        match conn.registered_interest {
+            Some(registered) if registered != interest => {
+                conn.registered_interest = Some(interest);
+                Some(interest)
+            }
+            _ => None,
+        }
+    }
+
+    // Returns the connections whose registration retry is due, in creation
+    // order, and clears their retry deadline.
+    pub fn take_due_register_retries(&mut self, current_time: u128) -> Vec<Uid> {
+        let mut connections: Vec<_> = self
+            .connection_objects
+            .iter_mut()
+            .filter(|(_, conn)| matches!(conn.register_retry_at, Some(at) if current_time >= at))
+            .collect();
+
+        connections.sort_by_key(|(_, conn)| conn.seq);
+        connections
+            .into_iter()
+            .map(|(uid, conn)| {
+                conn.register_retry_at = None;
+                *uid
+            })
+            .collect()
+    }
+
+    // Number of connections per (metrics) state name.
+    pub fn connection_counts(&self) -> [(&'static str, usize); 3] {
+        let mut counts = [("pending", 0), ("established", 0), ("closing", 0)];
+
+        for conn in self.connection_objects.values() {
+            let index = match conn.status {
+                ConnectionStatus::Pending | ConnectionStatus::PendingCheck => 0,
+                ConnectionStatus::Established | ConnectionStatus::WriteClosed => 1,
+                ConnectionStatus::CloseRequestInternal
+                | ConnectionStatus::CloseRequestNotify { .. } => 2,
+            };
+
+            counts[index].1 += 1;
+        }
+
+        counts
+    }
+
+    pub fn accept_latency_stats(&self) -> &AcceptLatencyStats {
+        &self.accept_latency
+    }
+
+    // Called once an incoming `connection` is accepted: its latency is the
+    // time since its listener became readable.
+    pub fn record_accept_latency(&mut self, connection: &Uid, current_time: u128) {
+        let ConnectionType::Incoming { listener, .. } = self.get_connection(connection).conn_type
+        else {
+            unreachable!()
+        };
+        let Some(readable_at) = self
+            .listener_objects
+            .get(&listener)
+            .and_then(|listener| listener.readable_at)
+        else {
+            return;
+        };
+        let latency = current_time.saturating_sub(readable_at);
+
+        self.get_connection_mut(connection).accept_latency = Some(latency);
+        self.accept_latency.count += 1;
+        self.accept_latency.total += latency;
+        self.accept_latency.max = self.accept_latency.max.max(latency);
+    }
+
+    // Checks the consistency of the objects tracked by the model, returns the
+    // inconsistencies found.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        match &self.status {
+            Status::Ready {
+                instance,
+                poll,
+                events,
+            } => {
+                if poll == events || poll == instance || events == instance {
+                    errors.push(format!("Status {:?} re-uses the same Uid", self.status))
+                }
+            }
+            // Listeners and connections can't exist without a poll object.
+            status => {
+                let count = self.listener_objects.len()
+                    + self.connection_objects.len()
+                    + self.poll_request_objects.len();
+
+                if count > 0 {
+                    errors.push(format!(
+                        "Status {:?} has no poll, but {} listeners/connections/poll requests exist",
+                        status, count
+                    ))
+                }
+            }
+        }
+
+        for (uid, conn) in self.connection_objects.iter() {
+            match (&conn.conn_type, &conn.status) {
+                (
+                    ConnectionType::Incoming { .. },
+                    ConnectionStatus::Pending | ConnectionStatus::PendingCheck,
+                ) => errors.push(format!(
+                    "Incoming connection {:?} has status {:?}",
+                    uid, conn.status
+                )),
+                (
+                    _,
+                    ConnectionStatus::CloseRequestInternal
+                    | ConnectionStatus::CloseRequestNotify { .. },
+                ) if conn.register_retry_at.is_some() => errors.push(format!(
+                    "Closing connection {:?} has a pending registration retry",
+                    uid
+                )),
+                _ => (),
+            }
+        }
+
+        for (uid, request) in self.poll_request_objects.iter() {
+            for object in request.objects.iter() {
+                if !self.listener_objects.contains_key(object) && !self.has_connection(object) {
+                    errors.push(format!(
+                        "PollRequest {:?} references inexistent object {:?}",
+                        uid, object
+                    ))
+                }
+            }
+        }
+
+        let requests = self
+            .send_request_objects
+            .iter()
+            .map(|(uid, request)| ("SendRequest", uid, request.connection))
+            .chain(
+                self.recv_request_objects
+                    .iter()
+                    .map(|(uid, request)| ("RecvRequest", uid, request.connection)),
+            )
+            .chain(
+                self.line_request_objects
+                    .iter()
+                    .map(|(uid, request)| ("LineRequest", uid, request.connection)),
+            );
+
+        for (kind, uid, connection) in requests {
+            if !self.has_connection(&connection) {
+                errors.push(format!(
+                    "{} {:?} references inexistent connection {:?}",
+                    kind, uid, connection
+                ))
+            }
+        }
+
+        // Lines are read by a recv request of the same `Uid`.
+        for uid in self.line_request_objects.keys() {
+            if !self.recv_request_objects.contains_key(uid) {
+                errors.push(format!("LineRequest {:?} has no RecvRequest", uid))
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn connection_addrs(&self, uid: &Uid) -> Option<(String, String)> {
+        self.connection_objects
+            .get(uid)
+            .and_then(|conn| conn.addrs.clone())
+    }
+
+    pub fn connection_stats(&self, uid: &Uid) -> ConnectionStats {
+        let conn = self.get_connection(uid);
+
+        ConnectionStats {
+            bytes_sent: conn.bytes_sent,
+            bytes_received: conn.bytes_received,
+            established_at: conn.established_at,
+        }
+    }
+
+    // Assigns the next number of the listener to an accepted connection.
+    pub fn new_connection_number(&mut self, uid: &Uid) -> u64 {
+        let ConnectionType::Incoming { listener, .. } = self.get_connection(uid).conn_type else {
+            panic!("Attempt to number outgoing connection {:?}", uid)
+        };
+        let listener = self.get_listener_mut(&listener);
+
+        listener.accepted += 1;
+
+        let number = listener.accepted;
+
+        self.get_connection_mut(uid).number = Some(number);
+        number
+    }
+
+    pub fn connection_number(&self, uid: &Uid) -> Option<u64> {
+        self.connection_objects
+            .get(uid)
+            .and_then(|conn| conn.number)
+    }
+
+    pub fn has_connection(&self, uid: &Uid) -> bool {
+        self.connection_objects.contains_key(uid)
+    }
+
+    pub fn has_listener(&self, uid: &Uid) -> bool {
+        self.listener_objects.contains_key(uid)
+    }
+
+    pub fn has_send_request(&self, uid: &Uid) -> bool {
+        self.send_request_objects.contains_key(uid)
+    }
+
+    pub fn has_recv_request(&self, uid: &Uid) -> bool {
+        self.recv_request_objects.contains_key(uid)
+    }
+
+    fn get_closed_connection(&self, uid: &Uid) -> Option<&ClosedConnection> {
+        self.closed_connections
+            .iter()
+            .rev()
+            .find(|closed| closed.uid == *uid)
+    }
+
+    // Also available for a while after the connection has been removed.
+    pub fn connection_history(&self, uid: &Uid) -> Option<&[ConnectionLogEntry]> {
+        match self.connection_objects.get(uid) {
+            Some(conn) => Some(conn.history.as_slice()),
+            None => self
+                .get_closed_connection(uid)
+                .map(|closed| closed.history.as_slice()),
+        }
+    }
+
+    // Like `connection_history()`, it can be queried from close notifications,
+    // which are delivered once the connection was removed.
+    pub fn connection_last_error(&self, uid: &Uid) -> Option<&str> {
+        match self.connection_objects.get(uid) {
+            Some(conn) => conn.last_error.as_deref(),
+            None => self
+                .get_closed_connection(uid)
+                .and_then(|closed| closed.last_error.as_deref()),
+        }
+    }
+
+    // Called before the failed send request `uid` is removed.
+    pub fn record_send_error(&mut self, uid: &Uid, kind: SendErrorKind) {
+        let connection = self.get_send_request(uid).connection;
+
+        self.get_connection_mut(&connection).failed_send = Some((*uid, kind));
+    }
+
+    // Why the send request `uid` on `connection` failed. Like
+    // `connection_last_error()`, it can be queried once the connection was
+    // removed.
+    pub fn send_error_kind(&self, connection: &Uid, uid: &Uid) -> SendErrorKind {
+        let failed_send = match self.connection_objects.get(connection) {
+            Some(conn) => conn.failed_send,
+            None => self
+                .get_closed_connection(connection)
+                .and_then(|closed| closed.failed_send),
+        };
+
+        match failed_send {
+            Some((failed, kind)) if failed == *uid => kind,
+            _ => SendErrorKind::NoConnection,
+        }
+    }
+
+    pub fn fd_exhaustion(&self) -> Option<&FdExhaustion> {
+        self.fd_exhaustion.as_ref()
+    }
+
+    // Whether new connections are held back, see `FdExhaustion`.
+    pub fn accepts_held(&self, current_time: u128) -> bool {
+        self.fd_exhaustion
+            .as_ref()
+            .is_some_and(|exhaustion| current_time < exhaustion.retry_at)
+    }
+
+    // Records an fd-exhaustion error. Returns true if it starts the degraded
+    // mode.
+    pub fn fd_exhausted(&mut self, current_time: u128) -> bool {
+        let retry_at = current_time.saturating_add(self.config.fd_exhaustion_backoff.into());
+
+        match &mut self.fd_exhaustion {
+            Some(exhaustion) => {
+                exhaustion.retry_at = retry_at;
+                false
+            }
+            None => {
+                self.fd_exhaustion = Some(FdExhaustion {
+                    since: current_time,
+                    retry_at,
+                });
+                true
+            }
+        }
+    }
+
+    // Records a successful accept or connect. Returns true if it ends the
+    // degraded mode.
+    pub fn fd_available(&mut self) -> bool {
+        self.fd_exhaustion.take().is_some()
+    }
+
+    // Like `connection_history()`, it can be queried once the connection was
+    // removed.
+    pub fn operation_log(&self, uid: &Uid) -> Option<&[Operation]> {
+        match self.connection_objects.get(uid) {
+            Some(conn) => conn.operation_log.as_deref(),
+            None => self
+                .get_closed_connection(uid)
+                .and_then(|closed| closed.operation_log.as_deref()),
+        }
+    }
+
+    pub fn set_operation_log(&mut self, connection: &Uid, enabled: bool) {
+        let conn = self.get_connection_mut(connection);
+
+        conn.operation_log = enabled.then(|| conn.operation_log.take().unwrap_or_default());
+    }
+
+    pub fn log_operation(
+        &mut self,
+        connection: &Uid,
+        time: u128,
+        kind: OperationKind,
+        data: &[u8],
+    ) {
+        if let Some(operation_log) = &mut self.get_connection_mut(connection).operation_log {
+            operation_log.push(Operation::new(time, kind, data))
+        }
+    }
+
+    pub fn log_connection(&mut self, uid: &Uid, time: u128, event: ConnectionLogEvent) {
+        if let Some(conn) = self.connection_objects.get_mut(uid) {
+            conn.log(time, event)
+        }
+    }
+
+    // The request's `seq` is stamped here.
+    pub fn new_send_request(&mut self, uid: Uid, mut request: SendRequest) {
+        self.check_reuse(&uid);
+        request.seq = self.next_seq();
+
+        let connection = request.connection;
+
+        if self.send_request_objects.insert(uid, request).is_some() {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+
+        if let Some(conn) = self.connection_objects.get_mut(&connection) {
+            conn.send_queue.push_back(uid)
+        }
+    }
+
+    // Same as `new_send_request()`.
+    pub fn new_recv_request(&mut self, uid: Uid, mut request: RecvRequest) {
+        self.check_reuse(&uid);
+        request.seq = self.next_seq();
+
+        if self.recv_request_objects.insert(uid, request).is_some() {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    pub fn listeners(&self) -> impl Iterator<Item = (&Uid, &Listener)> {
+        self.listener_objects.iter()
+    }
+
+    pub fn connections(&self) -> impl Iterator<Item = (&Uid, &Connection)> {
+        self.connection_objects.iter()
     }
 
     pub fn get_listener(&self, uid: &Uid) -> &Listener {
@@ -477,6 +1638,7 @@ impl TcpState {
     }
 
     pub fn remove_listener(&mut self, uid: &Uid) {
+        self.local_address_request_objects.remove(uid);
         self.listener_objects.remove(uid).expect(&format!(
             "Attempt to remove an inexistent Listener {:?}",
             uid
@@ -504,10 +1666,36 @@ impl TcpState {
         self.send_request_objects
             .retain(|_, req| req.connection != *uid);
 
-        self.connection_objects.remove(uid).expect(&format!(
+        self.buffer_status_request_objects
+            .retain(|_, req| req.connection != *uid);
+
+        self.bytes_available_request_objects.remove(uid);
+
+        self.shutdown_request_objects.remove(uid);
+
+        self.nodelay_request_objects.remove(uid);
+        self.congestion_request_objects.remove(uid);
+        self.local_address_request_objects.remove(uid);
+
+        self.line_request_objects
+            .retain(|_, req| req.connection != *uid);
+
+        let conn = self.connection_objects.remove(uid).expect(&format!(
             "Attempt to remove an inexistent Connection {:?}",
             uid
         ));
+
+        if self.closed_connections.len() == CLOSED_CONNECTION_HISTORY_LEN {
+            self.closed_connections.pop_front();
+        }
+
+        self.closed_connections.push_back(ClosedConnection {
+            uid: *uid,
+            history: conn.history,
+            last_error: conn.last_error,
+            failed_send: conn.failed_send,
+            operation_log: conn.operation_log,
+        });
     }
 
     pub fn get_poll_request(&self, uid: &Uid) -> &PollRequest {
@@ -535,18 +1723,97 @@ impl TcpState {
             .expect(&format!("SendRequest object {:?} not found", uid))
     }
 
+    // Returned by connection priority, then in creation order.
     pub fn pending_send_requests(&self) -> Vec<(&Uid, &SendRequest)> {
-        self.send_request_objects
+        let mut requests: Vec<_> = self
+            .send_request_objects
             .iter()
             .filter(|(_, request)| request.send_on_poll)
-            .collect()
+            .collect();
+
+        requests.sort_by_key(|(_, request)| {
+            (
+                Reverse(self.connection_priority(&request.connection)),
+                request.seq,
+            )
+        });
+        requests
     }
 
-    pub fn remove_send_request(&mut self, uid: &Uid) {
-        self.send_request_objects.remove(uid).expect(&format!(
+    pub fn remove_send_request(&mut self, uid: &Uid) -> SendRequest {
+        let request = self.send_request_objects.remove(uid).expect(&format!(
             "Attempt to remove an inexistent SendRequest {:?}",
             uid
         ));
+
+        if let Some(conn) = self.connection_objects.get_mut(&request.connection) {
+            conn.send_queue.retain(|queued| queued != uid)
+        }
+
+        request
+    }
+
+    // Whether the SendRequest is the next one to be written on its
+    // connection, see `Connection::send_queue`.
+    pub fn is_send_head(&self, uid: &Uid) -> bool {
+        let connection = self.get_send_request(uid).connection;
+
+        self.get_connection(&connection).send_queue.front() == Some(uid)
+    }
+
+    // The SendRequest to write next on `connection`, once the previous one
+    // completed.
+    pub fn next_send_request(&self, connection: &Uid) -> Option<Uid> {
+        self.connection_objects
+            .get(connection)
+            .and_then(|conn| conn.send_queue.front().copied())
+    }
+
+    // Sets the length of the next write of a SendRequest, as allowed by the
+    // connection's shaper. Returns 0 if the connection ran out of allowance.
+    pub fn reserve_send(&mut self, uid: &Uid, current_time: u128) -> usize {
+        let request = self
+            .send_request_objects
+            .get_mut(uid)
+            .expect(&format!("SendRequest object {:?} not found", uid));
+        let count = request.data.len() - request.bytes_sent;
+        let connection = self
+            .connection_objects
+            .get_mut(&request.connection)
+            .expect(&format!(
+                "Connection object {:?} not found",
+                request.connection
+            ));
+
+        request.write_len = match connection.send_shaper.as_mut() {
+            Some(shaper) => shaper.reserve(current_time, count),
+            None => count,
+        };
+        request.write_len
+    }
+
+    // Accounts `written` bytes of the in-flight write, the rest of the reserved
+    // allowance is given back to the shaper.
+    pub fn complete_send(&mut self, uid: &Uid, written: usize) {
+        let request = self
+            .send_request_objects
+            .get_mut(uid)
+            .expect(&format!("SendRequest object {:?} not found", uid));
+        let unused = request
+            .write_len
+            .checked_sub(written)
+            .expect("Sent more data than reserved");
+
+        request.bytes_sent += written;
+        request.write_len = 0;
+
+        if let Some(connection) = self.connection_objects.get_mut(&request.connection) {
+            connection.bytes_sent += written as u64;
+
+            if let Some(shaper) = connection.send_shaper.as_mut() {
+                shaper.refund(unused)
+            }
+        }
     }
 
     pub fn get_recv_request(&self, uid: &Uid) -> &RecvRequest {
@@ -561,28 +1828,493 @@ impl TcpState {
             .expect(&format!("RecvRequest object {:?} not found", uid))
     }
 
+    // Returned by connection priority, then in creation order.
     pub fn pending_recv_requests(&self) -> Vec<(&Uid, &RecvRequest)> {
-        self.recv_request_objects
+        let mut requests: Vec<_> = self
+            .recv_request_objects
             .iter()
             .filter(|(_, request)| request.recv_on_poll)
+            .collect();
+
+        requests.sort_by_key(|(_, request)| {
+            (
+                Reverse(self.connection_priority(&request.connection)),
+                request.seq,
+            )
+        });
+        requests
+    }
+
+    // Removes the recv requests of `connection` holding partial data, in
+    // creation order.
+    pub fn take_buffered_recv_requests(&mut self, connection: &Uid) -> Vec<(Uid, RecvRequest)> {
+        let mut uids: Vec<(u64, Uid)> = self
+            .recv_request_objects
+            .iter()
+            .filter(|(_, req)| req.connection == *connection && !req.buffered_data.is_empty())
+            .map(|(uid, req)| (req.seq, *uid))
+            .collect();
+
+        uids.sort();
+        uids.into_iter()
+            .map(|(_, uid)| (uid, self.recv_request_objects.remove(&uid).unwrap()))
             .collect()
     }
 
-    pub fn remove_recv_request(&mut self, uid: &Uid) {
+    pub fn remove_recv_request(&mut self, uid: &Uid) -> RecvRequest {
         self.recv_request_objects.remove(uid).expect(&format!(
             "Attempt to remove an inexistent RecvRequest {:?}",
             uid
-        ));
+        ))
     }
 
-    pub fn pending_connections_mut(&mut self) -> Vec<(&Uid, &mut Connection)> {
+    // Sets the length of the next read of a RecvRequest, as allowed by the
+    // connection's shaper. Returns 0 if the connection ran out of allowance.
+    pub fn reserve_recv(&mut self, uid: &Uid, current_time: u128) -> usize {
+        let request = self
+            .recv_request_objects
+            .get_mut(uid)
+            .expect(&format!("RecvRequest object {:?} not found", uid));
+        let count = request.remaining_bytes;
+        let connection = self
+            .connection_objects
+            .get_mut(&request.connection)
+            .expect(&format!(
+                "Connection object {:?} not found",
+                request.connection
+            ));
+
+        request.read_len = match connection.recv_shaper.as_mut() {
+            Some(shaper) => shaper.reserve(current_time, count),
+            None => count,
+        };
+        request.read_len
+    }
+
+    // Gives back to the shaper the reserved allowance that wasn't read.
+    pub fn complete_recv(&mut self, uid: &Uid, received: usize) {
+        let request = self
+            .recv_request_objects
+            .get_mut(uid)
+            .expect(&format!("RecvRequest object {:?} not found", uid));
+        let unused = request
+            .read_len
+            .checked_sub(received)
+            .expect("Received more data than reserved");
+
+        request.read_len = 0;
+
+        if let Some(connection) = self.connection_objects.get_mut(&request.connection) {
+            connection.bytes_received += received as u64;
+
+            if let Some(shaper) = connection.recv_shaper.as_mut() {
+                shaper.refund(unused)
+            }
+        }
+    }
+
+    pub fn set_priority(&mut self, connection: &Uid, priority: u8) {
+        self.get_connection_mut(connection).priority = priority
+    }
+
+    pub fn set_deadline(&mut self, connection: &Uid, deadline: TimeoutAbsolute) {
+        self.get_connection_mut(connection).deadline = deadline
+    }
+
+    // The deadline of a new send/recv request on `connection`: `timeout`, or
+    // the connection's deadline if it comes first.
+    pub fn request_deadline(&self, connection: &Uid, timeout: TimeoutAbsolute) -> TimeoutAbsolute {
+        timeout.earliest(self.get_connection(connection).deadline.clone())
+    }
+
+    // A zero weight would never get a write opportunity: it's clamped to 1.
+    pub fn set_weight(&mut self, connection: &Uid, weight: u32) {
+        self.get_connection_mut(connection).weight = weight.max(1)
+    }
+
+    // Picks at most `budget` of the writable send requests `uids` (in service
+    // order), by start-time fair queuing: each write opportunity advances the
+    // virtual time of its connection by `WRITE_QUANTUM / weight`, and the
+    // connection that is furthest behind goes next. Priorities still come
+    // first. Requests not picked are left for the next poll.
+    pub fn schedule_writes(&mut self, uids: Vec<Uid>, budget: usize) -> Vec<Uid> {
+        let mut queues: BTreeMap<Uid, VecDeque<Uid>> = BTreeMap::new();
+        let mut scheduled = Vec::new();
+
+        for uid in uids {
+            let connection = self.get_send_request(&uid).connection;
+
+            queues.entry(connection).or_default().push_back(uid);
+        }
+
+        while scheduled.len() < budget {
+            let Some((start, connection)) = queues
+                .keys()
+                .map(|connection| {
+                    let conn = self.get_connection(connection);
+
+                    (conn.virtual_finish.max(self.virtual_time), *connection)
+                })
+                .min_by_key(|(start, connection)| {
+                    let conn = self.get_connection(connection);
+
+                    (Reverse(conn.priority), *start, conn.seq)
+                })
+            else {
+                break;
+            };
+            let queue = queues.get_mut(&connection).unwrap();
+
+            scheduled.push(queue.pop_front().unwrap());
+
+            if queue.is_empty() {
+                queues.remove(&connection);
+            }
+
+            let conn = self.get_connection_mut(&connection);
+
+            conn.virtual_finish = start + WRITE_QUANTUM / u64::from(conn.weight);
+            self.virtual_time = start;
+        }
+
+        scheduled
+    }
+
+    // Requests can outlive their connection, which then counts as priority 0.
+    fn connection_priority(&self, connection: &Uid) -> u8 {
+        self.connection_objects
+            .get(connection)
+            .map_or(0, |conn| conn.priority)
+    }
+
+    // Both directions get their own shaper over the same schedule.
+    pub fn set_rate_schedule(&mut self, connection: &Uid, schedule: Vec<(u128, u64)>) {
+        let connection = self.get_connection_mut(connection);
+
+        connection.send_shaper = Some(RateShaper::new(schedule.clone()));
+        connection.recv_shaper = Some(RateShaper::new(schedule));
+    }
+
+    pub fn new_buffer_status_request(
+        &mut self,
+        uid: Uid,
+        connection: Uid,
+        on_success: Redispatch<(Uid, usize, usize)>,
+        on_error: Redispatch<(Uid, String)>,
+    ) {
+        if self
+            .buffer_status_request_objects
+            .insert(
+                uid,
+                BufferStatusRequest {
+                    connection,
+                    on_success,
+                    on_error,
+                },
+            )
+            .is_some()
+        {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    // The request might be gone if its connection was removed while the
+    // query was in flight.
+    pub fn take_buffer_status_request(&mut self, uid: &Uid) -> Option<BufferStatusRequest> {
+        self.buffer_status_request_objects.remove(uid)
+    }
+
+    pub fn new_bytes_available_request(
+        &mut self,
+        connection: Uid,
+        on_result: Redispatch<(Uid, BytesAvailableResult)>,
+    ) {
+        self.bytes_available_request_objects
+            .entry(connection)
+            .or_default()
+            .push_back(on_result)
+    }
+
+    // As with buffer status requests, the connection might have been removed
+    // while the query was in flight.
+    pub fn take_bytes_available_request(
+        &mut self,
+        connection: &Uid,
+    ) -> Option<Redispatch<(Uid, BytesAvailableResult)>> {
+        let requests = self.bytes_available_request_objects.get_mut(connection)?;
+        let on_result = requests.pop_front();
+
+        if requests.is_empty() {
+            self.bytes_available_request_objects.remove(connection);
+        }
+
+        on_result
+    }
+
+    pub fn new_shutdown_request(
+        &mut self,
+        connection: Uid,
+        how: ShutdownHow,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    ) {
+        self.shutdown_request_objects
+            .entry(connection)
+            .or_default()
+            .push_back(ShutdownRequest {
+                how,
+                on_success,
+                on_error,
+            })
+    }
+
+    // The connection might have been removed while the shutdown was in flight.
+    pub fn take_shutdown_request(&mut self, connection: &Uid) -> Option<ShutdownRequest> {
+        let requests = self.shutdown_request_objects.get_mut(connection)?;
+        let request = requests.pop_front();
+
+        if requests.is_empty() {
+            self.shutdown_request_objects.remove(connection);
+        }
+
+        request
+    }
+
+    pub fn new_nodelay_request(
+        &mut self,
+        connection: Uid,
+        value: bool,
+        on_result: Option<Redispatch<(Uid, Result<(), String>)>>,
+    ) {
+        self.nodelay_request_objects
+            .entry(connection)
+            .or_default()
+            .push_back(NodelayRequest { value, on_result })
+    }
+
+    // The connection might have been removed while the request was in flight.
+    pub fn take_nodelay_request(&mut self, connection: &Uid) -> Option<NodelayRequest> {
+        let requests = self.nodelay_request_objects.get_mut(connection)?;
+        let request = requests.pop_front();
+
+        if requests.is_empty() {
+            self.nodelay_request_objects.remove(connection);
+        }
+
+        request
+    }
+
+    pub fn new_congestion_request(
+        &mut self,
+        connection: Uid,
+        on_result: Redispatch<(Uid, Result<String, String>)>,
+    ) {
+        self.congestion_request_objects
+            .entry(connection)
+            .or_default()
+            .push_back(CongestionRequest { on_result })
+    }
+
+    // The connection might have been removed while the request was in flight.
+    pub fn take_congestion_request(&mut self, connection: &Uid) -> Option<CongestionRequest> {
+        let requests = self.congestion_request_objects.get_mut(connection)?;
+        let request = requests.pop_front();
+
+        if requests.is_empty() {
+            self.congestion_request_objects.remove(connection);
+        }
+
+        request
+    }
+
+    pub fn new_local_address_request(
+        &mut self,
+        uid: Uid,
+        on_success: Redispatch<(Uid, String)>,
+        on_error: Redispatch<(Uid, String)>,
+    ) {
+        self.local_address_request_objects
+            .entry(uid)
+            .or_default()
+            .push_back(LocalAddressRequest {
+                on_success,
+                on_error,
+            })
+    }
+
+    // The listener or connection might have been removed while the request
+    // was in flight.
+    pub fn take_local_address_request(&mut self, uid: &Uid) -> Option<LocalAddressRequest> {
+        let requests = self.local_address_request_objects.get_mut(uid)?;
+        let request = requests.pop_front();
+
+        if requests.is_empty() {
+            self.local_address_request_objects.remove(uid);
+        }
+
+        request
+    }
+
+    pub fn new_probe_request(
+        &mut self,
+        connection: Uid,
+        on_result: Redispatch<(Uid, ProbeResult)>,
+    ) {
+        if self
+            .probe_request_objects
+            .insert(
+                connection,
+                ProbeRequest {
+                    on_result,
+                    result: None,
+                },
+            )
+            .is_some()
+        {
+            panic!("Attempt to re-use existing {:?}", connection)
+        }
+    }
+
+    pub fn get_probe_request_mut(&mut self, connection: &Uid) -> &mut ProbeRequest {
+        self.probe_request_objects
+            .get_mut(connection)
+            .expect(&format!("ProbeRequest object {:?} not found", connection))
+    }
+
+    pub fn take_probe_request(&mut self, connection: &Uid) -> ProbeRequest {
+        self.probe_request_objects.remove(connection).expect(&format!(
+            "Take attempt on inexistent ProbeRequest {:?}",
+            connection
+        ))
+    }
+
+    pub fn new_line_request(
+        &mut self,
+        uid: Uid,
+        connection: Uid,
+        max_len: usize,
+        on_line: Redispatch<(Uid, Vec<u8>)>,
+        on_error: Redispatch<(Uid, String)>,
+    ) {
+        if self
+            .line_request_objects
+            .insert(
+                uid,
+                LineRequest {
+                    connection,
+                    max_len,
+                    on_line,
+                    on_error,
+                },
+            )
+            .is_some()
+        {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    pub fn get_line_request(&self, uid: &Uid) -> &LineRequest {
+        self.line_request_objects
+            .get(uid)
+            .expect(&format!("LineRequest object {:?} not found", uid))
+    }
+
+    pub fn take_line_request(&mut self, uid: &Uid) -> LineRequest {
+        self.line_request_objects.remove(uid).expect(&format!(
+            "Take attempt on inexistent LineRequest {:?}",
+            uid
+        ))
+    }
+
+    // Drops the LineRequest of a cancelled `RecvLine`, if `uid` is one.
+    pub fn discard_line_request(&mut self, uid: &Uid) {
+        self.line_request_objects.remove(uid);
+    }
+
+    // Returns the last observed (send queued, recv available) byte counts of
+    // the connection's OS socket buffers, or `None` if they were never queried
+    // (or the connection doesn't exist).
+    pub fn socket_buffer_status(&self, connection: &Uid) -> Option<(usize, usize)> {
+        self.connection_objects
+            .get(connection)
+            .and_then(|conn| conn.buffer_status)
+    }
+
+    // Returns the congestion control algorithm last reported for the
+    // connection by `TcpAction::GetCongestion`, if any.
+    pub fn congestion_algorithm(&self, connection: &Uid) -> Option<&str> {
         self.connection_objects
+            .get(connection)
+            .and_then(|conn| conn.congestion.as_deref())
+    }
+
+    // The nearest deadline across pending connections and send/recv requests.
+    pub fn nearest_deadline(&self) -> TimeoutAbsolute {
+        let connections = self
+            .connection_objects
+            .values()
+            .filter(|conn| {
+                matches!(
+                    conn.status,
+                    ConnectionStatus::Pending | ConnectionStatus::PendingCheck
+                )
+            })
+            .map(|conn| &conn.timeout);
+        let send_requests = self.send_request_objects.values().map(|req| &req.timeout);
+        let recv_requests = self.recv_request_objects.values().map(|req| &req.timeout);
+        let register_retries = self
+            .connection_objects
+            .values()
+            .filter_map(|conn| conn.register_retry_at);
+
+        connections
+            .chain(send_requests)
+            .chain(recv_requests)
+            .filter_map(|timeout| match timeout {
+                TimeoutAbsolute::Millis(ms) => Some(*ms),
+                TimeoutAbsolute::Never => None,
+            })
+            .chain(register_retries)
+            .min()
+            .map_or(TimeoutAbsolute::Never, TimeoutAbsolute::Millis)
+    }
+
+    // Poll until the caller's `timeout` or the nearest pending deadline,
+    // whichever is sooner, so request timeouts fire without busy polling.
+    pub fn poll_timeout(&self, current_time: u128, timeout: Timeout) -> Timeout {
+        match self.nearest_deadline() {
+            TimeoutAbsolute::Millis(deadline) => {
+                let until_deadline =
+                    deadline.saturating_sub(current_time).min(u64::MAX as u128) as u64;
+
+                match timeout {
+                    Timeout::Millis(ms) | Timeout::Inactivity(ms) => {
+                        Timeout::Millis(ms.min(until_deadline))
+                    }
+                    Timeout::Never => Timeout::Millis(until_deadline),
+                }
+            }
+            TimeoutAbsolute::Never => timeout,
+        }
+    }
+
+    // Returned in `Connection::service_order`. Connections waiting for a
+    // registration retry are not polled, so they are skipped.
+    pub fn pending_connections_mut(&mut self) -> Vec<(&Uid, &mut Connection)> {
+        let mut connections: Vec<_> = self
+            .connection_objects
             .iter_mut()
-            .filter(|(_, conn)| match conn.status {
-                ConnectionStatus::Pending | ConnectionStatus::PendingCheck => true,
-                _ => false,
+            .filter(|(_, conn)| conn.register_retry_at.is_none())
+            .filter(|(_, conn)| {
+                matches!(
+                    conn.status,
+                    ConnectionStatus::Pending | ConnectionStatus::PendingCheck
+                )
             })
-            .collect()
+            .collect();
+
+        connections.sort_by_key(|(_, conn)| conn.service_order());
+        connections
     }
 
     pub fn get_events(&self, uid: &Uid) -> Option<(Uid, Event)> {
@@ -601,13 +2333,39 @@ impl TcpState {
         }
     }
 
-    pub fn update_events(&mut self, event: &MioEvent) {
+    pub fn update_events(&mut self, current_time: u128, event: &MioEvent) {
         let uid = event.token;
 
         if let Some(listener) = self.listener_objects.get_mut(&uid) {
-            listener.update_events(uid, event)
+            listener.update_events(uid, event);
+
+            if let Some(ListenerEvent::AcceptPending) = listener.events {
+                listener.readable_at.get_or_insert(current_time);
+            }
         } else if let Some(connection) = self.connection_objects.get_mut(&uid) {
-            connection.update_events(uid, event)
+            connection.update_events(uid, event);
+
+            let log_event = match event {
+                MioEvent { error: true, .. } => ConnectionLogEvent::Error,
+                MioEvent {
+                    read_closed,
+                    write_closed,
+                    ..
+                } if *read_closed || (*write_closed && !connection.write_shut_down()) => {
+                    ConnectionLogEvent::Closed
+                }
+                MioEvent {
+                    readable, writable, ..
+                } => ConnectionLogEvent::Ready {
+                    readable: *readable,
+                    writable: *writable,
+                },
+            };
+
+            if connection.readiness.as_ref() != Some(&log_event) {
+                connection.readiness = Some(log_event.clone());
+                connection.log(current_time, log_event)
+            }
         } else {
             panic!("Received event for unknown object {:?}", uid)
         }
@@ -1,13 +1,13 @@
 use super::{
     action::{ConnectionEvent, Event, ListenerEvent, TcpPollEvents},
     state::{
-        Connection, ConnectionStatus, ConnectionType, EventUpdater, RecvRequest, SendRequest,
-        TcpState,
+        Connection, ConnectionLogEvent, ConnectionStatus, ConnectionType, EventUpdater,
+        LineRequest, OperationKind, RecvRequest, SendErrorKind, SendRequest, TcpState,
     },
 };
 use crate::{
     automaton::{
-        action::{Dispatcher, TimeoutAbsolute},
+        action::{Dispatcher, RcSlice, TimeoutAbsolute},
         state::Uid,
     },
     callback,
@@ -17,6 +17,34 @@ use crate::{
     },
 };
 
+// Error of send requests on a connection whose write side was shut down.
+const WRITE_SHUT_DOWN: &str = "Connection write side shut down";
+
+pub fn process_register_retries(
+    current_time: u128,
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+) {
+    for connection in tcp_state.take_due_register_retries(current_time) {
+        register_connection(tcp_state, dispatcher, connection)
+    }
+}
+
+// Registers `connection` with its poll, for the connection's interest.
+pub fn register_connection(tcp_state: &mut TcpState, dispatcher: &mut Dispatcher, connection: Uid) {
+    let poll = tcp_state.poll_for_connection(&connection);
+    let conn = tcp_state.get_connection_mut(&connection);
+
+    conn.registered_interest = Some(conn.interest);
+    dispatcher.dispatch_effect(MioEffectfulAction::PollRegisterTcpConnection {
+        poll,
+        connection,
+        interest: conn.interest,
+        on_success: callback!(|connection: Uid| TcpAction::RegisterConnectionSuccess { connection }),
+        on_error: callback!(|(connection: Uid, error: String)| TcpAction::RegisterConnectionError { connection, error }),
+    });
+}
+
 pub fn process_pending_connections(
     current_time: u128,
     tcp_state: &mut TcpState,
@@ -51,7 +79,7 @@ pub fn process_pending_connections(
                 ConnectionStatus::Pending => {
                     dispatcher.dispatch_effect(MioEffectfulAction::TcpGetPeerAddress {
                         connection,
-                        on_success: callback!(|(connection: Uid, address: String)| TcpAction::GetPeerAddressSuccess { connection, address }),
+                        on_success: callback!(|(connection: Uid, local_address: String, peer_address: String)| TcpAction::GetPeerAddressSuccess { connection, local_address, peer_address }),
                         on_error: callback!(|(connection: Uid, error: String)| TcpAction::GetPeerAddressError { connection, error }),
                     });
                     *status = ConnectionStatus::PendingCheck;
@@ -61,6 +89,12 @@ pub fn process_pending_connections(
             }
         }
     }
+
+    // The timed out connections are closed, so they aren't checked (and
+    // reported) again on the next poll.
+    for connection in purge_requests {
+        close_internal(tcp_state, dispatcher, connection)
+    }
 }
 
 pub fn process_pending_send_requests(
@@ -69,6 +103,7 @@ pub fn process_pending_send_requests(
     dispatcher: &mut Dispatcher,
 ) {
     let mut purge_requests = Vec::new();
+    let mut failed_requests = Vec::new();
     let mut dispatched_requests = Vec::new();
 
     process_pending_send_requests_aux(
@@ -76,12 +111,25 @@ pub fn process_pending_send_requests(
         tcp_state,
         dispatcher,
         &mut purge_requests,
+        &mut failed_requests,
         &mut dispatched_requests,
     );
 
+    for (uid, kind) in failed_requests {
+        tcp_state.record_send_error(&uid, kind)
+    }
+
     // remove requests for invalid or closed connections
     for uid in purge_requests.iter() {
-        tcp_state.remove_send_request(uid)
+        tcp_state.remove_send_request(uid);
+    }
+
+    if let Some(budget) = tcp_state.config.writes_per_poll {
+        dispatched_requests = tcp_state.schedule_writes(dispatched_requests, budget);
+    }
+
+    for uid in dispatched_requests {
+        dispatch_write(tcp_state, dispatcher, current_time, uid)
     }
 }
 
@@ -90,62 +138,65 @@ pub fn process_pending_send_requests_aux(
     tcp_state: &mut TcpState,
     dispatcher: &mut Dispatcher,
     purge_requests: &mut Vec<Uid>,
+    failed_requests: &mut Vec<(Uid, SendErrorKind)>,
     dispatched_requests: &mut Vec<Uid>,
 ) {
-    for (
-        &uid,
-        SendRequest {
+    for (&uid, request) in tcp_state.pending_send_requests() {
+        let SendRequest {
             connection,
-            data,
-            bytes_sent,
             timeout,
             on_timeout,
             on_error,
             ..
-        },
-    ) in tcp_state.pending_send_requests()
-    {
+        } = request;
         let timed_out = match timeout {
             TimeoutAbsolute::Millis(ms) => current_time >= *ms,
             TimeoutAbsolute::Never => false,
         };
         let connection = *connection;
-        let event = tcp_state.get_connection(&connection).events();
+        let conn = tcp_state.get_connection(&connection);
 
-        match event {
-            ConnectionEvent::Ready { can_send: true, .. } => {
+        if conn.write_shut_down() {
+            dispatcher.dispatch_back(
+                on_error,
+                (uid, request.error_message(WRITE_SHUT_DOWN.to_string())),
+            );
+            failed_requests.push((uid, SendErrorKind::WriteShutDown));
+            purge_requests.push(uid);
+            continue;
+        }
+
+        match conn.events() {
+            // Requests queued behind another one on the same connection wait
+            // for their turn, see `Connection::send_queue`.
+            ConnectionEvent::Ready { can_send: true, .. } if tcp_state.is_send_head(&uid) => {
                 if timed_out {
                     dispatcher.dispatch_back(on_timeout, uid);
                     purge_requests.push(uid);
                 } else {
-                    dispatcher.dispatch_effect(MioEffectfulAction::TcpWrite {
-                        uid,
-                        connection,
-                        data: (&data[*bytes_sent..]).into(),
-                        on_success: callback!(|uid: Uid| TcpAction::SendSuccess { uid }),
-                        on_success_partial: callback!(|(uid: Uid, count: usize)| TcpAction::SendSuccessPartial { uid, count }),
-                        on_interrupted: callback!(|uid: Uid| TcpAction::SendErrorInterrupted { uid }),
-                        on_would_block: callback!(|uid: Uid| TcpAction::SendErrorTryAgain { uid }),
-                        on_error: callback!(|(uid: Uid, error: String)| TcpAction::SendError { uid, error })
-                    });
-
+                    // dispatched by the caller, see `dispatch_write()`
                     dispatched_requests.push(uid);
                 }
             }
-            ConnectionEvent::Ready {
-                can_send: false, ..
-            } => {
+            ConnectionEvent::Ready { .. } => {
                 if timed_out {
                     dispatcher.dispatch_back(on_timeout, uid);
                     purge_requests.push(uid);
                 }
             }
             ConnectionEvent::Closed => {
-                dispatcher.dispatch_back(on_error, (uid, "Connection closed".to_string()));
+                dispatcher.dispatch_back(
+                    on_error,
+                    (uid, request.error_message("Connection closed".to_string())),
+                );
+                failed_requests.push((uid, request.error_kind()));
                 purge_requests.push(uid);
             }
             ConnectionEvent::Error => {
-                dispatcher.dispatch_back(on_error, (uid, "Connection error".to_string()));
+                let error = request.error_message("Connection error".to_string());
+
+                dispatcher.dispatch_back(on_error, (uid, error));
+                failed_requests.push((uid, request.error_kind()));
                 purge_requests.push(uid);
             }
         }
@@ -170,7 +221,11 @@ pub fn process_pending_recv_requests(
 
     // remove requests for invalid or closed connections
     for uid in purge_requests.iter() {
-        tcp_state.remove_recv_request(uid)
+        tcp_state.remove_recv_request(uid);
+    }
+
+    for uid in dispatched_requests {
+        dispatch_recv(tcp_state, dispatcher, current_time, uid)
     }
 }
 
@@ -186,7 +241,6 @@ pub fn input_pending_recv_requests_aux(
         RecvRequest {
             connection,
             buffered_data,
-            remaining_bytes,
             timeout,
             on_timeout,
             on_error,
@@ -207,17 +261,7 @@ pub fn input_pending_recv_requests_aux(
                     dispatcher.dispatch_back(on_timeout, (uid, buffered_data.clone()));
                     purge_requests.push(uid);
                 } else {
-                    dispatcher.dispatch_effect(MioEffectfulAction::TcpRead {
-                        uid,
-                        connection,
-                        len: *remaining_bytes,
-                        on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpAction::RecvSuccess { uid, data }),
-                        on_success_partial: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpAction::RecvSuccessPartial { uid, partial_data }),
-                        on_interrupted: callback!(|uid: Uid| TcpAction::RecvErrorInterrupted { uid }),
-                        on_would_block: callback!(|uid: Uid| TcpAction::RecvErrorTryAgain { uid }),
-                        on_error: callback!(|(uid: Uid, error: String)| TcpAction::RecvError { uid, error })
-                    });
-
+                    // dispatched by the caller, see `dispatch_recv()`
                     dispatched_requests.push(uid);
                 }
             }
@@ -230,11 +274,18 @@ pub fn input_pending_recv_requests_aux(
                 }
             }
             ConnectionEvent::Closed => {
-                dispatcher.dispatch_back(on_error, (uid, "Connection closed".to_string()));
-                purge_requests.push(uid);
+                if timed_out {
+                    dispatcher.dispatch_back(on_timeout, (uid, buffered_data.clone()));
+                    purge_requests.push(uid);
+                } else {
+                    // drained by the caller, see `dispatch_recv()`
+                    dispatched_requests.push(uid);
+                }
             }
             ConnectionEvent::Error => {
-                dispatcher.dispatch_back(on_error, (uid, "Connection error".to_string()));
+                let error = "Connection error".to_string();
+
+                dispatcher.dispatch_back(on_error, (uid, error));
                 purge_requests.push(uid);
             }
         }
@@ -250,9 +301,10 @@ pub fn handle_poll_success(
 ) {
     // update TCP object events (even for Uids that were not requested)
     for mio_event in events.iter() {
-        tcp_state.update_events(mio_event)
+        tcp_state.update_events(current_time, mio_event)
     }
 
+    process_register_retries(current_time, tcp_state, dispatcher);
     process_pending_connections(current_time, tcp_state, dispatcher);
     process_pending_send_requests(current_time, tcp_state, dispatcher);
     process_pending_recv_requests(current_time, tcp_state, dispatcher);
@@ -284,6 +336,16 @@ pub fn handle_send_common(
     uid: Uid,
     can_send_value: bool,
 ) {
+    let request = tcp_state.get_send_request(&uid);
+    let limit = tcp_state
+        .get_connection(&request.connection)
+        .deadline
+        .clone();
+
+    tcp_state
+        .get_send_request_mut(&uid)
+        .reset_inactivity_deadline(current_time, limit);
+
     let SendRequest {
         connection,
         timeout,
@@ -298,7 +360,7 @@ pub fn handle_send_common(
 
     if timed_out {
         dispatcher.dispatch_back(on_timeout, uid);
-        tcp_state.remove_send_request(&uid)
+        tcp_state.remove_send_request(&uid);
     } else {
         if can_send_value == false {
             tcp_state.get_send_request_mut(&uid).send_on_poll = true;
@@ -314,7 +376,7 @@ pub fn handle_send_common(
             };
 
             *can_send = can_send_value;
-            dispatch_send(tcp_state, dispatcher, uid);
+            dispatch_send(tcp_state, dispatcher, current_time, uid);
         } else {
             tcp_state.get_send_request_mut(&uid).send_on_poll = true;
         }
@@ -328,6 +390,16 @@ pub fn handle_recv_common(
     uid: Uid,
     can_recv_value: bool,
 ) {
+    let request = tcp_state.get_recv_request(&uid);
+    let limit = tcp_state
+        .get_connection(&request.connection)
+        .deadline
+        .clone();
+
+    tcp_state
+        .get_recv_request_mut(&uid)
+        .reset_inactivity_deadline(current_time, limit);
+
     let RecvRequest {
         connection,
         buffered_data,
@@ -343,10 +415,17 @@ pub fn handle_recv_common(
 
     if timed_out {
         dispatcher.dispatch_back(on_timeout, (uid, buffered_data.clone()));
-        tcp_state.remove_recv_request(&uid)
+        tcp_state.remove_recv_request(&uid);
     } else {
         if can_recv_value == false {
-            tcp_state.get_recv_request_mut(&uid).recv_on_poll = true;
+            let request = tcp_state.get_recv_request_mut(&uid);
+
+            // Nothing left to drain from a closed connection.
+            if request.draining {
+                complete_drain(tcp_state, dispatcher, uid, "Connection closed".to_string());
+            } else {
+                request.recv_on_poll = true;
+            }
             return;
         }
 
@@ -354,65 +433,75 @@ pub fn handle_recv_common(
         let conn = tcp_state.get_connection_mut(&connection);
 
         if conn.events.is_some() {
-            let ConnectionEvent::Ready { can_recv, .. } = conn.events_mut() else {
-                unreachable!()
-            };
+            if let ConnectionEvent::Ready { can_recv, .. } = conn.events_mut() {
+                *can_recv = can_recv_value;
+            }
 
-            *can_recv = can_recv_value;
-            dispatch_recv(tcp_state, dispatcher, uid);
+            dispatch_recv(tcp_state, dispatcher, current_time, uid);
         } else {
             tcp_state.get_recv_request_mut(&uid).recv_on_poll = true;
         }
     }
 }
 
-pub fn dispatch_send(tcp_state: &mut TcpState, dispatcher: &mut Dispatcher, uid: Uid) {
+pub fn dispatch_send(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    current_time: u128,
+    uid: Uid,
+) {
     let connection = tcp_state.get_send_request(&uid).connection;
     let conn = tcp_state.get_connection(&connection);
 
+    if conn.write_shut_down() {
+        let request = tcp_state.get_send_request(&uid);
+
+        dispatcher.dispatch_back(
+            &request.on_error,
+            (uid, request.error_message(WRITE_SHUT_DOWN.to_string())),
+        );
+        tcp_state.record_send_error(&uid, SendErrorKind::WriteShutDown);
+        tcp_state.remove_send_request(&uid);
+        return;
+    }
+
     if conn.events.is_none() {
         tcp_state.get_send_request_mut(&uid).send_on_poll = true;
         return;
     }
 
     match conn.events() {
-        ConnectionEvent::Ready { can_send: true, .. } => {
-            let SendRequest {
-                data, bytes_sent, ..
-            } = tcp_state.get_send_request(&uid);
-
-            dispatcher.dispatch_effect(MioEffectfulAction::TcpWrite {
-                uid,
-                connection,
-                data: (&data[*bytes_sent..]).into(),
-                on_success: callback!(|uid: Uid| TcpAction::SendSuccess { uid }),
-                on_success_partial: callback!(|(uid: Uid, count: usize)| TcpAction::SendSuccessPartial { uid, count }),
-                on_interrupted: callback!(|uid: Uid| TcpAction::SendErrorInterrupted { uid }),
-                on_would_block: callback!(|uid: Uid| TcpAction::SendErrorTryAgain { uid }),
-                on_error: callback!(|(uid: Uid, error: String)| TcpAction::SendError { uid, error })
-            });
+        ConnectionEvent::Ready { can_send: true, .. } if tcp_state.is_send_head(&uid) => {
+            dispatch_write(tcp_state, dispatcher, current_time, uid)
         }
-        ConnectionEvent::Ready {
-            can_send: false, ..
-        } => tcp_state.get_send_request_mut(&uid).send_on_poll = true,
+        ConnectionEvent::Ready { .. } => tcp_state.get_send_request_mut(&uid).send_on_poll = true,
         ConnectionEvent::Closed => {
+            let request = tcp_state.get_send_request(&uid);
+
             dispatcher.dispatch_back(
-                &tcp_state.get_send_request(&uid).on_error,
-                (uid, "Connection closed".to_string()),
+                &request.on_error,
+                (uid, request.error_message("Connection closed".to_string())),
             );
-            tcp_state.remove_send_request(&uid)
+            tcp_state.record_send_error(&uid, request.error_kind());
+            tcp_state.remove_send_request(&uid);
         }
         ConnectionEvent::Error => {
-            dispatcher.dispatch_back(
-                &tcp_state.get_send_request(&uid).on_error,
-                (uid, "Connection error".to_string()),
-            );
-            tcp_state.remove_send_request(&uid)
+            let request = tcp_state.get_send_request(&uid);
+            let error = request.error_message("Connection error".to_string());
+
+            dispatcher.dispatch_back(&request.on_error, (uid, error));
+            tcp_state.record_send_error(&uid, request.error_kind());
+            tcp_state.remove_send_request(&uid);
         }
     };
 }
 
-pub fn dispatch_recv(tcp_state: &mut TcpState, dispatcher: &mut Dispatcher, uid: Uid) {
+pub fn dispatch_recv(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    current_time: u128,
+    uid: Uid,
+) {
     let connection = tcp_state.get_recv_request(&uid).connection;
     let conn = tcp_state.get_connection(&connection);
 
@@ -423,35 +512,250 @@ pub fn dispatch_recv(tcp_state: &mut TcpState, dispatcher: &mut Dispatcher, uid:
 
     match conn.events() {
         ConnectionEvent::Ready { can_recv: true, .. } => {
-            dispatcher.dispatch_effect(MioEffectfulAction::TcpRead {
-                uid,
-                connection,
-                len: tcp_state.get_recv_request(&uid).remaining_bytes,
-                on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpAction::RecvSuccess { uid, data }),
-                on_success_partial: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpAction::RecvSuccessPartial { uid, partial_data }),
-                on_interrupted: callback!(|uid: Uid| TcpAction::RecvErrorInterrupted { uid }),
-                on_would_block: callback!(|uid: Uid| TcpAction::RecvErrorTryAgain { uid }),
-                on_error: callback!(|(uid: Uid, error: String)| TcpAction::RecvError { uid, error })
-            });
+            dispatch_read(tcp_state, dispatcher, current_time, uid)
         }
         ConnectionEvent::Ready {
             can_recv: false, ..
         } => tcp_state.get_recv_request_mut(&uid).recv_on_poll = true,
         ConnectionEvent::Closed => {
-            // Recv failed, notify caller
-            dispatcher.dispatch_back(
-                &tcp_state.get_recv_request_mut(&uid).on_error,
-                (uid, "Connection closed".to_string()),
-            );
-            tcp_state.remove_recv_request(&uid)
+            // The peer might have sent data right before closing the
+            // connection: read what is left before reporting the closure.
+            tcp_state.get_recv_request_mut(&uid).draining = true;
+            dispatch_read(tcp_state, dispatcher, current_time, uid)
         }
         ConnectionEvent::Error => {
             // Recv failed, notify caller
-            dispatcher.dispatch_back(
-                &tcp_state.get_recv_request_mut(&uid).on_error,
-                (uid, "Connection error".to_string()),
-            );
-            tcp_state.remove_recv_request(&uid)
+            let error = "Connection error".to_string();
+
+            dispatcher.dispatch_back(&tcp_state.get_recv_request(&uid).on_error, (uid, error));
+            tcp_state.remove_recv_request(&uid);
         }
     }
 }
+
+// Hands the buffered data to the caller once the RecvRequest is complete
+// (see `RecvRequest::is_complete`), otherwise reads more.
+pub fn continue_recv(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    current_time: u128,
+    uid: Uid,
+) {
+    let request = tcp_state.get_recv_request_mut(&uid);
+
+    if request.is_complete() {
+        let (connection, data) = (request.connection, request.buffered_data.clone());
+
+        dispatcher.dispatch_back(&request.on_success, (uid, data.clone()));
+        tcp_state.remove_recv_request(&uid);
+        tcp_state.log_operation(&connection, current_time, OperationKind::Recv, &data)
+    } else {
+        handle_recv_common(tcp_state, dispatcher, current_time, uid, true)
+    }
+}
+
+// Runs the timeout path of a send, recv or (outgoing) connect request, see
+// `TcpAction::ExpireRequest`. As with a connect deadline, closing the
+// connection is left to the caller.
+#[cfg(test)]
+pub fn expire_request(tcp_state: &mut TcpState, dispatcher: &mut Dispatcher, uid: Uid) {
+    if tcp_state.has_send_request(&uid) {
+        dispatcher.dispatch_back(&tcp_state.get_send_request(&uid).on_timeout, uid);
+        tcp_state.remove_send_request(&uid);
+    } else if tcp_state.has_recv_request(&uid) {
+        let RecvRequest {
+            buffered_data,
+            on_timeout,
+            ..
+        } = tcp_state.get_recv_request(&uid);
+
+        dispatcher.dispatch_back(on_timeout, (uid, buffered_data.clone()));
+        tcp_state.remove_recv_request(&uid);
+    } else if tcp_state.has_connection(&uid) {
+        let conn = tcp_state.get_connection_mut(&uid);
+        let ConnectionType::Outgoing { on_timeout, .. } = &conn.conn_type else {
+            panic!("ExpireRequest on incoming connection {:?}", uid)
+        };
+
+        assert!(
+            matches!(
+                conn.status,
+                ConnectionStatus::Pending | ConnectionStatus::PendingCheck
+            ),
+            "ExpireRequest on established connection {:?}",
+            uid
+        );
+        dispatcher.dispatch_back(on_timeout, uid);
+        // The deadline sweep must not report it again.
+        conn.timeout = TimeoutAbsolute::Never;
+    } else {
+        panic!("ExpireRequest on unknown request {:?}", uid)
+    }
+}
+
+// Completes a RecvRequest that drained a closed connection. The data read so
+// far is handed to `on_success`, like a short read at the end of the stream:
+// the closure itself is reported by the connection's close callback. If there
+// is no data the closure is reported as `error`.
+pub fn complete_drain(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    uid: Uid,
+    error: String,
+) {
+    let RecvRequest {
+        buffered_data,
+        on_success,
+        on_error,
+        ..
+    } = tcp_state.get_recv_request(&uid);
+
+    if buffered_data.is_empty() {
+        dispatcher.dispatch_back(on_error, (uid, error));
+    } else {
+        dispatcher.dispatch_back(on_success, (uid, buffered_data.clone()));
+    }
+
+    tcp_state.remove_recv_request(&uid);
+}
+
+// Writes as much of the SendRequest's remaining data as the connection's
+// shaper allows. If nothing is allowed, the request is retried on poll.
+fn dispatch_write(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    current_time: u128,
+    uid: Uid,
+) {
+    let len = tcp_state.reserve_send(&uid, current_time);
+    let request = tcp_state.get_send_request_mut(&uid);
+
+    if len == 0 && request.bytes_sent < request.data.len() {
+        request.send_on_poll = true;
+        return;
+    }
+
+    // In flight: polls don't write again for this request until the result
+    // is in, see `handle_send_common()`.
+    request.send_on_poll = false;
+    // The write might be issued from a poll: keep the request's trace.
+    dispatcher.with_trace_id(request.trace_id, |dispatcher| {
+        dispatcher.dispatch_effect(MioEffectfulAction::TcpWrite {
+            uid,
+            connection: request.connection,
+            data: RcSlice::new(
+                request.data.clone(),
+                request.bytes_sent..request.bytes_sent + len,
+            ),
+            on_success: callback!(|uid: Uid| TcpAction::SendSuccess { uid }),
+            on_success_partial: callback!(|(uid: Uid, count: usize)| TcpAction::SendSuccessPartial { uid, count }),
+            on_interrupted: callback!(|uid: Uid| TcpAction::SendErrorInterrupted { uid }),
+            on_would_block: callback!(|uid: Uid| TcpAction::SendErrorTryAgain { uid }),
+            on_error: callback!(|(uid: Uid, error: String)| TcpAction::SendError { uid, error })
+        })
+    });
+}
+
+// Reads as much of the RecvRequest's remaining data as the connection's
+// shaper allows. If nothing is allowed, the request is retried on poll.
+fn dispatch_read(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    current_time: u128,
+    uid: Uid,
+) {
+    let len = tcp_state.reserve_recv(&uid, current_time);
+    let request = tcp_state.get_recv_request_mut(&uid);
+
+    if len == 0 {
+        request.recv_on_poll = true;
+        return;
+    }
+
+    // In flight, see `dispatch_write()`.
+    request.recv_on_poll = false;
+    dispatcher.with_trace_id(request.trace_id, |dispatcher| {
+        dispatcher.dispatch_effect(MioEffectfulAction::TcpRead {
+            uid,
+            connection: request.connection,
+            len,
+            on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpAction::RecvSuccess { uid, data }),
+            on_success_partial: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpAction::RecvSuccessPartial { uid, partial_data }),
+            on_interrupted: callback!(|uid: Uid| TcpAction::RecvErrorInterrupted { uid }),
+            on_would_block: callback!(|uid: Uid| TcpAction::RecvErrorTryAgain { uid }),
+            on_error: callback!(|(uid: Uid, error: String)| TcpAction::RecvError { uid, error })
+        })
+    });
+}
+
+// Hands `line` to the LineRequest `uid` and keeps the data that follows it
+// for the next one.
+pub fn complete_line(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    uid: Uid,
+    line: Vec<u8>,
+    rest: Vec<u8>,
+) {
+    let LineRequest {
+        connection,
+        on_line,
+        ..
+    } = tcp_state.take_line_request(&uid);
+
+    tcp_state.get_connection_mut(&connection).line_buffer = rest;
+    dispatcher.dispatch_back(&on_line, (connection, line));
+}
+
+// A line longer than the LineRequest's `max_len` is a protocol error: the
+// connection is closed (without notifying the application once it's removed).
+pub fn line_overflow(
+    tcp_state: &mut TcpState,
+    dispatcher: &mut Dispatcher,
+    current_time: u128,
+    uid: Uid,
+) {
+    let LineRequest {
+        connection,
+        max_len,
+        on_error,
+        ..
+    } = tcp_state.take_line_request(&uid);
+    let error = format!("Line exceeds maximum length of {} bytes", max_len);
+
+    tcp_state.log_connection(
+        &connection,
+        current_time,
+        ConnectionLogEvent::RecvError(error.clone()),
+    );
+
+    tcp_state
+        .get_connection_mut(&connection)
+        .line_buffer
+        .clear();
+    close_internal(tcp_state, dispatcher, connection);
+    dispatcher.dispatch_back(&on_error, (connection, error));
+}
+
+// Closes `connection` on behalf of the application, which isn't notified once
+// it's removed.
+pub fn close_internal(tcp_state: &mut TcpState, dispatcher: &mut Dispatcher, connection: Uid) {
+    let poll = tcp_state.poll_for_connection(&connection);
+    let conn = tcp_state.get_connection_mut(&connection);
+
+    conn.status = ConnectionStatus::CloseRequestInternal;
+
+    if conn.register_retry_at.take().is_some() {
+        dispatcher.dispatch_effect(MioEffectfulAction::TcpClose {
+            connection,
+            on_success: callback!(|connection: Uid| TcpAction::CloseSuccess { connection }),
+        });
+    } else {
+        dispatcher.dispatch_effect(MioEffectfulAction::PollDeregisterTcpConnection {
+            poll,
+            connection,
+            on_success: callback!(|connection: Uid| TcpAction::DeregisterConnectionSuccess { connection }),
+            on_error: callback!(|(connection: Uid, error: String)| TcpAction::DeregisterConnectionError { connection, error })
+        });
+    }
+}
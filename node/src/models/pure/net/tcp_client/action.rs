@@ -3,7 +3,7 @@ use crate::{
         action::{self, Action, ActionKind, Redispatch, Timeout},
         state::Uid,
     },
-    models::pure::net::tcp::action::TcpPollEvents,
+    models::pure::net::tcp::action::{RecvResult, SendResult, TcpPollEvents},
 };
 use serde_derive::{Deserialize, Serialize};
 use std::rc::Rc;
@@ -17,6 +17,8 @@ pub enum TcpClientAction {
         address: String,
         timeout: Timeout,
         on_success: Redispatch<Uid>,
+        // The connection is closed (see `TcpAction::Connect`) and forgotten,
+        // `on_close` isn't called for it.
         on_timeout: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
         on_close: Redispatch<Uid>,
@@ -39,6 +41,7 @@ pub enum TcpClientAction {
     },
     Close {
         connection: Uid,
+        deliver_buffered: bool, // see `TcpAction::Close`
     },
     CloseEventNotify {
         connection: Uid,
@@ -69,6 +72,12 @@ pub enum TcpClientAction {
         uid: Uid,
         error: String,
     },
+    // The send was cancelled with `TcpAction::CancelSend`: its entry is
+    // dropped, whoever cancelled it is told by `TcpState`.
+    SendCancelled {
+        uid: Uid,
+        result: SendResult,
+    },
     Recv {
         uid: Uid,
         connection: Uid,
@@ -90,6 +99,35 @@ pub enum TcpClientAction {
         uid: Uid,
         error: String,
     },
+    // Same as `SendCancelled`, for `TcpAction::CancelRecv`.
+    RecvCancelled {
+        uid: Uid,
+        result: RecvResult,
+    },
+    // Like `Recv`, but the received bytes are appended to the connection's
+    // ring (see `TcpClientState::ring_mut`) instead of being handed over.
+    // Callbacks get the number of bytes buffered in the ring.
+    RecvIntoRing {
+        uid: Uid,
+        connection: Uid,
+        count: usize, // number of bytes to read
+        timeout: Timeout,
+        on_success: Redispatch<(Uid, usize)>,
+        on_timeout: Redispatch<(Uid, usize)>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    RecvIntoRingSuccess {
+        uid: Uid,
+        data: Vec<u8>,
+    },
+    RecvIntoRingTimeout {
+        uid: Uid,
+        partial_data: Vec<u8>,
+    },
+    RecvIntoRingError {
+        uid: Uid,
+        error: String,
+    },
 }
 
 impl Action for TcpClientAction {
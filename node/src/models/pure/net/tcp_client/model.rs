@@ -1,6 +1,6 @@
 use super::{
     action::TcpClientAction,
-    state::{RecvRequest, SendRequest, TcpClientState},
+    state::{RecvRequest, RingRecvRequest, SendRequest, TcpClientState},
 };
 use crate::{
     automaton::{
@@ -11,7 +11,10 @@ use crate::{
     },
     callback,
     models::pure::net::{
-        tcp::{action::TcpAction, state::TcpState},
+        tcp::{
+            action::{RecvResult, SendResult, TcpAction},
+            state::TcpState,
+        },
         tcp_client::state::Connection,
     },
 };
@@ -40,13 +43,13 @@ impl PureModel for TcpClientState {
                 timeout,
                 on_success,
                 on_error,
-            } => dispatcher.dispatch(TcpAction::Poll {
+            } => dispatcher.dispatch(TcpAction::poll(
                 uid,
-                objects: Vec::new(),
+                Vec::new(),
                 timeout,
                 on_success,
                 on_error,
-            }),
+            )),
             TcpClientAction::Connect {
                 connection,
                 address,
@@ -63,6 +66,9 @@ impl PureModel for TcpClientState {
                 dispatcher.dispatch(TcpAction::Connect {
                     connection,
                     address,
+                    options: None,
+                    bind_address: None,
+                    nodelay: false,
                     timeout,
                     on_success: callback!(|connection: Uid| TcpClientAction::ConnectSuccess { connection }),
                     on_timeout: callback!(|connection: Uid| TcpClientAction::ConnectTimeout { connection }),
@@ -77,11 +83,12 @@ impl PureModel for TcpClientState {
                 dispatcher.dispatch_back(on_success, connection);
             }
             TcpClientAction::ConnectTimeout { connection } => {
-                let Connection { on_timeout, .. } = state
-                    .substate::<TcpClientState>()
-                    .get_connection(&connection);
+                // `TcpState` already closes the connection, without notification.
+                let client_state: &mut TcpClientState = state.substate_mut();
+                let Connection { on_timeout, .. } = client_state.get_connection(&connection);
 
                 dispatcher.dispatch_back(on_timeout, connection);
+                client_state.remove_connection(&connection);
             }
             TcpClientAction::ConnectError { connection, error } => {
                 let Connection { on_error, .. } = state
@@ -90,8 +97,12 @@ impl PureModel for TcpClientState {
 
                 dispatcher.dispatch_back(on_error, (connection, error));
             }
-            TcpClientAction::Close { connection } => dispatcher.dispatch(TcpAction::Close {
+            TcpClientAction::Close {
+                connection,
+                deliver_buffered,
+            } => dispatcher.dispatch(TcpAction::Close {
                 connection,
+                deliver_buffered,
                 on_success: callback!(|connection: Uid| TcpClientAction::CloseEventNotify {
                     connection
                 }),
@@ -129,6 +140,7 @@ impl PureModel for TcpClientState {
                     on_success: callback!(|uid: Uid| TcpClientAction::SendSuccess { uid }),
                     on_timeout: callback!(|uid: Uid| TcpClientAction::SendTimeout { uid }),
                     on_error: callback!(|(uid: Uid, error: String)| TcpClientAction::SendError { uid, error }),
+                    on_cancelled: Some(callback!(|(uid: Uid, result: SendResult)| TcpClientAction::SendCancelled { uid, result })),
                 });
             }
             TcpClientAction::SendSuccess { uid } => {
@@ -157,11 +169,17 @@ impl PureModel for TcpClientState {
                 dispatcher.dispatch_back(&on_error, (uid, error));
                 dispatcher.dispatch(TcpAction::Close {
                     connection,
+                    deliver_buffered: false,
                     on_success: callback!(|connection: Uid| TcpClientAction::CloseEventNotify {
                         connection
                     }),
                 })   
             }
+            TcpClientAction::SendCancelled { uid, .. } => {
+                state
+                    .substate_mut::<TcpClientState>()
+                    .take_send_request(&uid);
+            }
             TcpClientAction::Recv {
                 uid,
                 connection,
@@ -183,6 +201,7 @@ impl PureModel for TcpClientState {
                     on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpClientAction::RecvSuccess { uid, data }),
                     on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpClientAction::RecvTimeout { uid, partial_data }),
                     on_error: callback!(|(uid: Uid, error: String)| TcpClientAction::RecvError { uid, error }),
+                    on_cancelled: Some(callback!(|(uid: Uid, result: RecvResult)| TcpClientAction::RecvCancelled { uid, result })),
                 });
             }
             TcpClientAction::RecvSuccess { uid, data } => {
@@ -211,11 +230,74 @@ impl PureModel for TcpClientState {
                 dispatcher.dispatch_back(&on_error, (uid, error));
                 dispatcher.dispatch(TcpAction::Close {
                     connection,
+                    deliver_buffered: false,
                     on_success: callback!(|connection: Uid| TcpClientAction::CloseEventNotify {
                         connection
                     }),
                 })
             }
+            TcpClientAction::RecvCancelled { uid, .. } => {
+                let client_state: &mut TcpClientState = state.substate_mut();
+
+                client_state.take_recv_request(&uid);
+                // Also set for a `RecvIntoRing`.
+                client_state.ring_recv_requests.remove(&uid);
+            }
+            TcpClientAction::RecvIntoRing {
+                uid,
+                connection,
+                count,
+                timeout,
+                on_success,
+                on_timeout,
+                on_error,
+            } => {
+                state
+                    .substate_mut::<TcpClientState>()
+                    .new_ring_recv_request(&uid, connection, on_success, on_timeout, on_error);
+
+                dispatcher.dispatch(TcpClientAction::Recv {
+                    uid,
+                    connection,
+                    count,
+                    timeout,
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpClientAction::RecvIntoRingSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpClientAction::RecvIntoRingTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| TcpClientAction::RecvIntoRingError { uid, error }),
+                });
+            }
+            TcpClientAction::RecvIntoRingSuccess { uid, data } => {
+                let client_state: &mut TcpClientState = state.substate_mut();
+                let RingRecvRequest {
+                    connection,
+                    on_success,
+                    ..
+                } = client_state.take_ring_recv_request(&uid);
+                let ring = client_state.ring_mut(&connection);
+
+                ring.extend(&data);
+                dispatcher.dispatch_back(&on_success, (uid, ring.len()))
+            }
+            TcpClientAction::RecvIntoRingTimeout { uid, partial_data } => {
+                let client_state: &mut TcpClientState = state.substate_mut();
+                let RingRecvRequest {
+                    connection,
+                    on_timeout,
+                    ..
+                } = client_state.take_ring_recv_request(&uid);
+                let ring = client_state.ring_mut(&connection);
+
+                ring.extend(&partial_data);
+                dispatcher.dispatch_back(&on_timeout, (uid, ring.len()))
+            }
+            TcpClientAction::RecvIntoRingError { uid, error } => {
+                // The connection is closed by `RecvError`.
+                let RingRecvRequest { on_error, .. } = state
+                    .substate_mut::<TcpClientState>()
+                    .take_ring_recv_request(&uid);
+
+                dispatcher.dispatch_back(&on_error, (uid, error))
+            }
         }
     }
 }
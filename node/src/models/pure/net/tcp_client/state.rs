@@ -1,17 +1,23 @@
-use crate::automaton::{
-    action::Redispatch,
-    state::{Objects, Uid},
+use crate::{
+    automaton::{
+        action::Redispatch,
+        state::{Objects, Uid},
+    },
+    models::pure::net::ring_buffer::RingBuffer,
 };
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Connection {
     pub on_success: Redispatch<Uid>,
     pub on_timeout: Redispatch<Uid>,
     pub on_error: Redispatch<(Uid, String)>,
     pub on_close: Redispatch<Uid>,
+    // Created by the first `TcpClientAction::RecvIntoRing`.
+    pub ring: Option<RingBuffer>,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct SendRequest {
     pub connection: Uid,
     pub on_success: Redispatch<Uid>,
@@ -19,7 +25,7 @@ pub struct SendRequest {
     pub on_error: Redispatch<(Uid, String)>,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct RecvRequest {
     pub connection: Uid,
     pub on_success: Redispatch<(Uid, Vec<u8>)>,
@@ -27,11 +33,21 @@ pub struct RecvRequest {
     pub on_error: Redispatch<(Uid, String)>,
 }
 
-#[derive(Debug)]
+// Callbacks get the number of bytes buffered in the connection's ring.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RingRecvRequest {
+    pub connection: Uid,
+    pub on_success: Redispatch<(Uid, usize)>,
+    pub on_timeout: Redispatch<(Uid, usize)>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct TcpClientState {
     pub connections: Objects<Connection>,
     pub send_requests: Objects<SendRequest>,
     pub recv_requests: Objects<RecvRequest>,
+    pub ring_recv_requests: Objects<RingRecvRequest>,
 }
 
 impl TcpClientState {
@@ -40,6 +56,7 @@ impl TcpClientState {
             connections: Objects::<Connection>::new(),
             send_requests: Objects::<SendRequest>::new(),
             recv_requests: Objects::<RecvRequest>::new(),
+            ring_recv_requests: Objects::<RingRecvRequest>::new(),
         }
     }
     pub fn get_connection(&self, connection: &Uid) -> &Connection {
@@ -65,6 +82,7 @@ impl TcpClientState {
                     on_timeout,
                     on_error,
                     on_close,
+                    ring: None,
                 },
             )
             .is_some()
@@ -141,4 +159,46 @@ impl TcpClientState {
             .remove(uid)
             .expect(&format!("Take attempt on inexistent RecvRequest {:?}", uid))
     }
+
+    pub fn new_ring_recv_request(
+        &mut self,
+        uid: &Uid,
+        connection: Uid,
+        on_success: Redispatch<(Uid, usize)>,
+        on_timeout: Redispatch<(Uid, usize)>,
+        on_error: Redispatch<(Uid, String)>,
+    ) {
+        if self
+            .ring_recv_requests
+            .insert(
+                *uid,
+                RingRecvRequest {
+                    connection,
+                    on_success,
+                    on_timeout,
+                    on_error,
+                },
+            )
+            .is_some()
+        {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    pub fn take_ring_recv_request(&mut self, uid: &Uid) -> RingRecvRequest {
+        self.ring_recv_requests.remove(uid).expect(&format!(
+            "Take attempt on inexistent RingRecvRequest {:?}",
+            uid
+        ))
+    }
+
+    // The ring of `connection`, for parsers to peek at and consume the bytes
+    // received with `TcpClientAction::RecvIntoRing`.
+    pub fn ring_mut(&mut self, connection: &Uid) -> &mut RingBuffer {
+        self.connections
+            .get_mut(connection)
+            .expect(&format!("Connection object {:?} not found", connection))
+            .ring
+            .get_or_insert_with(RingBuffer::default)
+    }
 }
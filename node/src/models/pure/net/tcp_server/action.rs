@@ -3,7 +3,7 @@ use crate::{
         action::{self, Action, ActionKind, Redispatch, Timeout},
         state::Uid,
     },
-    models::pure::net::tcp::action::TcpPollEvents,
+    models::pure::net::tcp::action::{RecvResult, SendResult, TcpPollEvents},
 };
 use serde_derive::{Deserialize, Serialize};
 use std::rc::Rc;
@@ -16,12 +16,34 @@ pub enum TcpServerAction {
         address: String,
         listener: Uid,
         max_connections: usize,
+        // Connections waiting to be accepted beyond the backlog are refused
+        // by the OS (on Linux, which also caps it to `net.core.somaxconn`).
+        // `None` keeps the default (see `MioEffectfulAction::TcpListen`),
+        // which fits unless many clients may connect at once: a server with a
+        // `max_connections` above it should raise it to `max_connections`
+        // (and the events capacity in `TcpAction::Init` along). Connections
+        // accepted beyond `max_connections` are still closed right away.
+        backlog: Option<i32>,
+        routing: RoutingPolicy,
+        // If set, asked to admit or reject every accepted connection (within
+        // `max_connections`) before it's handed to `on_new_connection`. The
+        // decision is taken with `Admit` or `Reject`.
+        admission_control: Option<Redispatch<(Uid, AdmissionRequest)>>,
+        // Sets TCP_NODELAY on every accepted connection.
+        nodelay: bool,
         on_success: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
         on_new_connection: Redispatch<(Uid, Uid)>,
         on_connection_closed: Redispatch<(Uid, Uid)>,
         on_listener_closed: Redispatch<Uid>,
     },
+    // Subscribes `on_event` to the lifecycle events of the connections of all
+    // listeners, replacing any previous subscriber. This is an aggregated
+    // notification path, the listeners' callbacks are still dispatched.
+    Subscribe {
+        // (connection, event)
+        on_event: Redispatch<(Uid, ConnectionLifecycleEvent)>,
+    },
     NewSuccess {
         listener: Uid,
     },
@@ -53,8 +75,26 @@ pub enum TcpServerAction {
         connection: Uid,
         error: String,
     },
+    // A no-op if the connection is already being closed (e.g. after a send
+    // or recv error).
     Close {
         connection: Uid,
+        deliver_buffered: bool, // see `TcpAction::Close`
+    },
+    // Closes every established connection of `listener` like `Close` does,
+    // while the listener keeps accepting new ones. `on_complete` is called
+    // once all of them are closed, including those that were already being
+    // closed.
+    CloseAll {
+        listener: Uid,
+        on_complete: Redispatch<Uid>,
+    },
+    // Closes, like `Close` does, the established connections of all listeners
+    // on which no send or recv completed for more than `max_idle_ms` (see
+    // `get_current_time`). Meant to be dispatched periodically, e.g. on every
+    // poll.
+    SweepIdle {
+        max_idle_ms: u64,
     },
     CloseEventNotify {
         connection: Uid,
@@ -62,6 +102,28 @@ pub enum TcpServerAction {
     CloseEventInternal {
         connection: Uid,
     },
+    CloseEventRejected {
+        connection: Uid,
+    },
+    // Admission decisions for a connection passed to `admission_control`.
+    // Ignored if the connection is no longer waiting for one (e.g. closed).
+    Admit {
+        connection: Uid,
+    },
+    Reject {
+        connection: Uid,
+    },
+    // Hands an established connection over to a different handler (e.g. after
+    // an HTTP → WebSocket upgrade). The socket and its poll registration are
+    // preserved; `leftover` holds bytes the previous handler already received
+    // but did not consume, and is passed as-is to `new_handler.on_upgrade`.
+    // The previous handler must not have in-flight send/recv requests on the
+    // connection.
+    Upgrade {
+        connection: Uid,
+        leftover: Vec<u8>,
+        new_handler: ConnectionHandler,
+    },
     Send {
         uid: Uid,
         connection: Uid,
@@ -85,6 +147,31 @@ pub enum TcpServerAction {
         uid: Uid,
         error: String,
     },
+    // The send was cancelled with `TcpAction::CancelSend`: its entry is
+    // dropped, whoever cancelled it is told by `TcpState`.
+    SendCancelled {
+        uid: Uid,
+        result: SendResult,
+    },
+    // Sends `data` on `send_connection` like `Send` does, and closes
+    // `close_connection` once the send is done (whatever its outcome), before
+    // the send's callback is called. E.g. a relay notifies one peer that the
+    // other one is gone. `close_connection` is left alone if it was closed
+    // meanwhile.
+    SendThenCloseOther {
+        uid: Uid,
+        send_connection: Uid,
+        #[serde(
+            serialize_with = "action::serialize_rc_bytes",
+            deserialize_with = "action::deserialize_rc_bytes"
+        )]
+        data: Rc<[u8]>,
+        close_connection: Uid,
+        timeout: Timeout,
+        on_success: Redispatch<Uid>,
+        on_timeout: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
     Recv {
         uid: Uid,
         connection: Uid,
@@ -106,8 +193,133 @@ pub enum TcpServerAction {
         uid: Uid,
         error: String,
     },
+    // Same as `SendCancelled`, for `TcpAction::CancelRecv`.
+    RecvCancelled {
+        uid: Uid,
+        result: RecvResult,
+    },
+    // Like `Recv`, but completes once the received data contains `delimiter`
+    // (see `TcpAction::RecvUntil`), or once `max_bytes` were received.
+    RecvUntil {
+        uid: Uid,
+        connection: Uid,
+        delimiter: Vec<u8>,
+        max_bytes: usize,
+        timeout: Timeout,
+        on_success: Redispatch<(Uid, Vec<u8>)>,
+        on_timeout: Redispatch<(Uid, Vec<u8>)>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    // Like `Recv`, but the received bytes are appended to the connection's
+    // ring (see `TcpServerState::ring_mut`) instead of being handed over.
+    // Callbacks get the number of bytes buffered in the ring.
+    RecvIntoRing {
+        uid: Uid,
+        connection: Uid,
+        count: usize, // number of bytes to read
+        timeout: Timeout,
+        on_success: Redispatch<(Uid, usize)>,
+        on_timeout: Redispatch<(Uid, usize)>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    RecvIntoRingSuccess {
+        uid: Uid,
+        data: Vec<u8>,
+    },
+    RecvIntoRingTimeout {
+        uid: Uid,
+        partial_data: Vec<u8>,
+    },
+    RecvIntoRingError {
+        uid: Uid,
+        error: String,
+    },
+    // Sends `data` to every member of `group` (see
+    // `TcpServerState::add_to_group`), with a `Send` per member. Once all the
+    // sends are done, `on_complete` gets the members the data couldn't be
+    // sent to (on timeout or error).
+    SendToGroup {
+        uid: Uid,
+        group: String,
+        #[serde(
+            serialize_with = "action::serialize_rc_bytes",
+            deserialize_with = "action::deserialize_rc_bytes"
+        )]
+        data: Rc<[u8]>,
+        timeout: Timeout,
+        on_complete: Redispatch<(Uid, Vec<Uid>)>,
+    },
+    GroupSendSuccess {
+        uid: Uid,
+    },
+    GroupSendTimeout {
+        uid: Uid,
+    },
+    GroupSendError {
+        uid: Uid,
+        error: String,
+    },
+    // Closes every member of `group` like `Close` does, skipping those already
+    // being closed. Closed connections leave their groups.
+    CloseGroup {
+        group: String,
+        deliver_buffered: bool,
+    },
 }
 
 impl Action for TcpServerAction {
     const KIND: ActionKind = ActionKind::Pure;
 }
+
+// How a listener assigns its accepted connections to shards (e.g. handlers
+// or worker instances of the application). The shard of a connection can be
+// queried with `TcpServerState::connection_shard` once the application is
+// notified of the connection.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum RoutingPolicy {
+    // Every connection is assigned shard 0.
+    None,
+    // Connections are assigned one of `shards` shards in turn.
+    RoundRobin { shards: usize },
+    // Connections from the same source IP are assigned the same shard, also
+    // across reconnects. New source IPs are assigned one of `shards` shards
+    // in turn.
+    StickyBySourceIp { shards: usize },
+}
+
+// Reported to the `TcpServerAction::Subscribe` subscriber, in the order they
+// happen for a given connection.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum ConnectionLifecycleEvent {
+    // The connection was accepted and registered. `number` is its
+    // per-listener number, see `Connection::number`.
+    Accepted { number: u64 },
+    // The connection was handed to the application (`on_new_connection`).
+    // Not reported for connections closed for exceeding `max_connections` or
+    // rejected by `admission_control`.
+    Established,
+    DataReceived { bytes: usize },
+    DataSent { bytes: usize },
+    // `reason` is the error that caused the closure, if any.
+    Closed { reason: Option<String> },
+}
+
+// Passed to a listener's `admission_control` along with the `Uid` of the
+// accepted connection.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct AdmissionRequest {
+    pub listener: Uid,
+    pub peer_address: String,
+    // Connections of the listener, not counting the accepted one.
+    pub connections: usize,
+    // Connections of the listener from the same IP as the accepted one.
+    pub peer_connections: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct ConnectionHandler {
+    // (connection, leftover bytes)
+    pub on_upgrade: Redispatch<(Uid, Vec<u8>)>,
+    // Replaces the listener's `on_connection_closed` for this connection.
+    pub on_connection_closed: Redispatch<(Uid, Uid)>,
+}
@@ -1,6 +1,9 @@
 use super::{
-    action::TcpServerAction,
-    state::{Listener, PollRequest, RecvRequest, SendRequest, TcpServerState},
+    action::{AdmissionRequest, ConnectionLifecycleEvent, TcpServerAction},
+    state::{
+        peer_ip, GroupSendRequest, Listener, PollRequest, RecvRequest, RingRecvRequest,
+        SendRequest, TcpServerState,
+    },
 };
 use crate::{
     automaton::{
@@ -10,15 +13,22 @@ use crate::{
         state::{ModelState, State, Uid},
     },
     callback,
-    models::pure::net::tcp::{
-        action::{Event, ListenerEvent, TcpAction, TcpPollEvents},
-        state::TcpState,
+    models::pure::{
+        net::tcp::{
+            action::{Event, ListenerEvent, RecvResult, SendResult, TcpAction, TcpPollEvents},
+            state::TcpState,
+        },
+        time::model::get_current_time,
     },
 };
 use log::warn;
+use std::collections::{BTreeMap, BTreeSet};
 
 // The `TcpServerState` model is an abstraction layer over the `TcpState` model
 // providing a simpler interface for working with TCP server operations.
+//
+// Besides the per-listener callbacks, the lifecycle of every connection can be
+// observed from a single subscriber (`TcpServerAction::Subscribe`).
 
 // This model depends on the `TcpState` model.
 impl RegisterModel for TcpServerState {
@@ -40,29 +50,47 @@ impl PureModel for TcpServerState {
                 address,
                 listener,
                 max_connections,
+                backlog,
+                routing,
+                admission_control,
+                nodelay,
                 on_success,
                 on_error,
                 on_new_connection,
                 on_connection_closed,
                 on_listener_closed,
             } => {
-                state.substate_mut::<TcpServerState>().new_listener(
+                let server_state: &mut TcpServerState = state.substate_mut();
+
+                server_state.new_listener(
                     listener,
-                    max_connections,
-                    on_success,
-                    on_error,
-                    on_new_connection,
-                    on_connection_closed,
-                    on_listener_closed,
+                    Listener::new(
+                        max_connections,
+                        on_success,
+                        on_error,
+                        on_new_connection,
+                        on_connection_closed,
+                        on_listener_closed,
+                    ),
                 );
+                let listener_object = server_state.get_listener_mut(&listener);
+
+                listener_object.routing = routing;
+                listener_object.admission_control = admission_control;
+                listener_object.nodelay = nodelay;
 
                 dispatcher.dispatch(TcpAction::Listen {
                     listener,
                     address,
+                    options: None,
+                    backlog,
                     on_success: callback!(|listener: Uid| TcpServerAction::NewSuccess { listener }),
                     on_error: callback!(|(listener: Uid, error: String)| TcpServerAction::NewError { listener, error })
                 });
             }
+            TcpServerAction::Subscribe { on_event } => {
+                state.substate_mut::<TcpServerState>().lifecycle_subscriber = Some(on_event);
+            }
             TcpServerAction::NewSuccess { listener } => {
                 let Listener { on_success, .. } =
                     state.substate::<TcpServerState>().get_listener(&listener);
@@ -89,13 +117,13 @@ impl PureModel for TcpServerState {
                     on_success,
                     on_error,
                 });
-                dispatcher.dispatch(TcpAction::Poll {
+                dispatcher.dispatch(TcpAction::poll(
                     uid,
                     objects,
                     timeout,
-                    on_success: callback!(|(uid: Uid, events: TcpPollEvents)| TcpServerAction::PollSuccess { uid, events } ),
-                    on_error: callback!(|(uid: Uid, error: String)| TcpServerAction::PollError { uid, error } ),
-                })
+                    callback!(|(uid: Uid, events: TcpPollEvents)| TcpServerAction::PollSuccess { uid, events } ),
+                    callback!(|(uid: Uid, error: String)| TcpServerAction::PollError { uid, error } ),
+                ))
             }
             TcpServerAction::PollSuccess { uid, events } => {
                 let PollRequest { on_success, .. } =
@@ -111,32 +139,88 @@ impl PureModel for TcpServerState {
                 dispatcher.dispatch_back(&on_error, (uid, error))
             }
             TcpServerAction::AcceptSuccess { connection } => {
-                let (
-                    listener,
-                    Listener {
-                        max_connections,
-                        on_new_connection,
-                        connections,
-                        ..
-                    },
-                ) = state
-                    .substate_mut::<TcpServerState>()
-                    .get_connection_listener_mut(&connection);
+                let peer_address = get_peer_address(state, &connection);
+                let number = state
+                    .substate::<TcpState>()
+                    .connection_number(&connection)
+                    .expect("accepted connection without number");
+                let server_state: &mut TcpServerState = state.substate_mut();
+
+                notify_lifecycle(server_state, dispatcher, connection, ConnectionLifecycleEvent::Accepted { number });
+
+                let (listener, listener_object) =
+                    server_state.get_connection_listener_mut(&connection);
 
                 // When we reach the max allowed connections we close it, without notifications.
-                // TODO: this could probably better handled at low-level by changing the TcpListener backlog.
-                // Currently, MIO sets a fixed value of 1024.
-                if connections.len() > *max_connections {
+                // The listener's backlog only bounds the connections waiting to be accepted (and
+                // is ignored on some platforms), so this is still needed.
+                if listener_object.connections.len() > listener_object.max_connections {
+                    listener_object.closing.insert(connection);
                     dispatcher.dispatch(TcpAction::Close {
                         connection,
+                        deliver_buffered: false,
                         on_success: callback!(|connection: Uid| {
                             TcpServerAction::CloseEventInternal { connection }
                         }),
                     })
+                } else if let Some(admission_control) = listener_object.admission_control.clone() {
+                    let listener = *listener;
+                    let connections: Vec<Uid> = listener_object
+                        .connections
+                        .iter()
+                        .filter(|&&uid| uid != connection)
+                        .cloned()
+                        .collect();
+
+                    listener_object.pending_admission.insert(connection);
+
+                    let ip = peer_ip(&peer_address);
+                    let peer_connections = connections
+                        .iter()
+                        .filter(|&uid| peer_ip(&get_peer_address(state, uid)) == ip)
+                        .count();
+                    let request = AdmissionRequest {
+                        listener,
+                        peer_address: peer_address.clone(),
+                        connections: connections.len(),
+                        peer_connections,
+                    };
+
+                    dispatcher.dispatch_back(&admission_control, (connection, request));
                 } else {
-                    // otherwise we notify the model user of the new connection.
-                    dispatcher.dispatch_back(on_new_connection, (*listener, connection))
+                    establish_connection(state, dispatcher, connection, &peer_address)
+                }
+            }
+            TcpServerAction::Admit { connection } => {
+                // The verdict raced with the connection's closure.
+                if !state
+                    .substate_mut::<TcpServerState>()
+                    .take_pending_admission(&connection)
+                {
+                    return;
+                }
+
+                let peer_address = get_peer_address(state, &connection);
+
+                establish_connection(state, dispatcher, connection, &peer_address)
+            }
+            TcpServerAction::Reject { connection } => {
+                // Same as `Admit`.
+                let server_state: &mut TcpServerState = state.substate_mut();
+
+                if !server_state.take_pending_admission(&connection)
+                    || !server_state.start_close(&connection)
+                {
+                    return;
                 }
+
+                dispatcher.dispatch(TcpAction::Close {
+                    connection,
+                    deliver_buffered: false,
+                    on_success: callback!(|connection: Uid| {
+                        TcpServerAction::CloseEventRejected { connection }
+                    }),
+                })
             }
             TcpServerAction::AcceptTryAgain { connection } => {
                 // No new connections, ignore.
@@ -154,29 +238,118 @@ impl PureModel for TcpServerState {
                 warn!("|TCP_SERVER| accept {:?} failed: {:?}", connection, error);
                 listener_object.remove_connection(&connection)
             }
-            TcpServerAction::Close { connection } => dispatcher.dispatch(TcpAction::Close {
+            TcpServerAction::Close {
                 connection,
-                on_success: callback!(|connection: Uid| TcpServerAction::CloseEventNotify {
-                    connection
-                }),
-            }),
-            TcpServerAction::CloseEventInternal { connection } => {
-                let (_, listener_object) = state
+                deliver_buffered,
+            } => {
+                let server_state: &mut TcpServerState = state.substate_mut();
+
+                // Already being closed, e.g. after a send or recv error.
+                if !server_state.start_close(&connection) {
+                    return;
+                }
+
+                server_state.forget_activity(&connection);
+                dispatcher.dispatch(TcpAction::Close {
+                    connection,
+                    deliver_buffered,
+                    on_success: callback!(|connection: Uid| TcpServerAction::CloseEventNotify {
+                        connection
+                    }),
+                })
+            }
+            TcpServerAction::CloseAll {
+                listener,
+                on_complete,
+            } => {
+                let listener_object = state
                     .substate_mut::<TcpServerState>()
-                    .get_connection_listener_mut(&connection);
+                    .get_listener_mut(&listener);
+                let connections: BTreeSet<Uid> =
+                    listener_object.established_connections().cloned().collect();
+
+                if connections.is_empty() {
+                    dispatcher.dispatch_back(&on_complete, listener)
+                } else {
+                    // Those already being closed are still waited for.
+                    for &connection in connections.difference(&listener_object.closing) {
+                        dispatcher.dispatch(TcpServerAction::Close {
+                            connection,
+                            deliver_buffered: false,
+                        })
+                    }
+
+                    listener_object.new_close_all(connections, on_complete)
+                }
+            }
+            TcpServerAction::SweepIdle { max_idle_ms } => {
+                let current_time = get_current_time(state);
+
+                for connection in state
+                    .substate_mut::<TcpServerState>()
+                    .take_idle_connections(current_time, max_idle_ms)
+                {
+                    dispatcher.dispatch(TcpServerAction::Close {
+                        connection,
+                        deliver_buffered: false,
+                    })
+                }
+            }
+            TcpServerAction::CloseEventInternal { connection } => {
+                let server_state: &mut TcpServerState = state.substate_mut();
+                let reason = Some("Max connections reached".to_string());
+
+                notify_lifecycle(server_state, dispatcher, connection, ConnectionLifecycleEvent::Closed { reason });
+
+                let (_, listener_object) = server_state.get_connection_listener_mut(&connection);
+
+                listener_object.remove_connection(&connection)
+            }
+            TcpServerAction::CloseEventRejected { connection } => {
+                let server_state: &mut TcpServerState = state.substate_mut();
+                let reason = Some("Rejected".to_string());
+
+                notify_lifecycle(server_state, dispatcher, connection, ConnectionLifecycleEvent::Closed { reason });
+
+                let (_, listener_object) = server_state.get_connection_listener_mut(&connection);
 
                 listener_object.remove_connection(&connection)
             }
             TcpServerAction::CloseEventNotify { connection } => {
-                let (listener, listener_object) = state
-                    .substate_mut::<TcpServerState>()
-                    .get_connection_listener_mut(&connection);
+                let reason = state
+                    .substate::<TcpState>()
+                    .connection_last_error(&connection)
+                    .map(str::to_string);
+                let server_state: &mut TcpServerState = state.substate_mut();
+
+                notify_lifecycle(server_state, dispatcher, connection, ConnectionLifecycleEvent::Closed { reason });
+
+                let (listener, listener_object) =
+                    server_state.get_connection_listener_mut(&connection);
 
                 dispatcher.dispatch_back(
-                    &listener_object.on_connection_closed,
+                    listener_object.on_connection_closed(&connection),
                     (*listener, connection),
                 );
-                listener_object.remove_connection(&connection)
+                listener_object.remove_connection(&connection);
+
+                if let Some(on_complete) = listener_object.close_all_progress(&connection) {
+                    dispatcher.dispatch_back(&on_complete, *listener)
+                }
+
+                server_state.remove_from_groups(&connection)
+            }
+            TcpServerAction::Upgrade {
+                connection,
+                leftover,
+                new_handler,
+            } => {
+                let (_, listener_object) = state
+                    .substate_mut::<TcpServerState>()
+                    .get_connection_listener_mut(&connection);
+
+                dispatcher.dispatch_back(&new_handler.on_upgrade, (connection, leftover));
+                listener_object.upgrade_connection(connection, new_handler);
             }
             TcpServerAction::Send {
                 uid,
@@ -187,9 +360,14 @@ impl PureModel for TcpServerState {
                 on_timeout,
                 on_error,
             } => {
-                state
-                    .substate_mut::<TcpServerState>()
-                    .new_send_request(&uid, connection, on_success, on_timeout, on_error);
+                state.substate_mut::<TcpServerState>().new_send_request(
+                    &uid,
+                    connection,
+                    data.len(),
+                    on_success,
+                    on_timeout,
+                    on_error,
+                );
 
                 dispatcher.dispatch(TcpAction::Send {
                     uid,
@@ -199,38 +377,88 @@ impl PureModel for TcpServerState {
                     on_success: callback!(|uid: Uid| TcpServerAction::SendSuccess { uid }),
                     on_timeout: callback!(|uid: Uid| TcpServerAction::SendTimeout { uid }),
                     on_error: callback!(|(uid: Uid, error: String)| TcpServerAction::SendError { uid, error }),
+                    on_cancelled: Some(callback!(|(uid: Uid, result: SendResult)| TcpServerAction::SendCancelled { uid, result })),
                 });
             }
             TcpServerAction::SendSuccess { uid } => {
-                let SendRequest { on_success, .. } = state
-                    .substate_mut::<TcpServerState>()
-                    .take_send_request(&uid);
+                let current_time = get_current_time(state);
+                let server_state: &mut TcpServerState = state.substate_mut();
+                let SendRequest {
+                    connection,
+                    len,
+                    on_success,
+                    ..
+                } = server_state.take_send_request(&uid);
+
+                server_state.record_activity(&connection, current_time);
 
+                notify_lifecycle(server_state, dispatcher, connection, ConnectionLifecycleEvent::DataSent { bytes: len });
+                close_after_send(server_state, dispatcher, &uid);
                 dispatcher.dispatch_back(&on_success, uid)
             }
             TcpServerAction::SendTimeout { uid } => {
-                let SendRequest { on_timeout, .. } = state
-                    .substate_mut::<TcpServerState>()
-                    .take_send_request(&uid);
+                let server_state: &mut TcpServerState = state.substate_mut();
+                let SendRequest { on_timeout, .. } = server_state.take_send_request(&uid);
 
+                close_after_send(server_state, dispatcher, &uid);
                 dispatcher.dispatch_back(&on_timeout, uid)
             }
             TcpServerAction::SendError { uid, error } => {
+                let server_state: &mut TcpServerState = state.substate_mut();
                 let SendRequest {
                     connection,
                     on_error,
                     ..
-                } = state
-                    .substate_mut::<TcpServerState>()
-                    .take_send_request(&uid);
+                } = server_state.take_send_request(&uid);
 
+                close_after_send(server_state, dispatcher, &uid);
                 dispatcher.dispatch_back(&on_error, (uid, error));
                 // close the connection on send errors
-                dispatcher.dispatch(TcpAction::Close {
-                    connection,
-                    on_success: callback!(|connection: Uid| TcpServerAction::CloseEventNotify {
-                        connection
-                    }),
+                if server_state.start_close(&connection) {
+                    server_state.forget_activity(&connection);
+                    dispatcher.dispatch(TcpAction::Close {
+                        connection,
+                        deliver_buffered: false,
+                        on_success: callback!(|connection: Uid| TcpServerAction::CloseEventNotify {
+                            connection
+                        }),
+                    });
+                }
+            }
+            TcpServerAction::SendCancelled { uid, .. } => {
+                let server_state: &mut TcpServerState = state.substate_mut();
+
+                server_state.take_send_request(&uid);
+                close_after_send(server_state, dispatcher, &uid);
+
+                // A cancelled member send counts as failed.
+                if server_state.is_group_send(&uid) {
+                    group_send_done(state, dispatcher, uid, true)
+                }
+            }
+            TcpServerAction::SendThenCloseOther {
+                uid,
+                send_connection,
+                data,
+                close_connection,
+                timeout,
+                on_success,
+                on_timeout,
+                on_error,
+            } => {
+                assert_ne!(send_connection, close_connection);
+                state
+                    .substate_mut::<TcpServerState>()
+                    .close_after_send
+                    .insert(uid, close_connection);
+                dispatcher.dispatch(TcpServerAction::Send {
+                    uid,
+                    connection: send_connection,
+                    data,
+                    timeout,
+                    on_success,
+                    on_timeout,
+                    on_error,
                 });
             }
             TcpServerAction::Recv {
@@ -242,9 +470,14 @@ impl PureModel for TcpServerState {
                 on_timeout,
                 on_error,
             } => {
-                state
-                    .substate_mut::<TcpServerState>()
-                    .new_recv_request(&uid, connection, on_success, on_timeout, on_error);
+                state.substate_mut::<TcpServerState>().new_recv_request(
+                    &uid,
+                    connection,
+                    Some(count),
+                    on_success,
+                    on_timeout,
+                    on_error,
+                );
 
                 dispatcher.dispatch(TcpAction::Recv {
                     uid,
@@ -254,45 +487,255 @@ impl PureModel for TcpServerState {
                     on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpServerAction::RecvSuccess { uid, data }),
                     on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpServerAction::RecvTimeout { uid, partial_data }),
                     on_error: callback!(|(uid: Uid, error: String)| TcpServerAction::RecvError { uid, error }),
+                    on_cancelled: Some(callback!(|(uid: Uid, result: RecvResult)| TcpServerAction::RecvCancelled { uid, result })),
                 });
             }
-            TcpServerAction::RecvSuccess { uid, data } => {
-                let RecvRequest { on_success, .. } = state
+            TcpServerAction::RecvUntil {
+                uid,
+                connection,
+                delimiter,
+                max_bytes,
+                timeout,
+                on_success,
+                on_timeout,
+                on_error,
+            } => {
+                state
                     .substate_mut::<TcpServerState>()
-                    .take_recv_request(&uid);
+                    .new_recv_request(&uid, connection, None, on_success, on_timeout, on_error);
+
+                dispatcher.dispatch(TcpAction::RecvUntil {
+                    uid,
+                    connection,
+                    delimiter,
+                    max_bytes,
+                    timeout,
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpServerAction::RecvSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpServerAction::RecvTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| TcpServerAction::RecvError { uid, error }),
+                    on_cancelled: Some(callback!(|(uid: Uid, result: RecvResult)| TcpServerAction::RecvCancelled { uid, result })),
+                });
+            }
+            TcpServerAction::RecvSuccess { uid, data } => {
+                let current_time = get_current_time(state);
+                let server_state: &mut TcpServerState = state.substate_mut();
+                let RecvRequest {
+                    connection,
+                    count,
+                    on_success,
+                    ..
+                } = server_state.take_recv_request(&uid);
+                let bytes = data.len();
+
+                if let Some(count) = count {
+                    server_state.record_recv(&connection, count, bytes)
+                }
 
+                server_state.record_activity(&connection, current_time);
+                notify_lifecycle(server_state, dispatcher, connection, ConnectionLifecycleEvent::DataReceived { bytes });
                 dispatcher.dispatch_back(&on_success, (uid, data))
             }
             TcpServerAction::RecvTimeout { uid, partial_data } => {
-                let RecvRequest { on_timeout, .. } = state
-                    .substate_mut::<TcpServerState>()
-                    .take_recv_request(&uid);
+                let current_time = get_current_time(state);
+                let server_state: &mut TcpServerState = state.substate_mut();
+                let RecvRequest {
+                    connection,
+                    count,
+                    on_timeout,
+                    ..
+                } = server_state.take_recv_request(&uid);
+                let bytes = partial_data.len();
+
+                if let Some(count) = count {
+                    server_state.record_recv(&connection, count, bytes)
+                }
+
+                if bytes > 0 {
+                    server_state.record_activity(&connection, current_time);
+                    notify_lifecycle(server_state, dispatcher, connection, ConnectionLifecycleEvent::DataReceived { bytes });
+                }
 
                 dispatcher.dispatch_back(&on_timeout, (uid, partial_data))
             }
             TcpServerAction::RecvError { uid, error } => {
+                let server_state: &mut TcpServerState = state.substate_mut();
                 let RecvRequest {
                     connection,
                     on_error,
                     ..
-                } = state
-                    .substate_mut::<TcpServerState>()
-                    .take_recv_request(&uid);
+                } = server_state.take_recv_request(&uid);
 
                 dispatcher.dispatch_back(&on_error, (uid, error));
 
                 // close the connection on recv errors
-                dispatcher.dispatch(TcpAction::Close {
+                if server_state.start_close(&connection) {
+                    server_state.forget_activity(&connection);
+                    dispatcher.dispatch(TcpAction::Close {
+                        connection,
+                        deliver_buffered: false,
+                        on_success: callback!(|connection: Uid| TcpServerAction::CloseEventNotify {
+                            connection
+                        }),
+                    })
+                }
+            }
+            TcpServerAction::RecvCancelled { uid, .. } => {
+                let server_state: &mut TcpServerState = state.substate_mut();
+
+                server_state.take_recv_request(&uid);
+                // Also set for a `RecvIntoRing`.
+                server_state.ring_recv_requests.remove(&uid);
+            }
+            TcpServerAction::RecvIntoRing {
+                uid,
+                connection,
+                count,
+                timeout,
+                on_success,
+                on_timeout,
+                on_error,
+            } => {
+                state
+                    .substate_mut::<TcpServerState>()
+                    .new_ring_recv_request(&uid, connection, on_success, on_timeout, on_error);
+
+                dispatcher.dispatch(TcpServerAction::Recv {
+                    uid,
                     connection,
-                    on_success: callback!(|connection: Uid| TcpServerAction::CloseEventNotify {
-                        connection
-                    }),
+                    count,
+                    timeout,
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpServerAction::RecvIntoRingSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpServerAction::RecvIntoRingTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| TcpServerAction::RecvIntoRingError { uid, error }),
+                });
+            }
+            TcpServerAction::RecvIntoRingSuccess { uid, data } => {
+                let server_state: &mut TcpServerState = state.substate_mut();
+                let RingRecvRequest {
+                    connection,
+                    on_success,
+                    ..
+                } = server_state.take_ring_recv_request(&uid);
+                let ring = server_state.ring_mut(&connection);
+
+                ring.extend(&data);
+                dispatcher.dispatch_back(&on_success, (uid, ring.len()))
+            }
+            TcpServerAction::RecvIntoRingTimeout { uid, partial_data } => {
+                let server_state: &mut TcpServerState = state.substate_mut();
+                let RingRecvRequest {
+                    connection,
+                    on_timeout,
+                    ..
+                } = server_state.take_ring_recv_request(&uid);
+                let ring = server_state.ring_mut(&connection);
+
+                ring.extend(&partial_data);
+                dispatcher.dispatch_back(&on_timeout, (uid, ring.len()))
+            }
+            TcpServerAction::RecvIntoRingError { uid, error } => {
+                // The connection is closed by `RecvError`.
+                let RingRecvRequest { on_error, .. } = state
+                    .substate_mut::<TcpServerState>()
+                    .take_ring_recv_request(&uid);
+
+                dispatcher.dispatch_back(&on_error, (uid, error))
+            }
+            TcpServerAction::SendToGroup {
+                uid,
+                group,
+                data,
+                timeout,
+                on_complete,
+            } => {
+                let members = state.substate::<TcpServerState>().group_members(&group);
+
+                if members.is_empty() {
+                    return dispatcher.dispatch_back(&on_complete, (uid, Vec::new()));
+                }
+
+                let pending: BTreeMap<Uid, Uid> = members
+                    .into_iter()
+                    .map(|member| (state.new_uid(), member))
+                    .collect();
+
+                for (&send, &connection) in pending.iter() {
+                    dispatcher.dispatch(TcpServerAction::Send {
+                        uid: send,
+                        connection,
+                        data: data.clone(),
+                        timeout: timeout.clone(),
+                        on_success: callback!(|uid: Uid| TcpServerAction::GroupSendSuccess { uid }),
+                        on_timeout: callback!(|uid: Uid| TcpServerAction::GroupSendTimeout { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| TcpServerAction::GroupSendError { uid, error }),
+                    });
+                }
+
+                state
+                    .substate_mut::<TcpServerState>()
+                    .new_group_send_request(&uid, pending, on_complete)
+            }
+            TcpServerAction::GroupSendSuccess { uid } => {
+                group_send_done(state, dispatcher, uid, false)
+            }
+            TcpServerAction::GroupSendTimeout { uid } => {
+                group_send_done(state, dispatcher, uid, true)
+            }
+            TcpServerAction::GroupSendError { uid, error } => {
+                // The connection is closed by `SendError`.
+                warn!("|TCP_SERVER| group send {:?} failed: {}", uid, error);
+                group_send_done(state, dispatcher, uid, true)
+            }
+            TcpServerAction::CloseGroup {
+                group,
+                deliver_buffered,
+            } => {
+                let server_state: &TcpServerState = state.substate();
+
+                for connection in server_state.group_members(&group) {
+                    // Already being closed, it leaves the group once it is.
+                    if server_state.is_closing(&connection) {
+                        continue;
+                    }
+
+                    dispatcher.dispatch(TcpServerAction::Close {
+                        connection,
+                        deliver_buffered,
+                    })
+                }
+            }
+        }
+    }
+
+    // Closes the connections of all listeners (but those already being
+    // closed), while the `TcpState` model is still alive.
+    fn on_shutdown<Substate: ModelState>(state: &mut State<Substate>, dispatcher: &mut Dispatcher) {
+        let server_state: &TcpServerState = state.substate();
+
+        for listener in server_state.listeners.values() {
+            for &connection in listener.connections.difference(&listener.closing) {
+                dispatcher.dispatch(TcpServerAction::Close {
+                    connection,
+                    deliver_buffered: false,
                 })
             }
         }
     }
 }
 
+// Closes the connection of a `SendThenCloseOther` once its send is done.
+fn close_after_send(server_state: &mut TcpServerState, dispatcher: &mut Dispatcher, uid: &Uid) {
+    match server_state.close_after_send.remove(uid) {
+        Some(connection) if server_state.has_connection(&connection) => {
+            dispatcher.dispatch(TcpServerAction::Close {
+                connection,
+                deliver_buffered: false,
+            })
+        }
+        _ => (),
+    }
+}
+
 fn process_poll_events<Substate: ModelState>(
     state: &mut State<Substate>,
     dispatcher: &mut Dispatcher,
@@ -303,13 +746,18 @@ fn process_poll_events<Substate: ModelState>(
             match event {
                 ListenerEvent::AcceptPending => {
                     let connection = state.new_uid();
-                    state
-                        .substate_mut::<TcpServerState>()
-                        .new_connection(connection, listener);
+                    let server_state: &mut TcpServerState = state.substate_mut();
+
+                    server_state.new_connection(connection, listener);
 
                     dispatcher.dispatch(TcpAction::Accept {
                         connection,
                         listener,
+                        register_delay: server_state
+                            .config
+                            .accept_register_delay
+                            .filter(|_| cfg!(test)),
+                        nodelay: server_state.get_listener(&listener).nodelay,
                         on_success: callback!(|connection: Uid| TcpServerAction::AcceptSuccess { connection }),
                         on_would_block: callback!(|connection: Uid| TcpServerAction::AcceptTryAgain { connection }),
                         on_error: callback!(|(connection: Uid, error: String)| TcpServerAction::AcceptError { connection, error }),
@@ -331,3 +779,62 @@ fn process_poll_events<Substate: ModelState>(
         }
     }
 }
+
+fn get_peer_address<Substate: ModelState>(state: &State<Substate>, connection: &Uid) -> String {
+    state
+        .substate::<TcpState>()
+        .connection_addrs(connection)
+        .map(|(_, peer_address)| peer_address)
+        .unwrap_or_default()
+}
+
+// Assigns a shard to an accepted connection and notifies the model user of
+// the new connection.
+fn establish_connection<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    connection: Uid,
+    peer_address: &str,
+) {
+    let current_time = get_current_time(state);
+    let server_state: &mut TcpServerState = state.substate_mut();
+    let (listener, listener_object) = server_state.get_connection_listener_mut(&connection);
+
+    listener_object.route_connection(connection, peer_address);
+    listener_object.last_activity.insert(connection, current_time);
+    dispatcher.dispatch_back(&listener_object.on_new_connection, (*listener, connection));
+    notify_lifecycle(server_state, dispatcher, connection, ConnectionLifecycleEvent::Established);
+}
+
+// A send of a `SendToGroup` is done, `failed` if it timed out or failed.
+fn group_send_done<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    uid: Uid,
+    failed: bool,
+) {
+    let progress = state
+        .substate_mut::<TcpServerState>()
+        .group_send_progress(&uid, failed);
+
+    if let Some((group_send, request)) = progress {
+        let GroupSendRequest {
+            failed,
+            on_complete,
+            ..
+        } = request;
+
+        dispatcher.dispatch_back(&on_complete, (group_send, failed))
+    }
+}
+
+fn notify_lifecycle(
+    server_state: &TcpServerState,
+    dispatcher: &mut Dispatcher,
+    connection: Uid,
+    event: ConnectionLifecycleEvent,
+) {
+    if let Some(on_event) = &server_state.lifecycle_subscriber {
+        dispatcher.dispatch_back(on_event, (connection, event))
+    }
+}
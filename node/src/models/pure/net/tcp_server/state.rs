@@ -1,10 +1,20 @@
-use crate::automaton::{
-    action::Redispatch,
-    state::{Objects, Uid},
+use super::action::{
+    AdmissionRequest, ConnectionHandler, ConnectionLifecycleEvent, RoutingPolicy,
+};
+use crate::{
+    automaton::{
+        action::Redispatch,
+        state::{Objects, Query, Uid},
+    },
+    models::pure::net::ring_buffer::RingBuffer,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    mem,
 };
-use std::{collections::BTreeSet, mem};
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Listener {
     pub max_connections: usize,
     pub on_success: Redispatch<Uid>,
@@ -13,6 +23,39 @@ pub struct Listener {
     pub on_connection_closed: Redispatch<(Uid, Uid)>,
     pub on_listener_closed: Redispatch<Uid>,
     pub connections: BTreeSet<Uid>,
+    // Handlers of connections that were upgraded with `TcpServerAction::Upgrade`.
+    pub upgraded_connections: Objects<ConnectionHandler>,
+    pub routing: RoutingPolicy,
+    pub admission_control: Option<Redispatch<(Uid, AdmissionRequest)>>,
+    // Set TCP_NODELAY on accepted connections.
+    pub nodelay: bool,
+    // Accepted connections waiting for an admission decision.
+    pub pending_admission: BTreeSet<Uid>,
+    pub connection_shards: Objects<usize>,
+    // Kept after the connections are closed, so that reconnects from the same
+    // IP are routed to the same shard (`RoutingPolicy::StickyBySourceIp`).
+    pub source_ip_shards: BTreeMap<String, usize>,
+    pub next_shard: usize,
+    // Pending `TcpServerAction::CloseAll`.
+    pub close_all: Option<CloseAllRequest>,
+    // Created by the first `TcpServerAction::RecvIntoRing` on a connection.
+    pub rings: Objects<RingBuffer>,
+    // Adapted recv sizes, see `TcpServerState::recv_size`.
+    pub recv_sizes: Objects<usize>,
+    // Time (see `get_current_time`) of the last send or recv completed on
+    // established connections, or of their establishment. Connections being
+    // closed are not tracked, see `TcpServerAction::SweepIdle`.
+    pub last_activity: Objects<u128>,
+    // Connections with a `TcpAction::Close` in flight, see
+    // `TcpServerState::start_close`.
+    pub closing: BTreeSet<Uid>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CloseAllRequest {
+    // Connections not closed yet.
+    pub connections: BTreeSet<Uid>,
+    pub on_complete: Redispatch<Uid>,
 }
 
 impl Listener {
@@ -32,51 +75,237 @@ impl Listener {
             on_connection_closed,
             on_listener_closed,
             connections: BTreeSet::new(),
+            upgraded_connections: Objects::new(),
+            routing: RoutingPolicy::None,
+            admission_control: None,
+            nodelay: false,
+            pending_admission: BTreeSet::new(),
+            connection_shards: Objects::new(),
+            source_ip_shards: BTreeMap::new(),
+            next_shard: 0,
+            close_all: None,
+            rings: Objects::new(),
+            recv_sizes: Objects::new(),
+            last_activity: Objects::new(),
+            closing: BTreeSet::new(),
         }
     }
 
     pub fn remove_connection(&mut self, uid: &Uid) {
         self.connections.remove(uid);
+        self.upgraded_connections.remove(uid);
+        self.pending_admission.remove(uid);
+        self.connection_shards.remove(uid);
+        self.rings.remove(uid);
+        self.recv_sizes.remove(uid);
+        self.last_activity.remove(uid);
+        self.closing.remove(uid);
+    }
+
+    // Established connections: accepted, admitted and handed to
+    // `on_new_connection`.
+    pub fn established_connections(&self) -> impl Iterator<Item = &Uid> {
+        self.connection_shards.keys()
+    }
+
+    pub fn new_close_all(&mut self, connections: BTreeSet<Uid>, on_complete: Redispatch<Uid>) {
+        assert!(self.close_all.is_none(), "CloseAll already in progress");
+        self.close_all = Some(CloseAllRequest {
+            connections,
+            on_complete,
+        });
+    }
+
+    // Called once `connection` is closed. Returns the `on_complete` callback
+    // of the pending `CloseAll` if it was the last connection to close.
+    pub fn close_all_progress(&mut self, connection: &Uid) -> Option<Redispatch<Uid>> {
+        let request = self.close_all.as_mut()?;
+
+        request.connections.remove(connection);
+
+        if request.connections.is_empty() {
+            self.close_all.take().map(|request| request.on_complete)
+        } else {
+            None
+        }
+    }
+
+    // Assigns a shard to an accepted connection according to the listener's
+    // `routing` policy. `peer_address` is the "ip:port" address of the peer.
+    pub fn route_connection(&mut self, uid: Uid, peer_address: &str) -> usize {
+        let shard = match self.routing {
+            RoutingPolicy::None => 0,
+            RoutingPolicy::RoundRobin { shards } => self.take_next_shard(shards),
+            RoutingPolicy::StickyBySourceIp { shards } => {
+                let ip = peer_ip(peer_address);
+
+                match self.source_ip_shards.get(ip) {
+                    Some(&shard) => shard,
+                    None => {
+                        let shard = self.take_next_shard(shards);
+
+                        self.source_ip_shards.insert(ip.to_string(), shard);
+                        shard
+                    }
+                }
+            }
+        };
+
+        assert!(self.connections.contains(&uid));
+        self.connection_shards.insert(uid, shard);
+        shard
+    }
+
+    fn take_next_shard(&mut self, shards: usize) -> usize {
+        assert_ne!(shards, 0);
+        let shard = self.next_shard % shards;
+
+        self.next_shard = self.next_shard.wrapping_add(1);
+        shard
+    }
+
+    pub fn upgrade_connection(&mut self, uid: Uid, handler: ConnectionHandler) {
+        assert!(self.connections.contains(&uid));
+        self.upgraded_connections.insert(uid, handler);
+    }
+
+    pub fn on_connection_closed(&self, uid: &Uid) -> &Redispatch<(Uid, Uid)> {
+        self.upgraded_connections
+            .get(uid)
+            .map_or(&self.on_connection_closed, |handler| {
+                &handler.on_connection_closed
+            })
     }
 }
 
-#[derive(Debug)]
+// The IP of an "ip:port" address.
+pub fn peer_ip(peer_address: &str) -> &str {
+    peer_address
+        .rsplit_once(':')
+        .map_or(peer_address, |(ip, _port)| ip)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct SendRequest {
     pub connection: Uid,
+    pub len: usize,
     pub on_success: Redispatch<Uid>,
     pub on_timeout: Redispatch<Uid>,
     pub on_error: Redispatch<(Uid, String)>,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct RecvRequest {
     pub connection: Uid,
+    // The `count` of a `Recv`, see `TcpServerState::recv_size`. Not set for
+    // `RecvUntil`, which completes before its buffer is full.
+    pub count: Option<usize>,
     pub on_success: Redispatch<(Uid, Vec<u8>)>,
     pub on_timeout: Redispatch<(Uid, Vec<u8>)>,
     pub on_error: Redispatch<(Uid, String)>,
 }
 
-#[derive(Debug)]
+// Callbacks get the number of bytes buffered in the connection's ring.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RingRecvRequest {
+    pub connection: Uid,
+    pub on_success: Redispatch<(Uid, usize)>,
+    pub on_timeout: Redispatch<(Uid, usize)>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+// A `TcpServerAction::SendToGroup` in progress.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupSendRequest {
+    // (send uid, member) of the sends not done yet.
+    pub pending: BTreeMap<Uid, Uid>,
+    pub failed: Vec<Uid>,
+    pub on_complete: Redispatch<(Uid, Vec<Uid>)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct PollRequest {
     pub on_success: Redispatch<Uid>,
     pub on_error: Redispatch<(Uid, String)>,
 }
 
-#[derive(Debug)]
+// Bounds of the recv size adapted to each connection, see
+// `TcpServerState::recv_size`. `min` must be positive and at most `max`.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct AdaptiveRecv {
+    pub min: usize,
+    pub max: usize,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug)]
+pub struct TcpServerConfig {
+    // Testing only: delays the poll registration of accepted connections by
+    // this many milliseconds, to reproduce data arriving before a connection
+    // is registered. Ignored outside of tests.
+    pub accept_register_delay: Option<u64>,
+    pub adaptive_recv: Option<AdaptiveRecv>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct TcpServerState {
+    pub config: TcpServerConfig,
     pub listeners: Objects<Listener>,
     pub send_requests: Objects<SendRequest>,
     pub recv_requests: Objects<RecvRequest>,
+    pub ring_recv_requests: Objects<RingRecvRequest>,
+    pub group_send_requests: Objects<GroupSendRequest>,
+    // Connections to close once the send of the same `Uid` is done, see
+    // `TcpServerAction::SendThenCloseOther`.
+    pub close_after_send: Objects<Uid>,
+    // Named sets of established connections, a connection can belong to
+    // several of them.
+    pub groups: BTreeMap<String, BTreeSet<Uid>>,
     pub poll_request: Option<PollRequest>,
+    // See `TcpServerAction::Subscribe`.
+    pub lifecycle_subscriber: Option<Redispatch<(Uid, ConnectionLifecycleEvent)>>,
+}
+
+// Number of established connections across all listeners (see
+// `Listener::established_connections`).
+pub struct ConnectionCount;
+
+impl Query<ConnectionCount> for TcpServerState {
+    type Answer = usize;
+
+    fn answer(&self, _query: ConnectionCount) -> usize {
+        self.listeners
+            .values()
+            .map(|listener| listener.established_connections().count())
+            .sum()
+    }
 }
 
 impl TcpServerState {
     pub fn new() -> Self {
+        Self::from_config(TcpServerConfig::default())
+    }
+
+    pub fn from_config(config: TcpServerConfig) -> Self {
+        if let Some(AdaptiveRecv { min, max }) = config.adaptive_recv {
+            assert!(
+                min > 0 && min <= max,
+                "Invalid adaptive recv bounds: min {}, max {}",
+                min,
+                max
+            );
+        }
+
         Self {
+            config,
             listeners: Objects::<Listener>::new(),
             send_requests: Objects::<SendRequest>::new(),
             recv_requests: Objects::<RecvRequest>::new(),
+            ring_recv_requests: Objects::<RingRecvRequest>::new(),
+            group_send_requests: Objects::<GroupSendRequest>::new(),
+            close_after_send: Objects::<Uid>::new(),
+            groups: BTreeMap::new(),
             poll_request: None,
+            lifecycle_subscriber: None,
         }
     }
 
@@ -93,6 +322,7 @@ impl TcpServerState {
         &mut self,
         uid: &Uid,
         connection: Uid,
+        len: usize,
         on_success: Redispatch<Uid>,
         on_timeout: Redispatch<Uid>,
         on_error: Redispatch<(Uid, String)>,
@@ -103,6 +333,7 @@ impl TcpServerState {
                 *uid,
                 SendRequest {
                     connection,
+                    len,
                     on_success,
                     on_timeout,
                     on_error,
@@ -124,6 +355,7 @@ impl TcpServerState {
         &mut self,
         uid: &Uid,
         connection: Uid,
+        count: Option<usize>,
         on_success: Redispatch<(Uid, Vec<u8>)>,
         on_timeout: Redispatch<(Uid, Vec<u8>)>,
         on_error: Redispatch<(Uid, String)>,
@@ -134,6 +366,7 @@ impl TcpServerState {
                 *uid,
                 RecvRequest {
                     connection,
+                    count,
                     on_success,
                     on_timeout,
                     on_error,
@@ -151,12 +384,260 @@ impl TcpServerState {
             .expect(&format!("Take attempt on inexistent SendRequest {:?}", uid))
     }
 
+    // The `count` to pass to the next `Recv` on `connection`, if
+    // `adaptive_recv` is configured. It starts at `min`, doubles (up to `max`)
+    // after a recv that filled its buffer, and halves (down to `min`) after
+    // one that got less than half of it.
+    pub fn recv_size(&self, connection: &Uid) -> Option<usize> {
+        let AdaptiveRecv { min, .. } = self.config.adaptive_recv?;
+
+        Some(
+            self.listeners
+                .values()
+                .find_map(|listener| listener.recv_sizes.get(connection).copied())
+                .unwrap_or(min),
+        )
+    }
+
+    // Adapts the recv size of `connection` to a recv of `count` bytes that
+    // completed with `received` bytes.
+    pub fn record_recv(&mut self, connection: &Uid, count: usize, received: usize) {
+        let Some(AdaptiveRecv { min, max }) = self.config.adaptive_recv else {
+            return;
+        };
+        let Some(listener) = self
+            .listeners
+            .values_mut()
+            .find(|listener| listener.connections.contains(connection))
+        else {
+            return;
+        };
+        let size = listener.recv_sizes.entry(*connection).or_insert(min);
+
+        if received >= count {
+            *size = (*size * 2).min(max)
+        } else if received < count / 2 {
+            *size = (*size / 2).max(min)
+        }
+    }
+
+    // A send or recv completed on `connection` at `time`.
+    pub fn record_activity(&mut self, connection: &Uid, time: u128) {
+        for listener in self.listeners.values_mut() {
+            if let Some(last_activity) = listener.last_activity.get_mut(connection) {
+                *last_activity = time
+            }
+        }
+    }
+
+    pub fn connection_last_activity(&self, connection: &Uid) -> Option<u128> {
+        self.listeners
+            .values()
+            .find_map(|listener| listener.last_activity.get(connection).copied())
+    }
+
+    // Stops tracking the activity of `connection`, which is being closed.
+    pub fn forget_activity(&mut self, connection: &Uid) {
+        for listener in self.listeners.values_mut() {
+            listener.last_activity.remove(connection);
+        }
+    }
+
+    // Connections idle for more than `max_idle_ms` at `current_time`. They
+    // are expected to be closed, their activity is no longer tracked.
+    pub fn take_idle_connections(&mut self, current_time: u128, max_idle_ms: u64) -> Vec<Uid> {
+        let mut idle = Vec::new();
+
+        for listener in self.listeners.values_mut() {
+            listener.last_activity.retain(|connection, last_activity| {
+                let is_idle = current_time.saturating_sub(*last_activity) > max_idle_ms as u128;
+
+                if is_idle {
+                    idle.push(*connection)
+                }
+
+                !is_idle
+            });
+        }
+
+        idle
+    }
+
+    pub fn new_ring_recv_request(
+        &mut self,
+        uid: &Uid,
+        connection: Uid,
+        on_success: Redispatch<(Uid, usize)>,
+        on_timeout: Redispatch<(Uid, usize)>,
+        on_error: Redispatch<(Uid, String)>,
+    ) {
+        if self
+            .ring_recv_requests
+            .insert(
+                *uid,
+                RingRecvRequest {
+                    connection,
+                    on_success,
+                    on_timeout,
+                    on_error,
+                },
+            )
+            .is_some()
+        {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    pub fn take_ring_recv_request(&mut self, uid: &Uid) -> RingRecvRequest {
+        self.ring_recv_requests.remove(uid).expect(&format!(
+            "Take attempt on inexistent RingRecvRequest {:?}",
+            uid
+        ))
+    }
+
+    // The ring of `connection`, for parsers to peek at and consume the bytes
+    // received with `TcpServerAction::RecvIntoRing`.
+    pub fn ring_mut(&mut self, connection: &Uid) -> &mut RingBuffer {
+        let (_, listener) = self.get_connection_listener_mut(connection);
+
+        listener.rings.entry(*connection).or_default()
+    }
+
+    pub fn new_group_send_request(
+        &mut self,
+        uid: &Uid,
+        pending: BTreeMap<Uid, Uid>,
+        on_complete: Redispatch<(Uid, Vec<Uid>)>,
+    ) {
+        if self
+            .group_send_requests
+            .insert(
+                *uid,
+                GroupSendRequest {
+                    pending,
+                    failed: Vec::new(),
+                    on_complete,
+                },
+            )
+            .is_some()
+        {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    // Whether `uid` is the send to a member of a pending `SendToGroup`.
+    pub fn is_group_send(&self, uid: &Uid) -> bool {
+        self.group_send_requests
+            .values()
+            .any(|request| request.pending.contains_key(uid))
+    }
+
+    // Called once the send `uid` to a group member is done. Returns the
+    // `GroupSendRequest` (and its uid) if it was the last one.
+    pub fn group_send_progress(
+        &mut self,
+        uid: &Uid,
+        failed: bool,
+    ) -> Option<(Uid, GroupSendRequest)> {
+        let (&group_send, request) = self
+            .group_send_requests
+            .iter_mut()
+            .find(|(_, request)| request.pending.contains_key(uid))
+            .expect(&format!("GroupSendRequest not found for send {:?}", uid));
+        let member = request.pending.remove(uid).unwrap();
+
+        if failed {
+            request.failed.push(member);
+        }
+
+        if request.pending.is_empty() {
+            self.group_send_requests
+                .remove(&group_send)
+                .map(|request| (group_send, request))
+        } else {
+            None
+        }
+    }
+
+    // Only established connections (handed to `on_new_connection`) can join
+    // a group. They leave all of their groups once closed.
+    pub fn add_to_group(&mut self, connection: Uid, group: &str) {
+        assert!(
+            self.connection_shard(&connection).is_some(),
+            "Connection {:?} is not established",
+            connection
+        );
+        self.groups
+            .entry(group.to_string())
+            .or_default()
+            .insert(connection);
+    }
+
+    pub fn remove_from_group(&mut self, connection: &Uid, group: &str) {
+        if let Some(members) = self.groups.get_mut(group) {
+            members.remove(connection);
+
+            if members.is_empty() {
+                self.groups.remove(group);
+            }
+        }
+    }
+
+    pub fn remove_from_groups(&mut self, connection: &Uid) {
+        self.groups.retain(|_, members| {
+            members.remove(connection);
+            !members.is_empty()
+        })
+    }
+
+    pub fn group_members(&self, group: &str) -> Vec<Uid> {
+        self.groups
+            .get(group)
+            .map_or_else(Vec::new, |members| members.iter().cloned().collect())
+    }
+
     pub fn new_connection(&mut self, connection: Uid, listener: Uid) {
         self.get_listener_mut(&listener)
             .connections
             .insert(connection);
     }
 
+    // Marks `connection` as being closed. Returns false if it already was:
+    // no other `TcpAction::Close` must be dispatched for it, `TcpState`
+    // doesn't expect a connection to be closed twice.
+    pub fn start_close(&mut self, connection: &Uid) -> bool {
+        match self
+            .listeners
+            .values_mut()
+            .find(|listener| listener.connections.contains(connection))
+        {
+            Some(listener) => listener.closing.insert(*connection),
+            // Left to `TcpState` to report.
+            None => true,
+        }
+    }
+
+    pub fn is_closing(&self, connection: &Uid) -> bool {
+        self.listeners
+            .values()
+            .any(|listener| listener.closing.contains(connection))
+    }
+
+    pub fn has_connection(&self, connection: &Uid) -> bool {
+        self.listeners
+            .values()
+            .any(|listener| listener.connections.contains(connection))
+    }
+
+    // Removes `connection` from its listener's `pending_admission`. Returns
+    // false if it isn't waiting for admission (anymore), e.g. it was closed
+    // before the verdict came in.
+    pub fn take_pending_admission(&mut self, connection: &Uid) -> bool {
+        self.listeners
+            .values_mut()
+            .any(|listener| listener.pending_admission.remove(connection))
+    }
+
     pub fn get_connection_listener_mut(&mut self, connection: &Uid) -> (&Uid, &mut Listener) {
         self.listeners
             .iter_mut()
@@ -167,31 +648,8 @@ impl TcpServerState {
             ))
     }
 
-    pub fn new_listener(
-        &mut self,
-        listener: Uid,
-        max_connections: usize,
-        on_success: Redispatch<Uid>,
-        on_error: Redispatch<(Uid, String)>,
-        on_new_connection: Redispatch<(Uid, Uid)>,
-        on_connection_closed: Redispatch<(Uid, Uid)>,
-        on_listener_closed: Redispatch<Uid>,
-    ) {
-        if self
-            .listeners
-            .insert(
-                listener,
-                Listener::new(
-                    max_connections,
-                    on_success,
-                    on_error,
-                    on_new_connection,
-                    on_connection_closed,
-                    on_listener_closed,
-                ),
-            )
-            .is_some()
-        {
+    pub fn new_listener(&mut self, listener: Uid, listener_object: Listener) {
+        if self.listeners.insert(listener, listener_object).is_some() {
             panic!("Attempt to re-use existing {:?}", listener)
         }
     }
@@ -208,6 +666,14 @@ impl TcpServerState {
             .expect(&format!("Listener object {:?} not found", listener))
     }
 
+    // Shard assigned to `connection` by its listener's `RoutingPolicy`, if
+    // the connection was accepted and not closed yet.
+    pub fn connection_shard(&self, connection: &Uid) -> Option<usize> {
+        self.listeners
+            .values()
+            .find_map(|listener| listener.connection_shards.get(connection).copied())
+    }
+
     pub fn remove_listener(&mut self, listener: &Uid) -> Listener {
         self.listeners.remove(listener).expect(&format!(
             "Attempt to remove an inexistent Listener {:?}",
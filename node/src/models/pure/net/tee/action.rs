@@ -0,0 +1,78 @@
+use crate::automaton::{
+    action::{Action, ActionKind, Redispatch, Timeout},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+// Transport models a `TeeState` can receive data from, or mirror it to.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum TeeTransport {
+    TcpServer,
+    TcpClient,
+    PnetServer,
+    PnetClient,
+}
+
+// Where `TeeState` copies the received data.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum TeeSink {
+    // Appended to the in-memory capture `capture`, see `TeeState::capture`.
+    Capture {
+        capture: Uid,
+    },
+    // Sent over `connection` of `transport`.
+    Connection {
+        transport: TeeTransport,
+        connection: Uid,
+        timeout: Timeout,
+    },
+}
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "e20f8d52-6dab-4049-a188-385d6679870d"]
+pub enum TeeAction {
+    // Same as the `Recv` action of `transport`, but the received data
+    // (including the partial data passed to `on_timeout`) is also copied to
+    // `sink` before it is passed to the caller.
+    //
+    // Mirroring is best-effort: a failed send to a `TeeSink::Connection` is
+    // logged and doesn't affect the recv request.
+    Recv {
+        uid: Uid,
+        transport: TeeTransport,
+        connection: Uid,
+        count: usize, // number of bytes to read
+        timeout: Timeout,
+        sink: TeeSink,
+        on_success: Redispatch<(Uid, Vec<u8>)>,
+        on_timeout: Redispatch<(Uid, Vec<u8>)>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    RecvSuccess {
+        uid: Uid,
+        data: Vec<u8>,
+    },
+    RecvTimeout {
+        uid: Uid,
+        partial_data: Vec<u8>,
+    },
+    RecvError {
+        uid: Uid,
+        error: String,
+    },
+    SinkSendSuccess {
+        uid: Uid,
+    },
+    SinkSendTimeout {
+        uid: Uid,
+    },
+    SinkSendError {
+        uid: Uid,
+        error: String,
+    },
+}
+
+impl Action for TeeAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
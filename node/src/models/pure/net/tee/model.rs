@@ -0,0 +1,220 @@
+use super::{
+    action::{TeeAction, TeeSink, TeeTransport},
+    state::{RecvRequest, TeeState},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::net::{
+        pnet::{client::action::PnetClientAction, server::action::PnetServerAction},
+        tcp_client::action::TcpClientAction,
+        tcp_server::action::TcpServerAction,
+    },
+};
+use log::warn;
+
+// The `TeeState` model sits between a transport and an application model:
+// recv requests issued through it are forwarded to the transport, and the
+// received data is copied to a `TeeSink` (an in-memory capture, or another
+// connection) before the application gets it. Used on top of a `Pnet*`
+// transport, it sees the decrypted application-layer traffic.
+
+// This model can be used on top of any of the `TeeTransport` models, the
+// application registers the ones it uses.
+impl RegisterModel for TeeState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.model_pure::<Self>()
+    }
+}
+
+impl PureModel for TeeState {
+    type Action = TeeAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            TeeAction::Recv {
+                uid,
+                transport,
+                connection,
+                count,
+                timeout,
+                sink,
+                on_success,
+                on_timeout,
+                on_error,
+            } => {
+                state.substate_mut::<TeeState>().new_recv_request(
+                    &uid,
+                    RecvRequest {
+                        sink,
+                        on_success,
+                        on_timeout,
+                        on_error,
+                    },
+                );
+                dispatch_recv(dispatcher, transport, uid, connection, count, timeout)
+            }
+            TeeAction::RecvSuccess { uid, data } => {
+                let RecvRequest {
+                    sink, on_success, ..
+                } = state.substate_mut::<TeeState>().take_recv_request(&uid);
+
+                mirror(state, dispatcher, sink, &data);
+                dispatcher.dispatch_back(&on_success, (uid, data))
+            }
+            TeeAction::RecvTimeout { uid, partial_data } => {
+                let RecvRequest {
+                    sink, on_timeout, ..
+                } = state.substate_mut::<TeeState>().take_recv_request(&uid);
+
+                mirror(state, dispatcher, sink, &partial_data);
+                dispatcher.dispatch_back(&on_timeout, (uid, partial_data))
+            }
+            TeeAction::RecvError { uid, error } => {
+                let RecvRequest { on_error, .. } =
+                    state.substate_mut::<TeeState>().take_recv_request(&uid);
+
+                dispatcher.dispatch_back(&on_error, (uid, error))
+            }
+            TeeAction::SinkSendSuccess { .. } => (),
+            TeeAction::SinkSendTimeout { uid } => {
+                warn!("|TEE| sink send {:?} timeout", uid)
+            }
+            TeeAction::SinkSendError { uid, error } => {
+                warn!("|TEE| sink send {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
+
+fn dispatch_recv(
+    dispatcher: &mut Dispatcher,
+    transport: TeeTransport,
+    uid: Uid,
+    connection: Uid,
+    count: usize,
+    timeout: Timeout,
+) {
+    let on_success = callback!(|(uid: Uid, data: Vec<u8>)| TeeAction::RecvSuccess { uid, data });
+    let on_timeout =
+        callback!(|(uid: Uid, partial_data: Vec<u8>)| TeeAction::RecvTimeout { uid, partial_data });
+    let on_error = callback!(|(uid: Uid, error: String)| TeeAction::RecvError { uid, error });
+
+    match transport {
+        TeeTransport::TcpServer => dispatcher.dispatch(TcpServerAction::Recv {
+            uid,
+            connection,
+            count,
+            timeout,
+            on_success,
+            on_timeout,
+            on_error,
+        }),
+        TeeTransport::TcpClient => dispatcher.dispatch(TcpClientAction::Recv {
+            uid,
+            connection,
+            count,
+            timeout,
+            on_success,
+            on_timeout,
+            on_error,
+        }),
+        TeeTransport::PnetServer => dispatcher.dispatch(PnetServerAction::Recv {
+            uid,
+            connection,
+            count,
+            timeout,
+            on_success,
+            on_timeout,
+            on_error,
+        }),
+        TeeTransport::PnetClient => dispatcher.dispatch(PnetClientAction::Recv {
+            uid,
+            connection,
+            count,
+            timeout,
+            on_success,
+            on_timeout,
+            on_error,
+        }),
+    }
+}
+
+fn mirror<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    sink: TeeSink,
+    data: &[u8],
+) {
+    if data.is_empty() {
+        return;
+    }
+
+    match sink {
+        TeeSink::Capture { capture } => state
+            .substate_mut::<TeeState>()
+            .captures
+            .entry(capture)
+            .or_default()
+            .extend_from_slice(data),
+        TeeSink::Connection {
+            transport,
+            connection,
+            timeout,
+        } => {
+            let uid = state.new_uid();
+            let on_success = callback!(|uid: Uid| TeeAction::SinkSendSuccess { uid });
+            let on_timeout = callback!(|uid: Uid| TeeAction::SinkSendTimeout { uid });
+            let on_error =
+                callback!(|(uid: Uid, error: String)| TeeAction::SinkSendError { uid, error });
+
+            match transport {
+                TeeTransport::TcpServer => dispatcher.dispatch(TcpServerAction::Send {
+                    uid,
+                    connection,
+                    data: data.into(),
+                    timeout,
+                    on_success,
+                    on_timeout,
+                    on_error,
+                }),
+                TeeTransport::TcpClient => dispatcher.dispatch(TcpClientAction::Send {
+                    uid,
+                    connection,
+                    data: data.into(),
+                    timeout,
+                    on_success,
+                    on_timeout,
+                    on_error,
+                }),
+                TeeTransport::PnetServer => dispatcher.dispatch(PnetServerAction::Send {
+                    uid,
+                    connection,
+                    data: data.to_vec(),
+                    timeout,
+                    on_success,
+                    on_timeout,
+                    on_error,
+                }),
+                TeeTransport::PnetClient => dispatcher.dispatch(PnetClientAction::Send {
+                    uid,
+                    connection,
+                    data: data.to_vec(),
+                    timeout,
+                    on_success,
+                    on_timeout,
+                    on_error,
+                }),
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::action::TeeSink;
+use crate::automaton::{
+    action::Redispatch,
+    state::{Objects, Uid},
+};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecvRequest {
+    pub sink: TeeSink,
+    pub on_success: Redispatch<(Uid, Vec<u8>)>,
+    pub on_timeout: Redispatch<(Uid, Vec<u8>)>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug)]
+pub struct TeeState {
+    pub recv_requests: Objects<RecvRequest>,
+    pub captures: Objects<Vec<u8>>,
+}
+
+impl TeeState {
+    pub fn new() -> Self {
+        Self {
+            recv_requests: Objects::<RecvRequest>::new(),
+            captures: Objects::<Vec<u8>>::new(),
+        }
+    }
+
+    pub fn new_recv_request(&mut self, uid: &Uid, request: RecvRequest) {
+        if self.recv_requests.insert(*uid, request).is_some() {
+            panic!("Attempt to re-use existing RecvRequest {:?}", uid)
+        }
+    }
+
+    pub fn take_recv_request(&mut self, uid: &Uid) -> RecvRequest {
+        self.recv_requests
+            .remove(uid)
+            .expect(&format!("Take attempt on inexistent RecvRequest {:?}", uid))
+    }
+
+    // Data mirrored so far to `TeeSink::Capture { capture }`, in the order it
+    // was received.
+    pub fn capture(&self, capture: &Uid) -> &[u8] {
+        self.captures
+            .get(capture)
+            .map_or(&[], |data| data.as_slice())
+    }
+
+    pub fn take_capture(&mut self, capture: &Uid) -> Vec<u8> {
+        self.captures.remove(capture).unwrap_or_default()
+    }
+}
@@ -0,0 +1,180 @@
+use super::tcp::state::{ConnectionType, TcpState};
+use crate::automaton::state::{ModelState, State, Uid};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::Write;
+
+// Graph of the listeners and connections of every instance of a simulation,
+// taken from their `TcpState` (the other net models are built on top of it).
+// Objects are identified by (instance, `Uid`).
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct Topology {
+    pub nodes: Vec<TopologyNode>,
+    pub edges: Vec<TopologyEdge>,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct TopologyNode {
+    pub instance: usize,
+    pub uid: Uid,
+    pub kind: TopologyNodeKind,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum TopologyNodeKind {
+    Listener {
+        address: String,
+    },
+    Connection {
+        incoming: bool,
+        // (local, peer) addresses, once the connection is established.
+        addrs: Option<(String, String)>,
+    },
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum TopologyEdge {
+    // A listener accepted an (incoming) connection.
+    Accepted {
+        listener: (usize, Uid),
+        connection: (usize, Uid),
+    },
+    // Both ends of a connection, matched by their addresses.
+    Connected {
+        outgoing: (usize, Uid),
+        incoming: (usize, Uid),
+    },
+}
+
+// Every instance must have a `TcpState`.
+pub fn topology_snapshot<Substate: ModelState>(state: &State<Substate>) -> Topology {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+
+    for (instance, substate) in state.substates.iter().enumerate() {
+        let tcp_state: &TcpState = substate.state();
+
+        for (&uid, listener) in tcp_state.listeners() {
+            nodes.push(TopologyNode {
+                instance,
+                uid,
+                kind: TopologyNodeKind::Listener {
+                    address: listener.address.clone(),
+                },
+            });
+        }
+
+        for (&uid, connection) in tcp_state.connections() {
+            let incoming = match connection.conn_type {
+                ConnectionType::Incoming { listener, .. } => {
+                    edges.push(TopologyEdge::Accepted {
+                        listener: (instance, listener),
+                        connection: (instance, uid),
+                    });
+                    true
+                }
+                ConnectionType::Outgoing { .. } => false,
+            };
+
+            nodes.push(TopologyNode {
+                instance,
+                uid,
+                kind: TopologyNodeKind::Connection {
+                    incoming,
+                    addrs: connection.addrs.clone(),
+                },
+            });
+        }
+    }
+
+    // The local address of each end is the peer address of the other one.
+    for outgoing in nodes.iter() {
+        let TopologyNodeKind::Connection {
+            incoming: false,
+            addrs: Some((local, peer)),
+        } = &outgoing.kind
+        else {
+            continue;
+        };
+
+        for incoming in nodes.iter() {
+            if let TopologyNodeKind::Connection {
+                incoming: true,
+                addrs: Some((incoming_local, incoming_peer)),
+            } = &incoming.kind
+            {
+                if incoming_local == peer && incoming_peer == local {
+                    edges.push(TopologyEdge::Connected {
+                        outgoing: (outgoing.instance, outgoing.uid),
+                        incoming: (incoming.instance, incoming.uid),
+                    });
+                }
+            }
+        }
+    }
+
+    Topology { nodes, edges }
+}
+
+impl Topology {
+    // Renders the graph in the Graphviz DOT format, with one cluster per
+    // instance.
+    pub fn to_dot(&self) -> String {
+        let node_id =
+            |(instance, uid): (usize, Uid)| format!("\"{}:{}\"", instance, usize::from(uid));
+        let mut dot = String::from("digraph topology {\n");
+        let mut instances: Vec<usize> = self.nodes.iter().map(|node| node.instance).collect();
+
+        instances.dedup();
+
+        for instance in instances {
+            writeln!(dot, "  subgraph cluster_{} {{", instance).unwrap();
+            writeln!(dot, "    label=\"instance {}\";", instance).unwrap();
+
+            for node in self.nodes.iter().filter(|node| node.instance == instance) {
+                let (label, shape) = match &node.kind {
+                    TopologyNodeKind::Listener { address } => (address.clone(), "box"),
+                    TopologyNodeKind::Connection {
+                        addrs: Some((local, _)),
+                        ..
+                    } => (local.clone(), "ellipse"),
+                    TopologyNodeKind::Connection { addrs: None, .. } => {
+                        ("pending".to_string(), "ellipse")
+                    }
+                };
+
+                writeln!(
+                    dot,
+                    "    {} [label=\"{}\", shape={}];",
+                    node_id((node.instance, node.uid)),
+                    label,
+                    shape
+                )
+                .unwrap();
+            }
+
+            dot.push_str("  }\n");
+        }
+
+        for edge in self.edges.iter() {
+            let (from, to, style) = match edge {
+                TopologyEdge::Accepted {
+                    listener,
+                    connection,
+                } => (*listener, *connection, "dashed"),
+                TopologyEdge::Connected { outgoing, incoming } => (*outgoing, *incoming, "solid"),
+            };
+
+            writeln!(
+                dot,
+                "  {} -> {} [style={}];",
+                node_id(from),
+                node_id(to),
+                style
+            )
+            .unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    automaton::{
+        action::{self, Action, ActionKind, Redispatch, Timeout},
+        state::Uid,
+    },
+    models::effectful::mio::action::MioEvent,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::rc::Rc;
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "855c0800-7eaa-4e73-aa9a-d3f0a4b59eec"]
+pub enum UdpAction {
+    Init {
+        instance: Uid,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    PollCreateSuccess {
+        poll: Uid,
+    },
+    PollCreateError {
+        poll: Uid,
+        error: String,
+    },
+    EventsCreate {
+        uid: Uid,
+    },
+    Bind {
+        socket: Uid,
+        address: String,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    BindSuccess {
+        socket: Uid,
+    },
+    BindError {
+        socket: Uid,
+        error: String,
+    },
+    RegisterSocketSuccess {
+        socket: Uid,
+    },
+    RegisterSocketError {
+        socket: Uid,
+        error: String,
+    },
+    // Pending send/recv requests of the socket fail with "Socket closed".
+    Close {
+        socket: Uid,
+        on_success: Redispatch<Uid>,
+    },
+    CloseSuccess {
+        socket: Uid,
+    },
+    // Waits for `timeout` (or the nearest request deadline), then carries on
+    // the send/recv requests of the sockets that became ready.
+    Poll {
+        uid: Uid,
+        timeout: Timeout,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    PollSuccess {
+        uid: Uid,
+        events: Vec<MioEvent>,
+    },
+    PollInterrupted {
+        uid: Uid,
+    },
+    PollError {
+        uid: Uid,
+        error: String,
+    },
+    // Sends `data` as a single datagram to `address`.
+    Send {
+        uid: Uid,
+        socket: Uid,
+        address: String,
+        #[serde(
+            serialize_with = "action::serialize_rc_bytes",
+            deserialize_with = "action::deserialize_rc_bytes"
+        )]
+        data: Rc<[u8]>,
+        timeout: Timeout,
+        on_success: Redispatch<Uid>,
+        on_timeout: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    SendSuccess {
+        uid: Uid,
+    },
+    SendErrorInterrupted {
+        uid: Uid,
+    },
+    SendErrorTryAgain {
+        uid: Uid,
+    },
+    SendError {
+        uid: Uid,
+        error: String,
+    },
+    // Receives the next datagram (up to `max_len` bytes) and its sender's
+    // address.
+    Recv {
+        uid: Uid,
+        socket: Uid,
+        max_len: usize,
+        timeout: Timeout,
+        on_success: Redispatch<(Uid, String, Vec<u8>)>, // (uid, peer address, data)
+        on_timeout: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    RecvSuccess {
+        uid: Uid,
+        peer_address: String,
+        data: Vec<u8>,
+    },
+    RecvErrorInterrupted {
+        uid: Uid,
+    },
+    RecvErrorTryAgain {
+        uid: Uid,
+    },
+    RecvError {
+        uid: Uid,
+        error: String,
+    },
+}
+
+impl Action for UdpAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
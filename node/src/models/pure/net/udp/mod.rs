@@ -0,0 +1,3 @@
+pub mod action;
+pub mod state;
+pub mod model;
\ No newline at end of file
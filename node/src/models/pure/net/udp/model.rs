@@ -0,0 +1,492 @@
+use super::{
+    action::UdpAction,
+    state::{PollRequest, RecvRequest, SendRequest, Socket, SocketStatus, Status, UdpState},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, TimeoutAbsolute},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::{
+        effectful::mio::{
+            action::{MioEffectfulAction, MioEvent},
+            state::MioState,
+        },
+        pure::time::{
+            model::{get_current_time, get_timeout_absolute},
+            state::TimeState,
+        },
+    },
+};
+
+// The `UdpState` model handles UDP sockets on top of the `MioState` model,
+// like `TcpState` does for TCP connections:
+// - Binding sockets to a local address (registered with the model's poll).
+// - Sending datagrams to, and receiving datagrams from, any peer. The peer
+//   address of a received datagram is a `String`, so it can be recorded and
+//   replayed like any other result.
+// - Timeout support for sends and receives, checked on every poll.
+//
+// Send/recv requests wait for their socket to be ready (as reported by
+// `UdpAction::Poll`), and go back to waiting when the operation would block.
+
+// This model depends on the `TimeState` (pure) and `MioState` (effectful).
+impl RegisterModel for UdpState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TimeState>()
+            .register::<MioState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for UdpState {
+    type Action = UdpAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            UdpAction::Init {
+                instance,
+                on_success,
+                on_error,
+            } => {
+                let poll = state.new_uid();
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                udp_state.status = Status::InitPollCreate {
+                    instance,
+                    poll,
+                    on_success,
+                    on_error,
+                };
+
+                dispatcher.dispatch_effect(MioEffectfulAction::PollCreate {
+                    poll,
+                    on_success: callback!(|poll: Uid| UdpAction::PollCreateSuccess { poll }),
+                    on_error: callback!(|(poll: Uid, error: String)| UdpAction::PollCreateError { poll, error })
+                });
+            }
+            UdpAction::PollCreateSuccess { .. } => {
+                let events = state.new_uid();
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                if let Status::InitPollCreate {
+                    instance,
+                    poll,
+                    on_success,
+                    ..
+                } = udp_state.status.clone()
+                {
+                    dispatcher.dispatch_effect(MioEffectfulAction::EventsCreate {
+                        uid: events,
+                        capacity: 1024,
+                        on_success: callback!(|uid: Uid| UdpAction::EventsCreate { uid }),
+                    });
+
+                    udp_state.status = Status::InitEventsCreate {
+                        instance,
+                        poll,
+                        events,
+                        on_success,
+                    };
+                } else {
+                    unreachable!()
+                }
+            }
+            UdpAction::PollCreateError { error, .. } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                if let Status::InitPollCreate {
+                    instance, on_error, ..
+                } = udp_state.status.clone()
+                {
+                    dispatcher.dispatch_back(&on_error, (instance, error));
+                    udp_state.status = Status::InitError { instance };
+                } else {
+                    unreachable!()
+                }
+            }
+            UdpAction::EventsCreate { .. } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                if let Status::InitEventsCreate {
+                    instance,
+                    poll,
+                    events,
+                    on_success,
+                } = udp_state.status.clone()
+                {
+                    dispatcher.dispatch_back(&on_success, instance);
+                    udp_state.status = Status::Ready {
+                        instance,
+                        poll,
+                        events,
+                    };
+                } else {
+                    unreachable!()
+                }
+            }
+            UdpAction::Bind {
+                socket,
+                address,
+                on_success,
+                on_error,
+            } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                assert!(udp_state.is_ready());
+                udp_state.new_socket(socket, address.clone(), on_success, on_error);
+                dispatcher.dispatch_effect(MioEffectfulAction::UdpBind {
+                    socket,
+                    address,
+                    on_success: callback!(|socket: Uid| UdpAction::BindSuccess { socket }),
+                    on_error: callback!(|(socket: Uid, error: String)| UdpAction::BindError { socket, error })
+                });
+            }
+            UdpAction::BindSuccess { socket } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                if let Status::Ready { poll, .. } = udp_state.status {
+                    udp_state.get_socket_mut(&socket).status = SocketStatus::Registering;
+                    dispatcher.dispatch_effect(MioEffectfulAction::PollRegisterUdpSocket {
+                        poll,
+                        socket,
+                        on_success: callback!(|socket: Uid| UdpAction::RegisterSocketSuccess { socket }),
+                        on_error: callback!(|(socket: Uid, error: String)| UdpAction::RegisterSocketError { socket, error }),
+                    });
+                } else {
+                    unreachable!()
+                }
+            }
+            UdpAction::BindError { socket, error } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let Socket { on_error, .. } = udp_state.get_socket(&socket);
+
+                dispatcher.dispatch_back(on_error, (socket, error));
+                udp_state.remove_socket(&socket);
+            }
+            UdpAction::RegisterSocketSuccess { socket } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let socket_obj = udp_state.get_socket_mut(&socket);
+
+                socket_obj.status = SocketStatus::Ready;
+                dispatcher.dispatch_back(&socket_obj.on_success, socket);
+            }
+            UdpAction::RegisterSocketError { socket, error } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let Socket { on_error, .. } = udp_state.get_socket(&socket);
+
+                dispatcher.dispatch_back(on_error, (socket, error));
+                udp_state.get_socket_mut(&socket).status = SocketStatus::Closing;
+                dispatcher.dispatch_effect(MioEffectfulAction::UdpClose {
+                    socket,
+                    on_success: callback!(|socket: Uid| UdpAction::CloseSuccess { socket }),
+                });
+            }
+            UdpAction::Close { socket, on_success } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let (send_requests, recv_requests) = udp_state.socket_requests(&socket);
+
+                for uid in send_requests {
+                    let SendRequest { on_error, .. } = udp_state.get_send_request(&uid);
+
+                    dispatcher.dispatch_back(on_error, (uid, "Socket closed".to_string()));
+                    udp_state.remove_send_request(&uid);
+                }
+
+                for uid in recv_requests {
+                    let RecvRequest { on_error, .. } = udp_state.get_recv_request(&uid);
+
+                    dispatcher.dispatch_back(on_error, (uid, "Socket closed".to_string()));
+                    udp_state.remove_recv_request(&uid);
+                }
+
+                let socket_obj = udp_state.get_socket_mut(&socket);
+
+                // Mio deregisters the socket when it's dropped.
+                socket_obj.status = SocketStatus::Closing;
+                socket_obj.on_close = Some(on_success);
+                dispatcher.dispatch_effect(MioEffectfulAction::UdpClose {
+                    socket,
+                    on_success: callback!(|socket: Uid| UdpAction::CloseSuccess { socket }),
+                });
+            }
+            UdpAction::CloseSuccess { socket } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                // Sockets closed after a registration error were already
+                // reported through `on_error`.
+                if let Some(on_close) = &udp_state.get_socket(&socket).on_close {
+                    dispatcher.dispatch_back(on_close, socket);
+                }
+
+                udp_state.remove_socket(&socket);
+            }
+            UdpAction::Poll {
+                uid,
+                timeout,
+                on_success,
+                on_error,
+            } => {
+                let current_time = get_current_time(state);
+                let udp_state: &mut UdpState = state.substate_mut();
+                let timeout = udp_state.poll_timeout(current_time, timeout);
+
+                if let Status::Ready { poll, events, .. } = udp_state.status {
+                    udp_state.new_poll(uid, timeout.clone(), on_success, on_error);
+                    dispatcher.dispatch_effect(MioEffectfulAction::PollEvents {
+                        uid,
+                        poll,
+                        events,
+                        timeout,
+                        on_success: callback!(|(uid: Uid, events: Vec<MioEvent>)| UdpAction::PollSuccess { uid, events }),
+                        on_interrupted: callback!(|uid: Uid| UdpAction::PollInterrupted { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| UdpAction::PollError { uid, error })
+                    })
+                } else {
+                    unreachable!()
+                };
+            }
+            UdpAction::PollSuccess { uid, events } => {
+                let current_time = get_current_time(state);
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                for event in events.iter() {
+                    udp_state.update_events(event)
+                }
+
+                process_pending_requests(current_time, udp_state, dispatcher);
+
+                let PollRequest { on_success, .. } = udp_state.get_poll_request(&uid);
+
+                dispatcher.dispatch_back(on_success, uid);
+                udp_state.remove_poll_request(&uid)
+            }
+            UdpAction::PollInterrupted { uid } => {
+                let udp_state: &UdpState = state.substate();
+
+                if let Status::Ready { poll, events, .. } = udp_state.status {
+                    dispatcher.dispatch_effect(MioEffectfulAction::PollEvents {
+                        uid,
+                        poll,
+                        events,
+                        timeout: udp_state.get_poll_request(&uid).timeout.clone(),
+                        on_success: callback!(|(uid: Uid, events: Vec<MioEvent>)| UdpAction::PollSuccess { uid, events }),
+                        on_interrupted: callback!(|uid: Uid| UdpAction::PollInterrupted { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| UdpAction::PollError { uid, error }),
+                    })
+                } else {
+                    unreachable!()
+                };
+            }
+            UdpAction::PollError { uid, error } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let PollRequest { on_error, .. } = udp_state.get_poll_request(&uid);
+
+                dispatcher.dispatch_back(on_error, (uid, error));
+                udp_state.remove_poll_request(&uid)
+            }
+            UdpAction::Send {
+                uid,
+                socket,
+                address,
+                data,
+                timeout,
+                on_success,
+                on_timeout,
+                on_error,
+            } => {
+                let current_time = get_current_time(state);
+                let timeout = get_timeout_absolute(state, timeout);
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                assert!(udp_state.has_socket(&socket));
+                udp_state.new_send_request(
+                    uid,
+                    SendRequest::new(
+                        socket, address, data, timeout, on_success, on_timeout, on_error,
+                    ),
+                );
+                process_pending_requests(current_time, udp_state, dispatcher);
+            }
+            UdpAction::SendSuccess { uid } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let SendRequest { on_success, .. } = udp_state.get_send_request(&uid);
+
+                dispatcher.dispatch_back(on_success, uid);
+                udp_state.remove_send_request(&uid)
+            }
+            UdpAction::SendErrorInterrupted { uid } => {
+                dispatch_send(state.substate_mut(), dispatcher, uid)
+            }
+            UdpAction::SendErrorTryAgain { uid } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let request = udp_state.get_send_request_mut(&uid);
+                let socket = request.socket;
+
+                // Sent again once the socket is writable.
+                request.sending = false;
+                udp_state.get_socket_mut(&socket).writable = false;
+            }
+            UdpAction::SendError { uid, error } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let SendRequest { on_error, .. } = udp_state.get_send_request(&uid);
+
+                dispatcher.dispatch_back(on_error, (uid, error));
+                udp_state.remove_send_request(&uid)
+            }
+            UdpAction::Recv {
+                uid,
+                socket,
+                max_len,
+                timeout,
+                on_success,
+                on_timeout,
+                on_error,
+            } => {
+                let current_time = get_current_time(state);
+                let timeout = get_timeout_absolute(state, timeout);
+                let udp_state: &mut UdpState = state.substate_mut();
+
+                assert!(udp_state.has_socket(&socket));
+                udp_state.new_recv_request(
+                    uid,
+                    RecvRequest::new(socket, max_len, timeout, on_success, on_timeout, on_error),
+                );
+                process_pending_requests(current_time, udp_state, dispatcher);
+            }
+            UdpAction::RecvSuccess {
+                uid,
+                peer_address,
+                data,
+            } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let RecvRequest { on_success, .. } = udp_state.get_recv_request(&uid);
+
+                dispatcher.dispatch_back(on_success, (uid, peer_address, data));
+                udp_state.remove_recv_request(&uid)
+            }
+            UdpAction::RecvErrorInterrupted { uid } => {
+                dispatch_recv(state.substate_mut(), dispatcher, uid)
+            }
+            UdpAction::RecvErrorTryAgain { uid } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let request = udp_state.get_recv_request_mut(&uid);
+                let socket = request.socket;
+
+                // Received once the socket is readable again.
+                request.receiving = false;
+                udp_state.get_socket_mut(&socket).readable = false;
+            }
+            UdpAction::RecvError { uid, error } => {
+                let udp_state: &mut UdpState = state.substate_mut();
+                let RecvRequest { on_error, .. } = udp_state.get_recv_request(&uid);
+
+                dispatcher.dispatch_back(on_error, (uid, error));
+                udp_state.remove_recv_request(&uid)
+            }
+        }
+    }
+}
+
+fn timed_out(current_time: u128, timeout: &TimeoutAbsolute) -> bool {
+    match timeout {
+        TimeoutAbsolute::Millis(ms) => current_time >= *ms,
+        TimeoutAbsolute::Never => false,
+    }
+}
+
+// Times out expired requests, and starts the operations of the others if
+// their socket is ready.
+fn process_pending_requests(
+    current_time: u128,
+    udp_state: &mut UdpState,
+    dispatcher: &mut Dispatcher,
+) {
+    for uid in udp_state.pending_send_requests() {
+        let SendRequest {
+            socket,
+            timeout,
+            on_timeout,
+            ..
+        } = udp_state.get_send_request(&uid);
+
+        if timed_out(current_time, timeout) {
+            dispatcher.dispatch_back(on_timeout, uid);
+            udp_state.remove_send_request(&uid);
+        } else if let Socket {
+            status: SocketStatus::Ready,
+            writable: true,
+            ..
+        } = udp_state.get_socket(socket)
+        {
+            udp_state.get_send_request_mut(&uid).sending = true;
+            dispatch_send(udp_state, dispatcher, uid)
+        }
+    }
+
+    for uid in udp_state.pending_recv_requests() {
+        let RecvRequest {
+            socket,
+            timeout,
+            on_timeout,
+            ..
+        } = udp_state.get_recv_request(&uid);
+
+        if timed_out(current_time, timeout) {
+            dispatcher.dispatch_back(on_timeout, uid);
+            udp_state.remove_recv_request(&uid);
+        } else if let Socket {
+            status: SocketStatus::Ready,
+            readable: true,
+            ..
+        } = udp_state.get_socket(socket)
+        {
+            udp_state.get_recv_request_mut(&uid).receiving = true;
+            dispatch_recv(udp_state, dispatcher, uid)
+        }
+    }
+}
+
+fn dispatch_send(udp_state: &mut UdpState, dispatcher: &mut Dispatcher, uid: Uid) {
+    let SendRequest {
+        socket,
+        address,
+        data,
+        ..
+    } = udp_state.get_send_request(&uid);
+
+    dispatcher.dispatch_effect(MioEffectfulAction::UdpSend {
+        uid,
+        socket: *socket,
+        address: address.clone(),
+        data: data.clone(),
+        on_success: callback!(|uid: Uid| UdpAction::SendSuccess { uid }),
+        on_interrupted: callback!(|uid: Uid| UdpAction::SendErrorInterrupted { uid }),
+        on_would_block: callback!(|uid: Uid| UdpAction::SendErrorTryAgain { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| UdpAction::SendError { uid, error }),
+    });
+}
+
+fn dispatch_recv(udp_state: &mut UdpState, dispatcher: &mut Dispatcher, uid: Uid) {
+    let RecvRequest {
+        socket, max_len, ..
+    } = udp_state.get_recv_request(&uid);
+
+    dispatcher.dispatch_effect(MioEffectfulAction::UdpRecvFrom {
+        uid,
+        socket: *socket,
+        len: *max_len,
+        on_success: callback!(|(uid: Uid, peer_address: String, data: Vec<u8>)| UdpAction::RecvSuccess { uid, peer_address, data }),
+        on_interrupted: callback!(|uid: Uid| UdpAction::RecvErrorInterrupted { uid }),
+        on_would_block: callback!(|uid: Uid| UdpAction::RecvErrorTryAgain { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| UdpAction::RecvError { uid, error }),
+    });
+}
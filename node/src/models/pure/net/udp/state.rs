@@ -0,0 +1,409 @@
+use crate::{
+    automaton::{
+        action::{self, Redispatch, Timeout, TimeoutAbsolute},
+        state::{Objects, Uid},
+    },
+    models::effectful::mio::action::MioEvent,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::rc::Rc;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum Status {
+    New,
+    InitError {
+        instance: Uid,
+    },
+    InitPollCreate {
+        instance: Uid,
+        poll: Uid,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    },
+    InitEventsCreate {
+        instance: Uid,
+        poll: Uid,
+        events: Uid,
+        on_success: Redispatch<Uid>,
+    },
+    Ready {
+        instance: Uid,
+        poll: Uid,
+        events: Uid,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub enum SocketStatus {
+    Binding,
+    Registering,
+    Ready,
+    Closing,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Socket {
+    pub address: String,
+    pub status: SocketStatus,
+    // Set by poll events, cleared when a send/recv would block.
+    pub readable: bool,
+    pub writable: bool,
+    pub on_success: Redispatch<Uid>,
+    pub on_error: Redispatch<(Uid, String)>,
+    // Set by `UdpAction::Close`.
+    pub on_close: Option<Redispatch<Uid>>,
+}
+
+impl Socket {
+    pub fn new(
+        address: String,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    ) -> Self {
+        Self {
+            address,
+            status: SocketStatus::Binding,
+            readable: false,
+            writable: false,
+            on_success,
+            on_error,
+            on_close: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PollRequest {
+    pub timeout: Timeout,
+    pub on_success: Redispatch<Uid>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SendRequest {
+    pub socket: Uid,
+    pub address: String,
+    #[serde(
+        serialize_with = "action::serialize_rc_bytes",
+        deserialize_with = "action::deserialize_rc_bytes"
+    )]
+    pub data: Rc<[u8]>,
+    pub timeout: TimeoutAbsolute,
+    // A `UdpSend` is in-flight.
+    pub sending: bool,
+    pub seq: u64,
+    pub on_success: Redispatch<Uid>,
+    pub on_timeout: Redispatch<Uid>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+impl SendRequest {
+    pub fn new(
+        socket: Uid,
+        address: String,
+        data: Rc<[u8]>,
+        timeout: TimeoutAbsolute,
+        on_success: Redispatch<Uid>,
+        on_timeout: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    ) -> Self {
+        Self {
+            socket,
+            address,
+            data,
+            timeout,
+            sending: false,
+            seq: 0,
+            on_success,
+            on_timeout,
+            on_error,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecvRequest {
+    pub socket: Uid,
+    pub max_len: usize,
+    pub timeout: TimeoutAbsolute,
+    // A `UdpRecvFrom` is in-flight.
+    pub receiving: bool,
+    pub seq: u64,
+    pub on_success: Redispatch<(Uid, String, Vec<u8>)>,
+    pub on_timeout: Redispatch<Uid>,
+    pub on_error: Redispatch<(Uid, String)>,
+}
+
+impl RecvRequest {
+    pub fn new(
+        socket: Uid,
+        max_len: usize,
+        timeout: TimeoutAbsolute,
+        on_success: Redispatch<(Uid, String, Vec<u8>)>,
+        on_timeout: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    ) -> Self {
+        Self {
+            socket,
+            max_len,
+            timeout,
+            receiving: false,
+            seq: 0,
+            on_success,
+            on_timeout,
+            on_error,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UdpState {
+    pub status: Status,
+    socket_objects: Objects<Socket>,
+    poll_request_objects: Objects<PollRequest>,
+    send_request_objects: Objects<SendRequest>,
+    recv_request_objects: Objects<RecvRequest>,
+    // Stamps send/recv requests with their creation order, like in `TcpState`.
+    seq: u64,
+}
+
+impl Default for UdpState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UdpState {
+    pub fn new() -> Self {
+        Self {
+            status: Status::New,
+            socket_objects: Objects::<Socket>::new(),
+            poll_request_objects: Objects::<PollRequest>::new(),
+            send_request_objects: Objects::<SendRequest>::new(),
+            recv_request_objects: Objects::<RecvRequest>::new(),
+            seq: 0,
+        }
+    }
+
+    fn next_seq(&mut self) -> u64 {
+        let seq = self.seq;
+        self.seq += 1;
+        seq
+    }
+
+    pub fn is_ready(&self) -> bool {
+        matches!(self.status, Status::Ready { .. })
+    }
+
+    pub fn new_socket(
+        &mut self,
+        uid: Uid,
+        address: String,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    ) {
+        if self
+            .socket_objects
+            .insert(uid, Socket::new(address, on_success, on_error))
+            .is_some()
+        {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    pub fn get_socket(&self, uid: &Uid) -> &Socket {
+        self.socket_objects
+            .get(uid)
+            .expect(&format!("Socket object {:?} not found", uid))
+    }
+
+    pub fn get_socket_mut(&mut self, uid: &Uid) -> &mut Socket {
+        self.socket_objects
+            .get_mut(uid)
+            .expect(&format!("Socket object {:?} not found", uid))
+    }
+
+    pub fn has_socket(&self, uid: &Uid) -> bool {
+        self.socket_objects.contains_key(uid)
+    }
+
+    pub fn remove_socket(&mut self, uid: &Uid) {
+        self.socket_objects
+            .remove(uid)
+            .expect(&format!("Attempt to remove an inexistent Socket {:?}", uid));
+    }
+
+    // Events for other objects (e.g. sockets closed since) are ignored.
+    pub fn update_events(&mut self, event: &MioEvent) {
+        if let Some(socket) = self.socket_objects.get_mut(&event.token) {
+            // Errors are reported by the next send/recv.
+            socket.readable |= event.readable || event.error;
+            socket.writable |= event.writable || event.error;
+        }
+    }
+
+    pub fn new_poll(
+        &mut self,
+        uid: Uid,
+        timeout: Timeout,
+        on_success: Redispatch<Uid>,
+        on_error: Redispatch<(Uid, String)>,
+    ) {
+        if self
+            .poll_request_objects
+            .insert(
+                uid,
+                PollRequest {
+                    timeout,
+                    on_success,
+                    on_error,
+                },
+            )
+            .is_some()
+        {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    pub fn get_poll_request(&self, uid: &Uid) -> &PollRequest {
+        self.poll_request_objects
+            .get(uid)
+            .expect(&format!("PollRequest object {:?} not found", uid))
+    }
+
+    pub fn remove_poll_request(&mut self, uid: &Uid) {
+        self.poll_request_objects.remove(uid).expect(&format!(
+            "Attempt to remove an inexistent PollRequest {:?}",
+            uid
+        ));
+    }
+
+    // The request's `seq` is stamped here.
+    pub fn new_send_request(&mut self, uid: Uid, mut request: SendRequest) {
+        request.seq = self.next_seq();
+
+        if self.send_request_objects.insert(uid, request).is_some() {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    pub fn get_send_request(&self, uid: &Uid) -> &SendRequest {
+        self.send_request_objects
+            .get(uid)
+            .expect(&format!("SendRequest object {:?} not found", uid))
+    }
+
+    pub fn get_send_request_mut(&mut self, uid: &Uid) -> &mut SendRequest {
+        self.send_request_objects
+            .get_mut(uid)
+            .expect(&format!("SendRequest object {:?} not found", uid))
+    }
+
+    pub fn remove_send_request(&mut self, uid: &Uid) {
+        self.send_request_objects.remove(uid).expect(&format!(
+            "Attempt to remove an inexistent SendRequest {:?}",
+            uid
+        ));
+    }
+
+    // Same as `new_send_request()`.
+    pub fn new_recv_request(&mut self, uid: Uid, mut request: RecvRequest) {
+        request.seq = self.next_seq();
+
+        if self.recv_request_objects.insert(uid, request).is_some() {
+            panic!("Attempt to re-use existing {:?}", uid)
+        }
+    }
+
+    pub fn get_recv_request(&self, uid: &Uid) -> &RecvRequest {
+        self.recv_request_objects
+            .get(uid)
+            .expect(&format!("RecvRequest object {:?} not found", uid))
+    }
+
+    pub fn get_recv_request_mut(&mut self, uid: &Uid) -> &mut RecvRequest {
+        self.recv_request_objects
+            .get_mut(uid)
+            .expect(&format!("RecvRequest object {:?} not found", uid))
+    }
+
+    pub fn remove_recv_request(&mut self, uid: &Uid) {
+        self.recv_request_objects.remove(uid).expect(&format!(
+            "Attempt to remove an inexistent RecvRequest {:?}",
+            uid
+        ));
+    }
+
+    // Requests with no operation in-flight, in creation order.
+    pub fn pending_send_requests(&self) -> Vec<Uid> {
+        let mut requests: Vec<_> = self
+            .send_request_objects
+            .iter()
+            .filter(|(_, request)| !request.sending)
+            .collect();
+
+        requests.sort_by_key(|(_, request)| request.seq);
+        requests.into_iter().map(|(uid, _)| *uid).collect()
+    }
+
+    pub fn pending_recv_requests(&self) -> Vec<Uid> {
+        let mut requests: Vec<_> = self
+            .recv_request_objects
+            .iter()
+            .filter(|(_, request)| !request.receiving)
+            .collect();
+
+        requests.sort_by_key(|(_, request)| request.seq);
+        requests.into_iter().map(|(uid, _)| *uid).collect()
+    }
+
+    // Requests of `socket`, in-flight or not.
+    pub fn socket_requests(&self, socket: &Uid) -> (Vec<Uid>, Vec<Uid>) {
+        let send_requests = self
+            .send_request_objects
+            .iter()
+            .filter(|(_, request)| request.socket == *socket)
+            .map(|(uid, _)| *uid)
+            .collect();
+        let recv_requests = self
+            .recv_request_objects
+            .iter()
+            .filter(|(_, request)| request.socket == *socket)
+            .map(|(uid, _)| *uid)
+            .collect();
+
+        (send_requests, recv_requests)
+    }
+
+    pub fn nearest_deadline(&self) -> TimeoutAbsolute {
+        let send_requests = self.send_request_objects.values().map(|req| &req.timeout);
+        let recv_requests = self.recv_request_objects.values().map(|req| &req.timeout);
+
+        send_requests
+            .chain(recv_requests)
+            .filter_map(|timeout| match timeout {
+                TimeoutAbsolute::Millis(ms) => Some(*ms),
+                TimeoutAbsolute::Never => None,
+            })
+            .min()
+            .map_or(TimeoutAbsolute::Never, TimeoutAbsolute::Millis)
+    }
+
+    // See `TcpState::poll_timeout`.
+    pub fn poll_timeout(&self, current_time: u128, timeout: Timeout) -> Timeout {
+        match self.nearest_deadline() {
+            TimeoutAbsolute::Millis(deadline) => {
+                let until_deadline =
+                    deadline.saturating_sub(current_time).min(u64::MAX as u128) as u64;
+
+                match timeout {
+                    Timeout::Millis(ms) | Timeout::Inactivity(ms) => {
+                        Timeout::Millis(ms.min(until_deadline))
+                    }
+                    Timeout::Never => Timeout::Millis(until_deadline),
+                }
+            }
+            TimeoutAbsolute::Never => timeout,
+        }
+    }
+}
@@ -14,6 +14,9 @@ use super::{action::PRNGPureAction, state::PRNGState};
 // typically necessary to use it. Instead, Models can (and should) access the
 // `PRNGState` directly through the `ModelState` interface.
 //
+// The generator algorithm is chosen with `PRNGConfig::algorithm` and kept when
+// reseeding, so a replayed session draws the same values.
+//
 // IMPORTANT: This implementation is designed for a fast and deterministic PRNG
 // primarily intended for testing purposes. It should NOT be used for
 // operations requiring cryptographic security due to its determinism and lack
@@ -1,24 +1,90 @@
-use rand::{rngs::SmallRng, SeedableRng};
+use rand::{rngs::SmallRng, Error, RngCore, SeedableRng};
+use rand_chacha::{ChaCha12Rng, ChaCha20Rng, ChaCha8Rng};
+
+// Generator algorithm used by `PRNGState`. `SmallRng` is the fastest, but the
+// algorithm behind it depends on the platform (and may change between `rand`
+// releases). The ChaCha generators produce the same sequence for a given seed
+// everywhere.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum PRNGAlgorithm {
+    #[default]
+    SmallRng,
+    ChaCha8,
+    ChaCha12,
+    ChaCha20,
+}
+
+// The ChaCha generators are boxed, they are much larger than `SmallRng`.
+#[derive(Clone, Debug)]
+pub enum PRNG {
+    SmallRng(SmallRng),
+    ChaCha8(Box<ChaCha8Rng>),
+    ChaCha12(Box<ChaCha12Rng>),
+    ChaCha20(Box<ChaCha20Rng>),
+}
+
+impl PRNG {
+    pub fn new(algorithm: PRNGAlgorithm, seed: u64) -> Self {
+        match algorithm {
+            PRNGAlgorithm::SmallRng => Self::SmallRng(SmallRng::seed_from_u64(seed)),
+            PRNGAlgorithm::ChaCha8 => Self::ChaCha8(Box::new(ChaCha8Rng::seed_from_u64(seed))),
+            PRNGAlgorithm::ChaCha12 => Self::ChaCha12(Box::new(ChaCha12Rng::seed_from_u64(seed))),
+            PRNGAlgorithm::ChaCha20 => Self::ChaCha20(Box::new(ChaCha20Rng::seed_from_u64(seed))),
+        }
+    }
+
+    fn inner(&mut self) -> &mut dyn RngCore {
+        match self {
+            Self::SmallRng(rng) => rng,
+            Self::ChaCha8(rng) => rng.as_mut(),
+            Self::ChaCha12(rng) => rng.as_mut(),
+            Self::ChaCha20(rng) => rng.as_mut(),
+        }
+    }
+}
+
+impl RngCore for PRNG {
+    fn next_u32(&mut self) -> u32 {
+        self.inner().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner().fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.inner().try_fill_bytes(dest)
+    }
+}
 
 #[allow(dead_code)]
 pub struct PRNGConfig {
     pub seed: u64,
+    pub algorithm: PRNGAlgorithm,
 }
 
 #[derive(Debug)]
 pub struct PRNGState {
-    pub rng: SmallRng,
+    // Kept so that reseeding (and replaying a recording) uses the same
+    // generator the state was created with.
+    pub algorithm: PRNGAlgorithm,
+    pub rng: PRNG,
 }
 
 #[allow(dead_code)]
 impl PRNGState {
     pub fn from_config(config: PRNGConfig) -> Self {
         Self {
-            rng: SmallRng::seed_from_u64(config.seed),
+            algorithm: config.algorithm,
+            rng: PRNG::new(config.algorithm, config.seed),
         }
     }
 
     pub fn seed(&mut self, seed: u64) {
-        self.rng = SmallRng::seed_from_u64(seed)
+        self.rng = PRNG::new(self.algorithm, seed)
     }
 }
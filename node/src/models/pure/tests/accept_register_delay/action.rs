@@ -0,0 +1,35 @@
+use crate::automaton::{
+    action::{Action, ActionKind},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "8f437e13-958f-4d8b-a2e5-9ce3e03280df"]
+pub enum AcceptRegisterDelayAction {
+    Tick,
+    PollSuccess { uid: Uid },
+    PollError { uid: Uid, error: String },
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    InitListenerSuccess { listener: Uid },
+    InitListenerError { listener: Uid, error: String },
+    ListenerCloseEvent { listener: Uid },
+    ConnectionEvent { listener: Uid, connection: Uid },
+    CloseEvent { listener: Uid, connection: Uid },
+    ConnectSuccess { connection: Uid },
+    ConnectTimeout { connection: Uid },
+    ConnectError { connection: Uid, error: String },
+    ConnectClose { connection: Uid },
+    SendSuccess { uid: Uid },
+    SendTimeout { uid: Uid },
+    SendError { uid: Uid, error: String },
+    RecvSuccess { uid: Uid, data: Vec<u8> },
+    RecvTimeout { uid: Uid, partial_data: Vec<u8> },
+    RecvError { uid: Uid, error: String },
+}
+
+impl Action for AcceptRegisterDelayAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,196 @@
+use super::{
+    action::AcceptRegisterDelayAction,
+    state::{AcceptRegisterDelayState, AcceptRegisterDelayStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `AcceptRegisterDelayState` model connects to its own listener and sends
+// `data` to the server as soon as the client is connected, while the server
+// delays the poll registration of the accepted connection (see
+// `TcpServerConfig`). The server receives once the registration completed.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for AcceptRegisterDelayState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for AcceptRegisterDelayState {
+    type Action = AcceptRegisterDelayAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            AcceptRegisterDelayAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<AcceptRegisterDelayState>().status
+                    == AcceptRegisterDelayStatus::Init
+                {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| AcceptRegisterDelayAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| AcceptRegisterDelayAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| AcceptRegisterDelayAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| AcceptRegisterDelayAction::PollError { uid, error }),
+                    })
+                }
+            }
+            AcceptRegisterDelayAction::PollSuccess { .. } => (),
+            AcceptRegisterDelayAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            AcceptRegisterDelayAction::InitSuccess { .. } => {
+                let address = state.substate::<AcceptRegisterDelayState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| AcceptRegisterDelayAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| AcceptRegisterDelayAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| AcceptRegisterDelayAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| AcceptRegisterDelayAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| AcceptRegisterDelayAction::ListenerCloseEvent { listener }),
+                });
+            }
+            AcceptRegisterDelayAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            AcceptRegisterDelayAction::InitListenerSuccess { .. } => {
+                let delay_state: &mut AcceptRegisterDelayState = state.substate_mut();
+                let address = delay_state.address.clone();
+
+                delay_state.status = AcceptRegisterDelayStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| AcceptRegisterDelayAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| AcceptRegisterDelayAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| AcceptRegisterDelayAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| AcceptRegisterDelayAction::ConnectClose { connection }),
+                });
+            }
+            AcceptRegisterDelayAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            AcceptRegisterDelayAction::ConnectionEvent { connection, .. } => {
+                state
+                    .substate_mut::<AcceptRegisterDelayState>()
+                    .server_connection = Some(connection);
+                recv_when_connected(state, dispatcher)
+            }
+            AcceptRegisterDelayAction::ConnectSuccess { connection } => {
+                let delay_state: &mut AcceptRegisterDelayState = state.substate_mut();
+                let data = delay_state.data.clone();
+
+                delay_state.client_connection = Some(connection);
+                // Send before the server end of the connection is registered.
+                dispatcher.dispatch(TcpClientAction::Send {
+                    uid: state.new_uid(),
+                    connection,
+                    data: data.into(),
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|uid: Uid| AcceptRegisterDelayAction::SendSuccess { uid }),
+                    on_timeout: callback!(|uid: Uid| AcceptRegisterDelayAction::SendTimeout { uid }),
+                    on_error: callback!(|(uid: Uid, error: String)| AcceptRegisterDelayAction::SendError { uid, error }),
+                });
+                recv_when_connected(state, dispatcher)
+            }
+            AcceptRegisterDelayAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            AcceptRegisterDelayAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            // The server receives once its end is registered.
+            AcceptRegisterDelayAction::SendSuccess { .. } => (),
+            AcceptRegisterDelayAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            AcceptRegisterDelayAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            AcceptRegisterDelayAction::RecvSuccess { data, .. } => {
+                state.substate_mut::<AcceptRegisterDelayState>().received = Some(data)
+            }
+            AcceptRegisterDelayAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timeout: {:?}", uid, partial_data)
+            }
+            AcceptRegisterDelayAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            AcceptRegisterDelayAction::ListenerCloseEvent { .. }
+            | AcceptRegisterDelayAction::CloseEvent { .. }
+            | AcceptRegisterDelayAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Receives `data` on the server end once the connection is established on
+// both ends.
+fn recv_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let AcceptRegisterDelayState {
+        data,
+        client_connection: Some(_),
+        server_connection: Some(connection),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let (connection, count) = (*connection, data.len());
+
+    dispatcher.dispatch(TcpServerAction::Recv {
+        uid: state.new_uid(),
+        connection,
+        count,
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|(uid: Uid, data: Vec<u8>)| AcceptRegisterDelayAction::RecvSuccess { uid, data }),
+        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| AcceptRegisterDelayAction::RecvTimeout { uid, partial_data }),
+        on_error: callback!(|(uid: Uid, error: String)| AcceptRegisterDelayAction::RecvError { uid, error }),
+    });
+}
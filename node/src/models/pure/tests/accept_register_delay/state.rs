@@ -0,0 +1,31 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AcceptRegisterDelayStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct AcceptRegisterDelayState {
+    pub status: AcceptRegisterDelayStatus,
+    pub address: String,
+    // Sent by the client as soon as it is connected.
+    pub data: Vec<u8>,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    pub received: Option<Vec<u8>>,
+}
+
+impl AcceptRegisterDelayState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: AcceptRegisterDelayStatus::Init,
+            address,
+            data,
+            client_connection: None,
+            server_connection: None,
+            received: None,
+        }
+    }
+}
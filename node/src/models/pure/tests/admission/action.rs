@@ -0,0 +1,72 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::tcp_server::action::{AdmissionRequest, ConnectionLifecycleEvent},
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "dfd96d43-c34f-477a-b87f-8d7fb5724bc7"]
+pub enum AdmissionAction {
+    Tick,
+    PollSuccess {
+        uid: Uid,
+    },
+    PollError {
+        uid: Uid,
+        error: String,
+    },
+    InitSuccess {
+        instance: Uid,
+    },
+    InitError {
+        instance: Uid,
+        error: String,
+    },
+    InitListenerSuccess {
+        listener: Uid,
+    },
+    InitListenerError {
+        listener: Uid,
+        error: String,
+    },
+    ListenerCloseEvent {
+        listener: Uid,
+    },
+    ConnectionEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    CloseEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    ConnectSuccess {
+        connection: Uid,
+    },
+    ConnectTimeout {
+        connection: Uid,
+    },
+    ConnectError {
+        connection: Uid,
+        error: String,
+    },
+    ConnectClose {
+        connection: Uid,
+    },
+    AdmissionRequest {
+        connection: Uid,
+        request: AdmissionRequest,
+    },
+    LifecycleEvent {
+        connection: Uid,
+        event: ConnectionLifecycleEvent,
+    },
+}
+
+impl Action for AdmissionAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,197 @@
+use super::{
+    action::AdmissionAction,
+    state::{AdmissionState, AdmissionStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{
+                    AdmissionRequest, ConnectionLifecycleEvent, RoutingPolicy, TcpServerAction,
+                },
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `AdmissionState` model admits at most one connection per IP with its
+// listener's `admission_control`. It connects to its own listener, then
+// connects a second time once the first connection is established: the second
+// connection is rejected and closed by the listener.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for AdmissionState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for AdmissionState {
+    type Action = AdmissionAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            AdmissionAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<AdmissionState>().status == AdmissionStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| AdmissionAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| AdmissionAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| AdmissionAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| AdmissionAction::PollError { uid, error }),
+                    })
+                }
+            }
+            AdmissionAction::PollSuccess { .. } => (),
+            AdmissionAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            AdmissionAction::InitSuccess { .. } => {
+                let address = state.substate::<AdmissionState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::Subscribe {
+                    on_event: callback!(|(connection: Uid, event: ConnectionLifecycleEvent)| AdmissionAction::LifecycleEvent { connection, event }),
+                });
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    // The second connection is rejected by `admission_control`,
+                    // not for exceeding `max_connections`.
+                    max_connections: 2,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: Some(
+                        callback!(|(connection: Uid, request: AdmissionRequest)| AdmissionAction::AdmissionRequest { connection, request }),
+                    ),
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| AdmissionAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| AdmissionAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| AdmissionAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| AdmissionAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| AdmissionAction::ListenerCloseEvent { listener }),
+                });
+            }
+            AdmissionAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            AdmissionAction::InitListenerSuccess { .. } => {
+                state.substate_mut::<AdmissionState>().status = AdmissionStatus::Listening;
+                connect(state, dispatcher)
+            }
+            AdmissionAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            AdmissionAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<AdmissionState>().server_connection = Some(connection);
+                connect_when_connected(state, dispatcher)
+            }
+            AdmissionAction::ConnectSuccess { connection } => {
+                let admission_state: &mut AdmissionState = state.substate_mut();
+
+                // The second connection is rejected by the server.
+                if admission_state.client_connection.is_none() {
+                    admission_state.client_connection = Some(connection);
+                    connect_when_connected(state, dispatcher)
+                }
+            }
+            AdmissionAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            AdmissionAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            AdmissionAction::AdmissionRequest {
+                connection,
+                request,
+            } => {
+                // At most one connection per IP.
+                let admitted = request.peer_connections == 0;
+
+                state
+                    .substate_mut::<AdmissionState>()
+                    .admissions
+                    .push((connection, request, admitted));
+
+                if admitted {
+                    dispatcher.dispatch(TcpServerAction::Admit { connection })
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Reject { connection });
+                    // No longer waiting for admission: ignored.
+                    dispatcher.dispatch(TcpServerAction::Admit { connection })
+                }
+            }
+            AdmissionAction::LifecycleEvent { connection, event } => {
+                if event
+                    == (ConnectionLifecycleEvent::Closed {
+                        reason: Some("Rejected".to_string()),
+                    })
+                {
+                    state.substate_mut::<AdmissionState>().rejected = Some(connection)
+                }
+            }
+            AdmissionAction::ListenerCloseEvent { .. }
+            | AdmissionAction::CloseEvent { .. }
+            | AdmissionAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+fn connect<Substate: ModelState>(state: &mut State<Substate>, dispatcher: &mut Dispatcher) {
+    let address = state.substate::<AdmissionState>().address.clone();
+
+    dispatcher.dispatch(TcpClientAction::Connect {
+        connection: state.new_uid(),
+        address,
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|connection: Uid| AdmissionAction::ConnectSuccess { connection }),
+        on_timeout: callback!(|connection: Uid| AdmissionAction::ConnectTimeout { connection }),
+        on_error: callback!(|(connection: Uid, error: String)| AdmissionAction::ConnectError { connection, error }),
+        on_close: callback!(|connection: Uid| AdmissionAction::ConnectClose { connection }),
+    });
+}
+
+// Connects a second time once the first connection is established on both
+// ends.
+fn connect_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let AdmissionState {
+        client_connection: Some(_),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    connect(state, dispatcher)
+}
@@ -0,0 +1,32 @@
+use crate::{automaton::state::Uid, models::pure::net::tcp_server::action::AdmissionRequest};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AdmissionStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct AdmissionState {
+    pub status: AdmissionStatus,
+    pub address: String,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    // (connection, request, admitted)
+    pub admissions: Vec<(Uid, AdmissionRequest, bool)>,
+    // Closed by the listener once rejected by `admission_control`.
+    pub rejected: Option<Uid>,
+}
+
+impl AdmissionState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: AdmissionStatus::Init,
+            address,
+            client_connection: None,
+            server_connection: None,
+            admissions: Vec::new(),
+            rejected: None,
+        }
+    }
+}
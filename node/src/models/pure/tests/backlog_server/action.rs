@@ -0,0 +1,25 @@
+use crate::automaton::{
+    action::{Action, ActionKind},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "6dfdb438-95d9-46c6-b2ae-7a95d8f2f26a"]
+pub enum BacklogServerAction {
+    Tick,
+    PollSuccess { uid: Uid },
+    PollError { uid: Uid, error: String },
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    InitListenerSuccess { listener: Uid },
+    InitListenerError { listener: Uid, error: String },
+    ListenerCloseEvent { listener: Uid },
+    ConnectionEvent { listener: Uid, connection: Uid },
+    CloseEvent { listener: Uid, connection: Uid },
+}
+
+impl Action for BacklogServerAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,117 @@
+use super::{
+    action::BacklogServerAction,
+    state::{BacklogServerState, BacklogServerStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `BacklogServerState` model only accepts connections. Its listener's
+// backlog is raised along with the poll events capacity, so that connections
+// opened while the runner isn't polling wait in the backlog.
+
+// This model depends on `TcpServerState`.
+impl RegisterModel for BacklogServerState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpServerState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for BacklogServerState {
+    type Action = BacklogServerAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            BacklogServerAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                let server_state: &BacklogServerState = state.substate();
+
+                if server_state.status == BacklogServerStatus::Init {
+                    let events_capacity = Some(server_state.connections);
+
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity,
+                        on_success: callback!(|instance: Uid| BacklogServerAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| BacklogServerAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| BacklogServerAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| BacklogServerAction::PollError { uid, error }),
+                    })
+                }
+            }
+            BacklogServerAction::PollSuccess { .. } => (),
+            BacklogServerAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            BacklogServerAction::InitSuccess { .. } => {
+                let server_state: &BacklogServerState = state.substate();
+                let (address, connections) =
+                    (server_state.address.clone(), server_state.connections);
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: connections,
+                    backlog: Some(connections as i32),
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| BacklogServerAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| BacklogServerAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| BacklogServerAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| BacklogServerAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| BacklogServerAction::ListenerCloseEvent { listener }),
+                });
+            }
+            BacklogServerAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            BacklogServerAction::InitListenerSuccess { listener } => {
+                let server_state: &mut BacklogServerState = state.substate_mut();
+
+                server_state.status = BacklogServerStatus::Listening;
+                server_state.listener = Some(listener);
+            }
+            BacklogServerAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            BacklogServerAction::ListenerCloseEvent { .. } => (),
+            BacklogServerAction::ConnectionEvent { connection, .. } => state
+                .substate_mut::<BacklogServerState>()
+                .accepted
+                .push(connection),
+            BacklogServerAction::CloseEvent { connection, .. } => state
+                .substate_mut::<BacklogServerState>()
+                .closed
+                .push(connection),
+        }
+    }
+}
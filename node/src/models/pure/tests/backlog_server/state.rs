@@ -0,0 +1,31 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BacklogServerStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct BacklogServerState {
+    pub status: BacklogServerStatus,
+    pub address: String,
+    // The listener's backlog, poll events capacity and `max_connections`.
+    pub connections: usize,
+    pub listener: Option<Uid>,
+    pub accepted: Vec<Uid>,
+    pub closed: Vec<Uid>,
+}
+
+impl BacklogServerState {
+    pub fn new(address: String, connections: usize) -> Self {
+        Self {
+            status: BacklogServerStatus::Init,
+            address,
+            connections,
+            listener: None,
+            accepted: Vec::new(),
+            closed: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::tcp::action::BytesAvailableResult,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "217b8931-0043-4c8e-b525-ffbd8180354f"]
+pub enum BytesAvailableAction {
+    Tick,
+    PollSuccess {
+        uid: Uid,
+    },
+    PollError {
+        uid: Uid,
+        error: String,
+    },
+    InitSuccess {
+        instance: Uid,
+    },
+    InitError {
+        instance: Uid,
+        error: String,
+    },
+    InitListenerSuccess {
+        listener: Uid,
+    },
+    InitListenerError {
+        listener: Uid,
+        error: String,
+    },
+    ListenerCloseEvent {
+        listener: Uid,
+    },
+    ConnectionEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    CloseEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    ConnectSuccess {
+        connection: Uid,
+    },
+    ConnectTimeout {
+        connection: Uid,
+    },
+    ConnectError {
+        connection: Uid,
+        error: String,
+    },
+    ConnectClose {
+        connection: Uid,
+    },
+    SendSuccess {
+        uid: Uid,
+    },
+    SendTimeout {
+        uid: Uid,
+    },
+    SendError {
+        uid: Uid,
+        error: String,
+    },
+    BytesAvailable {
+        connection: Uid,
+        result: BytesAvailableResult,
+    },
+}
+
+impl Action for BytesAvailableAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,205 @@
+use super::{
+    action::BytesAvailableAction,
+    state::{BytesAvailableState, BytesAvailableStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::{
+                action::{BytesAvailableResult, ConnectionEvent, TcpAction},
+                state::TcpState,
+            },
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `BytesAvailableState` model connects to its own listener and sends
+// `data` to the server. Once the server end is readable, it queries the bytes
+// available on it with `TcpAction::BytesAvailable`, before any recv.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for BytesAvailableState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for BytesAvailableState {
+    type Action = BytesAvailableAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            BytesAvailableAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<BytesAvailableState>().status == BytesAvailableStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| BytesAvailableAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| BytesAvailableAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| BytesAvailableAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| BytesAvailableAction::PollError { uid, error }),
+                    })
+                }
+            }
+            BytesAvailableAction::PollSuccess { .. } => {
+                let BytesAvailableState {
+                    server_connection: Some(connection),
+                    sent: true,
+                    querying: false,
+                    ..
+                } = state.substate()
+                else {
+                    return;
+                };
+
+                let connection = *connection;
+                let conn = state.substate::<TcpState>().get_connection(&connection);
+
+                // Wait until the data sent by the client is readable.
+                if let Some(ConnectionEvent::Ready { can_recv: true, .. }) = conn.events {
+                    state.substate_mut::<BytesAvailableState>().querying = true;
+                    dispatcher.dispatch(TcpAction::BytesAvailable {
+                        connection,
+                        on_result: callback!(|(connection: Uid, result: BytesAvailableResult)| BytesAvailableAction::BytesAvailable { connection, result }),
+                    });
+                }
+            }
+            BytesAvailableAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            BytesAvailableAction::InitSuccess { .. } => {
+                let address = state.substate::<BytesAvailableState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| BytesAvailableAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| BytesAvailableAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| BytesAvailableAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| BytesAvailableAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| BytesAvailableAction::ListenerCloseEvent { listener }),
+                });
+            }
+            BytesAvailableAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            BytesAvailableAction::InitListenerSuccess { .. } => {
+                let available_state: &mut BytesAvailableState = state.substate_mut();
+                let address = available_state.address.clone();
+
+                available_state.status = BytesAvailableStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| BytesAvailableAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| BytesAvailableAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| BytesAvailableAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| BytesAvailableAction::ConnectClose { connection }),
+                });
+            }
+            BytesAvailableAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            BytesAvailableAction::ConnectionEvent { connection, .. } => {
+                state
+                    .substate_mut::<BytesAvailableState>()
+                    .server_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            BytesAvailableAction::ConnectSuccess { connection } => {
+                state
+                    .substate_mut::<BytesAvailableState>()
+                    .client_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            BytesAvailableAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            BytesAvailableAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            // The server queries the bytes available once its end is readable.
+            BytesAvailableAction::SendSuccess { .. } => {
+                state.substate_mut::<BytesAvailableState>().sent = true
+            }
+            BytesAvailableAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            BytesAvailableAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            BytesAvailableAction::BytesAvailable { connection, result } => {
+                let available_state: &mut BytesAvailableState = state.substate_mut();
+
+                assert_eq!(Some(connection), available_state.server_connection);
+                available_state.bytes_available = Some(result)
+            }
+            BytesAvailableAction::ListenerCloseEvent { .. }
+            | BytesAvailableAction::CloseEvent { .. }
+            | BytesAvailableAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sends `data` to the server once the connection is established on both ends.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let BytesAvailableState {
+        data,
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let (connection, data) = (*connection, data.clone());
+
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid: state.new_uid(),
+        connection,
+        data: data.into(),
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|uid: Uid| BytesAvailableAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| BytesAvailableAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| BytesAvailableAction::SendError { uid, error }),
+    });
+}
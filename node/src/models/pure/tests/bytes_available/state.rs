@@ -0,0 +1,35 @@
+use crate::{automaton::state::Uid, models::pure::net::tcp::action::BytesAvailableResult};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BytesAvailableStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct BytesAvailableState {
+    pub status: BytesAvailableStatus,
+    pub address: String,
+    // Sent to the server, whose end is queried once it's readable.
+    pub data: Vec<u8>,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    pub sent: bool,
+    pub querying: bool,
+    pub bytes_available: Option<BytesAvailableResult>,
+}
+
+impl BytesAvailableState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: BytesAvailableStatus::Init,
+            address,
+            data,
+            client_connection: None,
+            server_connection: None,
+            sent: false,
+            querying: false,
+            bytes_available: None,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::tcp::action::{RecvResult, SendResult, TcpPollEvents},
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "d73f6efe-510a-41df-8fbe-90b70a9dc7a6"]
+pub enum CancelClientAction {
+    Tick,
+    PollSuccess { uid: Uid, events: TcpPollEvents },
+    PollError { uid: Uid, error: String },
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    ConnectSuccess { connection: Uid },
+    ConnectTimeout { connection: Uid },
+    ConnectError { connection: Uid, error: String },
+    CloseEvent { connection: Uid },
+    // Dispatched after the requests to cancel: `TcpClientState` has handed
+    // them to `TcpState` once it's processed.
+    Cancel { recv: Uid, send: Uid },
+    SendSuccess { uid: Uid },
+    SendTimeout { uid: Uid },
+    SendError { uid: Uid, error: String },
+    RecvSuccess { uid: Uid, data: Vec<u8> },
+    RecvTimeout { uid: Uid, partial_data: Vec<u8> },
+    RecvError { uid: Uid, error: String },
+    RecvCancelled { uid: Uid, result: RecvResult },
+    SendCancelled { uid: Uid, result: SendResult },
+}
+
+impl Action for CancelClientAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,200 @@
+use super::{
+    action::CancelClientAction,
+    state::{CancelClientState, CancelClientStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::{
+                action::{RecvResult, SendResult, TcpAction, TcpPollEvents},
+                state::TcpState,
+            },
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+        },
+        time::model::update_time,
+    },
+};
+use std::rc::Rc;
+
+// The `CancelClientState` model connects, then cancels a recv, and a send
+// queued behind the send of its data. Cancelled requests are reported once:
+// cancelling them again is a no-op. The requests are issued through
+// `TcpClientState`, which is told about the cancellation and drops them.
+
+// This model depends on `TcpClientState`.
+impl RegisterModel for CancelClientState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpClientState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for CancelClientState {
+    type Action = CancelClientAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            CancelClientAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<CancelClientState>().status == CancelClientStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| CancelClientAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| CancelClientAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpClientAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|(uid: Uid, events: TcpPollEvents)| CancelClientAction::PollSuccess { uid, events }),
+                        on_error: callback!(|(uid: Uid, error: String)| CancelClientAction::PollError { uid, error }),
+                    })
+                }
+            }
+            CancelClientAction::PollSuccess { .. } => (),
+            CancelClientAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            CancelClientAction::InitSuccess { .. } => {
+                let client_state: &mut CancelClientState = state.substate_mut();
+                let address = client_state.address.clone();
+
+                client_state.status = CancelClientStatus::Ready;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| CancelClientAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| CancelClientAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| CancelClientAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| CancelClientAction::CloseEvent { connection }),
+                });
+            }
+            CancelClientAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            CancelClientAction::ConnectSuccess { connection } => {
+                let client_state: &mut CancelClientState = state.substate_mut();
+                let data: Rc<[u8]> = client_state.data.clone().into();
+
+                client_state.connection = Some(connection);
+
+                let recv = state.new_uid();
+                let (first, second) = (state.new_uid(), state.new_uid());
+
+                dispatcher.dispatch(TcpClientAction::Recv {
+                    uid: recv,
+                    connection,
+                    count: data.len(),
+                    timeout: Timeout::Never,
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| CancelClientAction::RecvSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| CancelClientAction::RecvTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| CancelClientAction::RecvError { uid, error }),
+                });
+
+                // The second send is queued behind the first one, it is
+                // cancelled before being written.
+                for uid in [first, second] {
+                    dispatcher.dispatch(TcpClientAction::Send {
+                        uid,
+                        connection,
+                        data: data.clone(),
+                        timeout: Timeout::Millis(1000),
+                        on_success: callback!(|uid: Uid| CancelClientAction::SendSuccess { uid }),
+                        on_timeout: callback!(|uid: Uid| CancelClientAction::SendTimeout { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| CancelClientAction::SendError { uid, error }),
+                    });
+                }
+
+                dispatcher.dispatch(CancelClientAction::Cancel { recv, send: second });
+            }
+            CancelClientAction::Cancel { recv, send } => {
+                dispatcher.dispatch(TcpAction::CancelRecv {
+                    uid: recv,
+                    on_result: callback!(|(uid: Uid, result: RecvResult)| CancelClientAction::RecvCancelled { uid, result }),
+                });
+                dispatcher.dispatch(TcpAction::CancelSend {
+                    uid: send,
+                    on_result: callback!(|(uid: Uid, result: SendResult)| CancelClientAction::SendCancelled { uid, result }),
+                });
+            }
+            CancelClientAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            CancelClientAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            CancelClientAction::CloseEvent { connection } => {
+                panic!("Connection {:?} closed", connection)
+            }
+            CancelClientAction::SendSuccess { uid } => {
+                state.substate_mut::<CancelClientState>().sent.push(uid)
+            }
+            CancelClientAction::SendTimeout { uid } => {
+                panic!("Send {:?} timed out", uid)
+            }
+            CancelClientAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            CancelClientAction::RecvSuccess { uid, data } => {
+                panic!("Cancelled recv {:?} completed: {:?}", uid, data)
+            }
+            CancelClientAction::RecvTimeout { uid, partial_data } => {
+                panic!("Cancelled recv {:?} timed out: {:?}", uid, partial_data)
+            }
+            CancelClientAction::RecvError { uid, error } => {
+                panic!("Cancelled recv {:?} failed: {}", uid, error)
+            }
+            CancelClientAction::RecvCancelled { uid, result } => {
+                assert_eq!(result, RecvResult::Cancelled);
+                assert!(!state.substate::<TcpState>().has_recv_request(&uid));
+                // `TcpClientState` was told first.
+                assert!(!state
+                    .substate::<TcpClientState>()
+                    .recv_requests
+                    .contains_key(&uid));
+                cancelled(state, uid);
+                // Already cancelled: a no-op.
+                dispatcher.dispatch(TcpAction::CancelRecv {
+                    uid,
+                    on_result: callback!(|(uid: Uid, result: RecvResult)| CancelClientAction::RecvCancelled { uid, result }),
+                });
+            }
+            CancelClientAction::SendCancelled { uid, result } => {
+                assert_eq!(result, SendResult::Cancelled);
+                assert!(!state.substate::<TcpState>().has_send_request(&uid));
+                assert!(!state
+                    .substate::<TcpClientState>()
+                    .send_requests
+                    .contains_key(&uid));
+                cancelled(state, uid);
+                dispatcher.dispatch(TcpAction::CancelSend {
+                    uid,
+                    on_result: callback!(|(uid: Uid, result: SendResult)| CancelClientAction::SendCancelled { uid, result }),
+                });
+            }
+        }
+    }
+}
+
+// Records a request reported as cancelled, which happens only once.
+fn cancelled<Substate: ModelState>(state: &mut State<Substate>, uid: Uid) {
+    let client_state: &mut CancelClientState = state.substate_mut();
+
+    assert!(!client_state.cancelled.contains(&uid));
+    client_state.cancelled.push(uid);
+}
@@ -0,0 +1,31 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CancelClientStatus {
+    Init,
+    Ready,
+}
+
+#[derive(Debug)]
+pub struct CancelClientState {
+    pub status: CancelClientStatus,
+    pub address: String,
+    pub data: Vec<u8>,
+    pub connection: Option<Uid>,
+    // The sends that completed, and the requests reported as cancelled.
+    pub sent: Vec<Uid>,
+    pub cancelled: Vec<Uid>,
+}
+
+impl CancelClientState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: CancelClientStatus::Init,
+            address,
+            data,
+            connection: None,
+            sent: Vec::new(),
+            cancelled: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::automaton::{
+    action::{Action, ActionKind},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "df0cf748-79f4-4e44-9221-6194e1f60010"]
+pub enum CloseAllAction {
+    Tick,
+    PollSuccess { uid: Uid },
+    PollError { uid: Uid, error: String },
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    InitListenerSuccess { listener: Uid },
+    InitListenerError { listener: Uid, error: String },
+    ListenerCloseEvent { listener: Uid },
+    ConnectionEvent { listener: Uid, connection: Uid },
+    CloseEvent { listener: Uid, connection: Uid },
+    ConnectSuccess { connection: Uid },
+    ConnectTimeout { connection: Uid },
+    ConnectError { connection: Uid, error: String },
+    ConnectClose { connection: Uid },
+    CloseAllComplete { listener: Uid },
+}
+
+impl Action for CloseAllAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,162 @@
+use super::{
+    action::CloseAllAction,
+    state::{CloseAllState, CloseAllStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `CloseAllState` model connects to its own listener three times, then
+// closes all the server connections at once with `TcpServerAction::CloseAll`.
+// The listener keeps accepting connections: it connects once more.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for CloseAllState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for CloseAllState {
+    type Action = CloseAllAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            CloseAllAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<CloseAllState>().status == CloseAllStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| CloseAllAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| CloseAllAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| CloseAllAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| CloseAllAction::PollError { uid, error }),
+                    })
+                }
+            }
+            CloseAllAction::PollSuccess { .. } => (),
+            CloseAllAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            CloseAllAction::InitSuccess { .. } => {
+                let address = state.substate::<CloseAllState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    // Three connections, then a new one once they are closed.
+                    max_connections: 3,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| CloseAllAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| CloseAllAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| CloseAllAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| CloseAllAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| CloseAllAction::ListenerCloseEvent { listener }),
+                });
+            }
+            CloseAllAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            CloseAllAction::InitListenerSuccess { listener } => {
+                let close_all_state: &mut CloseAllState = state.substate_mut();
+
+                close_all_state.listener = Some(listener);
+                close_all_state.status = CloseAllStatus::Listening;
+                connect(state, dispatcher)
+            }
+            CloseAllAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            CloseAllAction::ConnectionEvent { connection, .. } => {
+                let close_all_state: &mut CloseAllState = state.substate_mut();
+
+                close_all_state.server_connections.push(connection);
+
+                // Accepted after the others were closed.
+                if close_all_state.close_all_complete {
+                    return;
+                }
+
+                if close_all_state.server_connections.len() < 3 {
+                    connect(state, dispatcher)
+                } else {
+                    dispatcher.dispatch(TcpServerAction::CloseAll {
+                        listener: close_all_state.listener.unwrap(),
+                        on_complete: callback!(|listener: Uid| CloseAllAction::CloseAllComplete { listener }),
+                    })
+                }
+            }
+            CloseAllAction::CloseEvent { connection, .. } => state
+                .substate_mut::<CloseAllState>()
+                .closed_connections
+                .push(connection),
+            CloseAllAction::CloseAllComplete { listener } => {
+                let server_state: &TcpServerState = state.substate();
+
+                // The listener is still open, without connections.
+                assert!(server_state.get_listener(&listener).connections.is_empty());
+                state.substate_mut::<CloseAllState>().close_all_complete = true;
+                connect(state, dispatcher)
+            }
+            CloseAllAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            CloseAllAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            CloseAllAction::ListenerCloseEvent { .. }
+            | CloseAllAction::ConnectSuccess { .. }
+            | CloseAllAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+fn connect<Substate: ModelState>(state: &mut State<Substate>, dispatcher: &mut Dispatcher) {
+    let address = state.substate::<CloseAllState>().address.clone();
+
+    dispatcher.dispatch(TcpClientAction::Connect {
+        connection: state.new_uid(),
+        address,
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|connection: Uid| CloseAllAction::ConnectSuccess { connection }),
+        on_timeout: callback!(|connection: Uid| CloseAllAction::ConnectTimeout { connection }),
+        on_error: callback!(|(connection: Uid, error: String)| CloseAllAction::ConnectError { connection, error }),
+        on_close: callback!(|connection: Uid| CloseAllAction::ConnectClose { connection }),
+    });
+}
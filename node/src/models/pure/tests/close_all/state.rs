@@ -0,0 +1,31 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CloseAllStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct CloseAllState {
+    pub status: CloseAllStatus,
+    pub address: String,
+    pub listener: Option<Uid>,
+    // All the server connections, and the closed ones.
+    pub server_connections: Vec<Uid>,
+    pub closed_connections: Vec<Uid>,
+    pub close_all_complete: bool,
+}
+
+impl CloseAllState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: CloseAllStatus::Init,
+            address,
+            listener: None,
+            server_connections: Vec::new(),
+            closed_connections: Vec::new(),
+            close_all_complete: false,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use super::{
+    action::CloseDeliverBufferedAction,
+    state::{CloseDeliverBufferedState, CloseDeliverBufferedStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::{action::TcpAction, state::TcpState},
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `CloseDeliverBufferedState` model connects to its own listener and
+// sends `data` to the server, whose recv request waits for twice as much.
+// Once the data is buffered, it closes the server connection with
+// `deliver_buffered`: the partially received data must be handed to the
+// recv request before the connection is removed.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for CloseDeliverBufferedState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for CloseDeliverBufferedState {
+    type Action = CloseDeliverBufferedAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            CloseDeliverBufferedAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<CloseDeliverBufferedState>().status
+                    == CloseDeliverBufferedStatus::Init
+                {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| CloseDeliverBufferedAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| CloseDeliverBufferedAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| CloseDeliverBufferedAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| CloseDeliverBufferedAction::PollError { uid, error }),
+                    })
+                }
+            }
+            CloseDeliverBufferedAction::PollSuccess { .. } => {
+                let CloseDeliverBufferedState {
+                    data,
+                    server_connection: Some(connection),
+                    recv: Some(uid),
+                    closing: false,
+                    ..
+                } = state.substate()
+                else {
+                    return;
+                };
+
+                let connection = *connection;
+                // Close once all the data sent by the client is buffered.
+                let buffered_len = state
+                    .substate::<TcpState>()
+                    .get_recv_request(uid)
+                    .buffered_data
+                    .len();
+
+                if buffered_len == data.len() {
+                    state.substate_mut::<CloseDeliverBufferedState>().closing = true;
+                    dispatcher.dispatch(TcpServerAction::Close {
+                        connection,
+                        deliver_buffered: true,
+                    });
+                }
+            }
+            CloseDeliverBufferedAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            CloseDeliverBufferedAction::InitSuccess { .. } => {
+                let address = state
+                    .substate::<CloseDeliverBufferedState>()
+                    .address
+                    .clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| CloseDeliverBufferedAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| CloseDeliverBufferedAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| CloseDeliverBufferedAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| CloseDeliverBufferedAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| CloseDeliverBufferedAction::ListenerCloseEvent { listener }),
+                });
+            }
+            CloseDeliverBufferedAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            CloseDeliverBufferedAction::InitListenerSuccess { .. } => {
+                let deliver_state: &mut CloseDeliverBufferedState = state.substate_mut();
+                let address = deliver_state.address.clone();
+
+                deliver_state.status = CloseDeliverBufferedStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| CloseDeliverBufferedAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| CloseDeliverBufferedAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| CloseDeliverBufferedAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| CloseDeliverBufferedAction::ConnectClose { connection }),
+                });
+            }
+            CloseDeliverBufferedAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            CloseDeliverBufferedAction::ConnectionEvent { connection, .. } => {
+                state
+                    .substate_mut::<CloseDeliverBufferedState>()
+                    .server_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            CloseDeliverBufferedAction::ConnectSuccess { connection } => {
+                state
+                    .substate_mut::<CloseDeliverBufferedState>()
+                    .client_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            CloseDeliverBufferedAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            CloseDeliverBufferedAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            CloseDeliverBufferedAction::SendSuccess { .. } => {
+                let uid = state.new_uid();
+                let deliver_state: &mut CloseDeliverBufferedState = state.substate_mut();
+
+                deliver_state.recv = Some(uid);
+                // Never completes: the client sends only half of `count` bytes.
+                dispatcher.dispatch(TcpServerAction::Recv {
+                    uid,
+                    connection: deliver_state.server_connection.unwrap(),
+                    count: 2 * deliver_state.data.len(),
+                    timeout: Timeout::Never,
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| CloseDeliverBufferedAction::RecvSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| CloseDeliverBufferedAction::RecvTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| CloseDeliverBufferedAction::RecvError { uid, error }),
+                });
+            }
+            CloseDeliverBufferedAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            CloseDeliverBufferedAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            CloseDeliverBufferedAction::RecvSuccess { uid, data } => {
+                panic!("Recv {:?} unexpectedly completed: {:?}", uid, data)
+            }
+            CloseDeliverBufferedAction::RecvTimeout { uid, partial_data } => {
+                let deliver_state: &mut CloseDeliverBufferedState = state.substate_mut();
+                let connection = deliver_state.server_connection.unwrap();
+
+                if !deliver_state.closing {
+                    panic!("Recv {:?} timeout: {:?}", uid, partial_data)
+                }
+
+                deliver_state.delivered_data = Some(partial_data);
+                // The partial data must be delivered before the connection is gone.
+                assert!(state.substate::<TcpState>().has_connection(&connection));
+            }
+            CloseDeliverBufferedAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            CloseDeliverBufferedAction::CloseEvent { connection, .. } => {
+                let deliver_state: &mut CloseDeliverBufferedState = state.substate_mut();
+
+                assert_eq!(Some(connection), deliver_state.server_connection);
+                assert_eq!(
+                    deliver_state.delivered_data.as_ref(),
+                    Some(&deliver_state.data)
+                );
+                deliver_state.closed = true;
+            }
+            CloseDeliverBufferedAction::ListenerCloseEvent { .. }
+            | CloseDeliverBufferedAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sends `data` to the server once the connection is established on both ends.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let CloseDeliverBufferedState {
+        data,
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let (connection, data) = (*connection, data.clone());
+
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid: state.new_uid(),
+        connection,
+        data: data.into(),
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|uid: Uid| CloseDeliverBufferedAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| CloseDeliverBufferedAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| CloseDeliverBufferedAction::SendError { uid, error }),
+    });
+}
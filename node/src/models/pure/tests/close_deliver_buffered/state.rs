@@ -0,0 +1,39 @@
+use crate::automaton::state::Uid;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum CloseDeliverBufferedStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CloseDeliverBufferedState {
+    pub status: CloseDeliverBufferedStatus,
+    pub address: String,
+    // Sent by the client, half of what the server's recv request waits for.
+    pub data: Vec<u8>,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    pub recv: Option<Uid>,
+    pub closing: bool,
+    // The partial data handed to the recv request on close.
+    pub delivered_data: Option<Vec<u8>>,
+    pub closed: bool,
+}
+
+impl CloseDeliverBufferedState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: CloseDeliverBufferedStatus::Init,
+            address,
+            data,
+            client_connection: None,
+            server_connection: None,
+            recv: None,
+            closing: false,
+            delivered_data: None,
+            closed: false,
+        }
+    }
+}
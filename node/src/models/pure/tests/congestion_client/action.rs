@@ -0,0 +1,51 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::tcp::action::TcpPollEvents,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "e2357be3-b052-431a-b965-4baa09537fa9"]
+pub enum CongestionClientAction {
+    Tick,
+    PollSuccess {
+        uid: Uid,
+        events: TcpPollEvents,
+    },
+    PollError {
+        uid: Uid,
+        error: String,
+    },
+    InitSuccess {
+        instance: Uid,
+    },
+    InitError {
+        instance: Uid,
+        error: String,
+    },
+    ConnectSuccess {
+        connection: Uid,
+    },
+    ConnectTimeout {
+        connection: Uid,
+    },
+    ConnectError {
+        connection: Uid,
+        error: String,
+    },
+    CloseEvent {
+        connection: Uid,
+    },
+    Congestion {
+        connection: Uid,
+        result: Result<String, String>,
+    },
+}
+
+impl Action for CongestionClientAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
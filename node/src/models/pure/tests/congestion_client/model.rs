@@ -0,0 +1,106 @@
+use super::{
+    action::CongestionClientAction,
+    state::{CongestionClientState, CongestionClientStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::{TcpAction, TcpPollEvents},
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+        },
+        time::model::update_time,
+    },
+};
+
+// The `CongestionClientState` model connects, then queries the congestion
+// control algorithm of its connection with `TcpAction::GetCongestion`.
+
+// This model depends on `TcpClientState`.
+impl RegisterModel for CongestionClientState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpClientState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for CongestionClientState {
+    type Action = CongestionClientAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            CongestionClientAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<CongestionClientState>().status == CongestionClientStatus::Init
+                {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| CongestionClientAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| CongestionClientAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpClientAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|(uid: Uid, events: TcpPollEvents)| CongestionClientAction::PollSuccess { uid, events }),
+                        on_error: callback!(|(uid: Uid, error: String)| CongestionClientAction::PollError { uid, error }),
+                    })
+                }
+            }
+            CongestionClientAction::PollSuccess { .. } => (),
+            CongestionClientAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            CongestionClientAction::InitSuccess { .. } => {
+                let client_state: &mut CongestionClientState = state.substate_mut();
+                let address = client_state.address.clone();
+
+                client_state.status = CongestionClientStatus::Ready;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| CongestionClientAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| CongestionClientAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| CongestionClientAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| CongestionClientAction::CloseEvent { connection }),
+                });
+            }
+            CongestionClientAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            CongestionClientAction::ConnectSuccess { connection } => {
+                state.substate_mut::<CongestionClientState>().connection = Some(connection);
+                dispatcher.dispatch(TcpAction::GetCongestion {
+                    connection,
+                    on_result: callback!(|(connection: Uid, result: Result<String, String>)| CongestionClientAction::Congestion { connection, result }),
+                });
+            }
+            CongestionClientAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            CongestionClientAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            CongestionClientAction::CloseEvent { connection } => {
+                panic!("Connection {:?} closed", connection)
+            }
+            CongestionClientAction::Congestion { result, .. } => {
+                state.substate_mut::<CongestionClientState>().algorithm = Some(result)
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CongestionClientStatus {
+    Init,
+    Ready,
+}
+
+#[derive(Debug)]
+pub struct CongestionClientState {
+    pub status: CongestionClientStatus,
+    pub address: String,
+    pub connection: Option<Uid>,
+    // The result of the congestion control query.
+    pub algorithm: Option<Result<String, String>>,
+}
+
+impl CongestionClientState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: CongestionClientStatus::Init,
+            address,
+            connection: None,
+            algorithm: None,
+        }
+    }
+}
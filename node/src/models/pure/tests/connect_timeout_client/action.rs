@@ -0,0 +1,27 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::tcp::action::TcpPollEvents,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "1eaaaa3e-f207-4690-8684-fc26cbd5190b"]
+pub enum ConnectTimeoutClientAction {
+    Tick,
+    PollSuccess { uid: Uid, events: TcpPollEvents },
+    PollError { uid: Uid, error: String },
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    ConnectSuccess { connection: Uid },
+    ConnectTimeout { connection: Uid },
+    ConnectError { connection: Uid, error: String },
+    CloseEvent { connection: Uid },
+}
+
+impl Action for ConnectTimeoutClientAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
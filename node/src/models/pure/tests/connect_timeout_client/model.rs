@@ -0,0 +1,115 @@
+use super::{
+    action::ConnectTimeoutClientAction,
+    state::{ConnectTimeoutClientState, ConnectTimeoutClientStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::{
+                action::{TcpAction, TcpPollEvents},
+                state::{ConnectionStatus, TcpState},
+            },
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+        },
+        time::model::update_time,
+    },
+};
+
+// The `ConnectTimeoutClientState` model connects with a zero timeout: the
+// connection times out at the first poll, then `TcpState` closes it.
+
+// This model depends on `TcpClientState`.
+impl RegisterModel for ConnectTimeoutClientState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpClientState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for ConnectTimeoutClientState {
+    type Action = ConnectTimeoutClientAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ConnectTimeoutClientAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                let client_state: &ConnectTimeoutClientState = state.substate();
+
+                if client_state.status == ConnectTimeoutClientStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| ConnectTimeoutClientAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| ConnectTimeoutClientAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpClientAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|(uid: Uid, events: TcpPollEvents)| ConnectTimeoutClientAction::PollSuccess { uid, events }),
+                        on_error: callback!(|(uid: Uid, error: String)| ConnectTimeoutClientAction::PollError { uid, error }),
+                    })
+                }
+            }
+            ConnectTimeoutClientAction::PollSuccess { .. } => {
+                let Some(connection) = state.substate::<ConnectTimeoutClientState>().timed_out
+                else {
+                    return;
+                };
+                let tcp_state: &TcpState = state.substate();
+
+                // Until it's removed, the connection is being closed.
+                if tcp_state.has_connection(&connection) {
+                    assert!(matches!(
+                        tcp_state.get_connection(&connection).status,
+                        ConnectionStatus::CloseRequestInternal
+                    ));
+                }
+            }
+            ConnectTimeoutClientAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            ConnectTimeoutClientAction::InitSuccess { .. } => {
+                let client_state: &mut ConnectTimeoutClientState = state.substate_mut();
+                let address = client_state.address.clone();
+
+                client_state.status = ConnectTimeoutClientStatus::Ready;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(0),
+                    on_success: callback!(|connection: Uid| ConnectTimeoutClientAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| ConnectTimeoutClientAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| ConnectTimeoutClientAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| ConnectTimeoutClientAction::CloseEvent { connection }),
+                });
+            }
+            ConnectTimeoutClientAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            ConnectTimeoutClientAction::ConnectSuccess { connection } => {
+                panic!("Connection {:?} established", connection)
+            }
+            ConnectTimeoutClientAction::ConnectTimeout { connection } => {
+                state.substate_mut::<ConnectTimeoutClientState>().timed_out = Some(connection)
+            }
+            ConnectTimeoutClientAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            ConnectTimeoutClientAction::CloseEvent { .. } => (),
+        }
+    }
+}
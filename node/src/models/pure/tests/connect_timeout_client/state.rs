@@ -0,0 +1,25 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConnectTimeoutClientStatus {
+    Init,
+    Ready,
+}
+
+#[derive(Debug)]
+pub struct ConnectTimeoutClientState {
+    pub status: ConnectTimeoutClientStatus,
+    pub address: String,
+    // The connection reported as timed out.
+    pub timed_out: Option<Uid>,
+}
+
+impl ConnectTimeoutClientState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: ConnectTimeoutClientStatus::Init,
+            address,
+            timed_out: None,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use super::{
+    action::ConnectionAddrsAction,
+    state::{ConnectionAddrsState, ConnectionAddrsStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `ConnectionAddrsState` model connects to its own listener and keeps
+// both ends of the connection, whose addresses are recorded by `TcpState`.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for ConnectionAddrsState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for ConnectionAddrsState {
+    type Action = ConnectionAddrsAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ConnectionAddrsAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<ConnectionAddrsState>().status == ConnectionAddrsStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| ConnectionAddrsAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| ConnectionAddrsAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| ConnectionAddrsAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| ConnectionAddrsAction::PollError { uid, error }),
+                    })
+                }
+            }
+            ConnectionAddrsAction::PollSuccess { .. } => (),
+            ConnectionAddrsAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            ConnectionAddrsAction::InitSuccess { .. } => {
+                let address = state.substate::<ConnectionAddrsState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| ConnectionAddrsAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| ConnectionAddrsAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| ConnectionAddrsAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| ConnectionAddrsAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| ConnectionAddrsAction::ListenerCloseEvent { listener }),
+                });
+            }
+            ConnectionAddrsAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            ConnectionAddrsAction::InitListenerSuccess { .. } => {
+                let addrs_state: &mut ConnectionAddrsState = state.substate_mut();
+                let address = addrs_state.address.clone();
+
+                addrs_state.status = ConnectionAddrsStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| ConnectionAddrsAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| ConnectionAddrsAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| ConnectionAddrsAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| ConnectionAddrsAction::ConnectClose { connection }),
+                });
+            }
+            ConnectionAddrsAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            ConnectionAddrsAction::ConnectionEvent { connection, .. } => {
+                state
+                    .substate_mut::<ConnectionAddrsState>()
+                    .server_connection = Some(connection)
+            }
+            ConnectionAddrsAction::ConnectSuccess { connection } => {
+                state
+                    .substate_mut::<ConnectionAddrsState>()
+                    .client_connection = Some(connection)
+            }
+            ConnectionAddrsAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            ConnectionAddrsAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            ConnectionAddrsAction::ListenerCloseEvent { .. }
+            | ConnectionAddrsAction::CloseEvent { .. }
+            | ConnectionAddrsAction::ConnectClose { .. } => (),
+        }
+    }
+}
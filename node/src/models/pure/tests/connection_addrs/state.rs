@@ -0,0 +1,26 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConnectionAddrsStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct ConnectionAddrsState {
+    pub status: ConnectionAddrsStatus,
+    pub address: String,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+}
+
+impl ConnectionAddrsState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: ConnectionAddrsStatus::Init,
+            address,
+            client_connection: None,
+            server_connection: None,
+        }
+    }
+}
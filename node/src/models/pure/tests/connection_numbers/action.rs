@@ -0,0 +1,68 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::tcp_server::action::ConnectionLifecycleEvent,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "724d2d72-e606-4afb-8f49-133f989e2490"]
+pub enum ConnectionNumbersAction {
+    Tick,
+    PollSuccess {
+        uid: Uid,
+    },
+    PollError {
+        uid: Uid,
+        error: String,
+    },
+    InitSuccess {
+        instance: Uid,
+    },
+    InitError {
+        instance: Uid,
+        error: String,
+    },
+    InitListenerSuccess {
+        listener: Uid,
+    },
+    InitListenerError {
+        listener: Uid,
+        error: String,
+    },
+    ListenerCloseEvent {
+        listener: Uid,
+    },
+    ConnectionEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    CloseEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    ConnectSuccess {
+        connection: Uid,
+    },
+    ConnectTimeout {
+        connection: Uid,
+    },
+    ConnectError {
+        connection: Uid,
+        error: String,
+    },
+    ConnectClose {
+        connection: Uid,
+    },
+    LifecycleEvent {
+        connection: Uid,
+        event: ConnectionLifecycleEvent,
+    },
+}
+
+impl Action for ConnectionNumbersAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,149 @@
+use super::{
+    action::ConnectionNumbersAction,
+    state::{ConnectionNumbersState, ConnectionNumbersStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{ConnectionLifecycleEvent, RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `ConnectionNumbersState` model connects three times to its own
+// listener, one connection after the other, and records the lifecycle events
+// of the server connections: the listener numbers them 1, 2, 3 in accept
+// order, whatever their `Uid`.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for ConnectionNumbersState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for ConnectionNumbersState {
+    type Action = ConnectionNumbersAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ConnectionNumbersAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<ConnectionNumbersState>().status
+                    == ConnectionNumbersStatus::Init
+                {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| ConnectionNumbersAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| ConnectionNumbersAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| ConnectionNumbersAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| ConnectionNumbersAction::PollError { uid, error }),
+                    })
+                }
+            }
+            ConnectionNumbersAction::PollSuccess { .. } => (),
+            ConnectionNumbersAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            ConnectionNumbersAction::InitSuccess { .. } => {
+                let address = state.substate::<ConnectionNumbersState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::Subscribe {
+                    on_event: callback!(|(connection: Uid, event: ConnectionLifecycleEvent)| ConnectionNumbersAction::LifecycleEvent { connection, event }),
+                });
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 3,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| ConnectionNumbersAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| ConnectionNumbersAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| ConnectionNumbersAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| ConnectionNumbersAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| ConnectionNumbersAction::ListenerCloseEvent { listener }),
+                });
+            }
+            ConnectionNumbersAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            ConnectionNumbersAction::InitListenerSuccess { .. } => {
+                state.substate_mut::<ConnectionNumbersState>().status =
+                    ConnectionNumbersStatus::Listening;
+                connect(state, dispatcher)
+            }
+            ConnectionNumbersAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            ConnectionNumbersAction::ConnectionEvent { connection, .. } => {
+                let numbers_state: &mut ConnectionNumbersState = state.substate_mut();
+
+                numbers_state.server_connections.push(connection);
+
+                // The next connection once this one was accepted.
+                if numbers_state.server_connections.len() < 3 {
+                    connect(state, dispatcher)
+                }
+            }
+            ConnectionNumbersAction::ConnectSuccess { .. } => (),
+            ConnectionNumbersAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            ConnectionNumbersAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            ConnectionNumbersAction::LifecycleEvent { connection, event } => state
+                .substate_mut::<ConnectionNumbersState>()
+                .lifecycle_events
+                .push((connection, event)),
+            ConnectionNumbersAction::ListenerCloseEvent { .. }
+            | ConnectionNumbersAction::CloseEvent { .. }
+            | ConnectionNumbersAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+fn connect<Substate: ModelState>(state: &mut State<Substate>, dispatcher: &mut Dispatcher) {
+    let address = state.substate::<ConnectionNumbersState>().address.clone();
+
+    dispatcher.dispatch(TcpClientAction::Connect {
+        connection: state.new_uid(),
+        address,
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|connection: Uid| ConnectionNumbersAction::ConnectSuccess { connection }),
+        on_timeout: callback!(|connection: Uid| ConnectionNumbersAction::ConnectTimeout { connection }),
+        on_error: callback!(|(connection: Uid, error: String)| ConnectionNumbersAction::ConnectError { connection, error }),
+        on_close: callback!(|connection: Uid| ConnectionNumbersAction::ConnectClose { connection }),
+    });
+}
@@ -0,0 +1,29 @@
+use crate::{
+    automaton::state::Uid, models::pure::net::tcp_server::action::ConnectionLifecycleEvent,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConnectionNumbersStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct ConnectionNumbersState {
+    pub status: ConnectionNumbersStatus,
+    pub address: String,
+    // The server connections, in accept order.
+    pub server_connections: Vec<Uid>,
+    pub lifecycle_events: Vec<(Uid, ConnectionLifecycleEvent)>,
+}
+
+impl ConnectionNumbersState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: ConnectionNumbersStatus::Init,
+            address,
+            server_connections: Vec::new(),
+            lifecycle_events: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use super::{
+    action::DrainOnCloseAction,
+    state::{DrainOnCloseState, DrainOnCloseStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::{
+                action::{ConnectionEvent, TcpAction},
+                state::TcpState,
+            },
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `DrainOnCloseState` model connects to its own listener, sends `data` to
+// the server and closes the client connection right away. The server only
+// receives once it has seen the closure, asking for more than was sent: the
+// data buffered before the closure must still be delivered.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for DrainOnCloseState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for DrainOnCloseState {
+    type Action = DrainOnCloseAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            DrainOnCloseAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<DrainOnCloseState>().status == DrainOnCloseStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| DrainOnCloseAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| DrainOnCloseAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| DrainOnCloseAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| DrainOnCloseAction::PollError { uid, error }),
+                    })
+                }
+            }
+            DrainOnCloseAction::PollSuccess { .. } => {
+                let DrainOnCloseState {
+                    data,
+                    server_connection: Some(connection),
+                    closing: true,
+                    draining: false,
+                    ..
+                } = state.substate()
+                else {
+                    return;
+                };
+
+                let connection = *connection;
+                // More than was sent: the recv completes with what was drained.
+                let count = 2 * data.len();
+                let conn = state.substate::<TcpState>().get_connection(&connection);
+
+                // Wait until the server end has seen the closure.
+                if conn.events == Some(ConnectionEvent::Closed) {
+                    state.substate_mut::<DrainOnCloseState>().draining = true;
+                    dispatcher.dispatch(TcpServerAction::Recv {
+                        uid: state.new_uid(),
+                        connection,
+                        count,
+                        timeout: Timeout::Millis(1000),
+                        on_success: callback!(|(uid: Uid, data: Vec<u8>)| DrainOnCloseAction::RecvSuccess { uid, data }),
+                        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| DrainOnCloseAction::RecvTimeout { uid, partial_data }),
+                        on_error: callback!(|(uid: Uid, error: String)| DrainOnCloseAction::RecvError { uid, error }),
+                    });
+                }
+            }
+            DrainOnCloseAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            DrainOnCloseAction::InitSuccess { .. } => {
+                let address = state.substate::<DrainOnCloseState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| DrainOnCloseAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| DrainOnCloseAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| DrainOnCloseAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| DrainOnCloseAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| DrainOnCloseAction::ListenerCloseEvent { listener }),
+                });
+            }
+            DrainOnCloseAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            DrainOnCloseAction::InitListenerSuccess { .. } => {
+                let drain_state: &mut DrainOnCloseState = state.substate_mut();
+                let address = drain_state.address.clone();
+
+                drain_state.status = DrainOnCloseStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| DrainOnCloseAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| DrainOnCloseAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| DrainOnCloseAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| DrainOnCloseAction::ConnectClose { connection }),
+                });
+            }
+            DrainOnCloseAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            DrainOnCloseAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<DrainOnCloseState>().server_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            DrainOnCloseAction::ConnectSuccess { connection } => {
+                state.substate_mut::<DrainOnCloseState>().client_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            DrainOnCloseAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            DrainOnCloseAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            DrainOnCloseAction::SendSuccess { .. } => {
+                let drain_state: &mut DrainOnCloseState = state.substate_mut();
+
+                // The server receives once it has seen the closure.
+                drain_state.closing = true;
+                dispatcher.dispatch(TcpClientAction::Close {
+                    connection: drain_state.client_connection.unwrap(),
+                    deliver_buffered: false,
+                });
+            }
+            DrainOnCloseAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            DrainOnCloseAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            DrainOnCloseAction::RecvSuccess { data, .. } => {
+                state.substate_mut::<DrainOnCloseState>().received = Some(data)
+            }
+            DrainOnCloseAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timeout: {:?}", uid, partial_data)
+            }
+            DrainOnCloseAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            DrainOnCloseAction::ListenerCloseEvent { .. }
+            | DrainOnCloseAction::CloseEvent { .. }
+            | DrainOnCloseAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sends `data` to the server once the connection is established on both ends.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let DrainOnCloseState {
+        data,
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let (connection, data) = (*connection, data.clone());
+
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid: state.new_uid(),
+        connection,
+        data: data.into(),
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|uid: Uid| DrainOnCloseAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| DrainOnCloseAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| DrainOnCloseAction::SendError { uid, error }),
+    });
+}
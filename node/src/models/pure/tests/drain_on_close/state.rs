@@ -0,0 +1,36 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DrainOnCloseStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct DrainOnCloseState {
+    pub status: DrainOnCloseStatus,
+    pub address: String,
+    // Sent by the client right before it closes the connection.
+    pub data: Vec<u8>,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    pub closing: bool,
+    pub draining: bool,
+    // Received by the server once it has seen the closure.
+    pub received: Option<Vec<u8>>,
+}
+
+impl DrainOnCloseState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: DrainOnCloseStatus::Init,
+            address,
+            data,
+            client_connection: None,
+            server_connection: None,
+            closing: false,
+            draining: false,
+            received: None,
+        }
+    }
+}
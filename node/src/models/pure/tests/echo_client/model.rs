@@ -1,6 +1,6 @@
 use super::{
     action::EchoClientAction,
-    state::{EchoClientState, EchoClientStatus},
+    state::{DataMismatch, EchoClientState, EchoClientStatus},
 };
 use crate::{
     automaton::{
@@ -22,7 +22,7 @@ use crate::{
 };
 use core::panic;
 use log::{info, warn};
-use rand::{Rng, RngCore};
+use rand::Rng;
 
 // The `EchoClientState` acts as a simulated echo client, used for testing the
 // functionality of the state-machine and its related models (`TcpClientState`,
@@ -47,9 +47,9 @@ use rand::{Rng, RngCore};
 //   `max_connection_attempts` attempts to reconnect.
 //   If this limit is exceeded, the client panics.
 //
-// - For each poll result the client sends random data to the echo server.
-//   The size and content of this data are randomly generated using the
-//   `PRNGState` model.
+// - For each poll result the client sends data to the echo server. Its size
+//   is randomly generated using the `PRNGState` model, and its content
+//   follows the configured `PayloadPattern` (random bytes by default).
 //
 // - After sending data, the client dispatches a receive action to read the
 //   server's response. A random timeout is generated using the `PRNGState`
@@ -96,6 +96,7 @@ impl PureModel for EchoClientState {
                         // Init TCP model
                         dispatcher.dispatch(TcpAction::Init {
                             instance: state.new_uid(),
+                            events_capacity: None,
                             on_success: callback!(|instance: Uid| EchoClientAction::InitSuccess { instance }),
                             on_error: callback!(|(instance: Uid, error: String)| EchoClientAction::InitError { instance, error }),
                         })
@@ -203,21 +204,24 @@ impl PureModel for EchoClientState {
                 connect(client_state, new_connection_uid, dispatcher);
             }
             EchoClientAction::PollSuccess { .. } => {
-                // Send random data on every poll if there are no pending send/recv requests.
+                // Send data on every poll if there are no pending send/recv requests.
                 if let EchoClientState {
                     status: EchoClientStatus::Connected { connection },
-                    config: EchoClientConfig { max_send_size, .. },
+                    config:
+                        EchoClientConfig {
+                            max_send_size,
+                            payload,
+                            ..
+                        },
                     ..
                 } = state.substate()
                 {
                     let connection = *connection;
                     let max_send_size = *max_send_size;
+                    let payload = payload.clone();
                     let request = state.new_uid();
                     let prng: &mut PRNGState = state.substate_mut();
-                    let random_size = prng.rng.gen_range(1..max_send_size) as usize;
-                    let mut data: Vec<u8> = vec![0; random_size];
-
-                    prng.rng.fill_bytes(&mut data[..]);
+                    let data = payload.generate(&mut prng.rng, max_send_size);
 
                     state.substate_mut::<EchoClientState>().status = EchoClientStatus::Sending {
                         connection,
@@ -304,7 +308,10 @@ impl PureModel for EchoClientState {
                         "|ECHO_CLIENT| send {:?} timeout to connection {:?}",
                         uid, connection
                     );
-                    dispatcher.dispatch(TcpClientAction::Close { connection })
+                    dispatcher.dispatch(TcpClientAction::Close {
+                        connection,
+                        deliver_buffered: false,
+                    })
                 } else {
                     unreachable!()
                 }
@@ -319,6 +326,7 @@ impl PureModel for EchoClientState {
                         "|ECHO_CLIENT| send {:?} to connection {:?} error: {}",
                         uid, connection, error
                     );
+                    state.substate_mut::<EchoClientState>().last_error = Some(error);
                 } else {
                     unreachable!()
                 }
@@ -337,17 +345,23 @@ impl PureModel for EchoClientState {
                     assert_eq!(uid, *request);
                     let connection = *connection;
 
-                    if *sent_data != data {
-                        panic!("Data mismatch: {:?} != {:?}", sent_data, data)
-                    }
+                    let mismatch = DataMismatch::find(sent_data, &data);
+                    let client_state = state.substate_mut::<EchoClientState>();
 
-                    state.substate_mut::<EchoClientState>().status =
-                        EchoClientStatus::Connected { connection };
+                    client_state.status = EchoClientStatus::Connected { connection };
 
-                    info!(
-                        "|ECHO_CLIENT| recv {:?} from connection {:?}, data matches.",
-                        uid, connection
-                    );
+                    if let Some(mismatch) = mismatch {
+                        warn!(
+                            "|ECHO_CLIENT| recv {:?} from connection {:?}: {}",
+                            uid, connection, mismatch
+                        );
+                        (client_state.on_mismatch)(&mismatch);
+                    } else {
+                        info!(
+                            "|ECHO_CLIENT| recv {:?} from connection {:?}, data matches.",
+                            uid, connection
+                        );
+                    }
                 } else {
                     unreachable!()
                 }
@@ -370,7 +384,10 @@ impl PureModel for EchoClientState {
                         "|ECHO_CLIENT| recv {:?} timeout from connection {:?}",
                         uid, connection
                     );
-                    dispatcher.dispatch(TcpClientAction::Close { connection })
+                    dispatcher.dispatch(TcpClientAction::Close {
+                        connection,
+                        deliver_buffered: false,
+                    })
                 } else {
                     unreachable!()
                 }
@@ -393,6 +410,7 @@ impl PureModel for EchoClientState {
                         "|ECHO_CLIENT| recv {:?} from connection {:?} error: {}",
                         uid, connection, error
                     );
+                    state.substate_mut::<EchoClientState>().last_error = Some(error);
                 } else {
                     unreachable!()
                 }
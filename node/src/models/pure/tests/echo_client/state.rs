@@ -1,4 +1,6 @@
 use crate::automaton::{action::Timeout, state::Uid};
+use rand::{Rng, RngCore};
+use std::fmt;
 
 #[derive(Debug)]
 pub struct EchoClientConfig {
@@ -10,6 +12,48 @@ pub struct EchoClientConfig {
     pub max_send_size: u64,
     pub min_rnd_timeout: u64,
     pub max_rnd_timeout: u64,
+    pub payload: PayloadPattern,
+}
+
+// Content of the data sent on each poll. The deterministic patterns make a
+// mismatch easier to read than random bytes.
+#[derive(Debug, Clone)]
+pub enum PayloadPattern {
+    Random,
+    // 0, 1, 2, ..., wrapping at 255: each byte is its offset (modulo 256).
+    Sequential,
+    // The pattern repeated (non-empty).
+    Repeat(Vec<u8>),
+    Zeros,
+    // Exactly these bytes on every send, regardless of `max_send_size`.
+    Bytes(Vec<u8>),
+}
+
+impl PayloadPattern {
+    // Except for `Bytes`, the size is random, between 1 and `max_send_size`.
+    pub fn generate(&self, rng: &mut impl RngCore, max_send_size: u64) -> Vec<u8> {
+        if let PayloadPattern::Bytes(bytes) = self {
+            return bytes.clone();
+        }
+
+        let size = rng.gen_range(1..max_send_size) as usize;
+
+        match self {
+            PayloadPattern::Random => {
+                let mut data: Vec<u8> = vec![0; size];
+
+                rng.fill_bytes(&mut data[..]);
+                data
+            }
+            PayloadPattern::Sequential => (0..size).map(|offset| offset as u8).collect(),
+            PayloadPattern::Repeat(pattern) => {
+                assert!(!pattern.is_empty(), "Empty payload pattern");
+                pattern.iter().copied().cycle().take(size).collect()
+            }
+            PayloadPattern::Zeros => vec![0; size],
+            PayloadPattern::Bytes(_) => unreachable!(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -31,11 +75,65 @@ pub enum EchoClientStatus {
     },
 }
 
+// Where the echoed data first differs from the sent data. A `None` byte means
+// that buffer ended before `index`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataMismatch {
+    pub index: usize,
+    pub expected: Option<u8>,
+    pub actual: Option<u8>,
+    pub expected_len: usize,
+    pub actual_len: usize,
+}
+
+impl DataMismatch {
+    // Returns `None` if both buffers are equal.
+    pub fn find(expected: &[u8], actual: &[u8]) -> Option<Self> {
+        let index = expected
+            .iter()
+            .zip(actual)
+            .position(|(expected, actual)| expected != actual)
+            .unwrap_or(expected.len().min(actual.len()));
+
+        if index == expected.len() && index == actual.len() {
+            return None;
+        }
+
+        Some(Self {
+            index,
+            expected: expected.get(index).copied(),
+            actual: actual.get(index).copied(),
+            expected_len: expected.len(),
+            actual_len: actual.len(),
+        })
+    }
+}
+
+impl fmt::Display for DataMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Data mismatch at byte {}: expected {:?}, got {:?} (sent {} bytes, received {})",
+            self.index, self.expected, self.actual, self.expected_len, self.actual_len
+        )
+    }
+}
+
+// Default `on_mismatch` handler.
+pub fn panic_on_mismatch(mismatch: &DataMismatch) {
+    panic!("{}", mismatch)
+}
+
 #[derive(Debug)]
 pub struct EchoClientState {
     pub status: EchoClientStatus,
     pub connection_attempt: usize,
+    // Last send or recv error reported to the client.
+    pub last_error: Option<String>,
     pub config: EchoClientConfig,
+    // Called when the echoed data doesn't match what was sent. If it returns,
+    // the client carries on with the next send.
+    pub on_mismatch: fn(&DataMismatch),
 }
 
 impl EchoClientState {
@@ -43,7 +141,9 @@ impl EchoClientState {
         Self {
             status: EchoClientStatus::Init,
             connection_attempt: 0,
+            last_error: None,
             config,
+            on_mismatch: panic_on_mismatch,
         }
     }
 }
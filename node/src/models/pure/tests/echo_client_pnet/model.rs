@@ -13,13 +13,13 @@ use crate::{
             tcp::action::{TcpAction, TcpPollEvents},
         },
         prng::state::PRNGState,
-        tests::echo_client::state::{EchoClientConfig, EchoClientStatus},
+        tests::echo_client::state::{DataMismatch, EchoClientConfig, EchoClientStatus},
         time::model::update_time,
     },
 };
 use core::panic;
 use log::{info, warn};
-use rand::{Rng, RngCore};
+use rand::Rng;
 
 impl RegisterModel for PnetEchoClientState {
     fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
@@ -57,6 +57,7 @@ impl PureModel for PnetEchoClientState {
                         // Init TCP model
                         dispatcher.dispatch(TcpAction::Init {
                             instance: state.new_uid(),
+                            events_capacity: None,
                             on_success: callback!(|instance: Uid| PnetEchoClientAction::InitSuccess { instance }),
                             on_error: callback!(|(instance: Uid, error: String)| PnetEchoClientAction::InitError { instance, error }),
                         })
@@ -164,21 +165,24 @@ impl PureModel for PnetEchoClientState {
                 connect(client_state, new_connection_uid, dispatcher);
             }
             PnetEchoClientAction::PollSuccess { .. } => {
-                // Send random data on every poll if there are no pending send/recv requests.
+                // Send data on every poll if there are no pending send/recv requests.
                 if let PnetEchoClientState {
                     status: EchoClientStatus::Connected { connection },
-                    config: EchoClientConfig { max_send_size, .. },
+                    config:
+                        EchoClientConfig {
+                            max_send_size,
+                            payload,
+                            ..
+                        },
                     ..
                 } = state.substate()
                 {
                     let connection = *connection;
                     let max_send_size = *max_send_size;
+                    let payload = payload.clone();
                     let request = state.new_uid();
                     let prng: &mut PRNGState = state.substate_mut();
-                    let random_size = prng.rng.gen_range(1..max_send_size) as usize;
-                    let mut data: Vec<u8> = vec![0; random_size];
-
-                    prng.rng.fill_bytes(&mut data[..]);
+                    let data = payload.generate(&mut prng.rng, max_send_size);
 
                     state.substate_mut::<PnetEchoClientState>().status =
                         EchoClientStatus::Sending {
@@ -299,17 +303,23 @@ impl PureModel for PnetEchoClientState {
                     assert_eq!(uid, *request);
                     let connection = *connection;
 
-                    if *sent_data != data {
-                        panic!("Data mismatch: {:?} != {:?}", sent_data, data)
-                    }
+                    let mismatch = DataMismatch::find(sent_data, &data);
+                    let client_state = state.substate_mut::<PnetEchoClientState>();
 
-                    state.substate_mut::<PnetEchoClientState>().status =
-                        EchoClientStatus::Connected { connection };
+                    client_state.status = EchoClientStatus::Connected { connection };
 
-                    info!(
-                        "|PNET_ECHO_CLIENT| recv {:?} from connection {:?}, data matches.",
-                        uid, connection
-                    );
+                    if let Some(mismatch) = mismatch {
+                        warn!(
+                            "|PNET_ECHO_CLIENT| recv {:?} from connection {:?}: {}",
+                            uid, connection, mismatch
+                        );
+                        (client_state.on_mismatch)(&mismatch);
+                    } else {
+                        info!(
+                            "|PNET_ECHO_CLIENT| recv {:?} from connection {:?}, data matches.",
+                            uid, connection
+                        );
+                    }
                 } else {
                     unreachable!()
                 }
@@ -1,10 +1,14 @@
-use crate::models::pure::tests::echo_client::state::{EchoClientConfig, EchoClientStatus};
+use crate::models::pure::tests::echo_client::state::{
+    panic_on_mismatch, DataMismatch, EchoClientConfig, EchoClientStatus,
+};
 
 #[derive(Debug)]
 pub struct PnetEchoClientState {
     pub status: EchoClientStatus,
     pub connection_attempt: usize,
     pub config: EchoClientConfig,
+    // See `EchoClientState::on_mismatch`.
+    pub on_mismatch: fn(&DataMismatch),
 }
 
 impl PnetEchoClientState {
@@ -13,6 +17,7 @@ impl PnetEchoClientState {
             status: EchoClientStatus::Init,
             connection_attempt: 0,
             config,
+            on_mismatch: panic_on_mismatch,
         }
     }
 }
@@ -13,7 +13,10 @@ use crate::{
     models::pure::{
         net::{
             tcp::action::TcpAction,
-            tcp_server::{action::TcpServerAction, state::TcpServerState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
         },
         tests::echo_server::state::Connection,
         time::model::update_time,
@@ -85,6 +88,7 @@ impl PureModel for EchoServerState {
                         // Init TCP model
                         dispatcher.dispatch(TcpAction::Init {
                             instance: state.new_uid(),
+                            events_capacity: None,
                             on_success: callback!(|instance: Uid| EchoServerAction::InitSuccess { instance }),
                             on_error: callback!(|(instance: Uid, error: String)| EchoServerAction::InitError { instance, error }),
                         })
@@ -115,6 +119,10 @@ impl PureModel for EchoServerState {
                     listener: state.new_uid(),
                     address,
                     max_connections,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
                     on_success: callback!(|listener: Uid| EchoServerAction::InitListenerSuccess { listener }),
                     on_error: callback!(|(listener: Uid, error: String)| EchoServerAction::InitListenerError { listener, error }),
                     on_new_connection: callback!(|(listener: Uid, connection: Uid)| EchoServerAction::ConnectionEvent { listener, connection }),
@@ -153,10 +161,13 @@ impl PureModel for EchoServerState {
             EchoServerAction::PollSuccess { .. } => {
                 let server_state: &EchoServerState = state.substate();
                 let timeout = Timeout::Millis(server_state.config.recv_timeout);
-                let count = 1024;
 
                 for connection in server_state.connections_ready_to_recv() {
                     let uid = state.new_uid();
+                    let count = state
+                        .substate::<TcpServerState>()
+                        .recv_size(&connection)
+                        .unwrap_or(1024);
 
                     info!(
                         "|ECHO_SERVER| dispatching recv request {:?} ({} bytes), connection {:?}, timeout {:?}",
@@ -226,7 +237,10 @@ impl PureModel for EchoServerState {
                         .get_connection_mut(&connection) = Connection::Sending { request };
                 } else {
                     // if we didn't receive anything in the time span close the connection
-                    dispatcher.dispatch(TcpServerAction::Close { connection });
+                    dispatcher.dispatch(TcpServerAction::Close {
+                        connection,
+                        deliver_buffered: false,
+                    });
                     warn!("|ECHO_SERVER| recv {:?} timeout", uid)
                 }
             }
@@ -245,7 +259,10 @@ impl PureModel for EchoServerState {
                     .substate_mut::<EchoServerState>()
                     .find_connection_uid_by_send_uid(uid);
 
-                dispatcher.dispatch(TcpServerAction::Close { connection });
+                dispatcher.dispatch(TcpServerAction::Close {
+                    connection,
+                    deliver_buffered: false,
+                });
                 warn!("|ECHO_SERVER| send {:?} timeout", uid)
             }
             EchoServerAction::SendError { uid, error } => {
@@ -49,6 +49,7 @@ impl PureModel for PnetEchoServerState {
                         // Init TCP model
                         dispatcher.dispatch(TcpAction::Init {
                             instance: state.new_uid(),
+                            events_capacity: None,
                             on_success: callback!(|instance: Uid| PnetEchoServerAction::InitSuccess { instance }),
                             on_error: callback!(|(instance: Uid, error: String)| PnetEchoServerAction::InitError { instance, error }),
                         })
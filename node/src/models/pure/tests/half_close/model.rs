@@ -0,0 +1,268 @@
+use super::{
+    action::HalfCloseAction,
+    state::{HalfCloseState, HalfCloseStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::{
+        effectful::mio::action::ShutdownHow,
+        pure::{
+            net::{
+                tcp::action::TcpAction,
+                tcp_client::{action::TcpClientAction, state::TcpClientState},
+                tcp_server::{
+                    action::{RoutingPolicy, TcpServerAction},
+                    state::TcpServerState,
+                },
+            },
+            time::model::update_time,
+        },
+    },
+};
+
+// The `HalfCloseState` model connects to its own listener and sends `request`
+// to the server, which answers with `response`. It then shuts down the
+// client's write side: a new send fails, while the response can still be
+// received.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for HalfCloseState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for HalfCloseState {
+    type Action = HalfCloseAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            HalfCloseAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<HalfCloseState>().status == HalfCloseStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| HalfCloseAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| HalfCloseAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| HalfCloseAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| HalfCloseAction::PollError { uid, error }),
+                    })
+                }
+            }
+            HalfCloseAction::PollSuccess { .. } => (),
+            HalfCloseAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            HalfCloseAction::InitSuccess { .. } => {
+                let address = state.substate::<HalfCloseState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| HalfCloseAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| HalfCloseAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| HalfCloseAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| HalfCloseAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| HalfCloseAction::ListenerCloseEvent { listener }),
+                });
+            }
+            HalfCloseAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            HalfCloseAction::InitListenerSuccess { .. } => {
+                let half_close_state: &mut HalfCloseState = state.substate_mut();
+                let address = half_close_state.address.clone();
+
+                half_close_state.status = HalfCloseStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| HalfCloseAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| HalfCloseAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| HalfCloseAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| HalfCloseAction::ConnectClose { connection }),
+                });
+            }
+            HalfCloseAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            HalfCloseAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<HalfCloseState>().server_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            HalfCloseAction::ConnectSuccess { connection } => {
+                state.substate_mut::<HalfCloseState>().client_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            HalfCloseAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            HalfCloseAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            HalfCloseAction::SendSuccess { .. } => {
+                let half_close_state: &HalfCloseState = state.substate();
+
+                if !half_close_state.responding {
+                    let connection = half_close_state.server_connection.unwrap();
+                    let count = half_close_state.request.len();
+
+                    dispatcher.dispatch(TcpServerAction::Recv {
+                        uid: state.new_uid(),
+                        connection,
+                        count,
+                        timeout: Timeout::Millis(1000),
+                        on_success: callback!(|(uid: Uid, data: Vec<u8>)| HalfCloseAction::RecvSuccess { uid, data }),
+                        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| HalfCloseAction::RecvTimeout { uid, partial_data }),
+                        on_error: callback!(|(uid: Uid, error: String)| HalfCloseAction::RecvError { uid, error }),
+                    });
+                } else {
+                    // The response was sent, shut down the client's write side.
+                    dispatcher.dispatch(TcpAction::Shutdown {
+                        connection: half_close_state.client_connection.unwrap(),
+                        how: ShutdownHow::Write,
+                        on_success: callback!(|connection: Uid| HalfCloseAction::ShutdownSuccess { connection }),
+                        on_error: callback!(|(connection: Uid, error: String)| HalfCloseAction::ShutdownError { connection, error }),
+                    });
+                }
+            }
+            HalfCloseAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            HalfCloseAction::SendError { uid, error } => {
+                let half_close_state: &mut HalfCloseState = state.substate_mut();
+
+                // Sending after the client's write side was shut down.
+                if half_close_state.send_error.is_none() {
+                    half_close_state.send_error = Some(error);
+                } else {
+                    panic!("Send {:?} failed: {}", uid, error)
+                }
+            }
+            HalfCloseAction::RecvSuccess { data, .. } => {
+                let half_close_state: &mut HalfCloseState = state.substate_mut();
+
+                if half_close_state.responding {
+                    half_close_state.received = Some(data);
+                    return;
+                }
+
+                let (connection, response) = (
+                    half_close_state.server_connection.unwrap(),
+                    half_close_state.response.clone(),
+                );
+
+                assert_eq!(data, half_close_state.request);
+                half_close_state.responding = true;
+                dispatcher.dispatch(TcpServerAction::Send {
+                    uid: state.new_uid(),
+                    connection,
+                    data: response.into(),
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|uid: Uid| HalfCloseAction::SendSuccess { uid }),
+                    on_timeout: callback!(|uid: Uid| HalfCloseAction::SendTimeout { uid }),
+                    on_error: callback!(|(uid: Uid, error: String)| HalfCloseAction::SendError { uid, error }),
+                });
+            }
+            HalfCloseAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timeout: {:?}", uid, partial_data)
+            }
+            HalfCloseAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            HalfCloseAction::ShutdownSuccess { connection } => {
+                let half_close_state: &HalfCloseState = state.substate();
+                let (request, count) = (
+                    half_close_state.request.clone(),
+                    half_close_state.response.len(),
+                );
+
+                // Fails right away, the response is still received. Sent with
+                // `TcpState` directly, as `TcpClientState` closes the connection
+                // on send errors.
+                dispatcher.dispatch(TcpAction::Send {
+                    uid: state.new_uid(),
+                    connection,
+                    data: request.into(),
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|uid: Uid| HalfCloseAction::SendSuccess { uid }),
+                    on_timeout: callback!(|uid: Uid| HalfCloseAction::SendTimeout { uid }),
+                    on_error: callback!(|(uid: Uid, error: String)| HalfCloseAction::SendError { uid, error }),
+                    on_cancelled: None,
+                });
+                dispatcher.dispatch(TcpClientAction::Recv {
+                    uid: state.new_uid(),
+                    connection,
+                    count,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| HalfCloseAction::RecvSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| HalfCloseAction::RecvTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| HalfCloseAction::RecvError { uid, error }),
+                });
+            }
+            HalfCloseAction::ShutdownError { connection, error } => {
+                panic!("Shutdown {:?} failed: {}", connection, error)
+            }
+            HalfCloseAction::ListenerCloseEvent { .. }
+            | HalfCloseAction::CloseEvent { .. }
+            | HalfCloseAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sends `request` to the server once the connection is established on both
+// ends.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let HalfCloseState {
+        request,
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let (connection, request) = (*connection, request.clone());
+
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid: state.new_uid(),
+        connection,
+        data: request.into(),
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|uid: Uid| HalfCloseAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| HalfCloseAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| HalfCloseAction::SendError { uid, error }),
+    });
+}
@@ -0,0 +1,39 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HalfCloseStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct HalfCloseState {
+    pub status: HalfCloseStatus,
+    pub address: String,
+    // Sent by the client, and answered by the server with `response`.
+    pub request: Vec<u8>,
+    pub response: Vec<u8>,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    pub responding: bool,
+    // Sending after the client's write side was shut down.
+    pub send_error: Option<String>,
+    // Received by the client once its write side was shut down.
+    pub received: Option<Vec<u8>>,
+}
+
+impl HalfCloseState {
+    pub fn new(address: String, request: Vec<u8>, response: Vec<u8>) -> Self {
+        Self {
+            status: HalfCloseStatus::Init,
+            address,
+            request,
+            response,
+            client_connection: None,
+            server_connection: None,
+            responding: false,
+            send_error: None,
+            received: None,
+        }
+    }
+}
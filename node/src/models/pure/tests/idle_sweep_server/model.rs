@@ -0,0 +1,146 @@
+use super::{
+    action::IdleSweepServerAction,
+    state::{IdleSweepServerState, IdleSweepServerStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::{get_current_time, update_time},
+    },
+};
+
+// The `IdleSweepServerState` model dispatches `TcpServerAction::SweepIdle` on
+// every poll. It receives `count` bytes on the connection it accepts, which
+// is closed once idle for more than `max_idle_ms`.
+
+// This model depends on `TcpServerState`.
+impl RegisterModel for IdleSweepServerState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpServerState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for IdleSweepServerState {
+    type Action = IdleSweepServerAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            IdleSweepServerAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<IdleSweepServerState>().status == IdleSweepServerStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| IdleSweepServerAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| IdleSweepServerAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(50),
+                        on_success: callback!(|uid: Uid| IdleSweepServerAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| IdleSweepServerAction::PollError { uid, error }),
+                    })
+                }
+            }
+            IdleSweepServerAction::PollSuccess { .. } => {
+                let max_idle_ms = state.substate::<IdleSweepServerState>().max_idle_ms;
+
+                dispatcher.dispatch(TcpServerAction::SweepIdle { max_idle_ms })
+            }
+            IdleSweepServerAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            IdleSweepServerAction::InitSuccess { .. } => {
+                let address = state.substate::<IdleSweepServerState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| IdleSweepServerAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| IdleSweepServerAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| IdleSweepServerAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| IdleSweepServerAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| IdleSweepServerAction::ListenerCloseEvent { listener }),
+                });
+            }
+            IdleSweepServerAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            IdleSweepServerAction::InitListenerSuccess { .. } => {
+                state.substate_mut::<IdleSweepServerState>().status =
+                    IdleSweepServerStatus::Listening
+            }
+            IdleSweepServerAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            IdleSweepServerAction::ListenerCloseEvent { .. } => (),
+            IdleSweepServerAction::ConnectionEvent { connection, .. } => {
+                let server_state: &mut IdleSweepServerState = state.substate_mut();
+                let count = server_state.count;
+
+                server_state.connection = Some(connection);
+                dispatcher.dispatch(TcpServerAction::Recv {
+                    uid: state.new_uid(),
+                    connection,
+                    count,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| IdleSweepServerAction::RecvSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| IdleSweepServerAction::RecvTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| IdleSweepServerAction::RecvError { uid, error }),
+                });
+            }
+            IdleSweepServerAction::CloseEvent { connection, .. } => {
+                let current_time = get_current_time(state);
+
+                state.substate_mut::<IdleSweepServerState>().closed =
+                    Some((connection, current_time))
+            }
+            IdleSweepServerAction::RecvSuccess { data, .. } => {
+                let connection = state.substate::<IdleSweepServerState>().connection.unwrap();
+                let last_activity = state
+                    .substate::<TcpServerState>()
+                    .connection_last_activity(&connection);
+
+                // The recv was the connection's last activity.
+                assert_eq!(last_activity, Some(get_current_time(state)));
+
+                let server_state: &mut IdleSweepServerState = state.substate_mut();
+
+                server_state.received = Some(data);
+                server_state.last_activity = last_activity;
+            }
+            IdleSweepServerAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timed out: {:?}", uid, partial_data)
+            }
+            IdleSweepServerAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
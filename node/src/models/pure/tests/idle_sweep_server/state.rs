@@ -0,0 +1,36 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum IdleSweepServerStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct IdleSweepServerState {
+    pub status: IdleSweepServerStatus,
+    pub address: String,
+    pub count: usize,
+    pub max_idle_ms: u64,
+    pub connection: Option<Uid>,
+    pub received: Option<Vec<u8>>,
+    // Last activity of the connection once the data was received.
+    pub last_activity: Option<u128>,
+    // (connection, time) of the closure.
+    pub closed: Option<(Uid, u128)>,
+}
+
+impl IdleSweepServerState {
+    pub fn new(address: String, count: usize, max_idle_ms: u64) -> Self {
+        Self {
+            status: IdleSweepServerStatus::Init,
+            address,
+            count,
+            max_idle_ms,
+            connection: None,
+            received: None,
+            last_activity: None,
+            closed: None,
+        }
+    }
+}
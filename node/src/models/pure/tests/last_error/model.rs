@@ -0,0 +1,208 @@
+use super::{
+    action::LastErrorAction,
+    state::{LastErrorState, LastErrorStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::{
+                action::{ConnectionEvent, TcpAction},
+                state::TcpState,
+            },
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `LastErrorState` model connects to its own listener and closes the
+// client connection right away. The server's recv fails once it has seen the
+// closure, which closes the server connection: the error that caused the
+// closure can still be queried from the close notification.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for LastErrorState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for LastErrorState {
+    type Action = LastErrorAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            LastErrorAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<LastErrorState>().status == LastErrorStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| LastErrorAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| LastErrorAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| LastErrorAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| LastErrorAction::PollError { uid, error }),
+                    })
+                }
+            }
+            LastErrorAction::PollSuccess { .. } => {
+                let LastErrorState {
+                    server_connection: Some(connection),
+                    closing: true,
+                    receiving: false,
+                    ..
+                } = state.substate()
+                else {
+                    return;
+                };
+
+                let connection = *connection;
+                let conn = state.substate::<TcpState>().get_connection(&connection);
+
+                // Wait until the server end has seen the closure.
+                if conn.events == Some(ConnectionEvent::Closed) {
+                    state.substate_mut::<LastErrorState>().receiving = true;
+                    dispatcher.dispatch(TcpServerAction::Recv {
+                        uid: state.new_uid(),
+                        connection,
+                        count: 1,
+                        timeout: Timeout::Millis(1000),
+                        on_success: callback!(|(uid: Uid, data: Vec<u8>)| LastErrorAction::RecvSuccess { uid, data }),
+                        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| LastErrorAction::RecvTimeout { uid, partial_data }),
+                        on_error: callback!(|(uid: Uid, error: String)| LastErrorAction::RecvError { uid, error }),
+                    });
+                }
+            }
+            LastErrorAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            LastErrorAction::InitSuccess { .. } => {
+                let address = state.substate::<LastErrorState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| LastErrorAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| LastErrorAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| LastErrorAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| LastErrorAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| LastErrorAction::ListenerCloseEvent { listener }),
+                });
+            }
+            LastErrorAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            LastErrorAction::InitListenerSuccess { .. } => {
+                let error_state: &mut LastErrorState = state.substate_mut();
+                let address = error_state.address.clone();
+
+                error_state.status = LastErrorStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| LastErrorAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| LastErrorAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| LastErrorAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| LastErrorAction::ConnectClose { connection }),
+                });
+            }
+            LastErrorAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            LastErrorAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<LastErrorState>().server_connection = Some(connection);
+                close_when_connected(state, dispatcher)
+            }
+            LastErrorAction::ConnectSuccess { connection } => {
+                state.substate_mut::<LastErrorState>().client_connection = Some(connection);
+                close_when_connected(state, dispatcher)
+            }
+            LastErrorAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            LastErrorAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            LastErrorAction::RecvSuccess { uid, data } => {
+                panic!("Recv {:?} unexpectedly completed: {:?}", uid, data)
+            }
+            LastErrorAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timeout: {:?}", uid, partial_data)
+            }
+            LastErrorAction::RecvError { error, .. } => {
+                state.substate_mut::<LastErrorState>().recv_error = Some(error)
+            }
+            LastErrorAction::CloseEvent { connection, .. } => {
+                let error_state: &LastErrorState = state.substate();
+
+                assert_eq!(Some(connection), error_state.server_connection);
+
+                // The connection is already removed, but the error that
+                // caused its closure is still known.
+                let last_error = state
+                    .substate::<TcpState>()
+                    .connection_last_error(&connection)
+                    .expect("last error not recorded");
+
+                assert_eq!(last_error, "Connection closed");
+                // Reported as is, without the connection's history.
+                assert_eq!(error_state.recv_error.as_deref(), Some(last_error));
+                state.substate_mut::<LastErrorState>().closed = true;
+            }
+            LastErrorAction::ListenerCloseEvent { .. } | LastErrorAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Closes the client connection once the connection is established on both
+// ends.
+fn close_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let error_state: &mut LastErrorState = state.substate_mut();
+
+    let (Some(connection), Some(_)) =
+        (error_state.client_connection, error_state.server_connection)
+    else {
+        return;
+    };
+
+    error_state.closing = true;
+    dispatcher.dispatch(TcpClientAction::Close {
+        connection,
+        deliver_buffered: false,
+    });
+}
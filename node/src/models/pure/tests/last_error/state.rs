@@ -0,0 +1,36 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LastErrorStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct LastErrorState {
+    pub status: LastErrorStatus,
+    pub address: String,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    pub closing: bool,
+    pub receiving: bool,
+    // The error of the server's recv, once it has seen the closure.
+    pub recv_error: Option<String>,
+    // The server connection was closed (following the recv error).
+    pub closed: bool,
+}
+
+impl LastErrorState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: LastErrorStatus::Init,
+            address,
+            client_connection: None,
+            server_connection: None,
+            closing: false,
+            receiving: false,
+            recv_error: None,
+            closed: false,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::tcp_server::action::ConnectionLifecycleEvent,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "3208f35c-8839-4a17-82ce-6ba43a0ad11a"]
+pub enum LifecycleEventsAction {
+    Tick,
+    PollSuccess {
+        uid: Uid,
+    },
+    PollError {
+        uid: Uid,
+        error: String,
+    },
+    InitSuccess {
+        instance: Uid,
+    },
+    InitError {
+        instance: Uid,
+        error: String,
+    },
+    InitListenerSuccess {
+        listener: Uid,
+    },
+    InitListenerError {
+        listener: Uid,
+        error: String,
+    },
+    ListenerCloseEvent {
+        listener: Uid,
+    },
+    ConnectionEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    CloseEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    ConnectSuccess {
+        connection: Uid,
+    },
+    ConnectTimeout {
+        connection: Uid,
+    },
+    ConnectError {
+        connection: Uid,
+        error: String,
+    },
+    ConnectClose {
+        connection: Uid,
+    },
+    SendSuccess {
+        uid: Uid,
+    },
+    SendTimeout {
+        uid: Uid,
+    },
+    SendError {
+        uid: Uid,
+        error: String,
+    },
+    RecvSuccess {
+        uid: Uid,
+        data: Vec<u8>,
+    },
+    RecvTimeout {
+        uid: Uid,
+        partial_data: Vec<u8>,
+    },
+    RecvError {
+        uid: Uid,
+        error: String,
+    },
+    LifecycleEvent {
+        connection: Uid,
+        event: ConnectionLifecycleEvent,
+    },
+}
+
+impl Action for LifecycleEventsAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,228 @@
+use super::{
+    action::LifecycleEventsAction,
+    state::{LifecycleEventsState, LifecycleEventsStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{ConnectionLifecycleEvent, RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `LifecycleEventsState` model subscribes to the connection lifecycle
+// events of `TcpServerState` and connects to its own listener. It sends `data`
+// to the server, echoes it back and closes the server connection, recording
+// the events reported along the way.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for LifecycleEventsState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for LifecycleEventsState {
+    type Action = LifecycleEventsAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            LifecycleEventsAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<LifecycleEventsState>().status == LifecycleEventsStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| LifecycleEventsAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| LifecycleEventsAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| LifecycleEventsAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| LifecycleEventsAction::PollError { uid, error }),
+                    })
+                }
+            }
+            LifecycleEventsAction::PollSuccess { .. } => (),
+            LifecycleEventsAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            LifecycleEventsAction::InitSuccess { .. } => {
+                let address = state.substate::<LifecycleEventsState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::Subscribe {
+                    on_event: callback!(|(connection: Uid, event: ConnectionLifecycleEvent)| LifecycleEventsAction::LifecycleEvent { connection, event }),
+                });
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| LifecycleEventsAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| LifecycleEventsAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| LifecycleEventsAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| LifecycleEventsAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| LifecycleEventsAction::ListenerCloseEvent { listener }),
+                });
+            }
+            LifecycleEventsAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            LifecycleEventsAction::InitListenerSuccess { .. } => {
+                let events_state: &mut LifecycleEventsState = state.substate_mut();
+                let address = events_state.address.clone();
+
+                events_state.status = LifecycleEventsStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| LifecycleEventsAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| LifecycleEventsAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| LifecycleEventsAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| LifecycleEventsAction::ConnectClose { connection }),
+                });
+            }
+            LifecycleEventsAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            LifecycleEventsAction::ConnectionEvent { connection, .. } => {
+                state
+                    .substate_mut::<LifecycleEventsState>()
+                    .server_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            LifecycleEventsAction::ConnectSuccess { connection } => {
+                state
+                    .substate_mut::<LifecycleEventsState>()
+                    .client_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            LifecycleEventsAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            LifecycleEventsAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            LifecycleEventsAction::SendSuccess { .. } => {
+                let events_state: &LifecycleEventsState = state.substate();
+                let connection = events_state.server_connection.unwrap();
+
+                if !events_state.echoing {
+                    let count = events_state.data.len();
+
+                    dispatcher.dispatch(TcpServerAction::Recv {
+                        uid: state.new_uid(),
+                        connection,
+                        count,
+                        timeout: Timeout::Millis(1000),
+                        on_success: callback!(|(uid: Uid, data: Vec<u8>)| LifecycleEventsAction::RecvSuccess { uid, data }),
+                        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| LifecycleEventsAction::RecvTimeout { uid, partial_data }),
+                        on_error: callback!(|(uid: Uid, error: String)| LifecycleEventsAction::RecvError { uid, error }),
+                    });
+                } else {
+                    // The echo was sent, close the server end.
+                    dispatcher.dispatch(TcpServerAction::Close {
+                        connection,
+                        deliver_buffered: false,
+                    });
+                }
+            }
+            LifecycleEventsAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            LifecycleEventsAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            LifecycleEventsAction::RecvSuccess { data, .. } => {
+                let events_state: &mut LifecycleEventsState = state.substate_mut();
+                let connection = events_state.server_connection.unwrap();
+
+                events_state.echoing = true;
+                dispatcher.dispatch(TcpServerAction::Send {
+                    uid: state.new_uid(),
+                    connection,
+                    data: data.into(),
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|uid: Uid| LifecycleEventsAction::SendSuccess { uid }),
+                    on_timeout: callback!(|uid: Uid| LifecycleEventsAction::SendTimeout { uid }),
+                    on_error: callback!(|(uid: Uid, error: String)| LifecycleEventsAction::SendError { uid, error }),
+                });
+            }
+            LifecycleEventsAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timeout: {:?}", uid, partial_data)
+            }
+            LifecycleEventsAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            LifecycleEventsAction::LifecycleEvent { connection, event } => state
+                .substate_mut::<LifecycleEventsState>()
+                .events
+                .push((connection, event)),
+            LifecycleEventsAction::CloseEvent { connection, .. } => {
+                let events_state: &mut LifecycleEventsState = state.substate_mut();
+
+                assert_eq!(Some(connection), events_state.server_connection);
+                events_state.closed = true;
+            }
+            LifecycleEventsAction::ListenerCloseEvent { .. }
+            | LifecycleEventsAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sends `data` to the server once the connection is established on both ends.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let LifecycleEventsState {
+        data,
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let (connection, data) = (*connection, data.clone());
+
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid: state.new_uid(),
+        connection,
+        data: data.into(),
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|uid: Uid| LifecycleEventsAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| LifecycleEventsAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| LifecycleEventsAction::SendError { uid, error }),
+    });
+}
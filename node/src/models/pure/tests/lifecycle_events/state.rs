@@ -0,0 +1,38 @@
+use crate::{
+    automaton::state::Uid, models::pure::net::tcp_server::action::ConnectionLifecycleEvent,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LifecycleEventsStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct LifecycleEventsState {
+    pub status: LifecycleEventsStatus,
+    pub address: String,
+    // Sent by the client, then echoed back by the server.
+    pub data: Vec<u8>,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    // Set once the server received `data` and echoes it.
+    pub echoing: bool,
+    pub events: Vec<(Uid, ConnectionLifecycleEvent)>,
+    pub closed: bool,
+}
+
+impl LifecycleEventsState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: LifecycleEventsStatus::Init,
+            address,
+            data,
+            client_connection: None,
+            server_connection: None,
+            echoing: false,
+            events: Vec::new(),
+            closed: false,
+        }
+    }
+}
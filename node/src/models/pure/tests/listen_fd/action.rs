@@ -0,0 +1,25 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::tcp::action::TcpPollEvents,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "a3c6e1f4-2b7d-4e89-8f05-6d9b4c2e7a13"]
+pub enum ListenFdAction {
+    Tick,
+    PollSuccess { uid: Uid, events: TcpPollEvents },
+    PollError { uid: Uid, error: String },
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    ListenSuccess { listener: Uid },
+    ListenError { listener: Uid, error: String },
+}
+
+impl Action for ListenFdAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
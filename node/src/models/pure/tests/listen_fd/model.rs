@@ -0,0 +1,105 @@
+use super::{
+    action::ListenFdAction,
+    state::{ListenFdState, ListenFdStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::tcp::{
+            action::{Event, ListenerEvent, TcpAction, TcpPollEvents},
+            state::TcpState,
+        },
+        time::model::update_time,
+    },
+};
+
+// The `ListenFdState` model adopts a listening socket with
+// `TcpAction::ListenFromFd`, then polls the listener until a connection is
+// pending. Nothing is accepted.
+
+// This model depends on `TcpState`.
+impl RegisterModel for ListenFdState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for ListenFdState {
+    type Action = ListenFdAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ListenFdAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                let listen_state: &ListenFdState = state.substate();
+
+                match listen_state.status {
+                    ListenFdStatus::Init => dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| ListenFdAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| ListenFdAction::InitError { instance, error }),
+                    }),
+                    ListenFdStatus::Listening if !listen_state.accept_pending => {
+                        let listener = listen_state.listener.unwrap();
+
+                        dispatcher.dispatch(TcpAction::poll(
+                            state.new_uid(),
+                            vec![listener],
+                            Timeout::Millis(10),
+                            callback!(|(uid: Uid, events: TcpPollEvents)| ListenFdAction::PollSuccess { uid, events }),
+                            callback!(|(uid: Uid, error: String)| ListenFdAction::PollError { uid, error }),
+                        ))
+                    }
+                    _ => (),
+                }
+            }
+            ListenFdAction::PollSuccess { events, .. } => {
+                let listen_state: &mut ListenFdState = state.substate_mut();
+                let listener = listen_state.listener.unwrap();
+
+                listen_state.accept_pending = events.iter().any(|(uid, event)| {
+                    *uid == listener && *event == Event::Listener(ListenerEvent::AcceptPending)
+                });
+            }
+            ListenFdAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            ListenFdAction::InitSuccess { .. } => {
+                let listener = state.new_uid();
+                let listen_state: &mut ListenFdState = state.substate_mut();
+
+                listen_state.status = ListenFdStatus::Adopting;
+                listen_state.listener = Some(listener);
+                dispatcher.dispatch(TcpAction::ListenFromFd {
+                    listener,
+                    fd: listen_state.fd,
+                    on_success: callback!(|listener: Uid| ListenFdAction::ListenSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| ListenFdAction::ListenError { listener, error }),
+                });
+            }
+            ListenFdAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            ListenFdAction::ListenSuccess { .. } => {
+                state.substate_mut::<ListenFdState>().status = ListenFdStatus::Listening
+            }
+            ListenFdAction::ListenError { error, .. } => {
+                state.substate_mut::<ListenFdState>().status = ListenFdStatus::Error(error)
+            }
+        }
+    }
+}
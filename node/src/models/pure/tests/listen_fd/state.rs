@@ -0,0 +1,30 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ListenFdStatus {
+    Init,
+    Adopting,
+    Listening,
+    Error(String),
+}
+
+#[derive(Debug)]
+pub struct ListenFdState {
+    pub status: ListenFdStatus,
+    // An open listening socket, adopted with `TcpAction::ListenFromFd`.
+    pub fd: i32,
+    pub listener: Option<Uid>,
+    // The listener was reported readable by a poll.
+    pub accept_pending: bool,
+}
+
+impl ListenFdState {
+    pub fn new(fd: i32) -> Self {
+        Self {
+            status: ListenFdStatus::Init,
+            fd,
+            listener: None,
+            accept_pending: false,
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use super::{
+    action::LocalAddressAction,
+    state::{LocalAddressState, LocalAddressStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `LocalAddressState` model listens on port 0, queries the port assigned
+// to the listener with `TcpAction::GetLocalAddress` and connects to it. Then
+// it queries the local address of the client connection.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for LocalAddressState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for LocalAddressState {
+    type Action = LocalAddressAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            LocalAddressAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<LocalAddressState>().status == LocalAddressStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| LocalAddressAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| LocalAddressAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| LocalAddressAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| LocalAddressAction::PollError { uid, error }),
+                    })
+                }
+            }
+            LocalAddressAction::PollSuccess { .. } => (),
+            LocalAddressAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            LocalAddressAction::InitSuccess { .. } => {
+                let address = state.substate::<LocalAddressState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| LocalAddressAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| LocalAddressAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| LocalAddressAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| LocalAddressAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| LocalAddressAction::ListenerCloseEvent { listener }),
+                });
+            }
+            LocalAddressAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            LocalAddressAction::InitListenerSuccess { listener } => {
+                let local_state: &mut LocalAddressState = state.substate_mut();
+
+                local_state.status = LocalAddressStatus::Listening;
+                local_state.listener = Some(listener);
+                // The port to connect to is only known once listening.
+                dispatcher.dispatch(TcpAction::GetLocalAddress {
+                    listener_or_connection: listener,
+                    on_success: callback!(|(uid: Uid, address: String)| LocalAddressAction::LocalAddress { uid, address }),
+                    on_error: callback!(|(uid: Uid, error: String)| LocalAddressAction::LocalAddressError { uid, error }),
+                });
+            }
+            LocalAddressAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            LocalAddressAction::ListenerCloseEvent { .. }
+            | LocalAddressAction::ConnectionEvent { .. }
+            | LocalAddressAction::CloseEvent { .. }
+            | LocalAddressAction::ConnectClose { .. } => (),
+            LocalAddressAction::ConnectSuccess { connection } => {
+                state.substate_mut::<LocalAddressState>().connection = Some(connection);
+                dispatcher.dispatch(TcpAction::GetLocalAddress {
+                    listener_or_connection: connection,
+                    on_success: callback!(|(uid: Uid, address: String)| LocalAddressAction::LocalAddress { uid, address }),
+                    on_error: callback!(|(uid: Uid, error: String)| LocalAddressAction::LocalAddressError { uid, error }),
+                });
+            }
+            LocalAddressAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            LocalAddressAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            LocalAddressAction::LocalAddress { uid, address } => {
+                let local_state: &mut LocalAddressState = state.substate_mut();
+
+                if local_state.listener != Some(uid) {
+                    local_state.connection_address = Some(address);
+                    return;
+                }
+
+                local_state.listener_address = Some(address.clone());
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| LocalAddressAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| LocalAddressAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| LocalAddressAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| LocalAddressAction::ConnectClose { connection }),
+                });
+            }
+            LocalAddressAction::LocalAddressError { uid, error } => {
+                panic!("Local address query {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
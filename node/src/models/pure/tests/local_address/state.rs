@@ -0,0 +1,32 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LocalAddressStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct LocalAddressState {
+    pub status: LocalAddressStatus,
+    // Port 0: the port is assigned when listening.
+    pub address: String,
+    pub listener: Option<Uid>,
+    pub connection: Option<Uid>,
+    // The local addresses reported for the listener and the client connection.
+    pub listener_address: Option<String>,
+    pub connection_address: Option<String>,
+}
+
+impl LocalAddressState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: LocalAddressStatus::Init,
+            address,
+            listener: None,
+            connection: None,
+            listener_address: None,
+            connection_address: None,
+        }
+    }
+}
@@ -3,3 +3,44 @@ pub mod echo_client;
 pub mod echo_server_pnet;
 pub mod echo_client_pnet;
 pub mod simple_client_pnet;
+pub mod pure_counter;
+pub mod priority_order;
+pub mod replay_effect;
+pub mod shared_config;
+pub mod udp_echo;
+pub mod output_log;
+pub mod replay_client;
+pub mod recursive_dispatch;
+pub mod tcp_upgrade;
+pub mod retry_send_client;
+pub mod shutdown_order;
+pub mod trace_client;
+pub mod op_log_client;
+pub mod connect_timeout_client;
+pub mod congestion_client;
+pub mod cancel_client;
+pub mod relay_server;
+pub mod local_address;
+pub mod backlog_server;
+pub mod idle_sweep_server;
+pub mod peer_address;
+pub mod listen_fd;
+pub mod connection_addrs;
+pub mod close_deliver_buffered;
+pub mod register_retry;
+pub mod tee_capture;
+pub mod probe;
+pub mod drain_on_close;
+pub mod last_error;
+pub mod accept_register_delay;
+pub mod lifecycle_events;
+pub mod recv_line;
+pub mod admission;
+pub mod close_all;
+pub mod ring_parse;
+pub mod send_to_group;
+pub mod bytes_available;
+pub mod half_close;
+pub mod recv_until;
+pub mod connection_numbers;
+pub mod nodelay;
@@ -0,0 +1,65 @@
+use crate::automaton::{
+    action::{Action, ActionKind},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "6df31ffb-c012-4731-964d-0c7163001869"]
+pub enum NodelayAction {
+    Tick,
+    PollSuccess {
+        uid: Uid,
+    },
+    PollError {
+        uid: Uid,
+        error: String,
+    },
+    InitSuccess {
+        instance: Uid,
+    },
+    InitError {
+        instance: Uid,
+        error: String,
+    },
+    InitListenerSuccess {
+        listener: Uid,
+    },
+    InitListenerError {
+        listener: Uid,
+        error: String,
+    },
+    ListenerCloseEvent {
+        listener: Uid,
+    },
+    ConnectionEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    CloseEvent {
+        listener: Uid,
+        connection: Uid,
+    },
+    ConnectSuccess {
+        connection: Uid,
+    },
+    ConnectTimeout {
+        connection: Uid,
+    },
+    ConnectError {
+        connection: Uid,
+        error: String,
+    },
+    ConnectClose {
+        connection: Uid,
+    },
+    Nodelay {
+        connection: Uid,
+        result: Result<(), String>,
+    },
+}
+
+impl Action for NodelayAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,157 @@
+use super::{
+    action::NodelayAction,
+    state::{NodelayState, NodelayStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `NodelayState` model connects to its own listener, which sets
+// TCP_NODELAY on the connections it accepts, then sets it on the client
+// connection with `TcpAction::SetNodelay`.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for NodelayState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for NodelayState {
+    type Action = NodelayAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            NodelayAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<NodelayState>().status == NodelayStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| NodelayAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| NodelayAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| NodelayAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| NodelayAction::PollError { uid, error }),
+                    })
+                }
+            }
+            NodelayAction::PollSuccess { .. } => (),
+            NodelayAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            NodelayAction::InitSuccess { .. } => {
+                let address = state.substate::<NodelayState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: true,
+                    on_success: callback!(|listener: Uid| NodelayAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| NodelayAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| NodelayAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| NodelayAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| NodelayAction::ListenerCloseEvent { listener }),
+                });
+            }
+            NodelayAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            NodelayAction::InitListenerSuccess { .. } => {
+                let nodelay_state: &mut NodelayState = state.substate_mut();
+                let address = nodelay_state.address.clone();
+
+                nodelay_state.status = NodelayStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| NodelayAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| NodelayAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| NodelayAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| NodelayAction::ConnectClose { connection }),
+                });
+            }
+            NodelayAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            NodelayAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<NodelayState>().server_connection = Some(connection);
+                set_nodelay_when_connected(state, dispatcher)
+            }
+            NodelayAction::ConnectSuccess { connection } => {
+                state.substate_mut::<NodelayState>().client_connection = Some(connection);
+                set_nodelay_when_connected(state, dispatcher)
+            }
+            NodelayAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            NodelayAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            NodelayAction::Nodelay { result, .. } => {
+                state.substate_mut::<NodelayState>().result = Some(result)
+            }
+            NodelayAction::ListenerCloseEvent { .. }
+            | NodelayAction::CloseEvent { .. }
+            | NodelayAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sets TCP_NODELAY on the client connection once the connection is
+// established on both ends.
+fn set_nodelay_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let NodelayState {
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    dispatcher.dispatch(TcpAction::SetNodelay {
+        connection: *connection,
+        value: true,
+        on_result: callback!(|(connection: Uid, result: Result<(), String>)| NodelayAction::Nodelay { connection, result }),
+    });
+}
@@ -0,0 +1,29 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NodelayStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct NodelayState {
+    pub status: NodelayStatus,
+    pub address: String,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    // The result of setting TCP_NODELAY on the client connection.
+    pub result: Option<Result<(), String>>,
+}
+
+impl NodelayState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: NodelayStatus::Init,
+            address,
+            client_connection: None,
+            server_connection: None,
+            result: None,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use super::{
+    action::OpLogClientAction,
+    state::{OpLogClientState, OpLogClientStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::{TcpAction, TcpPollEvents},
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+        },
+        time::model::update_time,
+    },
+};
+
+// The `OpLogClientState` model connects to an echo server, enables the
+// operation log of its connection, then sends its data and receives the echo.
+
+// This model depends on `TcpClientState`.
+impl RegisterModel for OpLogClientState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpClientState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for OpLogClientState {
+    type Action = OpLogClientAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            OpLogClientAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<OpLogClientState>().status == OpLogClientStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| OpLogClientAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| OpLogClientAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpClientAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|(uid: Uid, events: TcpPollEvents)| OpLogClientAction::PollSuccess { uid, events }),
+                        on_error: callback!(|(uid: Uid, error: String)| OpLogClientAction::PollError { uid, error }),
+                    })
+                }
+            }
+            OpLogClientAction::PollSuccess { .. } => (),
+            OpLogClientAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            OpLogClientAction::InitSuccess { .. } => {
+                let client_state: &mut OpLogClientState = state.substate_mut();
+                let address = client_state.address.clone();
+
+                client_state.status = OpLogClientStatus::Ready;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| OpLogClientAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| OpLogClientAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| OpLogClientAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| OpLogClientAction::CloseEvent { connection }),
+                });
+            }
+            OpLogClientAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            OpLogClientAction::ConnectSuccess { connection } => {
+                let client_state: &mut OpLogClientState = state.substate_mut();
+                let data = client_state.data.clone();
+
+                client_state.connection = Some(connection);
+                dispatcher.dispatch(TcpAction::SetOperationLog {
+                    connection,
+                    enabled: true,
+                });
+                dispatcher.dispatch(TcpClientAction::Send {
+                    uid: state.new_uid(),
+                    connection,
+                    data: data.into(),
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|uid: Uid| OpLogClientAction::SendSuccess { uid }),
+                    on_timeout: callback!(|uid: Uid| OpLogClientAction::SendTimeout { uid }),
+                    on_error: callback!(|(uid: Uid, error: String)| OpLogClientAction::SendError { uid, error }),
+                });
+            }
+            OpLogClientAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            OpLogClientAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            OpLogClientAction::CloseEvent { connection } => {
+                panic!("Connection {:?} closed", connection)
+            }
+            OpLogClientAction::SendSuccess { .. } => {
+                let client_state: &OpLogClientState = state.substate();
+                let (connection, count) =
+                    (client_state.connection.unwrap(), client_state.data.len());
+
+                dispatcher.dispatch(TcpClientAction::Recv {
+                    uid: state.new_uid(),
+                    connection,
+                    count,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| OpLogClientAction::RecvSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| OpLogClientAction::RecvTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| OpLogClientAction::RecvError { uid, error }),
+                });
+            }
+            OpLogClientAction::SendTimeout { uid } => {
+                panic!("Send {:?} timed out", uid)
+            }
+            OpLogClientAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            OpLogClientAction::RecvSuccess { data, .. } => {
+                state.substate_mut::<OpLogClientState>().received = Some(data)
+            }
+            OpLogClientAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timed out: {:?}", uid, partial_data)
+            }
+            OpLogClientAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
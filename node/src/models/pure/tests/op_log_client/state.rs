@@ -0,0 +1,29 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OpLogClientStatus {
+    Init,
+    Ready,
+}
+
+#[derive(Debug)]
+pub struct OpLogClientState {
+    pub status: OpLogClientStatus,
+    pub address: String,
+    pub data: Vec<u8>,
+    pub connection: Option<Uid>,
+    // The echoed data, once received.
+    pub received: Option<Vec<u8>>,
+}
+
+impl OpLogClientState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: OpLogClientStatus::Init,
+            address,
+            data,
+            connection: None,
+            received: None,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use crate::automaton::action::{Action, ActionKind};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "66931007-c38b-4135-8f0e-e5df274affa5"]
+pub enum OutputLogAction {
+    Tick,
+}
+
+impl Action for OutputLogAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
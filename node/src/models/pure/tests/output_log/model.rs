@@ -0,0 +1,46 @@
+use super::{action::OutputLogAction, state::OutputLogState};
+use crate::{
+    automaton::{
+        action::Dispatcher,
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State},
+    },
+    models::effectful::output::{
+        action::{OutputEffectfulAction, OutputLevel},
+        state::OutputState,
+    },
+};
+
+// Minimal model writing a line through `OutputState` on each tick, then
+// halting once `lines` were written.
+
+// This model depends on `OutputState` (effectful).
+impl RegisterModel for OutputLogState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<OutputState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for OutputLogState {
+    type Action = OutputLogAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        let OutputLogAction::Tick = action;
+        let output_state: &mut OutputLogState = state.substate_mut();
+
+        if output_state.logged == output_state.lines {
+            return dispatcher.halt();
+        }
+
+        dispatcher.dispatch_effect(OutputEffectfulAction::Log {
+            level: OutputLevel::Info,
+            message: format!("line {}", output_state.logged),
+        });
+        output_state.logged += 1;
+    }
+}
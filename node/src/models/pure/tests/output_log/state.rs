@@ -0,0 +1,12 @@
+#[derive(Debug)]
+pub struct OutputLogState {
+    // Lines to output, one per tick.
+    pub lines: usize,
+    pub logged: usize,
+}
+
+impl OutputLogState {
+    pub fn new(lines: usize) -> Self {
+        Self { lines, logged: 0 }
+    }
+}
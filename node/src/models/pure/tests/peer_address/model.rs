@@ -0,0 +1,159 @@
+use super::{
+    action::PeerAddressAction,
+    state::{PeerAddressState, PeerAddressStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::{PeerAddressResult, TcpAction},
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `PeerAddressState` model connects to its own listener, then queries
+// the addresses of both ends of the connection with `TcpAction::GetPeerAddress`
+// as soon as they are established. It also queries a connection that doesn't
+// exist.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for PeerAddressState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for PeerAddressState {
+    type Action = PeerAddressAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            PeerAddressAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<PeerAddressState>().status == PeerAddressStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| PeerAddressAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| PeerAddressAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| PeerAddressAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| PeerAddressAction::PollError { uid, error }),
+                    })
+                }
+            }
+            PeerAddressAction::PollSuccess { .. } => (),
+            PeerAddressAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            PeerAddressAction::InitSuccess { .. } => {
+                let address = state.substate::<PeerAddressState>().address.clone();
+
+                state.substate_mut::<PeerAddressState>().status = PeerAddressStatus::Listening;
+                dispatcher.dispatch(TcpAction::GetPeerAddress {
+                    connection: state.new_uid(),
+                    on_result: callback!(|(connection: Uid, result: PeerAddressResult)| PeerAddressAction::PeerAddress { connection, result }),
+                });
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| PeerAddressAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| PeerAddressAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| PeerAddressAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| PeerAddressAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| PeerAddressAction::ListenerCloseEvent { listener }),
+                });
+            }
+            PeerAddressAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            PeerAddressAction::InitListenerSuccess { .. } => {
+                let address = state.substate::<PeerAddressState>().address.clone();
+
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| PeerAddressAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| PeerAddressAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| PeerAddressAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| PeerAddressAction::ConnectClose { connection }),
+                });
+            }
+            PeerAddressAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            PeerAddressAction::ListenerCloseEvent { .. }
+            | PeerAddressAction::CloseEvent { .. }
+            | PeerAddressAction::ConnectClose { .. } => (),
+            PeerAddressAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<PeerAddressState>().server_connection = Some(connection);
+                dispatcher.dispatch(TcpAction::GetPeerAddress {
+                    connection,
+                    on_result: callback!(|(connection: Uid, result: PeerAddressResult)| PeerAddressAction::PeerAddress { connection, result }),
+                });
+            }
+            PeerAddressAction::ConnectSuccess { connection } => {
+                state.substate_mut::<PeerAddressState>().client_connection = Some(connection);
+                dispatcher.dispatch(TcpAction::GetPeerAddress {
+                    connection,
+                    on_result: callback!(|(connection: Uid, result: PeerAddressResult)| PeerAddressAction::PeerAddress { connection, result }),
+                });
+                dispatcher.dispatch(TcpAction::GetLocalAddress {
+                    listener_or_connection: connection,
+                    on_success: callback!(|(uid: Uid, address: String)| PeerAddressAction::LocalAddress { uid, address }),
+                    on_error: callback!(|(uid: Uid, error: String)| PeerAddressAction::LocalAddressError { uid, error }),
+                });
+            }
+            PeerAddressAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            PeerAddressAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            PeerAddressAction::PeerAddress { connection, result } => {
+                state
+                    .substate_mut::<PeerAddressState>()
+                    .peer_addresses
+                    .insert(connection, result);
+            }
+            PeerAddressAction::LocalAddress { address, .. } => {
+                state.substate_mut::<PeerAddressState>().client_local_address = Some(address)
+            }
+            PeerAddressAction::LocalAddressError { uid, error } => {
+                panic!("Local address query {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{automaton::state::Uid, models::pure::net::tcp::action::PeerAddressResult};
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PeerAddressStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct PeerAddressState {
+    pub status: PeerAddressStatus,
+    pub address: String,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    // The `TcpAction::GetPeerAddress` results, by connection.
+    pub peer_addresses: BTreeMap<Uid, PeerAddressResult>,
+    // Reported by `TcpAction::GetLocalAddress` for the client connection.
+    pub client_local_address: Option<String>,
+}
+
+impl PeerAddressState {
+    pub fn new(address: String) -> Self {
+        Self {
+            status: PeerAddressStatus::Init,
+            address,
+            client_connection: None,
+            server_connection: None,
+            peer_addresses: BTreeMap::new(),
+            client_local_address: None,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::automaton::action::{Action, ActionKind};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "6893e2bf-db2e-4a46-9cb1-f874a259d13a"]
+pub enum PriorityOrderAction {
+    Tick,
+}
+
+impl Action for PriorityOrderAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "55628ce1-c4f1-4189-b6b3-0423bcc00d21"]
+pub enum PriorityLowAction {
+    Process,
+}
+
+impl Action for PriorityLowAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "a62c0442-1958-4bd4-afa0-d0bdd8075e9b"]
+pub enum PriorityHighAction {
+    Process,
+}
+
+impl Action for PriorityHighAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,92 @@
+use super::{
+    action::{PriorityHighAction, PriorityLowAction, PriorityOrderAction},
+    state::{PriorityHighState, PriorityLowState, PriorityOrderState},
+};
+use crate::automaton::{
+    action::Dispatcher,
+    model::PureModel,
+    runner::{RegisterModel, RunnerBuilder},
+    state::{ModelState, State},
+};
+
+// Minimal models to test the processing order of actions queued for models
+// registered with different priorities. On its first tick, `PriorityOrderState`
+// dispatches an action to `PriorityLowState` followed by one to
+// `PriorityHighState`, then halts on the next tick.
+
+impl RegisterModel for PriorityOrderState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<PriorityLowState>()
+            .register::<PriorityHighState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl RegisterModel for PriorityLowState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .model_pure::<Self>()
+            .priority::<PriorityLowAction>(-1)
+    }
+}
+
+impl RegisterModel for PriorityHighState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .model_pure::<Self>()
+            .priority::<PriorityHighAction>(1)
+    }
+}
+
+impl PureModel for PriorityOrderState {
+    type Action = PriorityOrderAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        let PriorityOrderAction::Tick = action;
+        let order_state: &mut PriorityOrderState = state.substate_mut();
+
+        order_state.ticks += 1;
+
+        if order_state.ticks == 1 {
+            dispatcher.dispatch(PriorityLowAction::Process);
+            dispatcher.dispatch(PriorityHighAction::Process);
+        } else {
+            dispatcher.halt()
+        }
+    }
+}
+
+impl PureModel for PriorityLowState {
+    type Action = PriorityLowAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        _action: Self::Action,
+        _dispatcher: &mut Dispatcher,
+    ) {
+        state
+            .substate_mut::<PriorityOrderState>()
+            .processed
+            .push("low")
+    }
+}
+
+impl PureModel for PriorityHighState {
+    type Action = PriorityHighAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        _action: Self::Action,
+        _dispatcher: &mut Dispatcher,
+    ) {
+        state
+            .substate_mut::<PriorityOrderState>()
+            .processed
+            .push("high")
+    }
+}
@@ -0,0 +1,24 @@
+#[derive(Default, Debug)]
+pub struct PriorityOrderState {
+    pub ticks: u64,
+    // Names of the models that processed an action, in processing order.
+    pub processed: Vec<&'static str>,
+}
+
+impl PriorityOrderState {
+    pub fn new() -> Self {
+        Self {
+            ticks: 0,
+            processed: Vec::new(),
+        }
+    }
+}
+
+// The following models have no state of their own, they record their actions
+// in `PriorityOrderState`.
+
+#[derive(Debug)]
+pub struct PriorityLowState;
+
+#[derive(Debug)]
+pub struct PriorityHighState;
@@ -0,0 +1,132 @@
+use super::{
+    action::ProbeAction,
+    state::{ProbeState, ProbeStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::{
+                action::{ProbeResult, TcpAction},
+                state::TcpState,
+            },
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `ProbeState` model probes the address of its own listener, then
+// `closed_address` (where nothing listens), with `TcpAction::Probe`.
+
+// This model depends on `TcpServerState`.
+impl RegisterModel for ProbeState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpServerState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for ProbeState {
+    type Action = ProbeAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ProbeAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<ProbeState>().status == ProbeStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| ProbeAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| ProbeAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| ProbeAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| ProbeAction::PollError { uid, error }),
+                    })
+                }
+            }
+            ProbeAction::PollSuccess { .. } => (),
+            ProbeAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            ProbeAction::InitSuccess { .. } => {
+                let address = state.substate::<ProbeState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| ProbeAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| ProbeAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| ProbeAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| ProbeAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| ProbeAction::ListenerCloseEvent { listener }),
+                });
+            }
+            ProbeAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            ProbeAction::InitListenerSuccess { .. } => {
+                let probe_state: &mut ProbeState = state.substate_mut();
+                let address = probe_state.address.clone();
+
+                probe_state.status = ProbeStatus::Listening;
+                dispatcher.dispatch(TcpAction::Probe {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_result: callback!(|(connection: Uid, result: ProbeResult)| ProbeAction::ProbeResult { connection, result }),
+                });
+            }
+            ProbeAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            ProbeAction::ProbeResult { connection, result } => {
+                // The probe connection is gone once the result is reported.
+                assert!(!state.substate::<TcpState>().has_connection(&connection));
+
+                let probe_state: &mut ProbeState = state.substate_mut();
+                let first = probe_state.results.is_empty();
+                let address = probe_state.closed_address.clone();
+
+                probe_state.results.push(result);
+
+                if first {
+                    dispatcher.dispatch(TcpAction::Probe {
+                        connection: state.new_uid(),
+                        address,
+                        timeout: Timeout::Millis(1000),
+                        on_result: callback!(|(connection: Uid, result: ProbeResult)| ProbeAction::ProbeResult { connection, result }),
+                    });
+                }
+            }
+            ProbeAction::ListenerCloseEvent { .. }
+            | ProbeAction::ConnectionEvent { .. }
+            | ProbeAction::CloseEvent { .. } => (),
+        }
+    }
+}
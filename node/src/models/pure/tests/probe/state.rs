@@ -0,0 +1,27 @@
+use crate::models::pure::net::tcp::action::ProbeResult;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProbeStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct ProbeState {
+    pub status: ProbeStatus,
+    pub address: String,
+    // Where nothing listens.
+    pub closed_address: String,
+    pub results: Vec<ProbeResult>,
+}
+
+impl ProbeState {
+    pub fn new(address: String, closed_address: String) -> Self {
+        Self {
+            status: ProbeStatus::Init,
+            address,
+            closed_address,
+            results: Vec::new(),
+        }
+    }
+}
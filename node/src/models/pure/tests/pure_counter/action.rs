@@ -0,0 +1,13 @@
+use crate::automaton::action::{Action, ActionKind};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "2669ebd6-a51c-4c67-b54f-f6597a90936f"]
+pub enum PureCounterAction {
+    Tick,
+}
+
+impl Action for PureCounterAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
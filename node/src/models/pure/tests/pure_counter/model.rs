@@ -0,0 +1,54 @@
+use super::{action::PureCounterAction, state::PureCounterState};
+use crate::{
+    automaton::{
+        action::Dispatcher,
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State},
+    },
+    models::pure::time::{model::update_time, state::TimeState},
+};
+use std::{thread, time::Duration};
+
+// Minimal model counting its own ticks until `max_count` is reached. Used to
+// test the runner with (optionally) effect-free action sequences.
+
+impl RegisterModel for PureCounterState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TimeState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for PureCounterState {
+    type Action = PureCounterAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            PureCounterAction::Tick => {
+                if state.substate::<PureCounterState>().config.update_time
+                    && update_time(state, dispatcher)
+                {
+                    return;
+                }
+
+                let counter_state: &mut PureCounterState = state.substate_mut();
+
+                counter_state.count += 1;
+
+                if let Some((tick, ms)) = counter_state.config.slow_tick {
+                    if counter_state.count == tick {
+                        thread::sleep(Duration::from_millis(ms))
+                    }
+                }
+
+                if counter_state.count == counter_state.config.max_count {
+                    dispatcher.halt()
+                }
+            }
+        }
+    }
+}
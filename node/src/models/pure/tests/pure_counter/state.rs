@@ -0,0 +1,21 @@
+#[derive(Debug)]
+pub struct PureCounterConfig {
+    pub max_count: u64,
+    // Update the state-machine time on each tick (dispatches an effect).
+    pub update_time: bool,
+    // Testing only: (tick, ms) sleeps `ms` while processing the `tick`th
+    // tick, to exceed a `RunnerBuilder::max_step_duration` bound.
+    pub slow_tick: Option<(u64, u64)>,
+}
+
+#[derive(Debug)]
+pub struct PureCounterState {
+    pub count: u64,
+    pub config: PureCounterConfig,
+}
+
+impl PureCounterState {
+    pub fn from_config(config: PureCounterConfig) -> Self {
+        Self { count: 0, config }
+    }
+}
@@ -0,0 +1,14 @@
+use crate::automaton::action::{Action, ActionKind};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "4e5ae75a-e225-427c-a2c2-0d1dbb8e0f2f"]
+pub enum RecursiveDispatchAction {
+    Tick,
+    Recurse { level: usize },
+}
+
+impl Action for RecursiveDispatchAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,44 @@
+use super::{action::RecursiveDispatchAction, state::RecursiveDispatchState};
+use crate::automaton::{
+    action::Dispatcher,
+    model::PureModel,
+    runner::{RegisterModel, RunnerBuilder},
+    state::{ModelState, State},
+};
+
+// Minimal model to test the dispatch depth limit. On its first tick,
+// `RecursiveDispatchState` dispatches an action that dispatches another one
+// while handled, and so on, `levels` deep. Then it halts.
+
+impl RegisterModel for RecursiveDispatchState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.model_pure::<Self>()
+    }
+}
+
+impl PureModel for RecursiveDispatchState {
+    type Action = RecursiveDispatchAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            RecursiveDispatchAction::Tick => {
+                dispatcher.dispatch(RecursiveDispatchAction::Recurse { level: 1 })
+            }
+            RecursiveDispatchAction::Recurse { level } => {
+                let recursive_state: &mut RecursiveDispatchState = state.substate_mut();
+
+                recursive_state.deepest = level;
+
+                if level < recursive_state.levels {
+                    dispatcher.dispatch(RecursiveDispatchAction::Recurse { level: level + 1 })
+                } else {
+                    dispatcher.halt()
+                }
+            }
+        }
+    }
+}
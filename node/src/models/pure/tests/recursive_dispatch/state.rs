@@ -0,0 +1,13 @@
+#[derive(Default, Debug)]
+pub struct RecursiveDispatchState {
+    // Level at which the recursion stops.
+    pub levels: usize,
+    // Deepest level reached.
+    pub deepest: usize,
+}
+
+impl RecursiveDispatchState {
+    pub fn new(levels: usize) -> Self {
+        Self { levels, deepest: 0 }
+    }
+}
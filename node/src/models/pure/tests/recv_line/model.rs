@@ -0,0 +1,198 @@
+use super::{
+    action::RecvLineAction,
+    state::{RecvLineState, RecvLineStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `RecvLineState` model connects to its own listener and sends `data` to
+// the server in a single write. The server receives it line by line with
+// `TcpAction::RecvLine`: the lines after the first one are served from what
+// was already read.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for RecvLineState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for RecvLineState {
+    type Action = RecvLineAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            RecvLineAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<RecvLineState>().status == RecvLineStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| RecvLineAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| RecvLineAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| RecvLineAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| RecvLineAction::PollError { uid, error }),
+                    })
+                }
+            }
+            RecvLineAction::PollSuccess { .. } => (),
+            RecvLineAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            RecvLineAction::InitSuccess { .. } => {
+                let address = state.substate::<RecvLineState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| RecvLineAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| RecvLineAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| RecvLineAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| RecvLineAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| RecvLineAction::ListenerCloseEvent { listener }),
+                });
+            }
+            RecvLineAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            RecvLineAction::InitListenerSuccess { .. } => {
+                let line_state: &mut RecvLineState = state.substate_mut();
+                let address = line_state.address.clone();
+
+                line_state.status = RecvLineStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| RecvLineAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| RecvLineAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| RecvLineAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| RecvLineAction::ConnectClose { connection }),
+                });
+            }
+            RecvLineAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            RecvLineAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<RecvLineState>().server_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            RecvLineAction::ConnectSuccess { connection } => {
+                state.substate_mut::<RecvLineState>().client_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            RecvLineAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            RecvLineAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            // All the lines were sent at once, receive the first one.
+            RecvLineAction::SendSuccess { .. } => recv_line(state, dispatcher),
+            RecvLineAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            RecvLineAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            RecvLineAction::RecvLineSuccess { connection, line } => {
+                let line_state: &mut RecvLineState = state.substate_mut();
+                let count = line_state
+                    .data
+                    .split_inclusive(|&byte| byte == b'\n')
+                    .count();
+
+                assert_eq!(Some(connection), line_state.server_connection);
+                line_state.lines.push(line);
+
+                if line_state.lines.len() < count {
+                    recv_line(state, dispatcher)
+                }
+            }
+            RecvLineAction::RecvLineError { connection, error } => {
+                panic!("RecvLine {:?} failed: {}", connection, error)
+            }
+            RecvLineAction::ListenerCloseEvent { .. }
+            | RecvLineAction::CloseEvent { .. }
+            | RecvLineAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sends `data` to the server once the connection is established on both ends.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let RecvLineState {
+        data,
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let (connection, data) = (*connection, data.clone());
+
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid: state.new_uid(),
+        connection,
+        data: data.into(),
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|uid: Uid| RecvLineAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| RecvLineAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| RecvLineAction::SendError { uid, error }),
+    });
+}
+
+fn recv_line<Substate: ModelState>(state: &mut State<Substate>, dispatcher: &mut Dispatcher) {
+    let line_state: &RecvLineState = state.substate();
+
+    dispatcher.dispatch(TcpAction::RecvLine {
+        connection: line_state.server_connection.unwrap(),
+        max_len: 64,
+        timeout: Timeout::Millis(1000),
+        on_line: callback!(|(connection: Uid, line: Vec<u8>)| RecvLineAction::RecvLineSuccess { connection, line }),
+        on_error: callback!(|(connection: Uid, error: String)| RecvLineAction::RecvLineError { connection, error }),
+    });
+}
@@ -0,0 +1,32 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RecvLineStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct RecvLineState {
+    pub status: RecvLineStatus,
+    pub address: String,
+    // CRLF-terminated lines, sent to the server in a single write.
+    pub data: Vec<u8>,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    // Received by the server, one `TcpAction::RecvLine` at a time.
+    pub lines: Vec<Vec<u8>>,
+}
+
+impl RecvLineState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: RecvLineStatus::Init,
+            address,
+            data,
+            client_connection: None,
+            server_connection: None,
+            lines: Vec::new(),
+        }
+    }
+}
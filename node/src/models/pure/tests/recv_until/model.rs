@@ -0,0 +1,212 @@
+use super::{
+    action::RecvUntilAction,
+    state::{RecvUntilState, RecvUntilStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `RecvUntilState` model connects to its own listener and sends `data` to
+// the server in a single write. The server receives it with
+// `TcpServerAction::RecvUntil`: the first recv completes once it received
+// `max_bytes`, the second one (allowed more than what's left) can only
+// complete at the delimiter.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for RecvUntilState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for RecvUntilState {
+    type Action = RecvUntilAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            RecvUntilAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<RecvUntilState>().status == RecvUntilStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| RecvUntilAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| RecvUntilAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| RecvUntilAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| RecvUntilAction::PollError { uid, error }),
+                    })
+                }
+            }
+            RecvUntilAction::PollSuccess { .. } => (),
+            RecvUntilAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            RecvUntilAction::InitSuccess { .. } => {
+                let address = state.substate::<RecvUntilState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| RecvUntilAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| RecvUntilAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| RecvUntilAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| RecvUntilAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| RecvUntilAction::ListenerCloseEvent { listener }),
+                });
+            }
+            RecvUntilAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            RecvUntilAction::InitListenerSuccess { .. } => {
+                let until_state: &mut RecvUntilState = state.substate_mut();
+                let address = until_state.address.clone();
+
+                until_state.status = RecvUntilStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| RecvUntilAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| RecvUntilAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| RecvUntilAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| RecvUntilAction::ConnectClose { connection }),
+                });
+            }
+            RecvUntilAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            RecvUntilAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<RecvUntilState>().server_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            RecvUntilAction::ConnectSuccess { connection } => {
+                state.substate_mut::<RecvUntilState>().client_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            RecvUntilAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            RecvUntilAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            RecvUntilAction::SendSuccess { .. } => {
+                let max_bytes = state.substate::<RecvUntilState>().max_bytes;
+
+                recv_until(state, dispatcher, max_bytes)
+            }
+            RecvUntilAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            RecvUntilAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            RecvUntilAction::RecvSuccess { data, .. } => {
+                let until_state: &mut RecvUntilState = state.substate_mut();
+                let max_bytes = 2 * until_state.data.len();
+
+                until_state.received.push(data);
+
+                if until_state.received.len() == 1 {
+                    recv_until(state, dispatcher, max_bytes)
+                }
+            }
+            RecvUntilAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timeout: {:?}", uid, partial_data)
+            }
+            RecvUntilAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            RecvUntilAction::ListenerCloseEvent { .. }
+            | RecvUntilAction::CloseEvent { .. }
+            | RecvUntilAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sends `data` to the server once the connection is established on both ends.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let RecvUntilState {
+        data,
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let (connection, data) = (*connection, data.clone());
+
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid: state.new_uid(),
+        connection,
+        data: data.into(),
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|uid: Uid| RecvUntilAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| RecvUntilAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| RecvUntilAction::SendError { uid, error }),
+    });
+}
+
+fn recv_until<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    max_bytes: usize,
+) {
+    let until_state: &RecvUntilState = state.substate();
+    let (connection, delimiter) = (
+        until_state.server_connection.unwrap(),
+        until_state.delimiter.clone(),
+    );
+
+    dispatcher.dispatch(TcpServerAction::RecvUntil {
+        uid: state.new_uid(),
+        connection,
+        delimiter,
+        max_bytes,
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|(uid: Uid, data: Vec<u8>)| RecvUntilAction::RecvSuccess { uid, data }),
+        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| RecvUntilAction::RecvTimeout { uid, partial_data }),
+        on_error: callback!(|(uid: Uid, error: String)| RecvUntilAction::RecvError { uid, error }),
+    });
+}
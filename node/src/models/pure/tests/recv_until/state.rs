@@ -0,0 +1,38 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RecvUntilStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct RecvUntilState {
+    pub status: RecvUntilStatus,
+    pub address: String,
+    // Sent to the server in a single write, and received with
+    // `TcpServerAction::RecvUntil`: first at most `max_bytes`, then up to
+    // `delimiter`.
+    pub data: Vec<u8>,
+    pub delimiter: Vec<u8>,
+    pub max_bytes: usize,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    // Received by the server, one `TcpServerAction::RecvUntil` at a time.
+    pub received: Vec<Vec<u8>>,
+}
+
+impl RecvUntilState {
+    pub fn new(address: String, data: Vec<u8>, delimiter: Vec<u8>, max_bytes: usize) -> Self {
+        Self {
+            status: RecvUntilStatus::Init,
+            address,
+            data,
+            delimiter,
+            max_bytes,
+            client_connection: None,
+            server_connection: None,
+            received: Vec::new(),
+        }
+    }
+}
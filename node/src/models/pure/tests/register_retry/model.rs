@@ -0,0 +1,262 @@
+use super::{
+    action::RegisterRetryAction,
+    state::{RegisterRetryState, RegisterRetryStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::{
+        effectful::mio::action::MioEffectfulAction,
+        pure::{
+            net::{
+                tcp::{action::TcpAction, state::TcpState},
+                tcp_client::{action::TcpClientAction, state::TcpClientState},
+                tcp_server::{
+                    action::{RoutingPolicy, TcpServerAction},
+                    state::TcpServerState,
+                },
+            },
+            time::model::update_time,
+        },
+    },
+};
+
+// The `RegisterRetryState` model connects to its own listener, then fails the
+// poll registration of the client connection with a transient error. Once
+// `TcpState` retried the registration, the server sends `data` to the client,
+// which must still receive it.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for RegisterRetryState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for RegisterRetryState {
+    type Action = RegisterRetryAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            RegisterRetryAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<RegisterRetryState>().status == RegisterRetryStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| RegisterRetryAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| RegisterRetryAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| RegisterRetryAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| RegisterRetryAction::PollError { uid, error }),
+                    })
+                }
+            }
+            RegisterRetryAction::PollSuccess { .. } => {
+                let RegisterRetryState {
+                    data,
+                    client_connection: Some(client_connection),
+                    server_connection: Some(server_connection),
+                    register_error_injected: true,
+                    sending: false,
+                    ..
+                } = state.substate()
+                else {
+                    return;
+                };
+
+                let (client_connection, server_connection) =
+                    (*client_connection, *server_connection);
+                let tcp_state: &TcpState = state.substate();
+                let conn = tcp_state.get_connection(&client_connection);
+
+                // Not registered until the retry succeeds.
+                if conn.register_retry_at.is_some() {
+                    assert_eq!(tcp_state.connection_poll(&client_connection), None);
+                }
+
+                // Wait until the registration was retried.
+                if conn.register_attempts == 1 && conn.register_retry_at.is_none() {
+                    let data = data.clone();
+
+                    state.substate_mut::<RegisterRetryState>().sending = true;
+                    dispatcher.dispatch(TcpServerAction::Send {
+                        uid: state.new_uid(),
+                        connection: server_connection,
+                        data: data.into(),
+                        timeout: Timeout::Millis(1000),
+                        on_success: callback!(|uid: Uid| RegisterRetryAction::SendSuccess { uid }),
+                        on_timeout: callback!(|uid: Uid| RegisterRetryAction::SendTimeout { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| RegisterRetryAction::SendError { uid, error }),
+                    });
+                }
+            }
+            RegisterRetryAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            RegisterRetryAction::InitSuccess { .. } => {
+                let address = state.substate::<RegisterRetryState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| RegisterRetryAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| RegisterRetryAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| RegisterRetryAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| RegisterRetryAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| RegisterRetryAction::ListenerCloseEvent { listener }),
+                });
+            }
+            RegisterRetryAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            RegisterRetryAction::InitListenerSuccess { .. } => {
+                let retry_state: &mut RegisterRetryState = state.substate_mut();
+                let address = retry_state.address.clone();
+
+                retry_state.status = RegisterRetryStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| RegisterRetryAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| RegisterRetryAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| RegisterRetryAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| RegisterRetryAction::ConnectClose { connection }),
+                });
+            }
+            RegisterRetryAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            RegisterRetryAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<RegisterRetryState>().server_connection = Some(connection);
+                deregister_when_connected(state, dispatcher)
+            }
+            RegisterRetryAction::ConnectSuccess { connection } => {
+                state.substate_mut::<RegisterRetryState>().client_connection = Some(connection);
+                deregister_when_connected(state, dispatcher)
+            }
+            RegisterRetryAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            RegisterRetryAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            RegisterRetryAction::DeregisterSuccess { connection } => {
+                let tcp_state: &TcpState = state.substate();
+
+                assert_eq!(
+                    tcp_state.connection_poll(&connection),
+                    Some(tcp_state.poll_for_connection(&connection))
+                );
+                state
+                    .substate_mut::<RegisterRetryState>()
+                    .register_error_injected = true;
+                // The connection is no longer registered, as if its registration failed.
+                dispatcher.dispatch(TcpAction::RegisterConnectionError {
+                    connection,
+                    error: "Resource temporarily unavailable (os error 11)".to_string(),
+                });
+            }
+            RegisterRetryAction::DeregisterError { connection, error } => {
+                panic!("Deregister {:?} failed: {}", connection, error)
+            }
+            RegisterRetryAction::SendSuccess { .. } => {
+                let retry_state: &RegisterRetryState = state.substate();
+                let (connection, count) = (
+                    retry_state.client_connection.unwrap(),
+                    retry_state.data.len(),
+                );
+
+                dispatcher.dispatch(TcpClientAction::Recv {
+                    uid: state.new_uid(),
+                    connection,
+                    count,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| RegisterRetryAction::RecvSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| RegisterRetryAction::RecvTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| RegisterRetryAction::RecvError { uid, error }),
+                });
+            }
+            RegisterRetryAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            RegisterRetryAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            RegisterRetryAction::RecvSuccess { data, .. } => {
+                let retry_state: &RegisterRetryState = state.substate();
+                let connection = retry_state.client_connection.unwrap();
+                let tcp_state: &TcpState = state.substate();
+
+                assert_eq!(data, retry_state.data);
+                assert!(tcp_state.has_connection(&connection));
+                assert_eq!(
+                    tcp_state.connection_poll(&connection),
+                    Some(tcp_state.poll_for_connection(&connection))
+                );
+                state.substate_mut::<RegisterRetryState>().received = true;
+            }
+            RegisterRetryAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timeout: {:?}", uid, partial_data)
+            }
+            RegisterRetryAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            RegisterRetryAction::ListenerCloseEvent { .. }
+            | RegisterRetryAction::CloseEvent { .. }
+            | RegisterRetryAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Deregisters the client connection once the connection is established on
+// both ends.
+fn deregister_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let RegisterRetryState {
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let connection = *connection;
+
+    dispatcher.dispatch_effect(MioEffectfulAction::PollDeregisterTcpConnection {
+        poll: state
+            .substate::<TcpState>()
+            .poll_for_connection(&connection),
+        connection,
+        on_success: callback!(|connection: Uid| RegisterRetryAction::DeregisterSuccess { connection }),
+        on_error: callback!(|(connection: Uid, error: String)| RegisterRetryAction::DeregisterError { connection, error }),
+    });
+}
@@ -0,0 +1,35 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RegisterRetryStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct RegisterRetryState {
+    pub status: RegisterRetryStatus,
+    pub address: String,
+    // Sent by the server once the client registration was retried.
+    pub data: Vec<u8>,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    pub register_error_injected: bool,
+    pub sending: bool,
+    pub received: bool,
+}
+
+impl RegisterRetryState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: RegisterRetryStatus::Init,
+            address,
+            data,
+            client_connection: None,
+            server_connection: None,
+            register_error_injected: false,
+            sending: false,
+            received: false,
+        }
+    }
+}
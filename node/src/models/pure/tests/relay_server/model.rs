@@ -0,0 +1,141 @@
+use super::{
+    action::RelayServerAction,
+    state::{RelayServerState, RelayServerStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `RelayServerState` model accepts two connections, then sends its notice
+// on the first one and closes the second one, with
+// `TcpServerAction::SendThenCloseOther`.
+
+// This model depends on `TcpServerState`.
+impl RegisterModel for RelayServerState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpServerState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for RelayServerState {
+    type Action = RelayServerAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            RelayServerAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<RelayServerState>().status == RelayServerStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| RelayServerAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| RelayServerAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| RelayServerAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| RelayServerAction::PollError { uid, error }),
+                    })
+                }
+            }
+            RelayServerAction::PollSuccess { .. } => (),
+            RelayServerAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            RelayServerAction::InitSuccess { .. } => {
+                let address = state.substate::<RelayServerState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 2,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| RelayServerAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| RelayServerAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| RelayServerAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| RelayServerAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| RelayServerAction::ListenerCloseEvent { listener }),
+                });
+            }
+            RelayServerAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            RelayServerAction::InitListenerSuccess { .. } => {
+                state.substate_mut::<RelayServerState>().status = RelayServerStatus::Listening
+            }
+            RelayServerAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            RelayServerAction::ListenerCloseEvent { .. } => (),
+            RelayServerAction::ConnectionEvent { connection, .. } => {
+                let server_state: &mut RelayServerState = state.substate_mut();
+
+                server_state.connections.push(connection);
+
+                if server_state.connections.len() < 2 {
+                    return;
+                }
+
+                let (notice, connections) = (
+                    server_state.notice.clone(),
+                    server_state.connections.clone(),
+                );
+
+                dispatcher.dispatch(TcpServerAction::SendThenCloseOther {
+                    uid: state.new_uid(),
+                    send_connection: connections[0],
+                    data: notice.into(),
+                    close_connection: connections[1],
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|uid: Uid| RelayServerAction::SendSuccess { uid }),
+                    on_timeout: callback!(|uid: Uid| RelayServerAction::SendTimeout { uid }),
+                    on_error: callback!(|(uid: Uid, error: String)| RelayServerAction::SendError { uid, error }),
+                });
+            }
+            RelayServerAction::CloseEvent { connection, .. } => {
+                let server_state: &mut RelayServerState = state.substate_mut();
+
+                // The other connection is only closed once the notice was sent.
+                assert!(server_state.sent);
+                server_state.closed.push(connection);
+            }
+            RelayServerAction::SendSuccess { .. } => {
+                state.substate_mut::<RelayServerState>().sent = true
+            }
+            RelayServerAction::SendTimeout { uid } => {
+                panic!("Send {:?} timed out", uid)
+            }
+            RelayServerAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
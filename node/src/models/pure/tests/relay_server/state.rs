@@ -0,0 +1,31 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RelayServerStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct RelayServerState {
+    pub status: RelayServerStatus,
+    pub address: String,
+    pub notice: Vec<u8>,
+    // The accepted connections, in accept order.
+    pub connections: Vec<Uid>,
+    pub sent: bool,
+    pub closed: Vec<Uid>,
+}
+
+impl RelayServerState {
+    pub fn new(address: String, notice: Vec<u8>) -> Self {
+        Self {
+            status: RelayServerStatus::Init,
+            address,
+            notice,
+            connections: Vec::new(),
+            sent: false,
+            closed: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::{replay_sends::state::ReplayReport, tcp::action::TcpPollEvents},
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "168abe67-14fa-4c25-afbd-c276a4ee70e3"]
+pub enum ReplayClientAction {
+    Tick,
+    PollSuccess { uid: Uid, events: TcpPollEvents },
+    PollError { uid: Uid, error: String },
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    ReplaySuccess { uid: Uid, report: ReplayReport },
+    ReplayError { uid: Uid, error: String },
+}
+
+impl Action for ReplayClientAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
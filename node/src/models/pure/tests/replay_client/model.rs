@@ -0,0 +1,111 @@
+use super::{action::ReplayClientAction, state::ReplayClientState};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            replay_sends::{
+                action::ReplaySendsAction,
+                state::{ReplayReport, ReplaySendsState},
+            },
+            tcp::action::{TcpAction, TcpPollEvents},
+            tcp_client::action::TcpClientAction,
+        },
+        time::model::update_time,
+    },
+};
+use log::warn;
+
+// The `ReplayClientState` replays a recorded exchange against a live server
+// with `ReplaySendsState`, keeps the report and halts. A failed replay is
+// restarted at the next tick, up to `max_attempts` replays.
+
+// This model depends on the `ReplaySendsState` model.
+impl RegisterModel for ReplayClientState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<ReplaySendsState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for ReplayClientState {
+    type Action = ReplayClientAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ReplayClientAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                let ReplayClientState { ready, .. } = state.substate();
+
+                if !ready {
+                    return dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| ReplayClientAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| ReplayClientAction::InitError { instance, error }),
+                    });
+                }
+
+                let uid = state.new_uid();
+                let client_state: &mut ReplayClientState = state.substate_mut();
+                let timeout = Timeout::Millis(client_state.config.poll_timeout);
+
+                if !client_state.replaying {
+                    client_state.replaying = true;
+                    client_state.attempts += 1;
+                    dispatcher.dispatch(ReplaySendsAction::Replay {
+                        uid,
+                        address: client_state.config.address.clone(),
+                        exchange: client_state.config.exchange.clone(),
+                        compare: client_state.config.compare,
+                        timeout: Timeout::Millis(client_state.config.timeout),
+                        on_success: callback!(|(uid: Uid, report: ReplayReport)| ReplayClientAction::ReplaySuccess { uid, report }),
+                        on_error: callback!(|(uid: Uid, error: String)| ReplayClientAction::ReplayError { uid, error }),
+                    });
+                }
+
+                dispatcher.dispatch(TcpClientAction::Poll {
+                    uid: state.new_uid(),
+                    timeout,
+                    on_success: callback!(|(uid: Uid, events: TcpPollEvents)| ReplayClientAction::PollSuccess { uid, events }),
+                    on_error: callback!(|(uid: Uid, error: String)| ReplayClientAction::PollError { uid, error }),
+                })
+            }
+            ReplayClientAction::InitSuccess { .. } => {
+                state.substate_mut::<ReplayClientState>().ready = true
+            }
+            ReplayClientAction::InitError { error, .. } => {
+                panic!("Client initialization failed: {}", error)
+            }
+            ReplayClientAction::PollSuccess { .. } => (),
+            ReplayClientAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            ReplayClientAction::ReplaySuccess { report, .. } => {
+                state.substate_mut::<ReplayClientState>().report = Some(report);
+                dispatcher.halt()
+            }
+            ReplayClientAction::ReplayError { uid, error } => {
+                let client_state: &mut ReplayClientState = state.substate_mut();
+
+                if client_state.attempts == client_state.config.max_attempts {
+                    panic!("Replay {:?} failed: {}", uid, error)
+                }
+
+                warn!("Replay {:?} failed, restarting: {}", uid, error);
+                client_state.replaying = false;
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::models::pure::net::replay_sends::state::{RecordedOperation, ReplayReport};
+
+#[derive(Debug)]
+pub struct ReplayClientConfig {
+    pub address: String,
+    pub exchange: Vec<RecordedOperation>,
+    pub compare: bool,
+    pub poll_timeout: u64,
+    pub timeout: u64,
+    // The server might not be listening yet: failed replays are restarted
+    // (on a new connection) up to `max_attempts` replays in total.
+    pub max_attempts: usize,
+}
+
+#[derive(Debug)]
+pub struct ReplayClientState {
+    pub config: ReplayClientConfig,
+    pub ready: bool,
+    pub replaying: bool,
+    pub attempts: usize,
+    pub report: Option<ReplayReport>,
+}
+
+impl ReplayClientState {
+    pub fn from_config(config: ReplayClientConfig) -> Self {
+        Self {
+            config,
+            ready: false,
+            replaying: false,
+            attempts: 0,
+            report: None,
+        }
+    }
+}
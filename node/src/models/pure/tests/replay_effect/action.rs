@@ -0,0 +1,21 @@
+use crate::automaton::{
+    action::{Action, ActionKind},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "0f4b6a8e-2d1c-4a57-9e3b-7c5d8f1a2b64"]
+pub enum ReplayEffectAction {
+    Tick,
+    // Result of either effect.
+    Created { uid: Uid },
+    CreateError { uid: Uid, error: String },
+    // Result of the new effect.
+    EventsCreated { uid: Uid },
+}
+
+impl Action for ReplayEffectAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
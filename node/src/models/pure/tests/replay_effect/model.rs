@@ -0,0 +1,81 @@
+use super::{
+    action::ReplayEffectAction,
+    state::{ReplayEffect, ReplayEffectState},
+};
+use crate::{
+    automaton::{
+        action::Dispatcher,
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::effectful::mio::{action::MioEffectfulAction, state::MioState},
+};
+
+// Minimal model to test the replay of effect results. On its first tick it
+// dispatches the configured `ReplayEffect`, and halts on the next tick once
+// the result was received. Recording a session with one effect and replaying
+// it with the other feeds a recorded result to a different kind of effect.
+// With `new_effect` set, a second effect is dispatched along with the first.
+
+impl RegisterModel for ReplayEffectState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<MioState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for ReplayEffectState {
+    type Action = ReplayEffectAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ReplayEffectAction::Tick => {
+                let uid = state.new_uid();
+                let replay_state: &ReplayEffectState = state.substate();
+
+                if replay_state.created.is_some() {
+                    return dispatcher.halt();
+                }
+
+                match replay_state.effect {
+                    ReplayEffect::PollCreate => {
+                        dispatcher.dispatch_effect(MioEffectfulAction::PollCreate {
+                            poll: uid,
+                            on_success: callback!(|uid: Uid| ReplayEffectAction::Created { uid }),
+                            on_error: callback!(|(uid: Uid, error: String)| ReplayEffectAction::CreateError { uid, error }),
+                        })
+                    }
+                    ReplayEffect::EventsCreate => {
+                        dispatcher.dispatch_effect(MioEffectfulAction::EventsCreate {
+                            uid,
+                            capacity: 1,
+                            on_success: callback!(|uid: Uid| ReplayEffectAction::Created { uid }),
+                        })
+                    }
+                }
+
+                if state.substate::<ReplayEffectState>().new_effect {
+                    dispatcher.dispatch_effect(MioEffectfulAction::EventsCreate {
+                        uid: state.new_uid(),
+                        capacity: 1,
+                        on_success: callback!(|uid: Uid| ReplayEffectAction::EventsCreated { uid }),
+                    })
+                }
+            }
+            ReplayEffectAction::Created { uid } => {
+                state.substate_mut::<ReplayEffectState>().created = Some(uid)
+            }
+            ReplayEffectAction::EventsCreated { uid } => {
+                state.substate_mut::<ReplayEffectState>().events = Some(uid)
+            }
+            ReplayEffectAction::CreateError { uid, error } => {
+                panic!("Creation of {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::automaton::state::Uid;
+
+// The effect dispatched on the first tick. Both are `MioEffectfulAction`s
+// whose result is dispatched back as `ReplayEffectAction::Created`.
+#[derive(Debug)]
+pub enum ReplayEffect {
+    PollCreate,
+    EventsCreate,
+}
+
+#[derive(Debug)]
+pub struct ReplayEffectState {
+    pub effect: ReplayEffect,
+    // Also dispatch `MioEffectfulAction::EventsCreate` on the first tick, an
+    // effect that recordings made without it have no result for.
+    pub new_effect: bool,
+    pub created: Option<Uid>,
+    pub events: Option<Uid>,
+}
+
+impl ReplayEffectState {
+    pub fn new(effect: ReplayEffect) -> Self {
+        Self {
+            effect,
+            new_effect: false,
+            created: None,
+            events: None,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind},
+        state::Uid,
+    },
+    models::pure::net::tcp::action::TcpPollEvents,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "2c0f3a5e-8d61-4f0b-9a7e-5b1d3c6e4f27"]
+pub enum RetrySendClientAction {
+    Tick,
+    PollSuccess { uid: Uid, events: TcpPollEvents },
+    PollError { uid: Uid, error: String },
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    ConnectSuccess { connection: Uid },
+    ConnectTimeout { connection: Uid },
+    ConnectError { connection: Uid, error: String },
+    CloseEvent { connection: Uid },
+    SendSuccess { uid: Uid, connection: Uid },
+    SendTimeout { uid: Uid },
+    SendError { uid: Uid, error: String },
+}
+
+impl Action for RetrySendClientAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
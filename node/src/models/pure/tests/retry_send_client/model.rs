@@ -0,0 +1,154 @@
+use super::{
+    action::RetrySendClientAction,
+    state::{RetrySendClientState, RetrySendClientStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            retry_send::{action::RetrySendAction, state::RetrySendState},
+            tcp::{
+                action::{ConnectionEvent, TcpAction, TcpPollEvents},
+                state::TcpState,
+            },
+            tcp_client::action::TcpClientAction,
+        },
+        time::model::update_time,
+    },
+};
+
+// The `RetrySendClientState` model connects to `address` and waits for the
+// peer to close the connection. It then sends `data` with `RetrySendState`:
+// the first attempt fails, and the data is delivered over a new connection.
+
+// This model depends on `RetrySendState`.
+impl RegisterModel for RetrySendClientState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<RetrySendState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for RetrySendClientState {
+    type Action = RetrySendClientAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            RetrySendClientAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                let RetrySendClientState {
+                    status,
+                    poll_timeout,
+                    connection,
+                    ..
+                } = state.substate();
+                let timeout = Timeout::Millis(*poll_timeout);
+
+                if *status == RetrySendClientStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| RetrySendClientAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| RetrySendClientAction::InitError { instance, error }),
+                    });
+                    return;
+                }
+
+                let peer_closed = *status == RetrySendClientStatus::Connected
+                    && matches!(
+                        state
+                            .substate::<TcpState>()
+                            .get_connection(&connection.unwrap())
+                            .events,
+                        Some(ConnectionEvent::Closed)
+                    );
+
+                if peer_closed {
+                    let client_state: &mut RetrySendClientState = state.substate_mut();
+
+                    client_state.status = RetrySendClientStatus::Sending;
+
+                    let connection = client_state.connection.unwrap();
+                    let address = client_state.address.clone();
+                    let data = client_state.data.clone().into();
+
+                    dispatcher.dispatch(RetrySendAction::Send {
+                        uid: state.new_uid(),
+                        connection,
+                        address,
+                        data,
+                        timeout: Timeout::Millis(1000),
+                        max_attempts: 2,
+                        on_success: callback!(|(uid: Uid, connection: Uid)| RetrySendClientAction::SendSuccess { uid, connection }),
+                        on_timeout: callback!(|uid: Uid| RetrySendClientAction::SendTimeout { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| RetrySendClientAction::SendError { uid, error }),
+                        on_close: callback!(|connection: Uid| RetrySendClientAction::CloseEvent { connection }),
+                    });
+                }
+
+                dispatcher.dispatch(TcpClientAction::Poll {
+                    uid: state.new_uid(),
+                    timeout,
+                    on_success: callback!(|(uid: Uid, events: TcpPollEvents)| RetrySendClientAction::PollSuccess { uid, events }),
+                    on_error: callback!(|(uid: Uid, error: String)| RetrySendClientAction::PollError { uid, error }),
+                })
+            }
+            RetrySendClientAction::PollSuccess { .. } => (),
+            RetrySendClientAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            RetrySendClientAction::InitSuccess { .. } => {
+                let client_state: &mut RetrySendClientState = state.substate_mut();
+                let address = client_state.address.clone();
+
+                client_state.status = RetrySendClientStatus::Connecting;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| RetrySendClientAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| RetrySendClientAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| RetrySendClientAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| RetrySendClientAction::CloseEvent { connection }),
+                });
+            }
+            RetrySendClientAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            RetrySendClientAction::ConnectSuccess { connection } => {
+                let client_state: &mut RetrySendClientState = state.substate_mut();
+
+                client_state.status = RetrySendClientStatus::Connected;
+                client_state.connection = Some(connection);
+            }
+            RetrySendClientAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            RetrySendClientAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            RetrySendClientAction::CloseEvent { .. } => (),
+            RetrySendClientAction::SendSuccess { uid, connection } => {
+                state.substate_mut::<RetrySendClientState>().sent = Some((uid, connection))
+            }
+            RetrySendClientAction::SendTimeout { uid } => {
+                panic!("Send {:?} timed out", uid)
+            }
+            RetrySendClientAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RetrySendClientStatus {
+    Init,
+    Connecting,
+    Connected,
+    Sending,
+}
+
+#[derive(Debug)]
+pub struct RetrySendClientState {
+    pub status: RetrySendClientStatus,
+    pub address: String,
+    pub poll_timeout: u64,
+    pub data: Vec<u8>,
+    pub connection: Option<Uid>,
+    // (uid, connection) as passed to `RetrySendAction::Send`'s `on_success`.
+    pub sent: Option<(Uid, Uid)>,
+}
+
+impl RetrySendClientState {
+    pub fn new(address: String, poll_timeout: u64, data: Vec<u8>) -> Self {
+        Self {
+            status: RetrySendClientStatus::Init,
+            address,
+            poll_timeout,
+            data,
+            connection: None,
+            sent: None,
+        }
+    }
+}
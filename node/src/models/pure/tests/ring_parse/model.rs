@@ -0,0 +1,248 @@
+use super::{
+    action::RingParseAction,
+    state::{RingParseState, RingParseStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            ring_buffer::RingBuffer,
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `RingParseState` model connects to its own listener and sends
+// `messages`, each prefixed by its length byte, to the server in a single
+// write. The server receives them `chunk` bytes at a time into the
+// connection's ring with `TcpServerAction::RecvIntoRing`, and parses the
+// complete messages after each recv: messages can straddle the recv
+// boundaries.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for RingParseState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for RingParseState {
+    type Action = RingParseAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            RingParseAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<RingParseState>().status == RingParseStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| RingParseAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| RingParseAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| RingParseAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| RingParseAction::PollError { uid, error }),
+                    })
+                }
+            }
+            RingParseAction::PollSuccess { .. } => (),
+            RingParseAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            RingParseAction::InitSuccess { .. } => {
+                let address = state.substate::<RingParseState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| RingParseAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| RingParseAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| RingParseAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| RingParseAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| RingParseAction::ListenerCloseEvent { listener }),
+                });
+            }
+            RingParseAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            RingParseAction::InitListenerSuccess { .. } => {
+                let ring_state: &mut RingParseState = state.substate_mut();
+                let address = ring_state.address.clone();
+
+                ring_state.status = RingParseStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| RingParseAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| RingParseAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| RingParseAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| RingParseAction::ConnectClose { connection }),
+                });
+            }
+            RingParseAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            RingParseAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<RingParseState>().server_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            RingParseAction::ConnectSuccess { connection } => {
+                state.substate_mut::<RingParseState>().client_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            RingParseAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            RingParseAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            // All the messages were sent at once, receive the first chunk.
+            RingParseAction::SendSuccess { .. } => recv_into_ring(state, dispatcher),
+            RingParseAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            RingParseAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            RingParseAction::RingRecvSuccess { buffered, .. } => {
+                let connection = state
+                    .substate::<RingParseState>()
+                    .server_connection
+                    .unwrap();
+                let ring = state.substate_mut::<TcpServerState>().ring_mut(&connection);
+
+                assert_eq!(ring.len(), buffered);
+
+                let parsed = parse_messages(ring);
+                let ring_state: &mut RingParseState = state.substate_mut();
+
+                ring_state.parsed.extend(parsed);
+                ring_state.ring_progress.push(ring_state.parsed.len());
+
+                if ring_state.parsed.len() < ring_state.messages.len() {
+                    recv_into_ring(state, dispatcher)
+                } else {
+                    // Nothing is left over past the last message.
+                    assert!(state
+                        .substate_mut::<TcpServerState>()
+                        .ring_mut(&connection)
+                        .is_empty());
+                }
+            }
+            RingParseAction::RingRecvTimeout { uid, buffered } => {
+                panic!(
+                    "RecvIntoRing {:?} timeout ({} bytes buffered)",
+                    uid, buffered
+                )
+            }
+            RingParseAction::RingRecvError { uid, error } => {
+                panic!("RecvIntoRing {:?} failed: {}", uid, error)
+            }
+            RingParseAction::ListenerCloseEvent { .. }
+            | RingParseAction::CloseEvent { .. }
+            | RingParseAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sends all the `messages` to the server once the connection is established
+// on both ends.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let RingParseState {
+        messages,
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let connection = *connection;
+    let data: Vec<u8> = messages
+        .iter()
+        .flat_map(|message| [vec![message.len() as u8], message.clone()].concat())
+        .collect();
+
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid: state.new_uid(),
+        connection,
+        data: data.into(),
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|uid: Uid| RingParseAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| RingParseAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| RingParseAction::SendError { uid, error }),
+    });
+}
+
+fn recv_into_ring<Substate: ModelState>(state: &mut State<Substate>, dispatcher: &mut Dispatcher) {
+    let ring_state: &RingParseState = state.substate();
+    let (connection, count) = (ring_state.server_connection.unwrap(), ring_state.chunk);
+
+    dispatcher.dispatch(TcpServerAction::RecvIntoRing {
+        uid: state.new_uid(),
+        connection,
+        count,
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|(uid: Uid, buffered: usize)| RingParseAction::RingRecvSuccess { uid, buffered }),
+        on_timeout: callback!(|(uid: Uid, buffered: usize)| RingParseAction::RingRecvTimeout { uid, buffered }),
+        on_error: callback!(|(uid: Uid, error: String)| RingParseAction::RingRecvError { uid, error }),
+    });
+}
+
+// Consumes the complete (length-prefixed) messages buffered in `ring`.
+fn parse_messages(ring: &mut RingBuffer) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+
+    loop {
+        let buffered = ring.peek();
+        let Some(&len) = buffered.first() else {
+            break;
+        };
+        let len = len as usize;
+
+        if buffered.len() <= len {
+            break;
+        }
+
+        messages.push(buffered[1..=len].to_vec());
+        ring.consume(1 + len);
+    }
+
+    messages
+}
@@ -0,0 +1,37 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RingParseStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct RingParseState {
+    pub status: RingParseStatus,
+    pub address: String,
+    // Sent to the server in a single write, each prefixed by its length byte.
+    pub messages: Vec<Vec<u8>>,
+    // How many bytes the server receives into the connection's ring at a time.
+    pub chunk: usize,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    // Messages parsed from the ring, and how many after each recv.
+    pub parsed: Vec<Vec<u8>>,
+    pub ring_progress: Vec<usize>,
+}
+
+impl RingParseState {
+    pub fn new(address: String, messages: Vec<Vec<u8>>, chunk: usize) -> Self {
+        Self {
+            status: RingParseStatus::Init,
+            address,
+            messages,
+            chunk,
+            client_connection: None,
+            server_connection: None,
+            parsed: Vec::new(),
+            ring_progress: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use super::{
+    action::SendToGroupAction,
+    state::{SendToGroupState, SendToGroupStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::{action::TcpAction, state::TcpState},
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `SendToGroupState` model connects to its own listener three times and
+// adds the first two server connections to a group, then sends `data` to the
+// group: only the client ends of its members receive it. It then closes the
+// group, which closes the members' connections, and halts once both are
+// closed. The connection outside the group is closed on shutdown.
+
+// This model depends on `TcpServerState` and `TcpClientState`.
+impl RegisterModel for SendToGroupState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for SendToGroupState {
+    type Action = SendToGroupAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            SendToGroupAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<SendToGroupState>().status == SendToGroupStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| SendToGroupAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| SendToGroupAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| SendToGroupAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| SendToGroupAction::PollError { uid, error }),
+                    })
+                }
+            }
+            SendToGroupAction::PollSuccess { .. } => (),
+            SendToGroupAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            SendToGroupAction::InitSuccess { .. } => {
+                let address = state.substate::<SendToGroupState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 3,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| SendToGroupAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| SendToGroupAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| SendToGroupAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| SendToGroupAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| SendToGroupAction::ListenerCloseEvent { listener }),
+                });
+            }
+            SendToGroupAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            SendToGroupAction::InitListenerSuccess { .. } => {
+                state.substate_mut::<SendToGroupState>().status = SendToGroupStatus::Listening;
+                connect(state, dispatcher)
+            }
+            SendToGroupAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            SendToGroupAction::ConnectionEvent { connection, .. } => {
+                let group_state: &mut SendToGroupState = state.substate_mut();
+
+                group_state.server_connections.push(connection);
+
+                // The next client is connected once the previous one is
+                // accepted.
+                if group_state.server_connections.len() < 3 {
+                    connect(state, dispatcher)
+                } else {
+                    send_when_connected(state, dispatcher)
+                }
+            }
+            SendToGroupAction::ConnectSuccess { connection } => {
+                state
+                    .substate_mut::<SendToGroupState>()
+                    .client_connections
+                    .push(connection);
+                send_when_connected(state, dispatcher)
+            }
+            SendToGroupAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            SendToGroupAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            SendToGroupAction::GroupSendComplete { failed, .. } => {
+                assert!(failed.is_empty(), "Group send failed for {:?}", failed);
+
+                let group_state: &SendToGroupState = state.substate();
+                let count = group_state.data.len();
+
+                // The client end of the connection outside the group times out.
+                for connection in group_state.client_connections.clone() {
+                    let uid = state.new_uid();
+
+                    state
+                        .substate_mut::<SendToGroupState>()
+                        .recvs
+                        .insert(uid, (connection, None));
+                    dispatcher.dispatch(TcpClientAction::Recv {
+                        uid,
+                        connection,
+                        count,
+                        timeout: Timeout::Millis(300),
+                        on_success: callback!(|(uid: Uid, data: Vec<u8>)| SendToGroupAction::RecvSuccess { uid, data }),
+                        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| SendToGroupAction::RecvTimeout { uid, partial_data }),
+                        on_error: callback!(|(uid: Uid, error: String)| SendToGroupAction::RecvError { uid, error }),
+                    });
+                }
+            }
+            SendToGroupAction::RecvSuccess { uid, data }
+            | SendToGroupAction::RecvTimeout {
+                uid,
+                partial_data: data,
+            } => {
+                let group_state: &mut SendToGroupState = state.substate_mut();
+                let (_, received) = group_state.recvs.get_mut(&uid).expect("unexpected recv");
+
+                *received = Some(data);
+
+                if group_state
+                    .recvs
+                    .values()
+                    .all(|(_, received)| received.is_some())
+                {
+                    received_all(state, dispatcher)
+                }
+            }
+            SendToGroupAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            SendToGroupAction::CloseEvent { connection, .. } => {
+                let group_state: &mut SendToGroupState = state.substate_mut();
+
+                group_state.closed_connections.push(connection);
+
+                // The connection outside the group is closed on shutdown.
+                if group_state.closed_connections.len() == 2 {
+                    // The members left the group.
+                    assert!(state
+                        .substate::<TcpServerState>()
+                        .group_members(GROUP)
+                        .is_empty());
+                    dispatcher.halt()
+                }
+            }
+            SendToGroupAction::ListenerCloseEvent { .. }
+            | SendToGroupAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+const GROUP: &str = "room";
+
+fn connect<Substate: ModelState>(state: &mut State<Substate>, dispatcher: &mut Dispatcher) {
+    let address = state.substate::<SendToGroupState>().address.clone();
+
+    dispatcher.dispatch(TcpClientAction::Connect {
+        connection: state.new_uid(),
+        address,
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|connection: Uid| SendToGroupAction::ConnectSuccess { connection }),
+        on_timeout: callback!(|connection: Uid| SendToGroupAction::ConnectTimeout { connection }),
+        on_error: callback!(|(connection: Uid, error: String)| SendToGroupAction::ConnectError { connection, error }),
+        on_close: callback!(|connection: Uid| SendToGroupAction::ConnectClose { connection }),
+    });
+}
+
+// Once all three connections are established on both ends, the first two
+// server connections join the group, and `data` is sent to the group.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let group_state: &SendToGroupState = state.substate();
+
+    if group_state.server_connections.len() < 3 || group_state.client_connections.len() < 3 {
+        return;
+    }
+
+    let data = group_state.data.clone();
+    let members = group_state.server_connections[..2].to_vec();
+    let server_state: &mut TcpServerState = state.substate_mut();
+
+    for &connection in members.iter() {
+        server_state.add_to_group(connection, GROUP);
+    }
+
+    assert_eq!(server_state.group_members(GROUP), members);
+    dispatcher.dispatch(TcpServerAction::SendToGroup {
+        uid: state.new_uid(),
+        group: GROUP.to_string(),
+        data: data.into(),
+        timeout: Timeout::Millis(1000),
+        on_complete: callback!(|(uid: Uid, failed: Vec<Uid>)| SendToGroupAction::GroupSendComplete { uid, failed }),
+    });
+}
+
+// All the client ends received what was sent to the group (or timed out).
+// Their server ends must be the group members, then the group is closed.
+fn received_all<Substate: ModelState>(state: &State<Substate>, dispatcher: &mut Dispatcher) {
+    let group_state: &SendToGroupState = state.substate();
+    let tcp_state: &TcpState = state.substate();
+    let members = state.substate::<TcpServerState>().group_members(GROUP);
+
+    for (connection, received) in group_state.recvs.values() {
+        let (local, _) = tcp_state
+            .connection_addrs(connection)
+            .expect("client connection addresses not recorded");
+        let server_end = *group_state
+            .server_connections
+            .iter()
+            .find(|server_end| {
+                tcp_state
+                    .connection_addrs(server_end)
+                    .is_some_and(|(_, peer)| peer == local)
+            })
+            .expect("server end not found");
+
+        if members.contains(&server_end) {
+            assert_eq!(received.as_ref(), Some(&group_state.data));
+        } else {
+            assert_eq!(received.as_deref(), Some(&[][..]));
+        }
+    }
+
+    dispatcher.dispatch(TcpServerAction::CloseGroup {
+        group: GROUP.to_string(),
+        deliver_buffered: false,
+    })
+}
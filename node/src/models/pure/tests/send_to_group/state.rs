@@ -0,0 +1,37 @@
+use crate::automaton::state::Uid;
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SendToGroupStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct SendToGroupState {
+    pub status: SendToGroupStatus,
+    pub address: String,
+    // Sent to the group.
+    pub data: Vec<u8>,
+    // All the server connections, and the closed ones.
+    pub server_connections: Vec<Uid>,
+    pub closed_connections: Vec<Uid>,
+    pub client_connections: Vec<Uid>,
+    // (client connection, data received) of the recvs issued after sending to
+    // the group. The data is set once the recv completes or times out.
+    pub recvs: BTreeMap<Uid, (Uid, Option<Vec<u8>>)>,
+}
+
+impl SendToGroupState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: SendToGroupStatus::Init,
+            address,
+            data,
+            server_connections: Vec::new(),
+            closed_connections: Vec::new(),
+            client_connections: Vec::new(),
+            recvs: BTreeMap::new(),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::automaton::action::{Action, ActionKind};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "66b99d5e-14b1-49f4-a659-3454c70c7d40"]
+pub enum SharedConfigAction {
+    Tick,
+}
+
+impl Action for SharedConfigAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "3f386deb-a965-4907-b984-afc6d7fe1587"]
+pub enum SharedConfigPeerAction {
+    Read,
+}
+
+impl Action for SharedConfigPeerAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
@@ -0,0 +1,68 @@
+use super::{
+    action::{SharedConfigAction, SharedConfigPeerAction},
+    state::{ChaosConfig, SharedConfigPeerState, SharedConfigState},
+};
+use crate::automaton::{
+    action::Dispatcher,
+    model::PureModel,
+    runner::{RegisterModel, RunnerBuilder},
+    state::{ModelState, State},
+};
+
+// Minimal models reading the same `ChaosConfig` from the shared settings (see
+// `State::config`). On its first tick, `SharedConfigState` reads it and asks
+// `SharedConfigPeerState` to do the same, then halts on the next tick.
+
+impl RegisterModel for SharedConfigState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<SharedConfigPeerState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl RegisterModel for SharedConfigPeerState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.model_pure::<Self>()
+    }
+}
+
+impl PureModel for SharedConfigState {
+    type Action = SharedConfigAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        let SharedConfigAction::Tick = action;
+        let config = state.config::<ChaosConfig>().clone();
+        let shared_state: &mut SharedConfigState = state.substate_mut();
+
+        shared_state.ticks += 1;
+
+        if shared_state.ticks == 1 {
+            shared_state.read.push(("shared_config", config));
+            dispatcher.dispatch(SharedConfigPeerAction::Read);
+        } else {
+            dispatcher.halt()
+        }
+    }
+}
+
+impl PureModel for SharedConfigPeerState {
+    type Action = SharedConfigPeerAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        _action: Self::Action,
+        _dispatcher: &mut Dispatcher,
+    ) {
+        let config = state.config::<ChaosConfig>().clone();
+
+        state
+            .substate_mut::<SharedConfigState>()
+            .read
+            .push(("shared_config_peer", config))
+    }
+}
@@ -0,0 +1,22 @@
+// A cross-cutting setting, shared through `State::config`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChaosConfig {
+    pub enabled: bool,
+}
+
+#[derive(Default, Debug)]
+pub struct SharedConfigState {
+    pub ticks: u64,
+    // The settings read by each model, in reading order.
+    pub read: Vec<(&'static str, ChaosConfig)>,
+}
+
+impl SharedConfigState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// Has no state of its own, records what it reads in `SharedConfigState`.
+#[derive(Debug)]
+pub struct SharedConfigPeerState;
@@ -0,0 +1,24 @@
+use crate::automaton::action::{Action, ActionKind};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "9b3e6d42-71c5-4a8f-b0d2-5e4c8a1f7d63"]
+pub enum ShutdownLowerAction {
+    // Dispatched by `ShutdownUpperState` from its `on_shutdown` hook.
+    Flush,
+}
+
+impl Action for ShutdownLowerAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "c41f8a27-3d96-4e0b-8a5c-2b7e9f1d6a48"]
+pub enum ShutdownUpperAction {
+    Tick,
+}
+
+impl Action for ShutdownUpperAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
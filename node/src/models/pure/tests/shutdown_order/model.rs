@@ -0,0 +1,78 @@
+use super::{
+    action::{ShutdownLowerAction, ShutdownUpperAction},
+    state::{ShutdownLowerState, ShutdownUpperState},
+};
+use crate::automaton::{
+    action::Dispatcher,
+    model::PureModel,
+    runner::{RegisterModel, RunnerBuilder},
+    state::{ModelState, State},
+};
+
+// Minimal models to test the `on_shutdown` hooks. `ShutdownUpperState` depends
+// on `ShutdownLowerState` and halts on its first tick. When shut down, it
+// dispatches a final action to `ShutdownLowerState`, which must be processed
+// before `ShutdownLowerState` itself is shut down.
+
+impl RegisterModel for ShutdownLowerState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.model_pure::<Self>()
+    }
+}
+
+impl PureModel for ShutdownLowerState {
+    type Action = ShutdownLowerAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        _dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ShutdownLowerAction::Flush => state
+                .substate_mut::<ShutdownLowerState>()
+                .log
+                .push("lower: flush"),
+        }
+    }
+
+    fn on_shutdown<Substate: ModelState>(
+        state: &mut State<Substate>,
+        _dispatcher: &mut Dispatcher,
+    ) {
+        state
+            .substate_mut::<ShutdownLowerState>()
+            .log
+            .push("lower: shutdown")
+    }
+}
+
+impl RegisterModel for ShutdownUpperState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<ShutdownLowerState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for ShutdownUpperState {
+    type Action = ShutdownUpperAction;
+
+    fn process_pure<Substate: ModelState>(
+        _state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            ShutdownUpperAction::Tick => dispatcher.halt(),
+        }
+    }
+
+    fn on_shutdown<Substate: ModelState>(state: &mut State<Substate>, dispatcher: &mut Dispatcher) {
+        state
+            .substate_mut::<ShutdownLowerState>()
+            .log
+            .push("upper: shutdown");
+        dispatcher.dispatch(ShutdownLowerAction::Flush)
+    }
+}
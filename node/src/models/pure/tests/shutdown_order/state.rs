@@ -0,0 +1,8 @@
+#[derive(Default, Debug)]
+pub struct ShutdownLowerState {
+    // What happened during the runner's shutdown, in order.
+    pub log: Vec<&'static str>,
+}
+
+#[derive(Default, Debug)]
+pub struct ShutdownUpperState();
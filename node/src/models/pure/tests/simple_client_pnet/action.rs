@@ -12,6 +12,7 @@ use type_uuid::TypeUuid;
 #[uuid = "fd4da055-71d2-4484-9009-93d5a7924a23"]
 pub enum PnetSimpleClientAction {
     Tick,
+    LoadConfigResult { uid: Uid, result: Result<Vec<u8>, String> },
     PollSuccess { uid: Uid, events: TcpPollEvents },
     PollError { uid: Uid, error: String },
     InitSuccess { instance: Uid },
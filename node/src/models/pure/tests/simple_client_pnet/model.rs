@@ -10,9 +10,13 @@ use crate::{
         state::{ModelState, State, Uid},
     },
     callback,
+    models::effectful::config::{action::ConfigEffectfulAction, state::ConfigLoaderState},
     models::pure::{
         net::{
-            pnet::client::{action::PnetClientAction, state::PnetClientState},
+            pnet::{
+                client::{action::PnetClientAction, state::PnetClientState},
+                common::PnetKey,
+            },
             tcp::action::{TcpAction, TcpPollEvents},
         },
         prng::state::PRNGState,
@@ -26,6 +30,7 @@ impl RegisterModel for PnetSimpleClientState {
     fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
         builder
             .register::<PRNGState>()
+            .register::<ConfigLoaderState>()
             .register::<PnetClientState>()
             .model_pure::<Self>()
     }
@@ -49,15 +54,32 @@ impl PureModel for PnetSimpleClientState {
 
                 let PnetSimpleClientState {
                     status,
-                    config: PnetSimpleClientConfig { poll_timeout, .. },
+                    config:
+                        PnetSimpleClientConfig {
+                            poll_timeout,
+                            config_file,
+                            ..
+                        },
                     ..
                 } = state.substate_mut();
 
                 match status {
+                    ClientStatus::LoadConfig => {
+                        let path = config_file.clone().expect("No config file to load");
+
+                        *status = ClientStatus::LoadingConfig;
+                        dispatcher.dispatch_effect(ConfigEffectfulAction::LoadFile {
+                            uid: state.new_uid(),
+                            path,
+                            on_result: callback!(|(uid: Uid, result: Result<Vec<u8>, String>)| PnetSimpleClientAction::LoadConfigResult { uid, result }),
+                        })
+                    }
+                    ClientStatus::LoadingConfig => (),
                     ClientStatus::Init => {
                         // Init TCP model
                         dispatcher.dispatch(TcpAction::Init {
                             instance: state.new_uid(),
+                            events_capacity: None,
                             on_success: callback!(|instance: Uid| PnetSimpleClientAction::InitSuccess { instance }),
                             on_error: callback!(|(instance: Uid, error: String)| PnetSimpleClientAction::InitError { instance, error }),
                         })
@@ -78,6 +100,43 @@ impl PureModel for PnetSimpleClientState {
                     ClientStatus::TestCompleted => unreachable!(),
                 }
             }
+            PnetSimpleClientAction::LoadConfigResult { result, .. } => {
+                let data = result.unwrap_or_else(|error| panic!("Config loading failed: {}", error));
+                let config = String::from_utf8(data).expect("Config file is not valid UTF-8");
+
+                for line in config.lines().map(str::trim) {
+                    if line.is_empty() || line.starts_with('#') {
+                        continue;
+                    }
+
+                    let (key, value) = line
+                        .split_once('=')
+                        .map(|(key, value)| (key.trim(), value.trim()))
+                        .expect(&format!("Invalid config line: {}", line));
+
+                    match key {
+                        "chain_id" => {
+                            state.substate_mut::<PnetClientState>().config.pnet_key =
+                                PnetKey::new(value)
+                        }
+                        "address" => {
+                            state
+                                .substate_mut::<PnetSimpleClientState>()
+                                .config
+                                .connect_to_address = value.to_string()
+                        }
+                        _ => panic!("Unknown config key: {}", key),
+                    }
+                }
+
+                let client_state: &mut PnetSimpleClientState = state.substate_mut();
+
+                if let ClientStatus::LoadingConfig = client_state.status {
+                    client_state.status = ClientStatus::Init;
+                } else {
+                    unreachable!()
+                }
+            }
             PnetSimpleClientAction::InitSuccess { .. } => {
                 let connection = state.new_uid();
                 let client_state: &mut PnetSimpleClientState = state.substate_mut();
@@ -2,6 +2,9 @@ use crate::automaton::{action::Timeout, state::Uid};
 
 #[derive(Debug)]
 pub struct PnetSimpleClientConfig {
+    // Optional file overriding `connect_to_address` and the pnet key, with
+    // `address = <host:port>` and `chain_id = <id>` lines.
+    pub config_file: Option<String>,
     pub connect_to_address: String,
     pub connect_timeout: Timeout,
     pub poll_timeout: u64,
@@ -14,6 +17,8 @@ pub struct PnetSimpleClientConfig {
 
 #[derive(Debug)]
 pub enum ClientStatus {
+    LoadConfig,
+    LoadingConfig,
     Init,
     Connecting,
     Connected { connection: Uid },
@@ -31,8 +36,14 @@ pub struct PnetSimpleClientState {
 
 impl PnetSimpleClientState {
     pub fn from_config(config: PnetSimpleClientConfig) -> Self {
+        let status = if config.config_file.is_some() {
+            ClientStatus::LoadConfig
+        } else {
+            ClientStatus::Init
+        };
+
         Self {
-            status: ClientStatus::Init,
+            status,
             connection_attempt: 0,
             config,
         }
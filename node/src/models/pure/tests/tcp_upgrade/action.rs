@@ -0,0 +1,33 @@
+use crate::automaton::{
+    action::{Action, ActionKind},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "78591547-5ff6-4ab6-8dd7-9a4e7ef99dc7"]
+pub enum TcpUpgradeAction {
+    Tick,
+    PollSuccess { uid: Uid },
+    PollError { uid: Uid, error: String },
+    InitSuccess { instance: Uid },
+    InitError { instance: Uid, error: String },
+    InitListenerSuccess { listener: Uid },
+    InitListenerError { listener: Uid, error: String },
+    ListenerCloseEvent { listener: Uid },
+    ConnectionEvent { listener: Uid, connection: Uid },
+    CloseEvent { listener: Uid, connection: Uid },
+    RequestSuccess { uid: Uid, data: Vec<u8> },
+    RequestTimeout { uid: Uid, partial_data: Vec<u8> },
+    RecvError { uid: Uid, error: String },
+    // Handler the connection is upgraded to.
+    Upgraded { connection: Uid, leftover: Vec<u8> },
+    UpgradedCloseEvent { listener: Uid, connection: Uid },
+    UpgradedRecvSuccess { uid: Uid, data: Vec<u8> },
+    UpgradedRecvTimeout { uid: Uid, partial_data: Vec<u8> },
+}
+
+impl Action for TcpUpgradeAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
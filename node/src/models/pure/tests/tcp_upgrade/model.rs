@@ -0,0 +1,193 @@
+use super::{
+    action::TcpUpgradeAction,
+    state::{TcpUpgradeState, TcpUpgradeStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_server::{
+                action::{ConnectionHandler, RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `TcpUpgradeState` model listens for a connection and receives a request
+// ending with an empty line (like an HTTP upgrade request). It then upgrades
+// the connection to a new handler with `TcpServerAction::Upgrade`, passing it
+// the bytes received past the request. The new handler keeps receiving on the
+// connection.
+
+// This model depends on `TcpServerState`.
+impl RegisterModel for TcpUpgradeState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpServerState>().model_pure::<Self>()
+    }
+}
+
+const REQUEST_END: &[u8] = b"\r\n\r\n";
+
+impl PureModel for TcpUpgradeState {
+    type Action = TcpUpgradeAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            TcpUpgradeAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                let TcpUpgradeState {
+                    status,
+                    poll_timeout,
+                    ..
+                } = state.substate();
+
+                match status {
+                    TcpUpgradeStatus::Init => dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| TcpUpgradeAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| TcpUpgradeAction::InitError { instance, error }),
+                    }),
+                    TcpUpgradeStatus::Listening => {
+                        let timeout = Timeout::Millis(*poll_timeout);
+
+                        dispatcher.dispatch(TcpServerAction::Poll {
+                            uid: state.new_uid(),
+                            timeout,
+                            on_success: callback!(|uid: Uid| TcpUpgradeAction::PollSuccess { uid }),
+                            on_error: callback!(|(uid: Uid, error: String)| TcpUpgradeAction::PollError { uid, error }),
+                        })
+                    }
+                }
+            }
+            TcpUpgradeAction::PollSuccess { .. } => (),
+            TcpUpgradeAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            TcpUpgradeAction::InitSuccess { .. } => {
+                let address = state.substate::<TcpUpgradeState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| TcpUpgradeAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| TcpUpgradeAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| TcpUpgradeAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| TcpUpgradeAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| TcpUpgradeAction::ListenerCloseEvent { listener }),
+                });
+            }
+            TcpUpgradeAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            TcpUpgradeAction::InitListenerSuccess { listener } => {
+                let upgrade_state: &mut TcpUpgradeState = state.substate_mut();
+
+                upgrade_state.status = TcpUpgradeStatus::Listening;
+                upgrade_state.listener = Some(listener);
+            }
+            TcpUpgradeAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            TcpUpgradeAction::ListenerCloseEvent { listener } => {
+                panic!("Listener {:?} closed", listener)
+            }
+            TcpUpgradeAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<TcpUpgradeState>().connection = Some(connection);
+
+                dispatcher.dispatch(TcpServerAction::RecvUntil {
+                    uid: state.new_uid(),
+                    connection,
+                    delimiter: REQUEST_END.to_vec(),
+                    max_bytes: 1024,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpUpgradeAction::RequestSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpUpgradeAction::RequestTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| TcpUpgradeAction::RecvError { uid, error }),
+                });
+            }
+            TcpUpgradeAction::CloseEvent { connection, .. } => {
+                panic!("Connection {:?} closed before the upgrade", connection)
+            }
+            TcpUpgradeAction::RequestSuccess { data, .. } => {
+                let connection = state.substate::<TcpUpgradeState>().connection.unwrap();
+                let end = data
+                    .windows(REQUEST_END.len())
+                    .position(|window| window == REQUEST_END)
+                    .expect("incomplete request")
+                    + REQUEST_END.len();
+
+                dispatcher.dispatch(TcpServerAction::Upgrade {
+                    connection,
+                    leftover: data[end..].to_vec(),
+                    new_handler: ConnectionHandler {
+                        on_upgrade: callback!(|(connection: Uid, leftover: Vec<u8>)| TcpUpgradeAction::Upgraded { connection, leftover }),
+                        on_connection_closed: callback!(|(listener: Uid, connection: Uid)| TcpUpgradeAction::UpgradedCloseEvent { listener, connection }),
+                    },
+                });
+            }
+            TcpUpgradeAction::RequestTimeout { uid, partial_data } => {
+                panic!("Request {:?} timed out: {:?}", uid, partial_data)
+            }
+            TcpUpgradeAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            TcpUpgradeAction::Upgraded {
+                connection,
+                leftover,
+            } => {
+                state.substate_mut::<TcpUpgradeState>().upgraded = Some((connection, leftover));
+                recv_upgraded(state, dispatcher, connection);
+            }
+            TcpUpgradeAction::UpgradedCloseEvent { .. } => (),
+            TcpUpgradeAction::UpgradedRecvSuccess { data, .. }
+            | TcpUpgradeAction::UpgradedRecvTimeout {
+                partial_data: data,
+                ..
+            } => {
+                let upgrade_state: &mut TcpUpgradeState = state.substate_mut();
+                let (connection, _) = upgrade_state.upgraded.clone().unwrap();
+
+                upgrade_state.received.extend(data);
+                recv_upgraded(state, dispatcher, connection);
+            }
+        }
+    }
+}
+
+fn recv_upgraded<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+    connection: Uid,
+) {
+    dispatcher.dispatch(TcpServerAction::Recv {
+        uid: state.new_uid(),
+        connection,
+        count: 1024,
+        timeout: Timeout::Millis(100),
+        on_success: callback!(|(uid: Uid, data: Vec<u8>)| TcpUpgradeAction::UpgradedRecvSuccess { uid, data }),
+        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpUpgradeAction::UpgradedRecvTimeout { uid, partial_data }),
+        on_error: callback!(|(uid: Uid, error: String)| TcpUpgradeAction::RecvError { uid, error }),
+    });
+}
@@ -0,0 +1,34 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug)]
+pub enum TcpUpgradeStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct TcpUpgradeState {
+    pub status: TcpUpgradeStatus,
+    pub address: String,
+    pub poll_timeout: u64,
+    pub listener: Option<Uid>,
+    pub connection: Option<Uid>,
+    // (connection, leftover) as handed to the new handler.
+    pub upgraded: Option<(Uid, Vec<u8>)>,
+    // Received by the new handler after the upgrade.
+    pub received: Vec<u8>,
+}
+
+impl TcpUpgradeState {
+    pub fn new(address: String, poll_timeout: u64) -> Self {
+        Self {
+            status: TcpUpgradeStatus::Init,
+            address,
+            poll_timeout,
+            listener: None,
+            connection: None,
+            upgraded: None,
+            received: Vec::new(),
+        }
+    }
+}
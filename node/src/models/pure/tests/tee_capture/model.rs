@@ -0,0 +1,239 @@
+use super::{
+    action::TeeCaptureAction,
+    state::{TeeCaptureState, TeeCaptureStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::TcpAction,
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+            tcp_server::{
+                action::{RoutingPolicy, TcpServerAction},
+                state::TcpServerState,
+            },
+            tee::{
+                action::{TeeAction, TeeSink, TeeTransport},
+                state::TeeState,
+            },
+        },
+        time::model::update_time,
+    },
+};
+
+// The `TeeCaptureState` model connects to its own listener and sends `data` to
+// the server, which receives it through `TeeState` (with an in-memory capture
+// sink) and echoes it back to the client. The captured data must be exactly
+// what the server received.
+
+// This model depends on `TcpServerState`, `TcpClientState` and `TeeState`.
+impl RegisterModel for TeeCaptureState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<TcpServerState>()
+            .register::<TcpClientState>()
+            .register::<TeeState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for TeeCaptureState {
+    type Action = TeeCaptureAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            TeeCaptureAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<TeeCaptureState>().status == TeeCaptureStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| TeeCaptureAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| TeeCaptureAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpServerAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|uid: Uid| TeeCaptureAction::PollSuccess { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| TeeCaptureAction::PollError { uid, error }),
+                    })
+                }
+            }
+            TeeCaptureAction::PollSuccess { .. } => (),
+            TeeCaptureAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            TeeCaptureAction::InitSuccess { .. } => {
+                let address = state.substate::<TeeCaptureState>().address.clone();
+
+                dispatcher.dispatch(TcpServerAction::New {
+                    listener: state.new_uid(),
+                    address,
+                    max_connections: 1,
+                    backlog: None,
+                    routing: RoutingPolicy::None,
+                    admission_control: None,
+                    nodelay: false,
+                    on_success: callback!(|listener: Uid| TeeCaptureAction::InitListenerSuccess { listener }),
+                    on_error: callback!(|(listener: Uid, error: String)| TeeCaptureAction::InitListenerError { listener, error }),
+                    on_new_connection: callback!(|(listener: Uid, connection: Uid)| TeeCaptureAction::ConnectionEvent { listener, connection }),
+                    on_connection_closed: callback!(|(listener: Uid, connection: Uid)| TeeCaptureAction::CloseEvent { listener, connection }),
+                    on_listener_closed: callback!(|listener: Uid| TeeCaptureAction::ListenerCloseEvent { listener }),
+                });
+            }
+            TeeCaptureAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            TeeCaptureAction::InitListenerSuccess { .. } => {
+                let tee_state: &mut TeeCaptureState = state.substate_mut();
+                let address = tee_state.address.clone();
+
+                tee_state.status = TeeCaptureStatus::Listening;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| TeeCaptureAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| TeeCaptureAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| TeeCaptureAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| TeeCaptureAction::ConnectClose { connection }),
+                });
+            }
+            TeeCaptureAction::InitListenerError { listener, error } => {
+                panic!("Listener {:?} initialization failed: {}", listener, error)
+            }
+            TeeCaptureAction::ConnectionEvent { connection, .. } => {
+                state.substate_mut::<TeeCaptureState>().server_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            TeeCaptureAction::ConnectSuccess { connection } => {
+                state.substate_mut::<TeeCaptureState>().client_connection = Some(connection);
+                send_when_connected(state, dispatcher)
+            }
+            TeeCaptureAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            TeeCaptureAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            TeeCaptureAction::SendSuccess { .. } => {
+                let tee_state: &TeeCaptureState = state.substate();
+                let count = tee_state.data.len();
+
+                if !tee_state.echoing {
+                    let connection = tee_state.server_connection.unwrap();
+
+                    dispatcher.dispatch(TeeAction::Recv {
+                        uid: state.new_uid(),
+                        transport: TeeTransport::TcpServer,
+                        connection,
+                        count,
+                        timeout: Timeout::Millis(1000),
+                        sink: TeeSink::Capture {
+                            capture: connection,
+                        },
+                        on_success: callback!(|(uid: Uid, data: Vec<u8>)| TeeCaptureAction::RecvSuccess { uid, data }),
+                        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TeeCaptureAction::RecvTimeout { uid, partial_data }),
+                        on_error: callback!(|(uid: Uid, error: String)| TeeCaptureAction::RecvError { uid, error }),
+                    });
+                } else {
+                    let connection = tee_state.client_connection.unwrap();
+
+                    // The echo was sent, receive it on the client end.
+                    dispatcher.dispatch(TcpClientAction::Recv {
+                        uid: state.new_uid(),
+                        connection,
+                        count,
+                        timeout: Timeout::Millis(1000),
+                        on_success: callback!(|(uid: Uid, data: Vec<u8>)| TeeCaptureAction::RecvSuccess { uid, data }),
+                        on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TeeCaptureAction::RecvTimeout { uid, partial_data }),
+                        on_error: callback!(|(uid: Uid, error: String)| TeeCaptureAction::RecvError { uid, error }),
+                    });
+                }
+            }
+            TeeCaptureAction::SendTimeout { uid } => {
+                panic!("Send {:?} timeout", uid)
+            }
+            TeeCaptureAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            TeeCaptureAction::RecvSuccess { data, .. } => {
+                let tee_state: &mut TeeCaptureState = state.substate_mut();
+                let connection = tee_state.server_connection.unwrap();
+
+                if !tee_state.echoing {
+                    tee_state.echoing = true;
+                    tee_state.delivered_data = Some(data.clone());
+                    dispatcher.dispatch(TcpServerAction::Send {
+                        uid: state.new_uid(),
+                        connection,
+                        data: data.into(),
+                        timeout: Timeout::Millis(1000),
+                        on_success: callback!(|uid: Uid| TeeCaptureAction::SendSuccess { uid }),
+                        on_timeout: callback!(|uid: Uid| TeeCaptureAction::SendTimeout { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| TeeCaptureAction::SendError { uid, error }),
+                    });
+                } else {
+                    assert_eq!(data, tee_state.data);
+                    assert_eq!(tee_state.delivered_data.as_ref(), Some(&tee_state.data));
+                    assert_eq!(
+                        state.substate::<TeeState>().capture(&connection),
+                        data.as_slice()
+                    );
+                    dispatcher.halt()
+                }
+            }
+            TeeCaptureAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timeout: {:?}", uid, partial_data)
+            }
+            TeeCaptureAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+            TeeCaptureAction::ListenerCloseEvent { .. }
+            | TeeCaptureAction::CloseEvent { .. }
+            | TeeCaptureAction::ConnectClose { .. } => (),
+        }
+    }
+}
+
+// Sends `data` to the server once the connection is established on both ends.
+fn send_when_connected<Substate: ModelState>(
+    state: &mut State<Substate>,
+    dispatcher: &mut Dispatcher,
+) {
+    let TeeCaptureState {
+        data,
+        client_connection: Some(connection),
+        server_connection: Some(_),
+        ..
+    } = state.substate()
+    else {
+        return;
+    };
+
+    let (connection, data) = (*connection, data.clone());
+
+    dispatcher.dispatch(TcpClientAction::Send {
+        uid: state.new_uid(),
+        connection,
+        data: data.into(),
+        timeout: Timeout::Millis(1000),
+        on_success: callback!(|uid: Uid| TeeCaptureAction::SendSuccess { uid }),
+        on_timeout: callback!(|uid: Uid| TeeCaptureAction::SendTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| TeeCaptureAction::SendError { uid, error }),
+    });
+}
@@ -0,0 +1,35 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TeeCaptureStatus {
+    Init,
+    Listening,
+}
+
+#[derive(Debug)]
+pub struct TeeCaptureState {
+    pub status: TeeCaptureStatus,
+    pub address: String,
+    // Sent by the client, then echoed back by the server.
+    pub data: Vec<u8>,
+    pub client_connection: Option<Uid>,
+    pub server_connection: Option<Uid>,
+    // Set once the server received `data` and echoes it.
+    pub echoing: bool,
+    // The data received by the server through `TeeState`.
+    pub delivered_data: Option<Vec<u8>>,
+}
+
+impl TeeCaptureState {
+    pub fn new(address: String, data: Vec<u8>) -> Self {
+        Self {
+            status: TeeCaptureStatus::Init,
+            address,
+            data,
+            client_connection: None,
+            server_connection: None,
+            echoing: false,
+            delivered_data: None,
+        }
+    }
+}
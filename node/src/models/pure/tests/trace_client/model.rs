@@ -0,0 +1,146 @@
+use super::{
+    action::TraceClientAction,
+    state::{TraceClientState, TraceClientStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::{
+            tcp::action::{TcpAction, TcpPollEvents},
+            tcp_client::{action::TcpClientAction, state::TcpClientState},
+        },
+        time::model::update_time,
+    },
+};
+
+// The `TraceClientState` model connects to an echo server and sends a single
+// request with a trace id set, then receives the response. The trace id seen
+// by the `RecvSuccess` handler is recorded, even if the read was issued from
+// a later (untraced) poll.
+
+// This model depends on `TcpClientState`.
+impl RegisterModel for TraceClientState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpClientState>().model_pure::<Self>()
+    }
+}
+
+impl PureModel for TraceClientState {
+    type Action = TraceClientAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            TraceClientAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                if state.substate::<TraceClientState>().status == TraceClientStatus::Init {
+                    dispatcher.dispatch(TcpAction::Init {
+                        instance: state.new_uid(),
+                        events_capacity: None,
+                        on_success: callback!(|instance: Uid| TraceClientAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| TraceClientAction::InitError { instance, error }),
+                    });
+                } else {
+                    dispatcher.dispatch(TcpClientAction::Poll {
+                        uid: state.new_uid(),
+                        timeout: Timeout::Millis(10),
+                        on_success: callback!(|(uid: Uid, events: TcpPollEvents)| TraceClientAction::PollSuccess { uid, events }),
+                        on_error: callback!(|(uid: Uid, error: String)| TraceClientAction::PollError { uid, error }),
+                    })
+                }
+            }
+            TraceClientAction::PollSuccess { .. } => {
+                if dispatcher.trace_id.is_some() {
+                    state.substate_mut::<TraceClientState>().poll_traced = true;
+                }
+            }
+            TraceClientAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            TraceClientAction::InitSuccess { .. } => {
+                let client_state: &mut TraceClientState = state.substate_mut();
+                let address = client_state.address.clone();
+
+                client_state.status = TraceClientStatus::Connecting;
+                dispatcher.dispatch(TcpClientAction::Connect {
+                    connection: state.new_uid(),
+                    address,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|connection: Uid| TraceClientAction::ConnectSuccess { connection }),
+                    on_timeout: callback!(|connection: Uid| TraceClientAction::ConnectTimeout { connection }),
+                    on_error: callback!(|(connection: Uid, error: String)| TraceClientAction::ConnectError { connection, error }),
+                    on_close: callback!(|connection: Uid| TraceClientAction::CloseEvent { connection }),
+                });
+            }
+            TraceClientAction::InitError { error, .. } => {
+                panic!("Initialization failed: {}", error)
+            }
+            TraceClientAction::ConnectSuccess { connection } => {
+                let client_state: &mut TraceClientState = state.substate_mut();
+
+                client_state.status = TraceClientStatus::Connected;
+                client_state.connection = Some(connection);
+                dispatcher.set_trace_id(client_state.trace_id);
+                dispatcher.dispatch(TcpClientAction::Send {
+                    uid: state.new_uid(),
+                    connection,
+                    data: b"ping".to_vec().into(),
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|uid: Uid| TraceClientAction::SendSuccess { uid }),
+                    on_timeout: callback!(|uid: Uid| TraceClientAction::SendTimeout { uid }),
+                    on_error: callback!(|(uid: Uid, error: String)| TraceClientAction::SendError { uid, error }),
+                });
+            }
+            TraceClientAction::ConnectTimeout { connection } => {
+                panic!("Connection {:?} timed out", connection)
+            }
+            TraceClientAction::ConnectError { connection, error } => {
+                panic!("Connection {:?} failed: {}", connection, error)
+            }
+            TraceClientAction::CloseEvent { connection } => {
+                panic!("Connection {:?} closed", connection)
+            }
+            TraceClientAction::SendSuccess { .. } => {
+                let connection = state.substate::<TraceClientState>().connection.unwrap();
+
+                dispatcher.dispatch(TcpClientAction::Recv {
+                    uid: state.new_uid(),
+                    connection,
+                    count: 4,
+                    timeout: Timeout::Millis(1000),
+                    on_success: callback!(|(uid: Uid, data: Vec<u8>)| TraceClientAction::RecvSuccess { uid, data }),
+                    on_timeout: callback!(|(uid: Uid, partial_data: Vec<u8>)| TraceClientAction::RecvTimeout { uid, partial_data }),
+                    on_error: callback!(|(uid: Uid, error: String)| TraceClientAction::RecvError { uid, error }),
+                });
+            }
+            TraceClientAction::SendTimeout { uid } => {
+                panic!("Send {:?} timed out", uid)
+            }
+            TraceClientAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            TraceClientAction::RecvSuccess { data, .. } => {
+                state.substate_mut::<TraceClientState>().received =
+                    Some((data, dispatcher.trace_id))
+            }
+            TraceClientAction::RecvTimeout { uid, partial_data } => {
+                panic!("Recv {:?} timed out: {:?}", uid, partial_data)
+            }
+            TraceClientAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TraceClientStatus {
+    Init,
+    Connecting,
+    Connected,
+}
+
+#[derive(Debug)]
+pub struct TraceClientState {
+    pub status: TraceClientStatus,
+    pub address: String,
+    pub trace_id: u64,
+    pub connection: Option<Uid>,
+    // (data, trace id) of the response, as seen by the `RecvSuccess` handler.
+    pub received: Option<(Vec<u8>, Option<u64>)>,
+    // Whether a poll result was tagged with a trace id.
+    pub poll_traced: bool,
+}
+
+impl TraceClientState {
+    pub fn new(address: String, trace_id: u64) -> Self {
+        Self {
+            status: TraceClientStatus::Init,
+            address,
+            trace_id,
+            connection: None,
+            received: None,
+            poll_traced: false,
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use crate::automaton::{
+    action::{Action, ActionKind},
+    state::Uid,
+};
+use serde_derive::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "27155717-40e4-4b40-a29b-ce42278094ee"]
+pub enum UdpEchoAction {
+    Tick,
+    PollSuccess {
+        uid: Uid,
+    },
+    PollError {
+        uid: Uid,
+        error: String,
+    },
+    InitSuccess {
+        instance: Uid,
+    },
+    InitError {
+        instance: Uid,
+        error: String,
+    },
+    BindSuccess {
+        socket: Uid,
+    },
+    BindError {
+        socket: Uid,
+        error: String,
+    },
+    ServerRecvSuccess {
+        uid: Uid,
+        peer_address: String,
+        data: Vec<u8>,
+    },
+    ServerRecvTimeout {
+        uid: Uid,
+    },
+    ServerRecvError {
+        uid: Uid,
+        error: String,
+    },
+    ServerSendSuccess {
+        uid: Uid,
+    },
+    ServerSendTimeout {
+        uid: Uid,
+    },
+    ServerSendError {
+        uid: Uid,
+        error: String,
+    },
+    SendSuccess {
+        uid: Uid,
+    },
+    SendTimeout {
+        uid: Uid,
+    },
+    SendError {
+        uid: Uid,
+        error: String,
+    },
+    RecvSuccess {
+        uid: Uid,
+        peer_address: String,
+        data: Vec<u8>,
+    },
+    RecvTimeout {
+        uid: Uid,
+    },
+    RecvError {
+        uid: Uid,
+        error: String,
+    },
+}
+
+impl Action for UdpEchoAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
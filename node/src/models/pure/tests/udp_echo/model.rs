@@ -0,0 +1,313 @@
+use super::{
+    action::UdpEchoAction,
+    state::{UdpEchoConfig, UdpEchoSockets, UdpEchoState, UdpEchoStatus},
+};
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::udp::{action::UdpAction, state::UdpState},
+        prng::state::PRNGState,
+        tests::echo_client::state::DataMismatch,
+        time::model::update_time,
+    },
+};
+use log::{info, warn};
+use rand::{Rng, RngCore};
+
+// The `UdpEchoState` binds two UDP sockets, an echo server and a client, to
+// exercise `UdpState` (and the UDP support of `MioState`) end to end.
+//
+// - The server socket keeps a receive request (with no timeout) in-flight and
+//   sends every datagram it gets back to its sender.
+//
+// - On each poll result the client sends a datagram of random data (generated
+//   by `PRNGState`) to the server, then waits for the echo. A datagram whose
+//   echo doesn't arrive within `recv_timeout` is counted as lost, otherwise
+//   its contents are checked against the sent data.
+//
+// The model halts after `rounds` datagrams were echoed back.
+
+// This model depends on `PRNGState` and `UdpState`.
+impl RegisterModel for UdpEchoState {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<PRNGState>()
+            .register::<UdpState>()
+            .model_pure::<Self>()
+    }
+}
+
+impl PureModel for UdpEchoState {
+    type Action = UdpEchoAction;
+
+    fn process_pure<Substate: ModelState>(
+        state: &mut State<Substate>,
+        action: Self::Action,
+        dispatcher: &mut Dispatcher,
+    ) {
+        match action {
+            UdpEchoAction::Tick => {
+                if update_time(state, dispatcher) {
+                    return;
+                }
+
+                let UdpEchoState {
+                    status,
+                    config: UdpEchoConfig { poll_timeout, .. },
+                    ..
+                } = state.substate();
+
+                match status {
+                    UdpEchoStatus::Init => dispatcher.dispatch(UdpAction::Init {
+                        instance: state.new_uid(),
+                        on_success: callback!(|instance: Uid| UdpEchoAction::InitSuccess { instance }),
+                        on_error: callback!(|(instance: Uid, error: String)| UdpEchoAction::InitError { instance, error }),
+                    }),
+                    UdpEchoStatus::Binding { .. } => (),
+                    UdpEchoStatus::Ready
+                    | UdpEchoStatus::Sending { .. }
+                    | UdpEchoStatus::Receiving { .. } => {
+                        let timeout = Timeout::Millis(*poll_timeout);
+
+                        dispatcher.dispatch(UdpAction::Poll {
+                            uid: state.new_uid(),
+                            timeout,
+                            on_success: callback!(|uid: Uid| UdpEchoAction::PollSuccess { uid }),
+                            on_error: callback!(|(uid: Uid, error: String)| UdpEchoAction::PollError { uid, error }),
+                        })
+                    }
+                }
+            }
+            UdpEchoAction::InitSuccess { .. } => {
+                let sockets = UdpEchoSockets {
+                    server: state.new_uid(),
+                    client: state.new_uid(),
+                };
+                let UdpEchoState {
+                    status,
+                    config,
+                    sockets: bound,
+                    ..
+                } = state.substate_mut();
+
+                *status = UdpEchoStatus::Binding { pending: 2 };
+                *bound = Some(sockets);
+
+                for (socket, address) in [
+                    (sockets.server, &config.server_address),
+                    (sockets.client, &config.client_address),
+                ] {
+                    dispatcher.dispatch(UdpAction::Bind {
+                        socket,
+                        address: address.clone(),
+                        on_success: callback!(|socket: Uid| UdpEchoAction::BindSuccess { socket }),
+                        on_error: callback!(|(socket: Uid, error: String)| UdpEchoAction::BindError { socket, error }),
+                    })
+                }
+            }
+            UdpEchoAction::InitError { error, .. } => {
+                panic!("UDP initialization failed: {}", error)
+            }
+            UdpEchoAction::BindSuccess { socket } => {
+                let uid = state.new_uid();
+                let echo_state: &mut UdpEchoState = state.substate_mut();
+                let sockets = echo_state.sockets();
+
+                if let UdpEchoStatus::Binding { pending } = &mut echo_state.status {
+                    *pending -= 1;
+
+                    if *pending == 0 {
+                        echo_state.status = UdpEchoStatus::Ready;
+                    }
+                } else {
+                    unreachable!()
+                }
+
+                if socket == sockets.server {
+                    server_recv(uid, sockets.server, dispatcher);
+                }
+            }
+            UdpEchoAction::BindError { socket, error } => {
+                panic!("Bind of socket {:?} failed: {}", socket, error)
+            }
+            UdpEchoAction::PollSuccess { .. } => {
+                // Send random data on every poll if there is no datagram in-flight.
+                if let UdpEchoState {
+                    status: UdpEchoStatus::Ready,
+                    config:
+                        UdpEchoConfig {
+                            server_address,
+                            max_send_size,
+                            ..
+                        },
+                    ..
+                } = state.substate()
+                {
+                    let address = server_address.clone();
+                    let max_send_size = *max_send_size;
+                    let socket = state.substate::<UdpEchoState>().sockets().client;
+                    let request = state.new_uid();
+                    let prng: &mut PRNGState = state.substate_mut();
+                    let random_size = prng.rng.gen_range(1..max_send_size) as usize;
+                    let mut data: Vec<u8> = vec![0; random_size];
+
+                    prng.rng.fill_bytes(&mut data[..]);
+
+                    state.substate_mut::<UdpEchoState>().status = UdpEchoStatus::Sending {
+                        request,
+                        data: data.clone(),
+                    };
+
+                    dispatcher.dispatch(UdpAction::Send {
+                        uid: request,
+                        socket,
+                        address,
+                        data: data.into(),
+                        timeout: Timeout::Millis(200),
+                        on_success: callback!(|uid: Uid| UdpEchoAction::SendSuccess { uid }),
+                        on_timeout: callback!(|uid: Uid| UdpEchoAction::SendTimeout { uid }),
+                        on_error: callback!(|(uid: Uid, error: String)| UdpEchoAction::SendError { uid, error }),
+                    });
+                }
+            }
+            UdpEchoAction::PollError { uid, error } => {
+                panic!("Poll {:?} failed: {}", uid, error)
+            }
+            UdpEchoAction::ServerRecvSuccess {
+                uid,
+                peer_address,
+                data,
+            } => {
+                info!(
+                    "|UDP_ECHO| server recv {:?}: {} bytes from {}",
+                    uid,
+                    data.len(),
+                    peer_address
+                );
+
+                let send_request = state.new_uid();
+                let recv_request = state.new_uid();
+                let server = state.substate::<UdpEchoState>().sockets().server;
+
+                dispatcher.dispatch(UdpAction::Send {
+                    uid: send_request,
+                    socket: server,
+                    address: peer_address,
+                    data: data.into(),
+                    timeout: Timeout::Millis(200),
+                    on_success: callback!(|uid: Uid| UdpEchoAction::ServerSendSuccess { uid }),
+                    on_timeout: callback!(|uid: Uid| UdpEchoAction::ServerSendTimeout { uid }),
+                    on_error: callback!(|(uid: Uid, error: String)| UdpEchoAction::ServerSendError { uid, error }),
+                });
+                server_recv(recv_request, server, dispatcher);
+            }
+            UdpEchoAction::ServerRecvTimeout { uid } => {
+                unreachable!("Server recv {:?} has no timeout", uid)
+            }
+            UdpEchoAction::ServerRecvError { uid, error } => {
+                panic!("Server recv {:?} failed: {}", uid, error)
+            }
+            UdpEchoAction::ServerSendSuccess { uid } => {
+                info!("|UDP_ECHO| server send {:?} success", uid);
+            }
+            UdpEchoAction::ServerSendTimeout { uid } => {
+                warn!("|UDP_ECHO| server send {:?} timeout", uid);
+            }
+            UdpEchoAction::ServerSendError { uid, error } => {
+                panic!("Server send {:?} failed: {}", uid, error)
+            }
+            UdpEchoAction::SendSuccess { uid } => {
+                let recv_request = state.new_uid();
+                let echo_state: &mut UdpEchoState = state.substate_mut();
+                let client = echo_state.sockets().client;
+                let recv_timeout = echo_state.config.recv_timeout;
+
+                let UdpEchoStatus::Sending { request, data } = &mut echo_state.status else {
+                    unreachable!()
+                };
+                assert_eq!(uid, *request);
+
+                let sent_data = std::mem::take(data);
+                let max_len = sent_data.len();
+
+                echo_state.status = UdpEchoStatus::Receiving {
+                    request: recv_request,
+                    sent_data,
+                };
+
+                dispatcher.dispatch(UdpAction::Recv {
+                    uid: recv_request,
+                    socket: client,
+                    max_len,
+                    timeout: Timeout::Millis(recv_timeout),
+                    on_success: callback!(|(uid: Uid, peer_address: String, data: Vec<u8>)| UdpEchoAction::RecvSuccess { uid, peer_address, data }),
+                    on_timeout: callback!(|uid: Uid| UdpEchoAction::RecvTimeout { uid }),
+                    on_error: callback!(|(uid: Uid, error: String)| UdpEchoAction::RecvError { uid, error }),
+                });
+            }
+            UdpEchoAction::SendTimeout { uid } => {
+                let echo_state: &mut UdpEchoState = state.substate_mut();
+
+                warn!("|UDP_ECHO| send {:?} timeout", uid);
+                echo_state.lost += 1;
+                echo_state.status = UdpEchoStatus::Ready;
+            }
+            UdpEchoAction::SendError { uid, error } => {
+                panic!("Send {:?} failed: {}", uid, error)
+            }
+            UdpEchoAction::RecvSuccess {
+                uid,
+                peer_address,
+                data,
+            } => {
+                let echo_state: &mut UdpEchoState = state.substate_mut();
+
+                let UdpEchoStatus::Receiving { request, sent_data } = &echo_state.status else {
+                    unreachable!()
+                };
+                assert_eq!(uid, *request);
+                assert_eq!(peer_address, echo_state.config.server_address);
+
+                if let Some(mismatch) = DataMismatch::find(sent_data, &data) {
+                    panic!("{}", mismatch)
+                }
+
+                info!("|UDP_ECHO| recv {:?}: data matches", uid);
+                echo_state.echoed += 1;
+                echo_state.status = UdpEchoStatus::Ready;
+
+                if echo_state.echoed == echo_state.config.rounds {
+                    dispatcher.halt()
+                }
+            }
+            UdpEchoAction::RecvTimeout { uid } => {
+                let echo_state: &mut UdpEchoState = state.substate_mut();
+
+                warn!("|UDP_ECHO| recv {:?} timeout, datagram lost", uid);
+                echo_state.lost += 1;
+                echo_state.status = UdpEchoStatus::Ready;
+            }
+            UdpEchoAction::RecvError { uid, error } => {
+                panic!("Recv {:?} failed: {}", uid, error)
+            }
+        }
+    }
+}
+
+fn server_recv(uid: Uid, socket: Uid, dispatcher: &mut Dispatcher) {
+    dispatcher.dispatch(UdpAction::Recv {
+        uid,
+        socket,
+        max_len: 65507,
+        timeout: Timeout::Never,
+        on_success: callback!(|(uid: Uid, peer_address: String, data: Vec<u8>)| UdpEchoAction::ServerRecvSuccess { uid, peer_address, data }),
+        on_timeout: callback!(|uid: Uid| UdpEchoAction::ServerRecvTimeout { uid }),
+        on_error: callback!(|(uid: Uid, error: String)| UdpEchoAction::ServerRecvError { uid, error }),
+    })
+}
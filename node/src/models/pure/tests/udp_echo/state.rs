@@ -0,0 +1,53 @@
+use crate::automaton::state::Uid;
+
+#[derive(Debug)]
+pub struct UdpEchoConfig {
+    pub server_address: String,
+    pub client_address: String,
+    pub poll_timeout: u64,
+    pub max_send_size: u64,
+    pub recv_timeout: u64,
+    // Halts once this many datagrams were echoed back.
+    pub rounds: usize,
+}
+
+#[derive(Debug)]
+pub enum UdpEchoStatus {
+    Init,
+    Binding { pending: usize },
+    Ready,
+    Sending { request: Uid, data: Vec<u8> },
+    Receiving { request: Uid, sent_data: Vec<u8> },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UdpEchoSockets {
+    pub server: Uid,
+    pub client: Uid,
+}
+
+#[derive(Debug)]
+pub struct UdpEchoState {
+    pub status: UdpEchoStatus,
+    pub sockets: Option<UdpEchoSockets>,
+    pub config: UdpEchoConfig,
+    pub echoed: usize,
+    // Datagrams (or their echoes) lost.
+    pub lost: usize,
+}
+
+impl UdpEchoState {
+    pub fn from_config(config: UdpEchoConfig) -> Self {
+        Self {
+            status: UdpEchoStatus::Init,
+            sockets: None,
+            config,
+            echoed: 0,
+            lost: 0,
+        }
+    }
+
+    pub fn sockets(&self) -> UdpEchoSockets {
+        self.sockets.expect("UDP sockets not bound yet")
+    }
+}
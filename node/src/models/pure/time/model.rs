@@ -11,6 +11,7 @@ use crate::{
     },
     callback,
 };
+use log::warn;
 use std::time::Duration;
 
 impl RegisterModel for TimeState {
@@ -36,14 +37,28 @@ pub fn get_current_time<Substate: ModelState>(state: &State<Substate>) -> u128 {
     state.substate::<TimeState>().now().as_millis()
 }
 
+// Timeouts longer than this (a year) are most likely a bug, or a stand-in for
+// `Timeout::Never`.
+pub const SUSPICIOUS_TIMEOUT_MS: u64 = 365 * 24 * 60 * 60 * 1000;
+
 pub fn get_timeout_absolute<Substate: ModelState>(
     state: &State<Substate>,
     timeout: Timeout,
 ) -> TimeoutAbsolute {
-    // Convert relative the timeout we passed to absolute timeout by adding the current time
+    timeout_absolute(get_current_time(state), timeout)
+}
+
+// Convert relative the timeout we passed to absolute timeout by adding the
+// current time. The sum saturates: wrapping around would yield a deadline in
+// the past, and the request would time out right away.
+pub fn timeout_absolute(current_time: u128, timeout: Timeout) -> TimeoutAbsolute {
     match timeout {
-        Timeout::Millis(ms) => {
-            TimeoutAbsolute::Millis(get_current_time(state).saturating_add(ms.into()))
+        Timeout::Millis(ms) | Timeout::Inactivity(ms) => {
+            if cfg!(debug_assertions) && ms > SUSPICIOUS_TIMEOUT_MS {
+                warn!("|TIME| suspicious timeout of {} ms, use Timeout::Never instead?", ms)
+            }
+
+            TimeoutAbsolute::Millis(current_time.saturating_add(ms.into()))
         }
         Timeout::Never => TimeoutAbsolute::Never,
     }
@@ -0,0 +1,36 @@
+use crate::{
+    automaton::runner::RunnerBuilder,
+    models::pure::tests::pure_counter::{action::PureCounterAction, state::PureCounterConfig},
+    tests::forbid_effects::PureCounter,
+};
+
+fn run(builder: RunnerBuilder<PureCounter>) -> Option<f64> {
+    let mut runner = builder
+        .register::<PureCounter>()
+        .instance(
+            PureCounter::from_config(PureCounterConfig {
+                max_count: 10,
+                update_time: false,
+                slow_tick: None,
+            }),
+            || PureCounterAction::Tick.into(),
+        )
+        .build();
+    let labels = vec![
+        ("instance", "0".to_string()),
+        ("model", "pure_counter".to_string()),
+        ("kind", "pure".to_string()),
+    ];
+
+    runner.run();
+    runner
+        .state()
+        .metrics
+        .get("state_machine_actions_total", &labels)
+}
+
+#[test]
+fn action_metrics() {
+    assert_eq!(run(RunnerBuilder::new()), None);
+    assert!(run(RunnerBuilder::new().action_metrics()).is_some_and(|count| count >= 10.0));
+}
@@ -3,7 +3,7 @@ use crate::automaton::runner::{RegisterModel, RunnerBuilder};
 use crate::automaton::state::ModelState;
 use crate::models::pure::net::pnet::client::state::PnetClientConfig;
 use crate::models::pure::net::pnet::common::PnetKey;
-use crate::models::pure::prng::state::{PRNGConfig, PRNGState};
+use crate::models::pure::prng::state::{PRNGAlgorithm, PRNGConfig, PRNGState};
 use crate::models::pure::tests::simple_client_pnet::action::PnetSimpleClientAction;
 use crate::models::pure::{
     net::{
@@ -34,7 +34,10 @@ pub struct ClientConfig {
 impl PnetClient {
     pub fn from_config(config: ClientConfig) -> Self {
         Self {
-            prng: PRNGState::from_config(PRNGConfig { seed: 31337 }),
+            prng: PRNGState::from_config(PRNGConfig {
+                seed: 31337,
+                algorithm: PRNGAlgorithm::SmallRng,
+            }),
             time: TimeState::default(),
             tcp: TcpState::new(),
             tcp_client: TcpClientState::new(),
@@ -57,6 +60,7 @@ fn connect() {
         .instance(
             PnetClient::from_config(ClientConfig {
                 client: PnetSimpleClientConfig {
+                    config_file: None,
                     connect_to_address: "65.109.110.75:18302".to_string(),
                     connect_timeout: Timeout::Millis(2000),
                     poll_timeout: 1000,
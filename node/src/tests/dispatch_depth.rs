@@ -0,0 +1,83 @@
+use crate::{
+    automaton::{
+        model::effect_name,
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::tests::recursive_dispatch::{
+        action::RecursiveDispatchAction, state::RecursiveDispatchState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{
+    any::Any,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+#[derive(ModelState, Debug)]
+pub struct RecursiveDispatch {
+    pub recursive: RecursiveDispatchState,
+}
+
+impl RegisterModel for RecursiveDispatch {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<RecursiveDispatchState>()
+    }
+}
+
+fn run(levels: usize, max_depth: usize) -> usize {
+    let mut runner = RunnerBuilder::<RecursiveDispatch>::new()
+        .register::<RecursiveDispatch>()
+        .max_dispatch_depth(max_depth)
+        .instance(
+            RecursiveDispatch {
+                recursive: RecursiveDispatchState::new(levels),
+            },
+            || RecursiveDispatchAction::Tick.into(),
+        )
+        .build();
+
+    runner.run();
+    runner.state().substate::<RecursiveDispatchState>().deepest
+}
+
+#[test]
+fn dispatch_depth_within_limit() {
+    assert_eq!(run(5, 5), 5);
+}
+
+#[test]
+fn dispatch_depth_exceeded() {
+    let error = catch_unwind(AssertUnwindSafe(|| run(100, 8))).expect_err("limit not enforced");
+    let message = error
+        .downcast_ref::<String>()
+        .expect("unexpected panic payload");
+
+    assert!(message.starts_with(
+        "Dispatch depth limit (8) exceeded dispatching RecursiveDispatchAction::Recurse at "
+    ));
+    assert!(message.contains("recursive_dispatch/model.rs"));
+
+    // The tick, then the 8 nested actions that led to the 9th dispatch.
+    let chain: Vec<&str> = message.rsplit(": ").next().unwrap().split(" -> ").collect();
+
+    assert_eq!(chain.len(), 9);
+    assert_eq!(chain[0], "RecursiveDispatchAction");
+    assert!(chain[1..].iter().all(|action| {
+        action.starts_with("RecursiveDispatchAction at ")
+            && action.contains("recursive_dispatch/model.rs")
+    }));
+}
+
+#[test]
+fn dispatch_depth_action_name() {
+    // Only the variant is named, not its fields.
+    assert_eq!(
+        effect_name(&RecursiveDispatchAction::Recurse { level: 3 }),
+        "RecursiveDispatchAction::Recurse"
+    );
+    assert_eq!(
+        effect_name(&RecursiveDispatchAction::Tick),
+        "RecursiveDispatchAction::Tick"
+    );
+}
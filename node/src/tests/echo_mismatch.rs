@@ -0,0 +1,131 @@
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        state::{State, Uid},
+    },
+    models::pure::tests::echo_client::{
+        action::EchoClientAction,
+        state::{
+            DataMismatch, EchoClientConfig, EchoClientState, EchoClientStatus, PayloadPattern,
+        },
+    },
+    tests::echo_network::EchoClient,
+};
+use rand::{rngs::SmallRng, SeedableRng};
+use std::cell::RefCell;
+
+thread_local! {
+    static MISMATCHES: RefCell<Vec<DataMismatch>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record_mismatch(mismatch: &DataMismatch) {
+    MISMATCHES.with(|mismatches| mismatches.borrow_mut().push(mismatch.clone()));
+}
+
+#[test]
+fn data_mismatch_find() {
+    assert_eq!(DataMismatch::find(b"ping", b"ping"), None);
+    assert_eq!(
+        DataMismatch::find(b"ping", b"pi"),
+        Some(DataMismatch {
+            index: 2,
+            expected: Some(b'n'),
+            actual: None,
+            expected_len: 4,
+            actual_len: 2,
+        })
+    );
+}
+
+#[test]
+fn echo_client_reports_corrupted_byte() {
+    let mut state = State::<EchoClient>::new();
+    let mut client = EchoClient::from_config(EchoClientConfig {
+        connect_to_address: "127.0.0.1:8888".to_string(),
+        connect_timeout: Timeout::Millis(1000),
+        poll_timeout: 100,
+        max_connection_attempts: 1,
+        retry_interval_ms: 500,
+        max_send_size: 1024,
+        min_rnd_timeout: 1000,
+        max_rnd_timeout: 10000,
+        payload: PayloadPattern::Random,
+    });
+    let connection = Uid::from(1usize);
+    let request = Uid::from(2usize);
+    let sent_data: Vec<u8> = (0..=255).collect();
+    let mut data = sent_data.clone();
+
+    // A single flipped bit in the echoed data.
+    data[100] ^= 0x10;
+
+    client.echo_client.on_mismatch = record_mismatch;
+    client.echo_client.status = EchoClientStatus::Receiving {
+        connection,
+        request,
+        sent_data,
+    };
+    state.substates.push(client);
+
+    let mut dispatcher = Dispatcher::new(|| EchoClientAction::Tick.into());
+
+    EchoClientState::process_pure(
+        &mut state,
+        EchoClientAction::RecvSuccess { uid: request, data },
+        &mut dispatcher,
+    );
+
+    let mismatches = MISMATCHES.with(|mismatches| mismatches.take());
+
+    assert_eq!(
+        mismatches,
+        [DataMismatch {
+            index: 100,
+            expected: Some(100),
+            actual: Some(100 ^ 0x10),
+            expected_len: 256,
+            actual_len: 256,
+        }]
+    );
+    assert!(matches!(
+        state.substate::<EchoClientState>().status,
+        EchoClientStatus::Connected { .. }
+    ));
+}
+
+#[test]
+fn echo_client_sequential_payload_mismatch() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let sent_data = PayloadPattern::Sequential.generate(&mut rng, 1024);
+    let offset = sent_data.len() / 2;
+    let mut data = sent_data.clone();
+
+    assert!(sent_data
+        .iter()
+        .enumerate()
+        .all(|(offset, byte)| *byte == offset as u8));
+
+    data[offset] = !data[offset];
+
+    let report = DataMismatch::find(&sent_data, &data).unwrap().to_string();
+
+    assert!(
+        report.starts_with(&format!(
+            "Data mismatch at byte {}: expected Some({}), got Some({})",
+            offset,
+            offset as u8,
+            !(offset as u8)
+        )),
+        "{}",
+        report
+    );
+    assert_eq!(
+        PayloadPattern::Bytes(b"ping".to_vec()).generate(&mut rng, 1024),
+        b"ping"
+    );
+    assert!(PayloadPattern::Repeat(b"ab".to_vec())
+        .generate(&mut rng, 1024)
+        .chunks(2)
+        .all(|chunk| b"ab".starts_with(chunk)));
+}
@@ -8,11 +8,11 @@ use crate::{
         net::tcp::state::TcpState,
         net::tcp_client::state::TcpClientState,
         net::tcp_server::state::TcpServerState,
-        prng::state::{PRNGConfig, PRNGState},
+        prng::state::{PRNGAlgorithm, PRNGConfig, PRNGState},
         tests::{
             echo_client::{
                 action::EchoClientAction,
-                state::{EchoClientConfig, EchoClientState},
+                state::{EchoClientConfig, EchoClientState, PayloadPattern},
             },
             echo_server::{
                 action::EchoServerAction,
@@ -56,7 +56,10 @@ pub struct EchoClient {
 impl EchoClient {
     pub fn from_config(config: EchoClientConfig) -> Self {
         Self {
-            prng: PRNGState::from_config(PRNGConfig { seed: 1337 }),
+            prng: PRNGState::from_config(PRNGConfig {
+                seed: 1337,
+                algorithm: PRNGAlgorithm::SmallRng,
+            }),
             time: TimeState::default(),
             tcp: TcpState::new(),
             tcp_client: TcpClientState::new(),
@@ -102,6 +105,7 @@ fn echo_server_1_client() {
                 max_send_size: 10240,
                 min_rnd_timeout: 1000,
                 max_rnd_timeout: 10000,
+                payload: PayloadPattern::Random,
             })),
             || EchoClientAction::Tick.into(),
         )
@@ -133,6 +137,7 @@ fn echo_server_n_clients(n_clients: u64) {
                 max_send_size: 1024 / n_clients,
                 min_rnd_timeout: 1000,
                 max_rnd_timeout: 1000 * n_clients,
+                payload: PayloadPattern::Random,
             })),
             || EchoClientAction::Tick.into(),
         );
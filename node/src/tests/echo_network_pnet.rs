@@ -8,22 +8,29 @@ use crate::{
         net::{
             pnet::{
                 client::state::{PnetClientConfig, PnetClientState},
-                common::PnetKey,
+                common::{PnetKey, XSalsa20Wrapper},
                 server::state::{PnetServerConfig, PnetServerState},
             },
             tcp::state::TcpState,
             tcp_client::state::TcpClientState,
             tcp_server::state::TcpServerState,
         },
-        prng::state::{PRNGConfig, PRNGState},
+        prng::state::{PRNGAlgorithm, PRNGConfig, PRNGState},
         tests::{
-            echo_client::state::EchoClientConfig, echo_client_pnet::{action::PnetEchoClientAction, state::PnetEchoClientState}, echo_server::state::EchoServerConfig, echo_server_pnet::{action::PnetEchoServerAction, state::PnetEchoServerState}
+            echo_client::state::{EchoClientConfig, PayloadPattern}, echo_client_pnet::{action::PnetEchoClientAction, state::PnetEchoClientState}, echo_server::state::EchoServerConfig, echo_server_pnet::{action::PnetEchoServerAction, state::PnetEchoServerState}
         },
         time::state::TimeState,
     },
 };
 use model_state_derive::ModelState;
-use std::any::Any;
+use salsa20::cipher::StreamCipher;
+use std::{
+    any::Any,
+    io::{Read, Write},
+    net::TcpStream,
+    thread,
+    time::Duration,
+};
 
 #[derive(ModelState, Debug)]
 pub struct PnetEchoServer {
@@ -42,7 +49,10 @@ pub struct PnetEchoServerConfig {
 impl PnetEchoServer {
     pub fn from_config(config: PnetEchoServerConfig) -> Self {
         Self {
-            prng: PRNGState::from_config(PRNGConfig { seed: 31337 }),
+            prng: PRNGState::from_config(PRNGConfig {
+                seed: 31337,
+                algorithm: PRNGAlgorithm::SmallRng,
+            }),
             time: TimeState::default(),
             tcp: TcpState::new(),
             tcp_server: TcpServerState::new(),
@@ -70,7 +80,10 @@ pub struct PnetEchoClientConfig {
 impl PnetEchoClient {
     pub fn from_config(config: PnetEchoClientConfig) -> Self {
         Self {
-            prng: PRNGState::from_config(PRNGConfig { seed: 1337 }),
+            prng: PRNGState::from_config(PRNGConfig {
+                seed: 1337,
+                algorithm: PRNGAlgorithm::SmallRng,
+            }),
             time: TimeState::default(),
             tcp: TcpState::new(),
             tcp_client: TcpClientState::new(),
@@ -125,6 +138,7 @@ fn echo_server_1_client() {
                     max_send_size: 10240,
                     min_rnd_timeout: 1000,
                     max_rnd_timeout: 10000,
+                    payload: PayloadPattern::Random,
                 },
                 pnet: PnetClientConfig {
                     pnet_key: PnetKey::new("test"),
@@ -170,6 +184,7 @@ fn echo_server_n_clients(n_clients: u64) {
                     max_send_size: 1024 / n_clients,
                     min_rnd_timeout: 1000,
                     max_rnd_timeout: 1000 * n_clients,
+                    payload: PayloadPattern::Random,
                 },
                 pnet: PnetClientConfig {
                     pnet_key: PnetKey::new("test"),
@@ -194,3 +209,56 @@ fn echo_server_50_clients() {
     // WARNING: this test probably needs an increase in the fd limit (ulimit -n 10000)
     echo_server_n_clients(50)
 }
+
+// The peer sends its nonce and the first application bytes in a single write:
+// the handshake must not consume more than the nonce.
+#[test]
+fn echo_server_pnet_data_after_nonce() {
+    let address = "127.0.0.1:8910";
+    let mut runner = RunnerBuilder::<EchoNetwork>::new()
+        .register::<EchoNetwork>()
+        .instance(
+            EchoNetwork::PnetEchoServer(PnetEchoServer::from_config(PnetEchoServerConfig {
+                echo_server: EchoServerConfig {
+                    address: address.to_string(),
+                    max_connections: 1,
+                    poll_timeout: 100,
+                    recv_timeout: 500,
+                },
+                pnet: PnetServerConfig {
+                    pnet_key: PnetKey::new("test"),
+                    send_nonce_timeout: Timeout::Millis(500),
+                    recv_nonce_timeout: Timeout::Millis(500),
+                },
+            })),
+            || PnetEchoServerAction::Tick.into(),
+        )
+        .build();
+
+    let client = thread::spawn(move || {
+        let key = PnetKey::new("test");
+        let nonce = [7u8; 24];
+        let data = b"sent along with the nonce".to_vec();
+        let mut stream = loop {
+            match TcpStream::connect(address) {
+                Ok(stream) => break stream,
+                Err(_) => thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        let mut encrypted = data.clone();
+
+        XSalsa20Wrapper::new(&key.0, &nonce).apply_keystream(&mut encrypted);
+        stream.write_all(&[&nonce[..], &encrypted].concat()).unwrap();
+
+        let mut server_nonce = [0u8; 24];
+        let mut echo = vec![0u8; data.len()];
+
+        stream.read_exact(&mut server_nonce).unwrap();
+        stream.read_exact(&mut echo).unwrap();
+        XSalsa20Wrapper::new(&key.0, &server_nonce).apply_keystream(&mut echo);
+        assert_eq!(echo, data);
+    });
+
+    assert!(runner.run_until(|_| client.is_finished(), 1_000_000));
+    client.join().unwrap();
+}
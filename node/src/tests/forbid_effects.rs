@@ -0,0 +1,71 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        tests::pure_counter::{
+            action::PureCounterAction,
+            state::{PureCounterConfig, PureCounterState},
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct PureCounter {
+    pub time: TimeState,
+    pub counter: PureCounterState,
+}
+
+impl PureCounter {
+    pub fn from_config(config: PureCounterConfig) -> Self {
+        Self {
+            time: TimeState::default(),
+            counter: PureCounterState::from_config(config),
+        }
+    }
+}
+
+impl RegisterModel for PureCounter {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<PureCounterState>()
+    }
+}
+
+#[test]
+fn forbid_effects_pure() {
+    RunnerBuilder::<PureCounter>::new()
+        .register::<PureCounter>()
+        .forbid_effects()
+        .instance(
+            PureCounter::from_config(PureCounterConfig {
+                max_count: 100,
+                update_time: false,
+                slow_tick: None,
+            }),
+            || PureCounterAction::Tick.into(),
+        )
+        .build()
+        .run()
+}
+
+#[test]
+#[should_panic(expected = "TimeEffectfulAction")]
+fn forbid_effects_effectful() {
+    RunnerBuilder::<PureCounter>::new()
+        .register::<PureCounter>()
+        .forbid_effects()
+        .instance(
+            PureCounter::from_config(PureCounterConfig {
+                max_count: 100,
+                update_time: true,
+                slow_tick: None,
+            }),
+            || PureCounterAction::Tick.into(),
+        )
+        .build()
+        .run()
+}
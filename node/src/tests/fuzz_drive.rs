@@ -0,0 +1,38 @@
+use crate::fuzz::fuzz_run;
+
+#[test]
+fn fuzz_drive_seed() {
+    #[rustfmt::skip]
+    let seed = [
+        // tick: time; tick: TCP init (poll creation succeeds)
+        1, 0,
+        // tick: time; listen (max 1 connection, listen and registration succeed)
+        1, 0, 0, 0, 0,
+        // tick: time; poll (timeout 0, 1 event: listener readable, accept and
+        // registration succeed)
+        1, 1, 0, 0, 1, 0, 1, 0, 0,
+        // tick: time; recv (4 bytes, timeout 100)
+        1, 3, 0, 3, 100,
+        // tick: time; poll (timeout 0, 1 event: connection readable, read all,
+        // accept would block)
+        1, 1, 0, 0, 1, 1, 1, 0, 10, 11, 12, 13, 6,
+        // tick: time; send (4 bytes, timeout 100)
+        1, 2, 0, 3, 100,
+        // tick: time; poll (timeout 0, 1 event: connection writable, write all)
+        1, 1, 0, 0, 1, 1, 2, 0,
+    ];
+    let runner = fuzz_run(&seed);
+    let metrics = &runner.state().metrics;
+    let instance = vec![("instance", "0".to_string())];
+    let established = vec![
+        ("instance", "0".to_string()),
+        ("state", "established".to_string()),
+    ];
+
+    assert_eq!(
+        metrics.get("tcp_bytes_received_total", &instance),
+        Some(4.0)
+    );
+    assert_eq!(metrics.get("tcp_bytes_sent_total", &instance), Some(4.0));
+    assert_eq!(metrics.get("tcp_connections", &established), Some(1.0));
+}
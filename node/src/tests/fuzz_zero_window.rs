@@ -0,0 +1,60 @@
+use crate::{
+    automaton::runner::Runner,
+    fuzz::{fuzz_run_with, FuzzNode, NetworkConditions},
+};
+use std::time::Duration;
+
+#[rustfmt::skip]
+const BLOCKED: &[u8] = &[
+    // tick: time; tick: TCP init (poll creation succeeds)
+    1, 0,
+    // tick: time; listen (max 1 connection, listen and registration succeed)
+    1, 0, 0, 0, 0,
+    // tick: time; poll (timeout 0, 1 event: listener readable, accept and
+    // registration succeed)
+    1, 1, 0, 0, 1, 0, 1, 0, 0,
+    // tick: time; send (4 bytes, timeout 200)
+    1, 2, 0, 3, 200,
+    // tick: time; poll (timeout 0, 1 event: connection writable, the write
+    // blocks on the zero window without consuming input, accept would block)
+    1, 1, 0, 0, 1, 1, 2, 6,
+];
+
+#[rustfmt::skip]
+const REOPENED: &[u8] = &[
+    // tick: time (+60ms, the window reopens); poll (timeout 0, 1 event:
+    // connection writable, write all, accept would block)
+    60, 1, 0, 0, 1, 1, 2, 0, 6,
+];
+
+fn run(data: &[u8]) -> Runner<FuzzNode> {
+    fuzz_run_with(
+        data,
+        NetworkConditions {
+            zero_window_until: Some(Duration::from_millis(50)),
+        },
+    )
+}
+
+fn bytes_sent(runner: &Runner<FuzzNode>) -> Option<f64> {
+    runner
+        .state()
+        .metrics
+        .get("tcp_bytes_sent_total", &vec![("instance", "0".to_string())])
+}
+
+#[test]
+fn fuzz_zero_window_blocks_writes() {
+    let runner = run(BLOCKED);
+
+    assert_eq!(bytes_sent(&runner).unwrap_or(0.0), 0.0);
+}
+
+#[test]
+fn fuzz_zero_window_reopens() {
+    // The queued bytes are written on the first writable event after the
+    // window reopens.
+    let runner = run(&[BLOCKED, REOPENED].concat());
+
+    assert_eq!(bytes_sent(&runner), Some(4.0));
+}
@@ -1,3 +1,89 @@
 pub mod echo_network;
 pub mod echo_network_pnet;
-pub mod berkeley_pnet;
\ No newline at end of file
+pub mod berkeley_pnet;
+pub mod forbid_effects;
+pub mod fuzz_drive;
+pub mod tcp_server_routing;
+pub mod tcp_connection_history;
+pub mod prng;
+pub mod model_priority;
+pub mod snapshot;
+pub mod replay;
+pub mod tcp_validate;
+pub mod run_until;
+pub mod topology;
+pub mod tcp_accept_latency;
+pub mod tcp_poll_empty;
+pub mod tcp_timeouts;
+pub mod timeout;
+pub mod tcp_fd_exhaustion;
+pub mod stepper;
+pub mod shared_config;
+pub mod step_duration;
+pub mod echo_mismatch;
+pub mod replay_patch;
+pub mod udp_echo;
+pub mod tcp_priority;
+pub mod protocol_fsm;
+pub mod output;
+pub mod tcp_fair_queuing;
+pub mod tcp_socket_options;
+pub mod query;
+pub mod tcp_events_capacity;
+pub mod tcp_reuse_diagnostics;
+#[cfg(unix)]
+pub mod tcp_listen_from_fd;
+pub mod tcp_write_slice;
+pub mod fuzz_zero_window;
+pub mod tcp_send_queue;
+pub mod tcp_shutdown_queue;
+pub mod tcp_connection_stats;
+pub mod record_connection;
+pub mod tcp_in_flight;
+pub mod tcp_adaptive_recv;
+pub mod tcp_poll_interest;
+pub mod replay_sends;
+pub mod dispatch_depth;
+#[cfg(target_os = "linux")]
+pub mod tcp_buffer_status;
+pub mod tcp_upgrade;
+pub mod retry_send;
+pub mod tcp_rate_schedule;
+pub mod shutdown_order;
+pub mod trace_id;
+pub mod tcp_operation_log;
+pub mod tcp_connect_timeout;
+#[cfg(target_os = "linux")]
+pub mod tcp_congestion;
+pub mod tcp_cancel;
+pub mod tcp_send_then_close_other;
+pub mod tcp_local_address;
+pub mod tcp_backlog;
+pub mod tcp_sweep_idle;
+pub mod tcp_peer_address;
+pub mod tcp_connection_poll;
+pub mod tcp_unknown_connection;
+pub mod tcp_server_close;
+pub mod action_metrics;
+pub mod tcp_connection_addrs;
+pub mod tcp_close_deliver_buffered;
+pub mod tcp_metrics;
+pub mod tcp_register_retry;
+pub mod tcp_tee_capture;
+pub mod offload_effects;
+pub mod ndjson_export;
+pub mod tcp_probe;
+pub mod tcp_drain_on_close;
+pub mod tcp_last_error;
+pub mod tcp_accept_register_delay;
+pub mod tcp_lifecycle_events;
+pub mod tcp_recv_line;
+pub mod tcp_admission_control;
+pub mod tcp_server_close_all;
+pub mod tcp_server_recv_into_ring;
+pub mod tcp_server_send_to_group;
+pub mod tcp_bytes_available;
+pub mod tcp_half_close;
+pub mod tcp_recv_until;
+pub mod tcp_connection_numbers;
+pub mod tcp_nodelay;
@@ -0,0 +1,40 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::tests::priority_order::{action::PriorityOrderAction, state::PriorityOrderState},
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct PriorityOrder {
+    pub order: PriorityOrderState,
+}
+
+impl RegisterModel for PriorityOrder {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<PriorityOrderState>()
+    }
+}
+
+#[test]
+fn model_priority_order() {
+    let mut runner = RunnerBuilder::<PriorityOrder>::new()
+        .register::<PriorityOrder>()
+        .instance(
+            PriorityOrder {
+                order: PriorityOrderState::new(),
+            },
+            || PriorityOrderAction::Tick.into(),
+        )
+        .build();
+
+    runner.run();
+
+    // Dispatched last, but processed first.
+    let order_state: &PriorityOrderState = runner.state().substate();
+
+    assert_eq!(order_state.processed, vec!["high", "low"]);
+}
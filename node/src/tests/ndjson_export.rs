@@ -0,0 +1,72 @@
+use crate::{
+    automaton::runner::RunnerBuilder, models::pure::tests::tee_capture::action::TeeCaptureAction,
+    tests::tcp_tee_capture::TeeCapture,
+};
+use std::{cell::RefCell, io, rc::Rc};
+
+// Collects the output of `RunnerBuilder::export_ndjson`.
+#[derive(Clone, Default)]
+pub struct SharedBuffer(pub Rc<RefCell<Vec<u8>>>);
+
+impl io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn tcp_ndjson_export() {
+    let output = SharedBuffer::default();
+
+    RunnerBuilder::<TeeCapture>::new()
+        .register::<TeeCapture>()
+        .export_ndjson(output.clone())
+        .instance(TeeCapture::new("127.0.0.1:8921", b"ping"), || {
+            TeeCaptureAction::Tick.into()
+        })
+        .build()
+        .run();
+
+    let output = String::from_utf8(output.0.take()).unwrap();
+    let mut actions = Vec::new();
+
+    for (seq, line) in output.lines().enumerate() {
+        let line: serde_json::Value = serde_json::from_str(line).expect(line);
+
+        assert_eq!(line["seq"], seq);
+        assert!(line["time"].is_u64());
+        assert_eq!(line["instance"], 0);
+
+        // Unit variants are serialized as strings, the others as objects.
+        let variant = match &line["action"] {
+            serde_json::Value::String(variant) => variant.clone(),
+            serde_json::Value::Object(fields) => fields.keys().next().unwrap().clone(),
+            action => panic!("unexpected action {}", action),
+        };
+
+        actions.push((line["model"].as_str().unwrap().to_string(), variant));
+    }
+
+    for expected in [
+        ("tee_capture", "Tick"),
+        ("tcp_client", "Connect"),
+        ("tcp", "Connect"),
+        ("mio", "TcpConnect"),
+        ("mio", "TcpWrite"),
+        ("mio", "TcpRead"),
+        ("tee_capture", "SendSuccess"),
+    ] {
+        assert!(
+            actions
+                .iter()
+                .any(|(model, variant)| (model.as_str(), variant.as_str()) == expected),
+            "{:?} not exported",
+            expected
+        );
+    }
+}
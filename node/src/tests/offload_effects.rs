@@ -0,0 +1,69 @@
+use crate::{
+    automaton::runner::RunnerBuilder,
+    models::pure::tests::tee_capture::{action::TeeCaptureAction, state::TeeCaptureState},
+    tests::tcp_tee_capture::TeeCapture,
+};
+use std::{fs, panic};
+
+// Runs the `TeeCaptureState` model (the server echoes what it receives).
+// Returns the data received by the server and the number of effects that ran
+// off the runner's thread.
+fn run_tee_echo(address: &str, offload_effects: bool) -> (Option<Vec<u8>>, u64) {
+    let mut builder = RunnerBuilder::<TeeCapture>::new().register::<TeeCapture>();
+
+    if offload_effects {
+        builder = builder.offload_effects(2);
+    }
+
+    let mut runner = builder
+        .instance(TeeCapture::new(address, b"offloaded ping"), || {
+            TeeCaptureAction::Tick.into()
+        })
+        .build();
+
+    runner.run();
+
+    let tee_state: &TeeCaptureState = runner.state().substate();
+
+    (
+        tee_state.delivered_data.clone(),
+        runner.off_thread_effects(),
+    )
+}
+
+#[test]
+fn tcp_offload_effects() {
+    let (inline_data, inline_off_thread) = run_tee_echo("127.0.0.1:8901", false);
+    let (offloaded_data, offloaded_off_thread) = run_tee_echo("127.0.0.1:8902", true);
+
+    assert_eq!(inline_data, Some(b"offloaded ping".to_vec()));
+    assert_eq!(offloaded_data, inline_data);
+    assert_eq!(inline_off_thread, 0);
+    assert!(offloaded_off_thread > 0);
+}
+
+#[test]
+fn tcp_offload_effects_record_replay() {
+    let session = "tcp_offload_effects_record_replay";
+    let build = || {
+        RunnerBuilder::<TeeCapture>::new()
+            .register::<TeeCapture>()
+            .offload_effects(2)
+            .instance(TeeCapture::new("127.0.0.1:8949", b"offloaded ping"), || {
+                TeeCaptureAction::Tick.into()
+            })
+            .build()
+    };
+
+    build().record(session);
+
+    let mut runner = build();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| runner.replay(session)));
+
+    fs::remove_file(format!("{}_0.rec", session)).expect("recording not found");
+    assert!(result.is_ok(), "replay failed");
+
+    let tee_state: &TeeCaptureState = runner.state().substate();
+
+    assert_eq!(tee_state.delivered_data, Some(b"offloaded ping".to_vec()));
+}
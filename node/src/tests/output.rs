@@ -0,0 +1,78 @@
+use crate::{
+    automaton::{
+        model::Effectful,
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::{
+        effectful::output::{
+            action::OutputLevel,
+            state::{OutputSink, OutputState},
+        },
+        pure::tests::output_log::{action::OutputLogAction, state::OutputLogState},
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, cell::RefCell, fs, rc::Rc};
+
+#[derive(ModelState, Debug)]
+pub struct OutputLog {
+    pub output_log: OutputLogState,
+}
+
+impl RegisterModel for OutputLog {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<OutputLogState>()
+    }
+}
+
+type Captured = Rc<RefCell<Vec<(OutputLevel, String)>>>;
+
+fn builder(captured: &Captured) -> RunnerBuilder<OutputLog> {
+    RunnerBuilder::<OutputLog>::new()
+        .register::<OutputLog>()
+        .model_effectful(Effectful(OutputState::with_sink(OutputSink::Capture(
+            captured.clone(),
+        ))))
+        .instance(
+            OutputLog {
+                output_log: OutputLogState::new(3),
+            },
+            || OutputLogAction::Tick.into(),
+        )
+}
+
+#[test]
+fn output_recorded_and_suppressed_on_replay() {
+    let session = "output_recorded_and_suppressed_on_replay";
+    let captured = Captured::default();
+
+    builder(&captured).build().record(session);
+
+    let recording = fs::read(format!("{}_0.rec", session)).expect("recording not found");
+    let lines = ["line 0", "line 1", "line 2"];
+
+    assert_eq!(
+        *captured.borrow(),
+        lines.map(|line| (OutputLevel::Info, line.to_string()))
+    );
+
+    for line in lines {
+        assert!(
+            recording
+                .windows(line.len())
+                .any(|window| window == line.as_bytes()),
+            "{:?} not in the recording",
+            line
+        );
+    }
+
+    let replayed = Captured::default();
+    let mut runner = builder(&replayed).build();
+
+    runner.replay(session);
+    fs::remove_file(format!("{}_0.rec", session)).expect("recording not found");
+
+    assert!(replayed.borrow().is_empty());
+    assert_eq!(runner.state().substate::<OutputLogState>().logged, 3);
+}
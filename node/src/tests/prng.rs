@@ -0,0 +1,54 @@
+use crate::models::pure::prng::state::{PRNGAlgorithm, PRNGConfig, PRNGState};
+use rand::RngCore;
+
+fn bytes(prng: &mut PRNGState, len: usize) -> Vec<u8> {
+    let mut data = vec![0u8; len];
+
+    prng.rng.fill_bytes(&mut data);
+    data
+}
+
+fn prng(algorithm: PRNGAlgorithm, seed: u64) -> PRNGState {
+    PRNGState::from_config(PRNGConfig { seed, algorithm })
+}
+
+#[test]
+fn prng_algorithm_determinism() {
+    let algorithms = [
+        PRNGAlgorithm::SmallRng,
+        PRNGAlgorithm::ChaCha8,
+        PRNGAlgorithm::ChaCha12,
+        PRNGAlgorithm::ChaCha20,
+    ];
+    let sequences: Vec<Vec<u8>> = algorithms
+        .iter()
+        .map(|&algorithm| {
+            let sequence = bytes(&mut prng(algorithm, 1337), 64);
+
+            assert_eq!(bytes(&mut prng(algorithm, 1337), 64), sequence);
+            assert_ne!(bytes(&mut prng(algorithm, 1338), 64), sequence);
+            sequence
+        })
+        .collect();
+
+    // Each algorithm produces its own sequence for the same seed.
+    for (i, sequence) in sequences.iter().enumerate() {
+        assert!(!sequences[i + 1..].contains(sequence));
+    }
+
+    // ChaCha sequences don't depend on the platform or the run.
+    assert_eq!(sequences[1][..8], [174, 41, 21, 248, 73, 91, 130, 171]);
+}
+
+#[test]
+fn prng_reseed_keeps_algorithm() {
+    let mut prng_state = prng(PRNGAlgorithm::ChaCha8, 1337);
+
+    bytes(&mut prng_state, 16);
+    prng_state.seed(31337);
+    assert_eq!(prng_state.algorithm, PRNGAlgorithm::ChaCha8);
+    assert_eq!(
+        bytes(&mut prng_state, 64),
+        bytes(&mut prng(PRNGAlgorithm::ChaCha8, 31337), 64)
+    );
+}
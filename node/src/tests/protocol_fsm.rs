@@ -0,0 +1,119 @@
+use crate::{
+    automaton::{
+        action::{Action, ActionKind, Dispatcher, Timeout},
+        model::PureModel,
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::{
+        net::protocol_fsm::{
+            action::{ProtocolFsmAction, StateTimeout},
+            state::ProtocolFsmState,
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use serde_derive::{Deserialize, Serialize};
+use std::{any::Any, collections::BTreeMap, time::Duration};
+use type_uuid::TypeUuid;
+
+#[derive(ModelState, Debug)]
+pub struct ProtocolNode {
+    pub time: TimeState,
+    pub protocol_fsm: ProtocolFsmState,
+}
+
+// Timeout handlers of the protocol model driving the FSM.
+#[derive(Clone, PartialEq, Eq, TypeUuid, Serialize, Deserialize, Debug)]
+#[uuid = "9b85c0e2-1c29-469b-85af-014764491c7f"]
+pub enum GreeterAction {
+    GreetingTimeout { connection: Uid, state: String },
+    AuthTimeout { connection: Uid, state: String },
+}
+
+impl Action for GreeterAction {
+    const KIND: ActionKind = ActionKind::Pure;
+}
+
+fn process_at(
+    state: &mut State<ProtocolNode>,
+    time: u64,
+    action: ProtocolFsmAction,
+) -> Vec<GreeterAction> {
+    let mut dispatcher = Dispatcher::new(|| ProtocolFsmAction::CheckTimeouts.into());
+
+    state
+        .substate_mut::<TimeState>()
+        .set_time(Duration::from_millis(time));
+    ProtocolFsmState::process_pure(state, action, &mut dispatcher);
+
+    std::iter::from_fn(|| dispatcher.next_queued_action())
+        .map(|action| {
+            *action
+                .ptr
+                .downcast::<GreeterAction>()
+                .expect("unexpected action")
+        })
+        .collect()
+}
+
+#[test]
+fn protocol_fsm_timeout_per_state() {
+    let mut state = State::<ProtocolNode>::new();
+    let connection = Uid::from(1usize);
+    let states = BTreeMap::from([
+        (
+            "greeting".to_string(),
+            StateTimeout {
+                timeout: Timeout::Millis(5000),
+                on_timeout: callback!(|(connection: Uid, state: String)| GreeterAction::GreetingTimeout { connection, state }),
+            },
+        ),
+        (
+            "auth".to_string(),
+            StateTimeout {
+                timeout: Timeout::Millis(300),
+                on_timeout: callback!(|(connection: Uid, state: String)| GreeterAction::AuthTimeout { connection, state }),
+            },
+        ),
+    ]);
+
+    state.substates.push(ProtocolNode {
+        time: TimeState::default(),
+        protocol_fsm: ProtocolFsmState::new(),
+    });
+
+    let new = ProtocolFsmAction::New {
+        connection,
+        states,
+        initial: "greeting".to_string(),
+    };
+    let auth = ProtocolFsmAction::Transition {
+        connection,
+        state: "auth".to_string(),
+    };
+
+    assert!(process_at(&mut state, 0, new).is_empty());
+    // Greeted in time: the greeting timeout (due at 5000) is cancelled, and
+    // the auth one armed.
+    assert!(process_at(&mut state, 1000, auth).is_empty());
+    assert!(process_at(&mut state, 1299, ProtocolFsmAction::CheckTimeouts).is_empty());
+    assert_eq!(
+        process_at(&mut state, 1300, ProtocolFsmAction::CheckTimeouts),
+        [GreeterAction::AuthTimeout {
+            connection,
+            state: "auth".to_string()
+        }]
+    );
+
+    // Fired once, and never for the greeting state.
+    assert!(process_at(&mut state, 6000, ProtocolFsmAction::CheckTimeouts).is_empty());
+    assert_eq!(
+        state
+            .substate::<ProtocolFsmState>()
+            .get_fsm(&connection)
+            .state,
+        "auth"
+    );
+}
@@ -0,0 +1,83 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, Uid},
+    },
+    callback,
+    models::pure::net::{
+        tcp::state::TcpState,
+        tcp_server::{
+            action::TcpServerAction,
+            state::{ConnectionCount, Listener, TcpServerState},
+        },
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct QueryNode {
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+}
+
+impl RegisterModel for QueryNode {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpServerState>()
+    }
+}
+
+#[test]
+fn query_connection_count() {
+    let listener = Uid::from(1usize);
+    let mut tcp_server = TcpServerState::new();
+
+    tcp_server.new_listener(
+        listener,
+        Listener::new(
+            16,
+            callback!(|listener: Uid| TcpServerAction::NewSuccess { listener }),
+            callback!(|(listener: Uid, error: String)| TcpServerAction::NewError { listener, error }),
+            callback!(|(_listener: Uid, connection: Uid)| TcpServerAction::CloseEventNotify { connection }),
+            callback!(|(_listener: Uid, connection: Uid)| TcpServerAction::CloseEventNotify { connection }),
+            callback!(|listener: Uid| TcpServerAction::NewSuccess { listener }),
+        ),
+    );
+
+    for uid in 10..13usize {
+        let connection = Uid::from(uid);
+
+        tcp_server.new_connection(connection, listener);
+        tcp_server
+            .get_listener_mut(&listener)
+            .route_connection(connection, "10.0.0.1:40000");
+    }
+
+    let listener_object = tcp_server.get_listener_mut(&listener);
+
+    listener_object.remove_connection(&Uid::from(10usize));
+    // Accepted but not established until admitted.
+    listener_object.connections.insert(Uid::from(13usize));
+    listener_object.pending_admission.insert(Uid::from(13usize));
+
+    let runner = RunnerBuilder::<QueryNode>::new()
+        .register::<QueryNode>()
+        .instance(
+            QueryNode {
+                tcp: TcpState::new(),
+                tcp_server,
+            },
+            || {
+                TcpServerAction::CloseEventNotify {
+                    connection: Uid::default(),
+                }
+                .into()
+            },
+        )
+        .build();
+
+    assert_eq!(
+        runner.state().query::<TcpServerState, _>(ConnectionCount),
+        2
+    );
+}
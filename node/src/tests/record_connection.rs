@@ -0,0 +1,106 @@
+use crate::{
+    automaton::{
+        runner::{Runner, RunnerBuilder},
+        state::Uid,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::send_to_group::{action::SendToGroupAction, state::SendToGroupState},
+        time::state::TimeState,
+    },
+    tests::tcp_server_send_to_group::SendToGroup,
+};
+use serde_json::Value;
+use std::fs;
+
+// Three clients connect, then the server sends to the first two server ends.
+fn group_runner(recorded_connection: Option<Uid>) -> Runner<SendToGroup> {
+    let mut builder = RunnerBuilder::<SendToGroup>::new().register::<SendToGroup>();
+
+    if let Some(connection) = recorded_connection {
+        builder = builder.record_connection(connection);
+    }
+
+    builder
+        .instance(
+            SendToGroup {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                send_to_group: SendToGroupState::new(
+                    "127.0.0.1:8927".to_string(),
+                    b"hello room".to_vec(),
+                ),
+            },
+            || SendToGroupAction::Tick.into(),
+        )
+        .build()
+}
+
+// `connection` field of the action in a line of `Runner::recording_to_json`.
+fn connection(line: &Value) -> Option<u64> {
+    line["action"]
+        .as_object()?
+        .values()
+        .find_map(|fields| fields.get("connection")?.as_u64())
+}
+
+#[test]
+fn record_connection() {
+    let session = "record_connection";
+    let mut runner = group_runner(None);
+
+    runner.run();
+
+    // The first server end, a member of the group. `Uid`s are allocated in
+    // the same order on every run of the session.
+    let server_end = runner
+        .state()
+        .substate::<SendToGroupState>()
+        .server_connections[0];
+    let mut runner = group_runner(Some(server_end));
+
+    runner.record(session);
+
+    let recording = runner.recording_to_json(session, 0);
+
+    fs::remove_file(format!("{}_0.rec", session)).expect("recording not found");
+
+    let effects: Vec<&str> = recording
+        .iter()
+        .filter(|line| line["model"] == "mio")
+        .filter_map(|line| {
+            line["action"]
+                .as_object()?
+                .keys()
+                .next()
+                .map(String::as_str)
+        })
+        .collect();
+
+    // The connection's whole lifecycle, without the listener, the polls or
+    // the other connections (the next client is connected once it's
+    // accepted, and the group send also writes to the other member).
+    assert_eq!(
+        effects,
+        [
+            "TcpAccept",
+            "PollRegisterTcpConnection",
+            "TcpWrite",
+            "PollDeregisterTcpConnection",
+            "TcpClose"
+        ]
+    );
+    assert_eq!(
+        recording.last().map(|line| &line["model"]),
+        Some(&Value::from("send_to_group"))
+    );
+    assert!(recording
+        .iter()
+        .filter_map(connection)
+        .all(|connection| connection == u64::from(server_end)));
+}
@@ -0,0 +1,131 @@
+use crate::{
+    automaton::{
+        action::NewEffects,
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::tests::replay_effect::{
+        action::ReplayEffectAction,
+        state::{ReplayEffect, ReplayEffectState},
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, fs, panic};
+
+#[derive(ModelState, Debug)]
+pub struct ReplayNode {
+    pub replay_effect: ReplayEffectState,
+}
+
+impl RegisterModel for ReplayNode {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<ReplayEffectState>()
+    }
+}
+
+fn builder(effect: ReplayEffect) -> RunnerBuilder<ReplayNode> {
+    builder_with(ReplayEffectState::new(effect))
+}
+
+fn builder_with(replay_effect: ReplayEffectState) -> RunnerBuilder<ReplayNode> {
+    RunnerBuilder::<ReplayNode>::new()
+        .register::<ReplayNode>()
+        .instance(ReplayNode { replay_effect }, || {
+            ReplayEffectAction::Tick.into()
+        })
+}
+
+// `ReplayEffect::PollCreate`, plus the new effect.
+fn with_new_effect() -> ReplayEffectState {
+    let mut replay_effect = ReplayEffectState::new(ReplayEffect::PollCreate);
+
+    replay_effect.new_effect = true;
+    replay_effect
+}
+
+// Records a session with `recorded` and replays it with `replayed`. Returns
+// the replay panic message, if any.
+fn record_replay(session: &str, recorded: ReplayEffect, replayed: ReplayEffect) -> Option<String> {
+    builder(recorded).build().record(session);
+
+    let result = panic::catch_unwind(|| builder(replayed).build().replay(session));
+
+    fs::remove_file(format!("{}_0.rec", session)).expect("recording not found");
+
+    let error = result.err()?;
+
+    error
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| error.downcast_ref::<&str>().map(|error| error.to_string()))
+}
+
+#[test]
+fn replay_recorded_results() {
+    assert_eq!(
+        record_replay(
+            "replay_recorded_results",
+            ReplayEffect::PollCreate,
+            ReplayEffect::PollCreate
+        ),
+        None
+    );
+}
+
+#[test]
+fn replay_result_mismatch() {
+    let error = record_replay(
+        "replay_result_mismatch",
+        ReplayEffect::EventsCreate,
+        ReplayEffect::PollCreate,
+    )
+    .expect("replay succeeded");
+
+    assert!(
+        error.starts_with(
+            "Replay mismatch: recorded result of MioEffectfulAction::EventsCreate \
+             fed to MioEffectfulAction::PollCreate"
+        ),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn replay_new_effect_live() {
+    let session = "replay_new_effect_live";
+
+    builder(ReplayEffect::PollCreate).build().record(session);
+
+    let mut runner = builder_with(with_new_effect())
+        .replay_new_effects(NewEffects::Live)
+        .build();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| runner.replay(session)));
+
+    fs::remove_file(format!("{}_0.rec", session)).expect("recording not found");
+    assert!(result.is_ok(), "replay failed");
+
+    // The recorded result was fed to the recorded effect, and the new one ran.
+    let replay_state: &ReplayEffectState = runner.state().substate();
+
+    assert!(replay_state.created.is_some());
+    assert!(replay_state.events.is_some());
+}
+
+#[test]
+fn replay_new_effect_rejected() {
+    let session = "replay_new_effect_rejected";
+
+    builder(ReplayEffect::PollCreate).build().record(session);
+
+    let result = panic::catch_unwind(|| builder_with(with_new_effect()).build().replay(session));
+
+    fs::remove_file(format!("{}_0.rec", session)).expect("recording not found");
+
+    let error = result.expect_err("replay succeeded");
+    let error = error
+        .downcast_ref::<String>()
+        .expect("unexpected panic payload");
+
+    assert!(error.starts_with("Replay diverged"), "{}", error);
+}
@@ -0,0 +1,155 @@
+use crate::{
+    automaton::{
+        action::Timeout,
+        runner::{Runner, RunnerBuilder},
+        state::{State, Uid},
+    },
+    models::pure::{
+        net::tcp::{action::TcpAction, state::ConnectionLogEvent},
+        tests::{
+            echo_client::{
+                action::EchoClientAction,
+                state::{EchoClientConfig, EchoClientStatus, PayloadPattern},
+            },
+            echo_server::{action::EchoServerAction, state::EchoServerConfig},
+        },
+    },
+    tests::echo_network::{EchoClient, EchoNetwork, EchoServer},
+};
+use std::fs;
+
+const SESSION: &str = "replay_patch";
+
+fn echo_network() -> Runner<EchoNetwork> {
+    RunnerBuilder::<EchoNetwork>::new()
+        .register::<EchoNetwork>()
+        .instance(
+            EchoNetwork::EchoServer(EchoServer::from_config(EchoServerConfig {
+                address: "127.0.0.1:8913".to_string(),
+                max_connections: 1,
+                poll_timeout: 100,
+                recv_timeout: 500,
+            })),
+            || EchoServerAction::Tick.into(),
+        )
+        .instance(
+            EchoNetwork::EchoClient(EchoClient::from_config(EchoClientConfig {
+                connect_to_address: "127.0.0.1:8913".to_string(),
+                connect_timeout: Timeout::Millis(1000),
+                poll_timeout: 100,
+                max_connection_attempts: 10,
+                retry_interval_ms: 500,
+                max_send_size: 10240,
+                min_rnd_timeout: 1000,
+                max_rnd_timeout: 10000,
+                payload: PayloadPattern::Random,
+            })),
+            || EchoClientAction::Tick.into(),
+        )
+        .build()
+}
+
+fn client(state: &State<EchoNetwork>) -> &EchoClient {
+    let EchoNetwork::EchoClient(client) = &state.substates[1] else {
+        unreachable!()
+    };
+
+    client
+}
+
+fn recv_errors(client: &EchoClient, connection: &Uid) -> Vec<String> {
+    client
+        .tcp
+        .connection_history(connection)
+        .expect("connection not found")
+        .iter()
+        .filter_map(|entry| match &entry.event {
+            ConnectionLogEvent::RecvError(error) => Some(error.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn replay_patched_recv_error() {
+    // Record the session up to the end of the client's first echo.
+    let mut runner = echo_network();
+
+    runner.open_record(SESSION);
+
+    let mut stepper = runner.stepper();
+    // The client's recv request.
+    let mut recv = None;
+    // Once created in `TcpState`: the step its recorded result completed it.
+    let mut pending = false;
+    let mut completed = None;
+
+    while stepper.seq() < 100_000 {
+        assert!(stepper.step());
+
+        let client = client(stepper.state());
+
+        match (&client.echo_client.status, recv) {
+            (
+                EchoClientStatus::Receiving {
+                    connection,
+                    request,
+                    ..
+                },
+                None,
+            ) => recv = Some((*connection, *request)),
+            (EchoClientStatus::Connected { .. }, Some(_)) => break,
+            (_, Some((_, request))) if completed.is_none() => {
+                if client.tcp.has_recv_request(&request) {
+                    pending = true
+                } else if pending {
+                    completed = Some(stepper.seq())
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let (connection, request) = recv.expect("client never received");
+    let completed = completed.expect("recv request never completed");
+    let echoed = stepper.seq();
+
+    drop(runner);
+
+    // Replay it with the recv result made an error.
+    let mut runner = echo_network();
+    let mut replayer = runner.replayer(SESSION);
+
+    replayer.patch(completed, |recorded: TcpAction| match recorded {
+        TcpAction::RecvSuccess { uid, .. } | TcpAction::RecvSuccessPartial { uid, .. } => {
+            TcpAction::RecvError {
+                uid,
+                error: "patched".to_string(),
+            }
+        }
+        _ => panic!("unexpected recorded result {:?}", recorded),
+    });
+
+    assert!(replayer.run_to_seq(completed - 1));
+    assert!(recv_errors(client(replayer.state()), &connection).is_empty());
+    assert_eq!(client(replayer.state()).echo_client.last_error, None);
+
+    // Up to where the recorded client got its echo, the models took the
+    // error path instead.
+    assert!(replayer.run_to_seq(echoed));
+
+    let client = client(replayer.state());
+
+    assert_eq!(recv_errors(client, &connection), ["patched"]);
+    // The client's `RecvError` handler ran.
+    assert_eq!(client.echo_client.last_error.as_deref(), Some("patched"));
+    assert!(!client.tcp.has_recv_request(&request));
+    assert!(matches!(
+        client.echo_client.status,
+        EchoClientStatus::Receiving { request: receiving, .. } if receiving == request
+    ));
+
+    for instance in 0..2 {
+        fs::remove_file(format!("{}_{}.rec", SESSION, instance)).expect("recording not found");
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    automaton::{
+        action::Timeout,
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State},
+    },
+    models::pure::{
+        net::{
+            replay_sends::state::{
+                recorded_exchange, RecordedOperation, ReplayReport, ReplaySendsState,
+            },
+            tcp::state::{OperationKind, TcpState},
+            tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::{
+            echo_client::{
+                action::EchoClientAction,
+                state::{EchoClientConfig, EchoClientState, EchoClientStatus, PayloadPattern},
+            },
+            echo_server::{
+                action::EchoServerAction,
+                state::{EchoServerConfig, EchoServerState},
+            },
+            replay_client::{
+                action::ReplayClientAction,
+                state::{ReplayClientConfig, ReplayClientState},
+            },
+        },
+        time::state::TimeState,
+    },
+    tests::echo_network::{EchoClient, EchoServer},
+};
+use model_state_derive::ModelState;
+use std::{any::Any, fs};
+
+#[derive(ModelState, Debug)]
+pub struct ReplayClient {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_client: TcpClientState,
+    // Unused, but the shutdown hooks of all the models (`TcpServerState`'s
+    // included) run on every instance.
+    pub tcp_server: TcpServerState,
+    pub replay_sends: ReplaySendsState,
+    pub replay_client: ReplayClientState,
+}
+
+#[derive(ModelState, Debug)]
+pub enum ConformanceNetwork {
+    EchoServer(EchoServer),
+    EchoClient(EchoClient),
+    ReplayClient(ReplayClient),
+}
+
+impl RegisterModel for ConformanceNetwork {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder
+            .register::<EchoClientState>()
+            .register::<EchoServerState>()
+            .register::<ReplayClientState>()
+    }
+}
+
+fn echo_server(address: &str) -> ConformanceNetwork {
+    ConformanceNetwork::EchoServer(EchoServer::from_config(EchoServerConfig {
+        address: address.to_string(),
+        max_connections: 1,
+        poll_timeout: 100,
+        recv_timeout: 500,
+    }))
+}
+
+// Replays `exchange` against a fresh echo server, returns the report.
+fn replay(address: &str, exchange: Vec<RecordedOperation>) -> ReplayReport {
+    let mut runner = RunnerBuilder::<ConformanceNetwork>::new()
+        .register::<ConformanceNetwork>()
+        .instance(echo_server(address), || EchoServerAction::Tick.into())
+        .instance(
+            ConformanceNetwork::ReplayClient(ReplayClient {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_client: TcpClientState::new(),
+                tcp_server: TcpServerState::new(),
+                replay_sends: ReplaySendsState::new(),
+                replay_client: ReplayClientState::from_config(ReplayClientConfig {
+                    address: address.to_string(),
+                    exchange,
+                    compare: true,
+                    poll_timeout: 100,
+                    timeout: 1000,
+                    max_attempts: 10,
+                }),
+            }),
+            || ReplayClientAction::Tick.into(),
+        )
+        .build();
+
+    runner.run();
+
+    match &runner.state().substates[1] {
+        ConformanceNetwork::ReplayClient(client) => client
+            .replay_client
+            .report
+            .clone()
+            .expect("replay not completed"),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn replay_sends_conformance() {
+    let session = "replay_sends_conformance";
+    let mut runner = RunnerBuilder::<ConformanceNetwork>::new()
+        .register::<ConformanceNetwork>()
+        .instance(echo_server("127.0.0.1:8935"), || {
+            EchoServerAction::Tick.into()
+        })
+        .instance(
+            ConformanceNetwork::EchoClient(EchoClient::from_config(EchoClientConfig {
+                connect_to_address: "127.0.0.1:8935".to_string(),
+                connect_timeout: Timeout::Millis(1000),
+                poll_timeout: 100,
+                max_connection_attempts: 10,
+                retry_interval_ms: 500,
+                max_send_size: 256,
+                min_rnd_timeout: 1000,
+                max_rnd_timeout: 2000,
+                payload: PayloadPattern::Sequential,
+            })),
+            || EchoClientAction::Tick.into(),
+        )
+        .build();
+    let client_status = |state: &State<ConformanceNetwork>| match &state.substates[1] {
+        ConformanceNetwork::EchoClient(client) => match client.echo_client.status {
+            EchoClientStatus::Receiving { connection, .. } => (Some(connection), true),
+            EchoClientStatus::Connected { connection }
+            | EchoClientStatus::Sending { connection, .. } => (Some(connection), false),
+            _ => (None, false),
+        },
+        _ => unreachable!(),
+    };
+    let (mut connection, mut receiving, mut echoed) = (None, false, 0);
+
+    // Record the client's exchange up to its third echo.
+    runner.open_record(session);
+    assert!(runner.run_until(
+        |state| {
+            let (status_connection, status_receiving) = client_status(state);
+
+            connection = connection.or(status_connection);
+            echoed += (receiving && !status_receiving) as usize;
+            receiving = status_receiving;
+            echoed == 3
+        },
+        100_000
+    ));
+
+    // Flushes the recording.
+    drop(runner);
+
+    let recording = RunnerBuilder::<ConformanceNetwork>::new()
+        .register::<ConformanceNetwork>()
+        .build()
+        .recorded_actions(session, 1);
+
+    fs::remove_file(format!("{}_0.rec", session)).expect("recording not found");
+    fs::remove_file(format!("{}_1.rec", session)).expect("recording not found");
+
+    let exchange = recorded_exchange(&recording, connection.expect("client not connected"));
+    let kinds: Vec<OperationKind> = exchange.iter().map(|operation| operation.kind).collect();
+
+    // Outbound and inbound alternate, each send is echoed back.
+    assert_eq!(kinds, [OperationKind::Send, OperationKind::Recv].repeat(3));
+    assert!(exchange
+        .chunks(2)
+        .all(|operations| operations[0].data == operations[1].data));
+
+    let recorded: Vec<Vec<u8>> = exchange
+        .iter()
+        .filter(|operation| operation.kind == OperationKind::Recv)
+        .map(|operation| operation.data.clone())
+        .collect();
+    let report = replay("127.0.0.1:8936", exchange);
+
+    assert_eq!(report.mismatches, Vec::<usize>::new());
+    assert_eq!(report.received, recorded);
+}
+
+#[test]
+fn replay_sends_mismatch() {
+    // An echo server doesn't answer "pong".
+    let report = replay(
+        "127.0.0.1:8937",
+        vec![
+            RecordedOperation {
+                kind: OperationKind::Send,
+                data: b"ping".to_vec(),
+            },
+            RecordedOperation {
+                kind: OperationKind::Recv,
+                data: b"pong".to_vec(),
+            },
+        ],
+    );
+
+    assert_eq!(report.mismatches, [1]);
+    assert_eq!(report.received, [b"ping".to_vec()]);
+}
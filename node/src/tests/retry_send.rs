@@ -0,0 +1,77 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            retry_send::state::RetrySendState, tcp::state::TcpState,
+            tcp_client::state::TcpClientState,
+        },
+        tests::retry_send_client::{action::RetrySendClientAction, state::RetrySendClientState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, io::Read, net::TcpListener};
+
+#[derive(ModelState, Debug)]
+pub struct RetrySend {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_client: TcpClientState,
+    pub retry_send: RetrySendState,
+    pub client: RetrySendClientState,
+}
+
+impl RegisterModel for RetrySend {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<RetrySendClientState>()
+    }
+}
+
+#[test]
+fn retry_send_after_reconnect() {
+    let address = "127.0.0.1:8942";
+    let listener = TcpListener::bind(address).expect("bind failed");
+    let mut runner = RunnerBuilder::<RetrySend>::new()
+        .register::<RetrySend>()
+        .instance(
+            RetrySend {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_client: TcpClientState::new(),
+                retry_send: RetrySendState::default(),
+                client: RetrySendClientState::new(address.to_string(), 50, b"hello".to_vec()),
+            },
+            || RetrySendClientAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state
+            .substate::<RetrySendClientState>()
+            .connection
+            .is_some(),
+        1000
+    ));
+
+    // The peer goes away: the first send attempt fails.
+    drop(listener.accept().expect("accept failed"));
+
+    assert!(runner.run_until(
+        |state| state.substate::<RetrySendClientState>().sent.is_some(),
+        1000
+    ));
+
+    let client_state: &RetrySendClientState = runner.state().substate();
+    let (_, connection) = client_state.sent.unwrap();
+
+    assert_ne!(Some(connection), client_state.connection);
+
+    let (mut peer, _) = listener.accept().expect("accept failed");
+    let mut received = [0u8; 5];
+
+    peer.read_exact(&mut received).expect("read failed");
+    assert_eq!(&received, b"hello");
+}
@@ -0,0 +1,53 @@
+use crate::{
+    automaton::{action::Timeout, runner::RunnerBuilder},
+    models::pure::tests::{
+        echo_client::{
+            action::EchoClientAction,
+            state::{EchoClientConfig, EchoClientStatus, PayloadPattern},
+        },
+        echo_server::{action::EchoServerAction, state::EchoServerConfig},
+    },
+    tests::echo_network::{EchoClient, EchoNetwork, EchoServer},
+};
+
+#[test]
+fn run_until_client_connected() {
+    let mut runner = RunnerBuilder::<EchoNetwork>::new()
+        .register::<EchoNetwork>()
+        .instance(
+            EchoNetwork::EchoServer(EchoServer::from_config(EchoServerConfig {
+                address: "127.0.0.1:8905".to_string(),
+                max_connections: 1,
+                poll_timeout: 100,
+                recv_timeout: 500,
+            })),
+            || EchoServerAction::Tick.into(),
+        )
+        .instance(
+            EchoNetwork::EchoClient(EchoClient::from_config(EchoClientConfig {
+                connect_to_address: "127.0.0.1:8905".to_string(),
+                connect_timeout: Timeout::Millis(1000),
+                poll_timeout: 100,
+                max_connection_attempts: 10,
+                retry_interval_ms: 500,
+                max_send_size: 10240,
+                min_rnd_timeout: 1000,
+                max_rnd_timeout: 10000,
+                payload: PayloadPattern::Random,
+            })),
+            || EchoClientAction::Tick.into(),
+        )
+        .build();
+
+    let connected = runner.run_until(
+        |state| match &state.substates[1] {
+            EchoNetwork::EchoClient(client) => {
+                matches!(client.echo_client.status, EchoClientStatus::Connected { .. })
+            }
+            EchoNetwork::EchoServer(_) => unreachable!(),
+        },
+        10_000,
+    );
+
+    assert!(connected);
+}
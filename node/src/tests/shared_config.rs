@@ -0,0 +1,59 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::tests::shared_config::{
+        action::SharedConfigAction,
+        state::{ChaosConfig, SharedConfigState},
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct SharedConfig {
+    pub shared: SharedConfigState,
+}
+
+impl RegisterModel for SharedConfig {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<SharedConfigState>()
+    }
+}
+
+#[test]
+fn shared_config_read_by_two_models() {
+    let mut runner = RunnerBuilder::<SharedConfig>::new()
+        .register::<SharedConfig>()
+        .config(ChaosConfig { enabled: true })
+        .instance(
+            SharedConfig {
+                shared: SharedConfigState::new(),
+            },
+            || SharedConfigAction::Tick.into(),
+        )
+        .build();
+
+    runner.run();
+
+    let shared_state: &SharedConfigState = runner.state().substate();
+    let config = ChaosConfig { enabled: true };
+
+    assert_eq!(
+        shared_state.read,
+        [
+            ("shared_config", config.clone()),
+            ("shared_config_peer", config.clone())
+        ]
+    );
+    assert_eq!(runner.state().config::<ChaosConfig>(), &config);
+}
+
+#[test]
+#[should_panic(expected = "Attempt to re-set config")]
+fn shared_config_set_once() {
+    RunnerBuilder::<SharedConfig>::new()
+        .config(ChaosConfig { enabled: true })
+        .config(ChaosConfig { enabled: false });
+}
@@ -0,0 +1,47 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::tests::shutdown_order::{
+        action::ShutdownUpperAction,
+        state::{ShutdownLowerState, ShutdownUpperState},
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct ShutdownOrder {
+    pub lower: ShutdownLowerState,
+    pub upper: ShutdownUpperState,
+}
+
+impl RegisterModel for ShutdownOrder {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<ShutdownUpperState>()
+    }
+}
+
+#[test]
+fn shutdown_order() {
+    let mut runner = RunnerBuilder::<ShutdownOrder>::new()
+        .register::<ShutdownOrder>()
+        .instance(
+            ShutdownOrder {
+                lower: ShutdownLowerState::default(),
+                upper: ShutdownUpperState::default(),
+            },
+            || ShutdownUpperAction::Tick.into(),
+        )
+        .build();
+
+    runner.run();
+
+    // Dependents first, and their final actions are processed before their
+    // dependencies are shut down.
+    assert_eq!(
+        runner.state().substate::<ShutdownLowerState>().log,
+        ["upper: shutdown", "lower: flush", "lower: shutdown"]
+    );
+}
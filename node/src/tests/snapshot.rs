@@ -0,0 +1,147 @@
+use super::tcp_close_deliver_buffered::CloseDeliverBuffered;
+use crate::{
+    automaton::{
+        action::AnyAction,
+        runner::{Runner, RunnerBuilder},
+    },
+    models::pure::{
+        net::{tcp::state::TcpState, tcp_server::state::TcpServerState},
+        tests::close_deliver_buffered::{
+            action::CloseDeliverBufferedAction, state::CloseDeliverBufferedState,
+        },
+    },
+};
+
+fn tick() -> AnyAction {
+    CloseDeliverBufferedAction::Tick.into()
+}
+
+// Steps the `CloseDeliverBufferedState` model until the server has a pending
+// recv request (waiting for more bytes than the client sends).
+fn run_until_recv_pending(address: &str) -> Runner<CloseDeliverBuffered> {
+    let mut runner = RunnerBuilder::<CloseDeliverBuffered>::new()
+        .register::<CloseDeliverBuffered>()
+        .instance(CloseDeliverBuffered::new(address, b"ping"), tick)
+        .build();
+
+    while runner
+        .state()
+        .substate::<TcpState>()
+        .pending_recv_requests()
+        .is_empty()
+    {
+        assert!(runner.step(), "halted before the recv request");
+    }
+
+    runner
+}
+
+#[test]
+fn snapshot_restore() {
+    let runner = run_until_recv_pending("127.0.0.1:8903");
+    let restored = RunnerBuilder::<CloseDeliverBuffered>::new()
+        .register::<CloseDeliverBuffered>()
+        .from_snapshot(&runner.snapshot(), tick)
+        .build();
+    let (state, restored_state) = (runner.state(), restored.state());
+
+    // New `Uid`s don't collide with the restored objects.
+    assert_eq!(restored_state.uid_source, state.uid_source);
+    assert_eq!(restored_state.substates.len(), 1);
+
+    let CloseDeliverBufferedState {
+        client_connection: Some(client_connection),
+        server_connection: Some(server_connection),
+        recv: Some(recv),
+        ..
+    } = *restored_state.substate()
+    else {
+        panic!("connections not restored")
+    };
+
+    // Connections are restored on both ends, with the server's routing.
+    let (tcp, restored_tcp) = (
+        state.substate::<TcpState>(),
+        restored_state.substate::<TcpState>(),
+    );
+
+    for connection in [client_connection, server_connection] {
+        assert!(restored_tcp.has_connection(&connection));
+        assert_eq!(
+            restored_tcp.connection_addrs(&connection),
+            tcp.connection_addrs(&connection)
+        );
+        assert_eq!(
+            restored_tcp.connection_history(&connection),
+            tcp.connection_history(&connection)
+        );
+    }
+
+    let restored_server: &TcpServerState = restored_state.substate();
+
+    assert_eq!(restored_server.connection_shard(&server_connection), Some(0));
+
+    // The pending recv request is restored, with its buffered data and its
+    // callbacks (found again by name), so it can still be completed.
+    let [(&uid, request)] = restored_tcp.pending_recv_requests()[..] else {
+        panic!("recv request not restored")
+    };
+
+    assert_eq!(uid, recv);
+    assert_eq!(
+        request.buffered_data,
+        tcp.get_recv_request(&recv).buffered_data
+    );
+
+    let server_request = restored_server
+        .recv_requests
+        .get(&recv)
+        .expect("server recv request not restored");
+    let action = server_request
+        .on_success
+        .make((recv, b"pingping".to_vec()))
+        .ptr
+        .downcast::<CloseDeliverBufferedAction>()
+        .expect("unexpected callback action");
+
+    assert_eq!(
+        *action,
+        CloseDeliverBufferedAction::RecvSuccess {
+            uid: recv,
+            data: b"pingping".to_vec()
+        }
+    );
+}
+
+#[test]
+fn snapshot_restore_in_place() {
+    let mut runner = run_until_recv_pending("127.0.0.1:8934");
+    let snapshot = runner.snapshot();
+
+    // Moves on until the state differs from the snapshot.
+    for _ in 0..100 {
+        if runner.snapshot() != snapshot {
+            break;
+        }
+
+        assert!(runner.step(), "halted before the state changed");
+    }
+
+    assert_ne!(runner.snapshot(), snapshot);
+    runner.restore(&snapshot);
+    assert_eq!(runner.snapshot(), snapshot);
+
+    // Actions queued before the restore are dropped.
+    assert_eq!(
+        runner.step_instance(0),
+        Some(std::any::type_name::<CloseDeliverBufferedAction>())
+    );
+}
+
+#[test]
+#[should_panic(expected = "Not a snapshot")]
+fn snapshot_header() {
+    RunnerBuilder::<CloseDeliverBuffered>::new()
+        .register::<CloseDeliverBuffered>()
+        .from_snapshot(b"not a snapshot", tick);
+}
@@ -0,0 +1,47 @@
+use crate::{
+    automaton::runner::RunnerBuilder,
+    models::pure::tests::pure_counter::{
+        action::PureCounterAction,
+        state::{PureCounterConfig, PureCounterState},
+    },
+    tests::forbid_effects::PureCounter,
+};
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+#[test]
+fn max_step_duration_exceeded() {
+    let exceeded = Rc::new(RefCell::new(Vec::new()));
+    let on_exceed = {
+        let exceeded = exceeded.clone();
+
+        move |action: &'static str, duration: Duration| {
+            exceeded.borrow_mut().push((action, duration));
+            true
+        }
+    };
+    let mut runner = RunnerBuilder::<PureCounter>::new()
+        .register::<PureCounter>()
+        .max_step_duration(50, on_exceed)
+        .instance(
+            PureCounter::from_config(PureCounterConfig {
+                max_count: 100,
+                update_time: false,
+                slow_tick: Some((3, 100)),
+            }),
+            || PureCounterAction::Tick.into(),
+        )
+        .build();
+
+    runner.run();
+
+    let exceeded = exceeded.borrow();
+
+    assert_eq!(exceeded.len(), 1);
+    assert!(exceeded[0].0.ends_with("PureCounterAction"), "{}", exceeded[0].0);
+    assert!(exceeded[0].1 >= Duration::from_millis(100));
+
+    // Halted by `on_exceed`, right after the slow tick.
+    let counter_state: &PureCounterState = runner.state().substate();
+
+    assert_eq!(counter_state.count, 3);
+}
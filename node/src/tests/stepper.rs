@@ -0,0 +1,101 @@
+use crate::{
+    automaton::{action::Timeout, runner::RunnerBuilder, state::State},
+    models::pure::tests::{
+        echo_client::{
+            action::EchoClientAction,
+            state::{EchoClientConfig, EchoClientStatus, PayloadPattern},
+        },
+        echo_server::{
+            action::EchoServerAction,
+            state::{EchoServerConfig, EchoServerStatus},
+        },
+    },
+    tests::echo_network::{EchoClient, EchoNetwork, EchoServer},
+};
+
+fn client_status(state: &State<EchoNetwork>) -> &'static str {
+    let EchoNetwork::EchoClient(client) = &state.substates[1] else {
+        unreachable!()
+    };
+
+    match client.echo_client.status {
+        EchoClientStatus::Init => "Init",
+        EchoClientStatus::Connecting => "Connecting",
+        EchoClientStatus::Connected { .. } => "Connected",
+        EchoClientStatus::Sending { .. } => "Sending",
+        EchoClientStatus::Receiving { .. } => "Receiving",
+    }
+}
+
+fn server_listening(state: &State<EchoNetwork>) -> bool {
+    let EchoNetwork::EchoServer(server) = &state.substates[0] else {
+        unreachable!()
+    };
+
+    matches!(server.echo_server.status, EchoServerStatus::Listening { .. })
+}
+
+#[test]
+fn stepper_echo_round_trip() {
+    let mut runner = RunnerBuilder::<EchoNetwork>::new()
+        .register::<EchoNetwork>()
+        .instance(
+            EchoNetwork::EchoServer(EchoServer::from_config(EchoServerConfig {
+                address: "127.0.0.1:8911".to_string(),
+                max_connections: 1,
+                poll_timeout: 100,
+                recv_timeout: 500,
+            })),
+            || EchoServerAction::Tick.into(),
+        )
+        .instance(
+            EchoNetwork::EchoClient(EchoClient::from_config(EchoClientConfig {
+                connect_to_address: "127.0.0.1:8911".to_string(),
+                connect_timeout: Timeout::Millis(1000),
+                poll_timeout: 100,
+                max_connection_attempts: 10,
+                retry_interval_ms: 500,
+                max_send_size: 10240,
+                min_rnd_timeout: 1000,
+                max_rnd_timeout: 10000,
+                payload: PayloadPattern::Random,
+            })),
+            || EchoClientAction::Tick.into(),
+        )
+        .build();
+    let mut stepper = runner.stepper();
+
+    assert_eq!(client_status(stepper.state()), "Init");
+    assert!(!server_listening(stepper.state()));
+
+    // Instances are interleaved: the first two actions are their first ticks.
+    assert_eq!(stepper.step_n(2), 2);
+    assert!(stepper.run_to_seq(10));
+    assert_eq!(stepper.seq(), 10);
+
+    // Walk one action at a time and record every client status change.
+    let mut statuses = vec![client_status(stepper.state())];
+
+    while statuses.len() < 6 && stepper.seq() < 100_000 {
+        assert!(stepper.step());
+
+        let status = client_status(stepper.state());
+
+        if status != *statuses.last().unwrap() {
+            // The server listens before the client gets connected.
+            if status == "Connected" {
+                assert!(server_listening(stepper.state()));
+            }
+
+            statuses.push(status);
+        }
+    }
+
+    let round_trip = ["Connected", "Sending", "Receiving", "Connected"];
+    let start = statuses
+        .iter()
+        .position(|status| *status == "Connected")
+        .expect("client never connected");
+
+    assert_eq!(statuses[start..start + round_trip.len()], round_trip);
+}
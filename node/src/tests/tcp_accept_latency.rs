@@ -0,0 +1,29 @@
+use crate::{fuzz::fuzz_run, models::pure::net::tcp::state::TcpState};
+
+#[test]
+fn tcp_accept_latency() {
+    #[rustfmt::skip]
+    let seed = [
+        // tick: time; tick: TCP init (poll creation succeeds)
+        0, 0,
+        // tick: time; listen (max 2 connections, listen and registration
+        // succeed)
+        0, 0, 1, 0, 0,
+        // tick: time (+50ms); poll (timeout 50, 1 event: listener readable,
+        // accept and registration succeed)
+        50, 1, 50, 0, 1, 0, 1, 0, 0,
+        // tick: time (+50ms); poll (timeout 50, no events: the listener is
+        // still readable, accept and registration succeed)
+        50, 1, 50, 0, 0, 0, 0,
+        // tick: time (+50ms); poll (timeout 50, no events: accept would block)
+        50, 1, 50, 0, 0, 6,
+    ];
+    let runner = fuzz_run(&seed);
+    let stats = runner.state().substate::<TcpState>().accept_latency_stats();
+
+    // The first connection is accepted by the poll that reported the listener
+    // readable, the second one by the next poll.
+    assert_eq!(stats.count, 2);
+    assert_eq!(stats.mean(), Some(25));
+    assert!(stats.max <= 50, "{:?}", stats);
+}
@@ -0,0 +1,89 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::{ConnectionLogEvent, TcpState},
+            tcp_client::state::TcpClientState,
+            tcp_server::state::{TcpServerConfig, TcpServerState},
+        },
+        tests::accept_register_delay::{
+            action::AcceptRegisterDelayAction, state::AcceptRegisterDelayState,
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct AcceptRegisterDelay {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub accept_register_delay: AcceptRegisterDelayState,
+}
+
+impl RegisterModel for AcceptRegisterDelay {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<AcceptRegisterDelayState>()
+    }
+}
+
+#[test]
+fn tcp_accept_register_delay() {
+    let delay = 50;
+    let mut runner = RunnerBuilder::<AcceptRegisterDelay>::new()
+        .register::<AcceptRegisterDelay>()
+        .instance(
+            AcceptRegisterDelay {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::from_config(TcpServerConfig {
+                    accept_register_delay: Some(delay),
+                    ..TcpServerConfig::default()
+                }),
+                tcp_client: TcpClientState::new(),
+                accept_register_delay: AcceptRegisterDelayState::new(
+                    "127.0.0.1:8899".to_string(),
+                    b"early ping".to_vec(),
+                ),
+            },
+            || AcceptRegisterDelayAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| {
+            state
+                .substate::<AcceptRegisterDelayState>()
+                .received
+                .is_some()
+        },
+        1000
+    ));
+
+    let delay_state: &AcceptRegisterDelayState = runner.state().substate();
+    let history = runner
+        .state()
+        .substate::<TcpState>()
+        .connection_history(&delay_state.server_connection.unwrap())
+        .unwrap();
+    let time_of = |event| {
+        history
+            .iter()
+            .find(|entry| entry.event == event)
+            .map(|entry| entry.time)
+            .unwrap()
+    };
+
+    assert_eq!(delay_state.received, Some(b"early ping".to_vec()));
+    // Received once the delayed registration completed.
+    assert!(
+        time_of(ConnectionLogEvent::Registered)
+            >= time_of(ConnectionLogEvent::Accepted { number: 1 }) + u128::from(delay)
+    );
+}
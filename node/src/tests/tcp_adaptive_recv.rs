@@ -0,0 +1,148 @@
+use crate::{
+    automaton::{
+        runner::{Runner, RunnerBuilder},
+        state::Uid,
+    },
+    models::pure::{
+        net::tcp_server::state::{AdaptiveRecv, TcpServerConfig, TcpServerState},
+        tests::echo_server::{
+            action::EchoServerAction,
+            state::{EchoServerConfig, EchoServerState, EchoServerStatus},
+        },
+    },
+    tests::echo_network::EchoServer,
+};
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::TcpStream,
+};
+
+const MIN: usize = 256;
+const MAX: usize = 4096;
+
+fn recv_size(runner: &Runner<EchoServer>, connection: &Uid) -> usize {
+    runner
+        .state()
+        .substate::<TcpServerState>()
+        .recv_size(connection)
+        .unwrap()
+}
+
+// Writes `burst` bytes and runs the echo server until they are echoed back.
+// Returns the recv size on `connection` that follows.
+fn feed(
+    runner: &mut Runner<EchoServer>,
+    stream: &mut TcpStream,
+    connection: &Uid,
+    burst: usize,
+) -> usize {
+    let data: Vec<u8> = (0..burst).map(|i| i as u8).collect();
+    let mut echoed = Vec::new();
+
+    stream.write_all(&data).expect("write failed");
+    assert!(runner.run_until(
+        |_| {
+            let mut buf = [0u8; MAX];
+
+            match stream.read(&mut buf) {
+                Ok(len) => echoed.extend_from_slice(&buf[..len]),
+                Err(error) => assert_eq!(error.kind(), ErrorKind::WouldBlock),
+            }
+
+            echoed.len() >= burst
+        },
+        1000
+    ));
+    assert_eq!(echoed, data);
+    recv_size(runner, connection)
+}
+
+#[test]
+fn tcp_server_adaptive_recv() {
+    let address = "127.0.0.1:8952";
+    let mut server = EchoServer::from_config(EchoServerConfig {
+        address: address.to_string(),
+        max_connections: 1,
+        poll_timeout: 10,
+        // Short enough for the recvs of a chatty peer to time out quickly.
+        recv_timeout: 100,
+    });
+
+    server.tcp_server = TcpServerState::from_config(TcpServerConfig {
+        adaptive_recv: Some(AdaptiveRecv { min: MIN, max: MAX }),
+        ..TcpServerConfig::default()
+    });
+
+    let mut runner = RunnerBuilder::<EchoServer>::new()
+        .register::<EchoServerState>()
+        .instance(server, || EchoServerAction::Tick.into())
+        .build();
+
+    assert!(runner.run_until(
+        |state| matches!(
+            state.substate::<EchoServerState>().status,
+            EchoServerStatus::Listening { .. }
+        ),
+        1000
+    ));
+
+    let mut stream = TcpStream::connect(address).expect("connect failed");
+
+    stream
+        .set_nonblocking(true)
+        .expect("set_nonblocking failed");
+
+    assert!(runner.run_until(
+        |state| state
+            .substate::<TcpServerState>()
+            .listeners
+            .values()
+            .any(|listener| !listener.connections.is_empty()),
+        1000
+    ));
+
+    let connection = *runner
+        .state()
+        .substate::<TcpServerState>()
+        .listeners
+        .values()
+        .find_map(|listener| listener.connections.iter().next())
+        .unwrap();
+
+    assert_eq!(recv_size(&runner, &connection), MIN);
+
+    // Bulk transfer: every burst fills the recv buffer, which doubles.
+    let sizes: Vec<usize> = [MIN, 512, 1024, 2048]
+        .into_iter()
+        .map(|burst| feed(&mut runner, &mut stream, &connection, burst))
+        .collect();
+
+    assert_eq!(sizes, [512, 1024, 2048, MAX]);
+
+    // Chatty peer: recvs time out with a few bytes, the buffer halves.
+    let sizes: Vec<usize> = (0..4)
+        .map(|_| feed(&mut runner, &mut stream, &connection, 10))
+        .collect();
+
+    assert_eq!(sizes, [2048, 1024, 512, MIN]);
+
+    // The size is forgotten with the connection.
+    drop(stream);
+    assert!(runner.run_until(
+        |state| state
+            .substate::<TcpServerState>()
+            .listeners
+            .values()
+            .all(|listener| listener.recv_sizes.is_empty()),
+        1000
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Invalid adaptive recv bounds")]
+fn tcp_server_adaptive_recv_bounds() {
+    TcpServerState::from_config(TcpServerConfig {
+        adaptive_recv: Some(AdaptiveRecv { min: MAX, max: MIN }),
+        ..TcpServerConfig::default()
+    });
+}
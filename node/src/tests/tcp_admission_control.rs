@@ -0,0 +1,74 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, Uid},
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::admission::{action::AdmissionAction, state::AdmissionState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct Admission {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub admission: AdmissionState,
+}
+
+impl RegisterModel for Admission {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<AdmissionState>()
+    }
+}
+
+#[test]
+fn tcp_admission_control() {
+    let mut runner = RunnerBuilder::<Admission>::new()
+        .register::<Admission>()
+        .instance(
+            Admission {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                admission: AdmissionState::new("127.0.0.1:8906".to_string()),
+            },
+            || AdmissionAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<AdmissionState>().rejected.is_some(),
+        1000
+    ));
+
+    let admission_state: &AdmissionState = runner.state().substate();
+    let admitted = admission_state.server_connection.unwrap();
+    let rejected = admission_state.rejected.unwrap();
+    let admissions: Vec<(Uid, usize, usize, bool)> = admission_state
+        .admissions
+        .iter()
+        .map(|(connection, request, admitted)| {
+            (
+                *connection,
+                request.connections,
+                request.peer_connections,
+                *admitted,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        admissions,
+        vec![(admitted, 0, 0, true), (rejected, 1, 1, false)]
+    );
+}
@@ -0,0 +1,116 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{tcp::state::TcpState, tcp_server::state::TcpServerState},
+        tests::backlog_server::{action::BacklogServerAction, state::BacklogServerState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, net::TcpStream};
+
+#[derive(ModelState, Debug)]
+pub struct Backlog {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub server: BacklogServerState,
+}
+
+impl RegisterModel for Backlog {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<BacklogServerState>()
+    }
+}
+
+// Raises the soft limit of open files to `needed` if it's lower. Returns
+// false if the hard limit doesn't allow it.
+#[cfg(unix)]
+fn raise_fd_limit(needed: u64) -> bool {
+    let needed = needed as libc::rlim_t;
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return false;
+    }
+
+    if limit.rlim_cur >= needed {
+        return true;
+    }
+
+    if limit.rlim_max < needed {
+        return false;
+    }
+
+    limit.rlim_cur = needed;
+    unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) == 0 }
+}
+
+#[cfg(not(unix))]
+fn raise_fd_limit(_needed: u64) -> bool {
+    true
+}
+
+#[test]
+fn tcp_backlog_accepts() {
+    let address = "127.0.0.1:8938";
+    // More than `DEFAULT_BACKLOG` and `DEFAULT_EVENTS_CAPACITY`.
+    let connections = 1100;
+
+    // Both ends of every connection are open at once, plus the test
+    // harness' own files.
+    if !raise_fd_limit(2 * connections as u64 + 256) {
+        eprintln!("Skipping tcp_backlog_accepts: the open files limit is too low");
+        return;
+    }
+
+    let mut runner = RunnerBuilder::<Backlog>::new()
+        .register::<Backlog>()
+        .instance(
+            Backlog {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                server: BacklogServerState::new(address.to_string(), connections),
+            },
+            || BacklogServerAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<BacklogServerState>().listener.is_some(),
+        1000
+    ));
+
+    // All connected before any is accepted.
+    let streams: Vec<TcpStream> = (0..connections)
+        .map(|_| TcpStream::connect(address).expect("connection refused"))
+        .collect();
+
+    assert!(runner.run_until(
+        |state| state.substate::<BacklogServerState>().accepted.len() == connections,
+        100_000
+    ));
+
+    let server_state: &BacklogServerState = runner.state().substate();
+    let listener = server_state.listener.unwrap();
+
+    // None was closed for exceeding `max_connections`.
+    assert!(server_state.closed.is_empty());
+    assert_eq!(
+        runner
+            .state()
+            .substate::<TcpServerState>()
+            .get_listener(&listener)
+            .connections
+            .len(),
+        connections
+    );
+    drop(streams);
+}
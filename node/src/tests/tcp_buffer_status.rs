@@ -0,0 +1,49 @@
+use crate::{
+    automaton::state::Uid,
+    models::effectful::mio::{action::TcpWriteResult, state::MioState},
+};
+use std::{io::Write, net, thread, time::Duration};
+
+#[test]
+fn tcp_buffer_status() {
+    let address = "127.0.0.1:8940";
+    let connection = Uid::from(1u64);
+    let listener = net::TcpListener::bind(address).expect("bind failed");
+    let mut mio = MioState::new();
+
+    mio.tcp_connect(connection, address.to_string(), None, None)
+        .expect("connect failed");
+
+    // The peer never reads: the connection gets wedged.
+    let (mut peer, _) = listener.accept().expect("accept failed");
+    let data = vec![0u8; 64 * 1024];
+    let mut queued = Vec::new();
+
+    while queued.len() < 16 {
+        match mio.tcp_write(&connection, &data) {
+            TcpWriteResult::WrittenAll | TcpWriteResult::WrittenPartial(_) => {
+                let (send_queued, _) = mio.tcp_buffer_status(&connection).unwrap();
+                queued.push(send_queued);
+            }
+            TcpWriteResult::WouldBlock => break,
+            result => panic!("unexpected write result {:?}", result),
+        }
+    }
+
+    assert!(queued.first().is_some_and(|send_queued| *send_queued > 0));
+    assert!(queued.windows(2).all(|depth| depth[0] <= depth[1]));
+    assert!(queued.first() < queued.last());
+
+    peer.write_all(b"hello").expect("write failed");
+
+    // Wait for the data to arrive in the recv buffer.
+    let recv_available = (0..100)
+        .map(|_| {
+            thread::sleep(Duration::from_millis(10));
+            mio.tcp_buffer_status(&connection).unwrap().1
+        })
+        .find(|recv_available| *recv_available > 0);
+
+    assert_eq!(recv_available, Some(5));
+    mio.shutdown();
+}
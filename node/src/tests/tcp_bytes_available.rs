@@ -0,0 +1,68 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::{action::BytesAvailableResult, state::TcpState},
+            tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::bytes_available::{action::BytesAvailableAction, state::BytesAvailableState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct BytesAvailable {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub bytes_available: BytesAvailableState,
+}
+
+impl RegisterModel for BytesAvailable {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<BytesAvailableState>()
+    }
+}
+
+#[test]
+fn tcp_bytes_available() {
+    let mut runner = RunnerBuilder::<BytesAvailable>::new()
+        .register::<BytesAvailable>()
+        .instance(
+            BytesAvailable {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                bytes_available: BytesAvailableState::new(
+                    "127.0.0.1:8916".to_string(),
+                    vec![0x5a; 100],
+                ),
+            },
+            || BytesAvailableAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state
+            .substate::<BytesAvailableState>()
+            .bytes_available
+            .is_some(),
+        1000
+    ));
+
+    let available_state: &BytesAvailableState = runner.state().substate();
+
+    // Everything that was sent, still unread.
+    assert_eq!(
+        available_state.bytes_available,
+        Some(BytesAvailableResult::Available(100))
+    );
+}
@@ -0,0 +1,78 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{tcp::state::TcpState, tcp_client::state::TcpClientState},
+        tests::cancel_client::{action::CancelClientAction, state::CancelClientState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, io::Read, net::TcpListener, time::Duration};
+
+#[derive(ModelState, Debug)]
+pub struct Cancel {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_client: TcpClientState,
+    pub client: CancelClientState,
+}
+
+impl RegisterModel for Cancel {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<CancelClientState>()
+    }
+}
+
+#[test]
+fn tcp_cancel() {
+    let address = "127.0.0.1:8947";
+    let data = b"kept".to_vec();
+    let listener = TcpListener::bind(address).expect("bind failed");
+    let mut runner = RunnerBuilder::<Cancel>::new()
+        .register::<Cancel>()
+        .instance(
+            Cancel {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_client: TcpClientState::new(),
+                client: CancelClientState::new(address.to_string(), data.clone()),
+            },
+            || CancelClientAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| {
+            let client_state: &CancelClientState = state.substate();
+
+            client_state.sent.len() == 1 && client_state.cancelled.len() == 2
+        },
+        1000
+    ));
+
+    let (mut peer, _) = listener.accept().expect("accept failed");
+    let mut received = vec![0u8; data.len()];
+
+    peer.read_exact(&mut received).expect("read failed");
+    assert_eq!(received, data);
+
+    // Nothing was written for the cancelled send.
+    peer.set_read_timeout(Some(Duration::from_millis(100)))
+        .expect("set_read_timeout failed");
+    assert!(peer.read(&mut [0u8; 1]).is_err());
+
+    let connection = runner
+        .state()
+        .substate::<CancelClientState>()
+        .connection
+        .unwrap();
+    let stats = runner
+        .state()
+        .substate::<TcpState>()
+        .connection_stats(&connection);
+
+    assert_eq!(stats.bytes_sent, data.len() as u64);
+}
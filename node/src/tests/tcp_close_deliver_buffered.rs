@@ -0,0 +1,65 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::close_deliver_buffered::{
+            action::CloseDeliverBufferedAction, state::CloseDeliverBufferedState,
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use serde_derive::{Deserialize, Serialize};
+use std::any::Any;
+
+#[derive(ModelState, Serialize, Deserialize, Debug)]
+pub struct CloseDeliverBuffered {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub close_deliver_buffered: CloseDeliverBufferedState,
+}
+
+impl CloseDeliverBuffered {
+    pub fn new(address: &str, data: &[u8]) -> Self {
+        Self {
+            time: TimeState::default(),
+            tcp: TcpState::new(),
+            tcp_server: TcpServerState::new(),
+            tcp_client: TcpClientState::new(),
+            close_deliver_buffered: CloseDeliverBufferedState::new(
+                address.to_string(),
+                data.to_vec(),
+            ),
+        }
+    }
+}
+
+impl RegisterModel for CloseDeliverBuffered {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<CloseDeliverBufferedState>()
+    }
+}
+
+#[test]
+fn tcp_close_deliver_buffered() {
+    let mut runner = RunnerBuilder::<CloseDeliverBuffered>::new()
+        .register::<CloseDeliverBuffered>()
+        .instance(CloseDeliverBuffered::new("127.0.0.1:8891", b"ping"), || {
+            CloseDeliverBufferedAction::Tick.into()
+        })
+        .build();
+
+    // The delivered data is checked when the closure is reported.
+    assert!(runner.run_until(
+        |state| state.substate::<CloseDeliverBufferedState>().closed,
+        1000
+    ));
+}
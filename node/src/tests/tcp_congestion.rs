@@ -0,0 +1,71 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{tcp::state::TcpState, tcp_client::state::TcpClientState},
+        tests::congestion_client::{action::CongestionClientAction, state::CongestionClientState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, net::TcpListener};
+
+#[derive(ModelState, Debug)]
+pub struct Congestion {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_client: TcpClientState,
+    pub client: CongestionClientState,
+}
+
+impl RegisterModel for Congestion {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<CongestionClientState>()
+    }
+}
+
+#[test]
+fn tcp_congestion() {
+    let address = "127.0.0.1:8946";
+    let _listener = TcpListener::bind(address).expect("bind failed");
+    let mut runner = RunnerBuilder::<Congestion>::new()
+        .register::<Congestion>()
+        .instance(
+            Congestion {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_client: TcpClientState::new(),
+                client: CongestionClientState::new(address.to_string()),
+            },
+            || CongestionClientAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state
+            .substate::<CongestionClientState>()
+            .algorithm
+            .is_some(),
+        1000
+    ));
+
+    let client_state: &CongestionClientState = runner.state().substate();
+    let connection = client_state.connection.unwrap();
+    let algorithm = client_state
+        .algorithm
+        .clone()
+        .unwrap()
+        .expect("congestion control query failed");
+
+    assert!(!algorithm.is_empty());
+    // The algorithm is also recorded by `TcpState`.
+    assert_eq!(
+        runner
+            .state()
+            .substate::<TcpState>()
+            .congestion_algorithm(&connection),
+        Some(algorithm.as_str())
+    );
+}
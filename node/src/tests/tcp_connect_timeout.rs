@@ -0,0 +1,78 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::{ConnectionType, TcpState},
+            tcp_client::state::TcpClientState,
+        },
+        tests::connect_timeout_client::{
+            action::ConnectTimeoutClientAction, state::ConnectTimeoutClientState,
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, net::TcpListener};
+
+#[derive(ModelState, Debug)]
+pub struct ConnectTimeout {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_client: TcpClientState,
+    pub client: ConnectTimeoutClientState,
+}
+
+impl RegisterModel for ConnectTimeout {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<ConnectTimeoutClientState>()
+    }
+}
+
+#[test]
+fn tcp_connect_timeout() {
+    let address = "127.0.0.1:8945";
+    // The connection would be accepted, if it didn't time out first.
+    let _listener = TcpListener::bind(address).expect("bind failed");
+    let mut runner = RunnerBuilder::<ConnectTimeout>::new()
+        .register::<ConnectTimeout>()
+        .instance(
+            ConnectTimeout {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_client: TcpClientState::new(),
+                client: ConnectTimeoutClientState::new(address.to_string()),
+            },
+            || ConnectTimeoutClientAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| {
+            state
+                .substate::<ConnectTimeoutClientState>()
+                .timed_out
+                .is_some_and(|connection| !state.substate::<TcpState>().has_connection(&connection))
+        },
+        1000
+    ));
+
+    // No outgoing connection is left.
+    assert_eq!(
+        runner
+            .state()
+            .substate::<TcpState>()
+            .connections()
+            .filter(|(_, conn)| matches!(conn.conn_type, ConnectionType::Outgoing { .. }))
+            .count(),
+        0
+    );
+    // Nor is the client's entry for it.
+    assert!(runner
+        .state()
+        .substate::<TcpClientState>()
+        .connections
+        .is_empty());
+}
@@ -0,0 +1,82 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::connection_addrs::{action::ConnectionAddrsAction, state::ConnectionAddrsState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct ConnectionAddrs {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub connection_addrs: ConnectionAddrsState,
+}
+
+impl RegisterModel for ConnectionAddrs {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<ConnectionAddrsState>()
+    }
+}
+
+#[test]
+fn tcp_connection_addrs() {
+    let address = "127.0.0.1:8890";
+    let mut runner = RunnerBuilder::<ConnectionAddrs>::new()
+        .register::<ConnectionAddrs>()
+        .instance(
+            ConnectionAddrs {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                connection_addrs: ConnectionAddrsState::new(address.to_string()),
+            },
+            || ConnectionAddrsAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| {
+            let addrs_state: &ConnectionAddrsState = state.substate();
+
+            addrs_state.client_connection.is_some() && addrs_state.server_connection.is_some()
+        },
+        1000
+    ));
+
+    let addrs_state: &ConnectionAddrsState = runner.state().substate();
+    let tcp_state: &TcpState = runner.state().substate();
+    let client_connection = addrs_state.client_connection.unwrap();
+    let server_connection = addrs_state.server_connection.unwrap();
+    let (client_local, client_peer) = tcp_state
+        .connection_addrs(&client_connection)
+        .expect("client connection addresses not recorded");
+    let (server_local, server_peer) = tcp_state
+        .connection_addrs(&server_connection)
+        .expect("server connection addresses not recorded");
+
+    // The local address of each end is the peer address of the other one.
+    assert_eq!(client_peer, address);
+    assert_eq!(server_local, address);
+    assert_eq!(client_local, server_peer);
+    assert!(tcp_state
+        .connection_stats(&client_connection)
+        .established_at
+        .is_some());
+    assert!(tcp_state
+        .connection_stats(&server_connection)
+        .established_at
+        .is_some());
+}
@@ -0,0 +1,109 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::state::Uid,
+    fuzz::fuzz_run,
+    models::{
+        effectful::mio::action::MioEvent,
+        pure::net::tcp::{
+            action::ConnectionEvent,
+            state::{history_summary, ConnectionLogEvent, TcpState},
+        },
+    },
+};
+
+#[test]
+fn tcp_connection_history() {
+    #[rustfmt::skip]
+    let seed = [
+        // tick: time; tick: TCP init (poll creation succeeds)
+        1, 0,
+        // tick: time; listen (max 1 connection, listen and registration succeed)
+        1, 0, 0, 0, 0,
+        // tick: time; poll (timeout 0, 1 event: listener readable, accept and
+        // registration succeed)
+        1, 1, 0, 0, 1, 0, 1, 0, 0,
+        // tick: time; recv (4 bytes, timeout 100)
+        1, 3, 0, 3, 100,
+        // tick: time; poll (timeout 0, 1 event: connection readable, read all,
+        // accept would block)
+        1, 1, 0, 0, 1, 1, 1, 0, 10, 11, 12, 13, 6,
+        // tick: time; send (4 bytes, timeout 100)
+        1, 2, 0, 3, 100,
+        // tick: time; poll (timeout 0, 1 event: connection writable, write 2
+        // bytes, then the rest)
+        1, 1, 0, 0, 1, 1, 2, 4, 1, 0,
+        // tick: time; poll (timeout 0, 1 event: connection read closed)
+        1, 1, 0, 0, 1, 1, 4,
+    ];
+    let runner = fuzz_run(&seed);
+    let tcp_state: &TcpState = runner.state().substate();
+    // The connection was closed on shutdown, its history is kept for a while.
+    let history = (0..64usize)
+        .find_map(|uid| tcp_state.connection_history(&Uid::from(uid)))
+        .unwrap();
+    let events: Vec<ConnectionLogEvent> = history.iter().map(|entry| entry.event.clone()).collect();
+
+    assert_eq!(
+        events,
+        vec![
+            ConnectionLogEvent::Accepted { number: 1 },
+            ConnectionLogEvent::Registered,
+            ConnectionLogEvent::Ready {
+                readable: true,
+                writable: false
+            },
+            ConnectionLogEvent::Ready {
+                readable: false,
+                writable: true
+            },
+            ConnectionLogEvent::PartialWrite { written: 2, len: 4 },
+            ConnectionLogEvent::Closed,
+            ConnectionLogEvent::CloseRequested,
+        ]
+    );
+    assert!(history.windows(2).all(|pair| pair[0].time <= pair[1].time));
+}
+
+#[test]
+fn tcp_connection_history_readiness_transitions() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(ConnectionEvent::Ready {
+        can_recv: false,
+        can_send: false,
+    });
+    let mut tcp_state = builder.build();
+    let event = |readable, writable| MioEvent {
+        token: connection,
+        readable,
+        writable,
+        error: false,
+        read_closed: false,
+        write_closed: false,
+        priority: false,
+        aio: false,
+        lio: false,
+    };
+
+    // Each poll reports the connection, only the changes are logged.
+    for (time, (readable, writable)) in [
+        (true, false),
+        (true, false),
+        (false, true),
+        (false, true),
+        (true, false),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        tcp_state.update_events(time as u128, &event(readable, writable));
+    }
+
+    let history = tcp_state.connection_history(&connection).unwrap();
+
+    assert_eq!(
+        history_summary(history),
+        "0: Ready { readable: true, writable: false }, \
+         2: Ready { readable: false, writable: true }, \
+         4: Ready { readable: true, writable: false }"
+    );
+}
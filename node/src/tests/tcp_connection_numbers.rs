@@ -0,0 +1,101 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, Uid},
+    },
+    models::pure::{
+        net::{
+            tcp::state::{ConnectionLogEvent, TcpState},
+            tcp_client::state::TcpClientState,
+            tcp_server::{action::ConnectionLifecycleEvent, state::TcpServerState},
+        },
+        tests::connection_numbers::{
+            action::ConnectionNumbersAction, state::ConnectionNumbersState,
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct ConnectionNumbers {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub connection_numbers: ConnectionNumbersState,
+}
+
+impl RegisterModel for ConnectionNumbers {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<ConnectionNumbersState>()
+    }
+}
+
+#[test]
+fn tcp_connection_numbers() {
+    let mut runner = RunnerBuilder::<ConnectionNumbers>::new()
+        .register::<ConnectionNumbers>()
+        .instance(
+            ConnectionNumbers {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                connection_numbers: ConnectionNumbersState::new("127.0.0.1:8919".to_string()),
+            },
+            || ConnectionNumbersAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state
+            .substate::<ConnectionNumbersState>()
+            .server_connections
+            .len()
+            == 3,
+        1000
+    ));
+
+    let numbers_state: &ConnectionNumbersState = runner.state().substate();
+    let tcp_state: &TcpState = runner.state().substate();
+    let accepted: Vec<_> = numbers_state
+        .lifecycle_events
+        .iter()
+        .filter_map(|(connection, event)| match event {
+            ConnectionLifecycleEvent::Accepted { number } => Some((*connection, *number)),
+            _ => None,
+        })
+        .collect();
+    let numbers: Vec<_> = numbers_state
+        .server_connections
+        .iter()
+        .map(|connection| {
+            tcp_state
+                .connection_history(connection)
+                .unwrap()
+                .iter()
+                .find_map(|entry| match entry.event {
+                    ConnectionLogEvent::Accepted { number } => Some(number),
+                    _ => None,
+                })
+        })
+        .collect();
+
+    assert_eq!(
+        accepted,
+        numbers_state
+            .server_connections
+            .iter()
+            .zip(1..)
+            .map(|(connection, number)| (*connection, number))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(numbers, [Some(1), Some(2), Some(3)]);
+    // Other objects were allocated in between, the Uids are not 1, 2, 3.
+    assert_ne!(
+        numbers_state.server_connections,
+        [1u64, 2, 3].map(Uid::from)
+    );
+}
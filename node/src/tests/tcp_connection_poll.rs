@@ -0,0 +1,85 @@
+use super::{tcp_poll_interest::TcpNode, tcp_timeouts::TcpStateBuilder};
+use crate::{
+    automaton::{
+        action::Dispatcher,
+        model::PureModel,
+        state::{State, Uid},
+    },
+    models::pure::{
+        net::tcp::{
+            action::{ConnectionEvent, TcpAction},
+            state::{Status, TcpState},
+        },
+        time::state::TimeState,
+    },
+};
+
+// Switches the poll that `TcpState` assigns new connections to.
+fn use_poll(state: &mut State<TcpNode>, poll: Uid) {
+    let tcp_state: &mut TcpState = state.substate_mut();
+
+    if let Status::Ready {
+        instance, events, ..
+    } = tcp_state.status
+    {
+        tcp_state.status = Status::Ready {
+            instance,
+            poll,
+            events,
+        }
+    }
+}
+
+fn process(state: &mut State<TcpNode>, action: TcpAction) {
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    TcpState::process_pure(state, action, &mut dispatcher)
+}
+
+// The model drives a single poll, the second one is emulated by switching
+// the poll between registrations: `connection_poll` reports the poll each
+// connection was actually registered with.
+#[test]
+fn tcp_connection_poll() {
+    let (first_poll, second_poll) = (Uid::from(100usize), Uid::from(200usize));
+    let mut builder = TcpStateBuilder::new();
+    let first = builder.connection(ConnectionEvent::Closed);
+    let second = builder.connection(ConnectionEvent::Closed);
+    let mut state = State::<TcpNode>::new();
+
+    state.substates.push(TcpNode {
+        time: TimeState::default(),
+        tcp: builder.build(),
+    });
+
+    // Not registered yet.
+    assert_eq!(state.substate::<TcpState>().connection_poll(&first), None);
+
+    use_poll(&mut state, first_poll);
+    process(
+        &mut state,
+        TcpAction::RegisterConnectionSuccess { connection: first },
+    );
+    use_poll(&mut state, second_poll);
+    process(
+        &mut state,
+        TcpAction::RegisterConnectionSuccess { connection: second },
+    );
+
+    let tcp_state: &TcpState = state.substate();
+
+    assert_eq!(tcp_state.connection_poll(&first), Some(first_poll));
+    assert_eq!(tcp_state.connection_poll(&second), Some(second_poll));
+
+    // Deregistered on close.
+    process(
+        &mut state,
+        TcpAction::DeregisterConnectionSuccess { connection: first },
+    );
+
+    let tcp_state: &TcpState = state.substate();
+
+    assert_eq!(tcp_state.connection_poll(&first), None);
+    assert_eq!(tcp_state.connection_poll(&second), Some(second_poll));
+    assert_eq!(tcp_state.connection_poll(&Uid::from(999usize)), None);
+}
@@ -0,0 +1,43 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::action::TimeoutAbsolute,
+    models::pure::net::tcp::{
+        action::ConnectionEvent,
+        state::{ConnectionStats, TcpState},
+    },
+};
+
+#[test]
+fn tcp_connection_stats() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(ConnectionEvent::Ready {
+        can_recv: true,
+        can_send: true,
+    });
+    let send = builder.send_request(connection, TimeoutAbsolute::Never);
+    let recv = builder.recv_request(connection, TimeoutAbsolute::Never);
+    let mut tcp_state = builder.build();
+
+    // Two writes of "ping", one of them partial.
+    tcp_state.get_send_request_mut(&send).write_len = 4;
+    tcp_state.complete_send(&send, 3);
+    tcp_state.get_send_request_mut(&send).write_len = 1;
+    tcp_state.complete_send(&send, 1);
+    tcp_state.get_recv_request_mut(&recv).read_len = 4;
+    tcp_state.complete_recv(&recv, 2);
+    tcp_state.get_connection_mut(&connection).established_at = Some(1000);
+
+    let stats = ConnectionStats {
+        bytes_sent: 4,
+        bytes_received: 2,
+        established_at: Some(1000),
+    };
+
+    assert_eq!(tcp_state.connection_stats(&connection), stats);
+
+    // The totals are kept in snapshots.
+    let snapshot = bincode::serialize(&tcp_state).expect("serialization failed");
+    let tcp_state: TcpState = bincode::deserialize(&snapshot).expect("deserialization failed");
+
+    assert_eq!(tcp_state.connection_stats(&connection), stats);
+}
@@ -0,0 +1,61 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::drain_on_close::{action::DrainOnCloseAction, state::DrainOnCloseState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct DrainOnClose {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub drain_on_close: DrainOnCloseState,
+}
+
+impl RegisterModel for DrainOnClose {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<DrainOnCloseState>()
+    }
+}
+
+#[test]
+fn tcp_drain_on_close() {
+    let mut runner = RunnerBuilder::<DrainOnClose>::new()
+        .register::<DrainOnClose>()
+        .instance(
+            DrainOnClose {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                drain_on_close: DrainOnCloseState::new(
+                    "127.0.0.1:8895".to_string(),
+                    b"ping".to_vec(),
+                ),
+            },
+            || DrainOnCloseAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<DrainOnCloseState>().received.is_some(),
+        1000
+    ));
+
+    let drain_state: &DrainOnCloseState = runner.state().substate();
+
+    // Delivered, not lost to the closure.
+    assert_eq!(drain_state.received, Some(b"ping".to_vec()));
+}
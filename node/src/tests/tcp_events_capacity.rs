@@ -0,0 +1,74 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, Uid},
+    },
+    callback,
+    models::pure::{
+        net::tcp::{
+            action::TcpAction,
+            state::{Status, TcpState},
+        },
+        time::state::TimeState,
+    },
+    tests::ndjson_export::SharedBuffer,
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct TcpNode {
+    pub time: TimeState,
+    pub tcp: TcpState,
+}
+
+impl RegisterModel for TcpNode {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpState>()
+    }
+}
+
+#[test]
+fn tcp_events_capacity() {
+    let output = SharedBuffer::default();
+    let mut runner = RunnerBuilder::<TcpNode>::new()
+        .register::<TcpNode>()
+        .export_ndjson(output.clone())
+        .instance(
+            TcpNode {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+            },
+            || {
+                TcpAction::Init {
+                    instance: Uid::default(),
+                    events_capacity: Some(4096),
+                    on_success: callback!(|uid: Uid| TcpAction::EventsCreate { uid }),
+                    on_error: callback!(|(poll: Uid, error: String)| TcpAction::PollCreateError { poll, error }),
+                }
+                .into()
+            },
+        )
+        .build();
+    let mut stepper = runner.stepper();
+
+    // Init, PollCreate, PollCreateSuccess, EventsCreate (effect).
+    assert_eq!(stepper.step_n(4), 4);
+    assert!(matches!(
+        stepper.state().substate::<TcpState>().status,
+        Status::InitEventsCreate {
+            events_capacity: 4096,
+            ..
+        }
+    ));
+
+    let output = String::from_utf8(output.0.take()).unwrap();
+    let events_create: serde_json::Value = output
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .find(|line| line["model"] == "mio" && line["action"].get("EventsCreate").is_some())
+        .expect("EventsCreate not dispatched")["action"]["EventsCreate"]
+        .clone();
+
+    assert_eq!(events_create["capacity"], 4096);
+}
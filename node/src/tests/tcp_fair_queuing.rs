@@ -0,0 +1,79 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::{
+        action::{Dispatcher, TimeoutAbsolute},
+        state::Uid,
+    },
+    models::{
+        effectful::mio::action::MioEffectfulAction,
+        pure::net::tcp::{
+            action::{ConnectionEvent, TcpAction},
+            util::process_pending_send_requests,
+        },
+    },
+};
+use std::collections::BTreeMap;
+
+const WRITABLE: ConnectionEvent = ConnectionEvent::Ready {
+    can_recv: false,
+    can_send: true,
+};
+
+#[test]
+fn tcp_weighted_fair_writes() {
+    let mut builder = TcpStateBuilder::new();
+    let connections = [(); 3].map(|_| builder.connection(WRITABLE));
+
+    // More pending sends than the polls below can start.
+    for connection in connections {
+        for _ in 0..12 {
+            builder.send_request(connection, TimeoutAbsolute::Never);
+        }
+    }
+
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+    let mut writes: BTreeMap<Uid, usize> = BTreeMap::new();
+
+    // Connections only write the head of their send queue, the budget is
+    // below the number of connections so that weights matter.
+    tcp_state.config.writes_per_poll = Some(1);
+    tcp_state.set_weight(&connections[2], 2);
+    // Same as the default weight.
+    tcp_state.set_weight(&connections[1], 0);
+    assert_eq!(tcp_state.get_connection(&connections[1]).weight, 1);
+
+    for _ in 0..8 {
+        process_pending_send_requests(0, &mut tcp_state, &mut dispatcher);
+
+        let started: Vec<(Uid, Uid)> = std::iter::from_fn(|| dispatcher.next_queued_action())
+            .map(|action| {
+                match *action
+                    .ptr
+                    .downcast::<MioEffectfulAction>()
+                    .expect("unexpected action")
+                {
+                    MioEffectfulAction::TcpWrite {
+                        uid, connection, ..
+                    } => (uid, connection),
+                    action => panic!("unexpected action: {:?}", action),
+                }
+            })
+            .collect();
+
+        assert_eq!(started.len(), 1);
+
+        // The writes complete before the next poll.
+        for (uid, connection) in started {
+            *writes.entry(connection).or_default() += 1;
+            tcp_state.remove_send_request(&uid);
+        }
+    }
+
+    // The first connection, serviced first in creation order, doesn't starve
+    // the others.
+    assert_eq!(
+        connections.map(|connection| writes.get(&connection).copied()),
+        [Some(2), Some(2), Some(4)]
+    );
+}
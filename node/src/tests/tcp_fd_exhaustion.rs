@@ -0,0 +1,38 @@
+use crate::{fuzz::fuzz_run, models::pure::net::tcp::state::TcpState};
+
+#[test]
+fn tcp_fd_exhaustion_recovery() {
+    #[rustfmt::skip]
+    let seed = [
+        // tick: time; tick: TCP init (poll creation succeeds)
+        0, 0,
+        // tick: time; listen (max 2 connections, listen and registration
+        // succeed)
+        0, 0, 1, 0, 0,
+        // tick: time; poll (timeout 10, 1 event: listener readable, accept
+        // fails with EMFILE: accepts are held back for 50ms)
+        0, 1, 10, 0, 1, 0, 1, 7, 4,
+        // tick: time (+20ms); poll (timeout 10, no events: the listener is
+        // still readable, but the accept is held back)
+        20, 1, 10, 0, 0,
+        // tick: time (+30ms, past the backoff); poll (timeout 10, no events:
+        // accept and registration succeed)
+        30, 1, 10, 0, 0, 0, 0,
+    ];
+    let runner = fuzz_run(&seed);
+    let state = runner.state();
+    let count = |event: &str| {
+        let labels = vec![("instance", "0".to_string()), ("event", event.to_string())];
+
+        state.metrics.get("tcp_fd_exhaustion_total", &labels)
+    };
+
+    assert_eq!(count("exhausted"), Some(1.0));
+    assert_eq!(count("recovered"), Some(1.0));
+
+    let tcp_state: &TcpState = state.substate();
+
+    assert!(tcp_state.fd_exhaustion().is_none());
+    // Only the accept after the backoff succeeded.
+    assert_eq!(tcp_state.accept_latency_stats().count, 1);
+}
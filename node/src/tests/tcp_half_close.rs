@@ -0,0 +1,72 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::{ConnectionStatus, TcpState},
+            tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::half_close::{action::HalfCloseAction, state::HalfCloseState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct HalfClose {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub half_close: HalfCloseState,
+}
+
+impl RegisterModel for HalfClose {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<HalfCloseState>()
+    }
+}
+
+#[test]
+fn tcp_half_close() {
+    let mut runner = RunnerBuilder::<HalfClose>::new()
+        .register::<HalfClose>()
+        .instance(
+            HalfClose {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                half_close: HalfCloseState::new(
+                    "127.0.0.1:8917".to_string(),
+                    b"request".to_vec(),
+                    b"response".to_vec(),
+                ),
+            },
+            || HalfCloseAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<HalfCloseState>().received.is_some(),
+        1000
+    ));
+
+    let half_close_state: &HalfCloseState = runner.state().substate();
+    let connection = half_close_state.client_connection.unwrap();
+    let tcp_state: &TcpState = runner.state().substate();
+
+    assert_eq!(half_close_state.received, Some(b"response".to_vec()));
+    assert!(matches!(
+        tcp_state.get_connection(&connection).status,
+        ConnectionStatus::WriteClosed
+    ));
+    assert_eq!(
+        half_close_state.send_error.as_deref(),
+        Some("Connection write side shut down")
+    );
+}
@@ -0,0 +1,79 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::{
+        action::{Dispatcher, TimeoutAbsolute},
+        state::Uid,
+    },
+    models::{
+        effectful::mio::action::MioEffectfulAction,
+        pure::net::tcp::{
+            action::{ConnectionEvent, TcpAction},
+            util::{
+                handle_recv_common, handle_send_common, process_pending_recv_requests,
+                process_pending_send_requests,
+            },
+        },
+    },
+};
+
+const READY: ConnectionEvent = ConnectionEvent::Ready {
+    can_recv: true,
+    can_send: true,
+};
+
+// The requests of the TcpWrite/TcpRead effects dispatched so far.
+fn io_requests(dispatcher: &mut Dispatcher) -> Vec<Uid> {
+    std::iter::from_fn(|| dispatcher.next_queued_action())
+        .map(|action| {
+            match *action
+                .ptr
+                .downcast::<MioEffectfulAction>()
+                .expect("unexpected action")
+            {
+                MioEffectfulAction::TcpWrite { uid, .. }
+                | MioEffectfulAction::TcpRead { uid, .. } => uid,
+                action => panic!("unexpected action: {:?}", action),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn tcp_in_flight_send() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(READY);
+    let send = builder.send_request(connection, TimeoutAbsolute::Never);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    // Two polls before the write's result is in: it is written only once.
+    process_pending_send_requests(0, &mut tcp_state, &mut dispatcher);
+    process_pending_send_requests(0, &mut tcp_state, &mut dispatcher);
+    assert_eq!(io_requests(&mut dispatcher), [send]);
+
+    // The write would block, it is retried at the next poll.
+    tcp_state.complete_send(&send, 0);
+    handle_send_common(&mut tcp_state, &mut dispatcher, 0, send, false);
+    process_pending_send_requests(0, &mut tcp_state, &mut dispatcher);
+    process_pending_send_requests(0, &mut tcp_state, &mut dispatcher);
+    assert_eq!(io_requests(&mut dispatcher), [send]);
+}
+
+#[test]
+fn tcp_in_flight_recv() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(READY);
+    let recv = builder.recv_request(connection, TimeoutAbsolute::Never);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    process_pending_recv_requests(0, &mut tcp_state, &mut dispatcher);
+    process_pending_recv_requests(0, &mut tcp_state, &mut dispatcher);
+    assert_eq!(io_requests(&mut dispatcher), [recv]);
+
+    tcp_state.complete_recv(&recv, 0);
+    handle_recv_common(&mut tcp_state, &mut dispatcher, 0, recv, false);
+    process_pending_recv_requests(0, &mut tcp_state, &mut dispatcher);
+    process_pending_recv_requests(0, &mut tcp_state, &mut dispatcher);
+    assert_eq!(io_requests(&mut dispatcher), [recv]);
+}
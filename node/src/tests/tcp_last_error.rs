@@ -0,0 +1,51 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::last_error::{action::LastErrorAction, state::LastErrorState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct LastError {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub last_error: LastErrorState,
+}
+
+impl RegisterModel for LastError {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<LastErrorState>()
+    }
+}
+
+#[test]
+fn tcp_last_error() {
+    let mut runner = RunnerBuilder::<LastError>::new()
+        .register::<LastError>()
+        .instance(
+            LastError {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                last_error: LastErrorState::new("127.0.0.1:8896".to_string()),
+            },
+            || LastErrorAction::Tick.into(),
+        )
+        .build();
+
+    // The last error is checked when the closure is reported.
+    assert!(runner.run_until(|state| state.substate::<LastErrorState>().closed, 1000));
+}
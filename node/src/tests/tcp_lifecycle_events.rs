@@ -0,0 +1,75 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState,
+            tcp_client::state::TcpClientState,
+            tcp_server::{action::ConnectionLifecycleEvent, state::TcpServerState},
+        },
+        tests::lifecycle_events::{action::LifecycleEventsAction, state::LifecycleEventsState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct LifecycleEvents {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub lifecycle_events: LifecycleEventsState,
+}
+
+impl RegisterModel for LifecycleEvents {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<LifecycleEventsState>()
+    }
+}
+
+#[test]
+fn tcp_connection_lifecycle_events() {
+    let data = b"ping";
+    let mut runner = RunnerBuilder::<LifecycleEvents>::new()
+        .register::<LifecycleEvents>()
+        .instance(
+            LifecycleEvents {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                lifecycle_events: LifecycleEventsState::new(
+                    "127.0.0.1:8900".to_string(),
+                    data.to_vec(),
+                ),
+            },
+            || LifecycleEventsAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<LifecycleEventsState>().closed,
+        1000
+    ));
+
+    let events_state: &LifecycleEventsState = runner.state().substate();
+    let connection = events_state.server_connection.unwrap();
+    let bytes = data.len();
+
+    // Reported in order.
+    assert_eq!(
+        events_state.events,
+        [
+            ConnectionLifecycleEvent::Accepted { number: 1 },
+            ConnectionLifecycleEvent::Established,
+            ConnectionLifecycleEvent::DataReceived { bytes },
+            ConnectionLifecycleEvent::DataSent { bytes },
+            ConnectionLifecycleEvent::Closed { reason: None },
+        ]
+        .map(|event| (connection, event))
+    );
+}
@@ -0,0 +1,132 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, Runner, RunnerBuilder},
+        state::{ModelState, Uid},
+    },
+    models::{
+        effectful::mio::{action::TcpAcceptResult, state::MioState},
+        pure::{
+            net::tcp::state::TcpState,
+            tests::listen_fd::{
+                action::ListenFdAction,
+                state::{ListenFdState, ListenFdStatus},
+            },
+            time::state::TimeState,
+        },
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, net, os::fd::IntoRawFd};
+
+#[derive(ModelState, Debug)]
+pub struct ListenFd {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub listen_fd: ListenFdState,
+}
+
+impl RegisterModel for ListenFd {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<ListenFdState>()
+    }
+}
+
+fn listen_fd_runner(fd: i32) -> Runner<ListenFd> {
+    RunnerBuilder::<ListenFd>::new()
+        .register::<ListenFd>()
+        .instance(
+            ListenFd {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                listen_fd: ListenFdState::new(fd),
+            },
+            || ListenFdAction::Tick.into(),
+        )
+        .build()
+}
+
+#[test]
+fn tcp_listen_from_fd() {
+    let listener = Uid::from(1u64);
+    let connection = Uid::from(2u64);
+    let fd = net::TcpListener::bind("127.0.0.1:8925")
+        .expect("bind failed")
+        .into_raw_fd();
+    let mut mio = MioState::new();
+
+    assert_eq!(
+        mio.tcp_listen_from_fd(listener, fd).as_deref(),
+        Ok("127.0.0.1:8925")
+    );
+
+    let client = net::TcpStream::connect("127.0.0.1:8925").expect("connect failed");
+
+    // The adopted listener is non-blocking, the connection might not be
+    // pending yet.
+    let accepted = (0..100).find_map(|_| match mio.tcp_accept(connection, &listener) {
+        TcpAcceptResult::Success { peer_address, .. } => Some(peer_address),
+        TcpAcceptResult::WouldBlock => {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            None
+        }
+        TcpAcceptResult::Error(error) => panic!("accept failed: {}", error),
+    });
+
+    assert_eq!(accepted, Some(client.local_addr().unwrap().to_string()));
+    assert!(mio.tcp_listen_from_fd(Uid::from(3u64), -1).is_err());
+    mio.shutdown();
+}
+
+#[test]
+fn tcp_listen_from_fd_action() {
+    let address = "127.0.0.1:8951";
+    let fd = net::TcpListener::bind(address)
+        .expect("bind failed")
+        .into_raw_fd();
+    let mut runner = listen_fd_runner(fd);
+
+    assert!(runner.run_until(
+        |state| state.substate::<ListenFdState>().status == ListenFdStatus::Listening,
+        1000
+    ));
+
+    let listener = runner.state().substate::<ListenFdState>().listener.unwrap();
+
+    // The placeholder address was replaced by the adopted socket's.
+    assert_eq!(
+        runner
+            .state()
+            .substate::<TcpState>()
+            .get_listener(&listener)
+            .address,
+        address
+    );
+
+    // The listener is registered: its pending connection is polled.
+    let _client = net::TcpStream::connect(address).expect("connect failed");
+
+    assert!(runner.run_until(
+        |state| state.substate::<ListenFdState>().accept_pending,
+        1000
+    ));
+}
+
+#[test]
+fn tcp_listen_from_fd_action_error() {
+    let mut runner = listen_fd_runner(-1);
+
+    assert!(runner.run_until(
+        |state| matches!(
+            state.substate::<ListenFdState>().status,
+            ListenFdStatus::Error(_)
+        ),
+        1000
+    ));
+
+    let listener = runner.state().substate::<ListenFdState>().listener.unwrap();
+
+    assert!(!runner
+        .state()
+        .substate::<TcpState>()
+        .has_listener(&listener));
+}
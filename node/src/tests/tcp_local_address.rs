@@ -0,0 +1,74 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::local_address::{action::LocalAddressAction, state::LocalAddressState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, net::SocketAddr};
+
+#[derive(ModelState, Debug)]
+pub struct LocalAddress {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub local_address: LocalAddressState,
+}
+
+impl RegisterModel for LocalAddress {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<LocalAddressState>()
+    }
+}
+
+#[test]
+fn tcp_local_address() {
+    let mut runner = RunnerBuilder::<LocalAddress>::new()
+        .register::<LocalAddress>()
+        .instance(
+            LocalAddress {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                local_address: LocalAddressState::new("127.0.0.1:0".to_string()),
+            },
+            || LocalAddressAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| {
+            state
+                .substate::<LocalAddressState>()
+                .connection_address
+                .is_some()
+        },
+        1000
+    ));
+
+    let local_state: &LocalAddressState = runner.state().substate();
+    let listener_address = local_state.listener_address.clone().unwrap();
+    let port = listener_address
+        .parse::<SocketAddr>()
+        .expect("invalid listener address")
+        .port();
+    let (local, peer) = runner
+        .state()
+        .substate::<TcpState>()
+        .connection_addrs(&local_state.connection.unwrap())
+        .unwrap();
+
+    assert_ne!(port, 0);
+    assert_eq!(peer, listener_address);
+    assert_eq!(local_state.connection_address, Some(local));
+}
@@ -0,0 +1,64 @@
+use crate::{
+    automaton::runner::RunnerBuilder,
+    models::pure::tests::close_deliver_buffered::{
+        action::CloseDeliverBufferedAction, state::CloseDeliverBufferedState,
+    },
+    tests::tcp_close_deliver_buffered::CloseDeliverBuffered,
+};
+
+// Every sample line must be `name{label="value",...} value`.
+fn assert_prometheus_format(output: &str) {
+    for line in output.lines().filter(|line| !line.starts_with('#')) {
+        let (sample, value) = line.rsplit_once(' ').expect(line);
+        let name = sample.split('{').next().unwrap();
+
+        assert!(value.parse::<f64>().is_ok(), "invalid value: {}", line);
+        assert!(
+            name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+            "invalid metric name: {}",
+            line
+        );
+
+        if let Some(labels) = sample.strip_prefix(name).filter(|l| !l.is_empty()) {
+            let labels = labels.strip_prefix('{').and_then(|l| l.strip_suffix('}'));
+
+            for label in labels.expect(line).split(',') {
+                let (_, value) = label.split_once('=').expect(line);
+                assert!(value.starts_with('"') && value.ends_with('"'), "{}", line);
+            }
+        }
+    }
+}
+
+#[test]
+fn tcp_metrics_prometheus() {
+    let mut runner = RunnerBuilder::<CloseDeliverBuffered>::new()
+        .register::<CloseDeliverBuffered>()
+        .action_metrics()
+        .instance(CloseDeliverBuffered::new("127.0.0.1:8893", b"ping"), || {
+            CloseDeliverBufferedAction::Tick.into()
+        })
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<CloseDeliverBufferedState>().closed,
+        1000
+    ));
+
+    let output = runner.state().metrics_prometheus();
+
+    assert_prometheus_format(&output);
+
+    for expected in [
+        "# TYPE state_machine_actions_total counter",
+        "# TYPE tcp_connections gauge",
+        "tcp_bytes_sent_total{instance=\"0\"} 4\n",
+        "tcp_bytes_received_total{instance=\"0\"} 4\n",
+        "state_machine_actions_total{instance=\"0\",model=\"tcp\",kind=\"pure\"} ",
+        "state_machine_actions_total{instance=\"0\",model=\"mio\",kind=\"effectful\"} ",
+        "state_machine_action_duration_seconds_total{instance=\"0\",model=\"close_deliver_buffered\",kind=\"pure\"} ",
+        "tcp_connections{instance=\"0\",state=\"established\"} ",
+    ] {
+        assert!(output.contains(expected), "{} not found in:\n{}", expected, output);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::nodelay::{action::NodelayAction, state::NodelayState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct Nodelay {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub nodelay: NodelayState,
+}
+
+impl RegisterModel for Nodelay {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<NodelayState>()
+    }
+}
+
+#[test]
+fn tcp_nodelay() {
+    let mut runner = RunnerBuilder::<Nodelay>::new()
+        .register::<Nodelay>()
+        .instance(
+            Nodelay {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                nodelay: NodelayState::new("127.0.0.1:8924".to_string()),
+            },
+            || NodelayAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<NodelayState>().result.is_some(),
+        1000
+    ));
+
+    let nodelay_state: &NodelayState = runner.state().substate();
+    let tcp_state: &TcpState = runner.state().substate();
+
+    assert_eq!(nodelay_state.result, Some(Ok(())));
+    // Set by the client, and on accept by the listener.
+    assert!(
+        tcp_state
+            .get_connection(&nodelay_state.client_connection.unwrap())
+            .nodelay
+    );
+    assert!(
+        tcp_state
+            .get_connection(&nodelay_state.server_connection.unwrap())
+            .nodelay
+    );
+}
@@ -0,0 +1,89 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::{operation_transcript, OperationKind, TcpState},
+            tcp_client::state::TcpClientState,
+        },
+        tests::op_log_client::{action::OpLogClientAction, state::OpLogClientState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{
+    any::Any,
+    io::{Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+#[derive(ModelState, Debug)]
+pub struct OperationLog {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_client: TcpClientState,
+    pub client: OpLogClientState,
+}
+
+impl RegisterModel for OperationLog {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<OpLogClientState>()
+    }
+}
+
+#[test]
+fn tcp_operation_log() {
+    let address = "127.0.0.1:8944";
+    let data = b"ping".to_vec();
+    let listener = TcpListener::bind(address).expect("bind failed");
+    let echo = thread::spawn(move || {
+        let (mut peer, _) = listener.accept().expect("accept failed");
+        let mut request = [0u8; 4];
+
+        peer.read_exact(&mut request).expect("read failed");
+        peer.write_all(&request).expect("write failed");
+        peer
+    });
+    let mut runner = RunnerBuilder::<OperationLog>::new()
+        .register::<OperationLog>()
+        .instance(
+            OperationLog {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_client: TcpClientState::new(),
+                client: OpLogClientState::new(address.to_string(), data.clone()),
+            },
+            || OpLogClientAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<OpLogClientState>().received.is_some(),
+        1000
+    ));
+
+    let state = runner.state();
+    let connection = state.substate::<OpLogClientState>().connection.unwrap();
+    let operation_log = state
+        .substate::<TcpState>()
+        .operation_log(&connection)
+        .expect("operation log not enabled");
+    let kinds: Vec<(OperationKind, usize)> = operation_log
+        .iter()
+        .map(|operation| (operation.kind, operation.len))
+        .collect();
+
+    assert_eq!(kinds, [(OperationKind::Send, 4), (OperationKind::Recv, 4)]);
+    assert!(operation_log[0].time <= operation_log[1].time);
+
+    let transcript = operation_transcript(operation_log);
+    let lines: Vec<&str> = transcript.lines().collect();
+
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with(" Send 4 70696e67"));
+    assert!(lines[1].ends_with(" Recv 4 70696e67"));
+    drop(echo.join());
+}
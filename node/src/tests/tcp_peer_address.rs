@@ -0,0 +1,83 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::peer_address::{action::PeerAddressAction, state::PeerAddressState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct PeerAddress {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub peer_address: PeerAddressState,
+}
+
+impl RegisterModel for PeerAddress {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<PeerAddressState>()
+    }
+}
+
+#[test]
+fn tcp_peer_address() {
+    let address = "127.0.0.1:8950";
+    let mut runner = RunnerBuilder::<PeerAddress>::new()
+        .register::<PeerAddress>()
+        .instance(
+            PeerAddress {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                peer_address: PeerAddressState::new(address.to_string()),
+            },
+            || PeerAddressAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| {
+            let peer_state: &PeerAddressState = state.substate();
+
+            peer_state.peer_addresses.len() == 3 && peer_state.client_local_address.is_some()
+        },
+        1000
+    ));
+
+    let peer_state: &PeerAddressState = runner.state().substate();
+    let client_connection = peer_state.client_connection.unwrap();
+    let server_connection = peer_state.server_connection.unwrap();
+    let (client_local, client_peer) = peer_state.peer_addresses[&client_connection]
+        .clone()
+        .expect("client connection addresses");
+    let (server_local, server_peer) = peer_state.peer_addresses[&server_connection]
+        .clone()
+        .expect("server connection addresses");
+
+    assert_eq!(client_peer, address);
+    assert_eq!(server_local, address);
+    // Both ends agree, and with the `TcpGetLocalAddress` effect.
+    assert_eq!(server_peer, client_local);
+    assert_eq!(peer_state.client_local_address, Some(client_local));
+    // The query of the connection that doesn't exist failed.
+    assert_eq!(
+        peer_state
+            .peer_addresses
+            .values()
+            .filter(|result| result.is_err())
+            .count(),
+        1
+    );
+}
@@ -0,0 +1,32 @@
+use crate::{fuzz::fuzz_run, models::pure::time::state::TimeState};
+use std::time::Duration;
+
+#[test]
+fn tcp_poll_no_objects() {
+    #[rustfmt::skip]
+    let seed = [
+        // tick: time; tick: TCP init (poll creation succeeds)
+        0, 0,
+        // tick: time; poll (timeout 10, no listeners so no objects: no events)
+        0, 1, 10, 0, 0,
+        // tick: time
+        0,
+    ];
+    let runner = fuzz_run(&seed);
+    let state = runner.state();
+    let established = vec![
+        ("instance", "0".to_string()),
+        ("state", "established".to_string()),
+    ];
+
+    // The poll completed (connection metrics are sampled on every poll)
+    // after sleeping for its timeout.
+    assert_eq!(
+        state.metrics.get("tcp_connections", &established),
+        Some(0.0)
+    );
+    assert_eq!(
+        *state.substate::<TimeState>().now(),
+        Duration::from_millis(10)
+    );
+}
@@ -0,0 +1,166 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        state::{ModelState, State, Uid},
+    },
+    models::{
+        effectful::mio::{
+            action::{Interest, MioEffectfulAction, MioEvent, PollResult},
+            state::MioState,
+        },
+        pure::{
+            net::tcp::{
+                action::{ConnectionEvent, TcpAction},
+                state::{ConnectionLogEvent, ConnectionStatus, TcpState},
+                util::register_connection,
+            },
+            time::state::TimeState,
+        },
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct TcpNode {
+    pub time: TimeState,
+    pub tcp: TcpState,
+}
+
+fn poll_events(mio: &mut MioState, poll: &Uid, events: &Uid) -> Vec<MioEvent> {
+    match mio.poll_events(poll, events, Timeout::Millis(100)) {
+        PollResult::Events(events) => events,
+        result => panic!("unexpected poll result: {:?}", result),
+    }
+}
+
+#[test]
+fn tcp_poll_interest() {
+    let address = "127.0.0.1:8932".to_string();
+    let (poll, events) = (Uid::from(1u64), Uid::from(2u64));
+    let connection = Uid::from(4u64);
+    let mut mio = MioState::new();
+
+    mio.poll_create(poll).expect("poll creation failed");
+    mio.events_create(events, 16);
+    mio.tcp_listen(Uid::from(3u64), address.clone(), None, None)
+        .expect("listen failed");
+    mio.tcp_connect(connection, address, None, None)
+        .expect("connect failed");
+
+    // Nothing to read: no events, although the connection is writable.
+    mio.poll_register_tcp_connection(&poll, connection, Interest::Readable)
+        .expect("register failed");
+    assert_eq!(poll_events(&mut mio, &poll, &events), []);
+
+    mio.poll_reregister_tcp_connection(&poll, connection, Interest::Writable)
+        .expect("reregister failed");
+
+    let polled = poll_events(&mut mio, &poll, &events);
+
+    assert_eq!(polled.len(), 1);
+    assert_eq!(polled[0].token, connection);
+    assert!(polled[0].writable && !polled[0].readable);
+    mio.shutdown();
+}
+
+#[test]
+fn tcp_set_interest() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(ConnectionEvent::Closed);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    // Not registered yet: the connection is registered with it.
+    assert_eq!(
+        tcp_state.set_interest(&connection, Interest::Readable),
+        None
+    );
+    register_connection(&mut tcp_state, &mut dispatcher, connection);
+
+    let action = dispatcher.next_queued_action().expect("no registration");
+
+    match *action
+        .ptr
+        .downcast::<MioEffectfulAction>()
+        .expect("unexpected action")
+    {
+        MioEffectfulAction::PollRegisterTcpConnection { interest, .. } => {
+            assert_eq!(interest, Interest::Readable)
+        }
+        action => panic!("unexpected action: {:?}", action),
+    }
+
+    // Re-registered only when the interest changes.
+    assert_eq!(
+        tcp_state.set_interest(&connection, Interest::Readable),
+        None
+    );
+    assert_eq!(
+        tcp_state.set_interest(&connection, Interest::Both),
+        Some(Interest::Both)
+    );
+    assert_eq!(tcp_state.set_interest(&connection, Interest::Both), None);
+}
+
+#[test]
+fn tcp_reregister_error() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(ConnectionEvent::Closed);
+    let mut tcp = builder.build();
+    let mut state = State::<TcpNode>::new();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    tcp.get_connection_mut(&connection).registered_interest = Some(Interest::Readable);
+    state.substates.push(TcpNode {
+        time: TimeState::default(),
+        tcp,
+    });
+    TcpState::process_pure(
+        &mut state,
+        TcpAction::ReregisterConnectionError {
+            connection,
+            error: "Bad file descriptor (os error 9)".to_string(),
+        },
+        &mut dispatcher,
+    );
+
+    // The failure is logged, and the connection closed.
+    let tcp_state: &TcpState = state.substate();
+    let history = tcp_state.connection_history(&connection).unwrap();
+
+    assert!(matches!(
+        history.last().unwrap().event,
+        ConnectionLogEvent::RegisterError(_)
+    ));
+    assert!(matches!(
+        tcp_state.get_connection(&connection).status,
+        ConnectionStatus::CloseRequestInternal
+    ));
+
+    let action = dispatcher.next_queued_action().expect("no deregistration");
+
+    assert!(matches!(
+        *action
+            .ptr
+            .downcast::<MioEffectfulAction>()
+            .expect("unexpected action"),
+        MioEffectfulAction::PollDeregisterTcpConnection { .. }
+    ));
+
+    // The application is told.
+    let action = dispatcher.next_queued_action().expect("no error reported");
+
+    match *action
+        .ptr
+        .downcast::<TcpAction>()
+        .expect("unexpected action")
+    {
+        TcpAction::ConnectError {
+            connection: uid, ..
+        } => assert_eq!(uid, connection),
+        action => panic!("unexpected action: {:?}", action),
+    }
+}
@@ -0,0 +1,50 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::{
+        action::{Dispatcher, TimeoutAbsolute},
+        state::Uid,
+    },
+    models::{
+        effectful::mio::action::MioEffectfulAction,
+        pure::net::tcp::{
+            action::{ConnectionEvent, TcpAction},
+            util::process_pending_recv_requests,
+        },
+    },
+};
+
+const READABLE: ConnectionEvent = ConnectionEvent::Ready {
+    can_recv: true,
+    can_send: false,
+};
+
+#[test]
+fn tcp_priority_recv_order() {
+    let mut builder = TcpStateBuilder::new();
+    let low = builder.connection(READABLE);
+    let high = builder.connection(READABLE);
+    // Created first, so without priorities it would be serviced first.
+    builder.recv_request(low, TimeoutAbsolute::Never);
+    builder.recv_request(high, TimeoutAbsolute::Never);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    tcp_state.set_priority(&high, 1);
+    process_pending_recv_requests(0, &mut tcp_state, &mut dispatcher);
+
+    let reads: Vec<Uid> = std::iter::from_fn(|| dispatcher.next_queued_action())
+        .map(|action| {
+            match *action
+                .ptr
+                .downcast::<MioEffectfulAction>()
+                .expect("unexpected action")
+            {
+                MioEffectfulAction::TcpRead { connection, .. } => connection,
+                action => panic!("unexpected action: {:?}", action),
+            }
+        })
+        .collect();
+
+    // Both connections were readable in the same poll.
+    assert_eq!(reads, [high, low]);
+}
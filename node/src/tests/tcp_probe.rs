@@ -0,0 +1,62 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::{action::ProbeResult, state::TcpState},
+            tcp_server::state::TcpServerState,
+        },
+        tests::probe::{action::ProbeAction, state::ProbeState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct Probe {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub probe: ProbeState,
+}
+
+impl RegisterModel for Probe {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<ProbeState>()
+    }
+}
+
+#[test]
+fn tcp_probe() {
+    let mut runner = RunnerBuilder::<Probe>::new()
+        .register::<Probe>()
+        .instance(
+            Probe {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                probe: ProbeState::new("127.0.0.1:8897".to_string(), "127.0.0.1:8898".to_string()),
+            },
+            || ProbeAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<ProbeState>().results.len() == 2,
+        1000
+    ));
+
+    let results = &runner.state().substate::<ProbeState>().results;
+
+    assert!(
+        matches!(
+            results[..],
+            [ProbeResult::Reachable, ProbeResult::Refused(_)]
+        ),
+        "Unexpected probe results: {:?}",
+        results
+    );
+}
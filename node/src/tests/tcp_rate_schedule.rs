@@ -0,0 +1,67 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::action::{Dispatcher, TimeoutAbsolute},
+    models::{
+        effectful::mio::action::MioEffectfulAction,
+        pure::net::tcp::{
+            action::{ConnectionEvent, TcpAction},
+            util::{handle_send_common, process_pending_send_requests},
+        },
+    },
+};
+
+const READY: ConnectionEvent = ConnectionEvent::Ready {
+    can_recv: true,
+    can_send: true,
+};
+
+// Length of the TcpWrite effect dispatched so far, if any.
+fn write_len(dispatcher: &mut Dispatcher) -> Option<usize> {
+    let action = dispatcher.next_queued_action()?;
+
+    match *action
+        .ptr
+        .downcast::<MioEffectfulAction>()
+        .expect("unexpected action")
+    {
+        MioEffectfulAction::TcpWrite { data, .. } => Some(data.len()),
+        action => panic!("unexpected action: {:?}", action),
+    }
+}
+
+#[test]
+fn tcp_rate_schedule() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(READY);
+    let send = builder.send_data(connection, vec![0; 100_000], TimeoutAbsolute::Never);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    tcp_state.set_rate_schedule(&connection, vec![(0, 1000), (500, 100)]);
+
+    // Bytes written in each 100ms slot, every write completes right away.
+    let mut written = Vec::new();
+
+    for current_time in (0..3000).step_by(100) {
+        let mut slot = 0;
+
+        process_pending_send_requests(current_time, &mut tcp_state, &mut dispatcher);
+
+        while let Some(len) = write_len(&mut dispatcher) {
+            tcp_state.complete_send(&send, len);
+            handle_send_common(&mut tcp_state, &mut dispatcher, current_time, send, true);
+            slot += len;
+        }
+
+        written.push(slot);
+    }
+
+    let per_second: Vec<usize> = written.chunks(10).map(|slots| slots.iter().sum()).collect();
+
+    // The first second's budget is used up before the rate drops at 500ms:
+    // nothing more is written until the next window starts.
+    assert_eq!(written[0], 1000);
+    assert!(written[5..10].iter().all(|slot| *slot == 0));
+    assert_eq!(per_second, [1000, 100, 100]);
+    assert_eq!(tcp_state.get_send_request(&send).bytes_sent, 1200);
+}
@@ -0,0 +1,71 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::recv_line::{action::RecvLineAction, state::RecvLineState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct RecvLine {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub recv_line: RecvLineState,
+}
+
+impl RegisterModel for RecvLine {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<RecvLineState>()
+    }
+}
+
+#[test]
+fn tcp_recv_line() {
+    let mut runner = RunnerBuilder::<RecvLine>::new()
+        .register::<RecvLine>()
+        .instance(
+            RecvLine {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                recv_line: RecvLineState::new(
+                    "127.0.0.1:8904".to_string(),
+                    b"EHLO example.org\r\nNOOP\r\n".to_vec(),
+                ),
+            },
+            || RecvLineAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<RecvLineState>().lines.len() == 2,
+        1000
+    ));
+
+    let line_state: &RecvLineState = runner.state().substate();
+    let connection = line_state.server_connection.unwrap();
+
+    assert_eq!(
+        line_state.lines,
+        vec![b"EHLO example.org".to_vec(), b"NOOP".to_vec()]
+    );
+    // Nothing is left over past the last line.
+    assert!(runner
+        .state()
+        .substate::<TcpState>()
+        .get_connection(&connection)
+        .line_buffer
+        .is_empty());
+}
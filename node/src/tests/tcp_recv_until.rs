@@ -0,0 +1,63 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::recv_until::{action::RecvUntilAction, state::RecvUntilState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct RecvUntil {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub recv_until: RecvUntilState,
+}
+
+impl RegisterModel for RecvUntil {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<RecvUntilState>()
+    }
+}
+
+#[test]
+fn tcp_recv_until() {
+    let mut runner = RunnerBuilder::<RecvUntil>::new()
+        .register::<RecvUntil>()
+        .instance(
+            RecvUntil {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                recv_until: RecvUntilState::new(
+                    "127.0.0.1:8918".to_string(),
+                    b"HELO\r\n".to_vec(),
+                    b"\r\n".to_vec(),
+                    2,
+                ),
+            },
+            || RecvUntilAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<RecvUntilState>().received.len() == 2,
+        1000
+    ));
+
+    let until_state: &RecvUntilState = runner.state().substate();
+
+    // At most `max_bytes`, then up to (and including) the delimiter.
+    assert_eq!(until_state.received, [b"HE".to_vec(), b"LO\r\n".to_vec()]);
+}
@@ -0,0 +1,62 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::{TcpConfig, TcpState},
+            tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::register_retry::{action::RegisterRetryAction, state::RegisterRetryState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct RegisterRetry {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub register_retry: RegisterRetryState,
+}
+
+impl RegisterModel for RegisterRetry {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<RegisterRetryState>()
+    }
+}
+
+#[test]
+fn tcp_register_retry() {
+    let mut runner = RunnerBuilder::<RegisterRetry>::new()
+        .register::<RegisterRetry>()
+        .instance(
+            RegisterRetry {
+                time: TimeState::default(),
+                // Retry failed poll registrations once.
+                tcp: TcpState::from_config(TcpConfig {
+                    register_retries: 1,
+                    register_backoff: 10,
+                    ..TcpConfig::default()
+                }),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                register_retry: RegisterRetryState::new(
+                    "127.0.0.1:8892".to_string(),
+                    b"ping".to_vec(),
+                ),
+            },
+            || RegisterRetryAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<RegisterRetryState>().received,
+        1000
+    ));
+}
@@ -0,0 +1,75 @@
+use crate::{
+    automaton::{action::TimeoutAbsolute, state::Uid},
+    callback,
+    models::pure::net::tcp::{
+        action::TcpAction,
+        state::{ConnectionType, RecvRequest, TcpConfig, TcpState},
+    },
+};
+
+fn tcp_state() -> TcpState {
+    TcpState::from_config(TcpConfig {
+        reuse_diagnostics: true,
+        ..TcpConfig::default()
+    })
+}
+
+fn outgoing() -> ConnectionType {
+    ConnectionType::Outgoing {
+        on_success: callback!(|connection: Uid| TcpAction::ConnectSuccess { connection }),
+        on_timeout: callback!(|connection: Uid| TcpAction::ProbeConnectTimeout { connection }),
+        on_error: callback!(|(connection: Uid, error: String)| TcpAction::ConnectError { connection, error }),
+    }
+}
+
+#[test]
+fn tcp_reuse_diagnostics_describe() {
+    let mut tcp_state = tcp_state();
+    let connection = Uid::from(2u64);
+
+    tcp_state.new_connection(connection, outgoing(), TimeoutAbsolute::Never);
+
+    assert_eq!(
+        tcp_state.describe_object(&connection).as_deref(),
+        Some("outgoing connection (pending)")
+    );
+    assert_eq!(tcp_state.describe_object(&Uid::from(3u64)), None);
+}
+
+#[test]
+#[should_panic(expected = "Attempt to re-use existing Uid(1): listener on 127.0.0.1:8925")]
+fn tcp_reuse_diagnostics_listener() {
+    let mut tcp_state = tcp_state();
+    let uid = Uid::from(1u64);
+
+    tcp_state.new_listener(
+        uid,
+        "127.0.0.1:8925".to_string(),
+        callback!(|listener: Uid| TcpAction::ListenSuccess { listener }),
+        callback!(|(listener: Uid, error: String)| TcpAction::ListenError { listener, error }),
+    );
+    // Re-used for an object of another kind.
+    tcp_state.new_connection(uid, outgoing(), TimeoutAbsolute::Never);
+}
+
+#[test]
+#[should_panic(expected = "Attempt to re-use existing Uid(2): outgoing connection (pending)")]
+fn tcp_reuse_diagnostics_recv_request() {
+    let mut tcp_state = tcp_state();
+    let connection = Uid::from(2u64);
+
+    tcp_state.new_connection(connection, outgoing(), TimeoutAbsolute::Never);
+    // Re-used for a recv request on the connection itself.
+    tcp_state.new_recv_request(
+        connection,
+        RecvRequest::new(
+            connection,
+            1,
+            false,
+            TimeoutAbsolute::Never,
+            callback!(|(uid: Uid, data: Vec<u8>)| TcpAction::RecvSuccess { uid, data }),
+            callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpAction::RecvSuccessPartial { uid, partial_data }),
+            callback!(|(uid: Uid, error: String)| TcpAction::RecvError { uid, error }),
+        ),
+    );
+}
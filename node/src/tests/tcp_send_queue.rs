@@ -0,0 +1,93 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::{
+        action::{Dispatcher, TimeoutAbsolute},
+        state::Uid,
+    },
+    callback,
+    models::{
+        effectful::mio::action::MioEffectfulAction,
+        pure::net::tcp::{
+            action::{ConnectionEvent, TcpAction},
+            state::SendRequest,
+            util::process_pending_send_requests,
+        },
+    },
+};
+
+const WRITABLE: ConnectionEvent = ConnectionEvent::Ready {
+    can_recv: false,
+    can_send: true,
+};
+
+// Each write only takes this many bytes, so that every send needs more than
+// one poll to complete.
+const WRITE_SIZE: usize = 5;
+
+#[test]
+fn tcp_send_queue_order() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(WRITABLE);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+    let sends: Vec<(Uid, Vec<u8>)> = (1..=3u8)
+        .map(|n| (Uid::from(100 + n as usize), vec![n; 8]))
+        .collect();
+
+    for (uid, data) in sends.iter() {
+        tcp_state.new_send_request(
+            *uid,
+            SendRequest::new(
+                connection,
+                data.clone().into(),
+                true,
+                TimeoutAbsolute::Never,
+                callback!(|uid: Uid| TcpAction::SendSuccess { uid }),
+                callback!(|uid: Uid| TcpAction::SendSuccess { uid }),
+                callback!(|(uid: Uid, error: String)| TcpAction::SendError { uid, error }),
+            ),
+        );
+    }
+
+    let mut received = Vec::new();
+
+    while !tcp_state.pending_send_requests().is_empty() {
+        process_pending_send_requests(0, &mut tcp_state, &mut dispatcher);
+
+        let mut writes = Vec::new();
+
+        while let Some(action) = dispatcher.next_queued_action() {
+            match *action
+                .ptr
+                .downcast::<MioEffectfulAction>()
+                .expect("unexpected action")
+            {
+                MioEffectfulAction::TcpWrite { uid, data, .. } => writes.push((uid, data)),
+                action => panic!("unexpected action: {:?}", action),
+            }
+        }
+
+        // Only the head of the connection's queue is written.
+        assert_eq!(writes.len(), 1);
+
+        let (uid, data) = &writes[0];
+        let written = data.len().min(WRITE_SIZE);
+
+        received.extend_from_slice(&data[..written]);
+        tcp_state.complete_send(uid, written);
+
+        let request = tcp_state.get_send_request(uid);
+
+        if request.bytes_sent == request.data.len() {
+            tcp_state.remove_send_request(uid);
+        } else {
+            // The rest is written at the next poll.
+            tcp_state.get_send_request_mut(uid).send_on_poll = true;
+        }
+    }
+
+    let expected: Vec<u8> = sends.into_iter().flat_map(|(_, data)| data).collect();
+
+    assert_eq!(received, expected);
+    assert!(tcp_state.get_connection(&connection).send_queue.is_empty());
+}
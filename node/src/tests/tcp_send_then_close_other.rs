@@ -0,0 +1,77 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::{ModelState, State},
+    },
+    models::pure::{
+        net::{tcp::state::TcpState, tcp_server::state::TcpServerState},
+        tests::relay_server::{
+            action::RelayServerAction,
+            state::{RelayServerState, RelayServerStatus},
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, io::Read, net::TcpStream};
+
+#[derive(ModelState, Debug)]
+pub struct Relay {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub server: RelayServerState,
+}
+
+impl RegisterModel for Relay {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<RelayServerState>()
+    }
+}
+
+#[test]
+fn tcp_server_send_then_close_other() {
+    let address = "127.0.0.1:8948";
+    let notice = b"peer closing".to_vec();
+    let mut runner = RunnerBuilder::<Relay>::new()
+        .register::<Relay>()
+        .instance(
+            Relay {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                server: RelayServerState::new(address.to_string(), notice.clone()),
+            },
+            || RelayServerAction::Tick.into(),
+        )
+        .build();
+    let accepted = |state: &State<Relay>| state.substate::<RelayServerState>().connections.len();
+
+    assert!(runner.run_until(
+        |state| state.substate::<RelayServerState>().status == RelayServerStatus::Listening,
+        1000
+    ));
+
+    // Connected one at a time, so that they are accepted in order.
+    let mut first = TcpStream::connect(address).expect("connection refused");
+
+    assert!(runner.run_until(|state| accepted(state) == 1, 1000));
+
+    let mut second = TcpStream::connect(address).expect("connection refused");
+
+    assert!(runner.run_until(
+        |state| !state.substate::<RelayServerState>().closed.is_empty(),
+        1000
+    ));
+
+    let server_state: &RelayServerState = runner.state().substate();
+
+    assert_eq!(server_state.closed, [server_state.connections[1]]);
+
+    let mut received = vec![0u8; notice.len()];
+
+    first.read_exact(&mut received).expect("read failed");
+    assert_eq!(received, notice);
+    // The second connection was closed by the server.
+    assert_eq!(second.read(&mut [0u8; 1]).expect("read failed"), 0);
+}
@@ -0,0 +1,173 @@
+use crate::{
+    automaton::{
+        action::Dispatcher,
+        model::PureModel,
+        state::{ModelState, State, Uid},
+    },
+    callback,
+    models::pure::net::{
+        tcp::action::TcpAction,
+        tcp_server::{
+            action::TcpServerAction,
+            state::{Listener, TcpServerState},
+        },
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+struct ServerNode {
+    tcp_server: TcpServerState,
+}
+
+// A node whose `listener` has established `connections`.
+fn server_node(listener: Uid, connections: &[Uid]) -> State<ServerNode> {
+    let mut server_state = TcpServerState::new();
+    let mut state = State::<ServerNode>::new();
+
+    server_state.new_listener(
+        listener,
+        Listener::new(
+            16,
+            callback!(|listener: Uid| TcpServerAction::NewSuccess { listener }),
+            callback!(|(listener: Uid, error: String)| TcpServerAction::NewError { listener, error }),
+            callback!(|(_listener: Uid, connection: Uid)| TcpServerAction::CloseEventNotify { connection }),
+            callback!(|(_listener: Uid, connection: Uid)| TcpServerAction::CloseEventNotify { connection }),
+            callback!(|listener: Uid| TcpServerAction::NewSuccess { listener }),
+        ),
+    );
+
+    for &connection in connections {
+        server_state.new_connection(connection, listener);
+        server_state
+            .get_listener_mut(&listener)
+            .route_connection(connection, "127.0.0.1:40000");
+    }
+
+    state.substates.push(ServerNode {
+        tcp_server: server_state,
+    });
+    state
+}
+
+// Processes `action` and the `TcpServerAction`s it dispatches, returns the
+// connections `TcpAction::Close` was dispatched for.
+fn closed_by(state: &mut State<ServerNode>, action: TcpServerAction) -> Vec<Uid> {
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    TcpServerState::process_pure(state, action, &mut dispatcher);
+    closed_by_queued(state, &mut dispatcher)
+}
+
+fn closed_by_queued(state: &mut State<ServerNode>, dispatcher: &mut Dispatcher) -> Vec<Uid> {
+    let mut closed = Vec::new();
+
+    while let Some(action) = dispatcher.next_queued_action() {
+        if let Some(action) = action.ptr.downcast_ref::<TcpServerAction>() {
+            TcpServerState::process_pure(state, action.clone(), dispatcher)
+        } else if let Some(TcpAction::Close { connection, .. }) = action.ptr.downcast_ref() {
+            closed.push(*connection)
+        }
+    }
+
+    closed
+}
+
+#[test]
+fn tcp_server_close_twice() {
+    let (listener, connection) = (Uid::from(1u64), Uid::from(2u64));
+    let mut state = server_node(listener, &[connection]);
+    let close = TcpServerAction::Close {
+        connection,
+        deliver_buffered: false,
+    };
+
+    assert_eq!(closed_by(&mut state, close.clone()), [connection]);
+    assert_eq!(closed_by(&mut state, close), []);
+}
+
+// A connection already being closed isn't closed again, but `CloseAll` still
+// waits for it.
+#[test]
+fn tcp_server_close_all_closing() {
+    let listener = Uid::from(1u64);
+    let (closing, open) = (Uid::from(2u64), Uid::from(3u64));
+    let mut state = server_node(listener, &[closing, open]);
+
+    closed_by(
+        &mut state,
+        TcpServerAction::Close {
+            connection: closing,
+            deliver_buffered: false,
+        },
+    );
+    assert_eq!(
+        closed_by(
+            &mut state,
+            TcpServerAction::CloseAll {
+                listener,
+                on_complete: callback!(|listener: Uid| TcpServerAction::NewSuccess { listener }),
+            },
+        ),
+        [open]
+    );
+
+    let server_state: &TcpServerState = state.substate();
+    let close_all = server_state.listeners[&listener]
+        .close_all
+        .as_ref()
+        .unwrap();
+
+    assert_eq!(
+        close_all.connections.iter().cloned().collect::<Vec<_>>(),
+        [closing, open]
+    );
+}
+
+#[test]
+fn tcp_server_close_group_closing() {
+    let listener = Uid::from(1u64);
+    let (closing, open) = (Uid::from(2u64), Uid::from(3u64));
+    let mut state = server_node(listener, &[closing, open]);
+
+    state
+        .substate_mut::<TcpServerState>()
+        .groups
+        .insert("peers".to_string(), [closing, open].into());
+    closed_by(
+        &mut state,
+        TcpServerAction::Close {
+            connection: closing,
+            deliver_buffered: false,
+        },
+    );
+    assert_eq!(
+        closed_by(
+            &mut state,
+            TcpServerAction::CloseGroup {
+                group: "peers".to_string(),
+                deliver_buffered: false,
+            },
+        ),
+        [open]
+    );
+}
+
+#[test]
+fn tcp_server_shutdown_closing() {
+    let listener = Uid::from(1u64);
+    let (closing, open) = (Uid::from(2u64), Uid::from(3u64));
+    let mut state = server_node(listener, &[closing, open]);
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    closed_by(
+        &mut state,
+        TcpServerAction::Close {
+            connection: closing,
+            deliver_buffered: false,
+        },
+    );
+    TcpServerState::on_shutdown(&mut state, &mut dispatcher);
+    assert_eq!(closed_by_queued(&mut state, &mut dispatcher), [open]);
+}
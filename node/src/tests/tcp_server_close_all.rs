@@ -0,0 +1,70 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::close_all::{action::CloseAllAction, state::CloseAllState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct CloseAll {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub close_all: CloseAllState,
+}
+
+impl RegisterModel for CloseAll {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<CloseAllState>()
+    }
+}
+
+#[test]
+fn tcp_server_close_all() {
+    let mut runner = RunnerBuilder::<CloseAll>::new()
+        .register::<CloseAll>()
+        .instance(
+            CloseAll {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                close_all: CloseAllState::new("127.0.0.1:8908".to_string()),
+            },
+            || CloseAllAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<CloseAllState>().server_connections.len() == 4,
+        1000
+    ));
+
+    let close_all_state: &CloseAllState = runner.state().substate();
+    let server_state: &TcpServerState = runner.state().substate();
+    let (closed, accepted) = close_all_state.server_connections.split_at(3);
+
+    assert!(close_all_state.close_all_complete);
+    // All the connections were closed, but not the listener.
+    assert_eq!(close_all_state.closed_connections.len(), 3);
+    assert!(closed
+        .iter()
+        .all(|connection| close_all_state.closed_connections.contains(connection)));
+    assert_eq!(
+        server_state
+            .get_listener(&close_all_state.listener.unwrap())
+            .connections,
+        accepted.iter().copied().collect()
+    );
+}
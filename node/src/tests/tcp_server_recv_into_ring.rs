@@ -0,0 +1,61 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::ring_parse::{action::RingParseAction, state::RingParseState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct RingParse {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub ring_parse: RingParseState,
+}
+
+impl RegisterModel for RingParse {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<RingParseState>()
+    }
+}
+
+#[test]
+fn tcp_server_recv_into_ring() {
+    let messages = vec![b"abc".to_vec(), b"hello".to_vec(), b"x".to_vec()];
+    let mut runner = RunnerBuilder::<RingParse>::new()
+        .register::<RingParse>()
+        .instance(
+            RingParse {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                // Messages straddle the recv boundaries.
+                ring_parse: RingParseState::new("127.0.0.1:8909".to_string(), messages.clone(), 3),
+            },
+            || RingParseAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<RingParseState>().parsed.len() == 3,
+        1000
+    ));
+
+    let ring_state: &RingParseState = runner.state().substate();
+
+    assert_eq!(ring_state.parsed, messages);
+    // The number of messages parsed after each recv.
+    assert_eq!(ring_state.ring_progress, vec![0, 1, 1, 3]);
+}
@@ -0,0 +1,86 @@
+use crate::{
+    automaton::state::Uid,
+    callback,
+    models::pure::net::tcp_server::{
+        action::{RoutingPolicy, TcpServerAction},
+        state::{Listener, TcpServerState},
+    },
+};
+
+// Accepts a connection from `peer_address` on `listener` and returns its shard.
+fn accept(
+    server_state: &mut TcpServerState,
+    listener: Uid,
+    uid: usize,
+    peer_address: &str,
+) -> usize {
+    let connection = Uid::from(uid);
+
+    server_state.new_connection(connection, listener);
+    server_state
+        .get_listener_mut(&listener)
+        .route_connection(connection, peer_address);
+    server_state.connection_shard(&connection).unwrap()
+}
+
+fn close(server_state: &mut TcpServerState, listener: Uid, uid: usize) {
+    let connection = Uid::from(uid);
+
+    server_state
+        .get_listener_mut(&listener)
+        .remove_connection(&connection);
+    assert_eq!(server_state.connection_shard(&connection), None);
+}
+
+#[test]
+fn tcp_server_sticky_routing() {
+    let listener = Uid::from(1usize);
+    let mut server_state = TcpServerState::new();
+
+    server_state.new_listener(
+        listener,
+        Listener::new(
+            16,
+            callback!(|listener: Uid| TcpServerAction::NewSuccess { listener }),
+            callback!(|(listener: Uid, error: String)| TcpServerAction::NewError { listener, error }),
+            callback!(|(_listener: Uid, connection: Uid)| TcpServerAction::CloseEventNotify { connection }),
+            callback!(|(_listener: Uid, connection: Uid)| TcpServerAction::CloseEventNotify { connection }),
+            callback!(|listener: Uid| TcpServerAction::NewSuccess { listener }),
+        ),
+    );
+    server_state.get_listener_mut(&listener).routing =
+        RoutingPolicy::StickyBySourceIp { shards: 2 };
+
+    // Two concurrent connections from the same IP share a shard, where
+    // round-robin would have split them.
+    let shard_a = accept(&mut server_state, listener, 10, "10.0.0.1:40001");
+    assert_eq!(
+        accept(&mut server_state, listener, 11, "10.0.0.1:40002"),
+        shard_a
+    );
+
+    let shard_b = accept(&mut server_state, listener, 12, "10.0.0.2:40001");
+    assert_ne!(shard_a, shard_b);
+
+    // Reconnects are routed to the shard of their IP, whatever the order.
+    close(&mut server_state, listener, 10);
+    close(&mut server_state, listener, 11);
+    close(&mut server_state, listener, 12);
+    assert_eq!(
+        accept(&mut server_state, listener, 13, "10.0.0.2:40100"),
+        shard_b
+    );
+    assert_eq!(
+        accept(&mut server_state, listener, 14, "10.0.0.1:40100"),
+        shard_a
+    );
+    close(&mut server_state, listener, 13);
+    assert_eq!(
+        accept(&mut server_state, listener, 15, "10.0.0.2:40200"),
+        shard_b
+    );
+    assert_eq!(
+        accept(&mut server_state, listener, 16, "10.0.0.1:40200"),
+        shard_a
+    );
+}
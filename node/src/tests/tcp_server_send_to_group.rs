@@ -0,0 +1,64 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState,
+        },
+        tests::send_to_group::{action::SendToGroupAction, state::SendToGroupState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct SendToGroup {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub send_to_group: SendToGroupState,
+}
+
+impl RegisterModel for SendToGroup {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<SendToGroupState>()
+    }
+}
+
+#[test]
+fn tcp_server_send_to_group() {
+    let mut runner = RunnerBuilder::<SendToGroup>::new()
+        .register::<SendToGroup>()
+        .instance(
+            SendToGroup {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_client: TcpClientState::new(),
+                send_to_group: SendToGroupState::new(
+                    "127.0.0.1:8912".to_string(),
+                    b"hello room".to_vec(),
+                ),
+            },
+            || SendToGroupAction::Tick.into(),
+        )
+        .build();
+
+    runner.run();
+
+    let group_state: &SendToGroupState = runner.state().substate();
+
+    // Only the members were closed with the group, the other connection was
+    // closed on shutdown.
+    assert_eq!(
+        group_state.closed_connections[..2],
+        group_state.server_connections[..2]
+    );
+    // Every client end received (or timed out).
+    assert_eq!(group_state.recvs.len(), 3);
+}
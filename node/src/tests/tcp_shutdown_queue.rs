@@ -0,0 +1,43 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::state::Uid,
+    callback,
+    models::{
+        effectful::mio::action::ShutdownHow,
+        pure::net::tcp::{
+            action::{ConnectionEvent, TcpAction},
+            state::TcpState,
+        },
+    },
+};
+
+#[test]
+fn tcp_shutdown_queue() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(ConnectionEvent::Ready {
+        can_recv: true,
+        can_send: true,
+    });
+    let mut tcp_state = builder.build();
+
+    // A second shutdown while the first one is in flight is queued.
+    for how in [ShutdownHow::Write, ShutdownHow::Read] {
+        tcp_state.new_shutdown_request(
+            connection,
+            how,
+            callback!(|connection: Uid| TcpAction::ShutdownSuccess { connection }),
+            callback!(|(connection: Uid, error: String)| TcpAction::ShutdownError { connection, error }),
+        );
+    }
+
+    // Results are matched in request order.
+    let how = |tcp_state: &mut TcpState| {
+        tcp_state
+            .take_shutdown_request(&connection)
+            .map(|request| request.how)
+    };
+
+    assert_eq!(how(&mut tcp_state), Some(ShutdownHow::Write));
+    assert_eq!(how(&mut tcp_state), Some(ShutdownHow::Read));
+    assert_eq!(how(&mut tcp_state), None);
+}
@@ -0,0 +1,200 @@
+use crate::{
+    automaton::{action::Timeout, state::Uid},
+    callback,
+    models::{
+        effectful::mio::{action::SocketOptions, state::MioState},
+        pure::net::tcp::action::TcpAction,
+    },
+};
+
+fn socket_options() -> SocketOptions {
+    SocketOptions {
+        nodelay: true,
+        reuse_address: true,
+        recv_buffer: Some(64 * 1024),
+        send_buffer: Some(32 * 1024),
+        congestion: None,
+    }
+}
+
+#[test]
+fn tcp_socket_options() {
+    let address = "127.0.0.1:8920".to_string();
+    let mut mio = MioState::new();
+
+    mio.tcp_listen(
+        Uid::from(1u64),
+        address.clone(),
+        Some(socket_options()),
+        None,
+    )
+    .expect("listen with options failed");
+    mio.tcp_connect(
+        Uid::from(2u64),
+        address.clone(),
+        Some(socket_options()),
+        None,
+    )
+    .expect("connect with options failed");
+    mio.tcp_connect(Uid::from(4u64), address.clone(), None, None)
+        .expect("connect failed");
+
+    // The options are applied to the socket. Linux reports twice the
+    // requested buffer sizes, for bookkeeping overhead.
+    let applied = mio
+        .tcp_socket_options(&Uid::from(2u64))
+        .expect("reading socket options failed");
+
+    assert!(applied.nodelay);
+    assert!(applied.recv_buffer.unwrap() >= 64 * 1024);
+    assert!(applied.send_buffer.unwrap() >= 32 * 1024);
+
+    let applied = mio
+        .tcp_socket_options(&Uid::from(4u64))
+        .expect("reading socket options failed");
+
+    assert!(!applied.nodelay);
+
+    // The socket is actually bound to the address.
+    assert!(mio
+        .tcp_listen(
+            Uid::from(3u64),
+            address,
+            Some(SocketOptions::default()),
+            None
+        )
+        .is_err());
+    mio.shutdown();
+}
+
+#[test]
+fn tcp_connect_bind_address() {
+    let address = "127.0.0.1:8922".to_string();
+    let connection = Uid::from(2u64);
+    let mut mio = MioState::new();
+
+    mio.tcp_listen(Uid::from(1u64), address.clone(), None, None)
+        .expect("listen failed");
+    mio.tcp_connect(
+        connection,
+        address.clone(),
+        None,
+        Some("127.0.0.1:8923".to_string()),
+    )
+    .expect("connect with bind address failed");
+
+    let (local_address, _) = mio.tcp_peer_address(&connection).unwrap();
+
+    assert_eq!(local_address, "127.0.0.1:8923");
+
+    // Not a local address: reported as a connect error.
+    assert!(mio
+        .tcp_connect(
+            Uid::from(3u64),
+            address.clone(),
+            None,
+            Some("192.0.2.1:0".to_string())
+        )
+        .is_err());
+    assert!(mio
+        .tcp_connect(Uid::from(4u64), address, None, Some("invalid".to_string()))
+        .is_err());
+    mio.shutdown();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn tcp_congestion_control() {
+    let address = "127.0.0.1:8928".to_string();
+    let allowed = std::fs::read_to_string("/proc/sys/net/ipv4/tcp_allowed_congestion_control")
+        .unwrap_or_default();
+    let mut mio = MioState::new();
+
+    mio.tcp_listen(Uid::from(1u64), address.clone(), None, None)
+        .expect("listen failed");
+
+    // BBR where available, Reno is built in and always allowed.
+    let algorithms = ["bbr", "reno"].into_iter().filter(|algorithm| {
+        *algorithm == "reno" || allowed.split_whitespace().any(|a| a == *algorithm)
+    });
+
+    for (uid, algorithm) in (2u64..).zip(algorithms) {
+        let connection = Uid::from(uid);
+        let options = SocketOptions {
+            congestion: Some(algorithm.to_string()),
+            ..SocketOptions::default()
+        };
+
+        mio.tcp_connect(connection, address.clone(), Some(options), None)
+            .expect("connect with congestion control failed");
+        assert_eq!(
+            mio.tcp_get_congestion(&connection).as_deref(),
+            Ok(algorithm)
+        );
+    }
+
+    let options = SocketOptions {
+        congestion: Some("no-such-algorithm".to_string()),
+        ..SocketOptions::default()
+    };
+
+    assert!(mio
+        .tcp_connect(Uid::from(10u64), address, Some(options), None)
+        .is_err());
+    mio.shutdown();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn tcp_listen_backlog() {
+    let address = "127.0.0.1:8931";
+    let mut mio = MioState::new();
+
+    mio.tcp_listen(Uid::from(1u64), address.to_string(), None, Some(1))
+        .expect("listen with backlog failed");
+
+    // Nothing is accepted: once the queue is full (backlog + 1 connections
+    // on Linux), further connection attempts go unanswered.
+    let connected = (0..4)
+        .map(|_| {
+            std::net::TcpStream::connect_timeout(
+                &address.parse().unwrap(),
+                std::time::Duration::from_millis(200),
+            )
+        })
+        .filter(Result::is_ok)
+        .count();
+
+    assert_eq!(connected, 2);
+    mio.shutdown();
+}
+
+#[test]
+fn tcp_socket_options_serialized() {
+    let action = TcpAction::Connect {
+        connection: Uid::from(1u64),
+        address: "127.0.0.1:8920".to_string(),
+        options: Some(socket_options()),
+        bind_address: Some("127.0.0.1:8923".to_string()),
+        nodelay: true,
+        timeout: Timeout::Never,
+        on_success: callback!(|connection: Uid| TcpAction::ConnectSuccess { connection }),
+        on_timeout: callback!(|connection: Uid| TcpAction::ProbeConnectTimeout { connection }),
+        on_error: callback!(|(connection: Uid, error: String)| TcpAction::ConnectError { connection, error }),
+    };
+    let recorded = bincode::serialize(&action).expect("serialization failed");
+
+    match bincode::deserialize(&recorded).expect("deserialization failed") {
+        TcpAction::Connect {
+            options,
+            bind_address,
+            nodelay,
+            ..
+        } => {
+            assert_eq!(options, Some(socket_options()));
+            assert_eq!(bind_address.as_deref(), Some("127.0.0.1:8923"));
+            assert!(nodelay);
+        }
+        action => panic!("unexpected action {:?}", action),
+    }
+}
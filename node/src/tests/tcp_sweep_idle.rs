@@ -0,0 +1,75 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{tcp::state::TcpState, tcp_server::state::TcpServerState},
+        tests::idle_sweep_server::{
+            action::IdleSweepServerAction,
+            state::{IdleSweepServerState, IdleSweepServerStatus},
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{
+    any::Any,
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+#[derive(ModelState, Debug)]
+pub struct IdleSweep {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub server: IdleSweepServerState,
+}
+
+impl RegisterModel for IdleSweep {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<IdleSweepServerState>()
+    }
+}
+
+#[test]
+fn tcp_server_sweep_idle() {
+    let address = "127.0.0.1:8939";
+    let (data, max_idle_ms) = (b"hello".to_vec(), 300);
+    let mut runner = RunnerBuilder::<IdleSweep>::new()
+        .register::<IdleSweep>()
+        .instance(
+            IdleSweep {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                server: IdleSweepServerState::new(address.to_string(), data.len(), max_idle_ms),
+            },
+            || IdleSweepServerAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<IdleSweepServerState>().status == IdleSweepServerStatus::Listening,
+        1000
+    ));
+
+    // The client sends its data and goes silent.
+    let mut client = TcpStream::connect(address).expect("connection refused");
+
+    client.write_all(&data).expect("write failed");
+    assert!(runner.run_until(
+        |state| state.substate::<IdleSweepServerState>().closed.is_some(),
+        1000
+    ));
+
+    let server_state: &IdleSweepServerState = runner.state().substate();
+    let (connection, closed_at) = server_state.closed.unwrap();
+    let last_activity = server_state.last_activity.expect("closed before receiving");
+
+    assert_eq!(server_state.received.as_ref(), Some(&data));
+    assert_eq!(Some(connection), server_state.connection);
+    assert!(closed_at - last_activity > max_idle_ms as u128);
+    assert_eq!(client.read(&mut [0u8; 1]).expect("read failed"), 0);
+}
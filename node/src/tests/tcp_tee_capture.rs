@@ -0,0 +1,57 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{
+            tcp::state::TcpState, tcp_client::state::TcpClientState,
+            tcp_server::state::TcpServerState, tee::state::TeeState,
+        },
+        tests::tee_capture::{action::TeeCaptureAction, state::TeeCaptureState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::any::Any;
+
+#[derive(ModelState, Debug)]
+pub struct TeeCapture {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_client: TcpClientState,
+    pub tee: TeeState,
+    pub tee_capture: TeeCaptureState,
+}
+
+impl TeeCapture {
+    pub fn new(address: &str, data: &[u8]) -> Self {
+        Self {
+            time: TimeState::default(),
+            tcp: TcpState::new(),
+            tcp_server: TcpServerState::new(),
+            tcp_client: TcpClientState::new(),
+            tee: TeeState::new(),
+            tee_capture: TeeCaptureState::new(address.to_string(), data.to_vec()),
+        }
+    }
+}
+
+impl RegisterModel for TeeCapture {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TeeCaptureState>()
+    }
+}
+
+#[test]
+fn tcp_tee_capture() {
+    // Halts once the echo was received and the capture checked.
+    RunnerBuilder::<TeeCapture>::new()
+        .register::<TeeCapture>()
+        .instance(TeeCapture::new("127.0.0.1:8894", b"ping"), || {
+            TeeCaptureAction::Tick.into()
+        })
+        .build()
+        .run()
+}
@@ -0,0 +1,400 @@
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout, TimeoutAbsolute},
+        state::Uid,
+    },
+    callback,
+    models::pure::{
+        net::tcp::{
+            action::{ConnectionEvent, TcpAction},
+            state::{
+                ConnectionType, InactivityTimeout, RecvRequest, SendRequest, Status, TcpState,
+            },
+            util::{
+                expire_request, handle_recv_common, process_pending_recv_requests,
+                process_pending_send_requests,
+            },
+        },
+        time::model::timeout_absolute,
+    },
+};
+
+// Builds a `TcpState` with connections and pending send/recv requests at
+// known deadlines, so the timeout sweeps can be run with any `current_time`
+// without a runner (or a `TimeState`).
+pub(super) struct TcpStateBuilder {
+    tcp_state: TcpState,
+    next_uid: usize,
+}
+
+impl TcpStateBuilder {
+    pub(super) fn new() -> Self {
+        let mut tcp_state = TcpState::new();
+
+        tcp_state.status = Status::Ready {
+            instance: Uid::from(1usize),
+            poll: Uid::from(2usize),
+            events: Uid::from(3usize),
+        };
+
+        Self {
+            tcp_state,
+            next_uid: 10,
+        }
+    }
+
+    fn new_uid(&mut self) -> Uid {
+        self.next_uid += 1;
+        Uid::from(self.next_uid)
+    }
+
+    // A connection as left by the last poll: `events` is what it reported.
+    pub(super) fn connection(&mut self, events: ConnectionEvent) -> Uid {
+        let connection = self.new_uid();
+
+        self.tcp_state.new_connection(
+            connection,
+            ConnectionType::Outgoing {
+                on_success: callback!(|connection: Uid| TcpAction::ConnectSuccess { connection }),
+                on_timeout: callback!(|connection: Uid| TcpAction::ConnectSuccess { connection }),
+                on_error: callback!(|(connection: Uid, error: String)| TcpAction::ConnectError { connection, error }),
+            },
+            TimeoutAbsolute::Never,
+        );
+        self.tcp_state.get_connection_mut(&connection).events = Some(events);
+        connection
+    }
+
+    // A send request waiting for the next poll.
+    pub(super) fn send_request(&mut self, connection: Uid, timeout: TimeoutAbsolute) -> Uid {
+        self.send_data(connection, b"ping".to_vec(), timeout)
+    }
+
+    pub(super) fn send_data(
+        &mut self,
+        connection: Uid,
+        data: Vec<u8>,
+        timeout: TimeoutAbsolute,
+    ) -> Uid {
+        let uid = self.new_uid();
+
+        self.tcp_state.new_send_request(
+            uid,
+            SendRequest::new(
+                connection,
+                data.into(),
+                true,
+                timeout,
+                callback!(|uid: Uid| TcpAction::SendSuccess { uid }),
+                callback!(|uid: Uid| TcpAction::SendSuccess { uid }),
+                callback!(|(uid: Uid, error: String)| TcpAction::SendError { uid, error }),
+            ),
+        );
+        uid
+    }
+
+    // A recv request waiting for the next poll.
+    pub(super) fn recv_request(&mut self, connection: Uid, timeout: TimeoutAbsolute) -> Uid {
+        let uid = self.new_uid();
+
+        self.tcp_state.new_recv_request(
+            uid,
+            RecvRequest::new(
+                connection,
+                4,
+                true,
+                timeout,
+                callback!(|(uid: Uid, data: Vec<u8>)| TcpAction::RecvSuccess { uid, data }),
+                callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpAction::RecvSuccessPartial { uid, partial_data }),
+                callback!(|(uid: Uid, error: String)| TcpAction::RecvError { uid, error }),
+            ),
+        );
+        uid
+    }
+
+    pub(super) fn build(self) -> TcpState {
+        self.tcp_state
+    }
+}
+
+fn queued_actions(dispatcher: &mut Dispatcher) -> usize {
+    std::iter::from_fn(|| dispatcher.next_queued_action()).count()
+}
+
+// The connection is idle, so requests either time out or are left pending.
+const IDLE: ConnectionEvent = ConnectionEvent::Ready {
+    can_recv: false,
+    can_send: false,
+};
+
+#[test]
+fn tcp_send_timeout_boundaries() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(IDLE);
+    let expired = builder.send_request(connection, TimeoutAbsolute::Millis(99));
+    let at_deadline = builder.send_request(connection, TimeoutAbsolute::Millis(100));
+    let not_expired = builder.send_request(connection, TimeoutAbsolute::Millis(101));
+    let never = builder.send_request(connection, TimeoutAbsolute::Never);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    process_pending_send_requests(100, &mut tcp_state, &mut dispatcher);
+
+    let pending: Vec<Uid> = tcp_state
+        .pending_send_requests()
+        .into_iter()
+        .map(|(uid, _)| *uid)
+        .collect();
+
+    // A request times out once its deadline is reached.
+    assert_eq!(pending, [not_expired, never]);
+    assert_eq!(
+        queued_actions(&mut dispatcher),
+        [expired, at_deadline].len()
+    );
+
+    process_pending_send_requests(101, &mut tcp_state, &mut dispatcher);
+    assert_eq!(tcp_state.pending_send_requests().len(), 1);
+    assert_eq!(queued_actions(&mut dispatcher), 1);
+}
+
+#[test]
+fn tcp_recv_timeout_boundaries() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(IDLE);
+    let expired = builder.recv_request(connection, TimeoutAbsolute::Millis(99));
+    let at_deadline = builder.recv_request(connection, TimeoutAbsolute::Millis(100));
+    let not_expired = builder.recv_request(connection, TimeoutAbsolute::Millis(101));
+    let never = builder.recv_request(connection, TimeoutAbsolute::Never);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    process_pending_recv_requests(100, &mut tcp_state, &mut dispatcher);
+
+    let pending: Vec<Uid> = tcp_state
+        .pending_recv_requests()
+        .into_iter()
+        .map(|(uid, _)| *uid)
+        .collect();
+
+    assert_eq!(pending, [not_expired, never]);
+    assert_eq!(
+        queued_actions(&mut dispatcher),
+        [expired, at_deadline].len()
+    );
+
+    process_pending_recv_requests(101, &mut tcp_state, &mut dispatcher);
+    assert_eq!(tcp_state.pending_recv_requests().len(), 1);
+    assert_eq!(queued_actions(&mut dispatcher), 1);
+}
+
+#[test]
+fn tcp_expire_recv_request() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(IDLE);
+    let recv = builder.recv_request(connection, TimeoutAbsolute::Never);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    // `TcpAction::ExpireRequest { uid: recv }`, without a `State` around it.
+    expire_request(&mut tcp_state, &mut dispatcher, recv);
+    assert!(tcp_state.pending_recv_requests().is_empty());
+
+    let action = dispatcher
+        .next_queued_action()
+        .expect("timeout callback not dispatched")
+        .ptr
+        .downcast::<TcpAction>()
+        .expect("unexpected callback action");
+
+    assert_eq!(
+        *action,
+        TcpAction::RecvSuccessPartial {
+            uid: recv,
+            partial_data: Vec::new()
+        }
+    );
+    assert!(dispatcher.next_queued_action().is_none());
+}
+
+#[test]
+fn tcp_recv_inactivity_timeout() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(IDLE);
+    let absolute = builder.recv_request(connection, TimeoutAbsolute::Millis(100));
+    let inactivity = builder.recv_request(connection, TimeoutAbsolute::Millis(100));
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    // As set by `TcpAction::Recv` with `Timeout::Inactivity(100)` at time 0.
+    tcp_state.get_recv_request_mut(&inactivity).inactivity = Some(InactivityTimeout::new(100));
+
+    // Slow but steady: a byte every 80ms, the whole transfer outlasts 100ms.
+    for time in [80, 160, 240] {
+        for uid in [absolute, inactivity] {
+            if tcp_state.has_recv_request(&uid) {
+                tcp_state
+                    .get_recv_request_mut(&uid)
+                    .buffered_data
+                    .push(b'x');
+                handle_recv_common(&mut tcp_state, &mut dispatcher, time, uid, false);
+            }
+        }
+
+        process_pending_recv_requests(time, &mut tcp_state, &mut dispatcher);
+    }
+
+    // The absolute deadline was reached in spite of the progress.
+    assert!(!tcp_state.has_recv_request(&absolute));
+    assert_eq!(queued_actions(&mut dispatcher), 1);
+    assert_eq!(
+        tcp_state.get_recv_request(&inactivity).timeout,
+        TimeoutAbsolute::Millis(340)
+    );
+
+    // Stalled: the inactivity timeout fires 100ms after the last byte.
+    process_pending_recv_requests(339, &mut tcp_state, &mut dispatcher);
+    assert!(tcp_state.has_recv_request(&inactivity));
+
+    process_pending_recv_requests(340, &mut tcp_state, &mut dispatcher);
+    assert!(!tcp_state.has_recv_request(&inactivity));
+
+    let action = dispatcher
+        .next_queued_action()
+        .expect("timeout callback not dispatched")
+        .ptr
+        .downcast::<TcpAction>()
+        .expect("unexpected callback action");
+
+    assert_eq!(
+        *action,
+        TcpAction::RecvSuccessPartial {
+            uid: inactivity,
+            partial_data: b"xxx".to_vec()
+        }
+    );
+}
+
+#[test]
+fn tcp_connection_deadline() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(IDLE);
+    let recv = builder.recv_request(connection, TimeoutAbsolute::Millis(100));
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    // As set by `TcpAction::SetDeadline` with `Timeout::Millis(100)` at time 0.
+    tcp_state.set_deadline(&connection, timeout_absolute(0, Timeout::Millis(100)));
+
+    // A recv with a 200ms timeout issued at 80ms only gets the remaining 20ms.
+    assert_eq!(
+        tcp_state.request_deadline(&connection, timeout_absolute(80, Timeout::Millis(200))),
+        TimeoutAbsolute::Millis(100)
+    );
+    // Earlier deadlines are kept.
+    assert_eq!(
+        tcp_state.request_deadline(&connection, timeout_absolute(80, Timeout::Millis(10))),
+        TimeoutAbsolute::Millis(90)
+    );
+    assert_eq!(
+        tcp_state.request_deadline(&connection, TimeoutAbsolute::Never),
+        TimeoutAbsolute::Millis(100)
+    );
+
+    // Progress doesn't push an inactivity deadline past the connection's.
+    let request = tcp_state.get_recv_request_mut(&recv);
+
+    request.inactivity = Some(InactivityTimeout::new(100));
+    request.buffered_data.push(b'x');
+    handle_recv_common(&mut tcp_state, &mut dispatcher, 90, recv, false);
+
+    assert_eq!(
+        tcp_state.get_recv_request(&recv).timeout,
+        TimeoutAbsolute::Millis(100)
+    );
+}
+
+// The `Uid`s of the requests whose callbacks were dispatched, in order.
+fn dispatched_uids(dispatcher: &mut Dispatcher) -> Vec<Uid> {
+    std::iter::from_fn(|| dispatcher.next_queued_action())
+        .map(|action| {
+            match *action
+                .ptr
+                .downcast::<TcpAction>()
+                .expect("unexpected callback action")
+            {
+                TcpAction::SendSuccess { uid } | TcpAction::RecvSuccessPartial { uid, .. } => uid,
+                action => panic!("unexpected callback action: {:?}", action),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn tcp_timeout_creation_order() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(IDLE);
+    let mut sends = Vec::new();
+    let mut recvs = Vec::new();
+
+    // Same deadline, `Uid`s not in creation order.
+    for next_uid in [100, 50, 70] {
+        builder.next_uid = next_uid;
+        sends.push(builder.send_request(connection, TimeoutAbsolute::Millis(100)));
+        recvs.push(builder.recv_request(connection, TimeoutAbsolute::Millis(100)));
+    }
+
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    process_pending_send_requests(100, &mut tcp_state, &mut dispatcher);
+    assert_eq!(dispatched_uids(&mut dispatcher), sends);
+
+    process_pending_recv_requests(100, &mut tcp_state, &mut dispatcher);
+    assert_eq!(dispatched_uids(&mut dispatcher), recvs);
+}
+
+#[test]
+fn tcp_poll_timeout_nearest_deadline() {
+    let mut builder = TcpStateBuilder::new();
+
+    builder.connection(IDLE);
+
+    let tcp_state = builder.build();
+
+    // Nothing pending: the caller's timeout is used as is.
+    assert_eq!(
+        tcp_state.poll_timeout(0, Timeout::Millis(1000)),
+        Timeout::Millis(1000)
+    );
+    assert_eq!(tcp_state.poll_timeout(0, Timeout::Never), Timeout::Never);
+
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(IDLE);
+
+    builder.send_request(connection, TimeoutAbsolute::Millis(300));
+    builder.recv_request(connection, TimeoutAbsolute::Millis(200));
+    builder.send_request(connection, TimeoutAbsolute::Never);
+
+    let tcp_state = builder.build();
+
+    assert_eq!(tcp_state.nearest_deadline(), TimeoutAbsolute::Millis(200));
+    assert_eq!(
+        tcp_state.poll_timeout(50, Timeout::Millis(1000)),
+        Timeout::Millis(150)
+    );
+    assert_eq!(
+        tcp_state.poll_timeout(50, Timeout::Never),
+        Timeout::Millis(150)
+    );
+    // A sooner caller's timeout is kept.
+    assert_eq!(
+        tcp_state.poll_timeout(50, Timeout::Millis(100)),
+        Timeout::Millis(100)
+    );
+    // The deadline already passed: don't block.
+    assert_eq!(
+        tcp_state.poll_timeout(250, Timeout::Millis(1000)),
+        Timeout::Millis(0)
+    );
+}
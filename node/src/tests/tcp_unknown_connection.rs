@@ -0,0 +1,66 @@
+use super::tcp_poll_interest::TcpNode;
+use crate::{
+    automaton::{
+        action::{Dispatcher, Timeout},
+        model::PureModel,
+        state::{State, Uid},
+    },
+    models::pure::{
+        net::tcp::{action::TcpAction, state::TcpState},
+        time::state::TimeState,
+    },
+};
+
+// Processes `action` for a connection `TcpState` doesn't know (e.g. already
+// closed): it's ignored.
+fn process_unknown(action: impl FnOnce(Uid) -> TcpAction) {
+    let mut state = State::<TcpNode>::new();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+
+    state.substates.push(TcpNode {
+        time: TimeState::default(),
+        tcp: TcpState::new(),
+    });
+    TcpState::process_pure(&mut state, action(Uid::from(42usize)), &mut dispatcher);
+    assert!(dispatcher.next_queued_action().is_none());
+}
+
+#[test]
+fn tcp_set_priority_unknown_connection() {
+    process_unknown(|connection| TcpAction::SetPriority {
+        connection,
+        priority: 1,
+    })
+}
+
+#[test]
+fn tcp_set_deadline_unknown_connection() {
+    process_unknown(|connection| TcpAction::SetDeadline {
+        connection,
+        timeout: Timeout::Millis(100),
+    })
+}
+
+#[test]
+fn tcp_set_weight_unknown_connection() {
+    process_unknown(|connection| TcpAction::SetWeight {
+        connection,
+        weight: 2,
+    })
+}
+
+#[test]
+fn tcp_set_rate_schedule_unknown_connection() {
+    process_unknown(|connection| TcpAction::SetRateSchedule {
+        connection,
+        schedule: vec![(0, 1024)],
+    })
+}
+
+#[test]
+fn tcp_set_operation_log_unknown_connection() {
+    process_unknown(|connection| TcpAction::SetOperationLog {
+        connection,
+        enabled: true,
+    })
+}
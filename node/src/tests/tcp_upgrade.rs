@@ -0,0 +1,87 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{tcp::state::TcpState, tcp_server::state::TcpServerState},
+        tests::tcp_upgrade::{action::TcpUpgradeAction, state::TcpUpgradeState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, io::Write, net::TcpStream};
+
+#[derive(ModelState, Debug)]
+pub struct TcpUpgrade {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_server: TcpServerState,
+    pub tcp_upgrade: TcpUpgradeState,
+}
+
+impl RegisterModel for TcpUpgrade {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TcpUpgradeState>()
+    }
+}
+
+#[test]
+fn tcp_server_upgrade() {
+    let address = "127.0.0.1:8941";
+    let mut runner = RunnerBuilder::<TcpUpgrade>::new()
+        .register::<TcpUpgrade>()
+        .instance(
+            TcpUpgrade {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_server: TcpServerState::new(),
+                tcp_upgrade: TcpUpgradeState::new(address.to_string(), 50),
+            },
+            || TcpUpgradeAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<TcpUpgradeState>().listener.is_some(),
+        1000
+    ));
+
+    // The request and the first bytes of the new protocol in a single write.
+    let mut client = TcpStream::connect(address).expect("connect failed");
+
+    client
+        .write_all(b"GET /chat HTTP/1.1\r\nUpgrade: websocket\r\n\r\nhello")
+        .unwrap();
+    assert!(runner.run_until(
+        |state| state.substate::<TcpUpgradeState>().upgraded.is_some(),
+        1000
+    ));
+
+    let upgrade_state: &TcpUpgradeState = runner.state().substate();
+    let connection = upgrade_state.connection.unwrap();
+
+    assert_eq!(
+        upgrade_state.upgraded,
+        Some((connection, b"hello".to_vec()))
+    );
+
+    // Same socket and poll registration: the new handler receives the rest.
+    client.write_all(b" world").unwrap();
+    assert!(runner.run_until(
+        |state| state.substate::<TcpUpgradeState>().received == b" world",
+        1000
+    ));
+
+    let listener = runner
+        .state()
+        .substate::<TcpUpgradeState>()
+        .listener
+        .unwrap();
+    let server_state: &TcpServerState = runner.state().substate();
+
+    assert!(server_state
+        .get_listener(&listener)
+        .upgraded_connections
+        .contains_key(&connection));
+}
@@ -0,0 +1,66 @@
+use crate::{
+    automaton::{action::TimeoutAbsolute, state::Uid},
+    callback,
+    models::pure::net::tcp::{
+        action::TcpAction,
+        state::{ConnectionType, RecvRequest, Status, TcpState},
+    },
+};
+
+#[test]
+fn tcp_validate_orphan_request() {
+    let mut tcp_state = TcpState::new();
+    let connection = Uid::from(10usize);
+
+    tcp_state.status = Status::Ready {
+        instance: Uid::from(1usize),
+        poll: Uid::from(2usize),
+        events: Uid::from(3usize),
+    };
+    tcp_state.new_connection(
+        connection,
+        ConnectionType::Outgoing {
+            on_success: callback!(|connection: Uid| TcpAction::ConnectSuccess { connection }),
+            on_timeout: callback!(|connection: Uid| TcpAction::ConnectSuccess { connection }),
+            on_error: callback!(|(connection: Uid, error: String)| TcpAction::ConnectError { connection, error }),
+        },
+        TimeoutAbsolute::Never,
+    );
+    tcp_state.new_recv_request(
+        Uid::from(20usize),
+        RecvRequest::new(
+            connection,
+            4,
+            false,
+            TimeoutAbsolute::Never,
+            callback!(|(uid: Uid, data: Vec<u8>)| TcpAction::RecvSuccess { uid, data }),
+            callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpAction::RecvSuccessPartial { uid, partial_data }),
+            callback!(|(uid: Uid, error: String)| TcpAction::RecvError { uid, error }),
+        ),
+    );
+    assert_eq!(tcp_state.validate(), Ok(()));
+
+    // A recv request for a connection that doesn't exist.
+    let orphan = Uid::from(21usize);
+    let missing_connection = Uid::from(11usize);
+
+    tcp_state.new_recv_request(
+        orphan,
+        RecvRequest::new(
+            missing_connection,
+            4,
+            false,
+            TimeoutAbsolute::Never,
+            callback!(|(uid: Uid, data: Vec<u8>)| TcpAction::RecvSuccess { uid, data }),
+            callback!(|(uid: Uid, partial_data: Vec<u8>)| TcpAction::RecvSuccessPartial { uid, partial_data }),
+            callback!(|(uid: Uid, error: String)| TcpAction::RecvError { uid, error }),
+        ),
+    );
+    assert_eq!(
+        tcp_state.validate(),
+        Err(vec![format!(
+            "RecvRequest {:?} references inexistent connection {:?}",
+            orphan, missing_connection
+        )])
+    );
+}
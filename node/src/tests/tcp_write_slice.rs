@@ -0,0 +1,84 @@
+use super::tcp_timeouts::TcpStateBuilder;
+use crate::{
+    automaton::{
+        action::{Dispatcher, RcSlice, TimeoutAbsolute},
+        state::Uid,
+    },
+    callback,
+    models::{
+        effectful::mio::action::MioEffectfulAction,
+        pure::net::tcp::{
+            action::{ConnectionEvent, TcpAction},
+            state::{SendRequest, TcpState},
+            util::process_pending_send_requests,
+        },
+    },
+};
+use std::rc::Rc;
+
+const WRITABLE: ConnectionEvent = ConnectionEvent::Ready {
+    can_recv: false,
+    can_send: true,
+};
+
+fn next_write(tcp_state: &mut TcpState, dispatcher: &mut Dispatcher) -> RcSlice {
+    process_pending_send_requests(0, tcp_state, dispatcher);
+
+    let action = dispatcher.next_queued_action().expect("no write started");
+
+    match *action
+        .ptr
+        .downcast::<MioEffectfulAction>()
+        .expect("unexpected action")
+    {
+        MioEffectfulAction::TcpWrite { data, .. } => data,
+        action => panic!("unexpected action: {:?}", action),
+    }
+}
+
+#[test]
+fn tcp_partial_write_shares_data() {
+    let mut builder = TcpStateBuilder::new();
+    let connection = builder.connection(WRITABLE);
+    let mut tcp_state = builder.build();
+    let mut dispatcher = Dispatcher::new(|| TcpAction::Validate.into());
+    let uid = Uid::from(100usize);
+    let data: Rc<[u8]> = (0..4096).map(|i| i as u8).collect::<Vec<u8>>().into();
+
+    tcp_state.new_send_request(
+        uid,
+        SendRequest::new(
+            connection,
+            data.clone(),
+            true,
+            TimeoutAbsolute::Never,
+            callback!(|uid: Uid| TcpAction::SendSuccess { uid }),
+            callback!(|uid: Uid| TcpAction::SendSuccess { uid }),
+            callback!(|(uid: Uid, error: String)| TcpAction::SendError { uid, error }),
+        ),
+    );
+
+    let write = next_write(&mut tcp_state, &mut dispatcher);
+
+    assert_eq!(write.as_ptr(), data.as_ptr());
+    assert_eq!(write.len(), data.len());
+
+    // The retry after a partial write passes the rest of the same buffer.
+    tcp_state.complete_send(&uid, 1000);
+    tcp_state.get_send_request_mut(&uid).send_on_poll = true;
+
+    let write = next_write(&mut tcp_state, &mut dispatcher);
+
+    assert_eq!(tcp_state.get_send_request(&uid).bytes_sent, 1000);
+    assert_eq!(write.as_ptr(), data[1000..].as_ptr());
+    assert_eq!(&*write, &data[1000..]);
+
+    // Only the written range is recorded.
+    let recorded = bincode::serialize(&write).expect("serialization failed");
+
+    assert_eq!(recorded, bincode::serialize(&data[1000..]).unwrap());
+    assert_eq!(
+        bincode::deserialize::<RcSlice>(&recorded).expect("deserialization failed"),
+        write
+    );
+}
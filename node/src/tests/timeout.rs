@@ -0,0 +1,26 @@
+use crate::{
+    automaton::action::{Timeout, TimeoutAbsolute},
+    models::pure::time::model::timeout_absolute,
+};
+
+#[test]
+fn timeout_absolute_saturates() {
+    assert_eq!(
+        timeout_absolute(1000, Timeout::Millis(u64::MAX)),
+        TimeoutAbsolute::Millis(1000 + u64::MAX as u128)
+    );
+
+    // Close to the end of time the deadline saturates instead of wrapping
+    // around to the past.
+    assert_eq!(
+        timeout_absolute(u128::MAX - 1, Timeout::Millis(u64::MAX)),
+        TimeoutAbsolute::Millis(u128::MAX)
+    );
+
+    // `Millis(0)` is due immediately.
+    assert_eq!(
+        timeout_absolute(1000, Timeout::Millis(0)),
+        TimeoutAbsolute::Millis(1000)
+    );
+    assert_eq!(timeout_absolute(1000, Timeout::Never), TimeoutAbsolute::Never);
+}
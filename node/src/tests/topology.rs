@@ -0,0 +1,116 @@
+use crate::{
+    automaton::{action::Timeout, runner::RunnerBuilder},
+    models::pure::{
+        net::topology::{topology_snapshot, TopologyEdge, TopologyNodeKind},
+        tests::{
+            echo_client::{
+                action::EchoClientAction,
+                state::{EchoClientConfig, PayloadPattern},
+            },
+            echo_server::{action::EchoServerAction, state::EchoServerConfig},
+        },
+    },
+    tests::echo_network::{EchoClient, EchoNetwork, EchoServer},
+};
+
+#[test]
+fn topology_one_server_two_clients() {
+    let mut builder = RunnerBuilder::<EchoNetwork>::new()
+        .register::<EchoNetwork>()
+        .instance(
+            EchoNetwork::EchoServer(EchoServer::from_config(EchoServerConfig {
+                address: "127.0.0.1:8907".to_string(),
+                max_connections: 2,
+                poll_timeout: 50,
+                recv_timeout: 1000,
+            })),
+            || EchoServerAction::Tick.into(),
+        );
+
+    for _ in 0..2 {
+        builder = builder.instance(
+            EchoNetwork::EchoClient(EchoClient::from_config(EchoClientConfig {
+                connect_to_address: "127.0.0.1:8907".to_string(),
+                connect_timeout: Timeout::Millis(1000),
+                poll_timeout: 50,
+                max_connection_attempts: 10,
+                retry_interval_ms: 500,
+                max_send_size: 1024,
+                min_rnd_timeout: 1000,
+                max_rnd_timeout: 2000,
+                payload: PayloadPattern::Random,
+            })),
+            || EchoClientAction::Tick.into(),
+        );
+    }
+
+    let mut runner = builder.build();
+
+    // Until both clients are connected to the server.
+    let connected = runner.run_until(
+        |state| {
+            topology_snapshot(state)
+                .edges
+                .iter()
+                .filter(|edge| matches!(edge, TopologyEdge::Connected { .. }))
+                .count()
+                == 2
+        },
+        10_000,
+    );
+
+    assert!(connected);
+
+    let topology = topology_snapshot(runner.state());
+    let listeners: Vec<_> = topology
+        .nodes
+        .iter()
+        .filter(|node| matches!(node.kind, TopologyNodeKind::Listener { .. }))
+        .map(|node| (node.instance, node.uid))
+        .collect();
+    let connections = |incoming| {
+        topology
+            .nodes
+            .iter()
+            .filter(|node| {
+                matches!(node.kind, TopologyNodeKind::Connection { incoming: node_incoming, .. } if node_incoming == incoming)
+            })
+            .count()
+    };
+
+    assert_eq!(listeners.len(), 1);
+    assert_eq!(listeners[0].0, 0);
+    assert_eq!(connections(true), 2);
+    assert_eq!(connections(false), 2);
+
+    // Both server connections were accepted by the listener, and each one is
+    // connected to a different client.
+    let mut accepted = 0;
+    let mut clients = Vec::new();
+
+    for edge in topology.edges.iter() {
+        match edge {
+            TopologyEdge::Accepted {
+                listener,
+                connection,
+            } => {
+                assert_eq!(*listener, listeners[0]);
+                assert_eq!(connection.0, 0);
+                accepted += 1;
+            }
+            TopologyEdge::Connected { outgoing, incoming } => {
+                assert_eq!(incoming.0, 0);
+                clients.push(outgoing.0);
+            }
+        }
+    }
+
+    clients.sort();
+    assert_eq!(accepted, 2);
+    assert_eq!(clients, vec![1, 2]);
+
+    let dot = topology.to_dot();
+
+    assert!(dot.starts_with("digraph topology {"));
+    assert_eq!(dot.matches(" -> ").count(), 4);
+}
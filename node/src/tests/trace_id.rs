@@ -0,0 +1,73 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::{tcp::state::TcpState, tcp_client::state::TcpClientState},
+        tests::trace_client::{action::TraceClientAction, state::TraceClientState},
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{
+    any::Any,
+    io::{Read, Write},
+    net::TcpListener,
+    thread,
+    time::Duration,
+};
+
+#[derive(ModelState, Debug)]
+pub struct TraceId {
+    pub time: TimeState,
+    pub tcp: TcpState,
+    pub tcp_client: TcpClientState,
+    pub client: TraceClientState,
+}
+
+impl RegisterModel for TraceId {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<TraceClientState>()
+    }
+}
+
+#[test]
+fn trace_id_send_to_recv() {
+    let address = "127.0.0.1:8943";
+    let listener = TcpListener::bind(address).expect("bind failed");
+    let echo = thread::spawn(move || {
+        let (mut peer, _) = listener.accept().expect("accept failed");
+        let mut request = [0u8; 4];
+
+        peer.read_exact(&mut request).expect("read failed");
+        // Late enough for the client's read to be issued from a poll.
+        thread::sleep(Duration::from_millis(50));
+        peer.write_all(&request).expect("write failed");
+        peer
+    });
+    let mut runner = RunnerBuilder::<TraceId>::new()
+        .register::<TraceId>()
+        .instance(
+            TraceId {
+                time: TimeState::default(),
+                tcp: TcpState::new(),
+                tcp_client: TcpClientState::new(),
+                client: TraceClientState::new(address.to_string(), 42),
+            },
+            || TraceClientAction::Tick.into(),
+        )
+        .build();
+
+    assert!(runner.run_until(
+        |state| state.substate::<TraceClientState>().received.is_some(),
+        1000
+    ));
+
+    let client_state: &TraceClientState = runner.state().substate();
+
+    assert_eq!(client_state.received, Some((b"ping".to_vec(), Some(42))));
+    // The trace doesn't leak to unrelated actions.
+    assert!(!client_state.poll_traced);
+    drop(echo.join());
+}
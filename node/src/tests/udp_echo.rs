@@ -0,0 +1,89 @@
+use crate::{
+    automaton::{
+        runner::{RegisterModel, RunnerBuilder},
+        state::ModelState,
+    },
+    models::pure::{
+        net::udp::state::UdpState,
+        prng::state::{PRNGAlgorithm, PRNGConfig, PRNGState},
+        tests::udp_echo::{
+            action::UdpEchoAction,
+            state::{UdpEchoConfig, UdpEchoState},
+        },
+        time::state::TimeState,
+    },
+};
+use model_state_derive::ModelState;
+use std::{any::Any, fs};
+
+#[derive(ModelState, Debug)]
+pub struct UdpEcho {
+    pub prng: PRNGState,
+    pub time: TimeState,
+    pub udp: UdpState,
+    pub udp_echo: UdpEchoState,
+}
+
+impl UdpEcho {
+    pub fn from_config(config: UdpEchoConfig) -> Self {
+        Self {
+            prng: PRNGState::from_config(PRNGConfig {
+                seed: 1337,
+                algorithm: PRNGAlgorithm::SmallRng,
+            }),
+            time: TimeState::default(),
+            udp: UdpState::new(),
+            udp_echo: UdpEchoState::from_config(config),
+        }
+    }
+}
+
+impl RegisterModel for UdpEcho {
+    fn register<Substate: ModelState>(builder: RunnerBuilder<Substate>) -> RunnerBuilder<Substate> {
+        builder.register::<UdpEchoState>()
+    }
+}
+
+fn builder(server_port: u16) -> RunnerBuilder<UdpEcho> {
+    RunnerBuilder::<UdpEcho>::new()
+        .register::<UdpEcho>()
+        .instance(
+            UdpEcho::from_config(UdpEchoConfig {
+                server_address: format!("127.0.0.1:{}", server_port),
+                client_address: "127.0.0.1:0".to_string(),
+                poll_timeout: 100,
+                max_send_size: 1024,
+                recv_timeout: 500,
+                rounds: 10,
+            }),
+            || UdpEchoAction::Tick.into(),
+        )
+}
+
+#[test]
+fn udp_echo_round_trips() {
+    let mut runner = builder(8914).build();
+
+    runner.run();
+
+    let echo_state: &UdpEchoState = runner.state().substate();
+
+    assert_eq!(echo_state.echoed, 10);
+    assert_eq!(echo_state.lost, 0);
+}
+
+#[test]
+fn udp_echo_record_replay() {
+    let session = "udp_echo_record_replay";
+
+    builder(8915).build().record(session);
+
+    let mut runner = builder(8915).build();
+
+    runner.replay(session);
+    fs::remove_file(format!("{}_0.rec", session)).expect("recording not found");
+
+    let echo_state: &UdpEchoState = runner.state().substate();
+
+    assert_eq!(echo_state.echoed, 10);
+}